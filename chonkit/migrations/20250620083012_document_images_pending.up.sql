@@ -0,0 +1,3 @@
+-- Set when storing the images of a document fails so the processing can be retried
+
+ALTER TABLE documents ADD COLUMN images_pending BOOLEAN NOT NULL DEFAULT FALSE;
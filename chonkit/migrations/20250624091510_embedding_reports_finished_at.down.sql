@@ -0,0 +1,2 @@
+DROP INDEX embedding_reports_finished_at_idx;
+DROP INDEX embedding_removal_reports_finished_at_idx;
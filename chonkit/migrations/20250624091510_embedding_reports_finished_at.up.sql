@@ -0,0 +1,4 @@
+-- Used for ordering reports when listing and for removing reports outside of the retention window
+
+CREATE INDEX embedding_reports_finished_at_idx ON embedding_reports (finished_at);
+CREATE INDEX embedding_removal_reports_finished_at_idx ON embedding_removal_reports (finished_at);
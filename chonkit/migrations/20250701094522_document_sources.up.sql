@@ -0,0 +1,17 @@
+-- Additional sources of documents whose content was obtained from more than one location.
+-- The source a document was first obtained from is kept on the document itself.
+CREATE TABLE document_sources(
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+
+    -- Document source, e.g. fs, google_drive, etc.
+    src TEXT NOT NULL,
+
+    -- Absolute path to the file depending on type of file storage.
+    path TEXT NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (src, path)
+);
+
+CREATE INDEX ON document_sources(document_id);
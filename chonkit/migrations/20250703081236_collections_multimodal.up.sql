@@ -0,0 +1,4 @@
+-- Whether the embedding model of the collection supports images.
+-- NULL for collections whose model could not be checked, i.e. ones created before this
+-- column existed or ones obtained by syncing with vector databases.
+ALTER TABLE collections ADD COLUMN multimodal BOOLEAN;
@@ -0,0 +1,2 @@
+-- Custom document attributes copied to the payload of the document's vectors
+ALTER TABLE documents ADD COLUMN metadata JSONB;
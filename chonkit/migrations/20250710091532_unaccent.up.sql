@@ -0,0 +1,2 @@
+-- Enables accent insensitive searches on text columns
+CREATE EXTENSION IF NOT EXISTS unaccent;
@@ -0,0 +1,9 @@
+-- LLM generated document summaries, embedded into summary collections for two-stage retrieval.
+-- Keyed by the content hash so documents with the same content are only summarized once.
+CREATE TABLE document_summaries(
+    document_hash TEXT PRIMARY KEY,
+
+    summary TEXT NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,14 @@
+-- Stable names pointing to collections. Clients search by the alias and the
+-- collection behind it can be swapped, e.g. once a collection with a new model is embedded.
+CREATE TABLE collection_aliases(
+    alias TEXT PRIMARY KEY,
+
+    collection_id UUID NOT NULL REFERENCES collections ON DELETE CASCADE,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON collection_aliases(collection_id);
+
+SELECT manage_updated_at('collection_aliases');
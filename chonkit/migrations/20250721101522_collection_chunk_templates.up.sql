@@ -0,0 +1,3 @@
+-- Template wrapping every chunk with document context before it is embedded, e.g.
+-- 'Document: {name}\n\n{chunk}'. NULL embeds the chunks as they are.
+ALTER TABLE collections ADD COLUMN chunk_template TEXT;
@@ -0,0 +1 @@
+ALTER TABLE embedding_reports DROP COLUMN chunk_fallback;
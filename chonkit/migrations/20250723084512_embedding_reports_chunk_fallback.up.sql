@@ -0,0 +1,2 @@
+-- Set if the document was chunked with the fallback of its chunking configuration
+ALTER TABLE embedding_reports ADD COLUMN chunk_fallback BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,22 @@
+-- Every request made to an embedding provider, recorded only if auditing is enabled
+CREATE TABLE embedding_requests (
+    id SERIAL PRIMARY KEY,
+    -- Text or image
+    type TEXT NOT NULL,
+    embedding_provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    -- The amount of inputs embedded in the request
+    inputs INTEGER NOT NULL,
+    -- Estimated with the model's tokenizer, not reported by the provider
+    input_tokens INTEGER,
+    -- Reported by the provider, if it reports them
+    response_tokens INTEGER,
+    latency_ms INTEGER NOT NULL,
+    -- Set if the request failed
+    error TEXT,
+    requested_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX ON embedding_requests (embedding_provider);
+CREATE INDEX ON embedding_requests (model);
+CREATE INDEX ON embedding_requests (requested_at);
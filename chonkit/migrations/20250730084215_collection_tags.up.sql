@@ -0,0 +1,2 @@
+-- Labels for organizing collections, e.g. 'project:alpha' or 'env:prod'.
+ALTER TABLE collections ADD COLUMN tags TEXT[];
@@ -0,0 +1 @@
+ALTER TABLE collections DROP COLUMN insert_dedup_threshold;
@@ -0,0 +1,3 @@
+-- Chunks with a cosine similarity of at least this value with an earlier chunk of the same
+-- document are not inserted. NULL disables de-duplication.
+ALTER TABLE collections ADD COLUMN insert_dedup_threshold FLOAT8;
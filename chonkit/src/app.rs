@@ -9,6 +9,12 @@ pub mod document;
 /// Text embedder implementations.
 pub mod embedder;
 
+/// LLM client implementations.
+pub mod llm;
+
+/// Reranker client implementations.
+pub mod rerank;
+
 /// Application state configuration.
 pub mod state;
 
@@ -1,21 +1,65 @@
 use crate::{
     core::{
-        model::embedding::EmbeddingReportType,
-        service::{embedding::EmbedTextInput, ServiceState},
+        model::embedding::{EmbeddingReportType, TextEmbeddingSkipReport},
+        service::{collection::dto::CollectionData, embedding::EmbedTextInput, ServiceState},
     },
     error::ChonkitError,
 };
-use std::collections::HashMap;
-use tokio::{select, sync::mpsc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    select,
+    sync::{mpsc, watch},
+};
 use uuid::Uuid;
 
-/// Sending end for batch embedding jobs.
-pub type BatchEmbedderHandle = mpsc::Sender<BatchJob>;
+/// How many times documents failing with transient errors are retried if not specified.
+pub const DEFAULT_BATCH_RETRIES: u32 = 1;
+
+/// How long to wait before the first retry of failed documents, multiplied by the attempt on
+/// subsequent ones.
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
 
 pub fn start_batch_embedder(state: ServiceState) -> BatchEmbedderHandle {
     let (tx, rx) = mpsc::channel(128);
-    BatchEmbedder::new(rx, state).start();
-    tx
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    BatchEmbedder::new(rx, shutdown_rx, state).start();
+    BatchEmbedderHandle {
+        jobs: tx,
+        shutdown: Arc::new(shutdown_tx),
+    }
+}
+
+/// Sending end for batch embedding jobs.
+#[derive(Debug, Clone)]
+pub struct BatchEmbedderHandle {
+    jobs: mpsc::Sender<BatchJob>,
+
+    /// Set to `true` when the embedder is shutting down.
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl BatchEmbedderHandle {
+    /// Send a job to the embedder. Errors if the embedder is shut down.
+    pub async fn send(&self, job: BatchJob) -> Result<(), mpsc::error::SendError<BatchJob>> {
+        if *self.shutdown.borrow() {
+            return Err(mpsc::error::SendError(job));
+        }
+        self.jobs.send(job).await
+    }
+
+    /// Stop accepting new jobs. Jobs in progress finish embedding their current document
+    /// and skip the rest.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Wait for the embedder to finish its jobs in progress after a [shutdown][Self::shutdown].
+    /// Returns `false` if they did not finish within `timeout`.
+    pub async fn drained(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.jobs.closed())
+            .await
+            .is_ok()
+    }
 }
 
 pub struct BatchEmbedder {
@@ -33,11 +77,19 @@ pub struct BatchEmbedder {
     /// Here solely so we can just clone it for jobs.
     result_tx: mpsc::Sender<BatchJobResult>,
 
+    /// Shutdown signal. Once set, no new jobs are started and the executor exits
+    /// as soon as the jobs in progress are done.
+    shutdown_rx: watch::Receiver<bool>,
+
     state: ServiceState,
 }
 
 impl BatchEmbedder {
-    pub fn new(job_rx: mpsc::Receiver<BatchJob>, state: ServiceState) -> Self {
+    pub fn new(
+        job_rx: mpsc::Receiver<BatchJob>,
+        shutdown_rx: watch::Receiver<bool>,
+        state: ServiceState,
+    ) -> Self {
         let (result_tx, result_rx) = mpsc::channel(128);
         Self {
             q: HashMap::new(),
@@ -45,14 +97,29 @@ impl BatchEmbedder {
             state,
             result_tx,
             result_rx,
+            shutdown_rx,
         }
     }
 
     pub fn start(mut self) {
         tokio::spawn(async move {
+            let mut draining = false;
+
             loop {
+                if draining && self.q.is_empty() {
+                    tracing::info!("All jobs finished, shutting down executor");
+                    break;
+                }
+
                 select! {
-                    job = self.job_rx.recv() => {
+                    _ = self.shutdown_rx.changed(), if !draining => {
+                        tracing::info!("Shutdown requested, waiting for {} job(s) to finish", self.q.len());
+                        // Jobs still in the channel are dropped along with the executor,
+                        // closing their result channels
+                        draining = true;
+                    }
+
+                    job = self.job_rx.recv(), if !draining => {
                         let Some(job) = job else {
                             tracing::info!("Job receiver channel closed, shutting down executor");
                             break;
@@ -61,15 +128,16 @@ impl BatchEmbedder {
                         let job_id = Uuid::new_v4();
                         let state = self.state.clone();
                         let result_tx = self.result_tx.clone();
+                        let shutdown_rx = self.shutdown_rx.clone();
 
-                        let BatchJob { collection, add, remove, result_tx: job_result_tx } = job;
+                        let BatchJob { collection, add, remove, skip_existing, retries, result_tx: job_result_tx } = job;
 
                         self.q.insert(job_id, job_result_tx);
 
                         tracing::info!("Starting job '{job_id}' | Adding {} | Removing {}", add.len(), remove.len());
 
                         tokio::spawn(
-                            Self::execute_job(job_id, state, add, remove, collection, result_tx)
+                            Self::execute_job(job_id, state, add, remove, skip_existing, retries, collection, result_tx, shutdown_rx)
                         );
                     }
 
@@ -107,13 +175,17 @@ impl BatchEmbedder {
         });
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn execute_job(
         job_id: Uuid,
         services: ServiceState,
         add: Vec<Uuid>,
         remove: Vec<Uuid>,
+        skip_existing: bool,
+        retries: u32,
         collection_id: Uuid,
         result_tx: mpsc::Sender<BatchJobResult>,
+        shutdown_rx: watch::Receiver<bool>,
     ) {
         /// Matches the result and continues on error, sending the error to the result channel.
         macro_rules! ok_or_continue {
@@ -121,20 +193,13 @@ impl BatchEmbedder {
                 match $e {
                     Ok(v) => v,
                     Err(e) => {
-                        tracing::debug!("Sending error to channel ({:?})", e.error);
-                        e.print();
-                        let result = JobEvent {
-                            job_id,
-                            result: Err(e),
-                        };
-                        let _ = result_tx.send(BatchJobResult::Event(result)).await;
+                        Self::send_error(job_id, &result_tx, e).await;
                         continue;
                     }
                 }
             };
         }
 
-        let total = add.len();
         let collection = match services.collection.get_collection(collection_id).await {
             Ok(v) => v,
             Err(e) => {
@@ -149,28 +214,82 @@ impl BatchEmbedder {
             }
         };
 
-        for (i, document_id) in add.into_iter().enumerate() {
-            tracing::debug!("Processing document '{document_id}' ({}/{total})", i + 1);
+        // Checked before every document so documents are never left partially embedded
+        let shutdown = || *shutdown_rx.borrow();
 
-            let report = ok_or_continue!(
-                services
-                    .embedding
-                    .create_text_embeddings(EmbedTextInput::new(
-                        document_id,
-                        collection.collection.id
-                    ))
-                    .await
-            );
+        let mut pending = add;
+        let mut attempt = 0;
 
-            let result = JobEvent {
-                job_id,
-                result: Ok(EmbeddingReportType::TextAddition(report)),
-            };
+        loop {
+            let total = pending.len();
+            let mut failed = vec![];
 
-            result_tx.send(BatchJobResult::Event(result)).await.unwrap();
+            for (i, document_id) in pending.into_iter().enumerate() {
+                if shutdown() {
+                    break;
+                }
+
+                tracing::debug!("Processing document '{document_id}' ({}/{total})", i + 1);
+
+                let report =
+                    Self::embed_document(&services, &collection, document_id, skip_existing).await;
+
+                let report = match report {
+                    Ok(report) => report,
+                    // Transient failures are collected and retried once the pass is done
+                    Err(e) if attempt < retries && e.is_transient() => {
+                        tracing::warn!("Document '{document_id}' failed transiently ({e})");
+                        failed.push((document_id, e));
+                        continue;
+                    }
+                    Err(e) => {
+                        Self::send_error(job_id, &result_tx, e).await;
+                        continue;
+                    }
+                };
+
+                let result = JobEvent {
+                    job_id,
+                    result: Ok(report),
+                };
+
+                result_tx.send(BatchJobResult::Event(result)).await.unwrap();
+            }
+
+            if failed.is_empty() {
+                break;
+            }
+
+            attempt += 1;
+
+            if !shutdown() {
+                tokio::time::sleep(RETRY_BACKOFF * attempt).await;
+            }
+
+            // Documents not retried due to shutdown report their last error
+            if shutdown() {
+                for (_, e) in failed {
+                    Self::send_error(job_id, &result_tx, e).await;
+                }
+                break;
+            }
+
+            tracing::info!(
+                "Job '{job_id}' retrying {} document(s) ({attempt}/{retries})",
+                failed.len()
+            );
+
+            pending = failed
+                .into_iter()
+                .map(|(document_id, _)| document_id)
+                .collect();
         }
 
         for document_id in remove.into_iter() {
+            if shutdown() {
+                break;
+            }
+
             let report = ok_or_continue!(
                 services
                     .embedding
@@ -186,8 +305,57 @@ impl BatchEmbedder {
             result_tx.send(BatchJobResult::Event(result)).await.unwrap();
         }
 
+        if shutdown() {
+            tracing::warn!("Job '{job_id}' stopped early due to shutdown");
+        }
+
         let _ = result_tx.send(BatchJobResult::Done(job_id)).await;
     }
+
+    /// Embed the document into the collection, or report it as skipped if it is already
+    /// embedded and `skip_existing` is set.
+    async fn embed_document(
+        services: &ServiceState,
+        collection: &CollectionData,
+        document_id: Uuid,
+        skip_existing: bool,
+    ) -> Result<EmbeddingReportType, ChonkitError> {
+        let collection_id = collection.collection.id;
+
+        if skip_existing
+            && services
+                .embedding
+                .get_embeddings(document_id, collection_id)
+                .await?
+                .is_some()
+        {
+            tracing::debug!("Document '{document_id}' already embedded, skipping");
+
+            return Ok(EmbeddingReportType::TextSkipped(TextEmbeddingSkipReport {
+                document_id,
+                collection_id,
+                collection_name: collection.collection.name.clone(),
+            }));
+        }
+
+        let report = services
+            .embedding
+            .create_text_embeddings(EmbedTextInput::new(document_id, collection_id))
+            .await?;
+
+        Ok(EmbeddingReportType::TextAddition(report))
+    }
+
+    /// Send the error of a document to the result channel.
+    async fn send_error(job_id: Uuid, result_tx: &mpsc::Sender<BatchJobResult>, e: ChonkitError) {
+        tracing::debug!("Sending error to channel ({:?})", e.error);
+        e.print();
+        let result = JobEvent {
+            job_id,
+            result: Err(e),
+        };
+        let _ = result_tx.send(BatchJobResult::Event(result)).await;
+    }
 }
 
 /// Used for batch embedding jobs.
@@ -202,6 +370,14 @@ pub struct BatchJob {
     /// Documents to remove from the collection.
     remove: Vec<Uuid>,
 
+    /// If `true`, documents already embedded in the collection are reported as skipped
+    /// instead of failing.
+    skip_existing: bool,
+
+    /// How many times documents failing with transient errors are retried after the others
+    /// are processed. Other errors are reported immediately.
+    retries: u32,
+
     /// Sends finished document embeddings back to whatever sent the job.
     result_tx: mpsc::Sender<BatchJobResult>,
 }
@@ -217,9 +393,23 @@ impl BatchJob {
             collection,
             add,
             remove,
+            skip_existing: false,
+            retries: DEFAULT_BATCH_RETRIES,
             result_tx,
         }
     }
+
+    /// Skip documents already embedded in the collection instead of failing.
+    pub fn with_skip_existing(mut self, skip_existing: bool) -> Self {
+        self.skip_existing = skip_existing;
+        self
+    }
+
+    /// Retry documents failing with transient errors, e.g. rate limits, up to `retries` times.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
 }
 
 /// Used internally to track the status of an embedding job.
@@ -2,7 +2,7 @@ use crate::{
     config::FS_STORE_ID,
     core::{
         document::{
-            store::{DocumentFile, DocumentStorage, LocalPath},
+            store::{DocumentFile, DocumentReader, DocumentStorage, LocalPath},
             DocumentType,
         },
         provider::Identity,
@@ -45,6 +45,10 @@ impl DocumentStorage for FsDocumentStore {
         self.dir.read(path).await
     }
 
+    async fn read_stream(&self, path: &str) -> Result<Box<dyn DocumentReader>, ChonkitError> {
+        self.dir.read_stream(path).await
+    }
+
     async fn list_files(&self) -> Result<Vec<DocumentFile<LocalPath>>, ChonkitError> {
         self.dir.list_files().await
     }
@@ -90,6 +94,10 @@ impl TokioDirectory {
         Ok(map_err!(tokio::fs::read(&path).await))
     }
 
+    pub async fn read_stream(&self, path: &str) -> Result<Box<dyn DocumentReader>, ChonkitError> {
+        Ok(Box::new(map_err!(tokio::fs::File::open(&path).await)))
+    }
+
     /// Returns all files from the base directory this struct is instantiated with.
     /// The parameters are as follows:
     /// - `name`: The name of the file.
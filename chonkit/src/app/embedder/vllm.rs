@@ -9,7 +9,7 @@ use crate::{
 };
 use chonkit_embedders::EmbeddingModel;
 
-pub use chonkit_embedders::vllm::VllmEmbeddings;
+pub use chonkit_embedders::vllm::{ModelPooling, VllmEmbeddings};
 
 impl Identity for VllmEmbeddings {
     fn id(&self) -> &'static str {
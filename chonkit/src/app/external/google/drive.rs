@@ -3,7 +3,10 @@ use crate::{
     app::external::google::{
         DriveFile, GoogleApiError, GoogleError, ListFilesResponse, Operation, OperationResult,
     },
-    config::GOOGLE_STORE_ID,
+    config::{
+        DEFAULT_GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS, DEFAULT_GOOGLE_DRIVE_DOWNLOAD_BACKOFF,
+        GOOGLE_STORE_ID,
+    },
     core::{
         document::store::{external::ExternalDocumentStorage, DocumentFile, ExternalPath},
         document::DocumentType,
@@ -13,6 +16,9 @@ use crate::{
     error::{ChonkitErr, ChonkitError},
     map_err,
 };
+use reqwest::{header, StatusCode};
+use std::{path::PathBuf, time::Duration};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 
 const FILES_EP: &str = "https://www.googleapis.com/drive/v3/files";
 const OPERATIONS_EP: &str = "https://www.googleapis.com/drive/v3/operations";
@@ -28,6 +34,43 @@ pub struct GoogleDriveApi {
     /// Must already be in the form of `Bearer <JWT>`.
     token: String,
     client: reqwest::Client,
+    download: DriveDownloadConfig,
+}
+
+/// Controls how files are downloaded from Drive.
+///
+/// File contents are streamed to a partial file in `partial_dir`. If the connection
+/// drops mid-transfer, the download is resumed from the last received byte using
+/// a `Range` request instead of starting over.
+#[derive(Debug, Clone)]
+pub struct DriveDownloadConfig {
+    /// The maximum amount of attempts when polling download operations,
+    /// as well as the maximum amount of times a single download is resumed.
+    pub max_attempts: u32,
+
+    /// The delay between attempts, multiplied by the current attempt.
+    pub backoff: Duration,
+
+    /// Directory holding files whose download is in progress.
+    pub partial_dir: PathBuf,
+}
+
+impl Default for DriveDownloadConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS,
+            backoff: Duration::from_millis(DEFAULT_GOOGLE_DRIVE_DOWNLOAD_BACKOFF),
+            partial_dir: std::env::temp_dir().join("chonkit-gdrive"),
+        }
+    }
+}
+
+/// Outcome of a single transfer attempt in [GoogleDriveApi::download_resumable].
+enum Transfer {
+    Complete,
+
+    /// The connection dropped before the file was fully received. Holds the reason.
+    Interrupted(String),
 }
 
 impl GoogleDriveApi {
@@ -35,9 +78,15 @@ impl GoogleDriveApi {
         Self {
             token: token.0,
             client,
+            download: DriveDownloadConfig::default(),
         }
     }
 
+    pub fn with_download_config(mut self, config: DriveDownloadConfig) -> Self {
+        self.download = config;
+        self
+    }
+
     async fn list_all_drive_files(&self) -> Result<Vec<DriveFile>, ChonkitError> {
         let mut files = vec![];
         let mut next_page_token: Option<String> = None;
@@ -143,22 +192,12 @@ impl GoogleDriveApi {
         // for some reason. These can usually be downloaded with `?alt=media`.
         if let Some(ext) = file.file_extension {
             if !is_google_binary(DocumentType::try_from(ext)?) {
-                let response = map_err!(
-                    self.client
-                        .get(format!("{FILES_EP}/{drive_file_id}"))
-                        .query(&[("alt", "media")])
-                        .header("Authorization", &self.token)
-                        .send()
-                        .await
-                );
-
-                if !response.status().is_success() {
-                    let response = map_err!(response.json().await);
-                    return err!(ChonkitErr::GoogleApi(GoogleError::Api(response)));
-                }
-
-                let content = map_err!(response.bytes().await);
-                return Ok(content.to_vec());
+                return self
+                    .download_resumable(
+                        drive_file_id,
+                        &format!("{FILES_EP}/{drive_file_id}?alt=media"),
+                    )
+                    .await;
             }
         }
 
@@ -198,16 +237,8 @@ impl GoogleDriveApi {
                     err!(ChonkitErr::GoogleApi(GoogleError::Operation(e)))
                 }
                 OperationResult::Response(res) => {
-                    let response = map_err!(
-                        self.client
-                            .get(&res.download_uri)
-                            .header("Authorization", &self.token)
-                            .send()
-                            .await
-                    );
-
-                    let content = map_err!(response.bytes().await);
-                    Ok(content.to_vec())
+                    self.download_resumable(drive_file_id, &res.download_uri)
+                        .await
                 }
             }
         } else {
@@ -215,26 +246,27 @@ impl GoogleDriveApi {
                 "Operation '{}' incomplete, commencing download loop",
                 operation.name
             );
-            self.operation_download_loop(operation).await
+            self.operation_download_loop(drive_file_id, operation).await
         }
     }
 
     async fn operation_download_loop(
         &self,
+        drive_file_id: &str,
         mut operation: Operation,
     ) -> Result<Vec<u8>, ChonkitError> {
-        const MAX_ATTEMPTS: u64 = 10;
-        let mut attempts: u64 = 0;
+        let max_attempts = self.download.max_attempts;
+        let mut attempts = 0;
         tracing::info!("Starting download loop for operation {}", operation.name);
 
-        while !operation.done && attempts < MAX_ATTEMPTS {
+        while !operation.done && attempts < max_attempts {
             attempts += 1;
 
             // If the operation is not done, wait a bit and try again
-            tokio::time::sleep(std::time::Duration::from_millis(200 * attempts)).await;
+            tokio::time::sleep(self.download.backoff * attempts).await;
 
             tracing::info!(
-                "Polling operation {} for result (attempt {attempts}/{MAX_ATTEMPTS})",
+                "Polling operation {} for result (attempt {attempts}/{max_attempts})",
                 operation.name
             );
 
@@ -266,19 +298,138 @@ impl GoogleDriveApi {
                 err!(ChonkitErr::GoogleApi(GoogleError::Operation(e)))
             }
             OperationResult::Response(res) => {
-                let response = map_err!(
-                    self.client
-                        .get(&res.download_uri)
-                        .header("Authorization", &self.token)
-                        .send()
-                        .await
-                );
+                self.download_resumable(drive_file_id, &res.download_uri)
+                    .await
+            }
+        }
+    }
+
+    /// Stream the contents at `uri` to a partial file and return them once complete.
+    /// Interrupted transfers are resumed from the last received byte, up to the configured
+    /// amount of attempts.
+    async fn download_resumable(
+        &self,
+        drive_file_id: &str,
+        uri: &str,
+    ) -> Result<Vec<u8>, ChonkitError> {
+        map_err!(tokio::fs::create_dir_all(&self.download.partial_dir).await);
+
+        // Suffixed so concurrent downloads of the same file do not share a partial file
+        let path = self
+            .download
+            .partial_dir
+            .join(format!("{drive_file_id}-{}.part", uuid::Uuid::new_v4()));
+        let mut file = map_err!(tokio::fs::File::create(&path).await);
+        let mut received = 0;
+        let mut attempts = 0;
+
+        let result = loop {
+            let reason = match self.transfer(uri, &mut file, &mut received).await {
+                Ok(Transfer::Complete) => break Ok(()),
+                Ok(Transfer::Interrupted(reason)) => reason,
+                Err(e) => break Err(e),
+            };
+
+            attempts += 1;
+
+            if attempts >= self.download.max_attempts {
+                break err!(ChonkitErr::GoogleApi(GoogleError::App(format!(
+                    "Download of '{drive_file_id}' failed after {attempts} attempts: {reason}"
+                ))));
+            }
+
+            tracing::warn!(
+                "Download of '{drive_file_id}' interrupted at byte {received} ({reason}), resuming (attempt {attempts}/{})",
+                self.download.max_attempts
+            );
+
+            tokio::time::sleep(self.download.backoff * attempts).await;
+        };
+
+        drop(file);
+
+        // Errors are returned once the partial file is removed
+        let content = match result {
+            Ok(()) => Ok(tokio::fs::read(&path).await),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            tracing::warn!(
+                "Unable to remove partial download '{}': {e}",
+                path.display()
+            );
+        }
+
+        let content = map_err!(content?);
+
+        Ok(content)
+    }
+
+    /// Request the contents at `uri` starting from `received` and append them to `file`.
+    /// Errors only on failures that cannot be recovered by resuming.
+    async fn transfer(
+        &self,
+        uri: &str,
+        file: &mut tokio::fs::File,
+        received: &mut u64,
+    ) -> Result<Transfer, ChonkitError> {
+        let mut request = self.client.get(uri).header("Authorization", &self.token);
+
+        if *received > 0 {
+            request = request.header(header::RANGE, format!("bytes={received}-"));
+        }
 
-                let content = map_err!(response.bytes().await);
+        let mut response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return Ok(Transfer::Interrupted(e.to_string())),
+        };
 
-                Ok(content.to_vec())
+        match response.status() {
+            // Everything up to the end of the file was already received
+            StatusCode::RANGE_NOT_SATISFIABLE if *received > 0 => return Ok(Transfer::Complete),
+            StatusCode::PARTIAL_CONTENT => {}
+            status if status.is_success() => {
+                // The server ignored the range, start over
+                if *received > 0 {
+                    tracing::debug!("Range not honoured by server, restarting download");
+                    map_err!(file.set_len(0).await);
+                    map_err!(file.rewind().await);
+                    *received = 0;
+                }
+            }
+            status if status.is_server_error() => {
+                return Ok(Transfer::Interrupted(format!("status {status}")));
+            }
+            _ => {
+                let response = map_err!(response.text().await);
+                return err!(ChonkitErr::GoogleApi(GoogleError::App(response)));
             }
         }
+
+        let expected = response.content_length();
+        let mut chunk_received = 0;
+
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => return Ok(Transfer::Interrupted(e.to_string())),
+            };
+
+            map_err!(file.write_all(&chunk).await);
+            chunk_received += chunk.len() as u64;
+            *received += chunk.len() as u64;
+        }
+
+        map_err!(file.flush().await);
+
+        match expected {
+            Some(expected) if chunk_received < expected => Ok(Transfer::Interrupted(format!(
+                "received {chunk_received} out of {expected} bytes"
+            ))),
+            _ => Ok(Transfer::Complete),
+        }
     }
 }
 
@@ -356,7 +507,117 @@ impl ExternalDocumentStorage for GoogleDriveApi {
 
 fn is_google_binary(ext: DocumentType) -> bool {
     match ext {
-        DocumentType::Text(_) | DocumentType::Excel => false,
+        DocumentType::Text(_)
+        | DocumentType::Markdown
+        | DocumentType::Excel
+        | DocumentType::Rtf
+        | DocumentType::Odt => false,
         DocumentType::Docx | DocumentType::Pdf => true,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DriveDownloadConfig, GoogleDriveApi};
+    use crate::app::external::google::auth::GoogleAccessToken;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::{TcpListener, TcpStream},
+        path::PathBuf,
+        time::Duration,
+    };
+
+    const DIR: &str = "__gdrive_partial_download_tests";
+    const CONTENT: &[u8] =
+        b"Raguru crafts dishes from his floating kitchen, one byte range at a time.";
+    const CUTOFF: usize = 20;
+
+    /// Read the request head and return its `Range` header, if any.
+    fn read_range(stream: &TcpStream) -> Option<String> {
+        let mut range = None;
+
+        for line in BufReader::new(stream).lines() {
+            let line = line.unwrap();
+
+            if line.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("range") {
+                    range = Some(value.trim().to_string());
+                }
+            }
+        }
+
+        range
+    }
+
+    /// Accepts two connections. The first response drops the connection after [CUTOFF] bytes,
+    /// the second serves the requested range. Returns the `Range` headers of both requests.
+    fn serve_interrupted(listener: TcpListener) -> Vec<Option<String>> {
+        let mut ranges = vec![];
+
+        for stream in listener.incoming().take(2) {
+            let mut stream = stream.unwrap();
+            let range = read_range(&stream);
+
+            match range.as_deref().and_then(|r| r.strip_prefix("bytes=")) {
+                None => {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        CONTENT.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(&CONTENT[..CUTOFF]).unwrap();
+                }
+                Some(range) => {
+                    let start: usize = range.trim_end_matches('-').parse().unwrap();
+                    let head = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{}/{}\r\nConnection: close\r\n\r\n",
+                        CONTENT.len() - start,
+                        CONTENT.len() - 1,
+                        CONTENT.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(&CONTENT[start..]).unwrap();
+                }
+            }
+
+            stream.flush().unwrap();
+            ranges.push(range);
+        }
+
+        ranges
+    }
+
+    #[tokio::test]
+    async fn resumes_interrupted_download() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || serve_interrupted(listener));
+
+        let api = GoogleDriveApi::new(
+            reqwest::Client::new(),
+            GoogleAccessToken::new("Bearer ragu".to_string()),
+        )
+        .with_download_config(DriveDownloadConfig {
+            max_attempts: 3,
+            backoff: Duration::from_millis(10),
+            partial_dir: PathBuf::from(DIR),
+        });
+
+        let content = api
+            .download_resumable("ragu", &format!("http://{addr}/ragu"))
+            .await
+            .unwrap();
+
+        assert_eq!(CONTENT, content);
+
+        let ranges = server.join().unwrap();
+        assert_eq!(vec![None, Some(format!("bytes={CUTOFF}-"))], ranges);
+
+        // Fails if the partial file was left behind
+        tokio::fs::remove_dir(DIR).await.unwrap();
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     config::GOOGLE_STORE_ID,
     core::{
         document::{
-            store::{DocumentFile, DocumentStorage, LocalPath},
+            store::{DocumentFile, DocumentReader, DocumentStorage, LocalPath},
             DocumentType,
         },
         provider::Identity,
@@ -61,6 +61,10 @@ impl DocumentStorage for GoogleDriveStore {
         self.dir.read(path).await
     }
 
+    async fn read_stream(&self, path: &str) -> Result<Box<dyn DocumentReader>, ChonkitError> {
+        self.dir.read_stream(path).await
+    }
+
     async fn list_files(&self) -> Result<Vec<DocumentFile<LocalPath>>, ChonkitError> {
         self.dir.list_files().await
     }
@@ -0,0 +1,234 @@
+use crate::{
+    core::llm::Llm,
+    err,
+    error::{ChonkitErr, ChonkitError},
+    map_err,
+};
+use futures_util::{stream::BoxStream, StreamExt};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Client for OpenAI compatible chat completion APIs.
+pub struct OpenAiChat {
+    /// Base URL of the API, e.g. `https://api.openai.com/v1`.
+    endpoint: String,
+
+    /// The model used for completions.
+    model: String,
+
+    client: reqwest::Client,
+}
+
+impl OpenAiChat {
+    pub fn new(endpoint: String, model: String, key: Option<String>) -> Self {
+        let mut client = reqwest::ClientBuilder::new();
+        let mut default_headers = HeaderMap::new();
+
+        if let Some(key) = &key {
+            default_headers.append("Authorization", format!("Bearer {key}").parse().unwrap());
+            client = client.default_headers(default_headers);
+        }
+
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            model,
+            client: client.build().expect("unable to build http client"),
+        }
+    }
+
+    /// Send the chat completion request and return the response if it is successful.
+    async fn send(&self, request: &ChatRequest<'_>) -> Result<reqwest::Response, ChonkitError> {
+        let response = map_err!(
+            self.client
+                .post(format!("{}/chat/completions", self.endpoint))
+                .json(request)
+                .send()
+                .await
+        );
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return err!(Llm, "chat completion failed with status {status}: {body}");
+        }
+
+        Ok(response)
+    }
+
+    fn request<'a>(
+        &'a self,
+        instructions: &'a str,
+        prompt: &'a str,
+        stream: bool,
+    ) -> ChatRequest<'a> {
+        ChatRequest {
+            model: &self.model,
+            messages: [
+                ChatMessage {
+                    role: "system",
+                    content: instructions,
+                },
+                ChatMessage {
+                    role: "user",
+                    content: prompt,
+                },
+            ],
+            temperature: 0.,
+            stream,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Llm for OpenAiChat {
+    async fn complete(&self, instructions: &str, prompt: &str) -> Result<String, ChonkitError> {
+        let response = self
+            .send(&self.request(instructions, prompt, false))
+            .await?;
+
+        let response = map_err!(response.json::<ChatResponse>().await);
+
+        let Some(choice) = response.choices.into_iter().next() else {
+            return err!(Llm, "chat completion contains no choices");
+        };
+
+        tracing::debug!("Completed prompt with '{}'", self.model);
+
+        Ok(choice.message.content.trim().to_string())
+    }
+
+    async fn complete_stream(
+        &self,
+        instructions: &str,
+        prompt: &str,
+    ) -> Result<BoxStream<'static, Result<String, ChonkitError>>, ChonkitError> {
+        let response = self.send(&self.request(instructions, prompt, true)).await?;
+
+        tracing::debug!("Streaming completion with '{}'", self.model);
+
+        // Events can be split across chunks of the body, incomplete lines are buffered
+        let deltas = response
+            .bytes_stream()
+            .scan(Vec::new(), |buffer, bytes| {
+                let deltas = match bytes {
+                    Ok(bytes) => {
+                        buffer.extend_from_slice(&bytes);
+                        take_deltas(buffer)
+                    }
+                    Err(e) => vec![err!(ChonkitErr::Reqwest(e))],
+                };
+                futures_util::future::ready(Some(futures_util::stream::iter(deltas)))
+            })
+            .flatten();
+
+        Ok(deltas.boxed())
+    }
+}
+
+/// Drain the complete lines of server sent events from the `buffer` and return the content
+/// deltas they hold. The `[DONE]` event carries no content and is ignored.
+fn take_deltas(buffer: &mut Vec<u8>) -> Vec<Result<String, ChonkitError>> {
+    let mut deltas = vec![];
+
+    while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+        let line: Vec<u8> = buffer.drain(..=end).collect();
+        let line = String::from_utf8_lossy(&line);
+
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            continue;
+        };
+
+        let data = data.trim();
+        if data == "[DONE]" {
+            continue;
+        }
+
+        match serde_json::from_str::<ChatChunk>(data) {
+            Ok(chunk) => deltas.extend(
+                chunk
+                    .choices
+                    .into_iter()
+                    .filter_map(|choice| choice.delta.content)
+                    .filter(|content| !content.is_empty())
+                    .map(Ok),
+            ),
+            Err(e) => deltas.push(err!(ChonkitErr::SerdeJson(e))),
+        }
+    }
+
+    deltas
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 2],
+    temperature: f32,
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunk {
+    choices: Vec<ChatChunkChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunkChoice {
+    delta: ChatChunkDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChunkDelta {
+    content: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::take_deltas;
+
+    #[test]
+    fn takes_deltas_from_complete_lines() {
+        let mut buffer = b"data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+data: {\"choices\":[{\"delta\":{\"content\":\"Raguru \"}}]}\n\n\
+data: {\"choices\":[{\"delta\":{\"content\":\"cooks"
+            .to_vec();
+
+        let deltas = take_deltas(&mut buffer)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(vec!["Raguru "], deltas);
+
+        buffer.extend_from_slice(b"\"}}]}\n\ndata: [DONE]\n\n");
+
+        let deltas = take_deltas(&mut buffer)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(vec!["cooks"], deltas);
+        assert!(buffer.is_empty());
+    }
+}
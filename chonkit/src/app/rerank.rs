@@ -0,0 +1,101 @@
+use crate::{core::rerank::Reranker, err, error::ChonkitError, map_err};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Client for rerank APIs compatible with the Cohere `rerank` endpoint, e.g. Cohere, Jina,
+/// vLLM or Infinity.
+pub struct HttpReranker {
+    /// Base URL of the API, e.g. `https://api.cohere.com/v2`.
+    endpoint: String,
+
+    client: reqwest::Client,
+}
+
+impl HttpReranker {
+    pub fn new(endpoint: String, key: Option<String>) -> Self {
+        let mut client = reqwest::ClientBuilder::new();
+        let mut default_headers = HeaderMap::new();
+
+        if let Some(key) = &key {
+            default_headers.append("Authorization", format!("Bearer {key}").parse().unwrap());
+            client = client.default_headers(default_headers);
+        }
+
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client: client.build().expect("unable to build http client"),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Reranker for HttpReranker {
+    async fn rerank(
+        &self,
+        model: &str,
+        query: &str,
+        documents: &[&str],
+    ) -> Result<Vec<f64>, ChonkitError> {
+        let request = RerankRequest {
+            model,
+            query,
+            documents,
+            top_n: documents.len(),
+        };
+
+        let response = map_err!(
+            self.client
+                .post(format!("{}/rerank", self.endpoint))
+                .json(&request)
+                .send()
+                .await
+        );
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return err!(Rerank, "rerank failed with status {status}: {body}");
+        }
+
+        let response = map_err!(response.json::<RerankResponse>().await);
+
+        // Results are ordered by relevance, the scores are returned in the order of the documents
+        let mut scores = vec![None; documents.len()];
+        for result in response.results {
+            let Some(score) = scores.get_mut(result.index) else {
+                return err!(Rerank, "rerank result index {} out of range", result.index);
+            };
+            *score = Some(result.relevance_score);
+        }
+
+        tracing::debug!("Reranked {} documents with '{model}'", documents.len());
+
+        scores
+            .into_iter()
+            .enumerate()
+            .map(|(i, score)| match score {
+                Some(score) => Ok(score),
+                None => err!(Rerank, "rerank response contains no score for document {i}"),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RerankRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: &'a [&'a str],
+    top_n: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankResponse {
+    results: Vec<RerankResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankResult {
+    index: usize,
+    relevance_score: f64,
+}
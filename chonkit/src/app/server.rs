@@ -17,6 +17,21 @@ pub struct HttpConfiguration {
     pub cors_origins: Arc<[String]>,
     pub cors_headers: Arc<[String]>,
     pub cookie_domain: Arc<str>,
+    pub body_limits: BodyLimits,
+}
+
+/// Maximum sizes in bytes of request bodies, per group of endpoints. Larger requests are
+/// rejected with `413 Payload Too Large`.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimits {
+    /// Endpoints uploading documents.
+    pub upload: usize,
+
+    /// Searches and questions, whose bodies are small unless abused.
+    pub search: usize,
+
+    /// All other endpoints.
+    pub default: usize,
 }
 
 #[cfg(test)]
@@ -26,6 +41,11 @@ impl Default for HttpConfiguration {
             cors_origins: Arc::new([String::from("*")]),
             cors_headers: Arc::new([String::from("*")]),
             cookie_domain: "localhost".into(),
+            body_limits: BodyLimits {
+                upload: crate::config::DEFAULT_UPLOAD_BODY_LIMIT,
+                search: crate::config::DEFAULT_SEARCH_BODY_LIMIT,
+                default: crate::config::DEFAULT_BODY_LIMIT,
+            },
         }
     }
 }
@@ -1,21 +1,22 @@
-use super::dto::{EmbedBatchInput, ListDocumentsPayload, ListEmbeddingsPayload, UploadResult};
+use super::dto::{ArchiveUploadEvent, ArchiveUploadSummary, EmbedBatchInput, ListCollectionsPayload, ListDocumentsPayload, ListEmbeddingsPayload, UpdateContentResult, UploadResult};
 use crate::{
     app::{server::{dto::{UpdateDocumentMetadata, UpdateImageDescription}, router::collection::SyncParams}, state::AppConfig},
     core::{
-        chunk::{ChunkConfig, SemanticWindowConfig, SlidingWindowConfig, SnappingWindowConfig, SplitlineConfig},
-        document::parser::{PageRange, ParseConfig, SectionParseConfig, StringParseConfig},
+        chunk::{AutoTuneConfig, ByHeadingConfig, ChunkConfig, FixedCountConfig, ParentChildConfig, SemanticWindowConfig, SlidingWindowConfig, SnappingWindowConfig, SplitlineConfig},
+        document::{DocumentTypeCapabilities, parser::{redact::{PiiPattern, RedactionConfig, RedactionPattern}, LinkHandling, PageRange, ParseConfig, SectionParseConfig, StringParseConfig}},
+        llm::QueryRewrite,
         model::{
-            collection::{Collection, CollectionDisplay, CollectionDisplayAggregate, CollectionSearchColumn, CollectionShort}, document::{
-                Document, DocumentConfig, DocumentDisplay, DocumentSearchColumn, DocumentShort,
+            collection::{Collection, CollectionAlias, CollectionDisplay, CollectionDisplayAggregate, CollectionSearchColumn, CollectionShort}, document::{
+                Document, DocumentConfig, DocumentDisplay, DocumentSearchColumn, DocumentShort, DocumentSource,
             }, embedding::{
-                EmbeddingAdditionReport, EmbeddingReport, EmbeddingReportBase, EmbeddingReportSearchColumn, EmbeddingReportType, ImageEmbeddingAdditionReport, ImageEmbeddingRemovalReport, TextEmbedding, TextEmbeddingAdditionReport, TextEmbeddingRemovalReport
-            }, image::ImageModel, List, Pagination, PaginationSort, Search, SortDirection
+                ChunkTokenBreakdown, DocumentEmbeddingRemovalReport, EmbeddingAdditionReport, EmbeddingReport, EmbeddingReportAction, EmbeddingReportBase, EmbeddingReportKind, EmbeddingReportSearchColumn, EmbeddingReportType, ImageEmbeddingAdditionReport, ImageEmbeddingRemovalReport, TextEmbedding, TextEmbeddingAdditionReport, TextEmbeddingRemovalReport, TextEmbeddingSkipReport
+            }, image::{ImageCollection, ImageDisplay, ImageModel}, List, Pagination, PaginationSort, Search, SortDirection
         },
         service::{
-            collection::dto::{CollectionData, CollectionSearchResult, CreateCollectionPayload, SearchPayload, SyncIncompatibilityResolution}, document::dto::{ChunkForPreview, ChunkPreview, ChunkPreviewPayload, ListImagesParameters, ParseOutputPreview, ParsePreview, ParsedDocumentPage, ParsedDocumentSection}, embedding::{EmbedTextInput, ListEmbeddingReportsParams}
+            collection::dto::{CollectionData, CollectionImportResult, CollectionManifest, CollectionMetric, AskEvent, AskPayload, Citation, CollectionSearchResult, CreateCollectionPayload, MultiSearchItem, MultiSearchPayload, MultiSearchResult, SearchDiagnostics, SearchEvent, SearchPayload, SetAliasPayload, SyncIncompatibilityResolution}, document::dto::{BulkConfigurePayload, DocumentConfigureResult, BulkDeletePayload, ChunkForPreview, DocumentDeleteResult, DocumentHashMismatch, HashVerificationEvent, HashVerificationSummary, VerifyHashesPayload, ChunkDiff, ChunkDiffPayload, ChunkPreview, ChunkPreviewPayload, InlinePreviewPayload, ListImagesParameters, ParseOutputPreview, ParsePreview, ParsedDocument, ParsedDocumentPage, ParsedDocumentSection}, embedding::{CleanupReportsResult, EmbedFanoutInput, ReconstructedDocument, EmbedSummaryInput, EmbedTextInput, ListEmbeddingReportsParams}
         },
         token::TokenCount,
-        vector::{CollectionItemPayload, CollectionSearchItem, VectorCollection},
+        vector::{CollectionItemPayload, CollectionSearchItem, ExportedVector, FilterCondition, FilterExpr, FilterOp, FilterValue, VectorCollection},
     },
 };
 use utoipa::OpenApi;
@@ -25,20 +26,31 @@ use utoipa::OpenApi;
     paths(
         // App config
         super::router::app_config,
+        super::router::document::list_document_types,
 
         // Documents
         super::router::document::list_documents,
         super::router::document::list_documents_display,
         super::router::document::get_document,
+        super::router::document::list_document_sources,
         super::router::document::delete_document,
+        super::router::document::delete_documents_bulk,
+        super::router::document::configure_documents_bulk,
+        super::router::document::verify_document_hashes,
         super::router::document::upload_documents,
+        super::router::document::upload_archive,
         super::router::document::chunk_preview,
+        super::router::document::chunk_diff,
         super::router::document::parse_preview,
+        super::router::document::get_parsed_document,
+        super::router::document::inline_preview,
         super::router::document::update_document_config,
         super::router::document::sync,
         super::router::document::update_document_metadata,
+        super::router::document::update_document_content,
 
         // Images
+        super::router::document::list_document_images,
         super::router::document::list_images,
         super::router::document::delete_image,
         super::router::document::upload_images,
@@ -53,18 +65,35 @@ use utoipa::OpenApi;
         super::router::collection::list_collections_display,
         super::router::collection::collection_display,
         super::router::collection::search,
+        super::router::collection::search_stream,
+        super::router::collection::search_multi,
+        super::router::collection::ask,
         super::router::collection::sync,
         super::router::collection::update_collection_groups,
+        super::router::collection::update_collection_tags,
+        super::router::collection::export_collection,
+        super::router::collection::import_collection,
+        super::router::collection::get_collection_manifest,
+        super::router::collection::create_collection_from_manifest,
+        super::router::collection::set_alias,
+        super::router::collection::list_aliases,
+        super::router::collection::delete_alias,
 
         // Embeddings
         super::router::embedding::list_embedding_models,
         super::router::embedding::list_embedded_documents,
         super::router::embedding::list_embedding_reports,
+        super::router::embedding::get_embedding_report,
+        super::router::embedding::cleanup_embedding_reports,
         super::router::embedding::embed_text,
         super::router::embedding::batch_embed_text,
+        super::router::embedding::fanout_embed_text,
+        super::router::embedding::embed_summary,
         super::router::embedding::embed_image,
         super::router::embedding::delete_embeddings,
+        super::router::embedding::delete_document_embeddings,
         super::router::embedding::count_embeddings,
+        super::router::embedding::reconstruct_document,
     ),
     components(schemas(
         List<Collection>,
@@ -88,8 +117,12 @@ use utoipa::OpenApi;
 
         Document,
         DocumentConfig,
+        DocumentSource,
         UploadResult,
+        ArchiveUploadEvent,
+        ArchiveUploadSummary,
         UpdateDocumentMetadata,
+        UpdateContentResult,
 
         // Chunk config
         ChunkConfig,
@@ -98,45 +131,97 @@ use utoipa::OpenApi;
         SemanticWindowConfig,
         SemanticWindowConfig,
         SplitlineConfig,
+        FixedCountConfig,
+        ParentChildConfig,
+        ByHeadingConfig,
+        AutoTuneConfig,
 
         ChunkPreviewPayload,
+        ChunkDiffPayload,
+        InlinePreviewPayload,
+        BulkDeletePayload,
+        BulkConfigurePayload,
+        DocumentDeleteResult,
+        DocumentConfigureResult,
+        VerifyHashesPayload,
+        DocumentHashMismatch,
+        HashVerificationSummary,
+        HashVerificationEvent,
         ParseConfig,
         SectionParseConfig,
         StringParseConfig,
+        LinkHandling,
+        RedactionConfig,
+        RedactionPattern,
+        PiiPattern,
         ParseConfig,
         ParsePreview,
+        ParsedDocument,
         ParseOutputPreview,
         PageRange,
         ParsedDocumentSection,
         ParsedDocumentPage,
 
         ImageModel,
+        ImageDisplay,
+        ImageCollection,
         ListImagesParameters,
         UpdateImageDescription,
 
         CreateCollectionPayload,
         CollectionSearchResult,
+        SearchDiagnostics,
+        SearchEvent,
+        AskPayload,
+        AskEvent,
+        Citation,
         CollectionSearchItem,
         CollectionItemPayload,
         CollectionData,
+        CollectionImportResult,
+        CollectionManifest,
+        CollectionMetric,
+        ExportedVector,
         SyncIncompatibilityResolution,
         SyncParams,
         SearchPayload,
+        MultiSearchPayload,
+        MultiSearchResult,
+        MultiSearchItem,
+        FilterExpr,
+        FilterCondition,
+        FilterOp,
+        FilterValue,
+        SetAliasPayload,
+        CollectionAlias,
+        QueryRewrite,
         TextEmbedding,
         Collection,
         VectorCollection,
         AppConfig,
+        DocumentTypeCapabilities,
         EmbedBatchInput,
         EmbedTextInput,
+        EmbedFanoutInput,
+        EmbedSummaryInput,
         ListEmbeddingsPayload,
         ListDocumentsPayload,
+        ListCollectionsPayload,
         ChunkForPreview,
         ChunkPreview,
+        ChunkDiff,
         EmbeddingReport,
         EmbeddingReportType,
+        EmbeddingReportAction,
+        EmbeddingReportKind,
+        CleanupReportsResult,
+        ReconstructedDocument,
         TextEmbeddingAdditionReport,
+        ChunkTokenBreakdown,
         ImageEmbeddingAdditionReport,
         TextEmbeddingRemovalReport,
+        DocumentEmbeddingRemovalReport,
+        TextEmbeddingSkipReport,
         ImageEmbeddingRemovalReport,
         EmbeddingAdditionReport,
         EmbeddingReportBase,
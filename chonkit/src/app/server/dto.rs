@@ -4,7 +4,9 @@ use crate::core::{
     chunk::ChunkConfig,
     document::parser::ParseConfig,
     model::{
+        collection::CollectionSearchColumn,
         document::{Document, DocumentSearchColumn},
+        embedding::TextEmbeddingAdditionReport,
         Pagination, PaginationSort,
     },
 };
@@ -59,6 +61,61 @@ pub(super) struct ListDocumentsPayload {
     pub ready: Option<bool>,
 }
 
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct UpdateContentParams {
+    /// If `true`, re-embed the document into all the collections it is embedded in.
+    pub reembed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct UpdateContentResult {
+    pub document: Document,
+
+    /// The reports of re-embedding the document, empty unless requested.
+    pub reports: Vec<TextEmbeddingAdditionReport>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ArchiveUploadParams {
+    /// If given, every document uploaded from the archive is embedded into this collection.
+    pub collection_id: Option<Uuid>,
+}
+
+/// Progress of uploading documents from an archive, emitted once per archive entry.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub(super) enum ArchiveUploadEvent {
+    /// The entry was uploaded as a document.
+    Uploaded { path: String, document: Document },
+
+    /// The uploaded document was embedded into the requested collection.
+    Embedded {
+        path: String,
+        report: TextEmbeddingAdditionReport,
+    },
+
+    /// The entry was not uploaded, e.g. because its type is not supported.
+    Skipped { path: String, reason: String },
+
+    /// Uploading or embedding the entry failed.
+    Failed { path: String, error: String },
+
+    /// Always the last event of the stream.
+    Done(ArchiveUploadSummary),
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ArchiveUploadSummary {
+    pub uploaded: usize,
+    pub embedded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub(super) struct UpdateImageDescription {
     pub description: Option<String>,
@@ -69,6 +126,31 @@ pub(super) struct UpdateDocumentMetadata {
     pub name: Option<String>,
     pub label: Option<String>,
     pub tags: Option<Vec<String>>,
+
+    /// Custom attributes stored alongside the document's vectors. Only applies
+    /// to embeddings created after the update.
+    pub metadata: Option<serde_json::Value>,
+}
+
+// COLLECTIONS
+
+#[derive(Debug, Default, Deserialize, Validate, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ListCollectionsPayload {
+    /// Limit and offset
+    #[validate]
+    #[serde(flatten)]
+    #[param(inline)]
+    pub pagination: PaginationSort<CollectionSearchColumn>,
+
+    /// Only return collections with this tag.
+    pub tag: Option<String>,
+
+    /// Only return collections stored in this vector database provider.
+    pub provider: Option<String>,
+
+    /// Only return collections embedded with this embedding provider.
+    pub embedder: Option<String>,
 }
 
 // EMBEDDINGS
@@ -86,6 +168,16 @@ pub struct EmbedBatchInput {
 
     /// The ID of the collection in which to store/remove the embeddings to/from.
     pub collection: Uuid,
+
+    /// If `true`, documents already embedded in the collection are skipped instead of
+    /// resulting in an error, making the batch safe to re-run.
+    #[serde(default)]
+    pub skip_existing: bool,
+
+    /// How many times documents failing with transient errors, e.g. rate limits or
+    /// timeouts, are retried after the rest of the batch. Defaults to 1.
+    #[validate(range(max = 5.))]
+    pub retries: Option<u32>,
 }
 
 impl EmbedBatchInput {
@@ -59,18 +59,115 @@ pub fn router(state: AppState) -> Router {
             Method::PATCH,
         ]);
 
+    let router = routes(state.clone());
+
+    #[cfg(feature = "auth-jwt")]
+    let router = router.layer(axum::middleware::from_fn_with_state(
+        state.jwt_verifier.clone(),
+        crate::app::server::middleware::verify_jwt,
+    ));
+
+    router
+        .layer(
+            TraceLayer::new_for_http()
+                .on_request(|req: &axum::http::Request<_>, _span: &Span| {
+                    let ctype = req
+                        .headers()
+                        .get("content-type")
+                        .map(|v| v.to_str().unwrap_or("none"))
+                        .unwrap_or_else(|| "none");
+
+                    tracing::info!("Processing request | content-type: {ctype}");
+                })
+                .on_response(
+                    |res: &axum::http::Response<_>, latency: Duration, _span: &Span| {
+                        let status = res.status();
+                        let ctype = res
+                            .headers()
+                            .get("content-type")
+                            .map(|v| v.to_str().unwrap_or("none"))
+                            .unwrap_or_else(|| "none");
+
+                        tracing::info!(
+                            "Sending response | {status} | {}ms | {ctype}",
+                            latency.as_millis()
+                        );
+                    },
+                )
+                .on_failure(
+                    |error: ServerErrorsFailureClass, _latency: Duration, _span: &Span| {
+                        tracing::error!("Error in request: {error}")
+                    },
+                ),
+        )
+        .layer(cors)
+        // Unprotected at all times
+        .merge(
+            SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", {
+                #[allow(unused_mut)]
+                let mut api = ApiDoc::openapi();
+
+                #[cfg(feature = "gdrive")]
+                api.merge(google::GDriveApiDoc::openapi());
+
+                api
+            }),
+        )
+        // Has to go last to exclude all the tracing/cors layers
+        .route("/_health", get(health_check))
+}
+
+/// The API routes without the authentication, CORS and tracing layers.
+pub(crate) fn routes(state: AppState) -> Router {
+    let limits = state.http_config.body_limits;
+
+    let uploads = Router::new()
+        .route("/documents", post(document::upload_documents))
+        .route("/documents/from-archive", post(document::upload_archive))
+        .route(
+            "/documents/:id/content",
+            put(document::update_document_content),
+        )
+        .route_layer(DefaultBodyLimit::max(limits.upload));
+
+    let searches = Router::new()
+        .route("/search", post(collection::search))
+        .route("/search/stream", post(collection::search_stream))
+        .route("/search/multi", post(collection::search_multi))
+        .route("/ask", post(collection::ask))
+        .route_layer(DefaultBodyLimit::max(limits.search));
+
     let router = Router::new()
         .route("/info", get(app_config))
+        .route("/document-types", get(document::list_document_types))
         .route("/documents", get(document::list_documents))
-        .route("/documents", post(document::upload_documents))
-        .route_layer(DefaultBodyLimit::max(100_000_000))
         .route("/documents/:id", patch(document::update_document_metadata))
         .route("/documents/:id", get(document::get_document))
         .route("/documents/:id", delete(document::delete_document))
+        .route(
+            "/documents/:id/embeddings",
+            delete(embedding::delete_document_embeddings),
+        )
+        .route("/documents/delete-bulk", post(document::delete_documents_bulk))
+        .route(
+            "/documents/bulk-configure",
+            post(document::configure_documents_bulk),
+        )
+        .route("/documents/verify", post(document::verify_document_hashes))
+        .route("/documents/:id/sources", get(document::list_document_sources))
+        .route(
+            "/documents/:id/process-images",
+            post(document::process_document_images),
+        )
+        // Kept for backwards compatibility, prefer `/documents/:id/process-images`
         .route(
             "/documents/:id/images",
             post(document::process_document_images),
         )
+        .route(
+            "/documents/:id/images",
+            get(document::list_document_images),
+        )
         .route(
             "/documents/:id/config",
             put(document::update_document_config),
@@ -79,11 +176,14 @@ pub fn router(state: AppState) -> Router {
             "/documents/:id/chunk/preview",
             post(document::chunk_preview),
         )
+        .route("/documents/:id/chunk-diff", post(document::chunk_diff))
         .route(
             "/documents/:id/parse/preview",
             post(document::parse_preview),
         )
+        .route("/documents/:id/parsed", get(document::get_parsed_document))
         .route("/documents/sync/:provider", get(document::sync))
+        .route("/preview", post(document::inline_preview))
         .route("/images", post(document::upload_images))
         .route("/images", get(document::list_images))
         .route("/images/:image_id", put(document::update_image_description))
@@ -95,7 +195,27 @@ pub fn router(state: AppState) -> Router {
             "/collections/:id/groups",
             put(collection::update_collection_groups),
         )
+        .route(
+            "/collections/:id/tags",
+            put(collection::update_collection_tags),
+        )
         .route("/collections/:id", delete(collection::delete_collection))
+        .route(
+            "/collections/:id/export",
+            get(collection::export_collection),
+        )
+        .route(
+            "/collections/:id/import",
+            post(collection::import_collection),
+        )
+        .route(
+            "/collections/:id/manifest",
+            get(collection::get_collection_manifest),
+        )
+        .route(
+            "/collections/from-manifest",
+            post(collection::create_collection_from_manifest),
+        )
         .route(
             "/collections/:collection_id/documents/:document_id",
             delete(embedding::delete_embeddings),
@@ -104,6 +224,10 @@ pub fn router(state: AppState) -> Router {
             "/collections/:collection_id/documents/:document_id/count",
             get(embedding::count_embeddings),
         )
+        .route(
+            "/collections/:collection_id/documents/:document_id/reconstruct",
+            get(embedding::reconstruct_document),
+        )
         .route("/collections/sync", post(collection::sync))
         .route(
             "/collections/display",
@@ -117,6 +241,11 @@ pub fn router(state: AppState) -> Router {
             "/embeddings/reports",
             get(embedding::list_embedding_reports),
         )
+        .route(
+            "/reports/cleanup",
+            post(embedding::cleanup_embedding_reports),
+        )
+        .route("/reports/:id", get(embedding::get_embedding_report))
         .route("/embeddings", get(embedding::list_embedded_documents))
         .route(
             "/embeddings/:collection_id/outdated",
@@ -124,15 +253,22 @@ pub fn router(state: AppState) -> Router {
         )
         .route("/embeddings", post(embedding::embed_text))
         .route("/embeddings/image", post(embedding::embed_image))
+        .route("/embeddings/summary", post(embedding::embed_summary))
         .route(
             "/embeddings/:provider/models",
             get(embedding::list_embedding_models),
         )
         .route("/embeddings/batch", post(embedding::batch_embed_text))
-        .route("/search", post(collection::search))
+        .route("/embeddings/fanout", post(embedding::fanout_embed_text))
+        .route("/aliases", get(collection::list_aliases))
+        .route("/aliases", post(collection::set_alias))
+        .route("/aliases/:alias", delete(collection::delete_alias))
         .route("/documents/display", get(document::list_documents_display))
         .route("/blobs/images/:id", get(blob::get_image))
         .route("/blobs/documents/:id", get(blob::get_document_bytes))
+        .route_layer(DefaultBodyLimit::max(limits.default))
+        .merge(uploads)
+        .merge(searches)
         .with_state(state.clone());
 
     #[cfg(feature = "gdrive")]
@@ -155,60 +291,7 @@ pub fn router(state: AppState) -> Router {
         router.merge(Router::new().nest("/external", gdrive_router))
     };
 
-    #[cfg(feature = "auth-jwt")]
-    let router = router.layer(axum::middleware::from_fn_with_state(
-        state.jwt_verifier.clone(),
-        crate::app::server::middleware::verify_jwt,
-    ));
-
     router
-        .layer(
-            TraceLayer::new_for_http()
-                .on_request(|req: &axum::http::Request<_>, _span: &Span| {
-                    let ctype = req
-                        .headers()
-                        .get("content-type")
-                        .map(|v| v.to_str().unwrap_or("none"))
-                        .unwrap_or_else(|| "none");
-
-                    tracing::info!("Processing request | content-type: {ctype}");
-                })
-                .on_response(
-                    |res: &axum::http::Response<_>, latency: Duration, _span: &Span| {
-                        let status = res.status();
-                        let ctype = res
-                            .headers()
-                            .get("content-type")
-                            .map(|v| v.to_str().unwrap_or("none"))
-                            .unwrap_or_else(|| "none");
-
-                        tracing::info!(
-                            "Sending response | {status} | {}ms | {ctype}",
-                            latency.as_millis()
-                        );
-                    },
-                )
-                .on_failure(
-                    |error: ServerErrorsFailureClass, _latency: Duration, _span: &Span| {
-                        tracing::error!("Error in request: {error}")
-                    },
-                ),
-        )
-        .layer(cors)
-        // Unprotected at all times
-        .merge(
-            SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", {
-                #[allow(unused_mut)]
-                let mut api = ApiDoc::openapi();
-
-                #[cfg(feature = "gdrive")]
-                api.merge(google::GDriveApiDoc::openapi());
-
-                api
-            }),
-        )
-        // Has to go last to exclude all the tracing/cors layers
-        .route("/_health", get(health_check))
 }
 
 async fn health_check() -> impl IntoResponse {
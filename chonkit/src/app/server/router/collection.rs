@@ -1,16 +1,19 @@
 use crate::{
-    app::  state::AppState , core::{
+    app::{server::dto::ListCollectionsPayload, state::AppState}, core::{
          model::{
-            collection::{Collection, CollectionDisplay, CollectionDisplayAggregate, CollectionSearchColumn},  List, PaginationSort
-        }, service:: collection::dto::{CollectionData, CollectionSearchResult, CreateCollectionPayload, SearchPayload, SyncIncompatibilityResolution }
-    },  error::ChonkitError, map_err
+            collection::{Collection, CollectionAlias, CollectionDisplay, CollectionDisplayAggregate, CollectionFilter},  List
+        }, service:: collection::dto::{CollectionData, CollectionImportResult, CollectionManifest, CollectionSearchResult, CreateCollectionPayload, AskEvent, AskPayload, MultiSearchPayload, MultiSearchResult, SearchEvent, SearchPayload, SetAliasPayload, SyncIncompatibilityResolution }, vector::ExportedVector
+    },  err, error::ChonkitError, map_err
 };
 use axum::{
-    extract::{Path, Query, State}, http::StatusCode,  Json
+    body::Body, extract::{Path, Query, State}, http::{header, StatusCode}, response::{sse::{Event, KeepAlive}, IntoResponse, Sse}, Json
 };
+use futures_util::{Stream, StreamExt};
 use serde::Deserialize;
+use std::time::Duration;
 use utoipa::ToSchema;
 use uuid::Uuid;
+use validify::Validate;
 
 #[utoipa::path(
     get,
@@ -20,15 +23,29 @@ use uuid::Uuid;
         (status = 500, description = "Internal server error")
     ),
     params(
-        ("pagination" = Option<PaginationSort>, Query, description = "Pagination parameters")
+        ("pagination" = ListCollectionsPayload, Query, description = "Query parameters")
     )
 )]
 pub(super) async fn list_collections(
     State(state): State<AppState>,
-    params: Option<Query<PaginationSort<CollectionSearchColumn>>>,
+    params: Option<Query<ListCollectionsPayload>>,
 ) -> Result<Json<List<Collection>>, ChonkitError> {
-    let Query(params) = params.unwrap_or_default();
-    let collections = state.services.collection.list_collections(params).await?;
+    let Query(ListCollectionsPayload {
+        pagination,
+        tag,
+        provider,
+        embedder,
+    }) = params.unwrap_or_default();
+    let filter = CollectionFilter {
+        tag: tag.as_deref(),
+        provider: provider.as_deref(),
+        embedder: embedder.as_deref(),
+    };
+    let collections = state
+        .services
+        .collection
+        .list_collections(pagination, filter)
+        .await?;
     Ok(Json(collections))
 }
 
@@ -41,15 +58,25 @@ pub(super) async fn list_collections(
         (status = 500, description = "Internal server error")
     ),
     params(
-        ("pagination" = Option<PaginationSort>, Query, description = "Query parameters"),
+        ("pagination" = ListCollectionsPayload, Query, description = "Query parameters"),
     ),
 )]
 pub(super) async fn list_collections_display(
     State(state): State<AppState>,
-    payload: Option<Query<PaginationSort<CollectionSearchColumn>>>,
+    payload: Option<Query<ListCollectionsPayload>>,
 ) -> Result<Json<List<CollectionDisplay>>, ChonkitError> {
-    let Query(pagination) = payload.unwrap_or_default();
-    let collections = state.services.collection.list_collections_display(pagination).await?;
+    let Query(ListCollectionsPayload {
+        pagination,
+        tag,
+        provider,
+        embedder,
+    }) = payload.unwrap_or_default();
+    let filter = CollectionFilter {
+        tag: tag.as_deref(),
+        provider: provider.as_deref(),
+        embedder: embedder.as_deref(),
+    };
+    let collections = state.services.collection.list_collections_display(pagination, filter).await?;
     Ok(Json(collections))
 }
 
@@ -136,6 +163,29 @@ pub(super) async fn update_collection_groups(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    put,
+    path = "/collections/{id}/tags", 
+    responses(
+        (status = 204, description = "Collection tags updated successfully"),
+        (status = 404, description = "Collection not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    )
+)]
+pub(super) async fn update_collection_tags(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<Option<Vec<String>>>,
+) -> Result<StatusCode, ChonkitError> {
+    state.services.collection
+        .update_collection_tags(id, payload)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[utoipa::path(
     delete,
     path = "/collections/{id}", 
@@ -160,6 +210,64 @@ pub(super) async fn delete_collection(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/aliases", 
+    responses(
+        (status = 200, description = "Alias points to the collection", body = CollectionAlias),
+        (status = 404, description = "Collection not found"),
+        (status = 422, description = "Invalid alias"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = SetAliasPayload
+)]
+pub(super) async fn set_alias(
+    State(state): State<AppState>,
+    Json(payload): Json<SetAliasPayload>,
+) -> Result<Json<CollectionAlias>, ChonkitError> {
+    let alias = state.services.collection.set_alias(payload).await?;
+    Ok(Json(alias))
+}
+
+#[utoipa::path(
+    get,
+    path = "/aliases", 
+    responses(
+        (status = 200, description = "List aliases", body = Vec<CollectionAlias>),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("collectionId" = Option<Uuid>, Query, description = "Only list aliases pointing to this collection")
+    )
+)]
+pub(super) async fn list_aliases(
+    State(state): State<AppState>,
+    Query(params): Query<ListAliasesParams>,
+) -> Result<Json<Vec<CollectionAlias>>, ChonkitError> {
+    let aliases = state.services.collection.list_aliases(params.collection_id).await?;
+    Ok(Json(aliases))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/aliases/{alias}", 
+    responses(
+        (status = 204, description = "Alias deleted successfully"),
+        (status = 404, description = "Alias not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("alias" = String, Path, description = "Alias")
+    )
+)]
+pub(super) async fn delete_alias(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+) -> Result<StatusCode, ChonkitError> {
+    state.services.collection.delete_alias(&alias).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[utoipa::path(
     post,
     path = "/search", 
@@ -177,6 +285,234 @@ pub(super) async fn search(
     Ok(Json(chunks))
 }
 
+#[utoipa::path(
+    post,
+    path = "/search/multi", 
+    responses(
+        (status = 200, description = "Merged search results of all collections", body = MultiSearchResult),
+        (status = 404, description = "Collection not found"),
+        (status = 422, description = "Invalid search parameters or collections of different vector sizes"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = MultiSearchPayload
+)]
+pub(super) async fn search_multi(
+    State(state): State<AppState>,
+    Json(search): Json<MultiSearchPayload>,
+) -> Result<Json<MultiSearchResult>, ChonkitError> {
+    let result = state.services.collection.search_multi(search).await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/search/stream", 
+    responses(
+        (status = 200, description = "Stream of search events, the hits followed by their final ordering", body = inline(Vec<SearchEvent>)),
+        (status = 422, description = "Invalid search parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = SearchPayload
+)]
+pub(super) async fn search_stream(
+    State(state): State<AppState>,
+    Json(search): Json<SearchPayload>,
+) -> Result<Sse<impl Stream<Item = Result<Event, ChonkitError>>>, ChonkitError> {
+    map_err!(search.validate());
+
+    let stream = state
+        .services
+        .collection
+        .search_stream(search)
+        .map(|event| {
+            let event = match event {
+                Ok(event) => Event::default().json_data(event),
+                Err(err) => {
+                    tracing::error!("Error in search stream: {err}");
+                    let err = format!("error: {err}").replace('\n', " ");
+                    Ok(Event::default().data(err))
+                }
+            };
+            Ok(map_err!(event))
+        });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(1))
+            .text("keep-alive"),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/ask", 
+    responses(
+        (status = 200, description = "Stream of answer events, the citations followed by the answer as it is generated", body = inline(Vec<AskEvent>)),
+        (status = 404, description = "Collection not found"),
+        (status = 422, description = "Invalid question parameters"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = AskPayload
+)]
+pub(super) async fn ask(
+    State(state): State<AppState>,
+    Json(ask): Json<AskPayload>,
+) -> Result<Sse<impl Stream<Item = Result<Event, ChonkitError>>>, ChonkitError> {
+    let stream = state
+        .services
+        .collection
+        .ask(ask)
+        .await?
+        .map(|event| {
+            let event = match event {
+                Ok(event) => Event::default().json_data(event),
+                Err(err) => {
+                    tracing::error!("Error in answer stream: {err}");
+                    let err = format!("error: {err}").replace('\n', " ");
+                    Ok(Event::default().data(err))
+                }
+            };
+            Ok(map_err!(event))
+        });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(1))
+            .text("keep-alive"),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/collections/{id}/export",
+    responses(
+        (status = 200, description = "The collection's text vectors as newline delimited JSON", body = inline(Vec<ExportedVector>), content_type = "application/x-ndjson"),
+        (status = 404, description = "Collection not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    )
+)]
+pub(super) async fn export_collection(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ChonkitError> {
+    let vectors = state.services.collection.export_collection(id).await?;
+
+    let lines = vectors.map(|vector| {
+        let mut line = map_err!(serde_json::to_vec(&vector?));
+        line.push(b'\n');
+        Ok::<_, ChonkitError>(line)
+    });
+
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(lines)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/collections/{id}/import",
+    responses(
+        (status = 200, description = "Vectors imported successfully", body = CollectionImportResult),
+        (status = 404, description = "Collection not found"),
+        (status = 409, description = "Document already embedded in the collection"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "The ID of the collection to import to")
+    ),
+    request_body(content = inline(Vec<ExportedVector>), description = "Newline delimited JSON obtained from an export", content_type = "application/x-ndjson")
+)]
+pub(super) async fn import_collection(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    body: Body,
+) -> Result<Json<CollectionImportResult>, ChonkitError> {
+    let vectors = ndjson_lines(body).map(|line| {
+        Ok(map_err!(serde_json::from_slice::<ExportedVector>(&line?)))
+    });
+
+    let result = state.services.collection.import_collection(id, vectors).await?;
+
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/collections/{id}/manifest",
+    responses(
+        (status = 200, description = "Collection manifest", body = CollectionManifest),
+        (status = 404, description = "Collection not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    )
+)]
+pub(super) async fn get_collection_manifest(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CollectionManifest>, ChonkitError> {
+    let manifest = state.services.collection.get_collection_manifest(id).await?;
+    Ok(Json(manifest))
+}
+
+#[utoipa::path(
+    post,
+    path = "/collections/from-manifest",
+    responses(
+        (status = 201, description = "Collection created successfully", body = Collection),
+        (status = 400, description = "Invalid manifest"),
+        (status = 409, description = "Collection already exists"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = CollectionManifest
+)]
+pub(super) async fn create_collection_from_manifest(
+    State(state): State<AppState>,
+    Json(manifest): Json<CollectionManifest>,
+) -> Result<(StatusCode, Json<Collection>), ChonkitError> {
+    let collection = state.services.collection
+        .create_collection_from_manifest(manifest)
+        .await?;
+    Ok((StatusCode::CREATED, Json(collection)))
+}
+
+/// Split the body into its non-empty lines as it is received.
+fn ndjson_lines(body: Body) -> impl Stream<Item = Result<Vec<u8>, ChonkitError>> {
+    futures_util::stream::unfold(
+        (body.into_data_stream(), Vec::new(), false),
+        |(mut chunks, mut buf, mut done)| async move {
+            loop {
+                if let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=pos).collect();
+                    if line.trim_ascii().is_empty() {
+                        continue;
+                    }
+                    return Some((Ok(line), (chunks, buf, done)));
+                }
+
+                if done {
+                    if buf.trim_ascii().is_empty() {
+                        return None;
+                    }
+                    let line = std::mem::take(&mut buf);
+                    return Some((Ok(line), (chunks, buf, done)));
+                }
+
+                match chunks.next().await {
+                    Some(Ok(bytes)) => buf.extend_from_slice(&bytes),
+                    Some(Err(e)) => {
+                        buf.clear();
+                        return Some((err!(e.into()), (chunks, buf, true)));
+                    }
+                    None => done = true,
+                }
+            }
+        },
+    )
+}
+
 #[utoipa::path(
     post,
     path = "/sync", 
@@ -202,3 +538,9 @@ pub(super) async fn sync(
 pub(in crate::app::server) struct SyncParams {
     mode: Option<SyncIncompatibilityResolution>
 }
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ListAliasesParams {
+    collection_id: Option<Uuid>
+}
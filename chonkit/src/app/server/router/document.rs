@@ -1,32 +1,52 @@
 use crate::{
     app::{
         server::dto::{
-            ConfigUpdatePayload, ListDocumentsPayload, UpdateDocumentMetadata,
+            ArchiveUploadEvent, ArchiveUploadParams, ArchiveUploadSummary, ConfigUpdatePayload,
+            ListDocumentsPayload, UpdateContentParams, UpdateContentResult, UpdateDocumentMetadata,
             UpdateImageDescription, UploadResult,
         },
         state::AppState,
     },
     core::{
-        document::{parser::ParseConfig, DocumentType},
+        document::{
+            archive::ArchiveEntry, parser::ParseConfig, DocumentType, DocumentTypeCapabilities,
+        },
         model::{
-            document::{Document, DocumentConfig, DocumentDisplay, DocumentMetadataUpdate},
-            image::{ImageData, ImageModel},
+            document::{
+                Document, DocumentConfig, DocumentDisplay, DocumentMetadataUpdate, DocumentSource,
+            },
+            image::{ImageData, ImageDisplay, ImageModel},
             List,
         },
         service::document::dto::{
-            ChunkPreview, ChunkPreviewPayload, DocumentUpload, ListImagesParameters, ParsePreview,
+            BulkConfigurePayload, BulkDeletePayload, ChunkDiff, ChunkDiffPayload, ChunkPreview,
+            ChunkPreviewPayload, DocumentConfigureResult, DocumentDeleteResult,
+            DocumentImagesParameters, DocumentUpload, HashVerificationEvent, InlinePreviewPayload,
+            ListImagesParameters, ParsePreview, ParsedDocument, ParsedDocumentParameters,
+            VerifyHashesPayload,
         },
+        service::embedding::EmbedTextInput,
     },
     error::ChonkitError,
+    map_err,
 };
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive},
+        Sse,
+    },
     Json,
 };
-use std::collections::HashMap;
+use futures_util::{Stream, StreamExt};
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::mpsc::Sender;
 use uuid::Uuid;
 
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
 #[utoipa::path(
     get,
     path = "/documents",
@@ -54,6 +74,27 @@ pub(super) async fn list_documents(
     Ok(Json(documents))
 }
 
+#[utoipa::path(
+    get,
+    path = "/document-types",
+    responses(
+        (status = 200, description = "List document types accepted for upload and their parsing capabilities", body = Vec<DocumentTypeCapabilities>),
+    ),
+)]
+pub(super) async fn list_document_types(
+    State(state): State<AppState>,
+) -> Json<Vec<DocumentTypeCapabilities>> {
+    let types = state
+        .services
+        .document
+        .allowed_types()
+        .iter()
+        .map(|ty| ty.capabilities())
+        .collect();
+
+    Json(types)
+}
+
 #[utoipa::path(
     get,
     path = "/documents/display",
@@ -101,6 +142,26 @@ pub(super) async fn get_document(
     Ok(Json(document))
 }
 
+#[utoipa::path(
+    get,
+    path = "/documents/{id}/sources",
+    responses(
+        (status = 200, description = "List the sources of a document, starting with the one it was first obtained from", body = Vec<DocumentSource>),
+        (status = 404, description = "Document not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Document ID")
+    )
+)]
+pub(super) async fn list_document_sources(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<DocumentSource>>, ChonkitError> {
+    let sources = state.services.document.list_sources(id).await?;
+    Ok(Json(sources))
+}
+
 #[utoipa::path(
     delete,
     path = "/documents/{id}",
@@ -121,6 +182,74 @@ pub(super) async fn delete_document(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/documents/delete-bulk",
+    responses(
+        (status = 200, description = "Delete documents by id, returning the outcome for each", body = [DocumentDeleteResult]),
+        (status = 422, description = "No document IDs given"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = BulkDeletePayload
+)]
+pub(super) async fn delete_documents_bulk(
+    State(state): State<AppState>,
+    Json(payload): Json<BulkDeletePayload>,
+) -> Result<Json<Vec<DocumentDeleteResult>>, ChonkitError> {
+    let results = state.services.document.delete_many(payload).await?;
+    Ok(Json(results))
+}
+
+#[utoipa::path(
+    post,
+    path = "/documents/bulk-configure",
+    responses(
+        (status = 200, description = "Apply the parsing and chunking configuration to the documents, making them ready, returning the outcome for each", body = [DocumentConfigureResult]),
+        (status = 422, description = "No document IDs given or invalid configuration"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = BulkConfigurePayload
+)]
+pub(super) async fn configure_documents_bulk(
+    State(state): State<AppState>,
+    Json(payload): Json<BulkConfigurePayload>,
+) -> Result<Json<Vec<DocumentConfigureResult>>, ChonkitError> {
+    let results = state.services.document.configure_many(payload).await?;
+    Ok(Json(results))
+}
+
+#[utoipa::path(
+    post,
+    path = "/documents/verify",
+    responses(
+        (status = 200, description = "Stream of verification events; hash mismatches and documents that could not be verified as they are found, periodic progress and a final summary", body = inline(Vec<HashVerificationEvent>)),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = VerifyHashesPayload
+)]
+pub(super) async fn verify_document_hashes(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyHashesPayload>,
+) -> Result<Sse<impl Stream<Item = Result<Event, ChonkitError>>>, ChonkitError> {
+    let stream = state.services.document.verify_hashes(payload).map(|event| {
+        let event = match event {
+            Ok(event) => Event::default().json_data(event),
+            Err(err) => {
+                tracing::error!("Error in hash verification stream: {err}");
+                let err = format!("error: {err}").replace('\n', " ");
+                Ok(Event::default().data(err))
+            }
+        };
+        Ok(map_err!(event))
+    });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(1))
+            .text("keep-alive"),
+    ))
+}
+
 #[utoipa::path(
     patch,
     path = "/documents/{id}",
@@ -143,6 +272,7 @@ pub(super) async fn update_document_metadata(
         name: payload.name.as_deref(),
         label: payload.label.as_deref(),
         tags: payload.tags.as_deref(),
+        metadata: payload.metadata.as_ref(),
     };
 
     let document = state
@@ -163,11 +293,20 @@ pub(super) async fn update_document_metadata(
         (status = 400, description = "Bad request"),
         (status = 500, description = "Internal server error")
     ),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Repeated uploads with the same key return the originally created documents"),
+    ),
 )]
 pub(super) async fn upload_documents(
     State(state): State<AppState>,
+    headers: HeaderMap,
     mut form: axum::extract::Multipart,
 ) -> Result<Json<UploadResult>, ChonkitError> {
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .filter(|key| !key.is_empty());
+
     let mut documents = vec![];
     let mut errors = HashMap::<String, Vec<String>>::new();
 
@@ -204,7 +343,12 @@ pub(super) async fn upload_documents(
 
         let upload = DocumentUpload::new(name.to_string(), typ, &file);
 
-        let document = match state.services.document.upload(upload).await {
+        let result = match idempotency_key {
+            Some(key) => state.services.document.upload_idempotent(key, upload).await,
+            None => state.services.document.upload(upload).await,
+        };
+
+        let document = match result {
             Ok(doc) => doc,
             Err(e) => {
                 tracing::error!("{e}");
@@ -222,6 +366,198 @@ pub(super) async fn upload_documents(
     Ok(Json(UploadResult { documents, errors }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/documents/from-archive",
+    request_body(content = Vec<u8>, content_type = "application/octet-stream", description = "A zip or tar.gz archive whose files are uploaded as individual documents"),
+    responses(
+        (status = 200, description = "Stream of upload events, one or two per archive entry followed by a summary", body = inline(Vec<ArchiveUploadEvent>)),
+        (status = 400, description = "Unsupported archive format or archive exceeds the extraction limits"),
+        (status = 404, description = "Collection not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(ArchiveUploadParams)
+)]
+pub(super) async fn upload_archive(
+    State(state): State<AppState>,
+    params: Option<Query<ArchiveUploadParams>>,
+    archive: Bytes,
+) -> Result<Sse<impl Stream<Item = Result<Event, ChonkitError>>>, ChonkitError> {
+    let Query(params) = params.unwrap_or_default();
+
+    if let Some(collection_id) = params.collection_id {
+        state
+            .services
+            .collection
+            .get_collection(collection_id)
+            .await?;
+    }
+
+    let entries = state
+        .services
+        .document
+        .extract_archive(archive.to_vec())
+        .await?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel(entries.len() * 2 + 1);
+
+    tokio::spawn(async move {
+        upload_archive_entries(state, entries, params.collection_id, tx).await;
+    });
+
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|event: ArchiveUploadEvent| Ok(map_err!(Event::default().json_data(event))));
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(1))
+            .text("keep-alive"),
+    ))
+}
+
+/// Upload the extracted archive entries one by one, embedding them into the collection
+/// if one is given. Stops early if the receiving end is dropped.
+async fn upload_archive_entries(
+    state: AppState,
+    entries: Vec<ArchiveEntry>,
+    collection_id: Option<Uuid>,
+    tx: Sender<ArchiveUploadEvent>,
+) {
+    let mut summary = ArchiveUploadSummary::default();
+
+    for entry in entries {
+        let (path, name, content) = match entry {
+            ArchiveEntry::File {
+                path,
+                name,
+                content,
+            } => (path, name, content),
+            ArchiveEntry::Skipped { path, reason } => {
+                summary.skipped += 1;
+                let event = ArchiveUploadEvent::Skipped { path, reason };
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let ty = match DocumentType::try_from_file_name(&name)
+            .and_then(|ty| ty.ensure_allowed(state.services.document.allowed_types()))
+        {
+            Ok(ty) => ty,
+            Err(e) => {
+                summary.skipped += 1;
+                let reason = e.error.to_string();
+                let event = ArchiveUploadEvent::Skipped { path, reason };
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        let upload = DocumentUpload::new(name, ty, &content);
+
+        let document = match state.services.document.upload(upload).await {
+            Ok(document) => document,
+            Err(e) => {
+                tracing::error!("Error uploading '{path}' from archive: {e}");
+                summary.failed += 1;
+                let error = e.error.to_string();
+                let event = ArchiveUploadEvent::Failed { path, error };
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        summary.uploaded += 1;
+
+        let document_id = document.id;
+        let event = ArchiveUploadEvent::Uploaded {
+            path: path.clone(),
+            document,
+        };
+
+        if tx.send(event).await.is_err() {
+            return;
+        }
+
+        let Some(collection_id) = collection_id else {
+            continue;
+        };
+
+        let input = EmbedTextInput::new(document_id, collection_id);
+
+        let event = match state.services.embedding.create_text_embeddings(input).await {
+            Ok(report) => {
+                summary.embedded += 1;
+                ArchiveUploadEvent::Embedded { path, report }
+            }
+            Err(e) => {
+                tracing::error!("Error embedding '{path}' from archive: {e}");
+                summary.failed += 1;
+                let error = e.error.to_string();
+                ArchiveUploadEvent::Failed { path, error }
+            }
+        };
+
+        if tx.send(event).await.is_err() {
+            return;
+        }
+    }
+
+    tracing::info!(
+        "Uploaded {} documents from archive ({} embedded, {} skipped, {} failed)",
+        summary.uploaded,
+        summary.embedded,
+        summary.skipped,
+        summary.failed
+    );
+
+    let _ = tx.send(ArchiveUploadEvent::Done(summary)).await;
+}
+
+#[utoipa::path(
+    put,
+    path = "/documents/{id}/content",
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Replace the content of a document, keeping its ID and collections. Its embeddings become outdated unless it is re-embedded.", body = UpdateContentResult),
+        (status = 404, description = "Document not found"),
+        (status = 409, description = "Another document has the same content"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Document ID"),
+        UpdateContentParams,
+    )
+)]
+pub(super) async fn update_document_content(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    params: Option<Query<UpdateContentParams>>,
+    content: Bytes,
+) -> Result<Json<UpdateContentResult>, ChonkitError> {
+    let Query(params) = params.unwrap_or_default();
+
+    let document = state.services.document.update_content(id, &content).await?;
+
+    let reports = if params.reembed.unwrap_or(false) {
+        state
+            .services
+            .embedding
+            .refresh_text_embeddings(document.id)
+            .await?
+    } else {
+        vec![]
+    };
+
+    Ok(Json(UpdateContentResult { document, reports }))
+}
+
 #[utoipa::path(
     put,
     path = "/documents/{id}/config",
@@ -287,6 +623,47 @@ pub(super) async fn chunk_preview(
     Ok(Json(chunks))
 }
 
+#[utoipa::path(
+    post,
+    path = "/documents/{id}/chunk-diff",
+    responses(
+        (status = 200, description = "Difference between the chunk boundaries of two configurations", body = ChunkDiff),
+        (status = 400, description = "Invalid configuration or document parsed to sections"),
+        (status = 404, description = "Document not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Document ID"),
+    ),
+    request_body = ChunkDiffPayload
+)]
+pub(super) async fn chunk_diff(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ChunkDiffPayload>,
+) -> Result<Json<ChunkDiff>, ChonkitError> {
+    let diff = state.services.document.chunk_diff(id, payload).await?;
+    Ok(Json(diff))
+}
+
+#[utoipa::path(
+    post,
+    path = "/preview",
+    responses(
+        (status = 200, description = "Preview chunks of inline content", body = ChunkPreview),
+        (status = 400, description = "Invalid content or configuration"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = InlinePreviewPayload
+)]
+pub(super) async fn inline_preview(
+    State(state): State<AppState>,
+    Json(payload): Json<InlinePreviewPayload>,
+) -> Result<Json<ChunkPreview>, ChonkitError> {
+    let chunks = state.services.document.inline_preview(payload).await?;
+    Ok(Json(chunks))
+}
+
 #[utoipa::path(
     post,
     path = "/documents/{id}/parse/preview",
@@ -310,6 +687,28 @@ pub(super) async fn parse_preview(
     ))
 }
 
+#[utoipa::path(
+    get,
+    path = "/documents/{id}/parsed",
+    responses(
+        (status = 200, description = "The complete parse of the document, paginated by section for sectioned documents", body = ParsedDocument),
+        (status = 400, description = "Invalid parse configuration"),
+        (status = 404, description = "Document not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Document ID"),
+        ParsedDocumentParameters,
+    )
+)]
+pub(super) async fn get_parsed_document(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ParsedDocumentParameters>,
+) -> Result<Json<ParsedDocument>, ChonkitError> {
+    Ok(Json(state.services.document.get_parsed(id, params).await?))
+}
+
 #[utoipa::path(
     get,
     path = "/documents/sync/{provider}", 
@@ -331,10 +730,11 @@ pub(super) async fn sync(
 
 #[utoipa::path(
     post,
-    path = "/documents/{id}/images",
+    path = "/documents/{id}/process-images",
     responses(
-        (status = 204, description = "Parse document images and store them in the database. This is done automatically during upload. Skips already existing images."),
+        (status = 204, description = "Parse document images and store them in the database. This is done automatically during upload. Skips already existing images. Clears the document's pending images flag on success."),
         (status = 404, description = "Document not found"),
+        (status = 503, description = "Some of the images could not be stored, the document's images remain pending"),
         (status = 500, description = "Internal server error")
     ),
     params(
@@ -349,6 +749,33 @@ pub(super) async fn process_document_images(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    get,
+    path = "/documents/{id}/images",
+    responses(
+        (status = 200, description = "List the document's images and the collections they are embedded in", body = Vec<ImageDisplay>),
+        (status = 404, description = "Document not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Document ID"),
+        DocumentImagesParameters,
+    )
+)]
+pub(super) async fn list_document_images(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(parameters): Query<DocumentImagesParameters>,
+) -> Result<Json<Vec<ImageDisplay>>, ChonkitError> {
+    let images = state
+        .services
+        .document
+        .list_document_images(id, parameters)
+        .await?;
+
+    Ok(Json(images))
+}
+
 #[utoipa::path(
     get,
     path = "/images",
@@ -1,18 +1,22 @@
 use crate::{
     app::{
-        batch::{BatchJob, BatchJobResult},
+        batch::{BatchJob, BatchJobResult, DEFAULT_BATCH_RETRIES},
         server::dto::{EmbedBatchInput, ListEmbeddingsPayload},
         state::AppState,
     },
     core::{
         model::{
             embedding::{
-                EmbeddingReport, ImageEmbeddingAdditionReport, TextEmbedding,
-                TextEmbeddingAdditionReport, TextEmbeddingRemovalReport,
+                DocumentEmbeddingRemovalReport, EmbeddingReport, ImageEmbeddingAdditionReport,
+                TextEmbedding, TextEmbeddingAdditionReport, TextEmbeddingRemovalReport,
             },
             List,
         },
-        service::embedding::{EmbedImageInput, EmbedTextInput, ListEmbeddingReportsParams},
+        service::embedding::{
+            CleanupReportsParams, CleanupReportsResult, EmbedFanoutInput, EmbedImageInput,
+            EmbedSummaryInput, EmbedTextInput, GetEmbeddingReportParams,
+            ListEmbeddingReportsParams, ReconstructedDocument,
+        },
     },
     err,
     error::ChonkitError,
@@ -76,6 +80,29 @@ pub(super) async fn embed_text(
     Ok((StatusCode::OK, Json(report)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/embeddings/summary", 
+    responses(
+        (status = 200, description = "Summary embedded successfully", body = TextEmbeddingAdditionReport),
+        (status = 400, description = "No LLM is configured"),
+        (status = 404, description = "Collection or document not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = EmbedSummaryInput
+)]
+pub(super) async fn embed_summary(
+    State(state): State<AppState>,
+    Json(input): Json<EmbedSummaryInput>,
+) -> Result<(StatusCode, Json<TextEmbeddingAdditionReport>), ChonkitError> {
+    let report = state
+        .services
+        .embedding
+        .embed_summary(input.document, input.collection)
+        .await?;
+    Ok((StatusCode::OK, Json(report)))
+}
+
 #[utoipa::path(
     post,
     path = "/embeddings/images", 
@@ -117,11 +144,15 @@ pub(super) async fn batch_embed_text(
         collection,
         add,
         remove,
+        skip_existing,
+        retries,
     } = input;
 
     let (tx, rx) = tokio::sync::mpsc::channel::<BatchJobResult>(add.len() + remove.len() + 1);
 
-    let job = BatchJob::new(collection, add, remove, tx);
+    let job = BatchJob::new(collection, add, remove, tx)
+        .with_skip_existing(skip_existing)
+        .with_retries(retries.unwrap_or(DEFAULT_BATCH_RETRIES));
 
     if let Err(e) = state.batch_embedder.send(job).await {
         tracing::error!("Error sending embedding job: {:?}", e.0);
@@ -135,22 +166,7 @@ pub(super) async fn batch_embed_text(
                 unreachable!()
             };
             tracing::debug!("sse received event");
-            let event = match result.result {
-                Ok(report) => match Event::default().json_data(report) {
-                    Ok(event) => event,
-                    Err(err) => {
-                        tracing::error!("Error serializing embedding report: {err}");
-                        let err = format!("error: {err}").replace('\n', " ");
-                        Event::default().data(err)
-                    }
-                },
-                Err(err) => {
-                    tracing::error!("Received error in batch embedder: {err}");
-                    let err = format!("error: {err}").replace('\n', " ");
-                    Event::default().data(err)
-                }
-            };
-            Ok(event)
+            Ok(report_event(result.result))
         });
 
     Ok(Sse::new(stream).keep_alive(
@@ -160,6 +176,54 @@ pub(super) async fn batch_embed_text(
     ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/embeddings/fanout", 
+    responses(
+        (status = 200, description = "Stream of embedding reports, one per collection"),
+        (status = 400, description = "No collections provided"),
+        (status = 500, description = "Internal server error")
+    ),
+    request_body = EmbedFanoutInput
+)]
+pub(super) async fn fanout_embed_text(
+    State(state): State<AppState>,
+    Json(input): Json<EmbedFanoutInput>,
+) -> Result<Sse<impl Stream<Item = Result<Event, ChonkitError>>>, ChonkitError> {
+    map_err!(input.validate());
+
+    let stream = state
+        .services
+        .embedding
+        .create_text_embeddings_fanout(input)
+        .map(|result| Ok(report_event(result)));
+
+    Ok(Sse::new(stream).keep_alive(
+        axum::response::sse::KeepAlive::new()
+            .interval(Duration::from_secs(1))
+            .text("keep-alive"),
+    ))
+}
+
+/// Map the result of embedding to an SSE event containing either the report or the error.
+fn report_event(result: Result<impl serde::Serialize, ChonkitError>) -> Event {
+    match result {
+        Ok(report) => match Event::default().json_data(report) {
+            Ok(event) => event,
+            Err(err) => {
+                tracing::error!("Error serializing embedding report: {err}");
+                let err = format!("error: {err}").replace('\n', " ");
+                Event::default().data(err)
+            }
+        },
+        Err(err) => {
+            tracing::error!("Received error in embedder: {err}");
+            let err = format!("error: {err}").replace('\n', " ");
+            Event::default().data(err)
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/embeddings", 
@@ -232,6 +296,31 @@ pub(super) async fn count_embeddings(
     Ok(Json(amount))
 }
 
+#[utoipa::path(
+    get,
+    path = "/collections/{collection_id}/documents/{document_id}/reconstruct",
+    responses(
+        (status = 200, description = "The document's text reconstructed from its stored chunks", body = ReconstructedDocument),
+        (status = 404, description = "Collection or document chunks not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("collection_id" = Uuid, Path, description = "Collection ID"),
+        ("document_id" = Uuid, Path, description = "Document ID"),
+    ),
+)]
+pub(super) async fn reconstruct_document(
+    State(state): State<AppState>,
+    Path((collection_id, document_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ReconstructedDocument>, ChonkitError> {
+    let document = state
+        .services
+        .embedding
+        .reconstruct_document(collection_id, document_id)
+        .await?;
+    Ok(Json(document))
+}
+
 #[utoipa::path(
     delete,
     path = "/collections/{collection_id}/documents/{document_id}",
@@ -257,6 +346,26 @@ pub(super) async fn delete_embeddings(
     Ok((StatusCode::OK, Json(report)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/documents/{id}/embeddings",
+    responses(
+        (status = 200, description = "Delete the text and image embeddings of a document from every collection, keeping the document.", body = Vec<DocumentEmbeddingRemovalReport>),
+        (status = 404, description = "Document not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Document ID"),
+    ),
+)]
+pub(super) async fn delete_document_embeddings(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<DocumentEmbeddingRemovalReport>>, ChonkitError> {
+    let reports = state.services.embedding.delete_all_embeddings(id).await?;
+    Ok(Json(reports))
+}
+
 #[utoipa::path(
     get,
     path = "/embeddings/reports",
@@ -280,3 +389,51 @@ pub(super) async fn list_embedding_reports(
         .await?;
     Ok(Json(embeddings))
 }
+
+#[utoipa::path(
+    get,
+    path = "/reports/{id}",
+    responses(
+        (status = 200, description = "Embedding report", body = EmbeddingReport),
+        (status = 404, description = "Report not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(
+        ("id" = i32, Path, description = "Embedding report ID"),
+        GetEmbeddingReportParams,
+    ),
+)]
+pub(super) async fn get_embedding_report(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<GetEmbeddingReportParams>,
+) -> Result<Json<EmbeddingReport>, ChonkitError> {
+    let report = state
+        .services
+        .embedding
+        .get_embedding_report(id, params.kind)
+        .await?;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    post,
+    path = "/reports/cleanup",
+    responses(
+        (status = 200, description = "Amount of deleted embedding reports", body = CleanupReportsResult),
+        (status = 400, description = "Invalid retention"),
+        (status = 500, description = "Internal server error")
+    ),
+    params(CleanupReportsParams),
+)]
+pub(super) async fn cleanup_embedding_reports(
+    State(state): State<AppState>,
+    Query(params): Query<CleanupReportsParams>,
+) -> Result<Json<CleanupReportsResult>, ChonkitError> {
+    let deleted = state
+        .services
+        .embedding
+        .cleanup_reports(params.older_than)
+        .await?;
+    Ok(Json(CleanupReportsResult { deleted }))
+}
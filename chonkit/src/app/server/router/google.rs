@@ -31,7 +31,8 @@ pub(super) async fn import_files(
     access_token: axum::extract::Extension<GoogleAccessToken>,
     Json(payload): Json<ImportPayload>,
 ) -> Result<(StatusCode, Json<ImportResult>), ChonkitError> {
-    let api = GoogleDriveApi::new(state.http_client.clone(), access_token.0);
+    let api = GoogleDriveApi::new(state.http_client.clone(), access_token.0)
+        .with_download_config(state.google_drive_download.clone());
     let service = state.services.external.storage(api);
     let result = service.import_documents(payload.files).await?;
     Ok((StatusCode::OK, Json(result)))
@@ -53,7 +54,8 @@ pub(super) async fn import_file(
     access_token: axum::extract::Extension<GoogleAccessToken>,
     Path(file_id): Path<String>,
 ) -> Result<(StatusCode, Json<Document>), ChonkitError> {
-    let api = GoogleDriveApi::new(state.http_client.clone(), access_token.0);
+    let api = GoogleDriveApi::new(state.http_client.clone(), access_token.0)
+        .with_download_config(state.google_drive_download.clone());
     let service = state.services.external.storage(api);
     let document = service.import_document(&file_id).await?;
     Ok((StatusCode::CREATED, Json(document)))
@@ -71,7 +73,8 @@ pub(super) async fn list_outdated_documents(
     access_token: axum::extract::Extension<GoogleAccessToken>,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<OutdatedDocument>>, ChonkitError> {
-    let api = GoogleDriveApi::new(state.http_client.clone(), access_token.0);
+    let api = GoogleDriveApi::new(state.http_client.clone(), access_token.0)
+        .with_download_config(state.google_drive_download.clone());
     let service = state.services.external.storage(api);
     let outdated = service.list_outdated_documents().await?;
     Ok(Json(outdated))
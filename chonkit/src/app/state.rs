@@ -1,15 +1,17 @@
 use super::{
     batch::{self, BatchEmbedderHandle},
-    server::HttpConfiguration,
+    server::{BodyLimits, HttpConfiguration},
 };
 use crate::{
-    app::document::store::FsDocumentStore,
+    app::{document::store::FsDocumentStore, llm::OpenAiChat, rerank::HttpReranker},
     config::FS_STORE_ID,
     core::{
-        cache::{init, ImageEmbeddingCache, TextEmbeddingCache},
+        cache::{connect, IdempotencyCache, ImageEmbeddingCache, TextEmbeddingCache},
         chunk::ChunkConfig,
-        document::{DocumentType, TextDocumentType},
+        document::archive::ArchiveLimits,
+        embeddings::{AuditedEmbedder, ConcurrentEmbedder, NormalizedEmbedder, ProbingEmbedder},
         image::{minio::MinioClient, ImageStore},
+        model::embedding::{EmbeddingRequest, ReportRetention},
         provider::{
             DocumentStorageProvider, EmbeddingProvider, Identity, ProviderState, VectorDbProvider,
         },
@@ -20,13 +22,17 @@ use crate::{
         },
         token::Tokenizer,
     },
-    error::ChonkitError,
+    error::{ChonkitErr, ChonkitError},
 };
 use chonkit_embedders::EmbeddingModel;
 use serde::Serialize;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::mpsc::UnboundedSender;
 use tracing_subscriber::EnvFilter;
 
+/// How often embedding reports outside of the retention window are deleted.
+const REPORT_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Clone)]
 pub struct AppState {
     /// Chonkit services.
@@ -47,6 +53,10 @@ pub struct AppState {
 
     #[cfg(feature = "auth-jwt")]
     pub jwt_verifier: super::auth::JwtVerifier,
+
+    /// Retry configuration for downloading files from Google Drive.
+    #[cfg(feature = "gdrive")]
+    pub google_drive_download: super::external::google::drive::DriveDownloadConfig,
 }
 
 impl AppState {
@@ -59,39 +69,162 @@ impl AppState {
             .with_env_filter(EnvFilter::from(args.log()))
             .init();
 
-        let repository = crate::core::repo::Repository::new(&args.db_url()).await;
+        let attempts = args.startup_connect_attempts();
+        let backoff = args.startup_connect_backoff();
+
+        let repository =
+            match crate::core::repo::Repository::connect(&args.db_url(), attempts, backoff).await {
+                Ok(repository) => repository,
+                Err(e) => {
+                    tracing::error!("Unable to connect to postgres after {attempts} attempts: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+        let redis_url = args.redis_url();
+        let connect_redis = |db: String| {
+            let redis_url = &redis_url;
+            async move {
+                match connect(redis_url, &db, attempts, backoff).await {
+                    Ok(pool) => pool,
+                    Err(e) => {
+                        tracing::error!(
+                            "Unable to connect to redis db {db} after {attempts} attempts: {e}"
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            }
+        };
 
         let embedding_cache =
-            TextEmbeddingCache::new(init(&args.redis_url(), &args.redis_embedding_db()).await);
+            TextEmbeddingCache::new(connect_redis(args.redis_embedding_db()).await)
+                .with_precision(args.embedding_cache_precision());
 
         let image_embedding_cache =
-            ImageEmbeddingCache::new(init(&args.redis_url(), &args.redis_image_db()).await);
+            ImageEmbeddingCache::new(connect_redis(args.redis_image_db()).await);
+
+        let idempotency_cache = IdempotencyCache::new(
+            connect_redis(args.redis_idempotency_db()).await,
+            args.idempotency_ttl(),
+        );
+
+        let tokenizer = Tokenizer::new();
+
+        let audit = args.audit_embedding_requests().then(|| {
+            tracing::info!("Auditing embedding provider requests");
+            start_embedding_request_audit(repository.clone())
+        });
 
         let providers = AppProviderState {
             database: repository.clone(),
             vector: Self::init_vector_providers(args),
-            embedding: Self::init_embedding_providers(args),
+            embedding: Self::init_embedding_providers(args, &tokenizer, audit),
             document: Self::init_storage(args).await,
             image: Self::init_image_storage(args).await,
         };
 
+        let parser_defaults = args.parser_defaults();
+        let hashing = args.document_hashing();
+        let image_extraction = args.image_extraction();
+        let allowed_types = args.allowed_document_types();
+
+        let mut collection = CollectionService::new(
+            repository.clone(),
+            providers.clone().into(),
+            tokenizer.clone(),
+        )
+        .with_search_defaults(args.search_limit(), args.search_max_distance())
+        .with_search_timeout(args.search_timeout())
+        .with_max_page_size(args.max_page_size())
+        .with_max_vector_size(args.max_vector_size())
+        .with_ask_context_tokens(args.ask_context_tokens());
+
+        let mut embedding = EmbeddingService::new(
+            repository.clone(),
+            providers.clone().into(),
+            embedding_cache,
+            image_embedding_cache,
+            tokenizer.clone(),
+        )
+        .with_max_chunks(args.max_chunks())
+        .with_max_page_size(args.max_page_size())
+        .with_max_vector_size(args.max_vector_size())
+        .with_parse_timeout(args.parse_timeout())
+        .with_parser_defaults(parser_defaults.clone());
+
+        if let Some(auto_create) = args.auto_create_collections() {
+            tracing::info!(
+                "Creating collections automatically with model '{}' ({}) in '{}'",
+                auto_create.model,
+                auto_create.embedding_provider,
+                auto_create.vector_provider
+            );
+            embedding = embedding.with_auto_create_collections(auto_create);
+        }
+
+        if let Some(endpoint) = args.llm_endpoint() {
+            let model = args.llm_model();
+            tracing::info!(
+                "Query rewriting, answers and summaries enabled with LLM '{model}' at {endpoint}"
+            );
+            let llm = Arc::new(OpenAiChat::new(endpoint, model, args.llm_key()));
+            collection = collection.with_llm(llm.clone());
+            embedding = embedding.with_llm(llm);
+        }
+
+        if let Some(endpoint) = args.rerank_endpoint() {
+            tracing::info!("Search reranking enabled with reranker at {endpoint}");
+            let reranker = HttpReranker::new(endpoint, args.rerank_key());
+            collection = collection.with_reranker(Arc::new(reranker));
+        }
+
         let services = ServiceState {
             document: DocumentService::new(
                 repository.clone(),
                 providers.clone().into(),
-                Tokenizer::new(),
-            ),
-            collection: CollectionService::new(repository.clone(), providers.clone().into()),
-            external: ServiceFactory::new(repository.clone(), providers.clone().into()),
-            embedding: EmbeddingService::new(
-                repository,
-                providers.clone().into(),
-                embedding_cache,
-                image_embedding_cache,
-            ),
+                tokenizer.clone(),
+            )
+            .with_max_chunks(args.max_chunks())
+            .with_max_page_size(args.max_page_size())
+            .with_parser_defaults(parser_defaults)
+            .with_parse_timeout(args.parse_timeout())
+            .with_idempotency(idempotency_cache)
+            .with_hashing(hashing)
+            .with_allowed_types(allowed_types.clone())
+            .with_archive_limits(ArchiveLimits {
+                max_size: args.archive_max_size(),
+                max_entry_size: args.archive_max_entry_size(),
+            })
+            .with_image_extraction(image_extraction)
+            .with_ready_requires_embeddings(args.ready_requires_embeddings()),
+            collection,
+            external: ServiceFactory::new(repository, providers.clone().into())
+                .with_hashing(hashing)
+                .with_allowed_types(allowed_types)
+                .with_image_extraction(image_extraction),
+            embedding,
         };
 
-        services.document.create_default_document().await;
+        if let Err(e) = services.document.create_default_document().await {
+            tracing::error!("Unable to create default document: {e}");
+        }
+
+        if let Some(retention) = args.report_retention() {
+            tracing::info!("Deleting embedding reports older than {retention}");
+            start_report_cleanup(services.embedding.clone(), retention);
+        }
+
+        for (source, interval) in args.sync_intervals() {
+            if let Err(e) = providers.document.get_provider(&source) {
+                panic!("Unable to schedule sync for '{source}': {e}");
+            }
+            tracing::info!(
+                "Syncing documents with '{source}' every {}s",
+                interval.as_secs()
+            );
+            start_document_sync(services.document.clone(), source, interval);
+        }
 
         let http_client = reqwest::Client::new();
 
@@ -121,6 +254,13 @@ impl AppState {
 
             #[cfg(feature = "auth-jwt")]
             jwt_verifier,
+
+            #[cfg(feature = "gdrive")]
+            google_drive_download: super::external::google::drive::DriveDownloadConfig {
+                max_attempts: args.google_drive_download_attempts(),
+                backoff: Duration::from_millis(args.google_drive_download_backoff()),
+                ..Default::default()
+            },
         }
     }
 
@@ -133,30 +273,53 @@ impl AppState {
             cors_origins: std::sync::Arc::from(&*cors_origins.leak()),
             cors_headers: std::sync::Arc::from(&*cors_headers.leak()),
             cookie_domain: cookie_domain.into(),
+            body_limits: BodyLimits {
+                upload: args.upload_body_limit(),
+                search: args.search_body_limit(),
+                default: args.body_limit(),
+            },
         }
     }
 
     fn init_vector_providers(args: &crate::config::StartArgs) -> VectorDbProvider {
         let mut provider = VectorDbProvider::default();
 
+        #[cfg(any(feature = "qdrant", feature = "weaviate"))]
+        let batch_size = args.vector_insert_batch_size();
+
         #[cfg(feature = "qdrant")]
         {
             let qdrant = crate::app::vector::qdrant::init(&args.qdrant_url());
-            provider.register(qdrant);
-            tracing::info!("Registered Qdrant vector provider");
+            provider.register(Arc::new(crate::core::vector::BatchedVectorDb::new(
+                qdrant, batch_size,
+            )));
+            tracing::info!("Registered Qdrant vector provider (insert batch size: {batch_size})");
         }
 
         #[cfg(feature = "weaviate")]
         {
             let weaviate = crate::app::vector::weaviate::init(&args.weaviate_url());
-            provider.register(weaviate);
-            tracing::info!("Registered Weaviate vector provider");
+            let max_requests = args.weaviate_max_requests();
+            let weaviate = Arc::new(crate::core::vector::ConcurrentVectorDb::new(
+                weaviate,
+                max_requests,
+            ));
+            provider.register(Arc::new(crate::core::vector::BatchedVectorDb::new(
+                weaviate, batch_size,
+            )));
+            tracing::info!(
+                "Registered Weaviate vector provider (max requests: {max_requests}, insert batch size: {batch_size})"
+            );
         }
 
         provider
     }
 
-    fn init_embedding_providers(_args: &crate::config::StartArgs) -> EmbeddingProvider {
+    fn init_embedding_providers(
+        _args: &crate::config::StartArgs,
+        tokenizer: &Tokenizer,
+        audit: Option<UnboundedSender<EmbeddingRequest>>,
+    ) -> EmbeddingProvider {
         #[cfg(not(any(feature = "fe-local", feature = "fe-remote", feature = "openai")))]
         compile_error!("one of `fe-local`, `fe-remote` or `openai` features must be enabled");
 
@@ -198,6 +361,7 @@ impl AppState {
                 _args.azure_endpoint(),
                 _args.azure_key(),
                 _args.azure_api_version(),
+                _args.azure_deployments(),
             ));
             tracing::info!("Registered embedding provider: {}", azure.id());
             provider.register(azure);
@@ -205,15 +369,65 @@ impl AppState {
 
         #[cfg(feature = "vllm")]
         {
-            let vllm = Arc::new(crate::app::embedder::vllm::VllmEmbeddings::new(
-                _args.vllm_endpoint(),
-                _args.vllm_key(),
-            ));
+            let vllm = Arc::new(
+                crate::app::embedder::vllm::VllmEmbeddings::new(
+                    _args.vllm_endpoint(),
+                    _args.vllm_key(),
+                )
+                .with_pooling(_args.vllm_pooling()),
+            );
 
             tracing::info!("Registered embedding provider: {}", vllm.id());
             provider.register(vllm);
         }
 
+        // Audited before batching so every request made to the provider is recorded
+        if let Some(audit) = audit {
+            for id in provider.list_provider_ids() {
+                let embedder = provider
+                    .get_provider(id)
+                    .expect("provider id is registered");
+                provider.register(Arc::new(AuditedEmbedder::new(
+                    embedder,
+                    tokenizer.clone(),
+                    audit.clone(),
+                )));
+            }
+        }
+
+        if let Some(batch_size) = _args.embedding_batch_size() {
+            let concurrency = _args.embedding_concurrency();
+            for id in provider.list_provider_ids() {
+                let embedder = provider
+                    .get_provider(id)
+                    .expect("provider id is registered");
+                provider.register(Arc::new(ConcurrentEmbedder::new(
+                    embedder,
+                    batch_size,
+                    concurrency,
+                )));
+            }
+            tracing::info!("Embedding in batches of {batch_size} with concurrency {concurrency}");
+        }
+
+        for id in _args.normalize_embeddings() {
+            let Ok(embedder) = provider.get_provider(&id) else {
+                tracing::warn!("Cannot normalize embeddings of unregistered provider: {id}");
+                continue;
+            };
+            tracing::info!("Normalizing embeddings of provider: {id}");
+            provider.register(Arc::new(NormalizedEmbedder::new(embedder)));
+        }
+
+        for id in _args.probe_embedding_models() {
+            let Ok(embedder) = provider.get_provider(&id) else {
+                tracing::warn!("Cannot probe models of unregistered provider: {id}");
+                continue;
+            };
+            tracing::info!("Probing unlisted models of provider: {id}");
+            provider.register(Arc::new(ProbingEmbedder::new(embedder)));
+        }
+
         provider
     }
 
@@ -253,11 +467,20 @@ impl AppState {
     /// Used for metadata display.
     pub async fn get_configuration(&self) -> Result<AppConfig, ChonkitError> {
         let mut embedding_providers = HashMap::new();
+        let mut unavailable_embedding_providers = HashMap::new();
 
+        // A provider being down should not hide the others
         for provider in self.providers.embedding.list_provider_ids() {
             let embedder = self.providers.embedding.get_provider(provider)?;
-            let models = embedder.list_embedding_models().await?;
-            embedding_providers.insert(provider.to_string(), models);
+            match embedder.list_embedding_models().await {
+                Ok(models) => {
+                    embedding_providers.insert(provider.to_string(), models);
+                }
+                Err(e) => {
+                    tracing::warn!("Embedding provider '{provider}' is unavailable: {e}");
+                    unavailable_embedding_providers.insert(provider.to_string(), e.to_string());
+                }
+            }
         }
 
         let document_providers = vec![
@@ -275,22 +498,71 @@ impl AppState {
                 .map(|s| s.to_string())
                 .collect(),
             embedding_providers,
+            unavailable_embedding_providers,
             default_chunker: ChunkConfig::snapping_default(),
             document_providers,
-            supported_document_types: vec![
-                DocumentType::Text(TextDocumentType::Md).to_string(),
-                DocumentType::Text(TextDocumentType::Csv).to_string(),
-                DocumentType::Text(TextDocumentType::Xml).to_string(),
-                DocumentType::Text(TextDocumentType::Json).to_string(),
-                DocumentType::Text(TextDocumentType::Txt).to_string(),
-                DocumentType::Docx.to_string(),
-                DocumentType::Pdf.to_string(),
-                DocumentType::Excel.to_string(),
-            ],
+            supported_document_types: self
+                .services
+                .document
+                .allowed_types()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
         })
     }
 }
 
+/// Periodically delete embedding reports outside of the `retention` window.
+fn start_report_cleanup(service: EmbeddingService, retention: ReportRetention) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REPORT_CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = service.cleanup_reports(retention).await {
+                tracing::error!("Error cleaning up embedding reports: {e}");
+            }
+        }
+    });
+}
+
+/// Periodically sync the documents of the `source` storage with the repository.
+///
+/// Ticks occurring while a sync of the storage is still in progress are skipped, whether
+/// the sync was started by the schedule or manually.
+pub(super) fn start_document_sync(
+    service: DocumentService,
+    source: String,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            match service.sync(&source).await {
+                Ok(()) => {}
+                Err(e) if matches!(e.error, ChonkitErr::AlreadyExists(_)) => {
+                    tracing::info!("Sync of '{source}' still in progress, skipping");
+                }
+                Err(e) => tracing::error!("Error syncing documents with '{source}': {e}"),
+            }
+        }
+    })
+}
+
+/// Persist the embedding requests sent to the returned channel, see [AuditedEmbedder].
+fn start_embedding_request_audit(repository: Repository) -> UnboundedSender<EmbeddingRequest> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<EmbeddingRequest>();
+    tokio::spawn(async move {
+        while let Some(request) = rx.recv().await {
+            if let Err(e) = repository.insert_embedding_request(&request).await {
+                tracing::error!("Error persisting embedding request: {e}");
+            }
+        }
+    });
+    tx
+}
+
 /// Concrete version of [ProviderState].
 #[derive(Clone)]
 pub struct AppProviderState {
@@ -321,12 +593,16 @@ pub struct AppConfig {
     /// A map of available embedding providers, their models and their respective model sizes.
     pub embedding_providers: HashMap<String, Vec<EmbeddingModel>>,
 
+    /// A map of embedding providers whose models could not be listed to the error
+    /// encountered. These are not included in `embedding_providers`.
+    pub unavailable_embedding_providers: HashMap<String, String>,
+
     /// A list of available document storage providers.
     pub document_providers: Vec<String>,
 
     /// A list of default chunking configurations.
     pub default_chunker: ChunkConfig,
 
-    /// A list of extensions supported by chonkit.
+    /// A list of extensions accepted for upload and import.
     pub supported_document_types: Vec<String>,
 }
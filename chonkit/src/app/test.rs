@@ -9,7 +9,8 @@ use super::{
 };
 use crate::core::provider::Identity;
 use crate::core::{
-    cache::{init, ImageEmbeddingCache, TextEmbeddingCache},
+    cache::{init, IdempotencyCache, ImageEmbeddingCache, TextEmbeddingCache},
+    embeddings::{Embedder, Embeddings},
     provider::{DocumentStorageProvider, EmbeddingProvider, VectorDbProvider},
     repo::Repository,
     service::{
@@ -18,10 +19,14 @@ use crate::core::{
     },
     token::Tokenizer,
 };
+use crate::{err, error::ChonkitError};
 use chonkit_embedders::EmbeddingModel;
 use std::{
     collections::HashMap,
-    sync::{Arc, OnceLock},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
 };
 use testcontainers::{runners::AsyncRunner, ContainerAsync, GenericImage};
 use testcontainers_modules::{postgres::Postgres, redis::Redis};
@@ -57,7 +62,8 @@ impl TestState {
         // Set up test containers
 
         let (postgres, postgres_img) = init_repository().await;
-        let (embedding_cache, image_embedding_cache, redis_img) = init_cache().await;
+        let (embedding_cache, image_embedding_cache, idempotency_cache, redis_img) =
+            init_cache().await;
         let (minio, minio_img) = init_minio(postgres.clone()).await;
 
         #[cfg(feature = "qdrant")]
@@ -180,14 +186,24 @@ impl TestState {
         };
 
         let services = ServiceState {
-            collection: CollectionService::new(postgres.clone(), providers.clone().into()),
-            document: DocumentService::new(postgres.clone(), providers.clone().into(), tokenizer),
+            collection: CollectionService::new(
+                postgres.clone(),
+                providers.clone().into(),
+                tokenizer.clone(),
+            ),
+            document: DocumentService::new(
+                postgres.clone(),
+                providers.clone().into(),
+                tokenizer.clone(),
+            )
+            .with_idempotency(idempotency_cache),
             external: ServiceFactory::new(postgres.clone(), providers.clone().into()),
             embedding: EmbeddingService::new(
                 postgres,
                 providers.clone().into(),
                 embedding_cache.clone(),
                 image_embedding_cache.clone(),
+                tokenizer,
             ),
         };
 
@@ -204,6 +220,17 @@ impl TestState {
             image_embedding_cache,
         }
     }
+
+    /// Serve the API routes on a random local port and return the base URL.
+    pub async fn serve_routes(&self) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes = super::server::router::routes(self.app.clone());
+
+        tokio::spawn(async move { axum::serve(listener, routes).await });
+
+        format!("http://{addr}")
+    }
 }
 
 struct TestStateConfig {
@@ -227,6 +254,186 @@ struct TestContainers {
     pub _weaviate: ContainerAsync<GenericImage>,
 }
 
+/// A configurable embedder for tests.
+///
+/// Embeds every text with a vector of `size`, or with the embedder it wraps, and records the
+/// texts it embeds. Can be made to fail every request as if the provider was down, or to fail
+/// once on a marker to simulate transient errors.
+pub struct TestEmbedder {
+    id: &'static str,
+
+    /// Embeds instead of the test embedder if given, see [TestEmbedder::wrap].
+    inner: Option<Arc<dyn Embedder + Send + Sync>>,
+
+    /// The size of the vectors of every model.
+    size: usize,
+
+    /// Models returned when listing. Unlisted models can be embedded with all the same.
+    listed: Vec<&'static str>,
+
+    /// Embedding with these models fails as if they did not exist.
+    rejected: Vec<&'static str>,
+
+    /// Every request times out.
+    unavailable: bool,
+
+    /// Once armed, the next text embedding request containing the marker times out.
+    flaky: Option<&'static str>,
+
+    armed: AtomicBool,
+
+    /// The amount of text embedding requests.
+    calls: AtomicUsize,
+
+    /// The texts embedded so far.
+    inputs: Mutex<Vec<String>>,
+}
+
+impl TestEmbedder {
+    pub fn new(id: &'static str, size: usize) -> Self {
+        Self {
+            id,
+            inner: None,
+            size,
+            listed: vec![],
+            rejected: vec![],
+            unavailable: false,
+            flaky: None,
+            armed: AtomicBool::new(false),
+            calls: AtomicUsize::new(0),
+            inputs: Mutex::new(vec![]),
+        }
+    }
+
+    /// Delegate to `inner` under its ID, keeping the failures and recording of the test embedder.
+    pub fn wrap(inner: Arc<dyn Embedder + Send + Sync>) -> Self {
+        Self {
+            inner: Some(inner.clone()),
+            ..Self::new(inner.id(), 0)
+        }
+    }
+
+    pub fn with_listed_model(mut self, model: &'static str) -> Self {
+        self.listed.push(model);
+        self
+    }
+
+    pub fn with_rejected_model(mut self, model: &'static str) -> Self {
+        self.rejected.push(model);
+        self
+    }
+
+    pub fn unavailable(mut self) -> Self {
+        self.unavailable = true;
+        self
+    }
+
+    pub fn with_flaky_marker(mut self, marker: &'static str) -> Self {
+        self.flaky = Some(marker);
+        self
+    }
+
+    /// Fail the next embedding of the flaky marker.
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the embedder failed on the flaky marker since it was last armed.
+    pub fn fired(&self) -> bool {
+        !self.armed.load(Ordering::SeqCst)
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Return the recorded inputs and clear them.
+    pub fn take_inputs(&self) -> Vec<String> {
+        std::mem::take(&mut *self.inputs.lock().unwrap())
+    }
+}
+
+impl Identity for TestEmbedder {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+}
+
+#[async_trait::async_trait]
+impl Embedder for TestEmbedder {
+    async fn embed_text(&self, content: &[&str], model: &str) -> Result<Embeddings, ChonkitError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        if self.unavailable {
+            return err!(Timeout, "{} did not respond", self.id());
+        }
+
+        if self.rejected.contains(&model) {
+            return err!(InvalidEmbeddingModel, "{model}");
+        }
+
+        if let Some(marker) = self.flaky {
+            if content.iter().any(|c| c.contains(marker))
+                && self.armed.swap(false, Ordering::SeqCst)
+            {
+                return err!(Timeout, "{} did not respond", self.id());
+            }
+        }
+
+        self.inputs
+            .lock()
+            .unwrap()
+            .extend(content.iter().map(|c| c.to_string()));
+
+        match &self.inner {
+            Some(inner) => inner.embed_text(content, model).await,
+            None => Ok(Embeddings::new(
+                vec![vec![0.5; self.size]; content.len()],
+                None,
+            )),
+        }
+    }
+
+    async fn embed_image(
+        &self,
+        system: Option<&str>,
+        text: Option<&str>,
+        image: &str,
+        model: &str,
+    ) -> Result<Embeddings, ChonkitError> {
+        if self.unavailable {
+            return err!(Timeout, "{} did not respond", self.id());
+        }
+
+        match &self.inner {
+            Some(inner) => inner.embed_image(system, text, image, model).await,
+            None => err!(OperationUnsupported, "{} does not embed images", self.id()),
+        }
+    }
+
+    async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+        if self.unavailable {
+            return err!(Timeout, "{} did not respond", self.id());
+        }
+
+        if let Some(inner) = &self.inner {
+            return inner.list_embedding_models().await;
+        }
+
+        Ok(self
+            .listed
+            .iter()
+            .map(|model| EmbeddingModel {
+                name: model.to_string(),
+                size: self.size,
+                provider: self.id.to_string(),
+                multimodal: false,
+                max_input_tokens: 8192,
+            })
+            .collect())
+    }
+}
+
 impl AppState {
     #[cfg(test)]
     pub fn new_test(services: ServiceState, providers: AppProviderState) -> Self {
@@ -248,6 +455,8 @@ impl AppState {
                 ),
                 "",
             ),
+            #[cfg(feature = "gdrive")]
+            google_drive_download: Default::default(),
         }
     }
 }
@@ -271,7 +480,12 @@ pub async fn init_repository() -> (Repository, PostgresContainer) {
 /// Setup a redis test container and connect to it using RedisPool.
 /// When using suitest's [before_all][suitest::before_all], make sure you keep the TestState, othwerise the
 /// container will get dropped and cleaned up.
-pub async fn init_cache() -> (TextEmbeddingCache, ImageEmbeddingCache, RedisContainer) {
+pub async fn init_cache() -> (
+    TextEmbeddingCache,
+    ImageEmbeddingCache,
+    IdempotencyCache,
+    RedisContainer,
+) {
     let redis_image = Redis.start().await.unwrap();
     let redis_host = redis_image.get_host().await.unwrap();
     let redis_port = redis_image.get_host_port_ipv4(6379).await.unwrap();
@@ -279,8 +493,9 @@ pub async fn init_cache() -> (TextEmbeddingCache, ImageEmbeddingCache, RedisCont
 
     let embedding_cache = TextEmbeddingCache::new(init(&redis_url, "0").await);
     let image_cache = ImageEmbeddingCache::new(init(&redis_url, "1").await);
+    let idempotency_cache = IdempotencyCache::new(init(&redis_url, "2").await, 60);
 
-    (embedding_cache, image_cache, redis_image)
+    (embedding_cache, image_cache, idempotency_cache, redis_image)
 }
 
 pub async fn init_minio(
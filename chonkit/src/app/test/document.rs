@@ -3,17 +3,53 @@
 #[suitest::suite_cfg(sequential = true)]
 mod document_service_integration_tests {
     use crate::{
-        app::test::{TestState, TestStateConfig, DEFAULT_MODELS},
+        app::{
+            document::store::FsDocumentStore,
+            state::start_document_sync,
+            test::{init_repository, TestState, TestStateConfig, DEFAULT_MODELS},
+        },
+        config::{DEFAULT_BODY_LIMIT, DEFAULT_DOCUMENT_NAME, FS_STORE_ID, MAX_INLINE_PREVIEW_SIZE},
         core::{
+            chunk::ChunkConfig,
             document::{
-                parser::{parse_text, ParseConfig, StringParseConfig},
-                DocumentType, TextDocumentType,
+                parser::{
+                    parse_text, parse_text_from_reader, ParseConfig, ParseOutput, StringParseConfig,
+                },
+                store::{
+                    external::ExternalDocumentStorage, DocumentFile, DocumentStorage, ExternalPath,
+                    LocalPath,
+                },
+                DocumentHashing, DocumentType, ImageExtraction, ImageLimitPolicy, TextDocumentType,
             },
+            image::ImageStorage,
+            model::{image::Image, Pagination, PaginationSort},
+            provider::{Identity, ProviderState},
+            repo::Repository,
             service::{
-                collection::dto::CreateCollectionPayload, document::dto::DocumentUpload,
+                collection::dto::{CollectionModality, CreateCollectionPayload},
+                document::{
+                    dto::{
+                        BulkConfigurePayload, ChunkDiffPayload, DocumentUpload,
+                        HashVerificationEvent, InlinePreviewPayload, VerifyHashesPayload,
+                    },
+                    DocumentService,
+                },
                 embedding::EmbedTextInput,
+                external::ServiceFactory,
             },
+            token::{TokenCount, Tokenizer},
+        },
+        err,
+        error::{ChonkitErr, ChonkitError},
+    };
+    use futures_util::StreamExt;
+    use std::{
+        io::{Read, Seek, SeekFrom},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
         },
+        time::Duration,
     };
 
     const TEST_UPLOAD_PATH: &str = "__document_service_test_upload__";
@@ -94,6 +130,312 @@ mod document_service_integration_tests {
         assert!(tokio::fs::metadata(document.path).await.is_err());
     }
 
+    #[test]
+    async fn upload_rejects_documents_without_content(state: TestState) {
+        let service = state.app.services.document.clone();
+
+        let txt = DocumentType::Text(TextDocumentType::Txt);
+
+        let blank: [(&str, DocumentType, &[u8]); 3] = [
+            ("UPLOAD_TEST_EMPTY", txt, b""),
+            ("UPLOAD_TEST_WHITESPACE", txt, b" \n\t\r\n  "),
+            ("UPLOAD_TEST_EMPTY_PDF", DocumentType::Pdf, b""),
+        ];
+
+        for (name, ty, file) in blank {
+            let err = service
+                .upload(DocumentUpload::new(name.to_string(), ty, file))
+                .await
+                .unwrap_err();
+
+            assert!(matches!(err.error, ChonkitErr::InvalidFile(_)));
+            assert!(err.to_string().contains("no embeddable content"));
+        }
+    }
+
+    #[test]
+    async fn upload_markdown_stores_front_matter(state: TestState) {
+        let service = state.app.services.document.clone();
+
+        let content = b"---\ntitle: Raguru Labamba\nauthor: Chef\n---\n# Ragu\n\nSlow cooked.";
+        let upload = DocumentUpload {
+            name: "UPLOAD_TEST_FRONT_MATTER".to_string(),
+            ty: DocumentType::Markdown,
+            file: content,
+        };
+
+        let document = service.upload(upload).await.unwrap();
+
+        let stored = state
+            .app
+            .providers
+            .database
+            .get_document_by_id(document.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let metadata = stored.metadata.unwrap();
+        assert_eq!("Raguru Labamba", metadata["title"]);
+        assert_eq!("Chef", metadata["author"]);
+
+        service.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn upload_rejects_unsupported_types_early(state: TestState) {
+        let url = state.serve_routes().await;
+
+        let boundary = "RAGURU_BOUNDARY";
+        let body = format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"archive.zip\"\r\n\
+             Content-Type: application/zip\r\n\r\n\
+             PK\r\n\
+             --{boundary}--\r\n"
+        );
+
+        let response = reqwest::Client::new()
+            .post(format!("{url}/documents"))
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let result: serde_json::Value = response.json().await.unwrap();
+
+        assert!(result["documents"].as_array().unwrap().is_empty());
+        assert_eq!(
+            "Unsupported file type; zip",
+            result["errors"]["archive.zip"][0]
+        );
+
+        let mut entries = tokio::fs::read_dir(TEST_UPLOAD_PATH).await.unwrap();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            assert!(!entry.file_name().to_string_lossy().contains("archive"));
+        }
+    }
+
+    #[test]
+    async fn lists_document_type_capabilities(state: TestState) {
+        let url = state.serve_routes().await;
+
+        let response = reqwest::get(format!("{url}/document-types")).await.unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let types: Vec<serde_json::Value> = response.json().await.unwrap();
+        let capabilities = |ext: &str| {
+            types
+                .iter()
+                .find(|ty| ty["ext"] == ext)
+                .unwrap_or_else(|| panic!("missing document type {ext}"))
+                .clone()
+        };
+
+        let pdf = capabilities("pdf");
+        assert_eq!(true, pdf["sectioned"]);
+        assert_eq!(true, pdf["tables"]);
+        assert_eq!(true, pdf["images"]);
+
+        let txt = capabilities("txt");
+        assert_eq!(false, txt["sectioned"]);
+        assert_eq!(false, txt["ocr"]);
+        assert_eq!(false, txt["tables"]);
+        assert_eq!(false, txt["images"]);
+    }
+
+    #[test]
+    async fn request_bodies_are_limited_per_route(state: TestState) {
+        let url = state.serve_routes().await;
+        let client = reqwest::Client::new();
+
+        // Larger than the default limit of all but the upload routes
+        let size = DEFAULT_BODY_LIMIT + 1;
+
+        let search = serde_json::json!({
+            "query": "ragu ".repeat(size / 5),
+            "collectionId": uuid::Uuid::new_v4(),
+        });
+
+        let response = client
+            .post(format!("{url}/search"))
+            .json(&search)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::PAYLOAD_TOO_LARGE, response.status());
+
+        let boundary = "RAGURU_BOUNDARY";
+        let body = format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"UPLOAD_TEST_LARGE.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             {}\r\n\
+             --{boundary}--\r\n",
+            "Raguru stirs the ragu. ".repeat(size / 23 + 1)
+        );
+
+        let response = client
+            .post(format!("{url}/documents"))
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let result: serde_json::Value = response.json().await.unwrap();
+        let documents = result["documents"].as_array().unwrap();
+        assert_eq!(1, documents.len());
+
+        let id = documents[0]["id"].as_str().unwrap().parse().unwrap();
+        state.app.services.document.delete(id).await.unwrap();
+    }
+
+    #[test]
+    async fn uploads_documents_from_archive(state: TestState) {
+        use std::io::Write;
+        use zip::{write::SimpleFileOptions, ZipWriter};
+
+        let url = state.serve_routes().await;
+
+        let mut zip = ZipWriter::new(std::io::Cursor::new(vec![]));
+        for (name, content) in [
+            ("ARCHIVE_TEST_RAGURU.txt", "Raguru stirs the ragu."),
+            ("gura/ARCHIVE_TEST_GURA.md", "The twin moons of Gura."),
+            ("ARCHIVE_TEST_RAGU.exe", "Not a ragu."),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        let archive = zip.finish().unwrap().into_inner();
+
+        let response = reqwest::Client::new()
+            .post(format!("{url}/documents/from-archive"))
+            .body(archive)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let body = response.text().await.unwrap();
+        let events: Vec<serde_json::Value> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|data| serde_json::from_str(data.trim()).unwrap())
+            .collect();
+
+        let of_type = |ty: &str| {
+            events
+                .iter()
+                .filter(|event| event["type"] == ty)
+                .collect::<Vec<_>>()
+        };
+
+        let uploaded = of_type("uploaded");
+        let skipped = of_type("skipped");
+
+        assert_eq!(2, uploaded.len());
+        assert_eq!(1, skipped.len());
+        assert_eq!("ARCHIVE_TEST_RAGU.exe", skipped[0]["path"]);
+
+        let done = of_type("done");
+        assert_eq!(1, done.len());
+        assert_eq!(2, done[0]["uploaded"]);
+        assert_eq!(1, done[0]["skipped"]);
+        assert_eq!(0, done[0]["failed"]);
+
+        for event in uploaded {
+            assert_ne!("ARCHIVE_TEST_RAGU.exe", event["path"]);
+            let id = event["document"]["id"].as_str().unwrap().parse().unwrap();
+            state.app.services.document.delete(id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn default_document_is_created_once_across_instances(state: TestState) {
+        // A database of its own, since the shared one already has documents
+        let (repo, _postgres) = init_repository().await;
+
+        // Every instance connects with a pool of its own
+        let options = repo.client.connect_options().as_ref().clone();
+        let other = Repository {
+            client: sqlx::PgPool::connect_with(options).await.unwrap(),
+        };
+
+        let instance = |repo: Repository| {
+            DocumentService::new(repo, state.app.providers.clone().into(), Tokenizer::new())
+        };
+
+        let first = instance(repo.clone());
+        let second = instance(other);
+
+        let (first_result, second_result) = tokio::join!(
+            first.create_default_document(),
+            second.create_default_document()
+        );
+
+        first_result.unwrap();
+        second_result.unwrap();
+
+        let documents = first
+            .list_documents(PaginationSort::default(), None, None)
+            .await
+            .unwrap()
+            .items;
+
+        assert_eq!(1, documents.len());
+        assert_eq!(DEFAULT_DOCUMENT_NAME, documents[0].name);
+
+        first.delete(documents[0].id).await.unwrap();
+    }
+
+    #[test]
+    async fn upload_rejects_disallowed_types(state: TestState) {
+        let service = DocumentService::new(
+            state.app.providers.database.clone(),
+            state.app.providers.clone().into(),
+            Tokenizer::new(),
+        )
+        .with_allowed_types(vec![DocumentType::Text(TextDocumentType::Txt)]);
+
+        let content = b"Raguru only accepts plain text";
+        let upload = DocumentUpload {
+            name: "UPLOAD_TEST_DISALLOWED".to_string(),
+            ty: DocumentType::Markdown,
+            file: content,
+        };
+
+        let error = service.upload(upload).await.unwrap_err();
+
+        let ChonkitErr::UnsupportedFileType(message) = error.error else {
+            panic!("expected UnsupportedFileType, got {:?}", error.error);
+        };
+        assert_eq!("md - not one of the allowed document types (txt)", message);
+
+        let stored = state
+            .app
+            .providers
+            .database
+            .get_document_by_hash(&DocumentHashing::default().algorithm.hash(content))
+            .await
+            .unwrap();
+        assert!(stored.is_none());
+    }
+
     #[test]
     async fn upload_pdf_happy(state: TestState) {
         let service = state.app.services.document.clone();
@@ -140,6 +482,221 @@ mod document_service_integration_tests {
         assert!(tokio::fs::metadata(document.path).await.is_err());
     }
 
+    #[test]
+    async fn pdf_is_parsed_from_reader_without_buffering() {
+        let path = format!("{TEST_DOCS_PATH}/test.pdf");
+        let content = tokio::fs::read(&path).await.unwrap();
+
+        let mut reader = RunTrackingReader::new(std::fs::File::open(&path).unwrap());
+
+        let text_from_reader =
+            parse_text_from_reader(ParseConfig::default(), DocumentType::Pdf, &mut reader).unwrap();
+
+        let text_from_bytes =
+            parse_text(ParseConfig::default(), DocumentType::Pdf, &content).unwrap();
+
+        assert_eq!(text_from_bytes, text_from_reader);
+
+        // Buffering the document would read it whole in a single run
+        assert!(reader.longest_run > 0);
+        assert!(reader.longest_run < content.len());
+    }
+
+    #[test]
+    async fn pdf_tables_are_extracted_as_markdown() {
+        let content = tokio::fs::read(format!("{TEST_DOCS_PATH}/table.pdf"))
+            .await
+            .unwrap();
+
+        let ParseOutput::String(plain) =
+            parse_text(ParseConfig::default(), DocumentType::Pdf, &content).unwrap()
+        else {
+            panic!("unexpected parse output");
+        };
+
+        assert!(!plain.contains('|'));
+
+        let config = ParseConfig::String(StringParseConfig::default().extract_tables());
+        let ParseOutput::String(tables) = parse_text(config, DocumentType::Pdf, &content).unwrap()
+        else {
+            panic!("unexpected parse output");
+        };
+
+        let expected = "\
+Ragu ingredients
+
+| Ingredient | Amount | Price |
+| --- | --- | --- |
+| Tomatoes | 800 g | 3.20 |
+| Minced beef | 500 g | 6.50 |
+| Onion | 1 | 0.40 |
+
+Serve with tagliatelle.
+";
+
+        assert_eq!(expected, tables);
+    }
+
+    /// Keeps track of the most bytes read without seeking in between.
+    struct RunTrackingReader<R> {
+        inner: R,
+        run: usize,
+        longest_run: usize,
+    }
+
+    impl<R> RunTrackingReader<R> {
+        fn new(inner: R) -> Self {
+            Self {
+                inner,
+                run: 0,
+                longest_run: 0,
+            }
+        }
+    }
+
+    impl<R: Read> Read for RunTrackingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.run += read;
+            self.longest_run = self.longest_run.max(self.run);
+            Ok(read)
+        }
+    }
+
+    impl<R: Seek> Seek for RunTrackingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.run = 0;
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    async fn list_documents_route_includes_pagination(state: TestState) {
+        let url = state.serve_routes().await;
+        let service = state.app.services.document.clone();
+
+        let mut documents = vec![];
+        for i in 1..=3 {
+            let content = format!("Raguru cooks ragu number {i}.");
+            let upload = DocumentUpload::new(
+                format!("ENVELOPE_TEST_{i}"),
+                DocumentType::Text(TextDocumentType::Txt),
+                content.as_bytes(),
+            );
+            documents.push(service.upload(upload).await.unwrap());
+        }
+
+        let list = |page: &'static str| {
+            reqwest::Client::new()
+                .get(format!("{url}/documents"))
+                .query(&[
+                    ("perPage", "2"),
+                    ("page", page),
+                    ("column", "name"),
+                    ("q", "ENVELOPE"),
+                ])
+                .send()
+        };
+
+        let response = list("2").await.unwrap();
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let list_page: serde_json::Value = response.json().await.unwrap();
+
+        assert_eq!(3, list_page["total"]);
+        assert_eq!(1, list_page["items"].as_array().unwrap().len());
+        assert_eq!(2, list_page["page"]);
+        assert_eq!(2, list_page["perPage"]);
+        assert_eq!(Some(false), list_page["hasNext"].as_bool());
+
+        let list_page: serde_json::Value = list("1").await.unwrap().json().await.unwrap();
+
+        assert_eq!(2, list_page["items"].as_array().unwrap().len());
+        assert_eq!(1, list_page["page"]);
+        assert_eq!(Some(true), list_page["hasNext"].as_bool());
+
+        for document in documents {
+            service.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn parsed_document_route_returns_paginated_sections(state: TestState) {
+        let url = state.serve_routes().await;
+        let service = state.app.services.document.clone();
+
+        let content = &tokio::fs::read(format!("{TEST_DOCS_PATH}/test.pdf"))
+            .await
+            .unwrap();
+        let upload = DocumentUpload {
+            name: "PARSED_TEST_PDF".to_string(),
+            ty: DocumentType::Pdf,
+            file: content,
+        };
+
+        let document = service.upload(upload).await.unwrap();
+
+        let config = serde_json::json!({
+            "section": {
+                "sections": [
+                    { "start": 1, "end": 1 },
+                    { "start": 2, "end": 3 }
+                ],
+                "filters": []
+            }
+        });
+
+        let response = reqwest::Client::new()
+            .get(format!("{url}/documents/{}/parsed", document.id))
+            .query(&[
+                ("config", config.to_string().as_str()),
+                ("perPage", "1"),
+                ("page", "2"),
+            ])
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let parsed: serde_json::Value = response.json().await.unwrap();
+
+        assert_eq!(2, parsed["totalSections"]);
+
+        let sections = parsed["content"]["sections"].as_array().unwrap();
+        assert_eq!(1, sections.len());
+
+        let pages = sections[0]["pages"].as_array().unwrap();
+        assert_eq!(2, pages.len());
+        assert_eq!(2, pages[0]["number"]);
+        assert_eq!(3, pages[1]["number"]);
+        assert!(!pages[0]["content"].as_str().unwrap().is_empty());
+
+        // Without a configuration the stored one is used, which does not section the document
+        let response = reqwest::Client::new()
+            .get(format!("{url}/documents/{}/parsed", document.id))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let parsed: serde_json::Value = response.json().await.unwrap();
+        assert!(parsed["content"]["string"].is_string());
+        assert!(parsed.get("totalSections").is_none());
+
+        let invalid = reqwest::Client::new()
+            .get(format!("{url}/documents/{}/parsed", document.id))
+            .query(&[("config", "{")])
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::BAD_REQUEST, invalid.status());
+
+        service.delete(document.id).await.unwrap();
+    }
+
     #[test]
     async fn upload_docx_happy(state: TestState) {
         let service = state.app.services.document.clone();
@@ -276,6 +833,10 @@ mod document_service_integration_tests {
                     vector_provider: vector_db.id().to_string(),
                     embedding_provider: embedder.id().to_string(),
                     groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
                 };
 
                 let collection_2 = CreateCollectionPayload {
@@ -290,6 +851,10 @@ mod document_service_integration_tests {
                     vector_provider: vector_db.id().to_string(),
                     embedding_provider: embedder.id().to_string(),
                     groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
                 };
 
                 let collection_1 = state
@@ -310,12 +875,20 @@ mod document_service_integration_tests {
 
                 let embeddings_1 = EmbedTextInput {
                     document: document.id,
-                    collection: collection_1.id,
+                    collection: Some(collection_1.id),
+                    collection_name: None,
+                    detailed: false,
+                    embed_metadata: false,
+                    no_cache: false,
                 };
 
                 let embeddings_2 = EmbedTextInput {
                     document: document.id,
-                    collection: collection_2.id,
+                    collection: Some(collection_2.id),
+                    collection_name: None,
+                    detailed: false,
+                    embed_metadata: false,
+                    no_cache: false,
                 };
 
                 let report_1 = state
@@ -409,4 +982,996 @@ mod document_service_integration_tests {
             }
         }
     }
+
+    #[test]
+    async fn upload_survives_unavailable_image_store(state: TestState) {
+        let content = &tokio::fs::read(format!("{TEST_DOCS_PATH}/test.pdf"))
+            .await
+            .unwrap();
+
+        let providers = ProviderState {
+            image: Arc::new(UnavailableImageStore),
+            ..state.app.providers.clone().into()
+        };
+
+        let service = DocumentService::new(
+            state.app.providers.database.clone(),
+            providers,
+            Tokenizer::new(),
+        );
+
+        let upload = DocumentUpload {
+            name: "UPLOAD_TEST_IMAGES_PENDING".to_string(),
+            ty: DocumentType::Pdf,
+            file: content,
+        };
+
+        let document = service.upload(upload).await.unwrap();
+
+        // Images are processed in the background
+        let mut pending = false;
+        for _ in 0..50 {
+            pending = service
+                .get_document(document.id)
+                .await
+                .unwrap()
+                .images_pending;
+            if pending {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(pending);
+
+        let result = service.process_document_images(document.id).await;
+        assert!(matches!(
+            result.unwrap_err().error,
+            ChonkitErr::ImagesPending(_)
+        ));
+
+        for vector in state.active_vector_providers.iter() {
+            for embedder in state.active_embedding_providers.iter() {
+                let collection = CreateCollectionPayload {
+                    name: "ImagesPendingTestCollection".to_string(),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder)
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector.to_string(),
+                    embedding_provider: embedder.to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                };
+
+                let collection = state
+                    .app
+                    .services
+                    .collection
+                    .create_collection(collection)
+                    .await
+                    .unwrap();
+
+                state
+                    .app
+                    .services
+                    .embedding
+                    .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                    .await
+                    .unwrap();
+
+                let count = state
+                    .app
+                    .services
+                    .embedding
+                    .count_embeddings(collection.id, document.id)
+                    .await
+                    .unwrap();
+
+                assert!(count >= 1);
+
+                state
+                    .app
+                    .services
+                    .collection
+                    .delete_collection(collection.id)
+                    .await
+                    .unwrap();
+
+                state.embedding_cache.clear().await.unwrap();
+            }
+        }
+
+        // Retrying with an available image store clears the flag
+        state
+            .app
+            .services
+            .document
+            .process_document_images(document.id)
+            .await
+            .unwrap();
+
+        let document = service.get_document(document.id).await.unwrap();
+        assert!(!document.images_pending);
+
+        state
+            .app
+            .services
+            .document
+            .delete(document.id)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    async fn upload_stores_only_large_images_up_to_limit(state: TestState) {
+        use std::io::Write;
+        use zip::{write::SimpleFileOptions, ZipWriter};
+
+        let service = DocumentService::new(
+            state.app.providers.database.clone(),
+            state.app.providers.clone().into(),
+            Tokenizer::new(),
+        )
+        .with_image_extraction(ImageExtraction {
+            max_per_document: Some(2),
+            limit_policy: ImageLimitPolicy::Truncate,
+            min_size: 16,
+        });
+
+        // Every third image is large, the rest are icons
+        let sizes = [64, 8, 8, 64, 8, 8, 64, 8, 8];
+
+        let mut drawings = String::new();
+        let mut relationships = String::new();
+        let mut media = vec![];
+
+        for (i, size) in sizes.into_iter().enumerate() {
+            // Distinct pixels so every image has a distinct hash
+            let pixel = image::Rgba([i as u8, 0, 0, 255]);
+            let mut png = std::io::Cursor::new(vec![]);
+            image::RgbaImage::from_pixel(size, size, pixel)
+                .write_to(&mut png, image::ImageFormat::Png)
+                .unwrap();
+
+            drawings.push_str(&format!(
+                r#"<w:p><w:r><w:drawing><wp:inline>
+                    <wp:docPr id="{i}" name="Picture {i}"/>
+                    <a:graphic><a:graphicData><pic:pic><pic:blipFill>
+                        <a:blip r:embed="rId{i}"/>
+                    </pic:blipFill></pic:pic></a:graphicData></a:graphic>
+                </wp:inline></w:drawing></w:r></w:p>"#
+            ));
+            relationships.push_str(&format!(
+                r#"<Relationship Id="rId{i}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image{i}.png"/>"#
+            ));
+            media.push((format!("word/media/image{i}.png"), png.into_inner()));
+        }
+
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+            <w:document
+                xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+                xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
+                xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
+                xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"
+                xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+                <w:body>
+                    <w:p><w:r><w:t>Raguru's illustrated cookbook</w:t></w:r></w:p>
+                    {drawings}
+                </w:body>
+            </w:document>"#
+        );
+        let relationships = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+            <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+                {relationships}
+            </Relationships>"#
+        );
+
+        let mut archive = ZipWriter::new(std::io::Cursor::new(vec![]));
+        for (name, content) in [
+            ("word/document.xml".to_string(), document.into_bytes()),
+            (
+                "word/_rels/document.xml.rels".to_string(),
+                relationships.into_bytes(),
+            ),
+        ]
+        .into_iter()
+        .chain(media)
+        {
+            archive
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            archive.write_all(&content).unwrap();
+        }
+        let content = archive.finish().unwrap().into_inner();
+
+        let upload = DocumentUpload {
+            name: "UPLOAD_TEST_IMAGE_LIMITS".to_string(),
+            ty: DocumentType::Docx,
+            file: &content,
+        };
+
+        let document = service.upload(upload).await.unwrap();
+
+        let repo = &state.app.providers.database;
+        let (document_id, image_store) = (document.id, state.app.providers.image.id());
+        let list_images = || async move {
+            repo.list_all_document_images(document_id, image_store)
+                .await
+                .unwrap()
+        };
+
+        // Images are processed in the background
+        for _ in 0..50 {
+            if list_images().await.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        // Reprocessing counts the stored images towards the limit
+        service.process_document_images(document.id).await.unwrap();
+
+        let mut images = list_images().await;
+        images.sort_by_key(|image| image.image_number);
+
+        assert_eq!(
+            vec![Some(0), Some(3)],
+            images
+                .iter()
+                .map(|image| image.image_number)
+                .collect::<Vec<_>>()
+        );
+        for image in images {
+            assert_eq!((64, 64), (image.width, image.height));
+        }
+
+        service.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn upload_with_idempotency_key_creates_single_document(state: TestState) {
+        let service = state.app.services.document.clone();
+
+        let content = b"Raguru only cooks his ragu once.";
+        let upload = || DocumentUpload {
+            name: "UPLOAD_TEST_IDEMPOTENT_TXT".to_string(),
+            ty: DocumentType::Text(TextDocumentType::Txt),
+            file: content,
+        };
+
+        let first = service
+            .upload_idempotent("ragu-key", upload())
+            .await
+            .unwrap();
+
+        let second = service
+            .upload_idempotent("ragu-key", upload())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(&second).unwrap()
+        );
+
+        let documents = service
+            .list_documents(PaginationSort::default(), None, None)
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .filter(|document| document.name == "UPLOAD_TEST_IDEMPOTENT_TXT")
+            .count();
+
+        assert_eq!(1, documents);
+
+        // Without the key the upload is a regular one and collides with the existing document
+        let result = service.upload(upload()).await;
+        assert!(matches!(
+            result.unwrap_err().error,
+            ChonkitErr::AlreadyExists(_)
+        ));
+
+        service.delete(first.id).await.unwrap();
+    }
+
+    #[test]
+    async fn list_documents_clamps_page_size(state: TestState) {
+        let service = state.app.services.document.clone().with_max_page_size(2);
+
+        let mut documents = vec![];
+        for i in 0..3 {
+            let document = service
+                .upload(DocumentUpload {
+                    name: format!("LIST_TEST_PAGE_SIZE_{i}"),
+                    ty: DocumentType::Text(TextDocumentType::Txt),
+                    file: format!("Raguru serves ragu number {i}.").as_bytes(),
+                })
+                .await
+                .unwrap();
+            documents.push(document);
+        }
+
+        let list = service
+            .list_documents(
+                PaginationSort::new_default_sort(Pagination::new(1_000_000, 1)),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(2, list.items.len());
+        assert!(list.total.unwrap() >= 3);
+
+        // Listings without pagination are clamped as well
+        let list = service
+            .list_documents(
+                PaginationSort {
+                    pagination: None,
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(2, list.items.len());
+
+        for document in documents {
+            service.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn ready_documents_require_embeddings_when_configured(state: TestState) {
+        let service = state.app.services.document.clone();
+
+        let document = service
+            .upload(DocumentUpload {
+                name: "LIST_TEST_READY_EMBEDDINGS".to_string(),
+                ty: DocumentType::Text(TextDocumentType::Txt),
+                file: b"Raguru waits for his ragu to be embedded.",
+            })
+            .await
+            .unwrap();
+
+        service
+            .configure_many(BulkConfigurePayload {
+                ids: vec![document.id],
+                parser: ParseConfig::default(),
+                chunker: ChunkConfig::snapping_default(),
+            })
+            .await
+            .unwrap();
+
+        let id = document.id;
+        let is_ready = |service: DocumentService| async move {
+            service
+                .list_documents(
+                    PaginationSort::new_default_sort(Pagination::new(10, 1)),
+                    None,
+                    Some(true),
+                )
+                .await
+                .unwrap()
+                .items
+                .iter()
+                .any(|ready| ready.id == id)
+        };
+
+        assert!(is_ready(service.clone()).await);
+        assert!(!is_ready(service.clone().with_ready_requires_embeddings(true)).await);
+
+        service.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn inline_preview_chunks_markdown(state: TestState) {
+        let payload = serde_json::json!({
+            "content": "---\ntitle: Raguru\n---\n# Raguru\n\nRaguru is a chef. He cooks ragu.\nHis ragu is the finest in the galaxy.\n",
+            "type": "md",
+            "parser": {
+                "string": {
+                    "start": 0,
+                    "end": 0,
+                    "range": false,
+                    "filters": [],
+                    "stripMarkdown": true
+                }
+            },
+            "chunker": {
+                "snapping": {
+                    "size": 1,
+                    "overlap": 0,
+                    "delimiter": ".",
+                    "skipF": [],
+                    "skipB": []
+                }
+            }
+        });
+
+        let payload: InlinePreviewPayload = serde_json::from_value(payload).unwrap();
+
+        let preview = state
+            .app
+            .services
+            .document
+            .inline_preview(payload)
+            .await
+            .unwrap();
+
+        let chunks = preview
+            .chunks
+            .iter()
+            .map(|c| c.chunk.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "Raguru\n\nRaguru is a chef.",
+                " He cooks ragu.",
+                "\nHis ragu is the finest in the galaxy.\n"
+            ],
+            chunks
+        );
+
+        let tokenizer = Tokenizer::new();
+        let mut total = TokenCount::default();
+
+        for chunk in preview.chunks.iter() {
+            let count = tokenizer.count(&chunk.chunk);
+            assert_eq!(count.cl100k, chunk.token_count.cl100k);
+            assert_eq!(count.o200k, chunk.token_count.o200k);
+            total += count;
+        }
+
+        assert_eq!(total.cl100k, preview.total_tokens_post.cl100k);
+        assert_eq!(total.o200k, preview.total_tokens_post.o200k);
+        assert!(preview.total_tokens_pre.cl100k > 0);
+    }
+
+    #[test]
+    async fn inline_preview_route_chunks_markdown(state: TestState) {
+        let url = state.serve_routes().await;
+
+        let payload = serde_json::json!({
+            "content": "# Raguru\n\nRaguru is a chef. He cooks ragu.\nHis ragu is the finest in the galaxy.\n",
+            "type": "md",
+            "chunker": {
+                "snapping": {
+                    "size": 1,
+                    "overlap": 0,
+                    "delimiter": ".",
+                    "skipF": [],
+                    "skipB": []
+                }
+            }
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("{url}/preview"))
+            .json(&payload)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::OK, response.status());
+
+        let preview: serde_json::Value = response.json().await.unwrap();
+        let chunks = preview["chunks"].as_array().unwrap();
+
+        assert_eq!(3, chunks.len());
+        assert!(chunks[0]["chunk"]
+            .as_str()
+            .unwrap()
+            .ends_with("Raguru is a chef."));
+
+        let tokenizer = Tokenizer::new();
+        let mut total = TokenCount::default();
+
+        for chunk in chunks {
+            let count = tokenizer.count(chunk["chunk"].as_str().unwrap());
+            assert_eq!(count.cl100k, chunk["tokenCount"]["cl100k"]);
+            assert_eq!(count.o200k, chunk["tokenCount"]["o200k"]);
+            total += count;
+        }
+
+        assert_eq!(total.cl100k, preview["totalTokensPost"]["cl100k"]);
+        assert_eq!(total.o200k, preview["totalTokensPost"]["o200k"]);
+    }
+
+    #[test]
+    async fn chunk_diff_reports_boundaries_changed_by_skips(state: TestState) {
+        let service = state.app.services.document.clone();
+
+        let document = service
+            .upload(DocumentUpload::new(
+                "CHUNK_DIFF_TEST".to_string(),
+                DocumentType::Text(TextDocumentType::Txt),
+                b"Dr. Raguru cooks ragu. Mr. Gura tastes it. Everyone eats.",
+            ))
+            .await
+            .unwrap();
+
+        let snapping = |skip_b: &[&str]| {
+            serde_json::json!({
+                "snapping": {
+                    "size": 1,
+                    "overlap": 0,
+                    "delimiter": ".",
+                    "skipF": [],
+                    "skipB": skip_b
+                }
+            })
+        };
+
+        let payload: ChunkDiffPayload = serde_json::from_value(serde_json::json!({
+            "before": snapping(&[]),
+            "after": snapping(&["Dr", "Mr"]),
+        }))
+        .unwrap();
+
+        let diff = service.chunk_diff(document.id, payload).await.unwrap();
+
+        // The splits after "Dr." and "Mr." are gone, the rest are unchanged
+        assert_eq!(vec![3, 26], diff.removed);
+        assert!(diff.added.is_empty());
+        assert_eq!(5, diff.chunks_before);
+        assert_eq!(3, diff.chunks_after);
+        assert_eq!(-2, diff.chunk_count_delta);
+
+        service.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn inline_preview_route_rejects_invalid_input(state: TestState) {
+        let url = state.serve_routes().await;
+        let client = reqwest::Client::new();
+
+        let chunker = serde_json::json!({
+            "sliding": {
+                "size": 1000,
+                "overlap": 0
+            }
+        });
+
+        let empty = client
+            .post(format!("{url}/preview"))
+            .json(&serde_json::json!({
+                "content": "",
+                "type": "txt",
+                "chunker": chunker.clone()
+            }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::UNPROCESSABLE_ENTITY, empty.status());
+
+        let oversized = client
+            .post(format!("{url}/preview"))
+            .json(&serde_json::json!({
+                "content": "a".repeat(MAX_INLINE_PREVIEW_SIZE + 1),
+                "type": "txt",
+                "chunker": chunker
+            }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(reqwest::StatusCode::BAD_REQUEST, oversized.status());
+    }
+
+    #[test]
+    async fn inline_preview_rejects_oversized_content(state: TestState) {
+        let payload = serde_json::json!({
+            "content": "a".repeat(MAX_INLINE_PREVIEW_SIZE + 1),
+            "type": "txt",
+            "chunker": {
+                "sliding": {
+                    "size": 1000,
+                    "overlap": 0
+                }
+            }
+        });
+
+        let payload: InlinePreviewPayload = serde_json::from_value(payload).unwrap();
+
+        let result = state.app.services.document.inline_preview(payload).await;
+
+        assert!(matches!(
+            result.unwrap_err().error,
+            ChonkitErr::InvalidParameter(_)
+        ));
+    }
+
+    #[test]
+    async fn inline_preview_chunks_fixed_count(state: TestState) {
+        let preview = |content: String| {
+            let payload = serde_json::json!({
+                "content": content,
+                "type": "txt",
+                "chunker": {
+                    "fixedCount": {
+                        "count": 5
+                    }
+                }
+            });
+            serde_json::from_value::<InlinePreviewPayload>(payload).unwrap()
+        };
+
+        let long = "Raguru cooks ragu in his floating kitchen. ".repeat(200);
+        let result = state
+            .app
+            .services
+            .document
+            .inline_preview(preview(long))
+            .await
+            .unwrap();
+
+        assert_eq!(5, result.chunks.len());
+
+        let short = "Raguru cooks ragu. Gura eats it.".to_string();
+        let result = state
+            .app
+            .services
+            .document
+            .inline_preview(preview(short))
+            .await
+            .unwrap();
+
+        assert_eq!(2, result.chunks.len());
+    }
+
+    #[test]
+    async fn identical_content_from_two_sources_is_linked(state: TestState) {
+        let hashing = DocumentHashing {
+            link_duplicates: true,
+            ..Default::default()
+        };
+
+        let mut document = state.app.providers.document.clone();
+        document.register(Arc::new(StubDriveStore(
+            FsDocumentStore::new(TEST_UPLOAD_PATH).await,
+        )));
+
+        let providers = ProviderState {
+            document,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = DocumentService::new(
+            state.app.providers.database.clone(),
+            providers.clone(),
+            Tokenizer::new(),
+        )
+        .with_hashing(hashing);
+
+        let content = b"Raguru keeps his recipes in more than one place.";
+
+        let uploaded = service
+            .upload(DocumentUpload {
+                name: "UPLOAD_TEST_LINKED_TXT".to_string(),
+                ty: DocumentType::Text(TextDocumentType::Txt),
+                file: content,
+            })
+            .await
+            .unwrap();
+
+        let imported = ServiceFactory::new(state.app.providers.database.clone(), providers)
+            .with_hashing(hashing)
+            .storage(StubDriveApi(content))
+            .import_document("ragu_recipes")
+            .await
+            .unwrap();
+
+        assert_eq!(uploaded.id, imported.id);
+
+        let sources = service.list_sources(uploaded.id).await.unwrap();
+        assert_eq!(2, sources.len());
+        assert_eq!(FS_STORE_ID, sources[0].src);
+        assert_eq!(STUB_DRIVE_ID, sources[1].src);
+        assert!(sources
+            .iter()
+            .all(|source| source.document_id == uploaded.id));
+
+        let documents = service
+            .list_documents(PaginationSort::default(), None, None)
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .filter(|document| document.hash == uploaded.hash)
+            .count();
+
+        assert_eq!(1, documents);
+
+        service.delete(uploaded.id).await.unwrap();
+    }
+
+    #[test]
+    async fn verify_reports_corrupted_documents(state: TestState) {
+        let service = state.app.services.document.clone();
+
+        let document = service
+            .upload(DocumentUpload::new(
+                "UPLOAD_TEST_VERIFY_TXT".to_string(),
+                DocumentType::Text(TextDocumentType::Txt),
+                b"Raguru's secret ragu recipe.",
+            ))
+            .await
+            .unwrap();
+
+        // Corrupt the stored file behind the service's back
+        let corrupted = b"Raguru's secret ragu recipe, bit rotted.";
+        let store = state
+            .app
+            .providers
+            .document
+            .get_provider(&document.src)
+            .unwrap();
+        store.delete(&document.path).await.unwrap();
+        store.write(&document.path, corrupted).await.unwrap();
+
+        let verify = |fix| {
+            service
+                .verify_hashes(VerifyHashesPayload {
+                    src: Some(FS_STORE_ID.to_string()),
+                    fix: Some(fix),
+                })
+                .map(Result::unwrap)
+                .collect::<Vec<_>>()
+        };
+
+        let events = verify(false).await;
+
+        let mismatch = events
+            .iter()
+            .find_map(|event| match event {
+                HashVerificationEvent::Mismatch(mismatch)
+                    if mismatch.document_id == document.id =>
+                {
+                    Some(mismatch)
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        let expected = DocumentHashing::default().algorithm.hash(corrupted);
+        assert_eq!(document.hash, mismatch.stored);
+        assert_eq!(expected, mismatch.actual);
+        assert!(!mismatch.fixed);
+
+        let Some(HashVerificationEvent::Done(summary)) = events.last() else {
+            panic!("verification must end with a summary");
+        };
+        assert!(summary.mismatched >= 1);
+
+        // Verifying without fixing leaves the hash as is
+        let stored = service.get_document(document.id).await.unwrap();
+        assert_eq!(document.hash, stored.hash);
+
+        verify(true).await;
+
+        let fixed = service.get_document(document.id).await.unwrap();
+        assert_eq!(expected, fixed.hash);
+        assert!(fixed.updated_at > document.updated_at);
+
+        let events = verify(false).await;
+        assert!(!events.iter().any(|event| matches!(
+            event,
+            HashVerificationEvent::Mismatch(mismatch) if mismatch.document_id == document.id
+        )));
+
+        service.delete(document.id).await.unwrap();
+    }
+
+    struct UnavailableImageStore;
+
+    impl Identity for UnavailableImageStore {
+        fn id(&self) -> &'static str {
+            "unavailable"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ImageStorage for UnavailableImageStore {
+        async fn get_image(&self, _path: &str) -> Result<Vec<u8>, ChonkitError> {
+            err!(ChonkitErr::IO(std::io::ErrorKind::ConnectionRefused.into()))
+        }
+
+        async fn store_image(&self, _image: &Image) -> Result<(), ChonkitError> {
+            err!(ChonkitErr::IO(std::io::ErrorKind::ConnectionRefused.into()))
+        }
+
+        async fn delete_image(&self, _path: &str) -> Result<(), ChonkitError> {
+            err!(ChonkitErr::IO(std::io::ErrorKind::ConnectionRefused.into()))
+        }
+
+        async fn exists(&self, _path: &str) -> Result<bool, ChonkitError> {
+            err!(ChonkitErr::IO(std::io::ErrorKind::ConnectionRefused.into()))
+        }
+    }
+
+    #[test]
+    async fn scheduled_sync_runs_repeatedly_without_overlap(state: TestState) {
+        let store = Arc::new(SlowSyncStore {
+            inner: FsDocumentStore::new(TEST_UPLOAD_PATH).await,
+            running: AtomicUsize::new(0),
+            max_running: AtomicUsize::new(0),
+            runs: AtomicUsize::new(0),
+        });
+
+        let mut document = state.app.providers.document.clone();
+        document.register(store.clone());
+
+        let providers = ProviderState {
+            document,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = DocumentService::new(
+            state.app.providers.database.clone(),
+            providers,
+            Tokenizer::new(),
+        );
+
+        // Syncs take longer than the interval, so some ticks occur while one is running
+        let schedule = start_document_sync(
+            service.clone(),
+            SLOW_SYNC_ID.to_string(),
+            Duration::from_millis(20),
+        );
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while store.running.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let error = service.sync(SLOW_SYNC_ID).await.unwrap_err();
+        assert!(matches!(error.error, ChonkitErr::AlreadyExists(_)));
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        schedule.abort();
+
+        assert!(store.runs.load(Ordering::SeqCst) >= 3);
+        assert_eq!(1, store.max_running.load(Ordering::SeqCst));
+    }
+
+    const SLOW_SYNC_ID: &str = "slow_sync";
+
+    /// Storage whose syncs take a while and track how many of them run at once.
+    struct SlowSyncStore {
+        inner: FsDocumentStore,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        runs: AtomicUsize,
+    }
+
+    impl Identity for SlowSyncStore {
+        fn id(&self) -> &'static str {
+            SLOW_SYNC_ID
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DocumentStorage for SlowSyncStore {
+        fn absolute_path(&self, name: &str, ext: DocumentType) -> String {
+            self.inner.absolute_path(name, ext)
+        }
+
+        async fn read(&self, path: &str) -> Result<Vec<u8>, ChonkitError> {
+            self.inner.read(path).await
+        }
+
+        async fn list_files(&self) -> Result<Vec<DocumentFile<LocalPath>>, ChonkitError> {
+            self.inner.list_files().await
+        }
+
+        async fn delete(&self, path: &str) -> Result<(), ChonkitError> {
+            self.inner.delete(path).await
+        }
+
+        async fn write(&self, path: &str, content: &[u8]) -> Result<(), ChonkitError> {
+            self.inner.write(path, content).await
+        }
+
+        async fn sync(
+            &self,
+            _repo: &Repository,
+            _hashing: DocumentHashing,
+        ) -> Result<(), ChonkitError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    const STUB_DRIVE_ID: &str = "stub_drive";
+
+    /// Stores files of the [StubDriveApi] on the local FS.
+    struct StubDriveStore(FsDocumentStore);
+
+    impl Identity for StubDriveStore {
+        fn id(&self) -> &'static str {
+            STUB_DRIVE_ID
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DocumentStorage for StubDriveStore {
+        fn absolute_path(&self, name: &str, ext: DocumentType) -> String {
+            self.0
+                .absolute_path(&format!("{STUB_DRIVE_ID}_{name}"), ext)
+        }
+
+        async fn read(&self, path: &str) -> Result<Vec<u8>, ChonkitError> {
+            self.0.read(path).await
+        }
+
+        async fn list_files(&self) -> Result<Vec<DocumentFile<LocalPath>>, ChonkitError> {
+            self.0.list_files().await
+        }
+
+        async fn delete(&self, path: &str) -> Result<(), ChonkitError> {
+            self.0.delete(path).await
+        }
+
+        async fn write(&self, path: &str, content: &[u8]) -> Result<(), ChonkitError> {
+            self.0.write(path, content).await
+        }
+    }
+
+    /// External API serving a single text file with fixed contents.
+    struct StubDriveApi(&'static [u8]);
+
+    impl Identity for StubDriveApi {
+        fn id(&self) -> &'static str {
+            STUB_DRIVE_ID
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ExternalDocumentStorage for StubDriveApi {
+        async fn list_files(
+            &self,
+            _file_ids: Option<&[String]>,
+        ) -> Result<Vec<DocumentFile<ExternalPath>>, ChonkitError> {
+            err!(ChonkitErr::IO(std::io::ErrorKind::Unsupported.into()))
+        }
+
+        async fn get_file(
+            &self,
+            file_id: &str,
+        ) -> Result<DocumentFile<ExternalPath>, ChonkitError> {
+            Ok(DocumentFile::new(
+                file_id.to_string(),
+                DocumentType::Text(TextDocumentType::Txt),
+                ExternalPath(file_id.to_string()),
+                None,
+            ))
+        }
+
+        async fn download(&self, _file_id: &str) -> Result<Vec<u8>, ChonkitError> {
+            Ok(self.0.to_vec())
+        }
+    }
 }
@@ -6,17 +6,54 @@
 #[suitest::suite(integration_tests)]
 mod vector_service_integration_tests {
     use crate::{
-        app::test::{TestState, TestStateConfig, DEFAULT_MODELS},
-        config::{DEFAULT_COLLECTION_NAME, FEMBED_EMBEDDER_ID},
+        app::{
+            batch::{self, BatchJob, BatchJobResult},
+            test::{TestEmbedder, TestState, TestStateConfig, DEFAULT_MODELS},
+        },
+        config::{DEFAULT_COLLECTION_NAME, DEFAULT_MAX_VECTOR_SIZE, FEMBED_EMBEDDER_ID},
         core::{
-            document::{DocumentType, TextDocumentType},
+            chunk::{self, ChunkConfig, ChunkedDocument, ParentChildConfig},
+            document::{
+                parser::{
+                    redact::{PiiPattern, RedactionConfig},
+                    ParseConfig, StringParseConfig,
+                },
+                DocumentType, TextDocumentType,
+            },
+            embeddings::{Embedder, ProbingEmbedder},
+            llm::{Llm, QueryRewrite},
+            model::{
+                collection::CollectionInsert, document::DocumentMetadataUpdate,
+                embedding::EmbeddingReportType,
+            },
+            provider::{EmbeddingProvider, Identity, ProviderState},
+            rerank::Reranker,
             service::{
-                collection::dto::{CreateCollectionPayload, SearchPayload},
-                document::dto::DocumentUpload,
-                embedding::EmbedTextInput,
+                collection::{
+                    dto::{
+                        AskEvent, AskPayload, CollectionModality, CollectionSearchResult,
+                        CreateCollectionPayload, MultiSearchPayload, SearchEvent, SearchPayload,
+                        SetAliasPayload,
+                    },
+                    CollectionService,
+                },
+                document::dto::{BulkDeletePayload, DocumentUpload},
+                embedding::{
+                    AutoCreateCollections, EmbedFanoutInput, EmbedImageInput, EmbedTextInput,
+                    EmbeddingService,
+                },
+                ServiceState,
             },
+            token::Tokenizer,
+            vector::{chunk_id, CollectionItemInsert, CreateVectorCollection},
         },
-        error::ChonkitErr,
+        error::{ChonkitErr, ChonkitError},
+    };
+    use axum::http::StatusCode;
+    use futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
     };
     use suitest::{after_all, before_all, cleanup};
 
@@ -62,6 +99,10 @@ mod vector_service_integration_tests {
                 vector_provider: provider.to_string(),
                 embedding_provider: embedder.id().to_string(),
                 groups: None,
+                modality: CollectionModality::Text,
+                chunk_template: None,
+                tags: None,
+                insert_dedup_threshold: None,
             };
 
             test_state
@@ -166,6 +207,10 @@ mod vector_service_integration_tests {
                 vector_provider: vector_db.id().to_string(),
                 embedding_provider: embedder.id().to_string(),
                 groups: None,
+                modality: CollectionModality::Text,
+                chunk_template: None,
+                tags: None,
+                insert_dedup_threshold: None,
             };
 
             let collection = service.create_collection(params).await.unwrap();
@@ -209,6 +254,10 @@ mod vector_service_integration_tests {
                 vector_provider: vector_db.id().to_string(),
                 embedding_provider: embedder.id().to_string(),
                 groups: None,
+                modality: CollectionModality::Text,
+                chunk_template: None,
+                tags: None,
+                insert_dedup_threshold: None,
             };
 
             let result = service.create_collection(params).await;
@@ -217,6 +266,281 @@ mod vector_service_integration_tests {
         }
     }
 
+    #[test]
+    async fn text_only_collection_rejects_images(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let model = DEFAULT_MODELS
+            .get()
+            .unwrap()
+            .get(embedder.id())
+            .unwrap()
+            .clone();
+
+        assert!(!model.multimodal);
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let params = |name: &str, modality| CreateCollectionPayload {
+                model: model.name.clone(),
+                name: name.to_string(),
+                vector_provider: vector_db.id().to_string(),
+                embedding_provider: embedder.id().to_string(),
+                groups: None,
+                modality,
+                chunk_template: None,
+                tags: None,
+                insert_dedup_threshold: None,
+            };
+
+            // Image collections require a multimodal model
+            let result = services
+                .collection
+                .create_collection(params(
+                    "Test_collection_images",
+                    CollectionModality::Multimodal,
+                ))
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::InvalidEmbeddingModel(_)
+            ));
+
+            let collection = services
+                .collection
+                .create_collection(params(
+                    "Test_collection_text_only",
+                    CollectionModality::Text,
+                ))
+                .await
+                .unwrap();
+
+            assert_eq!(Some(false), collection.multimodal);
+
+            // The image does not exist, rejecting it up front means it is never looked up
+            let result = services
+                .embedding
+                .create_image_embeddings(EmbedImageInput::new(uuid::Uuid::new_v4(), collection.id))
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::InvalidEmbeddingModel(_)
+            ));
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn embedding_into_collection_of_other_size_is_dimension_mismatch(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let model = DEFAULT_MODELS
+            .get()
+            .unwrap()
+            .get(embedder.id())
+            .unwrap()
+            .clone();
+
+        let collection_size = model.size + 1;
+
+        let document = services
+            .document
+            .upload(DocumentUpload::new(
+                "test_document_dimension_mismatch".to_string(),
+                DocumentType::Text(TextDocumentType::Txt),
+                b"Raguru measures the ragu twice.",
+            ))
+            .await
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+            let name = format!("Test_collection_dimension_mismatch_{provider}");
+
+            // The vector collection no longer matches the model recorded for it
+            let insert = CollectionInsert::new(&name, &model.name, embedder.id(), vector_db.id());
+
+            vector_db
+                .create_vector_collection(CreateVectorCollection::new(
+                    insert.id,
+                    &name,
+                    collection_size,
+                    embedder.id(),
+                    &model.name,
+                    None,
+                ))
+                .await
+                .unwrap();
+
+            let collection = state
+                .app
+                .providers
+                .database
+                .insert_collection(insert, None)
+                .await
+                .unwrap();
+
+            let error = services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap_err();
+
+            assert_eq!(StatusCode::CONFLICT, error.status());
+
+            let ChonkitErr::DimensionMismatch {
+                model_size,
+                collection_size: actual_collection_size,
+            } = error.error
+            else {
+                panic!("expected DimensionMismatch, got {:?}", error.error);
+            };
+
+            assert_eq!(model.size, model_size);
+            assert_eq!(collection_size, actual_collection_size);
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+
+        services.document.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn create_collection_uses_probed_model_size(state: TestState) {
+        let mut embedding = EmbeddingProvider::default();
+        let unlisted = Arc::new(TestEmbedder::new("unlisted", 7));
+        embedding.register(Arc::new(ProbingEmbedder::new(unlisted.clone())));
+
+        let providers = ProviderState {
+            embedding,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = CollectionService::new(
+            state.app.providers.database.clone(),
+            providers,
+            Tokenizer::new(),
+        );
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let name = "Test_collection_probed";
+
+            let params = CreateCollectionPayload {
+                model: "local-model".to_string(),
+                name: name.to_string(),
+                vector_provider: vector_db.id().to_string(),
+                embedding_provider: unlisted.id().to_string(),
+                groups: None,
+                modality: CollectionModality::Text,
+                chunk_template: None,
+                tags: None,
+                insert_dedup_threshold: None,
+            };
+
+            let collection = service.create_collection(params).await.unwrap();
+
+            let v_collection = vector_db.get_collection(name).await.unwrap();
+
+            assert_eq!(7, v_collection.size);
+
+            service.delete_collection(collection.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn create_collection_rejects_invalid_vector_sizes(state: TestState) {
+        for size in [0, DEFAULT_MAX_VECTOR_SIZE + 1] {
+            let mut embedding = EmbeddingProvider::default();
+            embedding.register(Arc::new(
+                TestEmbedder::new(SIZED_EMBEDDER_ID, size).with_listed_model(SIZED_MODEL),
+            ));
+
+            let providers = ProviderState {
+                embedding,
+                ..state.app.providers.clone().into()
+            };
+
+            let service = CollectionService::new(
+                state.app.providers.database.clone(),
+                providers,
+                Tokenizer::new(),
+            );
+
+            for provider in state.active_vector_providers.iter() {
+                let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+                let name = "Test_collection_invalid_size";
+
+                let params = CreateCollectionPayload {
+                    model: SIZED_MODEL.to_string(),
+                    name: name.to_string(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: SIZED_EMBEDDER_ID.to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                };
+
+                let error = service.create_collection(params).await.unwrap_err();
+
+                assert!(matches!(error.error, ChonkitErr::InvalidEmbeddingModel(_)));
+
+                // Neither the collection nor its vector collection are created
+                assert!(vector_db.get_collection(name).await.is_err());
+            }
+        }
+    }
+
+    #[test]
+    async fn configuration_reports_unavailable_embedders(state: TestState) {
+        let mut app = state.app.clone();
+        app.providers.embedding.register(Arc::new(
+            TestEmbedder::new(UNAVAILABLE_EMBEDDER_ID, 8).unavailable(),
+        ));
+
+        let config = app.get_configuration().await.unwrap();
+
+        assert!(!config.embedding_providers[FEMBED_EMBEDDER_ID].is_empty());
+        assert!(!config
+            .embedding_providers
+            .contains_key(UNAVAILABLE_EMBEDDER_ID));
+        assert!(
+            config.unavailable_embedding_providers[UNAVAILABLE_EMBEDDER_ID]
+                .contains("did not respond")
+        );
+        assert!(!config
+            .unavailable_embedding_providers
+            .contains_key(FEMBED_EMBEDDER_ID));
+    }
+
     #[test]
     async fn create_collection_fails_with_existing_collection(state: TestState) {
         let service = &state.app.services.collection;
@@ -245,6 +569,10 @@ mod vector_service_integration_tests {
                 vector_provider: vector_db.id().to_string(),
                 embedding_provider: embedder.id().to_string(),
                 groups: None,
+                modality: CollectionModality::Text,
+                chunk_template: None,
+                tags: None,
+                insert_dedup_threshold: None,
             };
 
             let result = service.create_collection(params).await;
@@ -290,7 +618,11 @@ mod vector_service_integration_tests {
 
             let embeddings = EmbedTextInput {
                 document: document.id,
-                collection: default.id,
+                collection: Some(default.id),
+                collection_name: None,
+                detailed: false,
+                embed_metadata: false,
+                no_cache: false,
             };
 
             let collection = services
@@ -307,9 +639,23 @@ mod vector_service_integration_tests {
 
             let search = SearchPayload {
                 query: content.to_string(),
-                collection_id: collection.id,
+                collection_id: Some(collection.id),
+                collection_alias: None,
                 limit: Some(1),
                 max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
             };
 
             let results = services.collection.search(search).await.unwrap();
@@ -337,9 +683,8 @@ mod vector_service_integration_tests {
     }
 
     #[test]
-    async fn deleting_collection_removes_all_embeddings(state: TestState) {
+    async fn search_stream_emits_hits_then_final_ordering(state: TestState) {
         let services = &state.app.services;
-        let postgres = &state.app.providers.database;
         let embedder = state
             .app
             .providers
@@ -350,114 +695,4211 @@ mod vector_service_integration_tests {
 
         for provider in state.active_vector_providers.iter() {
             let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
-
-            let collection_name = "Test_collection_delete_embeddings";
+            let collection_name =
+                format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id());
 
             let collection = services
                 .collection
-                .create_collection(CreateCollectionPayload {
-                    name: collection_name.to_string(),
-                    model: DEFAULT_MODELS
-                        .get()
-                        .unwrap()
-                        .get(embedder.id())
-                        .unwrap()
-                        .name
-                        .clone(),
-                    vector_provider: vector_db.id().to_string(),
-                    embedding_provider: embedder.id().to_string(),
-                    groups: None,
-                })
+                .get_collection_by_name(&collection_name, vector_db.id())
                 .await
                 .unwrap();
 
+            let content = "Raguru streams his ragu recipes.";
+
             let document = services
                 .document
                 .upload(DocumentUpload::new(
-                    "test_document_420".to_string(),
+                    "test_document_search_stream".to_string(),
                     DocumentType::Text(TextDocumentType::Txt),
-                    b"Hello, world! 420",
+                    content.as_bytes(),
                 ))
                 .await
                 .unwrap();
 
-            let embeddings = EmbedTextInput {
-                document: document.id,
-                collection: collection.id,
-            };
-
             services
                 .embedding
-                .create_text_embeddings(embeddings)
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
                 .await
                 .unwrap();
 
-            services
+            let events = services
                 .collection
-                .delete_collection(collection.id)
+                .search_stream(SearchPayload {
+                    query: content.to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(1),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: None,
+                    dimension_adapt: None,
+                })
+                .try_collect::<Vec<_>>()
                 .await
                 .unwrap();
 
-            let embeddings = postgres
-                .get_text_embeddings(document.id, collection.id)
-                .await
-                .unwrap();
+            assert_eq!(2, events.len());
 
-            assert!(embeddings.is_none());
+            let SearchEvent::Hits(ref hits) = events[0] else {
+                panic!("expected hits first, got {:?}", events[0]);
+            };
+            assert_eq!(1, hits.items.len());
+            assert_eq!(content, hits.items[0].item.payload.as_content());
+
+            let SearchEvent::Ranked { ref order } = events[1] else {
+                panic!("expected final ordering last, got {:?}", events[1]);
+            };
+            assert_eq!(&vec![0], order);
 
             services.document.delete(document.id).await.unwrap();
         }
     }
 
     #[test]
-    async fn prevents_duplicate_embeddings(state: TestState) {
+    async fn search_caps_results_per_document(state: TestState) {
         let services = &state.app.services;
         let embedder = state
             .app
             .providers
             .embedding
             .get_provider(FEMBED_EMBEDDER_ID)
-            .unwrap()
-            .clone();
+            .unwrap();
+
+        let content = "Raguru seasons his ragu with stardust gathered from passing comets. \
+            He simmers it for three days in his floating kitchen above the clouds. \
+            Gura tastes every batch and adds a pinch of salt from the moon. \
+            The finished ragu is served in bowls carved from asteroid rock. \
+            Travellers from distant galaxies line up for hours to taste it.";
 
         for provider in state.active_vector_providers.iter() {
             let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
-            let collection_name =
-                format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id());
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
 
             let document = services
                 .document
                 .upload(DocumentUpload::new(
-                    "test_document_42069".to_string(),
+                    "test_document_search_dedup".to_string(),
                     DocumentType::Text(TextDocumentType::Txt),
-                    b"Hello, world! 42069",
+                    content.as_bytes(),
                 ))
                 .await
                 .unwrap();
 
-            let default = services
-                .collection
-                .get_collection_by_name(&collection_name, vector_db.id())
+            // Small, heavily overlapping chunks
+            services
+                .document
+                .update_chunker(document.id, None, ChunkConfig::sliding(60, 40).unwrap())
                 .await
                 .unwrap();
 
-            let create = EmbedTextInput {
-                document: document.id,
-                collection: default.id,
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let search = |dedup_by_document| SearchPayload {
+                query: "How does Raguru season his ragu?".to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(10),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let from_document = |result: &CollectionSearchResult| {
+                result
+                    .items
+                    .iter()
+                    .filter(|item| item.item.document_id == Some(document.id))
+                    .map(|item| item.item.payload.chunk_index().unwrap())
+                    .collect::<Vec<_>>()
             };
 
+            let result = services.collection.search(search(None)).await.unwrap();
+            assert!(from_document(&result).len() > 2);
+
+            let result = services.collection.search(search(Some(2))).await.unwrap();
+            let chunks = from_document(&result);
+
+            assert_eq!(2, chunks.len());
+            assert_ne!(chunks[0], chunks[1]);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_highlights_query_terms(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let content =
+            "Raguru simmers his ragu in a floating kitchen. Nobody makes RAGU like Raguru.";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_search_highlight".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
             services
                 .embedding
-                .create_text_embeddings(create.clone())
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
                 .await
                 .unwrap();
 
-            let duplicate = services.embedding.create_text_embeddings(create).await;
-            let error = duplicate.unwrap_err().error;
+            let search = |highlight| SearchPayload {
+                query: "Who makes ragu?".to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(10),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
 
-            assert!(matches!(error, ChonkitErr::AlreadyExists(_)));
+            let find = |result: CollectionSearchResult| {
+                result
+                    .items
+                    .into_iter()
+                    .find(|item| item.item.document_id == Some(document.id))
+                    .unwrap()
+            };
+
+            let item = find(services.collection.search(search(None)).await.unwrap());
+            assert!(item.highlights.is_empty());
+
+            let item = find(
+                services
+                    .collection
+                    .search(search(Some(true)))
+                    .await
+                    .unwrap(),
+            );
+            let content = item.item.payload.as_content();
+
+            let matched = item
+                .highlights
+                .iter()
+                .map(|(start, end)| &content[*start..*end])
+                .collect::<Vec<_>>();
+
+            // "Raguru" is a different word and is not highlighted
+            assert_eq!(vec!["ragu", "makes", "RAGU"], matched);
 
             services.document.delete(document.id).await.unwrap();
         }
     }
+
+    #[test]
+    async fn search_returns_parent_windows(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let content = "Raguru seasons his ragu with stardust gathered from passing comets. \
+            He simmers it for three days in his floating kitchen above the clouds. \
+            Gura tastes every batch and adds a pinch of salt from the moon. \
+            The finished ragu is served in bowls carved from asteroid rock. \
+            Travellers from distant galaxies line up for hours to taste it.";
+
+        let config = ParentChildConfig {
+            child_size: 60,
+            parent_size: 180,
+        };
+        let children = chunx::Sliding::new(config.child_size, 0)
+            .unwrap()
+            .chunk(content)
+            .unwrap();
+        let parents = config.parents(&children);
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_search_parents".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .document
+                .update_chunker(document.id, None, ChunkConfig::ParentChild(config.clone()))
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let search = |return_parent| SearchPayload {
+                query: "Gura tastes every batch".to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(10),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let from_document = |result: CollectionSearchResult| {
+                result
+                    .items
+                    .into_iter()
+                    .filter(|item| item.item.document_id == Some(document.id))
+                    .collect::<Vec<_>>()
+            };
+
+            let children_found =
+                from_document(services.collection.search(search(None)).await.unwrap());
+            assert!(!children_found.is_empty());
+
+            let child = &children_found[0].item.payload;
+            let chunk_index = child.chunk_index().unwrap();
+            assert_eq!(children[chunk_index], child.as_content());
+
+            let parents_found = from_document(
+                services
+                    .collection
+                    .search(search(Some(true)))
+                    .await
+                    .unwrap(),
+            );
+
+            // Only one hit per parent window
+            let mut windows = parents_found
+                .iter()
+                .map(|item| item.item.payload.as_content())
+                .collect::<Vec<_>>();
+            let found = windows.len();
+            windows.sort();
+            windows.dedup();
+            assert_eq!(found, windows.len());
+
+            let parent = &parents_found[0].item.payload;
+            assert_eq!(Some(chunk_index), parent.chunk_index());
+            assert_eq!(
+                parents[config.parent_index(chunk_index)],
+                parent.as_content()
+            );
+            assert!(parent.as_content().contains(&child.as_content()));
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn bulk_deleting_documents_removes_their_vectors(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let mut documents = vec![];
+
+            for i in 0..3 {
+                let content = format!("Raguru's recipe number {i} requires patience.");
+
+                let document = services
+                    .document
+                    .upload(DocumentUpload::new(
+                        format!("test_document_bulk_delete_{i}"),
+                        DocumentType::Text(TextDocumentType::Txt),
+                        content.as_bytes(),
+                    ))
+                    .await
+                    .unwrap();
+
+                services
+                    .embedding
+                    .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                    .await
+                    .unwrap();
+
+                documents.push(document);
+            }
+
+            let missing = uuid::Uuid::new_v4();
+
+            let results = services
+                .document
+                .delete_many(BulkDeletePayload {
+                    ids: vec![documents[0].id, missing, documents[1].id],
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(3, results.len());
+
+            assert_eq!(documents[0].id, results[0].id);
+            assert!(results[0].deleted);
+
+            // Failures do not prevent deleting the rest
+            assert_eq!(missing, results[1].id);
+            assert!(!results[1].deleted);
+            assert!(results[1].error.is_some());
+
+            assert_eq!(documents[1].id, results[2].id);
+            assert!(results[2].deleted);
+
+            for document in documents[..2].iter() {
+                let count = vector_db
+                    .count_vectors(&collection.name, document.id)
+                    .await
+                    .unwrap();
+
+                assert_eq!(0, count);
+                assert!(postgres
+                    .get_document_by_id(document.id)
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+
+            let remaining = &documents[2];
+
+            let count = vector_db
+                .count_vectors(&collection.name, remaining.id)
+                .await
+                .unwrap();
+
+            assert!(count > 0);
+            assert!(postgres
+                .get_document_by_id(remaining.id)
+                .await
+                .unwrap()
+                .is_some());
+
+            services.document.delete(remaining.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_embeds_rewritten_query(state: TestState) {
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let recorder = Arc::new(TestEmbedder::wrap(embedder.clone()));
+
+        let mut embedding = EmbeddingProvider::default();
+        embedding.register(recorder.clone());
+
+        let providers = ProviderState {
+            embedding,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = CollectionService::new(
+            state.app.providers.database.clone(),
+            providers,
+            Tokenizer::new(),
+        )
+        .with_llm(Arc::new(StubLlm));
+
+        let query = "How does Raguru season his ragu?";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = service
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let search = |rewrite| SearchPayload {
+                query: query.to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(1),
+                max_distance: None,
+                rewrite,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let expected = [
+                (QueryRewrite::None, query.to_string()),
+                (QueryRewrite::Hyde, STUB_COMPLETION.to_string()),
+                (QueryRewrite::Expand, format!("{query} {STUB_COMPLETION}")),
+            ];
+
+            for (rewrite, embedded) in expected {
+                let result = service.search(search(rewrite)).await.unwrap();
+
+                assert_eq!(query, result.query);
+                assert_eq!(vec![embedded.clone()], recorder.take_inputs());
+
+                if rewrite == QueryRewrite::None {
+                    assert!(result.rewritten_query.is_none());
+                } else {
+                    assert_eq!(Some(embedded), result.rewritten_query);
+                }
+            }
+
+            // Rewriting is rejected if no LLM is configured
+            let result = state
+                .app
+                .services
+                .collection
+                .search(search(QueryRewrite::Hyde))
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::OperationUnsupported(_)
+            ));
+        }
+    }
+
+    #[test]
+    async fn ask_answers_from_top_chunks(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let llm = Arc::new(RecordingLlm::default());
+
+        let service = CollectionService::new(
+            state.app.providers.database.clone(),
+            state.app.providers.clone().into(),
+            Tokenizer::new(),
+        )
+        .with_llm(llm.clone());
+
+        let content = "Raguru seasons his ragu with stardust.";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = service
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_ask".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let ask = |max_context_tokens| AskPayload {
+                search: SearchPayload {
+                    query: content.to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(1),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: None,
+                    dimension_adapt: None,
+                },
+                max_context_tokens,
+            };
+
+            let events = service
+                .ask(ask(None))
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+
+            let AskEvent::Citations { ref citations } = events[0] else {
+                panic!("expected citations first, got {:?}", events[0]);
+            };
+            assert_eq!(1, citations.len());
+            assert_eq!(1, citations[0].number);
+            assert_eq!(Some(document.id), citations[0].document_id);
+
+            let answer = events[1..]
+                .iter()
+                .map(|event| match event {
+                    AskEvent::Answer { text } => text.as_str(),
+                    event => panic!("expected answer, got {event:?}"),
+                })
+                .collect::<String>();
+            assert_eq!(STUB_COMPLETION, answer);
+
+            let prompt = llm.take_prompts().pop().unwrap();
+            assert!(prompt.contains(&format!("[1] {content}")));
+
+            // Chunks exceeding the token budget are left out of the context
+            let events = service
+                .ask(ask(Some(1)))
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+
+            let AskEvent::Citations { ref citations } = events[0] else {
+                panic!("expected citations first, got {:?}", events[0]);
+            };
+            assert!(citations.is_empty());
+
+            let prompt = llm.take_prompts().pop().unwrap();
+            assert!(!prompt.contains(content));
+
+            // Questions are rejected if no LLM is configured
+            let result = services.collection.ask(ask(None)).await;
+
+            assert!(matches!(
+                result.err().unwrap().error,
+                ChonkitErr::OperationUnsupported(_)
+            ));
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn summary_search_narrows_to_summarized_documents(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let embedding = services.embedding.clone().with_llm(Arc::new(EchoLlm));
+
+        let contents = [
+            "Raguru seasons his ragu with stardust in his floating kitchen.",
+            "Gura sails the seas of Atlantis searching for sunken treasure.",
+        ];
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let summaries = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: "Test_collection_summaries".to_string(),
+                    model: collection.model.clone(),
+                    vector_provider: provider.to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let mut documents = vec![];
+
+            for (i, content) in contents.iter().enumerate() {
+                let document = services
+                    .document
+                    .upload(DocumentUpload::new(
+                        format!("test_document_summary_{i}"),
+                        DocumentType::Text(TextDocumentType::Txt),
+                        content.as_bytes(),
+                    ))
+                    .await
+                    .unwrap();
+
+                let report = embedding
+                    .embed_summary(document.id, summaries.id)
+                    .await
+                    .unwrap();
+                assert_eq!(1, report.report.total_vectors);
+
+                embedding
+                    .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                    .await
+                    .unwrap();
+
+                documents.push(document);
+            }
+
+            for document in documents.iter() {
+                let summary = postgres.get_document_summary(&document.hash).await.unwrap();
+                assert!(summary.is_some());
+
+                let count = vector_db
+                    .count_vectors(&summaries.name, document.id)
+                    .await
+                    .unwrap();
+                assert_eq!(1, count);
+            }
+
+            // Summaries are embedded once per collection
+            let result = embedding.embed_summary(documents[0].id, summaries.id).await;
+            assert!(matches!(
+                result.err().unwrap().error,
+                ChonkitErr::AlreadyExists(_)
+            ));
+
+            let search = SearchPayload {
+                query: contents[1].to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(10),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: Some(summaries.id),
+                summary_documents: Some(1),
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let result = services.collection.search(search).await.unwrap();
+
+            assert!(!result.items.is_empty());
+            for item in result.items.iter() {
+                assert_eq!(Some(documents[1].id), item.item.document_id);
+            }
+
+            // Summaries cannot be generated without an LLM
+            let result = services
+                .embedding
+                .embed_summary(documents[1].id, summaries.id)
+                .await;
+            assert!(matches!(
+                result.err().unwrap().error,
+                ChonkitErr::OperationUnsupported(_)
+            ));
+
+            for document in documents {
+                services.document.delete(document.id).await.unwrap();
+            }
+            services
+                .collection
+                .delete_collection(summaries.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_by_alias_follows_swaps(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let current = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let next = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: "Test_collection_alias".to_string(),
+                    model: current.model.clone(),
+                    vector_provider: provider.to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let mut documents = vec![];
+
+            for (i, collection) in [&current, &next].into_iter().enumerate() {
+                let document = services
+                    .document
+                    .upload(DocumentUpload::new(
+                        format!("test_document_alias_{i}"),
+                        DocumentType::Text(TextDocumentType::Txt),
+                        format!("Raguru serves ragu number {i}.").as_bytes(),
+                    ))
+                    .await
+                    .unwrap();
+
+                services
+                    .embedding
+                    .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                    .await
+                    .unwrap();
+
+                documents.push(document);
+            }
+
+            let alias = format!("ragu_{provider}");
+
+            let search = || SearchPayload {
+                query: "Which ragu does Raguru serve?".to_string(),
+                collection_id: None,
+                collection_alias: Some(alias.clone()),
+                limit: Some(10),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            // Aliases must exist before searching by them
+            let result = services.collection.search(search()).await;
+            assert!(matches!(
+                result.err().unwrap().error,
+                ChonkitErr::DoesNotExist(_)
+            ));
+
+            for (collection, document) in [&current, &next].into_iter().zip(documents.iter()) {
+                let set = services
+                    .collection
+                    .set_alias(SetAliasPayload {
+                        alias: alias.clone(),
+                        collection_id: collection.id,
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(collection.id, set.collection_id);
+
+                let result = services.collection.search(search()).await.unwrap();
+
+                let found = result
+                    .items
+                    .iter()
+                    .filter_map(|item| item.item.document_id)
+                    .collect::<Vec<_>>();
+
+                assert!(found.contains(&document.id));
+                for other in documents.iter().filter(|d| d.id != document.id) {
+                    assert!(!found.contains(&other.id));
+                }
+            }
+
+            let aliases = services
+                .collection
+                .list_aliases(Some(next.id))
+                .await
+                .unwrap();
+            assert_eq!(1, aliases.len());
+            assert_eq!(alias, aliases[0].alias);
+
+            // Searches require exactly one of the ID and alias
+            let result = services
+                .collection
+                .search(SearchPayload {
+                    collection_id: Some(current.id),
+                    ..search()
+                })
+                .await;
+            assert!(matches!(
+                result.err().unwrap().error,
+                ChonkitErr::Validation(_)
+            ));
+
+            services.collection.delete_alias(&alias).await.unwrap();
+
+            let result = services.collection.search(search()).await;
+            assert!(matches!(
+                result.err().unwrap().error,
+                ChonkitErr::DoesNotExist(_)
+            ));
+
+            for document in documents {
+                services.document.delete(document.id).await.unwrap();
+            }
+            services
+                .collection
+                .delete_collection(next.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn detailed_report_includes_chunk_tokens(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let content =
+                "Raguru stirs the ragu slowly under the light of three moons. ".repeat(100);
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_detailed_report".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            let report = services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id).detailed())
+                .await
+                .unwrap();
+
+            let chunk_tokens = report.chunk_tokens.unwrap();
+
+            assert!(chunk_tokens.counts.len() > 1);
+            assert_eq!(
+                report.report.total_vectors as usize,
+                chunk_tokens.counts.len()
+            );
+
+            let (cl100k, o200k) = chunk_tokens
+                .counts
+                .iter()
+                .fold((0, 0), |(cl100k, o200k), count| {
+                    (cl100k + count.cl100k, o200k + count.o200k)
+                });
+
+            assert_eq!(chunk_tokens.total.cl100k, cl100k);
+            assert_eq!(chunk_tokens.total.o200k, o200k);
+            assert!(chunk_tokens.min.cl100k <= chunk_tokens.avg.cl100k);
+            assert!(chunk_tokens.avg.cl100k <= chunk_tokens.max.cl100k);
+
+            // Reports are not detailed by default
+            services
+                .embedding
+                .delete_text_embeddings(collection.id, document.id)
+                .await
+                .unwrap();
+
+            let report = services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            assert!(report.chunk_tokens.is_none());
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn embedding_without_cache_bypasses_cache(state: TestState) {
+        let services = &state.app.services;
+        let cache = &state.embedding_cache;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    format!("test_document_no_cache_{provider}"),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    format!("Raguru keeps the ragu recipe of {provider} a secret.").as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            let (reads, writes) = (cache.reads(), cache.writes());
+
+            let report = services
+                .embedding
+                .create_text_embeddings(
+                    EmbedTextInput::new(document.id, collection.id).without_cache(),
+                )
+                .await
+                .unwrap();
+
+            assert!(!report.report.cache);
+            assert_eq!(reads, cache.reads());
+            assert_eq!(writes, cache.writes());
+
+            // Embedding with the cache populates it
+            services
+                .embedding
+                .delete_text_embeddings(collection.id, document.id)
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            assert_eq!(reads + 1, cache.reads());
+            assert_eq!(writes + 1, cache.writes());
+
+            // The cached embeddings are not used either
+            services
+                .embedding
+                .delete_text_embeddings(collection.id, document.id)
+                .await
+                .unwrap();
+
+            let report = services
+                .embedding
+                .create_text_embeddings(
+                    EmbedTextInput::new(document.id, collection.id).without_cache(),
+                )
+                .await
+                .unwrap();
+
+            assert!(!report.report.cache);
+            assert_eq!(reads + 1, cache.reads());
+            assert_eq!(writes + 1, cache.writes());
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn document_metadata_round_trips_through_search(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let metadata = serde_json::json!({
+            "source_url": "https://gura.space/ragu",
+            "author": "Raguru",
+            "category": "recipes",
+        });
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let content = "Raguru simmers the ragu in his floating kitchen.";
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_metadata".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .update_document_metadata(
+                    document.id,
+                    DocumentMetadataUpdate {
+                        name: Some(&document.name),
+                        label: None,
+                        tags: None,
+                        metadata: Some(&metadata),
+                    },
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(Some(&metadata), document.metadata.as_ref());
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let result = services
+                .collection
+                .search(SearchPayload {
+                    query: content.to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(10),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: None,
+                    dimension_adapt: None,
+                })
+                .await
+                .unwrap();
+
+            let item = result
+                .items
+                .into_iter()
+                .find(|item| item.item.document_id == Some(document.id))
+                .unwrap();
+
+            assert_eq!(Some(metadata.clone()), item.item.metadata);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn metadata_chunk_surfaces_documents_by_title(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let title = "Zorblaxian Starship Maintenance Manual";
+        let metadata = serde_json::json!({
+            "title": title,
+            "author": "Quintessa Vrell",
+        });
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            // The content has nothing in common with the title
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_metadata_chunk".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    "Raguru simmers the ragu in his floating kitchen.".as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .document
+                .update_document_metadata(
+                    document.id,
+                    DocumentMetadataUpdate {
+                        name: Some(&document.name),
+                        label: None,
+                        tags: None,
+                        metadata: Some(&metadata),
+                    },
+                )
+                .await
+                .unwrap();
+
+            let report = services
+                .embedding
+                .create_text_embeddings(
+                    EmbedTextInput::new(document.id, collection.id).with_metadata(),
+                )
+                .await
+                .unwrap();
+
+            // The chunk and the synthetic chunk
+            assert_eq!(2, report.report.total_vectors);
+
+            let result = services
+                .collection
+                .search(SearchPayload {
+                    query: title.to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(1),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: None,
+                    dimension_adapt: None,
+                })
+                .await
+                .unwrap();
+
+            let top = &result.items[0].item;
+
+            assert_eq!(Some(document.id), top.document_id);
+            assert!(top.payload.is_synthetic());
+            assert!(top.payload.chunk_index().is_none());
+            assert!(top.payload.as_content().contains(title));
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn chunk_template_applies_to_embedded_text_only(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let recorder = Arc::new(TestEmbedder::wrap(embedder.clone()));
+
+        let mut embedding = EmbeddingProvider::default();
+        embedding.register(recorder.clone());
+
+        let providers = ProviderState {
+            embedding,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = EmbeddingService::new(
+            state.app.providers.database.clone(),
+            providers,
+            state.embedding_cache.clone(),
+            state.image_embedding_cache.clone(),
+            Tokenizer::new(),
+        );
+
+        let content = "Raguru simmers the ragu in his floating kitchen.";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let create = |chunk_template: &str| CreateCollectionPayload {
+                name: format!("Chunk_template_{provider}"),
+                model: DEFAULT_MODELS
+                    .get()
+                    .unwrap()
+                    .get(embedder.id())
+                    .unwrap()
+                    .name
+                    .clone(),
+                vector_provider: vector_db.id().to_string(),
+                embedding_provider: embedder.id().to_string(),
+                groups: None,
+                modality: CollectionModality::Text,
+                chunk_template: Some(chunk_template.to_string()),
+                tags: None,
+                insert_dedup_threshold: None,
+            };
+
+            // Templates without the chunk would embed every chunk as the same text
+            let result = services
+                .collection
+                .create_collection(create("Document: {name}"))
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::InvalidParameter(_)
+            ));
+
+            let collection = services
+                .collection
+                .create_collection(create("Document: {name}\n\n{chunk}"))
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_chunk_template".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            service
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                vec![format!("Document: {}\n\n{content}", document.name)],
+                recorder.take_inputs()
+            );
+
+            let result = services
+                .collection
+                .search(SearchPayload {
+                    query: content.to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(1),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: None,
+                    dimension_adapt: None,
+                })
+                .await
+                .unwrap();
+
+            let top = &result.items[0].item;
+
+            assert_eq!(Some(document.id), top.document_id);
+            assert_eq!(content, top.payload.as_content().trim());
+
+            services.document.delete(document.id).await.unwrap();
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn redaction_applies_to_stored_chunks_only(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let recorder = Arc::new(TestEmbedder::wrap(embedder.clone()));
+
+        let mut embedding = EmbeddingProvider::default();
+        embedding.register(recorder.clone());
+
+        let providers = ProviderState {
+            embedding,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = EmbeddingService::new(
+            state.app.providers.database.clone(),
+            providers,
+            state.embedding_cache.clone(),
+            state.image_embedding_cache.clone(),
+            Tokenizer::new(),
+        );
+
+        let content = "Raguru takes ragu orders at raguru.labamba@gura.moon every evening.";
+        let redacted = "Raguru takes ragu orders at [EMAIL] every evening.";
+
+        let parse_config = ParseConfig::String(StringParseConfig::default().with_redaction(
+            RedactionConfig {
+                builtin: vec![PiiPattern::Email],
+                patterns: vec![],
+            },
+        ));
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Redaction_{provider}"),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_redaction".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .document
+                .update_parser(document.id, None, parse_config.clone())
+                .await
+                .unwrap();
+
+            service
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            assert_eq!(vec![redacted.to_string()], recorder.take_inputs());
+
+            let result = services
+                .collection
+                .search(SearchPayload {
+                    query: content.to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(1),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: None,
+                    dimension_adapt: None,
+                })
+                .await
+                .unwrap();
+
+            let top = &result.items[0].item;
+
+            assert_eq!(Some(document.id), top.document_id);
+            assert_eq!(redacted, top.payload.as_content().trim());
+
+            // The stored document keeps the original content
+            let (_, stored) = services
+                .document
+                .get_document_with_content(document.id)
+                .await
+                .unwrap();
+
+            assert_eq!(content.as_bytes(), stored);
+
+            services.document.delete(document.id).await.unwrap();
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn insert_dedup_skips_near_duplicate_chunks(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let content = format!(
+            "{}Then he serves it to the crew.",
+            "Raguru stirs the ragu slowly under the light of three moons. ".repeat(8)
+        );
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let plain = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let deduplicated = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Insert_dedup_{provider}"),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: Some(0.95),
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(Some(0.95), deduplicated.insert_dedup_threshold);
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_insert_dedup".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            // Every sentence is a chunk
+            services
+                .document
+                .update_chunker(
+                    document.id,
+                    None,
+                    ChunkConfig::snapping(1, 0, vec![], vec![], '.').unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let mut counts = vec![];
+
+            for collection in [plain.id, deduplicated.id] {
+                let report = services
+                    .embedding
+                    .create_text_embeddings(EmbedTextInput::new(document.id, collection))
+                    .await
+                    .unwrap();
+
+                let count = services
+                    .embedding
+                    .count_embeddings(collection, document.id)
+                    .await
+                    .unwrap();
+
+                assert_eq!(report.report.total_vectors as usize, count);
+                counts.push(count);
+            }
+
+            assert_eq!(9, counts[0]);
+            assert!(counts[1] < counts[0]);
+
+            services.document.delete(document.id).await.unwrap();
+            services
+                .collection
+                .delete_collection(deduplicated.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_orders_equal_distances_deterministically(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let content = "Raguru stirs the ragu slowly under the light of three moons.".to_string();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Search_ties_{provider}"),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let vector = embedder
+                .embed_text(&[content.as_str()], &collection.model)
+                .await
+                .unwrap()
+                .embeddings
+                .remove(0);
+
+            // Every chunk of every document has the same vector
+            let chunks = [content.as_str(); 2];
+            for _ in 0..4 {
+                vector_db
+                    .insert_embeddings(CollectionItemInsert::new_text(
+                        uuid::Uuid::new_v4(),
+                        &collection.name,
+                        &chunks,
+                        vec![vector.clone(); 2],
+                        None,
+                    ))
+                    .await
+                    .unwrap();
+            }
+
+            let search = || SearchPayload {
+                query: content.clone(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(8),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let order = |result: CollectionSearchResult| {
+                result
+                    .items
+                    .into_iter()
+                    .map(|hit| {
+                        (
+                            hit.item.document_id.unwrap(),
+                            hit.item.payload.chunk_index(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let first = order(services.collection.search(search()).await.unwrap());
+
+            let mut expected = first.clone();
+            expected.sort();
+
+            assert_eq!(8, first.len());
+            assert_eq!(expected, first);
+
+            for _ in 0..3 {
+                let again = order(services.collection.search(search()).await.unwrap());
+                assert_eq!(first, again);
+            }
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn multi_search_merges_results_of_collections(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let ragu = "Raguru stirs the ragu slowly under the light of three moons.";
+        let moon = "Labamba paints the gura moon in silver every evening.";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let mut collections = vec![];
+
+            for (name, content) in [("Ragu", ragu), ("Moon", moon)] {
+                let collection = services
+                    .collection
+                    .create_collection(CreateCollectionPayload {
+                        name: format!("Multi_search_{name}_{provider}"),
+                        model: DEFAULT_MODELS
+                            .get()
+                            .unwrap()
+                            .get(embedder.id())
+                            .unwrap()
+                            .name
+                            .clone(),
+                        vector_provider: vector_db.id().to_string(),
+                        embedding_provider: embedder.id().to_string(),
+                        groups: None,
+                        modality: CollectionModality::Text,
+                        chunk_template: None,
+                        tags: None,
+                        insert_dedup_threshold: None,
+                    })
+                    .await
+                    .unwrap();
+
+                let vector = embedder
+                    .embed_text(&[content], &collection.model)
+                    .await
+                    .unwrap()
+                    .embeddings
+                    .remove(0);
+
+                vector_db
+                    .insert_embeddings(CollectionItemInsert::new_text(
+                        uuid::Uuid::new_v4(),
+                        &collection.name,
+                        &[content],
+                        vec![vector],
+                        None,
+                    ))
+                    .await
+                    .unwrap();
+
+                collections.push(collection.id);
+            }
+
+            for (query, expected) in [
+                (ragu, [(collections[0], ragu), (collections[1], moon)]),
+                (moon, [(collections[1], moon), (collections[0], ragu)]),
+            ] {
+                let result = services
+                    .collection
+                    .search_multi(MultiSearchPayload {
+                        query: query.to_string(),
+                        collection_ids: collections.clone(),
+                        limit: Some(10),
+                        max_distance: None,
+                        timeout: None,
+                        filter: None,
+                    })
+                    .await
+                    .unwrap();
+
+                assert!(result.timed_out.is_empty());
+
+                let items = result
+                    .items
+                    .iter()
+                    .map(|hit| (hit.collection_id, hit.item.item.payload.as_content()))
+                    .collect::<Vec<_>>();
+
+                let expected = expected
+                    .map(|(collection_id, content)| (collection_id, content.to_string()))
+                    .to_vec();
+
+                assert_eq!(expected, items);
+
+                let distances = result
+                    .items
+                    .iter()
+                    .map(|hit| hit.item.distance.unwrap())
+                    .collect::<Vec<_>>();
+
+                assert!(distances[0] <= distances[1]);
+            }
+
+            for collection_id in collections {
+                services
+                    .collection
+                    .delete_collection(collection_id)
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    async fn search_reorders_results_by_reranker_scores(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let chunks = [
+            "Raguru simmers his ragu for three days in a floating kitchen.",
+            "Gura adds a pinch of moon salt to every batch of ragu.",
+            "Travellers from distant galaxies line up to taste the ragu.",
+            "The bowls are carved from asteroid rock.",
+        ];
+
+        let reranking = services
+            .collection
+            .clone()
+            .with_reranker(Arc::new(StubReranker));
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Search_rerank_{provider}"),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let vectors = embedder
+                .embed_text(&chunks, &collection.model)
+                .await
+                .unwrap()
+                .embeddings;
+
+            vector_db
+                .insert_embeddings(CollectionItemInsert::new_text(
+                    uuid::Uuid::new_v4(),
+                    &collection.name,
+                    &chunks,
+                    vectors,
+                    None,
+                ))
+                .await
+                .unwrap();
+
+            let search = |rerank_model: Option<&str>| SearchPayload {
+                query: "Who makes ragu?".to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(3),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: rerank_model.map(String::from),
+                rerank_candidates: Some(4),
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let contents = |result: CollectionSearchResult| {
+                result
+                    .items
+                    .into_iter()
+                    .map(|hit| hit.item.payload.as_content())
+                    .collect::<Vec<_>>()
+            };
+
+            // The candidates in the order of the vector search
+            let candidates = services
+                .collection
+                .search(SearchPayload {
+                    limit: Some(4),
+                    ..search(None)
+                })
+                .await
+                .unwrap();
+            assert!(candidates
+                .items
+                .iter()
+                .all(|hit| hit.rerank_score.is_none()));
+
+            let vector_order = contents(candidates);
+            assert_eq!(4, vector_order.len());
+
+            let result = reranking.search(search(Some("stub"))).await.unwrap();
+
+            // The stub ranks the candidates in reverse, so only the nearest chunk is cut off
+            let scores = result
+                .items
+                .iter()
+                .map(|hit| hit.rerank_score.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(vec![3., 2., 1.], scores);
+
+            let expected = vector_order.into_iter().rev().take(3).collect::<Vec<_>>();
+            assert_eq!(expected, contents(result));
+
+            // Reranking requires a configured reranker
+            let error = services
+                .collection
+                .search(search(Some("stub")))
+                .await
+                .unwrap_err();
+            assert!(matches!(error.error, ChonkitErr::OperationUnsupported(_)));
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_filters_chunks_by_metadata(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let documents = [
+            (
+                "Raguru files the ragu trademark.",
+                serde_json::json!({ "category": "legal", "year": 2021 }),
+            ),
+            (
+                "Raguru signs the ragu kitchen lease.",
+                serde_json::json!({ "category": "legal", "year": 2018 }),
+            ),
+            (
+                "Raguru pays the ragu ingredient invoices.",
+                serde_json::json!({ "category": "finance", "year": 2022 }),
+            ),
+            (
+                "Raguru budgets for a bigger ragu pot.",
+                serde_json::json!({ "category": "finance", "year": 2015, "draft": true }),
+            ),
+        ];
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Search_filter_{provider}"),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            for (content, metadata) in documents.iter() {
+                let vectors = embedder
+                    .embed_text(&[*content], &collection.model)
+                    .await
+                    .unwrap()
+                    .embeddings;
+
+                vector_db
+                    .insert_embeddings(CollectionItemInsert::new_text(
+                        uuid::Uuid::new_v4(),
+                        &collection.name,
+                        &[*content],
+                        vectors,
+                        Some(metadata),
+                    ))
+                    .await
+                    .unwrap();
+            }
+
+            let search = |filter: serde_json::Value| {
+                let search = SearchPayload {
+                    query: "ragu".to_string(),
+                    collection_id: Some(collection.id),
+                    collection_alias: None,
+                    limit: Some(10),
+                    max_distance: None,
+                    rewrite: QueryRewrite::None,
+                    dedup_by_document: None,
+                    dedup_similarity: None,
+                    timeout: None,
+                    summary_collection_id: None,
+                    summary_documents: None,
+                    return_parent: None,
+                    highlight: None,
+                    debug: None,
+                    rerank_model: None,
+                    rerank_candidates: None,
+                    filter: Some(serde_json::from_value(filter).unwrap()),
+                    dimension_adapt: None,
+                };
+                services.collection.search(search)
+            };
+
+            let contents = |result: CollectionSearchResult| {
+                let mut contents = result
+                    .items
+                    .into_iter()
+                    .map(|hit| hit.item.payload.as_content())
+                    .collect::<Vec<_>>();
+                contents.sort();
+                contents
+            };
+
+            // (category = legal AND year >= 2020) OR (category = finance AND year < 2020)
+            let result = search(serde_json::json!({ "or": [
+                { "and": [
+                    { "field": "category", "op": "eq", "value": "legal" },
+                    { "field": "year", "op": "gte", "value": 2020 },
+                ] },
+                { "and": [
+                    { "field": "category", "op": "eq", "value": "finance" },
+                    { "field": "year", "op": "lt", "value": 2020 },
+                ] },
+            ] }))
+            .await
+            .unwrap();
+            assert_eq!(vec![documents[3].0, documents[0].0], contents(result));
+
+            let result = search(serde_json::json!({ "and": [
+                { "field": "category", "op": "ne", "value": "legal" },
+                { "field": "draft", "op": "ne", "value": true },
+            ] }))
+            .await
+            .unwrap();
+            assert_eq!(vec![documents[2].0], contents(result));
+
+            // No chunk has the field
+            let result = search(serde_json::json!(
+                { "field": "author.name", "op": "ne", "value": "Raguru" }
+            ))
+            .await
+            .unwrap();
+            assert_eq!(documents.len(), result.items.len());
+
+            let result = search(serde_json::json!(
+                { "field": "author.name", "op": "eq", "value": "Raguru" }
+            ))
+            .await
+            .unwrap();
+            assert!(result.items.is_empty());
+
+            let error = search(serde_json::json!({ "and": [] })).await.unwrap_err();
+            assert!(matches!(error.error, ChonkitErr::InvalidParameter(_)));
+
+            let error = search(serde_json::json!(
+                { "field": "category", "op": "gt", "value": "legal" }
+            ))
+            .await
+            .unwrap_err();
+            assert!(matches!(error.error, ChonkitErr::OperationUnsupported(_)));
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_adapts_query_dimension_when_requested(state: TestState) {
+        let service = |size: usize| {
+            let mut embedding = EmbeddingProvider::default();
+            embedding.register(Arc::new(
+                TestEmbedder::new(SIZED_EMBEDDER_ID, size).with_listed_model(SIZED_MODEL),
+            ));
+
+            let providers = ProviderState {
+                embedding,
+                ..state.app.providers.clone().into()
+            };
+
+            CollectionService::new(
+                state.app.providers.database.clone(),
+                providers,
+                Tokenizer::new(),
+            )
+        };
+
+        let original = service(8);
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = original
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Search_dimension_adapt_{provider}"),
+                    model: SIZED_MODEL.to_string(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: SIZED_EMBEDDER_ID.to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            vector_db
+                .insert_embeddings(CollectionItemInsert::new_text(
+                    uuid::Uuid::new_v4(),
+                    &collection.name,
+                    &["Raguru migrates his ragu recipes to a new model."],
+                    vec![vec![0.5; 8]],
+                    None,
+                ))
+                .await
+                .unwrap();
+
+            let search = |dimension_adapt: Option<bool>| SearchPayload {
+                query: "ragu".to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: Some(5),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt,
+            };
+
+            // The model now embeds into a larger and a smaller size
+            for size in [12, 4] {
+                let migrated = service(size);
+
+                assert!(migrated.search(search(None)).await.is_err());
+
+                let result = migrated.search(search(Some(true))).await.unwrap();
+                assert_eq!(1, result.items.len());
+                assert_eq!(Some(size), result.adapted_dimension);
+            }
+
+            // Queries of the collection's size are left as they are
+            let result = original.search(search(Some(true))).await.unwrap();
+            assert_eq!(1, result.items.len());
+            assert_eq!(None, result.adapted_dimension);
+
+            original.delete_collection(collection.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn updating_content_outdates_embeddings(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_update_content".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Raguru simmers the ragu.",
+                ))
+                .await
+                .unwrap();
+
+            let other = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_update_content_other".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Raguru serves the ragu.",
+                ))
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let outdated = |collection_id| async move {
+                services
+                    .embedding
+                    .list_outdated_embeddings(collection_id)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|embedding| embedding.document_id)
+                    .collect::<Vec<_>>()
+            };
+
+            assert!(!outdated(collection.id).await.contains(&document.id));
+
+            // Content of a different document is rejected
+            let result = services
+                .document
+                .update_content(document.id, b"Raguru serves the ragu.")
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::AlreadyExists(_)
+            ));
+
+            let updated = services
+                .document
+                .update_content(document.id, b"Raguru simmers the ragu for hours.")
+                .await
+                .unwrap();
+
+            assert_eq!(document.id, updated.id);
+            assert_ne!(document.hash, updated.hash);
+            assert!(outdated(collection.id).await.contains(&document.id));
+
+            let reports = services
+                .embedding
+                .refresh_text_embeddings(document.id)
+                .await
+                .unwrap();
+
+            assert_eq!(1, reports.len());
+            assert!(!outdated(collection.id).await.contains(&document.id));
+
+            services.document.delete(document.id).await.unwrap();
+            services.document.delete(other.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn embedding_auto_creates_collections(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let model = DEFAULT_MODELS
+            .get()
+            .unwrap()
+            .get(embedder.id())
+            .unwrap()
+            .name
+            .clone();
+
+        let document = services
+            .document
+            .upload(DocumentUpload::new(
+                "test_document_auto_create".to_string(),
+                DocumentType::Text(TextDocumentType::Txt),
+                b"Raguru opens a new kitchen.",
+            ))
+            .await
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+            let name = format!("Auto_created_{provider}");
+
+            // Unknown names are not created by default
+            let result = services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::by_name(document.id, &name))
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::DoesNotExist(_)
+            ));
+
+            let service = EmbeddingService::new(
+                state.app.providers.database.clone(),
+                state.app.providers.clone().into(),
+                state.embedding_cache.clone(),
+                state.image_embedding_cache.clone(),
+                Tokenizer::new(),
+            )
+            .with_auto_create_collections(AutoCreateCollections {
+                vector_provider: vector_db.id().to_string(),
+                embedding_provider: embedder.id().to_string(),
+                model: model.clone(),
+            });
+
+            // Names are still validated
+            let result = service
+                .create_text_embeddings(EmbedTextInput::by_name(document.id, "auto created"))
+                .await;
+
+            assert!(matches!(
+                result.unwrap_err().error,
+                ChonkitErr::Validation(_)
+            ));
+
+            let report = service
+                .create_text_embeddings(EmbedTextInput::by_name(document.id, &name))
+                .await
+                .unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(&name, vector_db.id())
+                .await
+                .unwrap();
+
+            assert_eq!(Some(collection.id), report.report.base.collection_id);
+            assert_eq!(model, collection.model);
+            assert_eq!(embedder.id(), collection.embedder);
+            assert_eq!(vector_db.id(), collection.provider);
+            assert!(vector_db.get_collection(&name).await.is_ok());
+
+            // Existing collections are reused
+            services
+                .embedding
+                .delete_text_embeddings(collection.id, document.id)
+                .await
+                .unwrap();
+
+            let report = service
+                .create_text_embeddings(EmbedTextInput::by_name(document.id, &name))
+                .await
+                .unwrap();
+
+            assert_eq!(Some(collection.id), report.report.base.collection_id);
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+        }
+
+        services.document.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn search_applies_default_max_distance(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let service = CollectionService::new(
+            state.app.providers.database.clone(),
+            state.app.providers.clone().into(),
+            Tokenizer::new(),
+        )
+        .with_search_defaults(5, Some(0.1));
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let content = "Hello World!";
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_search_defaults".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput {
+                    document: document.id,
+                    collection: Some(collection.id),
+                    collection_name: None,
+                    detailed: false,
+                    embed_metadata: false,
+                    no_cache: false,
+                })
+                .await
+                .unwrap();
+
+            let search = |query: &str, max_distance| SearchPayload {
+                query: query.to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: None,
+                max_distance,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let unrelated = "Quarterly tax filing deadlines for agricultural cooperatives";
+
+            // Exact matches are within the default threshold
+            let results = service.search(search(content, None)).await.unwrap();
+            assert!(results
+                .items
+                .iter()
+                .any(|item| item.item.payload.as_content() == content));
+
+            // Low similarity results are excluded when the threshold is omitted
+            let results = service.search(search(unrelated, None)).await.unwrap();
+            assert!(results.items.is_empty());
+
+            // The default is overridable per request
+            let results = service.search(search(unrelated, Some(2.))).await.unwrap();
+            assert!(!results.items.is_empty());
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn search_diagnostics_report_filtered_results(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_search_diagnostics".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Hello World!",
+                ))
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let search = |debug| SearchPayload {
+                query: "Quarterly tax filing deadlines for agricultural cooperatives".to_string(),
+                collection_id: Some(collection.id),
+                collection_alias: None,
+                limit: None,
+                max_distance: Some(0.1),
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let results = services.collection.search(search(None)).await.unwrap();
+            assert!(results.items.is_empty());
+            assert!(results.diagnostics.is_none());
+
+            let results = services
+                .collection
+                .search(search(Some(true)))
+                .await
+                .unwrap();
+            assert!(results.items.is_empty());
+
+            let diagnostics = results.diagnostics.unwrap();
+            assert!(diagnostics.candidates > 0);
+            assert!(diagnostics.best_filtered_distance.unwrap() > 0.1);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn batch_embedder_shutdown_leaves_no_partial_embeddings(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let mut documents = vec![];
+
+            for i in 0..5 {
+                let document = services
+                    .document
+                    .upload(DocumentUpload::new(
+                        format!("test_document_shutdown_{i}"),
+                        DocumentType::Text(TextDocumentType::Txt),
+                        format!("Raguru serves ragu number {i} to his guests.").as_bytes(),
+                    ))
+                    .await
+                    .unwrap();
+                documents.push(document.id);
+            }
+
+            let handle = batch::start_batch_embedder(services.clone());
+
+            let (tx, mut rx) = tokio::sync::mpsc::channel(documents.len() + 1);
+
+            handle
+                .send(BatchJob::new(collection.id, documents.clone(), vec![], tx))
+                .await
+                .unwrap();
+
+            // Shut down as soon as the first document is embedded
+            let Some(BatchJobResult::Event(event)) = rx.recv().await else {
+                panic!("expected a job event");
+            };
+            assert!(event.result.is_ok());
+
+            handle.shutdown();
+
+            while let Some(result) = rx.recv().await {
+                if let BatchJobResult::Done(_) = result {
+                    break;
+                }
+            }
+            drop(rx);
+
+            assert!(handle.drained(Duration::from_secs(30)).await);
+
+            let (tx, _rx) = tokio::sync::mpsc::channel(1);
+            assert!(handle
+                .send(BatchJob::new(collection.id, documents.clone(), vec![], tx))
+                .await
+                .is_err());
+
+            // Every document is either fully embedded or not embedded at all
+            for document_id in documents {
+                let embeddings = postgres
+                    .get_text_embeddings(document_id, collection.id)
+                    .await
+                    .unwrap();
+
+                let vectors = vector_db
+                    .count_vectors(&collection.name, document_id)
+                    .await
+                    .unwrap();
+
+                assert_eq!(embeddings.is_some(), vectors > 0);
+
+                services.document.delete(document_id).await.unwrap();
+            }
+        }
+    }
+
+    #[test]
+    async fn batch_embedder_skips_existing_embeddings(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    provider,
+                )
+                .await
+                .unwrap();
+
+            let mut documents = vec![];
+
+            for i in 0..3 {
+                let document = services
+                    .document
+                    .upload(DocumentUpload::new(
+                        format!("test_document_skip_existing_{i}"),
+                        DocumentType::Text(TextDocumentType::Txt),
+                        format!("Raguru reheats ragu number {i} for his guests.").as_bytes(),
+                    ))
+                    .await
+                    .unwrap();
+                documents.push(document.id);
+            }
+
+            let handle = &batch::start_batch_embedder(services.clone());
+            let documents = &documents;
+            let collection_id = collection.id;
+
+            // Collect the reports of every document in the job
+            let run = || async move {
+                let (tx, mut rx) = tokio::sync::mpsc::channel(documents.len() + 1);
+
+                handle
+                    .send(
+                        BatchJob::new(collection_id, documents.clone(), vec![], tx)
+                            .with_skip_existing(true),
+                    )
+                    .await
+                    .unwrap();
+
+                let mut reports = vec![];
+                while let Some(BatchJobResult::Event(event)) = rx.recv().await {
+                    reports.push(event.result.unwrap());
+                }
+                reports
+            };
+
+            let first = run().await;
+            assert_eq!(documents.len(), first.len());
+            assert!(first
+                .iter()
+                .all(|report| matches!(report, EmbeddingReportType::TextAddition(_))));
+
+            let created_at = postgres
+                .get_text_embeddings(documents[0], collection.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .created_at;
+
+            let second = run().await;
+            assert_eq!(documents.len(), second.len());
+            for (report, document_id) in second.iter().zip(documents.iter()) {
+                let EmbeddingReportType::TextSkipped(report) = report else {
+                    panic!("expected skip report, got {report:?}");
+                };
+                assert_eq!(*document_id, report.document_id);
+                assert_eq!(collection.id, report.collection_id);
+            }
+
+            // Nothing was re-embedded
+            let embeddings = postgres
+                .get_text_embeddings(documents[0], collection.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(created_at, embeddings.created_at);
+
+            for document_id in documents {
+                services.document.delete(*document_id).await.unwrap();
+            }
+        }
+    }
+
+    #[test]
+    async fn batch_embedder_retries_transient_failures(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let flaky = Arc::new(TestEmbedder::wrap(embedder.clone()).with_flaky_marker("moonlit"));
+
+        let mut embedding = EmbeddingProvider::default();
+        embedding.register(flaky.clone());
+
+        let providers = ProviderState {
+            embedding,
+            ..state.app.providers.clone().into()
+        };
+
+        let flaky_services = ServiceState {
+            embedding: EmbeddingService::new(
+                state.app.providers.database.clone(),
+                providers,
+                state.embedding_cache.clone(),
+                state.image_embedding_cache.clone(),
+                Tokenizer::new(),
+            ),
+            ..services.clone()
+        };
+
+        let handle = batch::start_batch_embedder(flaky_services);
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let mut documents = vec![];
+
+            // The provider keeps the content out of the embedding cache of other runs
+            for (i, content) in [
+                format!("Raguru serves moonlit ragu at dusk ({provider})."),
+                format!("Raguru serves ragu at dawn ({provider})."),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                let document = services
+                    .document
+                    .upload(DocumentUpload::new(
+                        format!("test_document_retry_{i}"),
+                        DocumentType::Text(TextDocumentType::Txt),
+                        content.as_bytes(),
+                    ))
+                    .await
+                    .unwrap();
+                documents.push(document.id);
+            }
+
+            flaky.arm();
+
+            let (tx, mut rx) = tokio::sync::mpsc::channel(documents.len() + 1);
+
+            handle
+                .send(BatchJob::new(collection.id, documents.clone(), vec![], tx).with_retries(1))
+                .await
+                .unwrap();
+
+            let mut reports = vec![];
+            while let Some(BatchJobResult::Event(event)) = rx.recv().await {
+                reports.push(event.result.unwrap());
+            }
+
+            assert!(flaky.fired());
+
+            // The failed document is reported after the rest of the batch
+            let embedded = reports
+                .iter()
+                .map(|report| {
+                    let EmbeddingReportType::TextAddition(report) = report else {
+                        panic!("expected addition report, got {report:?}");
+                    };
+                    report.document_id
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(vec![documents[1], documents[0]], embedded);
+
+            for document_id in documents {
+                let embeddings = postgres
+                    .get_text_embeddings(document_id, collection.id)
+                    .await
+                    .unwrap();
+
+                let vectors = vector_db
+                    .count_vectors(&collection.name, document_id)
+                    .await
+                    .unwrap();
+
+                assert!(embeddings.is_some());
+                assert!(vectors > 0);
+
+                services.document.delete(document_id).await.unwrap();
+            }
+        }
+    }
+
+    #[test]
+    async fn fanout_embeds_into_same_model_collections(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let default = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let mirror = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: "Test_collection_fanout".to_string(),
+                    model: default.model.clone(),
+                    vector_provider: provider.to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_fanout".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Raguru mirrors his ragu. It is served in two galaxies.",
+                ))
+                .await
+                .unwrap();
+
+            let reports = services
+                .embedding
+                .create_text_embeddings_fanout(EmbedFanoutInput {
+                    document: document.id,
+                    collections: vec![default.id, mirror.id],
+                    detailed: false,
+                    embed_metadata: false,
+                })
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+
+            assert_eq!(2, reports.len());
+
+            assert_eq!(Some(default.id), reports[0].report.base.collection_id);
+            assert_eq!(Some(mirror.id), reports[1].report.base.collection_id);
+
+            // The second collection reuses the chunks and embeddings of the first
+            assert!(!reports[0].report.cache);
+            assert!(reports[1].report.cache);
+            assert_eq!(Some(0), reports[1].report.tokens_used);
+            assert_eq!(
+                reports[0].report.total_vectors,
+                reports[1].report.total_vectors
+            );
+
+            for collection in [&default, &mirror] {
+                let count = vector_db
+                    .count_vectors(&collection.name, document.id)
+                    .await
+                    .unwrap();
+                assert_eq!(reports[0].report.total_vectors as usize, count);
+            }
+
+            services.document.delete(document.id).await.unwrap();
+            services
+                .collection
+                .delete_collection(mirror.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn deleting_document_embeddings_keeps_document(state: TestState) {
+        let url = state.serve_routes().await;
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let default = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let other = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: "Test_collection_unembed".to_string(),
+                    model: default.model.clone(),
+                    vector_provider: provider.to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_unembed".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Raguru unembeds his ragu. The recipe stays in the cookbook.",
+                ))
+                .await
+                .unwrap();
+
+            for collection in [&default, &other] {
+                services
+                    .embedding
+                    .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                    .await
+                    .unwrap();
+            }
+
+            let response = reqwest::Client::new()
+                .delete(format!("{url}/documents/{}/embeddings", document.id))
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(StatusCode::OK, response.status());
+
+            let reports: Vec<serde_json::Value> = response.json().await.unwrap();
+            assert_eq!(2, reports.len());
+
+            for collection in [&default, &other] {
+                assert!(reports.iter().any(|report| {
+                    report["report"]["collection_id"] == collection.id.to_string()
+                        && report["text"] == true
+                }));
+
+                let count = vector_db
+                    .count_vectors(&collection.name, document.id)
+                    .await
+                    .unwrap();
+                assert_eq!(0, count);
+
+                assert!(state
+                    .app
+                    .providers
+                    .database
+                    .get_text_embeddings(document.id, collection.id)
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+
+            let stored = services.document.get_document(document.id).await.unwrap();
+            assert_eq!(document.id, stored.id);
+
+            services.document.delete(document.id).await.unwrap();
+            services
+                .collection
+                .delete_collection(other.id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn deleting_collection_removes_all_embeddings(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap()
+            .clone();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection_name = "Test_collection_delete_embeddings";
+
+            let collection = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: collection_name.to_string(),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                })
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_420".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Hello, world! 420",
+                ))
+                .await
+                .unwrap();
+
+            let embeddings = EmbedTextInput {
+                document: document.id,
+                collection: Some(collection.id),
+                collection_name: None,
+                detailed: false,
+                embed_metadata: false,
+                no_cache: false,
+            };
+
+            services
+                .embedding
+                .create_text_embeddings(embeddings)
+                .await
+                .unwrap();
+
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+
+            let embeddings = postgres
+                .get_text_embeddings(document.id, collection.id)
+                .await
+                .unwrap();
+
+            assert!(embeddings.is_none());
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn prevents_duplicate_embeddings(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap()
+            .clone();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+            let collection_name =
+                format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id());
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_42069".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Hello, world! 42069",
+                ))
+                .await
+                .unwrap();
+
+            let default = services
+                .collection
+                .get_collection_by_name(&collection_name, vector_db.id())
+                .await
+                .unwrap();
+
+            let create = EmbedTextInput {
+                document: document.id,
+                collection: Some(default.id),
+                collection_name: None,
+                detailed: false,
+                embed_metadata: false,
+                no_cache: false,
+            };
+
+            services
+                .embedding
+                .create_text_embeddings(create.clone())
+                .await
+                .unwrap();
+
+            let duplicate = services.embedding.create_text_embeddings(create).await;
+            let error = duplicate.unwrap_err().error;
+
+            assert!(matches!(error, ChonkitErr::AlreadyExists(_)));
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn failed_commit_leaves_no_orphan_vectors(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap()
+            .clone();
+
+        let document = services
+            .document
+            .upload(DocumentUpload::new(
+                "test_document_failed_commit".to_string(),
+                DocumentType::Text(TextDocumentType::Txt),
+                b"Raguru's kitchen lost power mid ragu.",
+            ))
+            .await
+            .unwrap();
+
+        // Deferred constraint triggers run on commit, so raising in one fails the commit
+        // after the vectors have already been inserted.
+        let inject = [
+            r#"CREATE OR REPLACE FUNCTION fail_commit() RETURNS trigger AS $$
+               BEGIN RAISE EXCEPTION 'injected commit failure'; END
+               $$ LANGUAGE plpgsql"#
+                .to_string(),
+            format!(
+                r#"CREATE CONSTRAINT TRIGGER fail_commit AFTER INSERT ON embeddings
+                   DEFERRABLE INITIALLY DEFERRED FOR EACH ROW
+                   WHEN (NEW.document_id = '{}') EXECUTE FUNCTION fail_commit()"#,
+                document.id
+            ),
+        ];
+
+        for statement in inject {
+            sqlx::query(&statement)
+                .execute(&postgres.client)
+                .await
+                .unwrap();
+        }
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+            let collection_name =
+                format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id());
+
+            let collection = services
+                .collection
+                .get_collection_by_name(&collection_name, vector_db.id())
+                .await
+                .unwrap();
+
+            let error = services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap_err()
+                .error;
+
+            assert!(matches!(error, ChonkitErr::Sqlx(_)));
+
+            let embeddings = postgres
+                .get_text_embeddings(document.id, collection.id)
+                .await
+                .unwrap();
+
+            assert!(embeddings.is_none());
+
+            let vectors = vector_db
+                .count_vectors(&collection.name, document.id)
+                .await
+                .unwrap();
+
+            assert_eq!(0, vectors);
+        }
+
+        for statement in [
+            "DROP TRIGGER fail_commit ON embeddings",
+            "DROP FUNCTION fail_commit",
+        ] {
+            sqlx::query(statement)
+                .execute(&postgres.client)
+                .await
+                .unwrap();
+        }
+
+        services.document.delete(document.id).await.unwrap();
+    }
+
+    #[test]
+    async fn document_is_reconstructed_from_stored_chunks(state: TestState) {
+        let url = state.serve_routes().await;
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let content = "Raguru cooks ragu in his floating kitchen above Gura. \
+                       The crew gathers on the deck while the sauce simmers for hours. \
+                       Once the three moons rise, everyone eats and asks for seconds.";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_reconstruct".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .document
+                .update_chunker(document.id, None, ChunkConfig::sliding(40, 8).unwrap())
+                .await
+                .unwrap();
+
+            let report = services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let response = reqwest::Client::new()
+                .get(format!(
+                    "{url}/collections/{}/documents/{}/reconstruct",
+                    collection.id, document.id
+                ))
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(StatusCode::OK, response.status());
+
+            let reconstructed: serde_json::Value = response.json().await.unwrap();
+
+            assert_eq!(content, reconstructed["content"]);
+            assert_eq!(
+                report.report.total_vectors as u64,
+                reconstructed["chunks"].as_u64().unwrap()
+            );
+            assert_eq!(serde_json::json!([]), reconstructed["missing"]);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn chunk_ids_are_stable_across_re_embeds(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let content = "Raguru labels every jar of ragu before it leaves the kitchen. \
+                       Gura keeps a ledger of the labels so no jar is ever lost.";
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_chunk_ids".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .document
+                .update_chunker(document.id, None, ChunkConfig::sliding(40, 8).unwrap())
+                .await
+                .unwrap();
+
+            let (collection_id, document_id) = (collection.id, document.id);
+
+            // The chunk IDs of the document returned by a search, by chunk index
+            let search_chunk_ids = || async move {
+                let result = services
+                    .collection
+                    .search(SearchPayload {
+                        query: content.to_string(),
+                        collection_id: Some(collection_id),
+                        collection_alias: None,
+                        limit: Some(100),
+                        max_distance: None,
+                        rewrite: QueryRewrite::None,
+                        dedup_by_document: None,
+                        dedup_similarity: None,
+                        timeout: None,
+                        summary_collection_id: None,
+                        summary_documents: None,
+                        return_parent: None,
+                        highlight: None,
+                        debug: None,
+                        rerank_model: None,
+                        rerank_candidates: None,
+                        filter: None,
+                        dimension_adapt: None,
+                    })
+                    .await
+                    .unwrap();
+
+                let mut ids = result
+                    .items
+                    .into_iter()
+                    .filter(|hit| {
+                        hit.item.document_id == Some(document_id)
+                            && !hit.item.payload.is_synthetic()
+                    })
+                    .map(|hit| {
+                        (
+                            hit.item.payload.chunk_index().unwrap(),
+                            hit.chunk_id.unwrap(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids
+            };
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let first = search_chunk_ids().await;
+            assert!(first.len() > 1);
+
+            for (index, id) in first.iter() {
+                assert_eq!(chunk_id(document.id, *index), *id);
+            }
+
+            let reconstructed = services
+                .embedding
+                .reconstruct_document(collection.id, document.id)
+                .await
+                .unwrap();
+            assert_eq!(
+                first.iter().map(|(_, id)| *id).collect::<Vec<_>>(),
+                reconstructed.chunk_ids
+            );
+
+            services
+                .embedding
+                .delete_text_embeddings(collection.id, document.id)
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            assert_eq!(first, search_chunk_ids().await);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn collection_model_is_immutable_once_embedded(state: TestState) {
+        let services = &state.app.services;
+        let repo = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_model_immutable".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Raguru never swaps the ragu recipe once the pot is on the stove.",
+                ))
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            let error = repo
+                .update_collection_model(collection.id, "raguru-model", embedder.id())
+                .await
+                .unwrap_err();
+            assert!(matches!(error.error, ChonkitErr::ModelImmutable(_)));
+            assert_eq!(StatusCode::CONFLICT, error.status());
+
+            // Setting the current model is not a change
+            repo.update_collection_model(collection.id, &collection.model, &collection.embedder)
+                .await
+                .unwrap();
+
+            let unchanged = repo
+                .get_collection_by_id(collection.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(collection.model, unchanged.model);
+            assert_eq!(collection.embedder, unchanged.embedder);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn collection_manifest_recreates_collection(state: TestState) {
+        let url = state.serve_routes().await;
+        let client = reqwest::Client::new();
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .create_collection(CreateCollectionPayload {
+                    name: format!("Manifest_{provider}"),
+                    model: DEFAULT_MODELS
+                        .get()
+                        .unwrap()
+                        .get(embedder.id())
+                        .unwrap()
+                        .name
+                        .clone(),
+                    vector_provider: vector_db.id().to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: Some(vec!["chefs".to_string()]),
+                    modality: CollectionModality::Text,
+                    chunk_template: Some("Document: {name}\n\n{chunk}".to_string()),
+                    tags: Some(vec!["project:ragu".to_string()]),
+                    insert_dedup_threshold: Some(0.9),
+                })
+                .await
+                .unwrap();
+
+            let response = client
+                .get(format!("{url}/collections/{}/manifest", collection.id))
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(StatusCode::OK, response.status());
+
+            let manifest: serde_json::Value = response.json().await.unwrap();
+
+            assert_eq!("cosine", manifest["metric"]);
+            assert_eq!(serde_json::json!(["chefs"]), manifest["groups"]);
+
+            // Recreate it from scratch
+            services
+                .collection
+                .delete_collection(collection.id)
+                .await
+                .unwrap();
+
+            let response = client
+                .post(format!("{url}/collections/from-manifest"))
+                .json(&manifest)
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(StatusCode::CREATED, response.status());
+
+            let created: serde_json::Value = response.json().await.unwrap();
+            let created_id = created["id"].as_str().unwrap().parse().unwrap();
+
+            assert_ne!(collection.id, created_id);
+
+            let recreated = services
+                .collection
+                .get_collection(created_id)
+                .await
+                .unwrap();
+
+            assert_eq!(collection.name, recreated.collection.name);
+            assert_eq!(collection.model, recreated.collection.model);
+            assert_eq!(collection.embedder, recreated.collection.embedder);
+            assert_eq!(collection.provider, recreated.collection.provider);
+            assert_eq!(collection.multimodal, recreated.collection.multimodal);
+            assert_eq!(
+                collection.chunk_template,
+                recreated.collection.chunk_template
+            );
+            assert_eq!(collection.tags, recreated.collection.tags);
+            assert_eq!(
+                collection.insert_dedup_threshold,
+                recreated.collection.insert_dedup_threshold
+            );
+            assert_eq!(
+                Some(vec!["chefs".to_string()]),
+                recreated.vector_collection.groups
+            );
+
+            let manifest_again = services
+                .collection
+                .get_collection_manifest(created_id)
+                .await
+                .unwrap();
+
+            assert_eq!(manifest, serde_json::to_value(manifest_again).unwrap());
+
+            // Names are validated as for new collections
+            let mut invalid = manifest.clone();
+            invalid["name"] = "manifest with spaces".into();
+
+            let response = client
+                .post(format!("{url}/collections/from-manifest"))
+                .json(&invalid)
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+            services
+                .collection
+                .delete_collection(created_id)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    async fn exported_collection_imports_with_search_parity(state: TestState) {
+        let services = &state.app.services;
+        let postgres = &state.app.providers.database;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap()
+            .clone();
+
+        let model = DEFAULT_MODELS
+            .get()
+            .unwrap()
+            .get(embedder.id())
+            .unwrap()
+            .name
+            .clone();
+
+        for provider in state.active_vector_providers.iter() {
+            let mut collections = vec![];
+
+            for name in ["Export_source", "Export_target"] {
+                let create = CreateCollectionPayload {
+                    name: format!("{name}_{provider}"),
+                    model: model.clone(),
+                    vector_provider: provider.to_string(),
+                    embedding_provider: embedder.id().to_string(),
+                    groups: None,
+                    modality: CollectionModality::Text,
+                    chunk_template: None,
+                    tags: None,
+                    insert_dedup_threshold: None,
+                };
+
+                collections.push(services.collection.create_collection(create).await.unwrap());
+            }
+
+            let (source, target) = (&collections[0], &collections[1]);
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_export".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    b"Raguru exports his finest recipes before moving kitchens.",
+                ))
+                .await
+                .unwrap();
+
+            let metadata = serde_json::json!({ "kitchen": "floating" });
+
+            let document = services
+                .document
+                .update_document_metadata(
+                    document.id,
+                    DocumentMetadataUpdate {
+                        name: Some(&document.name),
+                        label: None,
+                        tags: None,
+                        metadata: Some(&metadata),
+                    },
+                )
+                .await
+                .unwrap();
+
+            services
+                .embedding
+                .create_text_embeddings(EmbedTextInput {
+                    document: document.id,
+                    collection: Some(source.id),
+                    collection_name: None,
+                    detailed: false,
+                    embed_metadata: true,
+                    no_cache: false,
+                })
+                .await
+                .unwrap();
+
+            let exported: Vec<_> = services
+                .collection
+                .export_collection(source.id)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+
+            assert!(!exported.is_empty());
+            assert!(exported.iter().all(|v| v.document_id == document.id));
+
+            // The metadata chunk keeps its flag and is not given an index
+            let synthetic: Vec<_> = exported.iter().filter(|v| v.synthetic).collect();
+            assert_eq!(1, synthetic.len());
+            assert!(synthetic[0].chunk_index.is_none());
+
+            let result = services
+                .collection
+                .import_collection(
+                    target.id,
+                    futures_util::stream::iter(exported.clone().into_iter().map(Ok)),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(exported.len(), result.total_vectors);
+            assert_eq!(1, result.total_documents);
+
+            let search = |collection_id| SearchPayload {
+                query: "finest recipes".to_string(),
+                collection_id: Some(collection_id),
+                collection_alias: None,
+                limit: Some(5),
+                max_distance: None,
+                rewrite: QueryRewrite::None,
+                dedup_by_document: None,
+                dedup_similarity: None,
+                timeout: None,
+                summary_collection_id: None,
+                summary_documents: None,
+                return_parent: None,
+                highlight: None,
+                debug: None,
+                rerank_model: None,
+                rerank_candidates: None,
+                filter: None,
+                dimension_adapt: None,
+            };
+
+            let expected = services.collection.search(search(source.id)).await.unwrap();
+            let actual = services.collection.search(search(target.id)).await.unwrap();
+
+            assert_eq!(expected.items.len(), actual.items.len());
+
+            for (expected, actual) in expected.items.iter().zip(actual.items.iter()) {
+                assert_eq!(expected.item.document_id, actual.item.document_id);
+                assert_eq!(
+                    expected.item.payload.as_content(),
+                    actual.item.payload.as_content()
+                );
+                assert_eq!(
+                    expected.item.payload.chunk_index(),
+                    actual.item.payload.chunk_index()
+                );
+                assert_eq!(
+                    expected.item.payload.is_synthetic(),
+                    actual.item.payload.is_synthetic()
+                );
+                assert_eq!(Some(&metadata), actual.item.metadata.as_ref());
+            }
+
+            let embeddings = postgres
+                .get_text_embeddings(document.id, target.id)
+                .await
+                .unwrap();
+
+            assert!(embeddings.is_some());
+
+            services.document.delete(document.id).await.unwrap();
+
+            for collection in collections {
+                services
+                    .collection
+                    .delete_collection(collection.id)
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    async fn semantic_chunking_falls_back_when_embedder_fails(state: TestState) {
+        let services = &state.app.services;
+        let embedder = state
+            .app
+            .providers
+            .embedding
+            .get_provider(FEMBED_EMBEDDER_ID)
+            .unwrap();
+
+        let mut embedding = EmbeddingProvider::default();
+        embedding.register(embedder.clone());
+        embedding.register(Arc::new(
+            TestEmbedder::new(UNAVAILABLE_EMBEDDER_ID, 8).unavailable(),
+        ));
+
+        let providers = ProviderState {
+            embedding,
+            ..state.app.providers.clone().into()
+        };
+
+        let service = EmbeddingService::new(
+            state.app.providers.database.clone(),
+            providers.clone(),
+            state.embedding_cache.clone(),
+            state.image_embedding_cache.clone(),
+            Tokenizer::new(),
+        );
+
+        let content = "Raguru simmers his ragu in a floating kitchen. \
+            The kitchen drifts between the rings of Saturn. \
+            Gura tastes every batch and adds a pinch of salt from the moon.";
+
+        let semantic = ChunkConfig::semantic_default(
+            UNAVAILABLE_EMBEDDER_ID.to_string(),
+            "local-model".to_string(),
+        );
+
+        let to_vec = |chunks: ChunkedDocument<'_>| match chunks {
+            ChunkedDocument::Ref(r) => r.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            ChunkedDocument::Owned(o) => o,
+        };
+
+        // Without a fallback the error is returned
+        let result = chunk::chunk(&providers, semantic.clone(), content, usize::MAX).await;
+        assert!(result.is_err());
+
+        let semantic = semantic.with_fallback(ChunkConfig::snapping_default());
+
+        let chunked = chunk::chunk(&providers, semantic.clone(), content, usize::MAX)
+            .await
+            .unwrap();
+
+        let snapping = chunk::chunk(
+            &providers,
+            ChunkConfig::snapping_default(),
+            content,
+            usize::MAX,
+        )
+        .await
+        .unwrap();
+
+        assert!(chunked.fallback);
+        assert!(!snapping.fallback);
+        assert_eq!(to_vec(snapping.chunks), to_vec(chunked.chunks));
+
+        for provider in state.active_vector_providers.iter() {
+            let vector_db = state.app.providers.vector.get_provider(provider).unwrap();
+
+            let collection = services
+                .collection
+                .get_collection_by_name(
+                    &format!("{DEFAULT_COLLECTION_NAME}_{}_{}", provider, embedder.id()),
+                    vector_db.id(),
+                )
+                .await
+                .unwrap();
+
+            let document = services
+                .document
+                .upload(DocumentUpload::new(
+                    "test_document_chunk_fallback".to_string(),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    content.as_bytes(),
+                ))
+                .await
+                .unwrap();
+
+            services
+                .document
+                .update_chunker(document.id, None, semantic.clone())
+                .await
+                .unwrap();
+
+            let report = service
+                .create_text_embeddings(EmbedTextInput::new(document.id, collection.id))
+                .await
+                .unwrap();
+
+            assert!(report.chunk_fallback);
+            assert!(!report.report.cache);
+
+            services.document.delete(document.id).await.unwrap();
+        }
+    }
+
+    const SIZED_MODEL: &str = "sized-model";
+
+    const SIZED_EMBEDDER_ID: &str = "sized";
+
+    const UNAVAILABLE_EMBEDDER_ID: &str = "unavailable";
+
+    /// Scores documents by their position, ranking the last one first.
+    struct StubReranker;
+
+    #[async_trait::async_trait]
+    impl Reranker for StubReranker {
+        async fn rerank(
+            &self,
+            _model: &str,
+            _query: &str,
+            documents: &[&str],
+        ) -> Result<Vec<f64>, ChonkitError> {
+            Ok((0..documents.len()).map(|i| i as f64).collect())
+        }
+    }
+
+    const STUB_COMPLETION: &str = "Raguru seasons his ragu with stardust and a pinch of salt.";
+
+    /// Responds to every prompt with [STUB_COMPLETION].
+    struct StubLlm;
+
+    #[async_trait::async_trait]
+    impl Llm for StubLlm {
+        async fn complete(
+            &self,
+            _instructions: &str,
+            _prompt: &str,
+        ) -> Result<String, ChonkitError> {
+            Ok(STUB_COMPLETION.to_string())
+        }
+    }
+
+    /// Responds to every prompt with the prompt itself.
+    struct EchoLlm;
+
+    #[async_trait::async_trait]
+    impl Llm for EchoLlm {
+        async fn complete(
+            &self,
+            _instructions: &str,
+            prompt: &str,
+        ) -> Result<String, ChonkitError> {
+            Ok(prompt.to_string())
+        }
+    }
+
+    /// Records the prompts it completes and responds with [STUB_COMPLETION] in two parts.
+    #[derive(Default)]
+    struct RecordingLlm {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl RecordingLlm {
+        /// Return the recorded prompts and clear them.
+        fn take_prompts(&self) -> Vec<String> {
+            std::mem::take(&mut *self.prompts.lock().unwrap())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Llm for RecordingLlm {
+        async fn complete(
+            &self,
+            _instructions: &str,
+            prompt: &str,
+        ) -> Result<String, ChonkitError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(STUB_COMPLETION.to_string())
+        }
+
+        async fn complete_stream(
+            &self,
+            instructions: &str,
+            prompt: &str,
+        ) -> Result<BoxStream<'static, Result<String, ChonkitError>>, ChonkitError> {
+            let completion = self.complete(instructions, prompt).await?;
+            let (head, tail) = completion.split_at(completion.len() / 2);
+            let parts = vec![Ok(head.to_string()), Ok(tail.to_string())];
+            Ok(futures_util::stream::iter(parts).boxed())
+        }
+    }
 }
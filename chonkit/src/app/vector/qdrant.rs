@@ -1,21 +1,32 @@
 use crate::config::QDRANT_ID;
 use crate::core::provider::Identity;
 use crate::core::vector::{
-    CollectionItemInsert, CollectionItemInsertPayload, CollectionSearchItem,
-    CreateVectorCollection, VectorCollection, VectorDb, COLLECTION_EMBEDDING_MODEL_PROPERTY,
-    COLLECTION_EMBEDDING_PROVIDER_PROPERTY, COLLECTION_GROUPS_PROPERTY, COLLECTION_ID_PROPERTY,
-    COLLECTION_NAME_PROPERTY, COLLECTION_SIZE_PROPERTY, CONTENT_PROPERTY, DOCUMENT_ID_PROPERTY,
-    IMAGE_ID_PROPERTY,
+    validate_vectors, CollectionItem, CollectionItemImage, CollectionItemInsert,
+    CollectionItemInsertPayload, CollectionItemPayload, CollectionItemText, CollectionSearchItem,
+    CreateVectorCollection, ExportedVector, FilterCondition, FilterExpr, FilterOp, FilterValue,
+    VectorCollection, VectorDb, VectorPage, CHUNK_INDEX_PROPERTY,
+    COLLECTION_EMBEDDING_MODEL_PROPERTY, COLLECTION_EMBEDDING_PROVIDER_PROPERTY,
+    COLLECTION_GROUPS_PROPERTY, COLLECTION_ID_PROPERTY, COLLECTION_NAME_PROPERTY,
+    COLLECTION_SIZE_PROPERTY, CONTENT_PROPERTY, DOCUMENT_ID_PROPERTY, IMAGE_B64_PROPERTY,
+    IMAGE_DESCRIPTION_PROPERTY, IMAGE_ID_PROPERTY, IMAGE_PATH_PROPERTY, LIST_DOCUMENT_PAGE_SIZE,
+    METADATA_PROPERTY, PARENT_CONTENT_PROPERTY, PARENT_INDEX_PROPERTY, SYNTHETIC_PROPERTY,
 };
 use crate::error::ChonkitError;
 use crate::{err, map_err};
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use qdrant_client::qdrant::r#match::MatchValue;
+use qdrant_client::qdrant::vector_output;
 use qdrant_client::qdrant::vectors_config::Config;
+use qdrant_client::qdrant::vectors_output::VectorsOptions;
 use qdrant_client::qdrant::with_payload_selector::SelectorOptions;
 use qdrant_client::qdrant::{
     value, Condition, CreateCollection, DeletePointsBuilder, Distance, Filter,
-    GetCollectionInfoResponse, PointStruct, SearchParams, SearchPoints, UpsertPointsBuilder,
-    VectorParams, VectorsConfig, WithPayloadSelector,
+    GetCollectionInfoResponse, GetPointsBuilder, MultiVectorConfig, PointId, PointStruct, Range,
+    ScoredPoint, ScrollPointsBuilder, SearchParams, SearchPoints, UpsertPointsBuilder, Value,
+    Vector, VectorOutput, VectorParams, VectorsConfig, WithPayloadSelector,
 };
+#[cfg(feature = "multivector")]
+use qdrant_client::qdrant::{MultiVectorComparator, Query, QueryPointsBuilder, VectorInput};
 use qdrant_client::{Payload, Qdrant, QdrantError};
 use serde_json::json;
 use std::collections::HashMap;
@@ -69,13 +80,13 @@ impl VectorDb for Qdrant {
         for name in collection_names {
             match self.collection_info(&name).await {
                 Ok(info) => {
-                    let Some(size) = get_collection_size(&info) else {
+                    if get_collection_size(&info).is_none() {
                         tracing::error!("Failed to get collection size: {info:?}");
                         results.push(err!(Qdrant, "{name}"));
                         continue;
-                    };
+                    }
 
-                    match get_id_vector(self, &name, size).await {
+                    match get_id_vector(self, &name).await {
                         Ok(info) => results.push(Ok(info)),
                         Err(e) => {
                             tracing::error!("Failed to get collection info: {e}");
@@ -97,30 +108,18 @@ impl VectorDb for Qdrant {
         &self,
         data: CreateVectorCollection<'_>,
     ) -> Result<(), ChonkitError> {
-        let CreateVectorCollection { name, size, .. } = data;
+        create_collection_with_id_vector(self, data, None).await
+    }
 
-        let config = VectorsConfig {
-            config: Some(Config::Params(VectorParams {
-                size: size as u64,
-                distance: Distance::Cosine.into(),
-                ..Default::default()
-            })),
+    #[cfg(feature = "multivector")]
+    async fn create_multivector_collection(
+        &self,
+        data: CreateVectorCollection<'_>,
+    ) -> Result<(), ChonkitError> {
+        let multivector = MultiVectorConfig {
+            comparator: MultiVectorComparator::MaxSim.into(),
         };
-
-        let res = map_err!(
-            self.create_collection(CreateCollection {
-                collection_name: name.to_string(),
-                vectors_config: Some(config),
-                ..Default::default()
-            })
-            .await
-        );
-
-        map_err!(upsert_id_vector(self, data).await);
-
-        debug_assert!(res.result);
-
-        Ok(())
+        create_collection_with_id_vector(self, data, Some(multivector)).await
     }
 
     async fn update_collection_groups(
@@ -128,15 +127,17 @@ impl VectorDb for Qdrant {
         collection: &str,
         groups: Option<Vec<String>>,
     ) -> Result<(), ChonkitError> {
+        let info = map_err!(self.collection_info(collection).await);
+        let multivector = get_vector_params(&info).is_some_and(|p| p.multivector_config.is_some());
+
         let collection = self.get_collection(collection).await?.with_groups(groups);
-        map_err!(upsert_id_vector(self, (&collection).into()).await);
+        map_err!(upsert_id_vector(self, (&collection).into(), multivector).await);
         Ok(())
     }
 
     async fn get_collection(&self, name: &str) -> Result<VectorCollection, ChonkitError> {
         let info = map_err!(self.collection_info(name).await);
-        let size = get_collection_size(&info);
-        let Some(size) = size else {
+        if get_collection_size(&info).is_none() {
             #[cfg(debug_assertions)]
             {
                 debug!("{info:?}")
@@ -145,9 +146,9 @@ impl VectorDb for Qdrant {
                 DoesNotExist,
                 "Size information for vector collection '{name}'"
             );
-        };
+        }
 
-        let info = get_id_vector(self, name, size).await?;
+        let info = get_id_vector(self, name).await?;
 
         Ok(info)
     }
@@ -163,58 +164,99 @@ impl VectorDb for Qdrant {
         collection: &str,
         limit: u32,
         max_distance: Option<f64>,
+        documents: Option<&[Uuid]>,
+        filter: Option<&FilterExpr>,
     ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        let mut search_filter = search_filter(documents);
+
+        if let Some(filter) = filter {
+            search_filter.must.push(metadata_condition(filter)?);
+        }
+
         let search_points = SearchPoints {
             collection_name: collection.to_string(),
             vector: search.into_iter().map(|x| x as f32).collect(),
-            filter: Some(Filter::must_not(vec![Condition::matches_text(
-                "id",
-                Uuid::nil().to_string(),
-            )])),
+            filter: Some(search_filter),
             limit: limit as u64,
             with_payload: Some(WithPayloadSelector {
                 selector_options: Some(SelectorOptions::Enable(true)),
             }),
             params: Some(SearchParams::default()),
-            score_threshold: max_distance.map(|x| x as f32),
+            // Collections use cosine similarity, whose score is the inverse of the distance
+            score_threshold: max_distance.map(|distance| (1. - distance) as f32),
             ..Default::default()
         };
 
         let search_result = map_err!(self.search_points(search_points).await);
 
-        let results = search_result
+        Ok(search_result
             .result
             .into_iter()
-            .filter_map(|mut point| {
-                let content = point.payload.remove(CONTENT_PROPERTY)?.kind?;
-                match content {
-                    // TODO: qdrant impl needs some love
-                    value::Kind::StringValue(s) => Some(CollectionSearchItem {
-                        item: todo!(),
-                        distance: None,
-                    }),
-                    v => {
-                        warn!("Found unsupported value kind: {v:?}");
-                        None
-                    }
-                }
+            .filter_map(|point| {
+                // The score is the cosine similarity, the distance is its inverse
+                let distance = 1. - point.score as f64;
+                search_item(point, distance)
             })
-            .collect();
+            .collect())
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn query_multivector(
+        &self,
+        search: Vec<Vec<f64>>,
+        collection: &str,
+        limit: u32,
+        documents: Option<&[Uuid]>,
+    ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        let search = search
+            .into_iter()
+            .map(|vector| vector.into_iter().map(|x| x as f32).collect())
+            .collect::<Vec<Vec<f32>>>();
+
+        // MaxSim sums the cosine similarities of every query vector, so subtracting it from
+        // their amount sums the cosine distances instead, keeping lower distances nearer
+        let max_score = search.len() as f64;
+
+        let query = QueryPointsBuilder::new(collection)
+            .query(Query::new_nearest(VectorInput::new_multi(search)))
+            .filter(search_filter(documents))
+            .limit(limit as u64)
+            .with_payload(true);
 
-        Ok(results)
+        // Multi-vectors can only be searched with the query API
+        let query_result = map_err!(Qdrant::query(self, query).await);
+
+        Ok(query_result
+            .result
+            .into_iter()
+            .filter_map(|point| {
+                let distance = max_score - point.score as f64;
+                search_item(point, distance)
+            })
+            .collect())
     }
 
     async fn insert_embeddings(
         &self,
         insert: CollectionItemInsert<'_>,
     ) -> Result<(), ChonkitError> {
+        let size = self.get_collection(insert.collection).await?.size;
+
         match insert.payload {
             CollectionItemInsertPayload::Text { items, vectors } => {
-                insert_text_embeddings(self, todo!(), insert.collection, todo!(), vectors).await
+                validate_vectors(&vectors, size)?;
+                insert_text_embeddings(self, insert.collection, items, vectors).await
             }
             CollectionItemInsertPayload::Image { item, vector } => {
-                insert_image_embeddings(self, todo!(), insert.collection, item.image_b64, vector)
-                    .await
+                validate_vectors(std::slice::from_ref(&vector), size)?;
+                insert_image_embeddings(self, insert.collection, item, vector).await
+            }
+            #[cfg(feature = "multivector")]
+            CollectionItemInsertPayload::MultiText { items, vectors } => {
+                for item_vectors in vectors.iter() {
+                    validate_vectors(item_vectors, size)?;
+                }
+                insert_multi_text_embeddings(self, insert.collection, items, vectors).await
             }
         }
     }
@@ -259,13 +301,63 @@ impl VectorDb for Qdrant {
         Ok(())
     }
 
+    async fn delete_text_embeddings_many(
+        &self,
+        collection: &str,
+        document_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        if document_ids.is_empty() {
+            return Ok(());
+        }
+
+        let document_ids: Vec<String> = document_ids.iter().map(Uuid::to_string).collect();
+
+        map_err!(
+            self.delete_points(
+                DeletePointsBuilder::new(collection)
+                    .points(Filter::must([Condition::matches(
+                        DOCUMENT_ID_PROPERTY,
+                        document_ids,
+                    )]))
+                    .wait(true),
+            )
+            .await
+        );
+
+        Ok(())
+    }
+
+    async fn delete_image_embeddings_many(
+        &self,
+        collection: &str,
+        image_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        if image_ids.is_empty() {
+            return Ok(());
+        }
+
+        let image_ids: Vec<String> = image_ids.iter().map(Uuid::to_string).collect();
+
+        map_err!(
+            self.delete_points(
+                DeletePointsBuilder::new(collection)
+                    .points(Filter::must([Condition::matches(
+                        IMAGE_ID_PROPERTY,
+                        image_ids,
+                    )]))
+                    .wait(true),
+            )
+            .await
+        );
+
+        Ok(())
+    }
+
     async fn count_vectors(
         &self,
         collection: &str,
         document_id: Uuid,
     ) -> Result<usize, ChonkitError> {
-        use qdrant_client::qdrant::{Condition, Filter, ScrollPointsBuilder};
-
         let scroll = map_err!(
             self.scroll(
                 ScrollPointsBuilder::new(collection)
@@ -281,12 +373,372 @@ impl VectorDb for Qdrant {
 
         Ok(scroll.result.len())
     }
+
+    async fn list_vectors(
+        &self,
+        collection: &str,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<VectorPage, ChonkitError> {
+        let mut scroll = ScrollPointsBuilder::new(collection)
+            .limit(limit)
+            .with_payload(true)
+            .with_vectors(true);
+
+        if let Some(cursor) = cursor {
+            scroll = scroll.offset(PointId::from(cursor));
+        }
+
+        let response = map_err!(self.scroll(scroll).await);
+
+        let vectors = response
+            .result
+            .into_iter()
+            .filter_map(|mut point| {
+                // Skips the collection info point and images
+                let (content, document_id, chunk_index) = take_text_payload(&mut point.payload)?;
+                let metadata = point
+                    .payload
+                    .remove(METADATA_PROPERTY)
+                    .map(Value::into_json);
+                let synthetic = take_synthetic_flag(&mut point.payload);
+                let (parent_index, parent_content) = take_parent_payload(&mut point.payload);
+
+                let Some(VectorsOptions::Vector(VectorOutput {
+                    vector: Some(vector_output::Vector::Dense(vector)),
+                    ..
+                })) = point.vectors?.vectors_options
+                else {
+                    warn!("Found unsupported vectors in point {:?}", point.id);
+                    return None;
+                };
+
+                Some(ExportedVector {
+                    document_id: document_id?,
+                    chunk_index,
+                    content,
+                    synthetic,
+                    metadata,
+                    parent_index,
+                    parent_content,
+                    vector: vector.data.into_iter().map(f64::from).collect(),
+                })
+            })
+            .collect();
+
+        let next = response
+            .next_page_offset
+            .and_then(|id| match id.point_id_options? {
+                PointIdOptions::Uuid(id) => Some(id),
+                PointIdOptions::Num(num) => Some(num.to_string()),
+            });
+
+        Ok(VectorPage { vectors, next })
+    }
+
+    async fn list_document_items(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<Vec<CollectionItem>, ChonkitError> {
+        let mut items = vec![];
+        let mut offset = None;
+
+        loop {
+            let mut scroll = ScrollPointsBuilder::new(collection)
+                .filter(Filter::must([Condition::matches(
+                    DOCUMENT_ID_PROPERTY,
+                    document_id.to_string(),
+                )]))
+                .limit(LIST_DOCUMENT_PAGE_SIZE)
+                .with_payload(true)
+                .with_vectors(false);
+
+            if let Some(offset) = offset {
+                scroll = scroll.offset(offset);
+            }
+
+            let response = map_err!(self.scroll(scroll).await);
+
+            items.extend(response.result.into_iter().filter_map(|mut point| {
+                // Skips images
+                let (content, document_id, chunk_index) = take_text_payload(&mut point.payload)?;
+                let metadata = point
+                    .payload
+                    .remove(METADATA_PROPERTY)
+                    .map(Value::into_json);
+                let synthetic = take_synthetic_flag(&mut point.payload);
+                let (parent_index, parent_content) = take_parent_payload(&mut point.payload);
+
+                Some(CollectionItem {
+                    document_id,
+                    metadata,
+                    payload: CollectionItemPayload::Text {
+                        content,
+                        chunk_index,
+                        synthetic,
+                        parent_index,
+                        parent_content,
+                    },
+                })
+            }));
+
+            match response.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => return Ok(items),
+            }
+        }
+    }
 }
+
+/// Create a collection and its ID vector. `multivector` is only set for multi-vector collections.
+async fn create_collection_with_id_vector(
+    qdrant: &Qdrant,
+    data: CreateVectorCollection<'_>,
+    multivector: Option<MultiVectorConfig>,
+) -> Result<(), ChonkitError> {
+    let is_multivector = multivector.is_some();
+
+    let config = VectorsConfig {
+        config: Some(Config::Params(VectorParams {
+            size: data.size as u64,
+            distance: Distance::Cosine.into(),
+            multivector_config: multivector,
+            ..Default::default()
+        })),
+    };
+
+    let res = map_err!(
+        qdrant
+            .create_collection(CreateCollection {
+                collection_name: data.name.to_string(),
+                vectors_config: Some(config),
+                ..Default::default()
+            })
+            .await
+    );
+
+    map_err!(upsert_id_vector(qdrant, data, is_multivector).await);
+
+    debug_assert!(res.result);
+
+    Ok(())
+}
+
+/// Excludes the collection info point and, if `documents` are given, points of other documents.
+fn search_filter(documents: Option<&[Uuid]>) -> Filter {
+    let mut filter = Filter::must_not(vec![Condition::matches_text("id", Uuid::nil().to_string())]);
+
+    if let Some(documents) = documents {
+        let documents: Vec<String> = documents.iter().map(Uuid::to_string).collect();
+        filter.must = vec![Condition::matches(DOCUMENT_ID_PROPERTY, documents)];
+    }
+
+    filter
+}
+
+/// Translate a metadata filter to a condition on the fields of the metadata payload.
+/// Qdrant only supports ranges on numbers.
+fn metadata_condition(expr: &FilterExpr) -> Result<Condition, ChonkitError> {
+    let condition = match expr {
+        FilterExpr::And { and } => Filter::must(
+            and.iter()
+                .map(metadata_condition)
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+        .into(),
+        FilterExpr::Or { or } => Filter::should(
+            or.iter()
+                .map(metadata_condition)
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+        .into(),
+        FilterExpr::Condition(FilterCondition { field, op, value }) => {
+            let key = format!("{METADATA_PROPERTY}.{field}");
+
+            if op.is_range() {
+                let Some(value) = value.as_f64() else {
+                    return err!(
+                        OperationUnsupported,
+                        "Qdrant only supports range filters on numbers, '{field}' is compared to {value:?}"
+                    );
+                };
+
+                let range = match op {
+                    FilterOp::Gt => Range {
+                        gt: Some(value),
+                        ..Default::default()
+                    },
+                    FilterOp::Gte => Range {
+                        gte: Some(value),
+                        ..Default::default()
+                    },
+                    FilterOp::Lt => Range {
+                        lt: Some(value),
+                        ..Default::default()
+                    },
+                    FilterOp::Lte => Range {
+                        lte: Some(value),
+                        ..Default::default()
+                    },
+                    FilterOp::Eq | FilterOp::Ne => unreachable!(),
+                };
+
+                return Ok(Condition::range(key, range));
+            }
+
+            let condition = match value {
+                FilterValue::Bool(value) => Condition::matches(key, *value),
+                FilterValue::Integer(value) => Condition::matches(key, *value),
+                // Keywords are matched exactly, unlike text
+                FilterValue::String(value) => {
+                    Condition::matches(key, MatchValue::Keyword(value.clone()))
+                }
+                // Only integers can be matched
+                FilterValue::Float(value) => Condition::range(
+                    key,
+                    Range {
+                        gte: Some(*value),
+                        lte: Some(*value),
+                        ..Default::default()
+                    },
+                ),
+            };
+
+            match op {
+                FilterOp::Ne => Filter::must_not([condition]).into(),
+                _ => condition,
+            }
+        }
+    };
+
+    Ok(condition)
+}
+
+/// Obtain a search item from a scored point, `None` if it is neither a text nor an image point.
+fn search_item(mut point: ScoredPoint, distance: f64) -> Option<CollectionSearchItem> {
+    let distance = Some(distance);
+
+    // Points without content are images
+    let Some((content, document_id, chunk_index)) = take_text_payload(&mut point.payload) else {
+        let item = take_image_payload(&mut point.payload)?;
+        return Some(CollectionSearchItem::new(item, distance));
+    };
+
+    let metadata = point
+        .payload
+        .remove(METADATA_PROPERTY)
+        .map(Value::into_json);
+    let synthetic = take_synthetic_flag(&mut point.payload);
+    let (parent_index, parent_content) = take_parent_payload(&mut point.payload);
+
+    Some(CollectionSearchItem::new(
+        CollectionItem {
+            document_id,
+            metadata,
+            payload: CollectionItemPayload::Text {
+                content,
+                chunk_index,
+                synthetic,
+                parent_index,
+                parent_content,
+            },
+        },
+        distance,
+    ))
+}
+
+/// Remove the content, document ID and chunk index from the payload of a text point.
+/// Returns `None` if the point has no content, i.e. is not a text point.
+fn take_text_payload(
+    payload: &mut HashMap<String, Value>,
+) -> Option<(String, Option<Uuid>, Option<usize>)> {
+    let content = payload.remove(CONTENT_PROPERTY)?.kind?;
+    let value::Kind::StringValue(content) = content else {
+        warn!("Found unsupported value kind: {content:?}");
+        return None;
+    };
+
+    let document_id = match payload.remove(DOCUMENT_ID_PROPERTY) {
+        Some(Value {
+            kind: Some(value::Kind::StringValue(id)),
+        }) => Uuid::parse_str(&id).ok(),
+        _ => None,
+    };
+
+    // Not present on points inserted before chunk indices were stored
+    let chunk_index = match payload.remove(CHUNK_INDEX_PROPERTY) {
+        Some(Value {
+            kind: Some(value::Kind::IntegerValue(i)),
+        }) => Some(i as usize),
+        _ => None,
+    };
+
+    Some((content, document_id, chunk_index))
+}
+
+/// Obtain an image item from the payload of an image point. The image's base64 encoding is
+/// left out since it is not needed in search results and is usually large.
+/// Returns `None` if the point has no image ID or path, i.e. is not an image point.
+fn take_image_payload(payload: &mut HashMap<String, Value>) -> Option<CollectionItem> {
+    let mut take_string = |key: &str| match payload.remove(key) {
+        Some(Value {
+            kind: Some(value::Kind::StringValue(value)),
+        }) => Some(value),
+        _ => None,
+    };
+
+    let image_id = Uuid::parse_str(&take_string(IMAGE_ID_PROPERTY)?).ok()?;
+    let image_path = take_string(IMAGE_PATH_PROPERTY)?;
+    let image_description = take_string(IMAGE_DESCRIPTION_PROPERTY);
+    let document_id = take_string(DOCUMENT_ID_PROPERTY).and_then(|id| Uuid::parse_str(&id).ok());
+
+    Some(CollectionItem {
+        document_id,
+        metadata: None,
+        payload: CollectionItemPayload::Image {
+            image_id,
+            image_b64: None,
+            image_path,
+            image_description,
+        },
+    })
+}
+
+/// Remove the synthetic flag from the payload of a text point, `false` if it is missing.
+fn take_synthetic_flag(payload: &mut HashMap<String, Value>) -> bool {
+    matches!(
+        payload.remove(SYNTHETIC_PROPERTY),
+        Some(Value {
+            kind: Some(value::Kind::BoolValue(true))
+        })
+    )
+}
+
+/// Remove the parent window index and content from the payload of a text point.
+/// Both are only present on points obtained with parent-child chunking.
+fn take_parent_payload(payload: &mut HashMap<String, Value>) -> (Option<usize>, Option<String>) {
+    let parent_index = match payload.remove(PARENT_INDEX_PROPERTY) {
+        Some(Value {
+            kind: Some(value::Kind::IntegerValue(i)),
+        }) => Some(i as usize),
+        _ => None,
+    };
+
+    let parent_content = match payload.remove(PARENT_CONTENT_PROPERTY) {
+        Some(Value {
+            kind: Some(value::Kind::StringValue(content)),
+        }) => Some(content),
+        _ => None,
+    };
+
+    (parent_index, parent_content)
+}
+
 async fn insert_text_embeddings(
     client: &Qdrant,
-    document_id: Uuid,
     collection: &str,
-    content: &[&str],
+    content: Vec<CollectionItemText<'_>>,
     vectors: Vec<Vec<f64>>,
 ) -> Result<(), ChonkitError> {
     debug!("Inserting vectors to {collection}");
@@ -300,17 +752,14 @@ async fn insert_text_embeddings(
     let points: Vec<PointStruct> = vectors
         .into_iter()
         .zip(content.iter())
-        .map(|(embedding, content)| {
-            let mut payload = Payload::new();
-            payload.insert(CONTENT_PROPERTY, content.to_string());
-            payload.insert(DOCUMENT_ID_PROPERTY, document_id.to_string());
+        .map(|(embedding, item)| {
             PointStruct::new(
-                uuid::Uuid::new_v4().to_string(),
+                item.id().to_string(),
                 embedding
                     .into_iter()
                     .map(|x| x as f32)
                     .collect::<Vec<f32>>(),
-                payload,
+                text_payload(item),
             )
         })
         .collect();
@@ -324,28 +773,118 @@ async fn insert_text_embeddings(
     Ok(())
 }
 
+#[cfg(feature = "multivector")]
+async fn insert_multi_text_embeddings(
+    client: &Qdrant,
+    collection: &str,
+    content: Vec<CollectionItemText<'_>>,
+    vectors: Vec<Vec<Vec<f64>>>,
+) -> Result<(), ChonkitError> {
+    debug!("Inserting multi-vectors to {collection}");
+
+    debug_assert_eq!(
+        content.len(),
+        vectors.len(),
+        "Content length is different from embeddings!"
+    );
+
+    let points: Vec<PointStruct> = vectors
+        .into_iter()
+        .zip(content.iter())
+        .map(|(embeddings, item)| {
+            let embeddings = embeddings
+                .into_iter()
+                .map(|vector| vector.into_iter().map(|x| x as f32).collect())
+                .collect::<Vec<Vec<f32>>>();
+            PointStruct::new(
+                item.id().to_string(),
+                Vector::new_multi(embeddings),
+                text_payload(item),
+            )
+        })
+        .collect();
+
+    map_err!(
+        client
+            .upsert_points(UpsertPointsBuilder::new(collection, points).wait(true))
+            .await
+    );
+
+    Ok(())
+}
+
+fn text_payload(item: &CollectionItemText<'_>) -> Payload {
+    let mut payload = Payload::new();
+    payload.insert(CONTENT_PROPERTY, item.content.to_string());
+    payload.insert(DOCUMENT_ID_PROPERTY, item.document_id.to_string());
+    if let Some(chunk_index) = item.chunk_index {
+        payload.insert(CHUNK_INDEX_PROPERTY, chunk_index as i64);
+    }
+    if item.synthetic {
+        payload.insert(SYNTHETIC_PROPERTY, true);
+    }
+    if let Some(parent_index) = item.parent_index {
+        payload.insert(PARENT_INDEX_PROPERTY, parent_index as i64);
+    }
+    if let Some(parent_content) = item.parent_content {
+        payload.insert(PARENT_CONTENT_PROPERTY, parent_content.to_string());
+    }
+    if let Some(metadata) = item.metadata {
+        payload.insert(METADATA_PROPERTY, Value::from(metadata.clone()));
+    }
+    payload
+}
+
 async fn insert_image_embeddings(
     client: &Qdrant,
-    document_id: Uuid,
     collection: &str,
-    image_b64: &str,
+    item: CollectionItemImage<'_>,
     vector: Vec<f64>,
 ) -> Result<(), ChonkitError> {
-    todo!()
+    debug!("Inserting image vector to {collection}");
+
+    let mut payload = Payload::new();
+    payload.insert(IMAGE_ID_PROPERTY, item.image_id.to_string());
+    payload.insert(IMAGE_B64_PROPERTY, item.image_b64.to_string());
+    payload.insert(IMAGE_PATH_PROPERTY, item.image_path.to_string());
+    if let Some(document_id) = item.document_id {
+        payload.insert(DOCUMENT_ID_PROPERTY, document_id.to_string());
+    }
+    if let Some(description) = item.image_description {
+        payload.insert(IMAGE_DESCRIPTION_PROPERTY, description.to_string());
+    }
+
+    let point = PointStruct::new(
+        uuid::Uuid::new_v4().to_string(),
+        vector.into_iter().map(|x| x as f32).collect::<Vec<f32>>(),
+        payload,
+    );
+
+    map_err!(
+        client
+            .upsert_points(UpsertPointsBuilder::new(collection, vec![point]).wait(true))
+            .await
+    );
+
+    Ok(())
 }
 
+/// Insert the collection info point. Multi-vector collections only accept multi-vector points.
 async fn upsert_id_vector(
     qdrant: &Qdrant,
     collection: CreateVectorCollection<'_>,
+    multivector: bool,
 ) -> Result<(), QdrantError> {
     let mut payload = Payload::new();
     payload.insert("collection_info", json! { collection });
 
-    let point = PointStruct::new(
-        uuid::Uuid::nil().to_string(),
-        vec![0.0; collection.size],
-        payload,
-    );
+    let vector = if multivector {
+        Vector::new_multi(vec![vec![0.0; collection.size]])
+    } else {
+        Vector::new_dense(vec![0.0; collection.size])
+    };
+
+    let point = PointStruct::new(uuid::Uuid::nil().to_string(), vector, payload);
 
     qdrant
         .upsert_points(UpsertPointsBuilder::new(collection.name, vec![point]).wait(true))
@@ -354,28 +893,18 @@ async fn upsert_id_vector(
     Ok(())
 }
 
-async fn get_id_vector(
-    qdrant: &Qdrant,
-    name: &str,
-    size: usize,
-) -> Result<VectorCollection, ChonkitError> {
-    let search_points = SearchPoints {
-        collection_name: name.to_string(),
-        vector: vec![0.0; size],
-        filter: Some(qdrant_client::qdrant::Filter::must(vec![
-            Condition::has_id(vec![Uuid::nil().to_string()]),
-        ])),
-        limit: 1,
-        with_payload: Some(WithPayloadSelector {
-            selector_options: Some(SelectorOptions::Enable(true)),
-        }),
-        params: Some(SearchParams::default()),
-        ..Default::default()
-    };
+/// Retrieve the collection info point by its ID. Unlike searching for it, this works the same
+/// way for single and multi-vector collections.
+async fn get_id_vector(qdrant: &Qdrant, name: &str) -> Result<VectorCollection, ChonkitError> {
+    let get_points = GetPointsBuilder::new(name, vec![PointId::from(Uuid::nil().to_string())])
+        .with_payload(true)
+        .with_vectors(false);
 
-    let mut search_result = map_err!(qdrant.search_points(search_points).await);
+    let mut get_result = map_err!(qdrant.get_points(get_points).await);
 
-    let results = &mut search_result.result[0];
+    let Some(results) = get_result.result.first_mut() else {
+        return err!(DoesNotExist, "Collection info vector for '{name}'");
+    };
 
     let Some(info_string) = results.payload.remove("collection_info") else {
         return err!(DoesNotExist, "Collection info vector for '{name}'");
@@ -405,6 +934,10 @@ async fn get_id_vector(
 }
 
 fn get_collection_size(info: &GetCollectionInfoResponse) -> Option<usize> {
+    get_vector_params(info).map(|params| params.size as usize)
+}
+
+fn get_vector_params(info: &GetCollectionInfoResponse) -> Option<&VectorParams> {
     let config = info
         .result
         .as_ref()?
@@ -417,7 +950,7 @@ fn get_collection_size(info: &GetCollectionInfoResponse) -> Option<usize> {
         .config
         .as_ref()?;
     match config {
-        Config::Params(VectorParams { size, .. }) => Some(*size as usize),
+        Config::Params(params) => Some(params),
         Config::ParamsMap(pm) => {
             warn!("Found unexpected params map! {pm:?}");
             None
@@ -505,7 +1038,10 @@ mod qdrant_tests {
             test::{init_qdrant, AsyncContainer},
             vector::qdrant::QdrantDb,
         },
-        core::vector::{CollectionItemInsert, CreateVectorCollection, VectorDb},
+        core::vector::{
+            CollectionItemInsert, CollectionItemPayload, CreateVectorCollection, VectorDb,
+        },
+        error::ChonkitErr,
     };
     use suitest::before_all;
     use uuid::Uuid;
@@ -587,16 +1123,168 @@ mod qdrant_tests {
                 name,
                 &["foo"],
                 vec![vec![0.420f64; 420]],
+                None,
+            ))
+            .await
+            .unwrap();
+
+        let results = VectorDb::query(
+            qdrant.as_ref(),
+            vec![0.420f64; 420],
+            name,
+            420,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(1, results.len());
+        assert_eq!("foo", results[0].item.payload.as_content());
+
+        qdrant.delete_vector_collection(name).await.unwrap();
+    }
+
+    #[test]
+    async fn returns_document_id_and_chunk_index(qdrant: QdrantDb) {
+        let name = "My_collection_for_query_item_meta";
+        let id = Uuid::new_v4();
+        let document_id = Uuid::new_v4();
+
+        let collection =
+            CreateVectorCollection::new(id, name, 420, "openai", "text-embedding-ada-002", None);
+
+        qdrant.create_vector_collection(collection).await.unwrap();
+
+        let mut foo = vec![0.0f64; 420];
+        foo[0] = 1.0;
+        let mut bar = vec![0.0f64; 420];
+        bar[1] = 1.0;
+
+        qdrant
+            .insert_embeddings(CollectionItemInsert::new_text(
+                document_id,
+                name,
+                &["foo", "bar"],
+                vec![foo, bar.clone()],
+                None,
             ))
             .await
             .unwrap();
 
-        let results = VectorDb::query(qdrant.as_ref(), vec![0.420f64; 420], name, 420, None)
+        let results = VectorDb::query(qdrant.as_ref(), bar, name, 1, None, None, None)
             .await
             .unwrap();
 
         assert_eq!(1, results.len());
-        assert_eq!("foo", results[0].item.payload.as_content());
+        assert_eq!("bar", results[0].item.payload.as_content());
+        assert_eq!(Some(document_id), results[0].item.document_id);
+        assert_eq!(Some(1), results[0].item.payload.chunk_index());
+
+        qdrant.delete_vector_collection(name).await.unwrap();
+    }
+
+    #[test]
+    async fn inserts_and_queries_images(qdrant: QdrantDb) {
+        let name = "My_collection_for_images";
+        let id = Uuid::new_v4();
+        let document_id = Uuid::new_v4();
+        let image_id = Uuid::new_v4();
+
+        let collection =
+            CreateVectorCollection::new(id, name, 420, "openai", "text-embedding-ada-002", None);
+
+        qdrant.create_vector_collection(collection).await.unwrap();
+
+        qdrant
+            .insert_embeddings(CollectionItemInsert::new_image(
+                Some(document_id),
+                name,
+                image_id,
+                "aW1hZ2U=",
+                "images/ragu.png",
+                Some("A bowl of ragu"),
+                vec![0.420f64; 420],
+            ))
+            .await
+            .unwrap();
+
+        let results = VectorDb::query(
+            qdrant.as_ref(),
+            vec![0.420f64; 420],
+            name,
+            10,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(1, results.len());
+        assert_eq!(Some(document_id), results[0].item.document_id);
+
+        let CollectionItemPayload::Image {
+            image_id: found_id,
+            ref image_path,
+            ref image_description,
+            ..
+        } = results[0].item.payload
+        else {
+            panic!("not an image");
+        };
+        assert_eq!(image_id, found_id);
+        assert_eq!("images/ragu.png", image_path.as_str());
+        assert_eq!(Some("A bowl of ragu"), image_description.as_deref());
+
+        qdrant
+            .delete_image_embeddings(name, image_id)
+            .await
+            .unwrap();
+
+        let results = VectorDb::query(
+            qdrant.as_ref(),
+            vec![0.420f64; 420],
+            name,
+            10,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(results.is_empty());
+
+        qdrant.delete_vector_collection(name).await.unwrap();
+    }
+
+    #[test]
+    async fn rejects_vectors_of_invalid_size(qdrant: QdrantDb) {
+        let name = "My_collection_for_invalid_insert";
+        let id = Uuid::new_v4();
+
+        let collection =
+            CreateVectorCollection::new(id, name, 420, "openai", "text-embedding-ada-002", None);
+
+        qdrant.create_vector_collection(collection).await.unwrap();
+
+        let err = qdrant
+            .insert_embeddings(CollectionItemInsert::new_text(
+                Uuid::new_v4(),
+                name,
+                &["foo", "bar"],
+                vec![vec![0.420f64; 420], vec![0.420f64; 69]],
+                None,
+            ))
+            .await
+            .unwrap_err();
+
+        let ChonkitErr::InvalidVector(msg) = err.error else {
+            panic!("expected InvalidVector, got {:?}", err.error);
+        };
+
+        assert_eq!("vector at index 1 has size 69 (expected 420)", msg);
 
         qdrant.delete_vector_collection(name).await.unwrap();
     }
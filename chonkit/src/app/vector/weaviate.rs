@@ -1,19 +1,24 @@
 use crate::config::WEAVIATE_ID;
 use crate::core::provider::Identity;
 use crate::core::vector::{
-    CollectionItem, CollectionItemImage, CollectionItemInsert, CollectionItemInsertPayload,
-    CollectionItemText, CollectionSearchItem, CreateVectorCollection, VectorCollection, VectorDb,
+    validate_vectors, CollectionItem, CollectionItemImage, CollectionItemInsert,
+    CollectionItemInsertPayload, CollectionItemPayload, CollectionItemText, CollectionSearchItem,
+    CreateVectorCollection, ExportedVector, FilterCondition, FilterExpr, FilterOp, FilterValue,
+    VectorCollection, VectorDb, VectorPage, CHUNK_INDEX_PROPERTY,
     COLLECTION_EMBEDDING_MODEL_PROPERTY, COLLECTION_EMBEDDING_PROVIDER_PROPERTY,
     COLLECTION_GROUPS_PROPERTY, COLLECTION_ID_PROPERTY, COLLECTION_NAME_PROPERTY,
     COLLECTION_SIZE_PROPERTY, CONTENT_PROPERTY, DOCUMENT_ID_PROPERTY, IMAGE_B64_PROPERTY,
-    IMAGE_DESCRIPTION_PROPERTY, IMAGE_ID_PROPERTY, IMAGE_PATH_PROPERTY,
+    IMAGE_DESCRIPTION_PROPERTY, IMAGE_ID_PROPERTY, IMAGE_PATH_PROPERTY, LIST_DOCUMENT_PAGE_SIZE,
+    METADATA_PROPERTY, PARENT_CONTENT_PROPERTY, PARENT_INDEX_PROPERTY, SYNTHETIC_PROPERTY,
 };
 use crate::{err, error::ChonkitError, map_err};
-use dto::{QueryResult, WeaviateError};
+use dto::{ListedObject, QueryResult, WeaviateError};
 use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
-use weaviate_community::collections::schema::{Properties, PropertyBuilder};
+use weaviate_community::collections::schema::{
+    Properties, Property, PropertyBuilder, Tokenization,
+};
 use weaviate_community::{
     collections::{
         batch::{BatchDeleteRequest, MatchConfig},
@@ -28,10 +33,33 @@ use weaviate_community::{
 const WEAVIATE_VECTOR_PROPERTIES: &[(&str, &str)] = &[
     (DOCUMENT_ID_PROPERTY, "uuid"),
     (CONTENT_PROPERTY, "text"),
+    (CHUNK_INDEX_PROPERTY, "int"),
+    (SYNTHETIC_PROPERTY, "boolean"),
+    (PARENT_INDEX_PROPERTY, "int"),
+    (PARENT_CONTENT_PROPERTY, "text"),
     (IMAGE_ID_PROPERTY, "uuid"),
     (IMAGE_B64_PROPERTY, "text"),
     (IMAGE_PATH_PROPERTY, "text"),
     (IMAGE_DESCRIPTION_PROPERTY, "text"),
+    // Stored as a JSON string since object properties require a predefined schema
+    (METADATA_PROPERTY, "text"),
+];
+
+/// Prefix of the properties the metadata fields are additionally stored in, since the
+/// [METADATA_PROPERTY] string cannot be filtered on. Nested fields are joined with
+/// [METADATA_FIELD_SEPARATOR], e.g. `author.name` is stored in `meta_author__name`.
+const METADATA_FIELD_PREFIX: &str = "meta_";
+
+const METADATA_FIELD_SEPARATOR: &str = "__";
+
+/// Vector properties introduced after classes were already being created with
+/// [WEAVIATE_VECTOR_PROPERTIES]. Older classes do not have them.
+const OPTIONAL_VECTOR_PROPERTIES: &[&str] = &[
+    CHUNK_INDEX_PROPERTY,
+    METADATA_PROPERTY,
+    SYNTHETIC_PROPERTY,
+    PARENT_INDEX_PROPERTY,
+    PARENT_CONTENT_PROPERTY,
 ];
 
 /// Alias for an arced Weaviate instance.
@@ -158,28 +186,65 @@ impl VectorDb for WeaviateClient {
         collection: &str,
         limit: u32,
         max_distance: Option<f64>,
+        documents: Option<&[Uuid]>,
+        filter: Option<&FilterExpr>,
     ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
         tracing::debug!("weaviate - querying collection '{collection}' (limit: {limit}, max_distance: {max_distance:?})");
+
         let near_vector = &format!("{{ vector: {search:?} }}");
-        let query = GetQuery::builder(collection, CollectionItem::query_properties().to_vec())
-            .with_near_vector(near_vector)
-            .with_where(&format!(
-                "{{ 
-                    path: [\"id\"],
-                    operator: NotEqual,
-                    valueText: \"{}\" 
+
+        let not_identity = format!(
+            "{{ 
+                path: [\"id\"],
+                operator: NotEqual,
+                valueText: \"{}\" 
+            }}",
+            Uuid::nil()
+        );
+
+        let mut operands = vec![not_identity];
+
+        if let Some(documents) = documents {
+            operands.push(format!(
+                "{{
+                    path: [\"{DOCUMENT_ID_PROPERTY}\"],
+                    operator: ContainsAny,
+                    valueText: {:?}
                 }}",
-                Uuid::nil()
-            ))
-            .with_limit(limit)
-            .with_additional(vec!["distance"])
-            .build();
+                documents.iter().map(Uuid::to_string).collect::<Vec<_>>()
+            ));
+        }
 
-        let response = match self.query.get(query).await {
-            Ok(res) => res,
-            Err(e) => return err!(Weaviate, "{}", e),
+        if let Some(filter) = filter {
+            let properties = WeaviateInner::new(self)
+                .class_properties(collection)
+                .await?;
+
+            match metadata_where(filter, &properties)? {
+                MetadataWhere::Operand(operand) => operands.push(operand),
+                MetadataWhere::Always => {}
+                MetadataWhere::Never => {
+                    tracing::debug!("weaviate - no chunks in '{collection}' can match the filter");
+                    return Ok(vec![]);
+                }
+            }
+        }
+
+        let where_filter = where_operator("And", operands);
+
+        let build_query = |properties: Vec<&str>| {
+            GetQuery::builder(collection, properties)
+                .with_near_vector(near_vector)
+                .with_where(&where_filter)
+                .with_limit(limit)
+                .with_additional(vec!["distance"])
+                .build()
         };
 
+        let response = WeaviateInner::new(self)
+            .get_with_class_properties(collection, CollectionItem::query_properties(), build_query)
+            .await?;
+
         if response["data"].is_null() {
             tracing::warn!("weaviate - query is missing 'data' field; response: {response:?}");
             let error = map_err!(serde_json::from_value::<WeaviateError>(response));
@@ -251,18 +316,30 @@ impl VectorDb for WeaviateClient {
         &self,
         insert: CollectionItemInsert<'_>,
     ) -> Result<(), ChonkitError> {
+        let size = self.get_collection(insert.collection).await?.size;
         let client = WeaviateInner::new(self);
+
         match insert.payload {
             CollectionItemInsertPayload::Text { items, vectors } => {
+                validate_vectors(&vectors, size)?;
                 client
                     .insert_text_embeddings(insert.collection, items, vectors)
                     .await
             }
             CollectionItemInsertPayload::Image { item, vector } => {
+                validate_vectors(std::slice::from_ref(&vector), size)?;
                 client
                     .insert_image_embeddings(insert.collection, item, vector)
                     .await
             }
+            #[cfg(feature = "multivector")]
+            CollectionItemInsertPayload::MultiText { .. } => {
+                err!(
+                    OperationUnsupported,
+                    "{} does not support multi-vector collections",
+                    self.id()
+                )
+            }
         }
     }
 
@@ -320,6 +397,72 @@ impl VectorDb for WeaviateClient {
         Ok(())
     }
 
+    async fn delete_text_embeddings_many(
+        &self,
+        collection: &str,
+        document_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        if document_ids.is_empty() {
+            return Ok(());
+        }
+
+        let document_ids: Vec<String> = document_ids.iter().map(Uuid::to_string).collect();
+
+        let delete = BatchDeleteRequest::builder(MatchConfig::new(
+            collection,
+            json!({
+                "path": [DOCUMENT_ID_PROPERTY],
+                "operator": "ContainsAny",
+                "valueTextArray": document_ids
+            }),
+        ))
+        .build();
+
+        if let Err(e) = self
+            .batch
+            .objects_batch_delete(delete, Some(ConsistencyLevel::ALL), None)
+            .await
+        {
+            tracing::error!("error deleting vectors: {}", e);
+            return err!(Weaviate, "{}", e);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_image_embeddings_many(
+        &self,
+        collection: &str,
+        image_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        if image_ids.is_empty() {
+            return Ok(());
+        }
+
+        let image_ids: Vec<String> = image_ids.iter().map(Uuid::to_string).collect();
+
+        let delete = BatchDeleteRequest::builder(MatchConfig::new(
+            collection,
+            json!({
+                "path": [IMAGE_ID_PROPERTY],
+                "operator": "ContainsAny",
+                "valueTextArray": image_ids
+            }),
+        ))
+        .build();
+
+        if let Err(e) = self
+            .batch
+            .objects_batch_delete(delete, Some(ConsistencyLevel::ALL), None)
+            .await
+        {
+            tracing::error!("error deleting vectors: {}", e);
+            return err!(Weaviate, "{}", e);
+        }
+
+        Ok(())
+    }
+
     async fn count_vectors(
         &self,
         collection: &str,
@@ -372,6 +515,183 @@ impl VectorDb for WeaviateClient {
 
         Ok(amount)
     }
+
+    async fn list_vectors(
+        &self,
+        collection: &str,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<VectorPage, ChonkitError> {
+        // Cursors are the IDs of the last objects of the previous pages
+        let cursor = match cursor {
+            Some(cursor) => Some(map_err!(Uuid::parse_str(&cursor))),
+            None => None,
+        };
+
+        let build_query = |properties: Vec<&str>| {
+            let query = GetQuery::builder(collection, properties)
+                .with_limit(limit)
+                .with_additional(vec!["id", "vector"]);
+
+            match cursor {
+                Some(cursor) => query.with_after(cursor).build(),
+                None => query.build(),
+            }
+        };
+
+        let response = WeaviateInner::new(self)
+            .get_with_class_properties(
+                collection,
+                &[
+                    DOCUMENT_ID_PROPERTY,
+                    CONTENT_PROPERTY,
+                    CHUNK_INDEX_PROPERTY,
+                    SYNTHETIC_PROPERTY,
+                    METADATA_PROPERTY,
+                    PARENT_INDEX_PROPERTY,
+                    PARENT_CONTENT_PROPERTY,
+                ],
+                build_query,
+            )
+            .await?;
+
+        if response["data"].is_null() {
+            tracing::warn!("weaviate - query is missing 'data' field; response: {response:?}");
+            let error = map_err!(serde_json::from_value::<WeaviateError>(response));
+            return err!(
+                Weaviate,
+                "{}",
+                error
+                    .errors
+                    .into_iter()
+                    .map(|e| e.message)
+                    .collect::<Vec<_>>()
+                    .join(";")
+            );
+        }
+
+        let result: QueryResult = map_err!(serde_json::from_value(response));
+
+        let Some(results) = result.data.get.get(collection) else {
+            return err!(
+                Weaviate,
+                "Response error - cannot index into '{collection}' in {}",
+                result.data.get
+            );
+        };
+
+        let results = map_err!(serde_json::from_value::<Vec<ListedObject>>(results.clone()));
+
+        // Weaviate has no cursor of its own, the ID of the last object is used to continue
+        let next = match results.last() {
+            Some(obj) if results.len() == limit as usize => Some(obj.additional.id.to_string()),
+            _ => None,
+        };
+
+        let vectors = results
+            .into_iter()
+            .filter_map(|obj| {
+                // Skips the collection info object and images
+                Some(ExportedVector {
+                    document_id: obj.document_id?,
+                    chunk_index: obj.chunk_index,
+                    content: obj.content?,
+                    synthetic: obj.synthetic.unwrap_or_default(),
+                    metadata: obj.metadata,
+                    parent_index: obj.parent_index,
+                    parent_content: obj.parent_content,
+                    vector: obj.additional.vector,
+                })
+            })
+            .collect();
+
+        Ok(VectorPage { vectors, next })
+    }
+
+    async fn list_document_items(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<Vec<CollectionItem>, ChonkitError> {
+        let where_filter = format!(
+            "{{
+                path: [\"{DOCUMENT_ID_PROPERTY}\"],
+                operator: Equal,
+                valueText: \"{document_id}\"
+            }}"
+        );
+
+        let mut items = vec![];
+        let mut offset = 0;
+
+        loop {
+            let build_query = |properties: Vec<&str>| {
+                GetQuery::builder(collection, properties)
+                    .with_where(&where_filter)
+                    .with_limit(LIST_DOCUMENT_PAGE_SIZE)
+                    .with_offset(offset)
+                    .build()
+            };
+
+            let response = WeaviateInner::new(self)
+                .get_with_class_properties(
+                    collection,
+                    CollectionItem::query_properties(),
+                    build_query,
+                )
+                .await?;
+
+            if response["data"].is_null() {
+                tracing::warn!("weaviate - query is missing 'data' field; response: {response:?}");
+                let error = map_err!(serde_json::from_value::<WeaviateError>(response));
+                return err!(
+                    Weaviate,
+                    "{}",
+                    error
+                        .errors
+                        .into_iter()
+                        .map(|e| e.message)
+                        .collect::<Vec<_>>()
+                        .join(";")
+                );
+            }
+
+            let result: QueryResult = map_err!(serde_json::from_value(response));
+
+            let Some(results) = result.data.get.get(collection) else {
+                return err!(
+                    Weaviate,
+                    "Response error - cannot index into '{collection}' in {}",
+                    result.data.get
+                );
+            };
+
+            let results = map_err!(serde_json::from_value::<Vec<serde_json::Value>>(
+                results.clone()
+            ));
+
+            let count = results.len();
+
+            items.extend(results.into_iter().filter_map(|obj| {
+                match serde_json::from_value::<CollectionItem>(obj) {
+                    // Skips images
+                    Ok(item) => {
+                        matches!(item.payload, CollectionItemPayload::Text { .. }).then_some(item)
+                    }
+                    Err(e) => {
+                        tracing::error!("weaviate - failed to parse item: {e}");
+                        None
+                    }
+                }
+            }));
+
+            if count < LIST_DOCUMENT_PAGE_SIZE as usize {
+                return Ok(items);
+            }
+
+            offset += LIST_DOCUMENT_PAGE_SIZE;
+        }
+    }
 }
 
 struct WeaviateInner<'a> {
@@ -413,6 +733,94 @@ impl<'a> WeaviateInner<'a> {
         Ok(())
     }
 
+    /// Run the query built with the `properties` the class has.
+    ///
+    /// Classes created before some properties were stored do not have them, see
+    /// [OPTIONAL_VECTOR_PROPERTIES]. The class schema is checked for these and
+    /// the missing ones are left out of the query.
+    async fn get_with_class_properties(
+        &self,
+        collection: &str,
+        properties: &[&str],
+        build_query: impl Fn(Vec<&str>) -> GetQuery,
+    ) -> Result<serde_json::Value, ChonkitError> {
+        let existing = self.class_properties(collection).await?;
+
+        let properties = properties
+            .iter()
+            .copied()
+            .filter(|property| {
+                if !OPTIONAL_VECTOR_PROPERTIES.contains(property)
+                    || existing.iter().any(|p| p.name == *property)
+                {
+                    return true;
+                }
+
+                tracing::debug!(
+                    "weaviate - '{collection}' has no '{property}' property, querying without it"
+                );
+
+                false
+            })
+            .collect();
+
+        match self.client.query.get(build_query(properties)).await {
+            Ok(res) => Ok(res),
+            Err(e) => err!(Weaviate, "{}", e),
+        }
+    }
+
+    async fn class_properties(&self, collection: &str) -> Result<Vec<Property>, ChonkitError> {
+        match self.client.schema.get_class(collection).await {
+            Ok(class) => Ok(class.properties.map(|p| p.0).unwrap_or_default()),
+            Err(e) => err!(Weaviate, "{e}"),
+        }
+    }
+
+    /// Add the properties the metadata fields of `content` are stored in to the class, see
+    /// [METADATA_FIELD_PREFIX]. Returns the properties of the class.
+    ///
+    /// Text is tokenized as a whole so that it is only equal to the exact same text.
+    async fn add_metadata_properties(
+        &self,
+        collection: &str,
+        content: &[CollectionItemText<'_>],
+    ) -> Result<Vec<Property>, ChonkitError> {
+        let mut properties = self.class_properties(collection).await?;
+        let mut outdated = false;
+
+        let fields = content
+            .iter()
+            .filter_map(|content| content.metadata)
+            .flat_map(metadata_fields);
+
+        for (name, data_type, _) in fields {
+            if properties.iter().any(|p| p.name == name) {
+                continue;
+            }
+
+            let mut property = PropertyBuilder::new(&name, vec![data_type]);
+            if data_type == "text" {
+                property = property.with_tokenization(Tokenization::FIELD);
+            }
+            let property = property.build();
+
+            // Concurrent inserts can add the same property
+            if let Err(e) = self.client.schema.add_property(collection, &property).await {
+                tracing::warn!("weaviate - failed to add property '{name}' to '{collection}': {e}");
+                outdated = true;
+            }
+
+            properties.push(property);
+        }
+
+        if outdated {
+            return self.class_properties(collection).await;
+        }
+
+        Ok(properties)
+    }
+
     async fn get_id_vector(&self, collection: &str) -> Result<VectorCollection, ChonkitError> {
         let query = GetQuery::builder(collection, VectorCollection::query_properties().to_vec())
             .with_where(&format!(
@@ -475,15 +883,29 @@ impl<'a> WeaviateInner<'a> {
     ) -> Result<(), ChonkitError> {
         debug_assert_eq!(content.len(), vectors.len());
 
+        let class_properties = self.add_metadata_properties(collection, &content).await?;
+
         let objects = content
             .iter()
             .zip(vectors.into_iter())
             .filter_map(|(content, vector)| {
-                let properties = serde_json::to_value(content).ok()?;
+                let mut properties = serde_json::to_value(content).ok()?;
+
+                if let Some(metadata) = content.metadata {
+                    properties[METADATA_PROPERTY] = serde_json::Value::String(metadata.to_string());
+
+                    // Fields whose type differs from their property's are not filterable
+                    for (name, data_type, value) in metadata_fields(metadata) {
+                        if has_property(&class_properties, &name, data_type) {
+                            properties[name] = value;
+                        }
+                    }
+                }
+
                 Some(
                     Object::builder(collection, properties)
                         .with_vector(vector)
-                        .with_id(uuid::Uuid::new_v4())
+                        .with_id(content.id())
                         .build(),
                 )
             })
@@ -531,8 +953,174 @@ impl<'a> WeaviateInner<'a> {
     }
 }
 
+/// A `where` operand translated from a metadata filter.
+///
+/// Weaviate rejects filters on properties a class does not have, so conditions on metadata
+/// fields no chunk has (or has with a different type) are resolved before querying.
+enum MetadataWhere {
+    Operand(String),
+    Always,
+    Never,
+}
+
+/// Translate a metadata filter to a `where` operand on the properties the metadata fields are
+/// stored in. Weaviate only supports ranges on numbers.
+fn metadata_where(
+    expr: &FilterExpr,
+    properties: &[Property],
+) -> Result<MetadataWhere, ChonkitError> {
+    let (operator, operands) = match expr {
+        FilterExpr::And { and } => ("And", and),
+        FilterExpr::Or { or } => ("Or", or),
+        FilterExpr::Condition(FilterCondition { field, op, value }) => {
+            let Some(property) = metadata_field_property(field) else {
+                return err!(
+                    OperationUnsupported,
+                    "Weaviate can only filter on metadata fields consisting of letters, digits and underscores, got '{field}'"
+                );
+            };
+
+            if op.is_range() && value.as_f64().is_none() {
+                return err!(
+                    OperationUnsupported,
+                    "Weaviate only supports range filters on numbers, '{field}' is compared to {value:?}"
+                );
+            }
+
+            let (data_type, value_type, value) = match value {
+                FilterValue::Bool(value) => ("boolean", "valueBoolean", value.to_string()),
+                FilterValue::Integer(value) => ("number", "valueNumber", value.to_string()),
+                FilterValue::Float(value) => ("number", "valueNumber", value.to_string()),
+                FilterValue::String(value) => {
+                    ("text", "valueText", map_err!(serde_json::to_string(value)))
+                }
+            };
+
+            // Missing fields are not equal to anything
+            if !has_property(properties, &property, data_type) {
+                return Ok(match op {
+                    FilterOp::Ne => MetadataWhere::Always,
+                    _ => MetadataWhere::Never,
+                });
+            }
+
+            let operator = match op {
+                FilterOp::Eq => "Equal",
+                FilterOp::Ne => "NotEqual",
+                FilterOp::Gt => "GreaterThan",
+                FilterOp::Gte => "GreaterThanEqual",
+                FilterOp::Lt => "LessThan",
+                FilterOp::Lte => "LessThanEqual",
+            };
+
+            return Ok(MetadataWhere::Operand(format!(
+                "{{ path: [\"{property}\"], operator: {operator}, {value_type}: {value} }}"
+            )));
+        }
+    };
+
+    let operands = operands
+        .iter()
+        .map(|expr| metadata_where(expr, properties))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let and = operator == "And";
+
+    let mut clauses = vec![];
+    for operand in operands {
+        match operand {
+            MetadataWhere::Operand(clause) => clauses.push(clause),
+            MetadataWhere::Never if and => return Ok(MetadataWhere::Never),
+            MetadataWhere::Always if !and => return Ok(MetadataWhere::Always),
+            MetadataWhere::Always | MetadataWhere::Never => {}
+        }
+    }
+
+    if clauses.is_empty() {
+        return Ok(if and {
+            MetadataWhere::Always
+        } else {
+            MetadataWhere::Never
+        });
+    }
+
+    Ok(MetadataWhere::Operand(where_operator(operator, clauses)))
+}
+
+/// Combine the `operands` with the `operator`, `operands` must not be empty.
+fn where_operator(operator: &str, mut operands: Vec<String>) -> String {
+    if operands.len() == 1 {
+        return operands.remove(0);
+    }
+
+    format!(
+        "{{ operator: {operator}, operands: [{}] }}",
+        operands.join(", ")
+    )
+}
+
+/// The property a metadata field is stored in, if the field can be stored in one.
+fn metadata_field_property(field: &str) -> Option<String> {
+    field.split('.').all(is_metadata_key).then(|| {
+        format!(
+            "{METADATA_FIELD_PREFIX}{}",
+            field.replace('.', METADATA_FIELD_SEPARATOR)
+        )
+    })
+}
+
+/// Property names may only contain letters, digits and underscores.
+fn is_metadata_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.contains(METADATA_FIELD_SEPARATOR)
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn has_property(properties: &[Property], name: &str, data_type: &str) -> bool {
+    properties
+        .iter()
+        .any(|p| p.name == name && p.data_type == [data_type])
+}
+
+/// The filterable metadata fields as their property names, types and values.
+/// Arrays and fields that cannot be stored in a property are left out.
+fn metadata_fields(metadata: &serde_json::Value) -> Vec<(String, &'static str, serde_json::Value)> {
+    fn collect(
+        value: &serde_json::Value,
+        property: &str,
+        fields: &mut Vec<(String, &'static str, serde_json::Value)>,
+    ) {
+        let data_type = match value {
+            serde_json::Value::Object(object) => {
+                for (key, value) in object.iter().filter(|(key, _)| is_metadata_key(key)) {
+                    let property = if property.is_empty() {
+                        format!("{METADATA_FIELD_PREFIX}{key}")
+                    } else {
+                        format!("{property}{METADATA_FIELD_SEPARATOR}{key}")
+                    };
+                    collect(value, &property, fields);
+                }
+                return;
+            }
+            serde_json::Value::Bool(_) => "boolean",
+            serde_json::Value::Number(_) => "number",
+            serde_json::Value::String(_) => "text",
+            serde_json::Value::Array(_) | serde_json::Value::Null => return,
+        };
+
+        if !property.is_empty() {
+            fields.push((property.to_string(), data_type, value.clone()));
+        }
+    }
+
+    let mut fields = vec![];
+    collect(metadata, "", &mut fields);
+    fields
+}
+
 mod dto {
     use serde::Deserialize;
+    use uuid::Uuid;
 
     #[derive(Debug, Deserialize)]
     pub struct WeaviateError {
@@ -554,6 +1142,30 @@ mod dto {
         #[serde(rename = "Get")]
         pub get: serde_json::Value,
     }
+
+    /// An object obtained when listing the vectors of a class.
+    #[derive(Debug, Deserialize)]
+    pub struct ListedObject {
+        pub document_id: Option<Uuid>,
+        pub chunk_index: Option<usize>,
+        pub content: Option<String>,
+        pub synthetic: Option<bool>,
+        #[serde(
+            default,
+            deserialize_with = "crate::core::vector::deserialize_metadata"
+        )]
+        pub metadata: Option<serde_json::Value>,
+        pub parent_index: Option<usize>,
+        pub parent_content: Option<String>,
+        #[serde(rename = "_additional")]
+        pub additional: ListedObjectAdditional,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ListedObjectAdditional {
+        pub id: Uuid,
+        pub vector: Vec<f64>,
+    }
 }
 
 #[cfg(test)]
@@ -565,10 +1177,19 @@ mod weaviate_tests {
             test::{init_weaviate, AsyncContainer},
             vector::weaviate::{WeaviateDb, WeaviateInner},
         },
-        core::vector::{CollectionItemText, CreateVectorCollection, VectorDb},
+        core::vector::{
+            CollectionItemInsert, CollectionItemText, CreateVectorCollection, VectorDb,
+            CONTENT_PROPERTY, DOCUMENT_ID_PROPERTY,
+        },
+        error::ChonkitErr,
     };
+    use serde_json::json;
     use suitest::before_all;
     use uuid::Uuid;
+    use weaviate_community::collections::{
+        objects::Object,
+        schema::{Class, Properties, PropertyBuilder},
+    };
 
     #[before_all]
     async fn setup() -> (WeaviateDb, AsyncContainer) {
@@ -646,7 +1267,12 @@ mod weaviate_tests {
                 name,
                 vec![CollectionItemText {
                     content: "foo",
+                    chunk_index: Some(0),
                     document_id,
+                    metadata: None,
+                    synthetic: false,
+                    parent_index: None,
+                    parent_content: None,
                 }],
                 vec![vec![0.420f64; 420]],
             )
@@ -654,12 +1280,120 @@ mod weaviate_tests {
             .unwrap();
 
         let results = weaviate
-            .query(vec![0.420f64; 420], name, 420, None)
+            .query(vec![0.420f64; 420], name, 420, None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(1, results.len());
+        assert_eq!("foo", results[0].item.payload.as_content());
+
+        weaviate.delete_vector_collection(name).await.unwrap();
+    }
+
+    #[test]
+    async fn returns_document_id_and_chunk_index(weaviate: WeaviateDb) {
+        let name = "My_collection_for_query_item_meta";
+        let id = Uuid::new_v4();
+        let document_id = Uuid::new_v4();
+
+        let collection =
+            CreateVectorCollection::new(id, name, 420, "openai", "text-embedding-ada-002", None);
+
+        weaviate.create_vector_collection(collection).await.unwrap();
+
+        let mut foo = vec![0.0f64; 420];
+        foo[0] = 1.0;
+        let mut bar = vec![0.0f64; 420];
+        bar[1] = 1.0;
+
+        weaviate
+            .insert_embeddings(CollectionItemInsert::new_text(
+                document_id,
+                name,
+                &["foo", "bar"],
+                vec![foo, bar.clone()],
+                None,
+            ))
+            .await
+            .unwrap();
+
+        let results = VectorDb::query(weaviate.as_ref(), bar, name, 1, None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(1, results.len());
+        assert_eq!("bar", results[0].item.payload.as_content());
+        assert_eq!(Some(document_id), results[0].item.document_id);
+        assert_eq!(Some(1), results[0].item.payload.chunk_index());
+
+        weaviate.delete_vector_collection(name).await.unwrap();
+    }
+
+    #[test]
+    async fn queries_classes_without_optional_properties(weaviate: WeaviateDb) {
+        let name = "My_collection_without_optional_properties";
+        let document_id = Uuid::new_v4();
+
+        // Classes created before chunk indices, metadata, etc. were stored
+        let class = Class::builder(name)
+            .with_properties(Properties::new(vec![
+                PropertyBuilder::new(DOCUMENT_ID_PROPERTY, vec!["uuid"]).build(),
+                PropertyBuilder::new(CONTENT_PROPERTY, vec!["text"]).build(),
+            ]))
+            .build();
+
+        weaviate.schema.create_class(&class).await.unwrap();
+
+        let object = Object::builder(
+            name,
+            json!({
+                DOCUMENT_ID_PROPERTY: document_id,
+                CONTENT_PROPERTY: "foo",
+            }),
+        )
+        .with_vector(vec![0.420f64; 420])
+        .build();
+
+        weaviate.objects.create(&object, None).await.unwrap();
+
+        let results = weaviate
+            .query(vec![0.420f64; 420], name, 1, None, None, None)
             .await
             .unwrap();
 
         assert_eq!(1, results.len());
         assert_eq!("foo", results[0].item.payload.as_content());
+        assert_eq!(None, results[0].item.payload.chunk_index());
+
+        weaviate.delete_vector_collection(name).await.unwrap();
+    }
+
+    #[test]
+    async fn rejects_vectors_of_invalid_size(weaviate: WeaviateDb) {
+        let name = "My_collection_for_invalid_insert";
+        let id = Uuid::new_v4();
+
+        let collection =
+            CreateVectorCollection::new(id, name, 420, "openai", "text-embedding-ada-002", None);
+
+        weaviate.create_vector_collection(collection).await.unwrap();
+
+        let err = weaviate
+            .insert_embeddings(CollectionItemInsert::new_text(
+                Uuid::new_v4(),
+                name,
+                &["foo", "bar"],
+                vec![vec![0.420f64; 420], vec![0.420f64; 69]],
+                None,
+            ))
+            .await
+            .unwrap_err();
+
+        let ChonkitErr::InvalidVector(msg) = err.error else {
+            panic!("expected InvalidVector, got {:?}", err.error);
+        };
+
+        assert_eq!("vector at index 1 has size 69 (expected 420)", msg);
 
         weaviate.delete_vector_collection(name).await.unwrap();
     }
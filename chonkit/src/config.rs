@@ -1,3 +1,10 @@
+use crate::core::cache::CachePrecision;
+use crate::core::document::parser::ParserDefaults;
+use crate::core::document::{
+    DocumentHashing, DocumentType, HashAlgorithm, ImageExtraction, ImageLimitPolicy,
+};
+use crate::core::model::embedding::ReportRetention;
+use crate::core::service::embedding::AutoCreateCollections;
 use clap::Parser;
 
 // Adapter identifiers.
@@ -34,16 +41,81 @@ pub const DEFAULT_DOCUMENT_CONTENT: &str = r#"Raguru Labamba, the pride of plane
 /// Used to estimate the token count, i.e. the context length of the image tokens.
 pub const DEFAULT_IMAGE_PATCH_SIZE: u32 = 14;
 
+/// The default maximum amount of chunks a single document can be chunked into.
+pub const DEFAULT_MAX_CHUNKS: usize = 100_000;
+
+/// The default amount of milliseconds to wait for a document to be parsed.
+pub const DEFAULT_PARSE_TIMEOUT: u64 = 120_000;
+
+/// The default maximum amount of items returned by a single page of list endpoints.
+pub const DEFAULT_MAX_PAGE_SIZE: usize = 200;
+
+/// The default maximum size of the vectors of collections.
+pub const DEFAULT_MAX_VECTOR_SIZE: usize = 65_536;
+
+/// The default amount of results returned by collection searches.
+pub const DEFAULT_SEARCH_LIMIT: u32 = 5;
+
+/// The default amount of milliseconds searches wait for the vector database.
+pub const DEFAULT_SEARCH_TIMEOUT: u64 = 10_000;
+
+/// The default model used for rewriting search queries and answering questions.
+pub const DEFAULT_LLM_MODEL: &str = "gpt-4o-mini";
+
+/// The default maximum amount of tokens of the context given to the LLM when answering questions.
+pub const DEFAULT_ASK_CONTEXT_TOKENS: usize = 4_000;
+
+/// The default maximum size in bytes of request bodies uploading documents.
+pub const DEFAULT_UPLOAD_BODY_LIMIT: usize = 100_000_000;
+
+/// The default maximum size in bytes of search and question request bodies.
+pub const DEFAULT_SEARCH_BODY_LIMIT: usize = 65_536;
+
+/// The default maximum size in bytes of request bodies of all other endpoints.
+pub const DEFAULT_BODY_LIMIT: usize = 2_097_152;
+
+/// The default maximum total size in bytes of the files extracted from an uploaded archive.
+pub const DEFAULT_ARCHIVE_MAX_SIZE: usize = 500_000_000;
+
+/// The default maximum size in bytes of a single file extracted from an uploaded archive.
+pub const DEFAULT_ARCHIVE_MAX_ENTRY_SIZE: usize = 100_000_000;
+
+/// The maximum size in bytes of content previewed without being stored as a document.
+pub const MAX_INLINE_PREVIEW_SIZE: usize = 1_000_000;
+
 const REDIS_EMBEDDING_DB: &str = "0";
 const REDIS_IMAGE_DB: &str = "1";
+const REDIS_IDEMPOTENCY_DB: &str = "2";
+
+/// The default amount of seconds document upload idempotency keys are kept for.
+const DEFAULT_IDEMPOTENCY_TTL: u64 = 60 * 60 * 24;
 
 /// The default upload path for the `fs` document storage provider.
 const DEFAULT_UPLOAD_PATH: &str = "data/upload";
 /// The default address to listen on.
 const DEFAULT_ADDRESS: &str = "0.0.0.0:42069";
+/// The default amount of concurrent embedding requests per document.
+const DEFAULT_EMBEDDING_CONCURRENCY: usize = 1;
+/// The default amount of concurrent requests to Weaviate.
+#[cfg(feature = "weaviate")]
+const DEFAULT_WEAVIATE_MAX_REQUESTS: usize = 16;
+/// The default maximum amount of vectors sent to a vector database in a single insert.
+const DEFAULT_VECTOR_INSERT_BATCH_SIZE: usize = 100;
+/// The default amount of seconds to wait for batch embedding jobs when shutting down.
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+/// The default amount of attempts at connecting to Postgres and Redis on startup.
+pub const DEFAULT_STARTUP_CONNECT_ATTEMPTS: u32 = 10;
+/// The default base delay between startup connection attempts, in milliseconds.
+pub const DEFAULT_STARTUP_CONNECT_BACKOFF: u64 = 500;
 
 #[cfg(feature = "gdrive")]
 const DEFAULT_GOOGLE_DRIVE_DOWNLOAD_PATH: &str = "data/gdrive";
+/// The default amount of attempts for polling and resuming Google Drive downloads.
+#[cfg(feature = "gdrive")]
+pub const DEFAULT_GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS: u32 = 10;
+/// The default base delay between Google Drive download attempts, in milliseconds.
+#[cfg(feature = "gdrive")]
+pub const DEFAULT_GOOGLE_DRIVE_DOWNLOAD_BACKOFF: u64 = 200;
 
 #[derive(Debug, Parser)]
 #[command(name = "chonkit", author = "biblius", version = "0.1", about = "Chunk documents", long_about = None)]
@@ -72,6 +144,26 @@ pub struct StartArgs {
     #[arg(long)]
     cors_allowed_headers: Option<String>,
 
+    /// The maximum size in bytes of request bodies uploading documents.
+    #[arg(long)]
+    upload_body_limit: Option<usize>,
+
+    /// The maximum size in bytes of search and question request bodies.
+    #[arg(long)]
+    search_body_limit: Option<usize>,
+
+    /// The maximum size in bytes of request bodies of all other endpoints.
+    #[arg(long)]
+    body_limit: Option<usize>,
+
+    /// The maximum total size in bytes of the files extracted from an uploaded archive.
+    #[arg(long)]
+    archive_max_size: Option<usize>,
+
+    /// The maximum size in bytes of a single file extracted from an uploaded archive.
+    #[arg(long)]
+    archive_max_entry_size: Option<usize>,
+
     /// Redis URL.
     #[arg(long)]
     redis_url: Option<String>,
@@ -79,9 +171,20 @@ pub struct StartArgs {
     #[arg(long)]
     redis_embedding_db: Option<String>,
 
+    /// The precision of vectors stored in the text embedding cache, `f64` or `f16`.
+    #[arg(long)]
+    embedding_cache_precision: Option<CachePrecision>,
+
     #[arg(long)]
     redis_image_db: Option<String>,
 
+    #[arg(long)]
+    redis_idempotency_db: Option<String>,
+
+    /// How long document upload idempotency keys are kept for, in seconds.
+    #[arg(long)]
+    idempotency_ttl: Option<u64>,
+
     #[arg(long)]
     minio_url: Option<String>,
 
@@ -94,10 +197,165 @@ pub struct StartArgs {
     #[arg(long)]
     minio_secret_key: Option<String>,
 
+    /// Comma separated list of embedding provider IDs whose vectors should be L2 normalized
+    /// before they are stored or used for search.
+    #[arg(long)]
+    normalize_embeddings: Option<String>,
+
+    /// Comma separated list of embedding provider IDs whose unlisted models should be probed
+    /// for their dimensions instead of being rejected.
+    #[arg(long)]
+    probe_embedding_models: Option<String>,
+
+    /// If set, every request made to an embedding provider is recorded in the database along
+    /// with its latency and token usage.
+    #[arg(long)]
+    audit_embedding_requests: bool,
+
+    /// If set, embedders will split the content they embed into batches of this size.
+    #[arg(long)]
+    embedding_batch_size: Option<usize>,
+
+    /// The maximum amount of batches embedded at the same time, per document.
+    /// Has no effect if `embedding_batch_size` is not set.
+    #[arg(long)]
+    embedding_concurrency: Option<usize>,
+
+    /// The maximum amount of chunks a single document can be chunked into. Chunking
+    /// configurations exceeding this are rejected.
+    #[arg(long)]
+    max_chunks: Option<usize>,
+
+    /// The amount of milliseconds to wait for a document to be parsed, unless its parsing
+    /// configuration specifies a timeout.
+    #[arg(long)]
+    parse_timeout: Option<u64>,
+
+    /// The maximum amount of items returned by a single page of list endpoints. Larger page
+    /// sizes, and requests without one, are clamped to this.
+    #[arg(long)]
+    max_page_size: Option<usize>,
+
+    /// The maximum vector size of the embedding model of new collections. Collections of
+    /// models reporting a larger size are rejected on creation.
+    #[arg(long)]
+    max_vector_size: Option<usize>,
+
+    /// The amount of results returned by searches that do not specify a limit.
+    #[arg(long)]
+    search_limit: Option<u32>,
+
+    /// The distance threshold, between 0 and 2, applied to searches that do not specify one.
+    /// If not set, searches without a threshold return all results up to the limit.
+    #[arg(long)]
+    search_max_distance: Option<f64>,
+
+    /// The amount of milliseconds to wait for the vector database on searches that do not
+    /// specify a timeout.
+    #[arg(long)]
+    search_timeout: Option<u64>,
+
+    /// Base URL of an OpenAI compatible chat completion API, e.g. `https://api.openai.com/v1`.
+    /// Searches can only rewrite queries and questions can only be answered if set.
+    #[arg(long)]
+    llm_endpoint: Option<String>,
+
+    /// The model used for rewriting search queries and answering questions.
+    #[arg(long)]
+    llm_model: Option<String>,
+
+    /// The maximum amount of tokens of the retrieved chunks given to the LLM when answering
+    /// questions that do not specify one.
+    #[arg(long)]
+    ask_context_tokens: Option<usize>,
+
+    /// Base URL of a Cohere compatible rerank API, e.g. `https://api.cohere.com/v2`.
+    /// Searches can only be reranked if set.
+    #[arg(long)]
+    rerank_endpoint: Option<String>,
+
+    /// If set, embedding reports older than this are periodically deleted, e.g. `30d`.
+    #[arg(long)]
+    report_retention: Option<ReportRetention>,
+
+    /// The algorithm used for hashing document contents, `sha256` or `sha512`.
+    #[arg(long)]
+    hash_algorithm: Option<HashAlgorithm>,
+
+    /// If set, documents with the same content as an existing document are linked to it as
+    /// an additional source instead of being rejected or stored separately.
+    #[arg(long)]
+    link_duplicate_documents: bool,
+
+    /// If set, documents are only considered ready once they are embedded in at least one
+    /// collection, in addition to having a parsing and chunking configuration.
+    #[arg(long)]
+    ready_requires_embeddings: bool,
+
+    /// The maximum amount of images stored per document. Unlimited if not set.
+    #[arg(long)]
+    max_images_per_document: Option<usize>,
+
+    /// What happens to documents with more images than the maximum, `truncate` to store
+    /// images up to the maximum or `reject` to store none.
+    #[arg(long)]
+    image_limit_policy: Option<ImageLimitPolicy>,
+
+    /// Images extracted from documents whose width or height is below this amount of pixels
+    /// are not stored.
+    #[arg(long)]
+    min_image_size: Option<u32>,
+
+    /// Comma separated list of `source=interval` pairs of document sources to periodically
+    /// sync, e.g. `fs=30m,google=1h`. Intervals are an amount followed by d, h, m or s.
+    #[arg(long)]
+    sync_interval: Option<String>,
+
+    /// Comma separated list of document extensions accepted for upload and import.
+    /// Defaults to all supported document types.
+    #[arg(long)]
+    allowed_document_types: Option<String>,
+
+    /// Create collections that do not exist when embedding into them by name, using the
+    /// providers and model of the `auto_create_*` arguments.
+    #[arg(long)]
+    auto_create_collections: bool,
+
+    /// Vector database provider of automatically created collections.
+    #[arg(long)]
+    auto_create_vector_provider: Option<String>,
+
+    /// Embedding provider of automatically created collections.
+    #[arg(long)]
+    auto_create_embedding_provider: Option<String>,
+
+    /// Embedding model of automatically created collections.
+    #[arg(long)]
+    auto_create_model: Option<String>,
+
+    /// Path to a JSON file mapping document extensions to the parsing configuration used for
+    /// documents of that type without a stored one.
+    #[arg(long)]
+    parser_defaults: Option<String>,
+
     /// Cookie domain used for setting chonkit-specific cookies.
     #[arg(long)]
     cookie_domain: Option<String>,
 
+    /// The amount of seconds to wait for batch embedding jobs in progress to finish
+    /// when shutting down.
+    #[arg(long)]
+    shutdown_timeout: Option<u64>,
+
+    /// The maximum amount of attempts at connecting to Postgres and Redis on startup.
+    #[arg(long)]
+    startup_connect_attempts: Option<u32>,
+
+    /// The base delay in milliseconds between startup connection attempts.
+    /// Multiplied by the current attempt.
+    #[arg(long)]
+    startup_connect_backoff: Option<u64>,
+
     /// Qdrant URL.
     #[cfg(feature = "qdrant")]
     #[arg(long)]
@@ -108,6 +366,17 @@ pub struct StartArgs {
     #[arg(long)]
     weaviate_url: Option<String>,
 
+    /// The maximum amount of concurrent requests to Weaviate.
+    /// Requests over the limit wait for running ones to complete.
+    #[cfg(feature = "weaviate")]
+    #[arg(long)]
+    weaviate_max_requests: Option<usize>,
+
+    /// The maximum amount of vectors sent to a vector database in a single insert.
+    /// Larger inserts are split into sub-batches of this size.
+    #[arg(long)]
+    vector_insert_batch_size: Option<usize>,
+
     /// If using the [AzureEmbeddings][crate::app::embedder::azure::AzureEmbeddings] module, set its endpoint.
     #[cfg(feature = "azure")]
     #[arg(long)]
@@ -118,6 +387,13 @@ pub struct StartArgs {
     #[arg(long)]
     azure_api_version: Option<String>,
 
+    /// If using the [AzureEmbeddings][crate::app::embedder::azure::AzureEmbeddings] module, map
+    /// model names to the Azure deployments serving them.
+    /// Comma separated list of `model=deployment` pairs.
+    #[cfg(feature = "azure")]
+    #[arg(long)]
+    azure_deployments: Option<String>,
+
     /// If using the [VllmEmbeddings][crate::app::embedder::vllm::VllmEmbeddings] module, set its
     /// endpoint.
     #[cfg(feature = "vllm")]
@@ -129,6 +405,14 @@ pub struct StartArgs {
     #[arg(long)]
     vllm_api_key: Option<String>,
 
+    /// If using the [VllmEmbeddings][crate::app::embedder::vllm::VllmEmbeddings] module, pool
+    /// the token-level outputs of models the server does not pool.
+    /// Comma separated list of `model=pooling:size[:normalize]` entries, where pooling is one of
+    /// `mean`, `cls` or `last-token`.
+    #[cfg(feature = "vllm")]
+    #[arg(long)]
+    vllm_pooling: Option<String>,
+
     /// If using the fastembedder remote embedding module, set its endpoint.
     #[cfg(feature = "fe-remote")]
     #[arg(short, long)]
@@ -147,6 +431,68 @@ pub struct StartArgs {
     #[cfg(feature = "gdrive")]
     #[arg(long)]
     google_drive_download_path: Option<String>,
+
+    /// The maximum amount of attempts when polling Drive download operations
+    /// and when resuming interrupted downloads.
+    #[cfg(feature = "gdrive")]
+    #[arg(long)]
+    google_drive_download_attempts: Option<u32>,
+
+    /// The base delay in milliseconds between Drive download attempts.
+    /// Multiplied by the current attempt.
+    #[cfg(feature = "gdrive")]
+    #[arg(long)]
+    google_drive_download_backoff: Option<u64>,
+}
+
+/// Parse a comma separated list of provider IDs from the arg, falling back to the env variable.
+fn provider_ids(arg: &Option<String>, env: &str) -> Vec<String> {
+    let ids = match arg {
+        Some(ids) => ids.clone(),
+        None => std::env::var(env).unwrap_or_default(),
+    };
+
+    ids.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Get a size limit in bytes from the arg, falling back to the env variable and then the default.
+fn size_limit(arg: Option<usize>, env: &str, default: usize) -> usize {
+    let limit = arg
+        .or_else(|| {
+            std::env::var(env).ok().map(|limit| {
+                limit
+                    .parse()
+                    .unwrap_or_else(|_| panic!("{env} must be a positive integer"))
+            })
+        })
+        .unwrap_or(default);
+
+    assert!(limit > 0, "{env} must be a positive integer");
+
+    limit
+}
+
+/// Parse an interval given as an amount followed by d, h, m or s, e.g. `30m`.
+/// Returns `None` if the interval is malformed or zero.
+fn parse_interval(interval: &str) -> Option<std::time::Duration> {
+    let unit = interval.chars().last()?;
+    let amount = interval[..interval.len() - unit.len_utf8()]
+        .parse::<u64>()
+        .ok()?;
+
+    let seconds = match unit {
+        'd' => amount * 60 * 60 * 24,
+        'h' => amount * 60 * 60,
+        'm' => amount * 60,
+        's' => amount,
+        _ => return None,
+    };
+
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
 }
 
 /// Implement a getter method on [StartArgs], using the `$var` environment variable as a fallback
@@ -217,6 +563,516 @@ impl StartArgs {
         }
     }
 
+    pub fn normalize_embeddings(&self) -> Vec<String> {
+        provider_ids(&self.normalize_embeddings, "NORMALIZE_EMBEDDINGS")
+    }
+
+    pub fn probe_embedding_models(&self) -> Vec<String> {
+        provider_ids(&self.probe_embedding_models, "PROBE_EMBEDDING_MODELS")
+    }
+
+    pub fn audit_embedding_requests(&self) -> bool {
+        self.audit_embedding_requests
+            || std::env::var("AUDIT_EMBEDDING_REQUESTS").is_ok_and(|audit| audit == "true")
+    }
+
+    pub fn embedding_batch_size(&self) -> Option<usize> {
+        self.embedding_batch_size.or_else(|| {
+            std::env::var("EMBEDDING_BATCH_SIZE").ok().map(|size| {
+                size.parse()
+                    .expect("EMBEDDING_BATCH_SIZE must be a positive integer")
+            })
+        })
+    }
+
+    pub fn embedding_concurrency(&self) -> usize {
+        self.embedding_concurrency
+            .or_else(|| {
+                std::env::var("EMBEDDING_CONCURRENCY")
+                    .ok()
+                    .map(|concurrency| {
+                        concurrency
+                            .parse()
+                            .expect("EMBEDDING_CONCURRENCY must be a positive integer")
+                    })
+            })
+            .unwrap_or(DEFAULT_EMBEDDING_CONCURRENCY)
+    }
+
+    #[cfg(feature = "weaviate")]
+    pub fn weaviate_max_requests(&self) -> usize {
+        let max = self
+            .weaviate_max_requests
+            .or_else(|| {
+                std::env::var("WEAVIATE_MAX_REQUESTS").ok().map(|max| {
+                    max.parse()
+                        .expect("WEAVIATE_MAX_REQUESTS must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_WEAVIATE_MAX_REQUESTS);
+
+        assert!(max > 0, "WEAVIATE_MAX_REQUESTS must be a positive integer");
+
+        max
+    }
+
+    pub fn vector_insert_batch_size(&self) -> usize {
+        let size = self
+            .vector_insert_batch_size
+            .or_else(|| {
+                std::env::var("VECTOR_INSERT_BATCH_SIZE").ok().map(|size| {
+                    size.parse()
+                        .expect("VECTOR_INSERT_BATCH_SIZE must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_VECTOR_INSERT_BATCH_SIZE);
+
+        assert!(
+            size > 0,
+            "VECTOR_INSERT_BATCH_SIZE must be a positive integer"
+        );
+
+        size
+    }
+
+    pub fn max_chunks(&self) -> usize {
+        self.max_chunks
+            .or_else(|| {
+                std::env::var("MAX_CHUNKS")
+                    .ok()
+                    .map(|max| max.parse().expect("MAX_CHUNKS must be a positive integer"))
+            })
+            .unwrap_or(DEFAULT_MAX_CHUNKS)
+    }
+
+    pub fn parse_timeout(&self) -> std::time::Duration {
+        let timeout = self
+            .parse_timeout
+            .or_else(|| {
+                std::env::var("PARSE_TIMEOUT").ok().map(|timeout| {
+                    timeout
+                        .parse()
+                        .expect("PARSE_TIMEOUT must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_PARSE_TIMEOUT);
+
+        assert!(timeout > 0, "PARSE_TIMEOUT must be a positive integer");
+
+        std::time::Duration::from_millis(timeout)
+    }
+
+    pub fn max_page_size(&self) -> usize {
+        let max = self
+            .max_page_size
+            .or_else(|| {
+                std::env::var("MAX_PAGE_SIZE").ok().map(|max| {
+                    max.parse()
+                        .expect("MAX_PAGE_SIZE must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_PAGE_SIZE);
+
+        assert!(max > 0, "MAX_PAGE_SIZE must be a positive integer");
+
+        max
+    }
+
+    pub fn upload_body_limit(&self) -> usize {
+        size_limit(
+            self.upload_body_limit,
+            "UPLOAD_BODY_LIMIT",
+            DEFAULT_UPLOAD_BODY_LIMIT,
+        )
+    }
+
+    pub fn search_body_limit(&self) -> usize {
+        size_limit(
+            self.search_body_limit,
+            "SEARCH_BODY_LIMIT",
+            DEFAULT_SEARCH_BODY_LIMIT,
+        )
+    }
+
+    pub fn body_limit(&self) -> usize {
+        size_limit(self.body_limit, "BODY_LIMIT", DEFAULT_BODY_LIMIT)
+    }
+
+    pub fn archive_max_size(&self) -> usize {
+        size_limit(
+            self.archive_max_size,
+            "ARCHIVE_MAX_SIZE",
+            DEFAULT_ARCHIVE_MAX_SIZE,
+        )
+    }
+
+    pub fn archive_max_entry_size(&self) -> usize {
+        size_limit(
+            self.archive_max_entry_size,
+            "ARCHIVE_MAX_ENTRY_SIZE",
+            DEFAULT_ARCHIVE_MAX_ENTRY_SIZE,
+        )
+    }
+
+    pub fn max_vector_size(&self) -> usize {
+        let max = self
+            .max_vector_size
+            .or_else(|| {
+                std::env::var("MAX_VECTOR_SIZE").ok().map(|max| {
+                    max.parse()
+                        .expect("MAX_VECTOR_SIZE must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_MAX_VECTOR_SIZE);
+
+        assert!(max > 0, "MAX_VECTOR_SIZE must be a positive integer");
+
+        max
+    }
+
+    pub fn search_limit(&self) -> u32 {
+        let limit = self
+            .search_limit
+            .or_else(|| {
+                std::env::var("SEARCH_LIMIT").ok().map(|limit| {
+                    limit
+                        .parse()
+                        .expect("SEARCH_LIMIT must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+        assert!(limit > 0, "SEARCH_LIMIT must be a positive integer");
+
+        limit
+    }
+
+    pub fn search_max_distance(&self) -> Option<f64> {
+        let distance = self.search_max_distance.or_else(|| {
+            std::env::var("SEARCH_MAX_DISTANCE").ok().map(|distance| {
+                distance
+                    .parse()
+                    .expect("SEARCH_MAX_DISTANCE must be a number between 0 and 2")
+            })
+        })?;
+
+        assert!(
+            (0. ..=2.).contains(&distance),
+            "SEARCH_MAX_DISTANCE must be a number between 0 and 2"
+        );
+
+        Some(distance)
+    }
+
+    pub fn search_timeout(&self) -> std::time::Duration {
+        let timeout = self
+            .search_timeout
+            .or_else(|| {
+                std::env::var("SEARCH_TIMEOUT").ok().map(|timeout| {
+                    timeout
+                        .parse()
+                        .expect("SEARCH_TIMEOUT must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_SEARCH_TIMEOUT);
+
+        assert!(timeout > 0, "SEARCH_TIMEOUT must be a positive integer");
+
+        std::time::Duration::from_millis(timeout)
+    }
+
+    pub fn ask_context_tokens(&self) -> usize {
+        let tokens = self
+            .ask_context_tokens
+            .or_else(|| {
+                std::env::var("ASK_CONTEXT_TOKENS").ok().map(|tokens| {
+                    tokens
+                        .parse()
+                        .expect("ASK_CONTEXT_TOKENS must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_ASK_CONTEXT_TOKENS);
+
+        assert!(tokens > 0, "ASK_CONTEXT_TOKENS must be a positive integer");
+
+        tokens
+    }
+
+    #[cfg(feature = "gdrive")]
+    pub fn google_drive_download_attempts(&self) -> u32 {
+        let attempts = self
+            .google_drive_download_attempts
+            .or_else(|| {
+                std::env::var("GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS")
+                    .ok()
+                    .map(|attempts| {
+                        attempts
+                            .parse()
+                            .expect("GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS must be a positive integer")
+                    })
+            })
+            .unwrap_or(DEFAULT_GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS);
+
+        assert!(
+            attempts > 0,
+            "GOOGLE_DRIVE_DOWNLOAD_ATTEMPTS must be a positive integer"
+        );
+
+        attempts
+    }
+
+    #[cfg(feature = "gdrive")]
+    pub fn google_drive_download_backoff(&self) -> u64 {
+        self.google_drive_download_backoff
+            .or_else(|| {
+                std::env::var("GOOGLE_DRIVE_DOWNLOAD_BACKOFF")
+                    .ok()
+                    .map(|backoff| {
+                        backoff
+                            .parse()
+                            .expect("GOOGLE_DRIVE_DOWNLOAD_BACKOFF must be a positive integer")
+                    })
+            })
+            .unwrap_or(DEFAULT_GOOGLE_DRIVE_DOWNLOAD_BACKOFF)
+    }
+
+    pub fn embedding_cache_precision(&self) -> CachePrecision {
+        self.embedding_cache_precision
+            .or_else(|| {
+                std::env::var("EMBEDDING_CACHE_PRECISION")
+                    .ok()
+                    .map(|precision| {
+                        precision
+                            .parse()
+                            .expect("EMBEDDING_CACHE_PRECISION must be f64 or f16")
+                    })
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn idempotency_ttl(&self) -> u64 {
+        self.idempotency_ttl
+            .or_else(|| {
+                std::env::var("IDEMPOTENCY_TTL").ok().map(|ttl| {
+                    ttl.parse()
+                        .expect("IDEMPOTENCY_TTL must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_IDEMPOTENCY_TTL)
+    }
+
+    pub fn shutdown_timeout(&self) -> std::time::Duration {
+        let timeout = self
+            .shutdown_timeout
+            .or_else(|| {
+                std::env::var("SHUTDOWN_TIMEOUT").ok().map(|timeout| {
+                    timeout
+                        .parse()
+                        .expect("SHUTDOWN_TIMEOUT must be a positive integer")
+                })
+            })
+            .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+
+        std::time::Duration::from_secs(timeout)
+    }
+
+    pub fn startup_connect_attempts(&self) -> u32 {
+        let attempts = self
+            .startup_connect_attempts
+            .or_else(|| {
+                std::env::var("STARTUP_CONNECT_ATTEMPTS")
+                    .ok()
+                    .map(|attempts| {
+                        attempts
+                            .parse()
+                            .expect("STARTUP_CONNECT_ATTEMPTS must be a positive integer")
+                    })
+            })
+            .unwrap_or(DEFAULT_STARTUP_CONNECT_ATTEMPTS);
+
+        assert!(
+            attempts > 0,
+            "STARTUP_CONNECT_ATTEMPTS must be a positive integer"
+        );
+
+        attempts
+    }
+
+    pub fn startup_connect_backoff(&self) -> std::time::Duration {
+        let backoff = self
+            .startup_connect_backoff
+            .or_else(|| {
+                std::env::var("STARTUP_CONNECT_BACKOFF")
+                    .ok()
+                    .map(|backoff| {
+                        backoff
+                            .parse()
+                            .expect("STARTUP_CONNECT_BACKOFF must be a positive integer")
+                    })
+            })
+            .unwrap_or(DEFAULT_STARTUP_CONNECT_BACKOFF);
+
+        std::time::Duration::from_millis(backoff)
+    }
+
+    pub fn document_hashing(&self) -> DocumentHashing {
+        let algorithm = self
+            .hash_algorithm
+            .or_else(|| {
+                std::env::var("HASH_ALGORITHM").ok().map(|algorithm| {
+                    algorithm
+                        .parse()
+                        .expect("HASH_ALGORITHM must be sha256 or sha512")
+                })
+            })
+            .unwrap_or_default();
+
+        let link_duplicates = self.link_duplicate_documents
+            || std::env::var("LINK_DUPLICATE_DOCUMENTS").is_ok_and(|link| link == "true");
+
+        DocumentHashing {
+            algorithm,
+            link_duplicates,
+        }
+    }
+
+    pub fn image_extraction(&self) -> ImageExtraction {
+        let max_per_document = self.max_images_per_document.or_else(|| {
+            std::env::var("MAX_IMAGES_PER_DOCUMENT").ok().map(|max| {
+                max.parse()
+                    .expect("MAX_IMAGES_PER_DOCUMENT must be a non-negative integer")
+            })
+        });
+
+        let limit_policy = self
+            .image_limit_policy
+            .or_else(|| {
+                std::env::var("IMAGE_LIMIT_POLICY").ok().map(|policy| {
+                    policy
+                        .parse()
+                        .expect("IMAGE_LIMIT_POLICY must be truncate or reject")
+                })
+            })
+            .unwrap_or_default();
+
+        let min_size = self
+            .min_image_size
+            .or_else(|| {
+                std::env::var("MIN_IMAGE_SIZE").ok().map(|size| {
+                    size.parse()
+                        .expect("MIN_IMAGE_SIZE must be a non-negative integer")
+                })
+            })
+            .unwrap_or_default();
+
+        ImageExtraction {
+            max_per_document,
+            limit_policy,
+            min_size,
+        }
+    }
+
+    pub fn ready_requires_embeddings(&self) -> bool {
+        self.ready_requires_embeddings
+            || std::env::var("READY_REQUIRES_EMBEDDINGS").is_ok_and(|require| require == "true")
+    }
+
+    /// The document sources to periodically sync and their intervals.
+    pub fn sync_intervals(&self) -> Vec<(String, std::time::Duration)> {
+        let intervals = match &self.sync_interval {
+            Some(intervals) => intervals.clone(),
+            None => std::env::var("SYNC_INTERVAL").unwrap_or_default(),
+        };
+
+        intervals
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let Some((source, interval)) = pair.split_once('=') else {
+                    panic!("Invalid sync interval '{pair}'; expected `source=interval`");
+                };
+                let interval = parse_interval(interval.trim()).unwrap_or_else(|| {
+                    panic!("Invalid sync interval '{pair}'; expected an amount followed by d, h, m or s")
+                });
+                (source.trim().to_string(), interval)
+            })
+            .collect()
+    }
+
+    pub fn allowed_document_types(&self) -> Vec<DocumentType> {
+        let Some(types) = self
+            .allowed_document_types
+            .clone()
+            .or_else(|| std::env::var("ALLOWED_DOCUMENT_TYPES").ok())
+        else {
+            return DocumentType::SUPPORTED.to_vec();
+        };
+
+        types
+            .split(',')
+            .map(str::trim)
+            .filter(|ty| !ty.is_empty())
+            .map(|ty| match DocumentType::try_from(ty) {
+                Ok(ty) => ty,
+                Err(e) => panic!("ALLOWED_DOCUMENT_TYPES must only contain supported types; {e}"),
+            })
+            .collect()
+    }
+
+    pub fn auto_create_collections(&self) -> Option<AutoCreateCollections> {
+        let enabled = self.auto_create_collections
+            || std::env::var("AUTO_CREATE_COLLECTIONS").is_ok_and(|create| create == "true");
+
+        if !enabled {
+            return None;
+        }
+
+        let required = |arg: &Option<String>, var: &str| {
+            arg.clone()
+                .or_else(|| std::env::var(var).ok())
+                .unwrap_or_else(|| panic!("{var} must be set to create collections automatically"))
+        };
+
+        Some(AutoCreateCollections {
+            vector_provider: required(
+                &self.auto_create_vector_provider,
+                "AUTO_CREATE_VECTOR_PROVIDER",
+            ),
+            embedding_provider: required(
+                &self.auto_create_embedding_provider,
+                "AUTO_CREATE_EMBEDDING_PROVIDER",
+            ),
+            model: required(&self.auto_create_model, "AUTO_CREATE_MODEL"),
+        })
+    }
+
+    pub fn report_retention(&self) -> Option<ReportRetention> {
+        self.report_retention.or_else(|| {
+            std::env::var("REPORT_RETENTION").ok().map(|retention| {
+                retention
+                    .parse()
+                    .expect("REPORT_RETENTION must be an amount followed by d, h or m")
+            })
+        })
+    }
+
+    pub fn parser_defaults(&self) -> ParserDefaults {
+        let Some(path) = self
+            .parser_defaults
+            .clone()
+            .or_else(|| std::env::var("PARSER_DEFAULTS").ok())
+        else {
+            return ParserDefaults::default();
+        };
+
+        let json = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Unable to read parser defaults at '{path}': {e}"));
+
+        ParserDefaults::from_json(&json)
+            .unwrap_or_else(|e| panic!("Invalid parser defaults at '{path}': {e}"))
+    }
+
     #[cfg(feature = "openai")]
     pub fn open_ai_key(&self) -> String {
         std::env::var("OPENAI_KEY").expect("Missing OPENAI_KEY in env")
@@ -227,10 +1083,118 @@ impl StartArgs {
         std::env::var("AZURE_KEY").expect("Missing AZURE_KEY in env")
     }
 
+    /// When no deployments are configured, `text-embedding-ada-002` is assumed to be deployed
+    /// under its own name.
+    #[cfg(feature = "azure")]
+    pub fn azure_deployments(&self) -> std::collections::HashMap<String, String> {
+        let deployments = match &self.azure_deployments {
+            Some(deployments) => deployments.clone(),
+            None => std::env::var("AZURE_DEPLOYMENTS").unwrap_or_default(),
+        };
+
+        let deployments: std::collections::HashMap<String, String> = deployments
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let Some((model, deployment)) = pair.split_once('=') else {
+                    panic!("Invalid Azure deployment '{pair}'; expected `model=deployment`");
+                };
+                (model.trim().to_string(), deployment.trim().to_string())
+            })
+            .collect();
+
+        if deployments.is_empty() {
+            return std::collections::HashMap::from([(
+                "text-embedding-ada-002".to_string(),
+                "text-embedding-ada-002".to_string(),
+            )]);
+        }
+
+        deployments
+    }
+
     #[cfg(feature = "vllm")]
     pub fn vllm_key(&self) -> Option<String> {
         std::env::var("VLLM_KEY").ok()
     }
+
+    #[cfg(feature = "vllm")]
+    pub fn vllm_pooling(
+        &self,
+    ) -> std::collections::HashMap<String, crate::app::embedder::vllm::ModelPooling> {
+        let pooling = match &self.vllm_pooling {
+            Some(pooling) => pooling.clone(),
+            None => std::env::var("VLLM_POOLING").unwrap_or_default(),
+        };
+
+        pooling
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                fn invalid(entry: &str) -> ! {
+                    panic!(
+                        "Invalid VLLM pooling '{entry}'; expected `model=pooling:size[:normalize]`"
+                    )
+                }
+
+                let Some((model, params)) = entry.split_once('=') else {
+                    invalid(entry)
+                };
+
+                let mut params = params.split(':').map(str::trim);
+
+                let pooling = match params.next().map(str::parse) {
+                    Some(Ok(pooling)) => pooling,
+                    Some(Err(e)) => panic!("{e}"),
+                    None => invalid(entry),
+                };
+
+                let Some(Ok(size)) = params.next().map(str::parse::<usize>) else {
+                    invalid(entry)
+                };
+
+                let normalize = match params.next() {
+                    Some("normalize") => true,
+                    None => false,
+                    Some(_) => invalid(entry),
+                };
+
+                if params.next().is_some() {
+                    invalid(entry)
+                }
+
+                let pooling = crate::app::embedder::vllm::ModelPooling {
+                    pooling,
+                    normalize,
+                    size,
+                };
+
+                (model.trim().to_string(), pooling)
+            })
+            .collect()
+    }
+
+    pub fn llm_endpoint(&self) -> Option<String> {
+        self.llm_endpoint
+            .clone()
+            .or_else(|| std::env::var("LLM_ENDPOINT").ok())
+    }
+
+    pub fn llm_key(&self) -> Option<String> {
+        std::env::var("LLM_KEY").ok()
+    }
+
+    pub fn rerank_endpoint(&self) -> Option<String> {
+        self.rerank_endpoint
+            .clone()
+            .or_else(|| std::env::var("RERANK_ENDPOINT").ok())
+    }
+
+    pub fn rerank_key(&self) -> Option<String> {
+        std::env::var("RERANK_KEY").ok()
+    }
 }
 
 arg!(log,             "RUST_LOG",        default "info".to_string());
@@ -238,12 +1202,14 @@ arg!(address,         "ADDRESS",         default DEFAULT_ADDRESS.to_string());
 arg!(cookie_domain,   "COOKIE_DOMAIN",   panic   "Cookie domain not found; Pass --cookie-domain or set COOKIE_DOMAIN");
 arg!(db_url,          "DATABASE_URL",    panic   "Database url not found; Pass --db-url or set DATABASE_URL");
 arg!(upload_path,     "UPLOAD_PATH",     default DEFAULT_UPLOAD_PATH.to_string());
+arg!(llm_model,       "LLM_MODEL",       default DEFAULT_LLM_MODEL.to_string());
 
 // redis
 
-arg!(redis_url,          "REDIS_URL",          panic   "Redis url not found; Pass --redis-url or set REDIS_URL");
-arg!(redis_embedding_db, "REDIS_EMBEDDING_DB", default REDIS_EMBEDDING_DB.to_string());
-arg!(redis_image_db,     "REDIS_IMAGE_DB",     default REDIS_IMAGE_DB.to_string());
+arg!(redis_url,            "REDIS_URL",            panic   "Redis url not found; Pass --redis-url or set REDIS_URL");
+arg!(redis_embedding_db,   "REDIS_EMBEDDING_DB",   default REDIS_EMBEDDING_DB.to_string());
+arg!(redis_image_db,       "REDIS_IMAGE_DB",       default REDIS_IMAGE_DB.to_string());
+arg!(redis_idempotency_db, "REDIS_IDEMPOTENCY_DB", default REDIS_IDEMPOTENCY_DB.to_string());
 
 // minio
 arg!(minio_url,        "MINIO_URL",        panic "Minio url not found; Pass --minio-url or set MINIO_URL");
@@ -13,6 +13,12 @@ pub mod embeddings;
 /// Embedding cache.
 pub mod cache;
 
+/// LLM interfaces used for rewriting search queries.
+pub mod llm;
+
+/// Reranking interfaces used for reordering search results.
+pub mod rerank;
+
 /// Database models.
 pub mod model;
 
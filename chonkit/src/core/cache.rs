@@ -1,6 +1,9 @@
 pub mod embedding;
 
-pub use {redis::init, redis::ImageEmbeddingCache, redis::TextEmbeddingCache};
+pub use {
+    embedding::CachePrecision, redis::connect, redis::init, redis::IdempotencyCache,
+    redis::ImageEmbeddingCache, redis::TextEmbeddingCache,
+};
 
 #[derive(Clone)]
 pub struct AppCache {
@@ -11,20 +14,57 @@ pub struct AppCache {
 mod redis {
     use crate::{
         core::cache::embedding::{
-            CachedImageEmbeddings, CachedTextEmbeddings, ImageEmbeddingCacheKey,
+            CachePrecision, CachedImageEmbeddings, CachedTextEmbeddings, ImageEmbeddingCacheKey,
             TextEmbeddingCacheKey,
         },
         error::ChonkitError,
         map_err,
     };
     use deadpool_redis::redis;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
 
     #[derive(Clone)]
-    pub struct TextEmbeddingCache(deadpool_redis::Pool);
+    pub struct TextEmbeddingCache {
+        pool: deadpool_redis::Pool,
+
+        /// The precision vectors are stored with.
+        precision: CachePrecision,
+
+        /// Amount of reads attempted, shared between clones.
+        reads: Arc<AtomicU64>,
+
+        /// Amount of writes attempted, shared between clones.
+        writes: Arc<AtomicU64>,
+    }
 
     impl TextEmbeddingCache {
         pub fn new(pool: deadpool_redis::Pool) -> Self {
-            Self(pool)
+            Self {
+                pool,
+                precision: CachePrecision::default(),
+                reads: Arc::default(),
+                writes: Arc::default(),
+            }
+        }
+
+        /// Amount of reads attempted since the cache was created, hits and misses alike.
+        pub fn reads(&self) -> u64 {
+            self.reads.load(Ordering::Relaxed)
+        }
+
+        /// Amount of writes attempted since the cache was created.
+        pub fn writes(&self) -> u64 {
+            self.writes.load(Ordering::Relaxed)
+        }
+
+        /// Set the precision vectors are stored with. Existing entries are still
+        /// readable after changing it.
+        pub fn with_precision(mut self, precision: CachePrecision) -> Self {
+            self.precision = precision;
+            self
         }
     }
 
@@ -37,12 +77,65 @@ mod redis {
         }
     }
 
+    /// Maps idempotency keys to the IDs of the resources created with them.
+    #[derive(Clone)]
+    pub struct IdempotencyCache {
+        pool: deadpool_redis::Pool,
+
+        /// How long keys are kept, in seconds.
+        ttl: u64,
+    }
+
+    impl IdempotencyCache {
+        pub fn new(pool: deadpool_redis::Pool, ttl: u64) -> Self {
+            Self { pool, ttl }
+        }
+    }
+
     pub async fn init(url: &str, db: &str) -> deadpool_redis::Pool {
         deadpool_redis::Config::from_url(format!("{url}/{db}"))
             .create_pool(Some(deadpool_redis::Runtime::Tokio1))
             .unwrap_or_else(|_| panic!("unable to create redis connection pool for db {db}"))
     }
 
+    /// Create a connection pool with [init] and verify redis is reachable by pinging it.
+    ///
+    /// The ping is attempted at most `attempts` times, waiting `backoff` multiplied
+    /// by the current attempt between them.
+    pub async fn connect(
+        url: &str,
+        db: &str,
+        attempts: u32,
+        backoff: Duration,
+    ) -> Result<deadpool_redis::Pool, ChonkitError> {
+        let pool = init(url, db).await;
+
+        let mut attempt = 1;
+        loop {
+            match ping(&pool).await {
+                Ok(()) => break,
+                Err(e) if attempt < attempts => {
+                    tracing::warn!(
+                        "Unable to connect to redis db {db} (attempt {attempt}/{attempts}): {e}"
+                    );
+                    tokio::time::sleep(backoff * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        tracing::info!("Connected to redis db {db}");
+
+        Ok(pool)
+    }
+
+    async fn ping(pool: &deadpool_redis::Pool) -> Result<(), ChonkitError> {
+        let mut conn = map_err!(pool.get().await);
+        map_err!(redis::cmd("PING").query_async::<String>(&mut conn).await);
+        Ok(())
+    }
+
     impl TextEmbeddingCache {
         pub async fn get(
             &self,
@@ -50,7 +143,9 @@ mod redis {
         ) -> Result<Option<CachedTextEmbeddings>, ChonkitError> {
             let __start = std::time::Instant::now();
 
-            let mut conn = map_err!(self.0.get().await);
+            self.reads.fetch_add(1, Ordering::Relaxed);
+
+            let mut conn = map_err!(self.pool.get().await);
             let data: Option<String> = map_err!(
                 redis::cmd("GET")
                     .arg(key.key())
@@ -62,7 +157,7 @@ mod redis {
                 return Ok(None);
             };
 
-            let data = map_err!(serde_json::from_str::<CachedTextEmbeddings>(&data));
+            let data = CachedTextEmbeddings::decode(&data)?;
 
             tracing::debug!(
                 "embedding retrieval took {}ms ({} vectors)",
@@ -78,8 +173,10 @@ mod redis {
             key: &TextEmbeddingCacheKey,
             value: CachedTextEmbeddings,
         ) -> Result<(), crate::error::ChonkitError> {
-            let data = map_err!(serde_json::to_string(&value));
-            let mut conn = map_err!(self.0.get().await);
+            self.writes.fetch_add(1, Ordering::Relaxed);
+
+            let data = value.encode(self.precision)?;
+            let mut conn = map_err!(self.pool.get().await);
             map_err!(
                 redis::cmd("SET")
                     .arg(key.key())
@@ -92,7 +189,7 @@ mod redis {
         }
 
         pub async fn exists(&self, key: &TextEmbeddingCacheKey) -> Result<bool, ChonkitError> {
-            let mut conn = map_err!(self.0.get().await);
+            let mut conn = map_err!(self.pool.get().await);
 
             Ok(map_err!(
                 redis::cmd("EXISTS")
@@ -103,7 +200,7 @@ mod redis {
         }
 
         pub async fn clear(&self) -> Result<(), ChonkitError> {
-            let mut conn = map_err!(self.0.get().await);
+            let mut conn = map_err!(self.pool.get().await);
             map_err!(redis::cmd("FLUSHDB").query_async::<()>(&mut conn).await);
             Ok(())
         }
@@ -174,4 +271,124 @@ mod redis {
             Ok(())
         }
     }
+
+    impl IdempotencyCache {
+        /// Atomically claim the key for a new request.
+        ///
+        /// Returns `None` if the key was claimed. Otherwise returns what is stored for it, i.e.
+        /// the ID of the resource created with the key, or `None` if the request that claimed
+        /// it is still in progress.
+        pub async fn claim(&self, key: &str) -> Result<Option<Option<Uuid>>, ChonkitError> {
+            let mut conn = map_err!(self.pool.get().await);
+
+            let claimed: Option<String> = map_err!(
+                redis::cmd("SET")
+                    .arg(key)
+                    .arg("")
+                    .arg("NX")
+                    .arg("EX")
+                    .arg(self.ttl)
+                    .query_async(&mut conn)
+                    .await
+            );
+
+            if claimed.is_some() {
+                return Ok(None);
+            }
+
+            let existing: Option<String> =
+                map_err!(redis::cmd("GET").arg(key).query_async(&mut conn).await);
+
+            Ok(Some(existing.and_then(|id| Uuid::parse_str(&id).ok())))
+        }
+
+        /// Store the ID of the resource created with a claimed key.
+        pub async fn complete(&self, key: &str, id: Uuid) -> Result<(), ChonkitError> {
+            let mut conn = map_err!(self.pool.get().await);
+            map_err!(
+                redis::cmd("SET")
+                    .arg(key)
+                    .arg(id.to_string())
+                    .arg("EX")
+                    .arg(self.ttl)
+                    .query_async::<()>(&mut conn)
+                    .await
+            );
+            Ok(())
+        }
+
+        /// Release a claimed key so the request can be retried.
+        pub async fn release(&self, key: &str) -> Result<(), ChonkitError> {
+            let mut conn = map_err!(self.pool.get().await);
+            map_err!(
+                redis::cmd("DEL")
+                    .arg(key)
+                    .query_async::<()>(&mut conn)
+                    .await
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        embedding::{CachedTextEmbeddings, TextEmbeddingCacheKey},
+        init, CachePrecision, TextEmbeddingCache,
+    };
+    use crate::core::document::parser::ParseConfig;
+    use deadpool_redis::redis;
+    use testcontainers::runners::AsyncRunner;
+    use testcontainers_modules::redis::Redis;
+
+    #[tokio::test]
+    async fn f16_cache_stores_smaller_entries() {
+        let redis_image = Redis.start().await.unwrap();
+        let redis_host = redis_image.get_host().await.unwrap();
+        let redis_port = redis_image.get_host_port_ipv4(6379).await.unwrap();
+        let pool = init(&format!("redis://{redis_host}:{redis_port}"), "0").await;
+
+        let full = TextEmbeddingCache::new(pool.clone());
+        let half = TextEmbeddingCache::new(pool.clone()).with_precision(CachePrecision::F16);
+
+        let vector = (0..768)
+            .map(|i| ((i as f64) * 0.13).cos() * 0.05)
+            .collect::<Vec<_>>();
+        let embeddings = || CachedTextEmbeddings::new(vec![vector.clone()], None, vec![]);
+
+        let parse_config = ParseConfig::default();
+        let full_key =
+            TextEmbeddingCacheKey::new("f64", "ragu", None, &parse_config, None).unwrap();
+        let half_key =
+            TextEmbeddingCacheKey::new("f16", "ragu", None, &parse_config, None).unwrap();
+
+        full.set(&full_key, embeddings()).await.unwrap();
+        half.set(&half_key, embeddings()).await.unwrap();
+
+        let mut conn = pool.get().await.unwrap();
+        let full_len: usize = redis::cmd("STRLEN")
+            .arg(full_key.key())
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+        let half_len: usize = redis::cmd("STRLEN")
+            .arg(half_key.key())
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+
+        assert!(half_len * 4 < full_len);
+
+        // Entries are readable regardless of the precision they were stored with
+        for cache in [&full, &half] {
+            let cached = cache.get(&half_key).await.unwrap().unwrap();
+            for (original, cached) in vector.iter().zip(cached.embeddings[0].iter()) {
+                assert!((original - cached).abs() < 1e-3);
+            }
+
+            let cached = cache.get(&full_key).await.unwrap().unwrap();
+            assert_eq!(vector, cached.embeddings[0]);
+        }
+    }
 }
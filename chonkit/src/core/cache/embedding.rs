@@ -7,8 +7,47 @@ use crate::{
     error::ChonkitError,
     map_err,
 };
+use base64::Engine;
+use half::f16;
 use serde::{Deserialize, Serialize};
 
+/// The precision of the vectors stored in the text embedding cache.
+///
+/// Cached entries of either precision can always be read, regardless of the configured one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CachePrecision {
+    /// Vectors are stored as they are obtained from the embedder.
+    #[default]
+    F64,
+
+    /// Vectors are quantized to half precision floats, making cache entries
+    /// roughly a quarter of the size at the cost of a slight loss of precision.
+    F16,
+}
+
+impl std::fmt::Display for CachePrecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CachePrecision::F64 => write!(f, "f64"),
+            CachePrecision::F16 => write!(f, "f16"),
+        }
+    }
+}
+
+impl std::str::FromStr for CachePrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "f64" => Ok(Self::F64),
+            "f16" => Ok(Self::F16),
+            _ => Err(format!(
+                "unsupported cache precision '{s}', expected f64 or f16"
+            )),
+        }
+    }
+}
+
 /// Cached text embeddings with their chunks.
 ///
 /// We keep the document chunks in the cache in order to skip processing it.
@@ -28,11 +67,86 @@ impl CachedTextEmbeddings {
             chunks,
         }
     }
+
+    /// Serialize the embeddings to the format stored in the cache.
+    ///
+    /// With [CachePrecision::F16], every vector is stored as the base64 encoded
+    /// little endian bytes of its half precision components.
+    pub(super) fn encode(self, precision: CachePrecision) -> Result<String, ChonkitError> {
+        let data = match precision {
+            CachePrecision::F64 => map_err!(serde_json::to_string(&self)),
+            CachePrecision::F16 => {
+                let embeddings_f16 = self
+                    .embeddings
+                    .iter()
+                    .map(|vector| {
+                        let bytes = vector
+                            .iter()
+                            .flat_map(|value| f16::from_f64(*value).to_le_bytes())
+                            .collect::<Vec<_>>();
+                        base64::engine::general_purpose::STANDARD.encode(bytes)
+                    })
+                    .collect();
+
+                map_err!(serde_json::to_string(&HalfTextEmbeddings {
+                    embeddings_f16,
+                    tokens_used: self.tokens_used,
+                    chunks: self.chunks,
+                }))
+            }
+        };
+
+        Ok(data)
+    }
+
+    /// Deserialize embeddings stored in the cache with any [CachePrecision].
+    pub(super) fn decode(data: &str) -> Result<Self, ChonkitError> {
+        let embeddings = match map_err!(serde_json::from_str::<StoredTextEmbeddings>(data)) {
+            StoredTextEmbeddings::F64(embeddings) => embeddings,
+            StoredTextEmbeddings::F16(HalfTextEmbeddings {
+                embeddings_f16,
+                tokens_used,
+                chunks,
+            }) => {
+                let mut embeddings = Vec::with_capacity(embeddings_f16.len());
+
+                for vector in embeddings_f16 {
+                    let bytes = map_err!(base64::engine::general_purpose::STANDARD.decode(vector));
+                    let vector = bytes
+                        .chunks_exact(2)
+                        .map(|value| f16::from_le_bytes([value[0], value[1]]).to_f64())
+                        .collect();
+                    embeddings.push(vector);
+                }
+
+                Self::new(embeddings, tokens_used, chunks)
+            }
+        };
+
+        Ok(embeddings)
+    }
+}
+
+/// The formats of [CachedTextEmbeddings] in the cache, distinguished by their fields.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTextEmbeddings {
+    F64(CachedTextEmbeddings),
+    F16(HalfTextEmbeddings),
+}
+
+/// [CachedTextEmbeddings] stored with [CachePrecision::F16].
+#[derive(Serialize, Deserialize)]
+struct HalfTextEmbeddings {
+    embeddings_f16: Vec<String>,
+    tokens_used: Option<usize>,
+    chunks: Vec<String>,
 }
 
 /// A wrapper around the resulting cache key obtained via [TextEmbeddingCacheKey::new].
 ///
-/// Always obtained from a combination of the document's hash, its chunking config and the parse mode.
+/// Always obtained from a combination of the document's hash, its chunking config, the parse mode
+/// and the chunk template of the collection, if any.
 #[derive(Debug)]
 pub struct TextEmbeddingCacheKey(String);
 
@@ -42,10 +156,17 @@ impl TextEmbeddingCacheKey {
         document_hash: &str,
         chunk_config: Option<&ChunkConfig>,
         parse_config: &ParseConfig,
+        chunk_template: Option<&str>,
     ) -> Result<Self, ChonkitError> {
         Ok(TextEmbeddingCacheKey(
-            TextEmbeddingCacheKeyInner::new(model_name, document_hash, chunk_config, parse_config)
-                .into_cache_key()?,
+            TextEmbeddingCacheKeyInner::new(
+                model_name,
+                document_hash,
+                chunk_config,
+                parse_config,
+                chunk_template,
+            )
+            .into_cache_key()?,
         ))
     }
 
@@ -60,6 +181,9 @@ struct TextEmbeddingCacheKeyInner<'a> {
     document_hash: &'a str,
     chunk_config: Option<&'a ChunkConfig>,
     parse_config: &'a ParseConfig,
+    /// Omitted when absent so keys of untemplated chunks stay the same.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunk_template: Option<&'a str>,
 }
 
 impl<'a> TextEmbeddingCacheKeyInner<'a> {
@@ -68,12 +192,14 @@ impl<'a> TextEmbeddingCacheKeyInner<'a> {
         document_hash: &'a str,
         chunk_config: Option<&'a ChunkConfig>,
         parse_config: &'a ParseConfig,
+        chunk_template: Option<&'a str>,
     ) -> Self {
         TextEmbeddingCacheKeyInner {
             model_name,
             document_hash,
             chunk_config,
             parse_config,
+            chunk_template,
         }
     }
 
@@ -123,3 +249,60 @@ impl<'a> ImageEmbeddingCacheKey<'a> {
         format!("{}-{}", self.hash, self.model)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CachePrecision, CachedTextEmbeddings};
+
+    fn embeddings() -> CachedTextEmbeddings {
+        let vector = (0..384)
+            .map(|i| ((i as f64) * 0.37).sin() * 0.1)
+            .collect::<Vec<_>>();
+
+        CachedTextEmbeddings::new(
+            vec![vector.clone(), vector.iter().map(|v| -v).collect()],
+            Some(42),
+            vec!["Raguru cooks.".to_string(), "Gura eats.".to_string()],
+        )
+    }
+
+    #[test]
+    fn f16_round_trip_is_within_tolerance() {
+        let original = embeddings();
+
+        let full = embeddings().encode(CachePrecision::F64).unwrap();
+        let half = embeddings().encode(CachePrecision::F16).unwrap();
+
+        assert!(half.len() * 4 < full.len());
+
+        let decoded = CachedTextEmbeddings::decode(&half).unwrap();
+
+        assert_eq!(original.tokens_used, decoded.tokens_used);
+        assert_eq!(original.chunks, decoded.chunks);
+        assert_eq!(original.embeddings.len(), decoded.embeddings.len());
+
+        for (original, decoded) in original.embeddings.iter().zip(decoded.embeddings.iter()) {
+            assert_eq!(original.len(), decoded.len());
+            for (original, decoded) in original.iter().zip(decoded.iter()) {
+                assert!((original - decoded).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn f64_round_trip_is_exact() {
+        let original = embeddings();
+
+        let full = embeddings().encode(CachePrecision::F64).unwrap();
+        let decoded = CachedTextEmbeddings::decode(&full).unwrap();
+
+        assert_eq!(original.embeddings, decoded.embeddings);
+    }
+
+    #[test]
+    fn parses_precision() {
+        assert_eq!(CachePrecision::F16, " F16 ".parse().unwrap());
+        assert_eq!(CachePrecision::F64, "f64".parse().unwrap());
+        assert!("bf16".parse::<CachePrecision>().is_err());
+    }
+}
@@ -3,14 +3,34 @@ use crate::{err, error::ChonkitError, map_err};
 use chunx::ChunkerError;
 use serde::{Deserialize, Serialize};
 
+/// Chunk the input using the provided configuration.
+///
+/// If semantic chunking fails, e.g. because the embedding provider is unavailable, and the
+/// configuration has a `fallback`, the input is chunked with the fallback instead.
+///
+/// Configurations with `autoTune` are tuned to the length of the input before chunking,
+/// the tuned configuration is returned alongside the chunks.
+///
+/// * `providers`: Used to obtain the embedder for semantic chunking.
+/// * `config`: The chunking configuration.
+/// * `input`: The text to chunk.
+/// * `max_chunks`: The maximum amount of chunks the input is allowed to be chunked into.
 pub async fn chunk<'i>(
     providers: &ProviderState,
     config: ChunkConfig,
     input: &'i str,
-) -> Result<ChunkedDocument<'i>, ChonkitError> {
+    max_chunks: usize,
+) -> Result<Chunked<'i>, ChonkitError> {
+    // Tuning changes the amount of chunks, so it has to happen before the limits are checked
+    let tuned = auto_tune(&config, input)?;
+    let config = tuned.clone().unwrap_or(config);
+
+    check_limits(&config, input, max_chunks)?;
+
     let chunks = match config {
         ChunkConfig::Sliding(config) => {
-            let chunker = map_err!(chunx::Sliding::new(config.size, config.overlap));
+            let chunker = map_err!(chunx::Sliding::new(config.size, config.overlap))
+                .with_snap_to(config.snap_to.unwrap_or_default());
             let chunked = map_err!(chunker.chunk(input));
 
             ChunkedDocument::Ref(chunked)
@@ -22,47 +42,47 @@ pub async fn chunk<'i>(
                 delimiter,
                 skip_f,
                 skip_b,
+                boundary_requires_whitespace,
+                segmenter,
+                language,
+                ..
             } = config;
 
             let chunker = map_err!(chunx::Snapping::new(
                 size, overlap, delimiter, skip_f, skip_b
-            ));
+            ))
+            .with_boundary_requires_whitespace(boundary_requires_whitespace.unwrap_or(true))
+            .with_segmenter(segmenter.unwrap_or_default());
+
+            let chunker = match language {
+                Some(language) => chunker.with_language(language),
+                None => chunker,
+            };
 
             let chunked = map_err!(chunker.chunk(input));
 
             ChunkedDocument::Owned(chunked)
         }
-        ChunkConfig::Semantic(config) => {
-            let SemanticWindowConfig {
-                size,
-                threshold,
-                distance_fn,
-                delimiter,
-                embedding_provider,
-                embedding_model,
-                skip_f,
-                skip_b,
-            } = config;
-
-            let chunker =
-                chunx::Semantic::new(size, threshold, distance_fn, delimiter, skip_f, skip_b);
+        ChunkConfig::Semantic(mut config) => {
+            let fallback = config.fallback.take();
 
-            let embedder = providers.embedding.get_provider(&embedding_provider)?;
+            match chunk_semantic(providers, config, input).await {
+                Ok(chunked) => ChunkedDocument::Owned(chunked),
+                Err(e) => {
+                    let Some(fallback) = fallback else {
+                        return Err(e);
+                    };
 
-            if embedder.model_details(&embedding_model).await?.is_none() {
-                return err!(
-                    InvalidEmbeddingModel,
-                    "Model '{embedding_model}' not supported by '{embedding_provider}'"
-                );
-            };
-
-            let semantic_embedder = SemanticEmbedder(embedder.clone());
+                    tracing::warn!("Semantic chunking failed, using {fallback} fallback: {e}");
 
-            let chunked = chunker
-                .chunk(input, &semantic_embedder, &embedding_model)
-                .await?;
+                    let chunked = Box::pin(chunk(providers, *fallback, input, max_chunks)).await?;
 
-            ChunkedDocument::Owned(chunked)
+                    return Ok(Chunked {
+                        fallback: true,
+                        ..chunked
+                    });
+                }
+            }
         }
         ChunkConfig::Splitline(config) => {
             let SplitlineConfig {
@@ -83,6 +103,24 @@ pub async fn chunk<'i>(
                 prepend_latest_header.unwrap_or(false),
             );
 
+            ChunkedDocument::Owned(chunker.chunk(input))
+        }
+        ChunkConfig::FixedCount(FixedCountConfig { count }) => {
+            let chunker = map_err!(chunx::FixedCount::new(count));
+            let chunked = map_err!(chunker.chunk(input));
+
+            ChunkedDocument::Ref(chunked)
+        }
+        ChunkConfig::ParentChild(ParentChildConfig { child_size, .. }) => {
+            // Children are contiguous so that parent windows can be assembled from them
+            let chunker = map_err!(chunx::Sliding::new(child_size, 0));
+            let chunked = map_err!(chunker.chunk(input));
+
+            ChunkedDocument::Ref(chunked)
+        }
+        ChunkConfig::ByHeading(ByHeadingConfig { max_level, size }) => {
+            let chunker = map_err!(chunx::ByHeading::new(max_level, size));
+
             ChunkedDocument::Owned(chunker.chunk(input))
         }
     };
@@ -91,7 +129,264 @@ pub async fn chunk<'i>(
         return err!(Chunks, "chunks cannot be empty");
     }
 
-    Ok(chunks)
+    if chunks.len() > max_chunks {
+        return err!(
+            InvalidParameter,
+            "Chunking produced {} chunks, the maximum is {max_chunks}",
+            chunks.len()
+        );
+    }
+
+    Ok(Chunked {
+        chunks,
+        fallback: false,
+        tuned,
+    })
+}
+
+/// Obtain the configuration with the chunk size tuned to the length of the input, if the
+/// configuration is auto-tuned. The tuned configuration is not auto-tuned.
+fn auto_tune(config: &ChunkConfig, input: &str) -> Result<Option<ChunkConfig>, ChonkitError> {
+    let auto_tune = match config {
+        ChunkConfig::Sliding(SlidingWindowConfig { auto_tune, .. })
+        | ChunkConfig::Snapping(SnappingWindowConfig { auto_tune, .. }) => auto_tune,
+        _ => return Ok(None),
+    };
+
+    let Some(auto_tune) = auto_tune else {
+        return Ok(None);
+    };
+
+    let AutoTuneConfig {
+        target_chunks,
+        min_size,
+        max_size,
+    } = *auto_tune;
+
+    if target_chunks == 0 {
+        return err!(
+            InvalidParameter,
+            "Auto-tune target chunks must be greater than 0"
+        );
+    }
+
+    if min_size == 0 || min_size > max_size {
+        return err!(
+            InvalidParameter,
+            "Auto-tune bounds must satisfy 0 < min size ({min_size}) <= max size ({max_size})"
+        );
+    }
+
+    let size = auto_tune.size(input.trim().len());
+
+    // The overlap keeps its share of the chunk
+    let tune = |original: usize, overlap: usize| overlap * size / original.max(1);
+
+    let tuned = match config.clone() {
+        ChunkConfig::Sliding(config) => ChunkConfig::Sliding(SlidingWindowConfig {
+            size,
+            overlap: tune(config.size, config.overlap),
+            auto_tune: None,
+            ..config
+        }),
+        ChunkConfig::Snapping(config) => ChunkConfig::Snapping(SnappingWindowConfig {
+            size,
+            overlap: tune(config.size, config.overlap),
+            auto_tune: None,
+            ..config
+        }),
+        _ => unreachable!("only sliding and snapping configurations are auto-tuned"),
+    };
+
+    tracing::debug!("Auto-tuned {config} chunk size to {size}");
+
+    Ok(Some(tuned))
+}
+
+async fn chunk_semantic(
+    providers: &ProviderState,
+    config: SemanticWindowConfig,
+    input: &str,
+) -> Result<Vec<String>, ChonkitError> {
+    let SemanticWindowConfig {
+        size,
+        threshold,
+        distance_fn,
+        delimiter,
+        embedding_provider,
+        embedding_model,
+        skip_f,
+        skip_b,
+        ..
+    } = config;
+
+    let chunker = chunx::Semantic::new(size, threshold, distance_fn, delimiter, skip_f, skip_b);
+
+    let embedder = providers.embedding.get_provider(&embedding_provider)?;
+
+    if embedder.model_details(&embedding_model).await?.is_none() {
+        return err!(
+            InvalidEmbeddingModel,
+            "Model '{embedding_model}' not supported by '{embedding_provider}'"
+        );
+    };
+
+    let semantic_embedder = SemanticEmbedder(embedder.clone());
+
+    chunker
+        .chunk(input, &semantic_embedder, &embedding_model)
+        .await
+}
+
+/// Reject degenerate configurations and, where the amount of chunks can be estimated
+/// up front, configurations that would exceed `max_chunks` for the input.
+fn check_limits(config: &ChunkConfig, input: &str, max_chunks: usize) -> Result<(), ChonkitError> {
+    let size = match config {
+        ChunkConfig::Sliding(SlidingWindowConfig { size, .. })
+        | ChunkConfig::Snapping(SnappingWindowConfig { size, .. })
+        | ChunkConfig::Semantic(SemanticWindowConfig { size, .. }) => Some(*size),
+        ChunkConfig::Splitline(SplitlineConfig { size, .. }) => *size,
+        ChunkConfig::ByHeading(ByHeadingConfig { size, .. }) => Some(*size),
+        ChunkConfig::FixedCount(_) => None,
+        ChunkConfig::ParentChild(ParentChildConfig { child_size, .. }) => Some(*child_size),
+    };
+
+    if size == Some(0) {
+        return err!(InvalidParameter, "Chunk size must be greater than 0");
+    }
+
+    if let ChunkConfig::ParentChild(ParentChildConfig {
+        child_size,
+        parent_size,
+    }) = config
+    {
+        if parent_size < child_size {
+            return err!(
+                InvalidParameter,
+                "Parent size {parent_size} must not be smaller than child size {child_size}"
+            );
+        }
+    }
+
+    if let ChunkConfig::FixedCount(FixedCountConfig { count }) = config {
+        if *count == 0 {
+            return err!(InvalidParameter, "Chunk count must be greater than 0");
+        }
+
+        if *count > max_chunks {
+            return err!(
+                InvalidParameter,
+                "Chunk count {count} exceeds the maximum of {max_chunks}"
+            );
+        }
+    }
+
+    // Sliding windows are fixed in size, so the amount is known before chunking
+    let sliding = match config {
+        ChunkConfig::Sliding(SlidingWindowConfig { size, overlap, .. }) => Some((*size, *overlap)),
+        ChunkConfig::ParentChild(ParentChildConfig { child_size, .. }) => Some((*child_size, 0)),
+        _ => None,
+    };
+
+    if let Some((size, overlap)) = sliding {
+        let input_size = input.trim().len();
+        let estimate = if input_size <= size + overlap * 2 {
+            1
+        } else {
+            input_size.saturating_sub(overlap) / size + 1
+        };
+
+        if estimate > max_chunks {
+            return err!(
+                InvalidParameter,
+                "Chunking would produce about {estimate} chunks, the maximum is {max_chunks}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Placeholder replaced with the chunk in chunk templates. Required in every template.
+pub const CHUNK_TEMPLATE_CHUNK: &str = "{chunk}";
+
+/// Ensure the chunk template contains the chunk placeholder, otherwise every chunk
+/// would be embedded as the same text.
+pub fn validate_chunk_template(template: &str) -> Result<(), ChonkitError> {
+    if !template.contains(CHUNK_TEMPLATE_CHUNK) {
+        return err!(
+            InvalidParameter,
+            "Chunk template must contain the '{CHUNK_TEMPLATE_CHUNK}' placeholder"
+        );
+    }
+    Ok(())
+}
+
+/// Wrap the chunk with document context before it is embedded.
+///
+/// Replaces the `{name}`, `{page}` and `{chunk}` placeholders in the template with the
+/// document name, the page range of the chunk and the chunk itself. `{page}` is
+/// replaced with an empty string if the chunk does not map to pages.
+///
+/// Placeholders are replaced in a single pass, so ones appearing in the chunk or the
+/// name are kept as they are.
+pub fn apply_chunk_template(template: &str, name: &str, page: Option<&str>, chunk: &str) -> String {
+    let placeholders = [
+        (CHUNK_TEMPLATE_CHUNK, chunk),
+        ("{name}", name),
+        ("{page}", page.unwrap_or_default()),
+    ];
+
+    let mut output = String::with_capacity(template.len() + chunk.len() + name.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                output.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// The minimum amount of bytes consecutive chunks have to share for the overlap to be removed
+/// when joining them. Chunks that merely happen to end and start with the same few characters
+/// are kept whole.
+const MIN_JOIN_OVERLAP: usize = 8;
+
+/// Join chunks, given in order, back into the text they were obtained from.
+///
+/// Where the start of a chunk repeats the end of the text joined so far, e.g. with the sliding
+/// chunker, the longest such overlap of at least [MIN_JOIN_OVERLAP] bytes is output only once.
+pub fn join_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> String {
+    let mut text = String::new();
+
+    for chunk in chunks {
+        let max = text.len().min(chunk.len());
+
+        let overlap = (MIN_JOIN_OVERLAP..=max)
+            .rev()
+            .find(|len| chunk.is_char_boundary(*len) && text.ends_with(&chunk[..*len]))
+            .unwrap_or(0);
+
+        text.push_str(&chunk[overlap..]);
+    }
+
+    text
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -112,6 +407,71 @@ pub enum ChunkConfig {
     /// Splitline chunking implementation.
     /// See [Splitline](chunx::Splitline) for more details.
     Splitline(SplitlineConfig),
+
+    /// Chunking into a fixed amount of chunks.
+    /// See [FixedCount](chunx::FixedCount) for more details.
+    FixedCount(FixedCountConfig),
+
+    /// Small child chunks embedded on their own, grouped into larger parent windows
+    /// which can be returned in their stead when searching.
+    /// See [ParentChildConfig] for more details.
+    ParentChild(ParentChildConfig),
+
+    /// Chunking Markdown at headings, never crossing headings up to a level.
+    /// See [ByHeading](chunx::ByHeading) for more details.
+    ByHeading(ByHeadingConfig),
+}
+
+/// Children are cut as non-overlapping [Sliding](chunx::Sliding) windows of `child_size`.
+/// Every run of consecutive children spanning at most `parent_size` forms a parent window,
+/// so parents are always assembled from whole children.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ParentChildConfig {
+    /// The size of the embedded child chunks.
+    pub child_size: usize,
+
+    /// The maximum size of the parent windows. Must not be smaller than `child_size`.
+    pub parent_size: usize,
+}
+
+impl ParentChildConfig {
+    /// The amount of consecutive children forming a single parent window.
+    pub fn children_per_parent(&self) -> usize {
+        (self.parent_size / self.child_size.max(1)).max(1)
+    }
+
+    /// The index of the parent window the child at `chunk_index` belongs to.
+    pub fn parent_index(&self, chunk_index: usize) -> usize {
+        chunk_index / self.children_per_parent()
+    }
+
+    /// Assemble the parent windows from the children obtained by chunking with this config.
+    pub fn parents<T: AsRef<str>>(&self, children: &[T]) -> Vec<String> {
+        children
+            .chunks(self.children_per_parent())
+            .map(|group| group.iter().map(AsRef::as_ref).collect())
+            .collect()
+    }
+}
+
+/// See [ByHeading](chunx::ByHeading) for more details.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ByHeadingConfig {
+    /// Headings of this level or lower, 1 to 6, always start a new chunk.
+    pub max_level: usize,
+
+    /// The size budget of chunks when merging deeper subsections.
+    pub size: usize,
+}
+
+/// See [FixedCount](chunx::FixedCount) for more details.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FixedCountConfig {
+    /// The amount of chunks to divide the input into. Short inputs may produce fewer.
+    pub count: usize,
 }
 
 /// See [Splitline](chunx::splitline::Splitline) for more details.
@@ -134,6 +494,15 @@ pub struct SplitlineConfig {
 pub struct SlidingWindowConfig {
     pub size: usize,
     pub overlap: usize,
+
+    /// The boundaries windows are extended to, so that they do not cut words or sentences.
+    /// Defaults to `char` if not provided, cutting windows at exact offsets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snap_to: Option<chunx::sliding::SnapTarget>,
+
+    /// If given, `size` and `overlap` are tuned to the length of the input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tune: Option<AutoTuneConfig>,
 }
 
 /// See [Snapping](chunx::Snapping) for more details.
@@ -145,6 +514,47 @@ pub struct SnappingWindowConfig {
     pub delimiter: char,
     pub skip_f: Vec<String>,
     pub skip_b: Vec<String>,
+
+    /// Whether delimiters must be followed by whitespace to be treated as sentence stops.
+    /// Defaults to `true` if not provided.
+    pub boundary_requires_whitespace: Option<bool>,
+
+    /// How sentence boundaries are found. The `unicode` segmenter requires the `segmenter`
+    /// feature. Defaults to `heuristic` if not provided.
+    pub segmenter: Option<chunx::snapping::SentenceSegmenter>,
+
+    /// If given, the built-in abbreviations of the language are skipped in addition to
+    /// `skip_f` and `skip_b`, e.g. `z.B.` for `german`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<chunx::snapping::SnappingLanguage>,
+
+    /// If given, `size` and `overlap` are tuned to the length of the input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tune: Option<AutoTuneConfig>,
+}
+
+/// Scales the chunk size with the length of the input so that inputs of any length are
+/// chunked into about `target_chunks` chunks. The overlap is scaled along with the size.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTuneConfig {
+    /// The amount of chunks to aim for.
+    pub target_chunks: usize,
+
+    /// The smallest size the chunks are tuned to. Shorter inputs produce fewer chunks.
+    pub min_size: usize,
+
+    /// The largest size the chunks are tuned to. Longer inputs produce more chunks.
+    pub max_size: usize,
+}
+
+impl AutoTuneConfig {
+    /// The chunk size dividing an input of `input_size` into `target_chunks`, within bounds.
+    pub fn size(&self, input_size: usize) -> usize {
+        input_size
+            .div_ceil(self.target_chunks.max(1))
+            .clamp(self.min_size, self.max_size)
+    }
 }
 
 /// See [Semantic](chunx::Semantic) for more details.
@@ -161,6 +571,12 @@ pub struct SemanticWindowConfig {
     pub embedding_model: String,
     #[serde(alias = "embedProvider")]
     pub embedding_provider: String,
+
+    /// Chunking configuration used in place of this one if semantic chunking fails, e.g.
+    /// because the embedding provider is down. Trades chunk quality for not failing the
+    /// whole operation; documents chunked this way can be re-chunked later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Box<ChunkConfig>>,
 }
 
 impl ChunkConfig {
@@ -169,7 +585,12 @@ impl ChunkConfig {
     /// * `size`: Chunk base size.
     /// * `overlap`: Chunk overlap.
     pub fn sliding(size: usize, overlap: usize) -> Result<Self, ChunkerError> {
-        Ok(Self::Sliding(SlidingWindowConfig { size, overlap }))
+        Ok(Self::Sliding(SlidingWindowConfig {
+            size,
+            overlap,
+            snap_to: None,
+            auto_tune: None,
+        }))
     }
 
     /// Create a default `Sliding` chunker.
@@ -178,6 +599,8 @@ impl ChunkConfig {
         Self::Sliding(SlidingWindowConfig {
             size: config.size,
             overlap: config.overlap,
+            snap_to: None,
+            auto_tune: None,
         })
     }
 
@@ -200,6 +623,10 @@ impl ChunkConfig {
             skip_f,
             skip_b,
             delimiter,
+            boundary_requires_whitespace: None,
+            segmenter: None,
+            language: None,
+            auto_tune: None,
         }))
     }
 
@@ -212,6 +639,10 @@ impl ChunkConfig {
             skip_f: config.skip_forward,
             skip_b: config.skip_back,
             delimiter: '.',
+            boundary_requires_whitespace: Some(config.boundary_requires_whitespace),
+            segmenter: Some(config.segmenter),
+            language: None,
+            auto_tune: None,
         })
     }
 
@@ -238,9 +669,36 @@ impl ChunkConfig {
             embedding_model,
             skip_f,
             skip_b,
+            fallback: None,
+        })
+    }
+
+    /// Create a `FixedCount` chunker.
+    ///
+    /// * `count`: The amount of chunks to produce.
+    pub fn fixed_count(count: usize) -> Self {
+        Self::FixedCount(FixedCountConfig { count })
+    }
+
+    /// Create a `ParentChild` chunker.
+    ///
+    /// * `child_size`: The size of the embedded chunks.
+    /// * `parent_size`: The maximum size of the windows they are grouped into.
+    pub fn parent_child(child_size: usize, parent_size: usize) -> Self {
+        Self::ParentChild(ParentChildConfig {
+            child_size,
+            parent_size,
         })
     }
 
+    /// Create a `ByHeading` chunker.
+    ///
+    /// * `max_level`: Headings of this level or lower always start a new chunk.
+    /// * `size`: The size budget of chunks when merging deeper subsections.
+    pub fn by_heading(max_level: usize, size: usize) -> Self {
+        Self::ByHeading(ByHeadingConfig { max_level, size })
+    }
+
     /// Create a default `SemanticWindow` chunker.
     ///
     /// * `embedder`: Embedder to use for embedding chunks, uses the default embedder model.
@@ -255,8 +713,17 @@ impl ChunkConfig {
             skip_b: config.skip_back,
             embedding_provider,
             embedding_model,
+            fallback: None,
         })
     }
+
+    /// Use `fallback` if semantic chunking fails. Has no effect on other chunkers.
+    pub fn with_fallback(mut self, fallback: ChunkConfig) -> Self {
+        if let Self::Semantic(ref mut config) = self {
+            config.fallback = Some(Box::new(fallback));
+        }
+        self
+    }
 }
 
 impl std::fmt::Display for ChunkConfig {
@@ -266,10 +733,24 @@ impl std::fmt::Display for ChunkConfig {
             Self::Snapping(_) => write!(f, "SnappingWindow"),
             Self::Semantic(_) => write!(f, "SemanticWindow"),
             Self::Splitline(_) => write!(f, "Splitline"),
+            Self::FixedCount(_) => write!(f, "FixedCount"),
+            Self::ParentChild(_) => write!(f, "ParentChild"),
+            Self::ByHeading(_) => write!(f, "ByHeading"),
         }
     }
 }
 
+/// The result of [chunk].
+pub struct Chunked<'content> {
+    pub chunks: ChunkedDocument<'content>,
+
+    /// Whether the configured chunker failed and the chunks were obtained with its fallback.
+    pub fallback: bool,
+
+    /// The configuration the chunks were obtained with if it was auto-tuned.
+    pub tuned: Option<ChunkConfig>,
+}
+
 /// The result of chunking a document.
 /// Some chunkers do not allocate.
 pub enum ChunkedDocument<'content> {
@@ -278,6 +759,13 @@ pub enum ChunkedDocument<'content> {
 }
 
 impl ChunkedDocument<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Ref(v) => v.len(),
+            Self::Owned(v) => v.len(),
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         match self {
             Self::Ref(v) => v.is_empty(),
@@ -296,3 +784,175 @@ impl chunx::semantic::Embedder for SemanticEmbedder {
         Ok(embeddings.embeddings)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_chunk_template, auto_tune, check_limits, join_chunks, validate_chunk_template,
+        AutoTuneConfig, ChunkConfig, ParentChildConfig, SlidingWindowConfig, SplitlineConfig,
+    };
+    use crate::error::ChonkitErr;
+
+    #[test]
+    fn joins_chunks_without_overlap() {
+        let input = "Raguru cooks ragu in his floating kitchen. Gura tastes it and asks for more. \
+                     The crew waits patiently for dinner under the light of three moons.";
+
+        let chunks = chunx::Sliding::new(20, 6).unwrap().chunk(input).unwrap();
+        assert!(chunks.len() > 1);
+
+        assert_eq!(input, join_chunks(chunks));
+
+        // Chunks ending and starting with the same few characters are not overlaps
+        assert_eq!("ragu ragu", join_chunks(["ragu", " ragu"]));
+        assert_eq!("Raguru. Raguru.", join_chunks(["Raguru.", " Raguru."]));
+    }
+
+    #[test]
+    fn rejects_configs_exceeding_max_chunks() {
+        let input = "Raguru Labamba. ".repeat(100_000);
+        let config = ChunkConfig::sliding(1, 0).unwrap();
+
+        let err = check_limits(&config, &input, 10_000).unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+    }
+
+    #[test]
+    fn accepts_configs_within_max_chunks() {
+        let input = "Raguru Labamba. ".repeat(100_000);
+        let config = ChunkConfig::sliding(1000, 100).unwrap();
+
+        assert!(check_limits(&config, &input, 10_000).is_ok());
+        assert!(check_limits(&ChunkConfig::snapping_default(), &input, 10_000).is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_size() {
+        let configs = [
+            ChunkConfig::sliding(0, 0).unwrap(),
+            ChunkConfig::snapping(0, 0, vec![], vec![], '.').unwrap(),
+            ChunkConfig::Splitline(SplitlineConfig {
+                size: Some(0),
+                patterns: vec![],
+                prepend_latest_header: None,
+            }),
+            ChunkConfig::by_heading(2, 0),
+        ];
+
+        for config in configs {
+            let err = check_limits(&config, "Raguru Labamba", 10_000).unwrap_err();
+            assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_fixed_count() {
+        for count in [0, 10_001] {
+            let config = ChunkConfig::fixed_count(count);
+            let err = check_limits(&config, "Raguru Labamba", 10_000).unwrap_err();
+            assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+        }
+
+        assert!(check_limits(&ChunkConfig::fixed_count(10), "Raguru Labamba", 10_000).is_ok());
+    }
+
+    #[test]
+    fn rejects_parent_smaller_than_child() {
+        let err = check_limits(&ChunkConfig::parent_child(100, 50), "Raguru", 10_000).unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+
+        assert!(check_limits(&ChunkConfig::parent_child(50, 100), "Raguru", 10_000).is_ok());
+    }
+
+    #[test]
+    fn auto_tuning_evens_out_chunk_counts() {
+        let tuned = |min_size, max_size| {
+            ChunkConfig::Sliding(SlidingWindowConfig {
+                size: 500,
+                overlap: 50,
+                snap_to: None,
+                auto_tune: Some(AutoTuneConfig {
+                    target_chunks: 10,
+                    min_size,
+                    max_size,
+                }),
+            })
+        };
+
+        let config = tuned(50, 50_000);
+
+        let short = "Raguru cooks ragu. ".repeat(20);
+        let long = "Raguru cooks ragu. ".repeat(10_000);
+
+        for input in [short, long] {
+            let Some(ChunkConfig::Sliding(tuned)) = auto_tune(&config, &input).unwrap() else {
+                panic!("sliding config not tuned");
+            };
+
+            assert!(tuned.auto_tune.is_none());
+
+            let chunker = chunx::Sliding::new(tuned.size, tuned.overlap).unwrap();
+            let chunks = chunker.chunk(&input).unwrap();
+
+            assert!(
+                (5..=15).contains(&chunks.len()),
+                "{} chunks of size {}",
+                chunks.len(),
+                tuned.size
+            );
+        }
+
+        let untuned = ChunkConfig::sliding(500, 50).unwrap();
+        assert!(auto_tune(&untuned, "Raguru").unwrap().is_none());
+
+        for config in [tuned(0, 100), tuned(100, 50)] {
+            let err = auto_tune(&config, "Raguru").unwrap_err();
+            assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+        }
+    }
+
+    #[test]
+    fn parents_are_assembled_from_children() {
+        let config = ParentChildConfig {
+            child_size: 10,
+            parent_size: 25,
+        };
+
+        let children = ["Raguru ", "cooks ", "ragu ", "in his ", "kitchen."];
+        let parents = config.parents(&children);
+
+        assert_eq!(2, config.children_per_parent());
+        assert_eq!(vec!["Raguru cooks ", "ragu in his ", "kitchen."], parents);
+        assert_eq!(0, config.parent_index(1));
+        assert_eq!(1, config.parent_index(2));
+        assert_eq!(2, config.parent_index(4));
+    }
+
+    #[test]
+    fn chunk_templates_wrap_chunks() {
+        let template = "Document: {name} ({page})\n\n{chunk}";
+
+        assert_eq!(
+            "Document: ragu.pdf (2-3)\n\nRaguru cooks ragu.",
+            apply_chunk_template(template, "ragu.pdf", Some("2-3"), "Raguru cooks ragu.")
+        );
+        assert_eq!(
+            "Document: ragu.txt ()\n\nRaguru cooks ragu.",
+            apply_chunk_template(template, "ragu.txt", None, "Raguru cooks ragu.")
+        );
+
+        // Placeholders in the chunk and unknown placeholders are kept
+        assert_eq!(
+            "{name} {unknown} ragu.txt",
+            apply_chunk_template("{chunk} {unknown} {name}", "ragu.txt", None, "{name}")
+        );
+    }
+
+    #[test]
+    fn chunk_templates_require_chunk() {
+        let err = validate_chunk_template("Document: {name}").unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+
+        assert!(validate_chunk_template("Document: {name}\n\n{chunk}").is_ok());
+    }
+}
@@ -3,7 +3,10 @@ use std::{collections::HashSet, sync::Arc};
 use crate::{
     core::{
         chunk::ChunkConfig,
-        document::{parser::ParseConfig, store::DocumentStorage},
+        document::{
+            parser::{markdown, ParseConfig},
+            store::DocumentStorage,
+        },
         image::ImageStorage,
         model::{
             document::{Document, DocumentInsert},
@@ -13,11 +16,15 @@ use crate::{
     },
     err,
     error::ChonkitError,
+    map_err,
 };
 use serde::Serialize;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
 use uuid::Uuid;
 
+/// Extracting documents from uploaded archives.
+pub mod archive;
+
 /// Parsing implementations for various file types.
 pub mod parser;
 
@@ -25,12 +32,16 @@ pub mod parser;
 pub mod store;
 
 /// All possible file types chonkit can process.
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum DocumentType {
     /// Encapsulates any files that can be read as strings.
     /// Does not necessarily have to be `.txt`, could be `.json`, `.csv`, etc.
     Text(TextDocumentType),
 
+    /// Markdown document. Unlike other text documents, can have its formatting stripped
+    /// and its front matter is never part of the parsed output.
+    Markdown,
+
     /// Microschlong steaming pile of garbage document.
     Docx,
 
@@ -38,11 +49,16 @@ pub enum DocumentType {
     Pdf,
 
     Excel,
+
+    /// Rich Text Format document.
+    Rtf,
+
+    /// OpenDocument text document.
+    Odt,
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum TextDocumentType {
-    Md,
     Xml,
     Json,
     Csv,
@@ -50,27 +66,94 @@ pub enum TextDocumentType {
 }
 
 impl DocumentType {
+    /// All document types chonkit can parse.
+    pub const SUPPORTED: [DocumentType; 10] = [
+        DocumentType::Markdown,
+        DocumentType::Text(TextDocumentType::Csv),
+        DocumentType::Text(TextDocumentType::Xml),
+        DocumentType::Text(TextDocumentType::Json),
+        DocumentType::Text(TextDocumentType::Txt),
+        DocumentType::Docx,
+        DocumentType::Pdf,
+        DocumentType::Excel,
+        DocumentType::Rtf,
+        DocumentType::Odt,
+    ];
+
     pub fn try_from_file_name(name: &str) -> Result<Self, ChonkitError> {
         let Some((_, ext)) = name.rsplit_once('.') else {
             return err!(UnsupportedFileType, "{name} - missing extension");
         };
         Self::try_from(ext)
     }
+
+    /// Return an error if the document type is not one of the `allowed` ones.
+    ///
+    /// * `allowed`: The document types accepted for upload and import.
+    pub fn ensure_allowed(self, allowed: &[DocumentType]) -> Result<Self, ChonkitError> {
+        if allowed.contains(&self) {
+            return Ok(self);
+        }
+
+        let allowed = allowed
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        err!(
+            UnsupportedFileType,
+            "{self} - not one of the allowed document types ({allowed})"
+        )
+    }
+
+    /// Get the parsing capabilities of the document type.
+    pub fn capabilities(self) -> DocumentTypeCapabilities {
+        DocumentTypeCapabilities {
+            ext: self.to_string(),
+            sectioned: matches!(self, DocumentType::Pdf),
+            ocr: false,
+            tables: matches!(self, DocumentType::Pdf),
+            images: matches!(self, DocumentType::Pdf | DocumentType::Docx),
+        }
+    }
+}
+
+/// Describes which parsing options are available for a document type. Every type can be parsed
+/// to a string.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DocumentTypeCapabilities {
+    /// The extension of the document type.
+    pub ext: String,
+
+    /// Whether the type can be parsed with [ParseConfig::Section].
+    pub sectioned: bool,
+
+    /// Whether text is recognized in scanned pages and images of the type.
+    pub ocr: bool,
+
+    /// Whether tables can be output as Markdown tables.
+    pub tables: bool,
+
+    /// Whether images can be extracted from the type.
+    pub images: bool,
 }
 
 impl std::fmt::Display for DocumentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DocumentType::Text(ty) => match ty {
-                TextDocumentType::Md => write!(f, "md"),
                 TextDocumentType::Xml => write!(f, "xml"),
                 TextDocumentType::Json => write!(f, "json"),
                 TextDocumentType::Csv => write!(f, "csv"),
                 TextDocumentType::Txt => write!(f, "txt"),
             },
+            DocumentType::Markdown => write!(f, "md"),
             DocumentType::Docx => write!(f, "docx"),
             DocumentType::Pdf => write!(f, "pdf"),
             DocumentType::Excel => write!(f, "xlsx"),
+            DocumentType::Rtf => write!(f, "rtf"),
+            DocumentType::Odt => write!(f, "odt"),
         }
     }
 }
@@ -82,7 +165,7 @@ impl TryFrom<&str> for DocumentType {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "xlsx" | "application/vnd.google-apps.spreadsheet" => Ok(Self::Excel),
-            "md" => Ok(Self::Text(TextDocumentType::Md)),
+            "md" | "text/markdown" => Ok(Self::Markdown),
             "xml" => Ok(Self::Text(TextDocumentType::Xml)),
             "json" | "application/json" => Ok(Self::Text(TextDocumentType::Json)),
             "csv" => Ok(Self::Text(TextDocumentType::Csv)),
@@ -93,6 +176,8 @@ impl TryFrom<&str> for DocumentType {
             | "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
                 Ok(Self::Docx)
             }
+            "rtf" | "application/rtf" | "text/rtf" => Ok(Self::Rtf),
+            "odt" | "application/vnd.oasis.opendocument.text" => Ok(Self::Odt),
             _ => err!(UnsupportedFileType, "{value}"),
         }
     }
@@ -116,6 +201,197 @@ pub fn sha256(input: &[u8]) -> String {
     hex::encode(out)
 }
 
+/// Algorithms for hashing document contents. The hash is used to detect duplicate documents
+/// regardless of their source.
+///
+/// Existing documents are not rehashed when the algorithm changes, so their content is not
+/// recognized as a duplicate of content obtained after the change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Return the hex encoded hash of the input.
+    pub fn hash(&self, input: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => sha256(input),
+            HashAlgorithm::Sha512 => {
+                let mut hasher = Sha512::new();
+                Digest::update(&mut hasher, input);
+                hex::encode(hasher.finalize())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashAlgorithm::Sha256 => write!(f, "sha256"),
+            HashAlgorithm::Sha512 => write!(f, "sha512"),
+        }
+    }
+}
+
+impl std::str::FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(format!(
+                "unsupported hash algorithm '{s}', expected sha256 or sha512"
+            )),
+        }
+    }
+}
+
+/// Determines how document contents are hashed and what happens when content that already
+/// exists is obtained again.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DocumentHashing {
+    pub algorithm: HashAlgorithm,
+
+    /// If `true`, new sources of existing content are linked to the existing document.
+    /// Otherwise uploads of existing content are rejected and synced files with existing
+    /// content are stored as separate documents.
+    pub link_duplicates: bool,
+}
+
+/// What happens to documents with more images than the maximum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageLimitPolicy {
+    /// Store images up to the maximum and skip the rest.
+    #[default]
+    Truncate,
+
+    /// Store none of the document's images.
+    Reject,
+}
+
+impl std::fmt::Display for ImageLimitPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageLimitPolicy::Truncate => write!(f, "truncate"),
+            ImageLimitPolicy::Reject => write!(f, "reject"),
+        }
+    }
+}
+
+impl std::str::FromStr for ImageLimitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "truncate" => Ok(Self::Truncate),
+            "reject" => Ok(Self::Reject),
+            _ => Err(format!(
+                "unsupported image limit policy '{s}', expected truncate or reject"
+            )),
+        }
+    }
+}
+
+/// Determines which images extracted from documents are stored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageExtraction {
+    /// The maximum amount of images stored per document. Unlimited if `None`.
+    pub max_per_document: Option<usize>,
+
+    /// What happens to documents with more images than `max_per_document`.
+    pub limit_policy: ImageLimitPolicy,
+
+    /// Images whose width or height is below this amount of pixels are skipped,
+    /// e.g. icons and bullets.
+    pub min_size: u32,
+}
+
+impl ImageExtraction {
+    /// Drop the images below the minimum size and apply the limit to the rest, in order.
+    ///
+    /// * `document_id`: The document the images were extracted from, used for reporting.
+    /// * `images`: The extracted images.
+    /// * `existing`: The amount of images of the document already stored, counting towards the limit.
+    pub fn select(
+        &self,
+        document_id: Uuid,
+        mut images: Vec<Image>,
+        existing: usize,
+    ) -> Result<Vec<Image>, ChonkitError> {
+        let total = images.len();
+        images.retain(|image| {
+            image.image.width >= self.min_size && image.image.height >= self.min_size
+        });
+
+        if images.len() < total {
+            tracing::debug!(
+                "Skipping {} images smaller than {}px of document {document_id}",
+                total - images.len(),
+                self.min_size
+            );
+        }
+
+        let Some(max) = self.max_per_document else {
+            return Ok(images);
+        };
+
+        let remaining = max.saturating_sub(existing);
+
+        if images.len() <= remaining {
+            return Ok(images);
+        }
+
+        match self.limit_policy {
+            ImageLimitPolicy::Truncate => {
+                tracing::warn!(
+                    "Document {document_id} has {} images, storing only the first {remaining} (maximum {max})",
+                    existing + images.len(),
+                );
+                images.truncate(remaining);
+                Ok(images)
+            }
+            ImageLimitPolicy::Reject => err!(
+                InvalidFile,
+                "Document {document_id} has {} images, more than the maximum of {max}",
+                existing + images.len()
+            ),
+        }
+    }
+}
+
+/// Link the source to the document with the same content `hash`, if one exists.
+/// Returns the document the source is linked to.
+///
+/// Linking the document's own source is a no-op.
+pub(in crate::core) async fn link_document_source(
+    repo: &Repository,
+    hash: &str,
+    src: &str,
+    path: &str,
+) -> Result<Option<Document>, ChonkitError> {
+    let Some(existing) = repo.get_document_by_hash(hash).await? else {
+        return Ok(None);
+    };
+
+    if existing.src == src && existing.path == path {
+        return Ok(Some(existing));
+    }
+
+    repo.insert_document_source(existing.id, src, path).await?;
+
+    tracing::info!(
+        "Linked '{path}' ({src}) to existing document '{}' ({})",
+        existing.name,
+        existing.id
+    );
+
+    Ok(Some(existing))
+}
+
 pub async fn get_image(
     repo: Repository,
     provider: &(dyn ImageStorage + Send + Sync),
@@ -157,12 +433,12 @@ pub(in crate::core) async fn store_document(
     name: &str,
     ty: DocumentType,
     file: &[u8],
+    hash: &str,
 ) -> Result<Document, ChonkitError> {
     let path = store.absolute_path(name, ty);
-    let hash = sha256(file);
 
     // Always return errors if there is a hash collision
-    if let Some(existing) = repo.get_document_by_hash(&hash).await? {
+    if let Some(existing) = repo.get_document_by_hash(hash).await? {
         return err!(
             AlreadyExists,
             "New document '{name}' has same hash as existing '{}' ({})",
@@ -171,9 +447,20 @@ pub(in crate::core) async fn store_document(
         );
     };
 
+    // Front matter describes the document rather than being part of its content
+    let metadata = match ty {
+        DocumentType::Markdown => markdown::front_matter(file),
+        _ => None,
+    };
+    let metadata = map_err!(metadata.map(serde_json::to_value).transpose());
+
     repo.transaction(|tx| {
         Box::pin(async {
-            let insert = DocumentInsert::new(name, &path, ty, &hash, store.id());
+            let mut insert = DocumentInsert::new(name, &path, ty, hash, store.id());
+
+            if let Some(ref metadata) = metadata {
+                insert = insert.with_metadata(metadata.clone());
+            }
 
             let document = repo
                 .insert_document_with_configs(
@@ -193,13 +480,39 @@ pub(in crate::core) async fn store_document(
 }
 
 /// Process document images in a background tokio job.
-pub(in crate::core) async fn process_document_images(
+///
+/// Failures are only logged; the document's images are marked as pending and can be
+/// reprocessed with [store_document_images].
+pub(in crate::core) fn process_document_images(
+    repo: Repository,
+    storage: Arc<dyn ImageStorage + Send + Sync>,
+    extraction: ImageExtraction,
+    document_id: Uuid,
+    ty: DocumentType,
+    file: Vec<u8>,
+) {
+    tokio::spawn(async move {
+        if let Err(e) =
+            store_document_images(repo, storage, extraction, document_id, ty, file).await
+        {
+            tracing::error!("Error processing images of document {document_id}: {e}");
+        }
+    });
+}
+
+/// Parse the document's images and store the ones that do not exist yet and are selected
+/// by the `extraction`.
+///
+/// Marks the document's images as pending if any of them could not be stored and
+/// clears the mark once all of them are. Returns the amount of stored images.
+pub(in crate::core) async fn store_document_images(
     repo: Repository,
     storage: Arc<dyn ImageStorage + Send + Sync>,
+    extraction: ImageExtraction,
     document_id: Uuid,
     ty: DocumentType,
     file: Vec<u8>,
-) -> Result<(), ChonkitError> {
+) -> Result<usize, ChonkitError> {
     let existing_images = repo
         .list_all_document_images(document_id, storage.id())
         .await?
@@ -209,32 +522,50 @@ pub(in crate::core) async fn process_document_images(
 
     let existing_amount = existing_images.len();
 
-    tokio::spawn(async move {
-        let images = match tokio::task::spawn_blocking(move || {
-            match parser::parse_images(ty, &file, &existing_images) {
-                Ok(i) => i,
-                Err(e) => {
-                    tracing::error!("error parsing images: {}", e);
-                    vec![]
-                }
-            }
-        })
-        .await
-        {
+    let images = tokio::task::spawn_blocking(move || {
+        match parser::parse_images(ty, &file, &existing_images) {
             Ok(i) => i,
             Err(e) => {
-                tracing::error!("error joining image parsing job: {}", e);
-                return;
+                tracing::error!("error parsing images: {}", e);
+                vec![]
             }
-        };
+        }
+    })
+    .await;
 
-        match store_images(repo, storage, Some(document_id), images).await {
-            Ok(i) => tracing::info!("Parsed {} images ({} skipped)", i.len(), existing_amount),
-            Err(_) => todo!(),
-        };
-    });
+    let images = match images {
+        Ok(images) => images,
+        Err(e) => {
+            repo.update_document_images_pending(document_id, true)
+                .await?;
+            return err!(e.into());
+        }
+    };
+
+    let images = extraction.select(document_id, images, existing_amount)?;
+
+    let total = images.len();
+    let stored = store_images(repo.clone(), storage, Some(document_id), images)
+        .await?
+        .len();
+
+    tracing::info!(
+        "Parsed {stored}/{total} images ({existing_amount} skipped) of document {document_id}"
+    );
 
-    Ok(())
+    if stored < total {
+        repo.update_document_images_pending(document_id, true)
+            .await?;
+        return err!(
+            ImagesPending,
+            "Stored {stored} out of {total} images of document {document_id}"
+        );
+    }
+
+    repo.update_document_images_pending(document_id, false)
+        .await?;
+
+    Ok(stored)
 }
 
 /// Store the provided images in document storage.
@@ -295,3 +626,80 @@ pub(in crate::core) async fn store_images(
 
     Ok(parsed_images)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        sha256, DocumentType, HashAlgorithm, ImageExtraction, ImageLimitPolicy, TextDocumentType,
+    };
+    use crate::core::model::image::Image;
+    use uuid::Uuid;
+
+    #[test]
+    fn hashes_with_configured_algorithm() {
+        let input = b"Raguru Labamba";
+
+        assert_eq!(sha256(input), HashAlgorithm::default().hash(input));
+        assert_eq!(64, HashAlgorithm::Sha256.hash(input).len());
+        assert_eq!(128, HashAlgorithm::Sha512.hash(input).len());
+    }
+
+    #[test]
+    fn parses_hash_algorithm() {
+        assert_eq!(Ok(HashAlgorithm::Sha256), "sha256".parse());
+        assert_eq!(Ok(HashAlgorithm::Sha512), "SHA512".parse());
+        assert!("md5".parse::<HashAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn reports_document_type_capabilities() {
+        let pdf = DocumentType::Pdf.capabilities();
+        assert_eq!("pdf", pdf.ext);
+        assert!(pdf.sectioned && pdf.tables && pdf.images);
+
+        let txt = DocumentType::Text(TextDocumentType::Txt).capabilities();
+        assert_eq!("txt", txt.ext);
+        assert!(!txt.sectioned && !txt.ocr && !txt.tables && !txt.images);
+    }
+
+    #[test]
+    fn selects_images_above_minimum_size_up_to_limit() {
+        // Small icons interleaved with large images
+        let images = || {
+            (0..10)
+                .map(|i| {
+                    let size = if i % 3 == 0 { 64 } else { 8 };
+                    let format = image::ImageFormat::Png;
+                    Image::new(Some(0), Some(i), vec![i as u8], format, size, size)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut extraction = ImageExtraction {
+            max_per_document: Some(3),
+            limit_policy: ImageLimitPolicy::Truncate,
+            min_size: 16,
+        };
+
+        let selected = extraction
+            .select(Uuid::nil(), images(), 0)
+            .unwrap()
+            .into_iter()
+            .map(|image| image.image_number.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 3, 6], selected);
+
+        // Already stored images count towards the limit
+        let selected = extraction.select(Uuid::nil(), images(), 2).unwrap();
+        assert_eq!(1, selected.len());
+
+        extraction.limit_policy = ImageLimitPolicy::Reject;
+        assert!(extraction.select(Uuid::nil(), images(), 0).is_err());
+
+        extraction.max_per_document = Some(4);
+        assert_eq!(
+            4,
+            extraction.select(Uuid::nil(), images(), 0).unwrap().len()
+        );
+    }
+}
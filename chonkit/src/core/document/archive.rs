@@ -0,0 +1,307 @@
+use crate::{err, error::ChonkitError, map_err};
+use std::{
+    io::{Cursor, Read},
+    path::{Component, Path},
+};
+use zip::ZipArchive;
+
+/// Magic bytes of zip local file headers.
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Magic bytes of gzip streams.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// Limits applied when extracting archives, guarding against archives that decompress into
+/// more than they claim to.
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveLimits {
+    /// Maximum total amount of bytes extracted from an archive. Archives exceeding it are
+    /// rejected as a whole.
+    pub max_size: usize,
+
+    /// Maximum amount of bytes extracted from a single entry. Entries exceeding it are skipped.
+    pub max_entry_size: usize,
+}
+
+/// A file found in an archive.
+#[derive(Debug)]
+pub enum ArchiveEntry {
+    /// A regular file that was extracted.
+    File {
+        /// The full path of the entry in the archive.
+        path: String,
+
+        /// The file name of the entry, used as the document name.
+        name: String,
+
+        content: Vec<u8>,
+    },
+
+    /// An entry that was not extracted.
+    Skipped { path: String, reason: String },
+}
+
+/// Extract the files of a zip or gzipped tar archive, detected from its content.
+///
+/// Directories are omitted. Entries whose paths are absolute or escape the archive root,
+/// non regular files and entries larger than the entry limit are reported as skipped.
+/// Errors if the archive cannot be read or its extracted content exceeds the total limit.
+///
+/// * `archive`: The archive bytes.
+/// * `limits`: Extraction limits.
+pub fn extract(archive: &[u8], limits: ArchiveLimits) -> Result<Vec<ArchiveEntry>, ChonkitError> {
+    if archive.starts_with(ZIP_MAGIC) {
+        extract_zip(archive, limits)
+    } else if archive.starts_with(GZIP_MAGIC) {
+        extract_tar_gz(archive, limits)
+    } else {
+        err!(
+            InvalidParameter,
+            "Unsupported archive format; expected zip or tar.gz"
+        )
+    }
+}
+
+fn extract_zip(archive: &[u8], limits: ArchiveLimits) -> Result<Vec<ArchiveEntry>, ChonkitError> {
+    let mut archive = map_err!(ZipArchive::new(Cursor::new(archive)));
+    let mut extractor = Extractor::new(limits);
+
+    for i in 0..archive.len() {
+        let file = map_err!(archive.by_index(i));
+
+        if file.is_dir() {
+            continue;
+        }
+
+        let path = file.name().to_string();
+
+        if file.enclosed_name().is_none() {
+            extractor.skip(path, "unsafe path");
+            continue;
+        }
+
+        if !file.is_file() {
+            extractor.skip(path, "not a regular file");
+            continue;
+        }
+
+        let declared = file.size();
+        extractor.read(path, declared, file)?;
+    }
+
+    Ok(extractor.entries)
+}
+
+fn extract_tar_gz(
+    archive: &[u8],
+    limits: ArchiveLimits,
+) -> Result<Vec<ArchiveEntry>, ChonkitError> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    let mut extractor = Extractor::new(limits);
+
+    for entry in map_err!(archive.entries()) {
+        let entry = map_err!(entry);
+        let kind = entry.header().entry_type();
+
+        if kind.is_dir() {
+            continue;
+        }
+
+        let path = map_err!(entry.path());
+        let enclosed = is_enclosed(&path);
+        let path = path.to_string_lossy().to_string();
+
+        if !enclosed {
+            extractor.skip(path, "unsafe path");
+            continue;
+        }
+
+        if !kind.is_file() {
+            extractor.skip(path, "not a regular file");
+            continue;
+        }
+
+        let declared = entry.size();
+        extractor.read(path, declared, entry)?;
+    }
+
+    Ok(extractor.entries)
+}
+
+/// Returns `true` if the path is relative and never leaves the directory it is relative to.
+fn is_enclosed(path: &Path) -> bool {
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let Some(parent) = depth.checked_sub(1) else {
+                    return false;
+                };
+                depth = parent;
+            }
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Accumulates extracted entries while keeping track of the limits.
+struct Extractor {
+    limits: ArchiveLimits,
+    total: usize,
+    entries: Vec<ArchiveEntry>,
+}
+
+impl Extractor {
+    fn new(limits: ArchiveLimits) -> Self {
+        Self {
+            limits,
+            total: 0,
+            entries: vec![],
+        }
+    }
+
+    fn skip(&mut self, path: String, reason: impl Into<String>) {
+        self.entries.push(ArchiveEntry::Skipped {
+            path,
+            reason: reason.into(),
+        });
+    }
+
+    /// Read the entry, never reading more than one byte over the entry limit, regardless of
+    /// the size the archive declares for it.
+    fn read(&mut self, path: String, declared: u64, entry: impl Read) -> Result<(), ChonkitError> {
+        let max = self.limits.max_entry_size;
+
+        if declared > max as u64 {
+            self.skip(path, format!("exceeds maximum entry size of {max} bytes"));
+            return Ok(());
+        }
+
+        let mut content = vec![];
+        map_err!(entry.take(max as u64 + 1).read_to_end(&mut content));
+
+        if content.len() > max {
+            self.skip(path, format!("exceeds maximum entry size of {max} bytes"));
+            return Ok(());
+        }
+
+        self.total += content.len();
+
+        if self.total > self.limits.max_size {
+            return err!(
+                InvalidParameter,
+                "Archive exceeds maximum extracted size of {} bytes",
+                self.limits.max_size
+            );
+        }
+
+        let Some(name) = Path::new(&path).file_name() else {
+            self.skip(path, "missing file name");
+            return Ok(());
+        };
+
+        let name = name.to_string_lossy().to_string();
+
+        self.entries.push(ArchiveEntry::File {
+            path,
+            name,
+            content,
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract, is_enclosed, ArchiveEntry, ArchiveLimits};
+    use std::{
+        io::{Cursor, Write},
+        path::Path,
+    };
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    const LIMITS: ArchiveLimits = ArchiveLimits {
+        max_size: 64,
+        max_entry_size: 16,
+    };
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let gz = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            // `append_data` refuses paths with `..`, write the name in the header directly.
+            header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_cksum();
+            tar.append(&header, *content).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn summarize(entries: Vec<ArchiveEntry>) -> Vec<(String, bool)> {
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                ArchiveEntry::File { name, .. } => (name, true),
+                ArchiveEntry::Skipped { path, .. } => (path, false),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_zip_and_tar_gz() {
+        let entries: &[(&str, &[u8])] = &[
+            ("docs/raguru.txt", b"Raguru Labamba"),
+            ("../escape.txt", b"Gura"),
+            ("large.md", b"Ragu from the twin moons"),
+        ];
+
+        let expected = vec![
+            ("raguru.txt".to_string(), true),
+            ("../escape.txt".to_string(), false),
+            ("large.md".to_string(), false),
+        ];
+
+        assert_eq!(expected, summarize(extract(&zip(entries), LIMITS).unwrap()));
+        assert_eq!(
+            expected,
+            summarize(extract(&tar_gz(entries), LIMITS).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_archives_over_total_limit() {
+        let content = [b'R'; 16];
+        let entries: Vec<_> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|name| (*name, &content[..]))
+            .collect();
+
+        assert!(extract(&zip(&entries), LIMITS).is_err());
+        assert!(extract(&zip(&entries[..4]), LIMITS).is_ok());
+        assert!(extract(b"Raguru Labamba", LIMITS).is_err());
+    }
+
+    #[test]
+    fn detects_paths_escaping_root() {
+        assert!(is_enclosed(Path::new("a/b/../c.txt")));
+        assert!(is_enclosed(Path::new("./c.txt")));
+        assert!(!is_enclosed(Path::new("a/../../c.txt")));
+        assert!(!is_enclosed(Path::new("/etc/passwd")));
+    }
+}
@@ -1,12 +1,26 @@
 use super::DocumentType;
 use crate::{core::model::image::Image, err, error::ChonkitError, map_err};
+use redact::RedactionConfig;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    io::{Read, Seek},
+    sync::Arc,
+    time::Duration,
+};
 use validify::{schema_err, schema_validation, Validate, ValidationErrors};
 
 pub mod docx;
 pub mod excel;
+pub mod markdown;
+pub mod odt;
 pub mod pdf;
+pub mod redact;
+pub mod rtf;
+pub mod sanitize;
 pub mod text;
 
 /// Text parsing entry point.
@@ -18,16 +32,102 @@ pub fn parse_text(
     config: ParseConfig,
     ext: DocumentType,
     input: &[u8],
+) -> Result<ParseOutput, ChonkitError> {
+    parse(config, ext, ParseInput::Bytes(input))
+}
+
+/// Same as [parse_text], except the document is read from the `reader` as the parser needs it
+/// instead of being held in memory whole. Only PDFs are read incrementally, other document types
+/// are read to the end before parsing.
+///
+/// This performs blocking reads and should not be called on an async runtime.
+///
+/// * `config`: Parsing configuration for the document.
+/// * `ext`: Document extension.
+/// * `reader`: Document content.
+pub fn parse_text_from_reader(
+    config: ParseConfig,
+    ext: DocumentType,
+    reader: impl Read + Seek,
+) -> Result<ParseOutput, ChonkitError> {
+    parse(config, ext, ParseInput::Reader(Box::new(reader)))
+}
+
+/// Run the `parse` on a blocking thread, failing with [Timeout][crate::error::ChonkitErr::Timeout]
+/// if it does not finish within the `timeout`.
+///
+/// Parsing cannot be interrupted, so a parse that times out keeps running in the background
+/// until it finishes, but the caller is released.
+///
+/// * `timeout`: How long to wait for the parse.
+/// * `parse`: The parsing, e.g. a call to [parse_text].
+pub async fn parse_with_timeout(
+    timeout: Duration,
+    parse: impl FnOnce() -> Result<ParseOutput, ChonkitError> + Send + 'static,
+) -> Result<ParseOutput, ChonkitError> {
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(parse)).await {
+        Ok(result) => map_err!(result),
+        Err(_) => err!(
+            Timeout,
+            "Parsing did not finish within {}ms",
+            timeout.as_millis()
+        ),
+    }
+}
+
+/// Blanket trait for readers of [ParseInput].
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Document content handed to the text parsers.
+enum ParseInput<'a> {
+    Bytes(&'a [u8]),
+    Reader(Box<dyn ReadSeek + 'a>),
+}
+
+impl<'a> ParseInput<'a> {
+    /// Obtain the whole content, reading the reader to its end.
+    fn into_bytes(self) -> Result<Cow<'a, [u8]>, ChonkitError> {
+        match self {
+            Self::Bytes(bytes) => Ok(Cow::Borrowed(bytes)),
+            Self::Reader(mut reader) => {
+                let mut bytes = vec![];
+                map_err!(reader.read_to_end(&mut bytes));
+                Ok(Cow::Owned(bytes))
+            }
+        }
+    }
+}
+
+fn parse(
+    config: ParseConfig,
+    ext: DocumentType,
+    input: ParseInput<'_>,
 ) -> Result<ParseOutput, ChonkitError> {
     map_err!(config.validate());
 
     match config {
         ParseConfig::String(config) => {
             let out = match ext {
-                DocumentType::Text(_) => text::parse(&config, input)?,
-                DocumentType::Docx => docx::parse(&config, input)?,
-                DocumentType::Excel => excel::parse(&config, input)?,
+                DocumentType::Text(_) => text::parse(&config, &input.into_bytes()?)?,
+                DocumentType::Markdown => markdown::parse(&config, &input.into_bytes()?)?,
+                DocumentType::Docx => docx::parse(&config, &input.into_bytes()?)?,
+                DocumentType::Excel => excel::parse(&config, &input.into_bytes()?)?,
                 DocumentType::Pdf => pdf::parse_to_string(&config, input)?,
+                DocumentType::Rtf => rtf::parse(&config, &input.into_bytes()?)?,
+                DocumentType::Odt => odt::parse(&config, &input.into_bytes()?)?,
+            };
+
+            let out = if config.sanitize.unwrap_or(ext == DocumentType::Pdf) {
+                sanitize::sanitize(&out, config.keep_blank_lines, config.normalize_unicode)
+            } else {
+                out
+            };
+
+            let out = match config.redact {
+                Some(ref redact) => redact.redactor()?.redact(&out).into_owned(),
+                None => out,
             };
 
             if out.trim().is_empty() {
@@ -38,7 +138,14 @@ pub fn parse_text(
         }
         ParseConfig::Section(config) => match ext {
             DocumentType::Pdf => {
-                let out = pdf::parse_to_sections(&config, input)?;
+                let mut out = pdf::parse_to_sections(&config, input)?;
+
+                if let Some(ref redact) = config.redact {
+                    let redactor = redact.redactor()?;
+                    for page in out.iter_mut().flat_map(|section| section.pages.iter_mut()) {
+                        page.content = redactor.redact(&page.content).into_owned();
+                    }
+                }
 
                 if out.is_empty() {
                     return err!(InvalidFile, "Parsing resulted in empty output");
@@ -54,6 +161,47 @@ pub fn parse_text(
     }
 }
 
+/// Output the paragraphs selected by the `config`, each on its own line. Paragraphs with no
+/// text are not counted. Ranges are 1-based and inclusive.
+fn select_paragraphs(config: &StringParseConfig, paragraphs: &[String]) -> String {
+    let filters: Vec<Regex> = config
+        .filters
+        .iter()
+        .filter_map(|re| Regex::new(re).ok())
+        .collect();
+
+    let paragraphs: Vec<&str> = paragraphs
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    let (skip, take) = if config.range {
+        (config.start - 1, config.end + 1 - config.start)
+    } else {
+        (
+            config.start,
+            paragraphs.len().saturating_sub(config.start + config.end),
+        )
+    };
+
+    let mut out = String::new();
+
+    for paragraph in paragraphs.into_iter().skip(skip).take(take) {
+        'lines: for line in paragraph.lines() {
+            for filter in filters.iter() {
+                if filter.is_match(line) {
+                    continue 'lines;
+                }
+            }
+
+            let _ = writeln!(out, "{line}");
+        }
+    }
+
+    out
+}
+
 /// Parse all images of a document, skipping those found in `skip`.
 ///
 /// The `skip` set is a set of the combination of an image's page number and
@@ -65,6 +213,7 @@ pub fn parse_images(
 ) -> Result<Vec<Image>, ChonkitError> {
     match ext {
         DocumentType::Pdf => pdf::parse_images(input, skip),
+        DocumentType::Docx => docx::parse_images(input, skip),
         _ => err!(
             InvalidParameter,
             "Image parsing not yet supported for document type '{ext}'"
@@ -86,6 +235,17 @@ impl Default for ParseConfig {
     }
 }
 
+impl ParseConfig {
+    /// How long to wait for parsing, if set.
+    pub fn timeout(&self) -> Option<Duration> {
+        let timeout = match self {
+            ParseConfig::String(config) => config.timeout,
+            ParseConfig::Section(config) => config.timeout,
+        };
+        timeout.map(Duration::from_millis)
+    }
+}
+
 impl std::fmt::Display for ParseConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -95,6 +255,47 @@ impl std::fmt::Display for ParseConfig {
     }
 }
 
+/// Parsing configurations used for documents that do not have one stored, per document type.
+/// Document types without an entry fall back to [ParseConfig::default].
+#[derive(Debug, Clone, Default)]
+pub struct ParserDefaults(Arc<HashMap<DocumentType, ParseConfig>>);
+
+impl ParserDefaults {
+    /// Load the defaults from a JSON object mapping document extensions to parsing
+    /// configurations, e.g.
+    /// `{ "pdf": { "section": { "sections": [{ "start": 1, "end": 10 }], "filters": [] } } }`.
+    pub fn from_json(json: &str) -> Result<Self, ChonkitError> {
+        let configs: HashMap<String, ParseConfig> = map_err!(serde_json::from_str(json));
+
+        let mut defaults = HashMap::with_capacity(configs.len());
+
+        for (ext, config) in configs {
+            let ty = DocumentType::try_from(ext.as_str())?;
+
+            map_err!(config.validate());
+
+            if matches!(config, ParseConfig::Section(_)) && !matches!(ty, DocumentType::Pdf) {
+                return err!(
+                    InvalidParameter,
+                    "Sectioned parsing not yet supported for document type '{ty}'"
+                );
+            }
+
+            defaults.insert(ty, config);
+        }
+
+        Ok(Self(Arc::new(defaults)))
+    }
+
+    /// Get the parsing configuration for a document of type `ty`, using its `stored`
+    /// configuration if it has one.
+    pub fn resolve(&self, ty: DocumentType, stored: Option<ParseConfig>) -> ParseConfig {
+        stored
+            .or_else(|| self.0.get(&ty).cloned())
+            .unwrap_or_default()
+    }
+}
+
 /// Note: PartialEq implementation checks text only.
 #[derive(Debug)]
 pub enum ParseOutput {
@@ -142,6 +343,46 @@ pub struct StringParseConfig {
 
     /// Exclude specific lines matching any of the patterns provided here from the output.
     pub filters: Vec<String>,
+
+    /// Markdown only. If true, the formatting is stripped from the output, leaving only the text.
+    /// Useful for embedding, while the formatting can be preserved for display.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strip_markdown: bool,
+
+    /// DOCX and Markdown only. Determines how hyperlinks are output. If not set, DOCX links are
+    /// output as text only and Markdown links are kept as is, unless stripped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_handling: Option<LinkHandling>,
+
+    /// If true, the output is sanitized by normalizing whitespace and removing control
+    /// characters. If not set, only PDF output is sanitized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sanitize: Option<bool>,
+
+    /// Sanitization only. If true, consecutive blank lines are kept instead of being collapsed
+    /// into a single one.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keep_blank_lines: bool,
+
+    /// Sanitization only. If true, the output is NFC normalized.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub normalize_unicode: bool,
+
+    /// PDF only. If true, text laid out in rows and columns is output as Markdown tables.
+    /// Pages where no table is confidently detected are output as plain text.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub extract_tables: bool,
+
+    /// How long to wait for parsing, in milliseconds. If not set, the server default is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1.))]
+    pub timeout: Option<u64>,
+
+    /// If set, sensitive content is redacted from the output, and therefore from the chunks
+    /// embedded and stored from it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate]
+    pub redact: Option<RedactionConfig>,
 }
 
 impl StringParseConfig {
@@ -159,6 +400,36 @@ impl StringParseConfig {
         self
     }
 
+    /// Strip the formatting from Markdown documents.
+    pub fn strip_markdown(mut self) -> Self {
+        self.strip_markdown = true;
+        self
+    }
+
+    /// Set whether to sanitize the output.
+    pub fn with_sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = Some(sanitize);
+        self
+    }
+
+    /// Output PDF tables as Markdown tables.
+    pub fn extract_tables(mut self) -> Self {
+        self.extract_tables = true;
+        self
+    }
+
+    /// Set how hyperlinks are output.
+    pub fn with_link_handling(mut self, link_handling: LinkHandling) -> Self {
+        self.link_handling = Some(link_handling);
+        self
+    }
+
+    /// Redact sensitive content from the output.
+    pub fn with_redaction(mut self, redact: RedactionConfig) -> Self {
+        self.redact = Some(redact);
+        self
+    }
+
     /// Add a filter to the parser.
     ///
     /// * `re`: The expression to match for.
@@ -181,6 +452,20 @@ impl StringParseConfig {
     }
 }
 
+/// Determines how parsers output hyperlinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkHandling {
+    /// Output only the text of the link.
+    TextOnly,
+
+    /// Output the text of the link followed by its URL in parentheses, e.g. `text (url)`.
+    TextWithUrl,
+
+    /// Omit the link, including its text.
+    Drop,
+}
+
 /// Pagination based parser.
 ///
 /// Applicable to documents with pagination:
@@ -196,6 +481,17 @@ pub struct SectionParseConfig {
 
     /// Exclude lines matching any of the provided filters.
     pub filters: Vec<String>,
+
+    /// How long to wait for parsing, in milliseconds. If not set, the server default is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1.))]
+    pub timeout: Option<u64>,
+
+    /// If set, sensitive content is redacted from the output, and therefore from the sections
+    /// embedded and stored from it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate]
+    pub redact: Option<RedactionConfig>,
 }
 
 /// Represents a range of pages in a document to capture in the final output.
@@ -237,3 +533,86 @@ pub struct DocumentPage {
     /// The page number.
     pub number: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_text, parse_with_timeout, ParseConfig, ParseOutput, ParserDefaults, StringParseConfig,
+    };
+    use crate::{
+        core::document::{DocumentType, TextDocumentType},
+        error::ChonkitErr,
+    };
+    use std::time::Duration;
+
+    const DEFAULTS: &str =
+        r#"{ "pdf": { "section": { "sections": [{ "start": 1, "end": 10 }], "filters": [] } } }"#;
+
+    #[test]
+    fn uses_configured_default_without_stored_config() {
+        let defaults = ParserDefaults::from_json(DEFAULTS).unwrap();
+
+        let config = defaults.resolve(DocumentType::Pdf, None);
+        assert!(matches!(config, ParseConfig::Section(_)));
+
+        let config = defaults.resolve(DocumentType::Text(TextDocumentType::Txt), None);
+        assert!(matches!(config, ParseConfig::String(_)));
+    }
+
+    #[test]
+    fn prefers_stored_config() {
+        let defaults = ParserDefaults::from_json(DEFAULTS).unwrap();
+
+        let stored = ParseConfig::String(StringParseConfig::new(1, 0));
+        let ParseConfig::String(config) = defaults.resolve(DocumentType::Pdf, Some(stored)) else {
+            panic!("expected stored string config");
+        };
+
+        assert_eq!(1, config.start);
+    }
+
+    #[test]
+    fn rejects_unsupported_section_defaults() {
+        let err = ParserDefaults::from_json(
+            r#"{ "txt": { "section": { "sections": [], "filters": [] } } }"#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err.error, ChonkitErr::InvalidParameter(_)));
+    }
+
+    #[test]
+    fn sanitizes_when_configured() {
+        let input = "Raguru\u{00A0}cooks   ragu.\u{000C}\n\n\nGura";
+        let ty = DocumentType::Text(TextDocumentType::Txt);
+
+        let out = parse_text(ParseConfig::default(), ty, input.as_bytes()).unwrap();
+        assert_eq!(ParseOutput::String(input.to_string()), out);
+
+        let config = ParseConfig::String(StringParseConfig::default().with_sanitize(true));
+        let out = parse_text(config, ty, input.as_bytes()).unwrap();
+        assert_eq!(
+            ParseOutput::String("Raguru cooks ragu.\n\nGura".to_string()),
+            out
+        );
+    }
+
+    #[tokio::test]
+    async fn parsing_times_out() {
+        // Stands in for a parser stuck on a malformed document
+        let slow_parse = || {
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(ParseOutput::String("Raguru".to_string()))
+        };
+
+        let err = parse_with_timeout(Duration::from_millis(10), slow_parse)
+            .await
+            .unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::Timeout(_)));
+
+        let out = parse_with_timeout(Duration::from_secs(5), slow_parse)
+            .await
+            .unwrap();
+        assert_eq!(ParseOutput::String("Raguru".to_string()), out);
+    }
+}
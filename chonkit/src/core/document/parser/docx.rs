@@ -1,22 +1,56 @@
-use super::StringParseConfig;
-use crate::error::ChonkitError;
-use crate::map_err;
+use super::{LinkHandling, StringParseConfig};
+use crate::core::model::image::Image;
+use crate::error::{ChonkitErr, ChonkitError};
+use crate::{err, map_err};
 use docx_rs::read_docx;
-use docx_rs::{Paragraph, ParagraphChild, RunChild, Table};
-use std::{fmt::Write, time::Instant};
+use docx_rs::{HyperlinkData, Paragraph, ParagraphChild, RunChild, Table};
+use quick_xml::{events::Event, Reader};
+use std::io::{Cursor, Read};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    time::Instant,
+};
 use tracing::debug;
+use zip::{result::ZipError, ZipArchive};
 
-pub(super) fn parse(_config: &StringParseConfig, input: &[u8]) -> Result<String, ChonkitError> {
+/// The archive entry holding the document body.
+const DOCUMENT_ENTRY: &str = "word/document.xml";
+
+/// The archive entry holding the relationships of the document body, e.g. image paths.
+const RELATIONSHIPS_ENTRY: &str = "word/_rels/document.xml.rels";
+
+/// Parser implementation for DOCX documents.
+///
+/// Configuration:
+///
+/// * `link_handling`: Determines how hyperlinks are output, defaults to [LinkHandling::TextOnly].
+///   Only external links have a URL, internal links (bookmarks) are always output as text only
+///   when using [LinkHandling::TextWithUrl].
+pub(super) fn parse(config: &StringParseConfig, input: &[u8]) -> Result<String, ChonkitError> {
     let start = Instant::now();
 
     let input = map_err!(read_docx(input));
     let mut out = String::new();
 
-    for el in input.document.children {
+    // Maps relationship IDs to the targets of external hyperlinks
+    let urls: HashMap<&str, &str> = input
+        .hyperlinks
+        .iter()
+        .map(|(rid, path, _)| (rid.as_str(), path.as_str()))
+        .collect();
+
+    let links = Links {
+        handling: config.link_handling.unwrap_or(LinkHandling::TextOnly),
+        urls: &urls,
+    };
+
+    for el in input.document.children.iter() {
         match el {
             docx_rs::DocumentChild::Paragraph(ref el) => {
                 let mut paragraph = String::new();
-                let text = map_err!(extract_paragraph(el));
+                let text = map_err!(extract_paragraph(el, &links));
                 for text in text {
                     let text = text.trim();
                     if text.is_empty() {
@@ -27,7 +61,7 @@ pub(super) fn parse(_config: &StringParseConfig, input: &[u8]) -> Result<String,
                 let _ = writeln!(out, "{paragraph}");
             }
             docx_rs::DocumentChild::Table(el) => {
-                let table = map_err!(extract_table(*el));
+                let table = map_err!(extract_table(el, &links));
                 let _ = writeln!(out, "{table}");
             }
             _ => {}
@@ -45,7 +79,8 @@ pub(super) fn parse(_config: &StringParseConfig, input: &[u8]) -> Result<String,
 /// Given a DOCX table, create the equivalent table in Markdown style.
 ///
 /// * `table`: The table to process.
-fn extract_table(table: Table) -> Result<String, std::fmt::Error> {
+/// * `links`: How to output hyperlinks in cells.
+fn extract_table(table: &Table, links: &Links) -> Result<String, std::fmt::Error> {
     let mut table_out = String::new();
 
     for row in table.rows.iter() {
@@ -69,7 +104,7 @@ fn extract_table(table: Table) -> Result<String, std::fmt::Error> {
             for child in cell.children.iter() {
                 match child {
                     docx_rs::TableCellContent::Paragraph(ref p) => {
-                        let text = extract_paragraph(p)?;
+                        let text = extract_paragraph(p, links)?;
                         write!(cell_buf, " {} ", text.join(""))?;
                     }
                     c => debug!("Unrecognized child in table cell: {:?}", c),
@@ -96,7 +131,10 @@ fn extract_table(table: Table) -> Result<String, std::fmt::Error> {
     Ok(table_out)
 }
 
-fn extract_paragraph(p: &Paragraph) -> Result<Vec<&str>, std::fmt::Error> {
+fn extract_paragraph<'a>(
+    p: &'a Paragraph,
+    links: &Links,
+) -> Result<Vec<Cow<'a, str>>, std::fmt::Error> {
     let mut out = vec![];
 
     for child in p.children.iter() {
@@ -104,17 +142,27 @@ fn extract_paragraph(p: &Paragraph) -> Result<Vec<&str>, std::fmt::Error> {
             docx_rs::ParagraphChild::Run(run) => {
                 for rchild in run.children.iter() {
                     let RunChild::Text(t) = rchild else { continue };
-                    out.push(t.text.as_str());
+                    out.push(Cow::Borrowed(t.text.as_str()));
                 }
             }
             docx_rs::ParagraphChild::Hyperlink(hl) => {
+                if links.handling == LinkHandling::Drop {
+                    continue;
+                }
+
                 for rchild in hl.children.iter() {
                     let ParagraphChild::Run(run) = rchild else {
                         continue;
                     };
                     for rchild in run.children.iter() {
                         let RunChild::Text(t) = rchild else { continue };
-                        out.push(t.text.as_str());
+                        out.push(Cow::Borrowed(t.text.as_str()));
+                    }
+                }
+
+                if links.handling == LinkHandling::TextWithUrl {
+                    if let Some(url) = links.url(&hl.link) {
+                        out.push(Cow::Owned(format!(" ({url})")));
                     }
                 }
             }
@@ -124,3 +172,369 @@ fn extract_paragraph(p: &Paragraph) -> Result<Vec<&str>, std::fmt::Error> {
 
     Ok(out)
 }
+
+/// Hyperlink output configuration.
+struct Links<'a> {
+    handling: LinkHandling,
+
+    /// External hyperlink targets, keyed by their relationship ID.
+    urls: &'a HashMap<&'a str, &'a str>,
+}
+
+impl Links<'_> {
+    /// Get the URL of an external link. When reading documents the path is usually empty and
+    /// the target is found in the document relationships instead.
+    fn url<'a>(&'a self, link: &'a HyperlinkData) -> Option<&'a str> {
+        let HyperlinkData::External { rid, path } = link else {
+            return None;
+        };
+
+        if !path.is_empty() {
+            return Some(path);
+        }
+
+        self.urls.get(rid.as_str()).copied()
+    }
+}
+
+/// Extract the images embedded in the document body.
+///
+/// DOCX documents are not paginated, so all images are on page 0 and numbered in the order
+/// they appear in. The alt-text of an image's drawing (its `descr`, or `title` if it has none)
+/// is used as the image's initial description. Images whose format cannot be decoded,
+/// e.g. EMF, and linked images are skipped.
+pub(super) fn parse_images(
+    input: &[u8],
+    skip: &HashSet<(usize, usize)>,
+) -> Result<Vec<Image>, ChonkitError> {
+    let start = Instant::now();
+
+    let mut archive = map_err!(ZipArchive::new(Cursor::new(input)));
+
+    let relationships = read_entry(&mut archive, RELATIONSHIPS_ENTRY)?;
+    let relationships = map_err!(String::from_utf8(relationships));
+    let targets = extract_image_targets(&relationships)?;
+
+    let document = read_entry(&mut archive, DOCUMENT_ENTRY)?;
+    let document = map_err!(String::from_utf8(document));
+    let drawings = extract_drawings(&document)?;
+
+    let mut images = vec![];
+
+    for (image_num, drawing) in drawings.into_iter().enumerate() {
+        if skip.contains(&(0, image_num)) {
+            continue;
+        }
+
+        let Some(target) = targets.get(drawing.rid.as_str()) else {
+            debug!("Image {} is not embedded in the document", drawing.rid);
+            continue;
+        };
+
+        let bytes = match read_entry(&mut archive, target) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                debug!("Error reading image '{target}': {e}");
+                continue;
+            }
+        };
+
+        let reader = map_err!(image::ImageReader::new(Cursor::new(&bytes)).with_guessed_format());
+
+        let Some(format) = reader.format() else {
+            debug!("Unsupported format of image '{target}'");
+            continue;
+        };
+
+        let (width, height) = match reader.into_dimensions() {
+            Ok(dimensions) => dimensions,
+            Err(e) => {
+                debug!("Error decoding image '{target}': {e}");
+                continue;
+            }
+        };
+
+        let mut image = Image::new(Some(0), Some(image_num), bytes, format, width, height);
+        image.description = drawing.description;
+
+        images.push(image);
+    }
+
+    debug!(
+        "Finished processing DOCX images, took {}ms",
+        Instant::now().duration_since(start).as_millis()
+    );
+
+    Ok(images)
+}
+
+/// An image drawn in the document body.
+struct Drawing {
+    /// The relationship ID of the image.
+    rid: String,
+
+    /// The alt-text of the drawing.
+    description: Option<String>,
+}
+
+/// Extract the drawings in the document body in order of appearance. Drawings without an
+/// image, e.g. shapes and charts, are omitted.
+fn extract_drawings(document: &str) -> Result<Vec<Drawing>, ChonkitError> {
+    let mut reader = Reader::from_str(document);
+
+    let mut drawings = vec![];
+
+    let mut rid = None;
+    let mut description = None;
+
+    loop {
+        match map_err!(reader.read_event()) {
+            Event::Start(el) | Event::Empty(el) => match el.name().as_ref() {
+                b"wp:docPr" => {
+                    description = ["descr", "title"].into_iter().find_map(|attr| {
+                        let value = el.try_get_attribute(attr).ok().flatten()?;
+                        let value = value.decode_and_unescape_value(reader.decoder()).ok()?;
+                        let value = value.trim();
+                        (!value.is_empty()).then(|| value.to_string())
+                    });
+                }
+                b"a:blip" => {
+                    rid = el
+                        .try_get_attribute("r:embed")
+                        .ok()
+                        .flatten()
+                        .map(|rid| String::from_utf8_lossy(&rid.value).to_string());
+                }
+                _ => {}
+            },
+            Event::End(el) if el.name().as_ref() == b"w:drawing" => {
+                let description = description.take();
+                if let Some(rid) = rid.take() {
+                    drawings.push(Drawing { rid, description });
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(drawings)
+}
+
+/// Map the relationship IDs of embedded images to their archive entries.
+fn extract_image_targets(relationships: &str) -> Result<HashMap<String, String>, ChonkitError> {
+    let mut reader = Reader::from_str(relationships);
+
+    let mut targets = HashMap::new();
+
+    loop {
+        match map_err!(reader.read_event()) {
+            Event::Start(el) | Event::Empty(el) if el.name().as_ref() == b"Relationship" => {
+                let attr = |name: &str| {
+                    el.try_get_attribute(name)
+                        .ok()
+                        .flatten()
+                        .map(|attr| String::from_utf8_lossy(&attr.value).to_string())
+                };
+
+                let (Some(id), Some(ty), Some(target)) = (attr("Id"), attr("Type"), attr("Target"))
+                else {
+                    continue;
+                };
+
+                if !ty.ends_with("/image") || attr("TargetMode").is_some_and(|m| m == "External") {
+                    continue;
+                }
+
+                // Targets are relative to the document body unless absolute
+                let entry = match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("word/{target}"),
+                };
+
+                targets.insert(id, entry);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(targets)
+}
+
+fn read_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Vec<u8>, ChonkitError> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => {
+            return err!(InvalidFile, "DOCX document is missing '{name}'")
+        }
+        Err(e) => return err!(ChonkitErr::Zip(e)),
+    };
+
+    let mut bytes = vec![];
+    map_err!(entry.read_to_end(&mut bytes));
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_images};
+    use crate::core::document::parser::{LinkHandling, StringParseConfig};
+    use docx_rs::{Docx, Hyperlink, HyperlinkType, Paragraph, Run};
+    use std::{
+        collections::HashSet,
+        io::{Cursor, Write},
+    };
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    fn docx() -> Vec<u8> {
+        let paragraph = Paragraph::new()
+            .add_run(Run::new().add_text("Raguru cooks in his"))
+            .add_hyperlink(
+                Hyperlink::new("https://gura.space", HyperlinkType::External)
+                    .add_run(Run::new().add_text("floating kitchen")),
+            )
+            .add_run(Run::new().add_text("above Gura."));
+
+        let mut out = Cursor::new(vec![]);
+        Docx::new()
+            .add_paragraph(paragraph)
+            .build()
+            .pack(&mut out)
+            .unwrap();
+
+        out.into_inner()
+    }
+
+    fn parse_with(handling: Option<LinkHandling>) -> String {
+        let config = StringParseConfig {
+            link_handling: handling,
+            ..Default::default()
+        };
+        parse(&config, &docx()).unwrap()
+    }
+
+    #[test]
+    fn outputs_link_text_by_default() {
+        let expected = "Raguru cooks in his floating kitchen above Gura. \n";
+
+        assert_eq!(expected, parse_with(None));
+        assert_eq!(expected, parse_with(Some(LinkHandling::TextOnly)));
+    }
+
+    #[test]
+    fn appends_link_url() {
+        assert_eq!(
+            "Raguru cooks in his floating kitchen (https://gura.space) above Gura. \n",
+            parse_with(Some(LinkHandling::TextWithUrl))
+        );
+    }
+
+    #[test]
+    fn drops_links() {
+        assert_eq!(
+            "Raguru cooks in his above Gura. \n",
+            parse_with(Some(LinkHandling::Drop))
+        );
+    }
+
+    /// A drawing of the image with the relationship ID and `wp:docPr` attributes.
+    fn drawing(rid: &str, doc_pr: &str) -> String {
+        format!(
+            r#"<w:p><w:r><w:drawing><wp:inline>
+                <wp:docPr id="1" name="Picture" {doc_pr}/>
+                <a:graphic><a:graphicData><pic:pic><pic:blipFill>
+                    <a:blip r:embed="{rid}"/>
+                </pic:blipFill></pic:pic></a:graphicData></a:graphic>
+            </wp:inline></w:drawing></w:r></w:p>"#
+        )
+    }
+
+    fn docx_with_images() -> Vec<u8> {
+        let mut png = Cursor::new(vec![]);
+        image::RgbaImage::new(4, 2)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+            <w:document
+                xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+                xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
+                xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
+                xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"
+                xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+                <w:body>
+                    <w:p><w:r><w:t>Raguru's menu</w:t></w:r></w:p>
+                    {}
+                    {}
+                    {}
+                </w:body>
+            </w:document>"#,
+            drawing("rId1", r#"descr="Raguru stirring ragu &amp; spices""#),
+            drawing("rId2", r#"descr=" " title="The floating kitchen""#),
+            drawing("rId1", ""),
+        );
+
+        let relationships = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+            <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+                <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.png"/>
+                <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="/word/media/image1.png"/>
+            </Relationships>"#;
+
+        let mut archive = ZipWriter::new(Cursor::new(vec![]));
+        for (name, content) in [
+            ("word/document.xml", document.as_bytes()),
+            ("word/_rels/document.xml.rels", relationships.as_bytes()),
+            ("word/media/image1.png", png.get_ref().as_slice()),
+        ] {
+            archive
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            archive.write_all(content).unwrap();
+        }
+
+        archive.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn uses_alt_text_as_image_description() {
+        let images = parse_images(&docx_with_images(), &HashSet::new()).unwrap();
+
+        assert_eq!(3, images.len());
+
+        assert_eq!(
+            Some("Raguru stirring ragu & spices"),
+            images[0].description.as_deref()
+        );
+        assert_eq!(
+            Some("The floating kitchen"),
+            images[1].description.as_deref()
+        );
+        assert_eq!(None, images[2].description);
+
+        for (i, image) in images.iter().enumerate() {
+            assert_eq!(Some(0), image.page_number);
+            assert_eq!(Some(i), image.image_number);
+            assert_eq!(image::ImageFormat::Png, image.image.format);
+            assert_eq!((4, 2), (image.image.width, image.image.height));
+        }
+    }
+
+    #[test]
+    fn skips_parsed_images() {
+        let skip = HashSet::from([(0, 0), (0, 2)]);
+        let images = parse_images(&docx_with_images(), &skip).unwrap();
+
+        assert_eq!(1, images.len());
+        assert_eq!(Some(1), images[0].image_number);
+        assert_eq!(
+            Some("The floating kitchen"),
+            images[0].description.as_deref()
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use super::{LinkHandling, StringParseConfig};
+use crate::error::ChonkitError;
+use regex::{Captures, Regex};
+use std::{borrow::Cow, collections::BTreeMap, fmt::Write};
+
+/// Markdown front matter delimiter.
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+/// Parser implementation for Markdown documents.
+///
+/// The front matter, if any, is never included in the output. Use [front_matter] to obtain it.
+///
+/// Configuration:
+///
+/// * `filters`: Line based, i.e. lines matching a filter will be skipped.
+/// * `strip_markdown`: If `true`, the formatting is removed, leaving only the text. Contents of
+///   code blocks are kept as is.
+/// * `link_handling`: If set, links outside of code blocks are rewritten accordingly. Images are
+///   not considered links.
+pub(super) fn parse(config: &StringParseConfig, input: &[u8]) -> Result<String, ChonkitError> {
+    let input = String::from_utf8_lossy(input);
+    let (_, body) = split_front_matter(&input);
+
+    let filters: Vec<Regex> = config
+        .filters
+        .iter()
+        .filter_map(|re| Regex::new(re).ok())
+        .collect();
+
+    let stripper = config.strip_markdown.then(MarkdownStripper::new);
+    let links = config.link_handling.map(LinkRewriter::new);
+
+    let mut out = String::new();
+    let mut in_code_block = false;
+
+    'lines: for line in body.lines() {
+        for filter in filters.iter() {
+            if filter.is_match(line) {
+                continue 'lines;
+            }
+        }
+
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            if stripper.is_none() {
+                let _ = writeln!(out, "{line}");
+            }
+            continue;
+        }
+
+        if in_code_block {
+            let _ = writeln!(out, "{line}");
+            continue;
+        }
+
+        let line = match links {
+            Some(ref links) => links.rewrite(line),
+            None => Cow::Borrowed(line),
+        };
+
+        let Some(ref stripper) = stripper else {
+            let _ = writeln!(out, "{line}");
+            continue;
+        };
+
+        if let Some(line) = stripper.strip(&line) {
+            let _ = writeln!(out, "{line}");
+        }
+    }
+
+    Ok(out)
+}
+
+/// Extract the front matter of a Markdown document, i.e. the YAML delimited by `---` at the very
+/// start of the document.
+///
+/// Only top level `key: value` pairs are extracted. Indented lines and list items are appended
+/// to the value of the preceding key.
+pub fn front_matter(input: &[u8]) -> Option<BTreeMap<String, String>> {
+    let input = String::from_utf8_lossy(input);
+    let (front_matter, _) = split_front_matter(&input);
+    let front_matter = front_matter?;
+
+    let mut out = BTreeMap::<String, String>::new();
+    let mut current: Option<String> = None;
+
+    for line in front_matter.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let continuation = line.starts_with(char::is_whitespace) || line.starts_with('-');
+
+        if let (true, Some(key)) = (continuation, &current) {
+            if let Some(value) = out.get_mut(key) {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        let key = key.trim().to_string();
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+
+        out.insert(key.clone(), value.to_string());
+        current = Some(key);
+    }
+
+    Some(out)
+}
+
+/// Split the input into its front matter (without the delimiters) and its body.
+fn split_front_matter(input: &str) -> (Option<&str>, &str) {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+
+    let Some(rest) = input.strip_prefix(FRONT_MATTER_DELIMITER) else {
+        return (None, input);
+    };
+
+    let Some(rest) = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))
+    else {
+        return (None, input);
+    };
+
+    let mut offset = 0;
+
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FRONT_MATTER_DELIMITER {
+            let front_matter = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return (Some(front_matter), body);
+        }
+        offset += line.len();
+    }
+
+    // Unterminated, treat as regular content
+    (None, input)
+}
+
+/// Rewrites inline Markdown links according to a [LinkHandling].
+struct LinkRewriter {
+    /// Matches links and images, capturing the image marker, the text and the URL.
+    /// Link titles, e.g. `[text](url "title")`, are matched but not captured.
+    link: Regex,
+
+    handling: LinkHandling,
+}
+
+impl LinkRewriter {
+    fn new(handling: LinkHandling) -> Self {
+        Self {
+            link: Regex::new(r"(!?)\[([^\]]*)\]\(\s*([^)\s]*)[^)]*\)").unwrap(),
+            handling,
+        }
+    }
+
+    fn rewrite<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        self.link.replace_all(line, |caps: &Captures| {
+            // Images are left to the stripper
+            if !caps[1].is_empty() {
+                return caps[0].to_string();
+            }
+
+            match self.handling {
+                LinkHandling::TextOnly => caps[2].to_string(),
+                LinkHandling::TextWithUrl if caps[3].is_empty() => caps[2].to_string(),
+                LinkHandling::TextWithUrl => format!("{} ({})", &caps[2], &caps[3]),
+                LinkHandling::Drop => String::new(),
+            }
+        })
+    }
+}
+
+/// Removes Markdown formatting from lines.
+struct MarkdownStripper {
+    /// Matches lines consisting only of horizontal rules or table separators.
+    skip: Regex,
+
+    /// Matches block level prefixes; headings, blockquotes and list markers.
+    prefix: Regex,
+
+    /// Inline patterns and their replacements, applied in order.
+    inline: Vec<(Regex, &'static str)>,
+}
+
+impl MarkdownStripper {
+    fn new() -> Self {
+        Self {
+            skip: Regex::new(r"^\s*(?:(?:[-*_]\s*){3,}|\|?(?:\s*:?-+:?\s*\|)+\s*:?-*:?\s*)$")
+                .unwrap(),
+            prefix: Regex::new(r"^\s*(?:>\s?)*(?:#{1,6}\s+|[-*+]\s+(?:\[[ xX]\]\s+)?|\d+[.)]\s+)?")
+                .unwrap(),
+            inline: vec![
+                // Images and links
+                (Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap(), "$1"),
+                (Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap(), "$1"),
+                // Emphasis, strikethrough and inline code
+                (Regex::new(r"\*\*([^*]+)\*\*").unwrap(), "$1"),
+                (Regex::new(r"\b__([^_]+)__\b").unwrap(), "$1"),
+                (Regex::new(r"\*([^*\s][^*]*)\*").unwrap(), "$1"),
+                (Regex::new(r"\b_([^_\s][^_]*)_\b").unwrap(), "$1"),
+                (Regex::new(r"~~([^~]+)~~").unwrap(), "$1"),
+                (Regex::new(r"`([^`]+)`").unwrap(), "$1"),
+                // Trailing heading hashes, e.g. `## Heading ##`
+                (Regex::new(r"\s+#+\s*$").unwrap(), ""),
+            ],
+        }
+    }
+
+    /// Strip the formatting from the line. Returns `None` if the line
+    /// should be omitted from the output.
+    fn strip(&self, line: &str) -> Option<String> {
+        if self.skip.is_match(line) {
+            return None;
+        }
+
+        let mut line = self.prefix.replace(line, "").to_string();
+
+        for (re, replacement) in self.inline.iter() {
+            line = re.replace_all(&line, *replacement).to_string();
+        }
+
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{front_matter, parse};
+    use crate::core::document::parser::{LinkHandling, StringParseConfig};
+
+    const INPUT: &str = r#"---
+title: "Raguru Labamba"
+author: Chef
+tags:
+  - ragu
+  - gura
+---
+# The finest **ragu** in the galaxy
+
+Raguru crafts dishes from his [floating kitchen](https://gura.space).
+
+- *Guran* spices
+- `interstellar` ingredients
+"#;
+
+    #[test]
+    fn parses_front_matter() {
+        let front_matter = front_matter(INPUT.as_bytes()).unwrap();
+
+        assert_eq!("Raguru Labamba", front_matter["title"]);
+        assert_eq!("Chef", front_matter["author"]);
+        assert_eq!("- ragu\n- gura", front_matter["tags"]);
+
+        assert!(super::front_matter(b"# No front matter").is_none());
+    }
+
+    #[test]
+    fn omits_front_matter_from_output() {
+        let out = parse(&StringParseConfig::default(), INPUT.as_bytes()).unwrap();
+
+        assert!(!out.contains("title:"));
+        assert!(!out.contains("---"));
+        assert!(out.starts_with("# The finest **ragu** in the galaxy"));
+    }
+
+    #[test]
+    fn strips_formatting() {
+        let config = StringParseConfig {
+            strip_markdown: true,
+            ..Default::default()
+        };
+
+        let out = parse(&config, INPUT.as_bytes()).unwrap();
+
+        assert_eq!(
+            "The finest ragu in the galaxy\n\nRaguru crafts dishes from his floating kitchen.\n\nGuran spices\ninterstellar ingredients\n",
+            out
+        );
+    }
+
+    #[test]
+    fn handles_links() {
+        let input =
+            "See the [floating kitchen](https://gura.space \"Gura\") and ![ragu](ragu.png).\n\
+                     ```\n[code](https://gura.space)\n```\n";
+
+        let parse_with = |handling| {
+            let config = StringParseConfig::default().with_link_handling(handling);
+            parse(&config, input.as_bytes()).unwrap()
+        };
+
+        assert_eq!(
+            "See the floating kitchen and ![ragu](ragu.png).\n```\n[code](https://gura.space)\n```\n",
+            parse_with(LinkHandling::TextOnly)
+        );
+        assert_eq!(
+            "See the floating kitchen (https://gura.space) and ![ragu](ragu.png).\n```\n[code](https://gura.space)\n```\n",
+            parse_with(LinkHandling::TextWithUrl)
+        );
+        assert_eq!(
+            "See the  and ![ragu](ragu.png).\n```\n[code](https://gura.space)\n```\n",
+            parse_with(LinkHandling::Drop)
+        );
+
+        let config = StringParseConfig::default()
+            .strip_markdown()
+            .with_link_handling(LinkHandling::TextWithUrl);
+
+        assert_eq!(
+            "See the floating kitchen (https://gura.space) and ragu.\n[code](https://gura.space)\n",
+            parse(&config, input.as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn keeps_unterminated_front_matter() {
+        let input = "---\ntitle: foo\n# Heading";
+        let out = parse(&StringParseConfig::default(), input.as_bytes()).unwrap();
+        assert_eq!("---\ntitle: foo\n# Heading\n", out);
+        assert!(front_matter(input.as_bytes()).is_none());
+    }
+}
@@ -0,0 +1,184 @@
+use super::{select_paragraphs, StringParseConfig};
+use crate::{
+    err,
+    error::{ChonkitErr, ChonkitError},
+    map_err,
+};
+use quick_xml::{events::Event, Reader};
+use std::{
+    io::{Cursor, Read},
+    time::Instant,
+};
+use tracing::debug;
+use zip::{result::ZipError, ZipArchive};
+
+/// The archive entry holding the document body.
+const CONTENT_ENTRY: &str = "content.xml";
+
+/// Elements whose text is not part of the document body.
+const SKIPPED_ELEMENTS: &[&[u8]] = &[b"office:annotation", b"text:tracked-changes"];
+
+/// Parser implementation for ODT documents.
+///
+/// Every paragraph and heading is output on its own line. Tables output each of their cells
+/// as a paragraph. Annotations and tracked changes are omitted.
+///
+/// Configuration:
+///
+/// * `start`: The amount of paragraphs to skip from the start of the document.
+/// * `end`: The amount of paragraphs to omit from the back of the document.
+/// * `range`: If `true`, `start` and `end` are treated as a range.
+/// * `filters`: Line based, i.e. lines matching a filter will be skipped.
+pub(super) fn parse(config: &StringParseConfig, input: &[u8]) -> Result<String, ChonkitError> {
+    let start = Instant::now();
+
+    let mut archive = map_err!(ZipArchive::new(Cursor::new(input)));
+
+    let mut content = String::new();
+    {
+        let mut entry = match archive.by_name(CONTENT_ENTRY) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => {
+                return err!(InvalidFile, "ODT document is missing '{CONTENT_ENTRY}'")
+            }
+            Err(e) => return err!(ChonkitErr::Zip(e)),
+        };
+        map_err!(entry.read_to_string(&mut content));
+    }
+
+    let paragraphs = extract_paragraphs(&content)?;
+    let out = select_paragraphs(config, &paragraphs);
+
+    debug!(
+        "Finished processing ODT, took {}ms",
+        Instant::now().duration_since(start).as_millis()
+    );
+
+    Ok(out)
+}
+
+/// Extract the text of all paragraphs and headings in the document body.
+fn extract_paragraphs(content: &str) -> Result<Vec<String>, ChonkitError> {
+    let mut reader = Reader::from_str(content);
+
+    let mut paragraphs = vec![];
+    let mut current = String::new();
+
+    // Paragraphs can be nested, e.g. in frames, their text is kept in the outer paragraph
+    let mut paragraph_depth = 0;
+    let mut skip_depth = 0;
+
+    loop {
+        match map_err!(reader.read_event()) {
+            Event::Start(el)
+                if skip_depth > 0 || SKIPPED_ELEMENTS.contains(&el.name().as_ref()) =>
+            {
+                skip_depth += 1;
+            }
+            Event::End(_) if skip_depth > 0 => skip_depth -= 1,
+            _ if skip_depth > 0 => {}
+
+            Event::Start(el) if is_paragraph(el.name().as_ref()) => paragraph_depth += 1,
+            Event::End(el) if is_paragraph(el.name().as_ref()) => {
+                paragraph_depth -= 1;
+                if paragraph_depth == 0 {
+                    paragraphs.push(std::mem::take(&mut current));
+                }
+            }
+            Event::Empty(el) if paragraph_depth > 0 => match el.name().as_ref() {
+                b"text:s" => {
+                    let count = el
+                        .try_get_attribute("text:c")
+                        .ok()
+                        .flatten()
+                        .and_then(|c| String::from_utf8_lossy(&c.value).parse().ok())
+                        .unwrap_or(1);
+                    current.push_str(&" ".repeat(count));
+                }
+                b"text:tab" => current.push('\t'),
+                b"text:line-break" => current.push('\n'),
+                _ => {}
+            },
+            Event::Text(text) if paragraph_depth > 0 => {
+                let text = map_err!(text.unescape());
+                push_collapsed(&mut current, &text);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(paragraphs)
+}
+
+fn is_paragraph(name: &[u8]) -> bool {
+    matches!(name, b"text:p" | b"text:h")
+}
+
+/// Push the text, collapsing consecutive whitespace to a single space as ODF requires.
+/// Intentional whitespace is encoded with dedicated elements, e.g. `text:s`.
+fn push_collapsed(out: &mut String, text: &str) {
+    for ch in text.chars() {
+        if !ch.is_whitespace() {
+            out.push(ch);
+            continue;
+        }
+
+        if !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{core::document::parser::StringParseConfig, error::ChonkitErr};
+    use std::io::{Cursor, Write};
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    const INPUT: &[u8] = include_bytes!("../../../../test/docs/test.odt");
+
+    #[test]
+    fn extracts_paragraphs() {
+        let out = parse(&StringParseConfig::default(), INPUT).unwrap();
+
+        assert_eq!(
+            "The finest ragu in the galaxy\n\
+             Raguru crafts dishes from his floating kitchen.\n\
+             Guran spices & café crème\n\
+             served  with\ta smile.\n\
+             Ragu\n\
+             42\n",
+            out
+        );
+    }
+
+    #[test]
+    fn applies_config() {
+        let config = StringParseConfig::new(2, 3)
+            .use_range()
+            .with_filter("^served");
+        let out = parse(&config, INPUT).unwrap();
+
+        assert_eq!(
+            "Raguru crafts dishes from his floating kitchen.\nGuran spices & café crème\n",
+            out
+        );
+    }
+
+    #[test]
+    fn rejects_archive_without_content() {
+        let mut archive = ZipWriter::new(Cursor::new(vec![]));
+        archive
+            .start_file("mimetype", SimpleFileOptions::default())
+            .unwrap();
+        archive
+            .write_all(b"application/vnd.oasis.opendocument.text")
+            .unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        let err = parse(&StringParseConfig::default(), &archive).unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidFile(_)));
+    }
+}
@@ -1,14 +1,39 @@
-use super::{DocumentPage, DocumentSection, SectionParseConfig, StringParseConfig};
+use super::{DocumentPage, DocumentSection, ParseInput, SectionParseConfig, StringParseConfig};
 use crate::{
     core::{document::parser::PageRange, model::image::Image},
     error::ChonkitError,
     map_err,
 };
-use pdfium_render::prelude::{PdfPageObject, PdfPageObjectsCommon, Pdfium};
+use pdfium_render::prelude::{
+    PdfDocument, PdfPage, PdfPageObject, PdfPageObjectCommon, PdfPageObjectsCommon, PdfPageText,
+    PdfRect, Pdfium,
+};
 use regex::Regex;
 use std::{collections::HashSet, fmt::Write, time::Instant};
 use tracing::debug;
 
+/// Matches text starting like a figure caption, e.g. `Figure 3: ...` or `Fig. 2 - ...`.
+const CAPTION_PATTERN: &str = r"^(?i)(?:figure|fig\.|image|slika)\s*\d+";
+
+/// The maximum distance in points between an image's edge and its caption, roughly two lines
+/// of regular sized text.
+const CAPTION_DISTANCE: f32 = 24.0;
+
+/// The minimum gap in points between text on the same line for it to be split into table
+/// cells, roughly three spaces of regular sized text.
+const TABLE_CELL_GAP: f32 = 9.0;
+
+/// The minimum gap in points between pieces of text in the same cell for them to be separated
+/// by a space.
+const WORD_GAP: f32 = 1.0;
+
+/// The minimum amount of rows of a table, including its header.
+const TABLE_MIN_ROWS: usize = 3;
+
+/// The maximum length of table cells. Keeps the columns of multi-column layouts, whose lines
+/// are longer, from being mistaken for tables.
+const TABLE_MAX_CELL_LEN: usize = 64;
+
 /// Parser implementation that reads the _whole_ PDF document and extracts its text to a single string.
 ///
 /// Configuration:
@@ -17,15 +42,16 @@ use tracing::debug;
 /// * `end`: The amount of pages to omit from the back of the document.
 /// * `range`: If `true`, `skip_start` and `skip_end` are treated as a range.
 /// * `filters`: Line based, i.e. lines matching a filter will be skipped.
+/// * `extract_tables`: Tables are output in Markdown, see [page_with_tables].
 pub(super) fn parse_to_string(
     config: &StringParseConfig,
-    input: &[u8],
+    input: ParseInput<'_>,
 ) -> Result<String, ChonkitError> {
     let _start = Instant::now();
     let mut _page_count = 0;
 
     let pdfium = Pdfium::default();
-    let input = map_err!(pdfium.load_pdf_from_byte_slice(input, None));
+    let input = load(&pdfium, input)?;
 
     let filters: Vec<Regex> = config
         .filters
@@ -66,7 +92,13 @@ pub(super) fn parse_to_string(
 
         // Process text line by line and apply filters
         let text = map_err!(page.text());
-        'lines: for line in text.all().lines() {
+        let content = if config.extract_tables {
+            page_with_tables(&page, &text).unwrap_or_else(|| text.all())
+        } else {
+            text.all()
+        };
+
+        'lines: for line in content.lines() {
             let line = line.trim();
 
             for filter in filters.iter() {
@@ -91,7 +123,7 @@ pub(super) fn parse_to_string(
 /// a paginated format, i.e. [DocumentSection].
 pub(super) fn parse_to_sections(
     config: &SectionParseConfig,
-    input: &[u8],
+    input: ParseInput<'_>,
 ) -> Result<Vec<DocumentSection>, ChonkitError> {
     let _start = Instant::now();
 
@@ -102,7 +134,7 @@ pub(super) fn parse_to_sections(
         .collect();
 
     let pdfium = Pdfium::default();
-    let input = map_err!(pdfium.load_pdf_from_byte_slice(input, None));
+    let input = load(&pdfium, input)?;
 
     let pages = input.pages();
 
@@ -154,12 +186,24 @@ pub(super) fn parse_to_sections(
     Ok(sections)
 }
 
+/// Load the document from the input. Readers are read from as pages are processed.
+fn load<'a>(pdfium: &'a Pdfium, input: ParseInput<'a>) -> Result<PdfDocument<'a>, ChonkitError> {
+    let document = match input {
+        ParseInput::Bytes(bytes) => pdfium.load_pdf_from_byte_slice(bytes, None),
+        ParseInput::Reader(reader) => pdfium.load_pdf_from_reader(reader, None),
+    };
+
+    Ok(map_err!(document))
+}
+
 /// Implementation that goes through the whole document to extract images.
 ///
 /// The `skip` set contains images already parsed and is usually obtained from the database.
 /// The set should be empty during initial parsing. The set should consist of the page number
 /// and image number combination for a specific document. This metadata is stored with every
 /// image obtained from the document in upstream layers.
+///
+/// Figure captions are used as the initial image descriptions, see [find_caption].
 pub(super) fn parse_images(
     input: &[u8],
     skip: &HashSet<(usize, usize)>,
@@ -170,11 +214,15 @@ pub(super) fn parse_images(
     let pages = input.pages();
     let total_pages = pages.len();
 
+    let caption_pattern = Regex::new(CAPTION_PATTERN).unwrap();
+
     let mut images = vec![];
 
     for (page_num, page) in pages.iter().enumerate() {
         let len_pre = images.len();
 
+        let text = page.text().ok();
+
         let mut image_num = 0;
         for object in page.objects().iter() {
             let PdfPageObject::Image(ref pdf_page_image_object) = object else {
@@ -202,14 +250,20 @@ pub(super) fn parse_images(
                         )
                         .unwrap();
 
-                    images.push(Image::new(
+                    let mut image = Image::new(
                         Some(page_num),
                         Some(image_num),
                         bytes,
                         image::ImageFormat::WebP,
                         width,
                         height,
-                    ));
+                    );
+
+                    if let (Some(text), Ok(bounds)) = (&text, pdf_page_image_object.bounds()) {
+                        image.description = find_caption(text, bounds.to_rect(), &caption_pattern);
+                    }
+
+                    images.push(image);
 
                     image_num += 1;
                 }
@@ -229,3 +283,231 @@ pub(super) fn parse_images(
 
     Ok(images)
 }
+
+/// Find the caption of an image occupying `bounds` on the page.
+///
+/// The heuristic is deliberately conservative: only the text directly below the image, and
+/// then directly above it, within [CAPTION_DISTANCE] and the image's horizontal extent is
+/// considered, and it is only used if it starts like a caption, see [CAPTION_PATTERN].
+/// Surrounding body text is never picked up as a description.
+fn find_caption(text: &PdfPageText, bounds: PdfRect, pattern: &Regex) -> Option<String> {
+    let (bottom, left, top, right) = (
+        bounds.bottom().value,
+        bounds.left().value,
+        bounds.top().value,
+        bounds.right().value,
+    );
+
+    let below = PdfRect::new_from_values(bottom - CAPTION_DISTANCE, left, bottom, right);
+    let above = PdfRect::new_from_values(top, left, top + CAPTION_DISTANCE, right);
+
+    [below, above].into_iter().find_map(|rect| {
+        let caption = text
+            .inside_rect(rect)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        pattern.is_match(&caption).then_some(caption)
+    })
+}
+
+/// Lay out the text of the page line by line, outputting text aligned in rows and columns
+/// as Markdown tables.
+///
+/// The detection is conservative. A table needs at least [TABLE_MIN_ROWS] consecutive lines
+/// with the same amount of cells, at least 2, where every cell overlaps the same column of the
+/// rows before it and the columns never overlap each other. Cells are separated by at least
+/// [TABLE_CELL_GAP] and cannot be longer than [TABLE_MAX_CELL_LEN].
+///
+/// Returns `None` if the page has no tables, in which case its plain text should be used.
+fn page_with_tables(page: &PdfPage, text: &PdfPageText) -> Option<String> {
+    let fragments = page
+        .objects()
+        .iter()
+        .filter_map(|object| {
+            let PdfPageObject::Text(ref object) = object else {
+                return None;
+            };
+
+            let content = text.for_object(object);
+            let content = content.trim();
+
+            if content.is_empty() {
+                return None;
+            }
+
+            let bounds = object.bounds().ok()?.to_rect();
+
+            Some(TextFragment {
+                text: content.to_string(),
+                left: bounds.left().value,
+                right: bounds.right().value,
+                bottom: bounds.bottom().value,
+                top: bounds.top().value,
+            })
+        })
+        .collect();
+
+    let rows: Vec<_> = lines(fragments).into_iter().map(cells).collect();
+
+    let mut out = String::new();
+    let mut found = false;
+    let mut i = 0;
+
+    while i < rows.len() {
+        let count = table_rows(&rows[i..]);
+
+        if count >= TABLE_MIN_ROWS {
+            write_table(&mut out, &rows[i..i + count]);
+            found = true;
+            i += count;
+            continue;
+        }
+
+        let line = rows[i]
+            .iter()
+            .map(|cell| cell.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let _ = writeln!(out, "{line}");
+        i += 1;
+    }
+
+    found.then_some(out)
+}
+
+/// A piece of text on a page and its bounds in points.
+#[derive(Debug)]
+struct TextFragment {
+    text: String,
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+}
+
+impl TextFragment {
+    fn center(&self) -> f32 {
+        (self.bottom + self.top) / 2.
+    }
+
+    /// Whether the vertical centers of the fragments are within half the height of the
+    /// smaller one.
+    fn same_line(&self, other: &TextFragment) -> bool {
+        let height = (self.top - self.bottom).min(other.top - other.bottom);
+        (self.center() - other.center()).abs() <= height / 2.
+    }
+}
+
+/// Group the fragments into lines, top to bottom, each ordered left to right.
+fn lines(mut fragments: Vec<TextFragment>) -> Vec<Vec<TextFragment>> {
+    fragments.sort_by(|a, b| b.center().total_cmp(&a.center()));
+
+    let mut lines: Vec<Vec<TextFragment>> = vec![];
+
+    for fragment in fragments {
+        match lines.last_mut() {
+            Some(line) if line[0].same_line(&fragment) => line.push(fragment),
+            _ => lines.push(vec![fragment]),
+        }
+    }
+
+    for line in lines.iter_mut() {
+        line.sort_by(|a, b| a.left.total_cmp(&b.left));
+    }
+
+    lines
+}
+
+/// Merge the fragments of a line into cells, splitting it wherever the gap between them is
+/// at least [TABLE_CELL_GAP].
+fn cells(line: Vec<TextFragment>) -> Vec<TextFragment> {
+    let mut cells: Vec<TextFragment> = vec![];
+
+    for fragment in line {
+        match cells.last_mut() {
+            Some(cell) if fragment.left - cell.right < TABLE_CELL_GAP => {
+                if fragment.left - cell.right >= WORD_GAP {
+                    cell.text.push(' ');
+                }
+                cell.text.push_str(&fragment.text);
+                cell.right = cell.right.max(fragment.right);
+            }
+            _ => cells.push(fragment),
+        }
+    }
+
+    cells
+}
+
+/// The amount of rows belonging to the table starting at the first row, 0 if it cannot
+/// start one.
+fn table_rows(rows: &[Vec<TextFragment>]) -> usize {
+    let Some(header) = rows.first().filter(|row| is_table_row(row)) else {
+        return 0;
+    };
+
+    let mut columns: Vec<(f32, f32)> = header.iter().map(|cell| (cell.left, cell.right)).collect();
+    let mut count = 1;
+
+    for row in rows[1..].iter() {
+        if row.len() != columns.len() || !is_table_row(row) {
+            break;
+        }
+
+        let aligned = row
+            .iter()
+            .zip(columns.iter())
+            .all(|(cell, (left, right))| cell.left < *right && cell.right > *left);
+
+        if !aligned {
+            break;
+        }
+
+        let extended: Vec<(f32, f32)> = row
+            .iter()
+            .zip(columns.iter())
+            .map(|(cell, (left, right))| (cell.left.min(*left), cell.right.max(*right)))
+            .collect();
+
+        if extended.windows(2).any(|pair| pair[0].1 >= pair[1].0) {
+            break;
+        }
+
+        columns = extended;
+        count += 1;
+    }
+
+    count
+}
+
+/// Whether the cells of the line can form a table row.
+fn is_table_row(row: &[TextFragment]) -> bool {
+    row.len() >= 2
+        && row
+            .iter()
+            .all(|cell| cell.text.chars().count() <= TABLE_MAX_CELL_LEN)
+}
+
+/// Write the rows as a Markdown table, the first one being its header.
+fn write_table(out: &mut String, rows: &[Vec<TextFragment>]) {
+    // Tables have to be separated from preceding text by a blank line
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| cell.text.replace('|', "\\|"))
+            .collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+
+        if i == 0 {
+            let _ = writeln!(out, "|{}", " --- |".repeat(row.len()));
+        }
+    }
+
+    out.push('\n');
+}
@@ -0,0 +1,191 @@
+use crate::{err, error::ChonkitError};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use validify::{field_err, Validate, ValidationError};
+
+/// Built-in patterns for common personally identifiable information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PiiPattern {
+    /// Email addresses, replaced with `[EMAIL]`.
+    Email,
+
+    /// US social security numbers, e.g. `123-45-6789`, replaced with `[SSN]`.
+    Ssn,
+
+    /// Payment card numbers of 13 to 19 digits, optionally grouped with spaces or dashes,
+    /// replaced with `[CARD]`.
+    CreditCard,
+
+    /// Phone numbers of 10 digits, optionally grouped and with a country code, replaced
+    /// with `[PHONE]`.
+    Phone,
+}
+
+impl PiiPattern {
+    /// The order in which built-in patterns are applied. Patterns of longer digit sequences go
+    /// first so that shorter ones do not redact parts of them.
+    const ORDERED: [PiiPattern; 4] = [
+        PiiPattern::Email,
+        PiiPattern::Ssn,
+        PiiPattern::CreditCard,
+        PiiPattern::Phone,
+    ];
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            PiiPattern::Email => r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b",
+            PiiPattern::Ssn => r"\b\d{3}-\d{2}-\d{4}\b",
+            PiiPattern::CreditCard => r"\b\d(?:[ -]?\d){12,18}\b",
+            PiiPattern::Phone => {
+                r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\)|\b\d{3})[ .-]?\d{3}[ .-]?\d{4}\b"
+            }
+        }
+    }
+
+    fn replacement(&self) -> &'static str {
+        match self {
+            PiiPattern::Email => "[EMAIL]",
+            PiiPattern::Ssn => "[SSN]",
+            PiiPattern::CreditCard => "[CARD]",
+            PiiPattern::Phone => "[PHONE]",
+        }
+    }
+}
+
+/// A custom pattern to redact.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionPattern {
+    /// Regular expression matching the content to redact.
+    #[validate(custom(valid_regex))]
+    pub pattern: String,
+
+    /// The text the matches are replaced with, e.g. `[ID]`.
+    pub replacement: String,
+}
+
+/// Redacts sensitive content from the parser output, so that neither the embedded nor the stored
+/// chunks contain it. The stored document is left as is.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionConfig {
+    /// Built-in patterns to redact, each replaced with its own token, e.g. `[EMAIL]`.
+    #[serde(default)]
+    pub builtin: Vec<PiiPattern>,
+
+    /// Custom patterns to redact, applied after the built-in ones.
+    #[serde(default)]
+    #[validate]
+    pub patterns: Vec<RedactionPattern>,
+}
+
+impl RedactionConfig {
+    /// Compile the patterns of the configuration.
+    pub fn redactor(&self) -> Result<Redactor, ChonkitError> {
+        let builtin = PiiPattern::ORDERED
+            .into_iter()
+            .filter(|pattern| self.builtin.contains(pattern))
+            .map(|pattern| (pattern.pattern(), pattern.replacement()));
+
+        let custom = self
+            .patterns
+            .iter()
+            .map(|pattern| (pattern.pattern.as_str(), pattern.replacement.as_str()));
+
+        let mut patterns = vec![];
+
+        for (pattern, replacement) in builtin.chain(custom) {
+            let Ok(regex) = Regex::new(pattern) else {
+                return err!(InvalidParameter, "Invalid redaction pattern '{pattern}'");
+            };
+            patterns.push((regex, replacement.to_string()));
+        }
+
+        Ok(Redactor { patterns })
+    }
+}
+
+/// Compiled [RedactionConfig].
+#[derive(Debug)]
+pub struct Redactor {
+    patterns: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    /// Replace all matches of the patterns in `text`. Replacements are inserted literally.
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+
+        for (regex, replacement) in self.patterns.iter() {
+            if let Cow::Owned(redacted) = regex.replace_all(&text, regex::NoExpand(replacement)) {
+                text = Cow::Owned(redacted);
+            }
+        }
+
+        text
+    }
+}
+
+fn valid_regex(pattern: &str) -> Result<(), ValidationError> {
+    if Regex::new(pattern).is_err() {
+        return Err(field_err!(
+            "redaction_pattern",
+            "redaction pattern must be a valid regular expression"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PiiPattern, RedactionConfig, RedactionPattern};
+    use validify::Validate;
+
+    #[test]
+    fn redacts_builtin_and_custom_patterns() {
+        let config = RedactionConfig {
+            builtin: vec![
+                PiiPattern::Phone,
+                PiiPattern::Email,
+                PiiPattern::Ssn,
+                PiiPattern::CreditCard,
+            ],
+            patterns: vec![RedactionPattern {
+                pattern: r"RAGU-\d+".to_string(),
+                replacement: "[ID]".to_string(),
+            }],
+        };
+
+        let redactor = config.redactor().unwrap();
+
+        let text = "Reach Raguru at raguru.labamba@gura.moon or +1 (555) 123-4567. \
+                    SSN 123-45-6789, card 4111 1111 1111 1111, badge RAGU-42.";
+
+        assert_eq!(
+            "Reach Raguru at [EMAIL] or [PHONE]. SSN [SSN], card [CARD], badge [ID].",
+            redactor.redact(text)
+        );
+
+        let clean = "Raguru stirs the ragu under the twin moons.";
+        assert!(matches!(
+            redactor.redact(clean),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_custom_patterns() {
+        let config = RedactionConfig {
+            builtin: vec![],
+            patterns: vec![RedactionPattern {
+                pattern: "RAGU-(".to_string(),
+                replacement: "[ID]".to_string(),
+            }],
+        };
+
+        assert!(config.validate().is_err());
+        assert!(config.redactor().is_err());
+    }
+}
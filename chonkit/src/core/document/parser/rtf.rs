@@ -0,0 +1,320 @@
+use super::{select_paragraphs, StringParseConfig};
+use crate::{err, error::ChonkitError};
+use std::time::Instant;
+use tracing::debug;
+
+/// RTF documents always start with this group and control word.
+const RTF_HEADER: &[u8] = b"{\\rtf";
+
+/// Destinations whose contents are not part of the document text.
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "fldinst",
+    "listtable",
+    "listoverridetable",
+    "revtbl",
+    "rsidtbl",
+    "generator",
+    "themedata",
+    "colorschememapping",
+    "latentstyles",
+    "datastore",
+    "xmlnstbl",
+    "header",
+    "footer",
+];
+
+/// Parser implementation for RTF documents.
+///
+/// Control words are stripped, leaving only the text. Destinations that do not hold document
+/// text, such as the font table or embedded pictures, are skipped entirely.
+///
+/// Configuration:
+///
+/// * `start`: The amount of paragraphs to skip from the start of the document.
+/// * `end`: The amount of paragraphs to omit from the back of the document.
+/// * `range`: If `true`, `start` and `end` are treated as a range.
+/// * `filters`: Line based, i.e. lines matching a filter will be skipped.
+pub(super) fn parse(config: &StringParseConfig, input: &[u8]) -> Result<String, ChonkitError> {
+    let start = Instant::now();
+
+    if !input.starts_with(RTF_HEADER) {
+        return err!(InvalidFile, "Missing RTF header");
+    }
+
+    let paragraphs = RtfReader::new(input).read();
+    let out = select_paragraphs(config, &paragraphs);
+
+    debug!(
+        "Finished processing RTF, took {}ms",
+        Instant::now().duration_since(start).as_millis()
+    );
+
+    Ok(out)
+}
+
+/// State of an RTF group, inherited by its nested groups.
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    /// Whether the group is a destination whose text is ignored.
+    skip: bool,
+
+    /// The amount of fallback characters following a `\u` control word.
+    unicode_skip: usize,
+}
+
+struct RtfReader<'a> {
+    input: &'a [u8],
+    pos: usize,
+    groups: Vec<Group>,
+
+    /// Fallback characters of the last `\u` control word still left to skip.
+    fallback: usize,
+
+    /// High surrogate of a character outside the BMP, awaiting its low surrogate.
+    surrogate: Option<u16>,
+
+    paragraphs: Vec<String>,
+    current: String,
+}
+
+impl<'a> RtfReader<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            pos: 0,
+            groups: vec![Group {
+                skip: false,
+                unicode_skip: 1,
+            }],
+            fallback: 0,
+            surrogate: None,
+            paragraphs: vec![],
+            current: String::new(),
+        }
+    }
+
+    fn read(mut self) -> Vec<String> {
+        while let Some(&byte) = self.input.get(self.pos) {
+            self.pos += 1;
+
+            match byte {
+                b'{' => {
+                    let group = self.group();
+                    self.groups.push(group);
+                }
+                b'}' => {
+                    self.groups.pop();
+                    self.fallback = 0;
+                    if self.groups.is_empty() {
+                        break;
+                    }
+                }
+                // Line breaks in the source carry no meaning
+                b'\r' | b'\n' => {}
+                b'\\' => self.control(),
+                byte => self.push_byte(byte),
+            }
+        }
+
+        self.end_paragraph();
+        self.paragraphs
+    }
+
+    /// Handle a control word or control symbol, the backslash already being consumed.
+    fn control(&mut self) {
+        let Some(&byte) = self.input.get(self.pos) else {
+            return;
+        };
+
+        if !byte.is_ascii_alphabetic() {
+            self.pos += 1;
+            match byte {
+                b'\'' => {
+                    let hex = self.input.get(self.pos..self.pos + 2);
+                    self.pos += 2;
+                    let byte = hex
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    if let Some(byte) = byte {
+                        self.push_byte(byte);
+                    }
+                }
+                b'\\' | b'{' | b'}' => self.push_byte(byte),
+                b'~' => self.push(' '),
+                b'_' => self.push('-'),
+                b'*' => self.skip_group(),
+                b'\r' | b'\n' => self.end_paragraph(),
+                // Optional hyphens, index subentries, etc.
+                _ => {}
+            }
+            return;
+        }
+
+        let word_start = self.pos;
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(u8::is_ascii_alphabetic)
+        {
+            self.pos += 1;
+        }
+        let word = String::from_utf8_lossy(&self.input[word_start..self.pos]).to_string();
+
+        let param_start = self.pos;
+        if self.input.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        let param = std::str::from_utf8(&self.input[param_start..self.pos])
+            .ok()
+            .and_then(|p| p.parse::<i32>().ok());
+
+        // A single space delimits the control word and is not part of the text
+        if self.input.get(self.pos) == Some(&b' ') {
+            self.pos += 1;
+        }
+
+        match word.as_str() {
+            "par" | "sect" | "page" | "row" => self.end_paragraph(),
+            "line" => self.push('\n'),
+            "tab" | "cell" => self.push('\t'),
+            "emdash" => self.push('—'),
+            "endash" => self.push('–'),
+            "bullet" => self.push('•'),
+            "lquote" => self.push('‘'),
+            "rquote" => self.push('’'),
+            "ldblquote" => self.push('“'),
+            "rdblquote" => self.push('”'),
+            "emspace" | "enspace" | "qmspace" => self.push(' '),
+            "u" => {
+                if let Some(code) = param {
+                    // Values above 32767 are written as negative numbers
+                    self.push_unicode(code as u16);
+                    self.fallback = self.group().unicode_skip;
+                }
+            }
+            "uc" => {
+                if let (Some(n), Some(group)) = (param, self.groups.last_mut()) {
+                    group.unicode_skip = n.max(0) as usize;
+                }
+            }
+            "bin" => {
+                self.pos += param.unwrap_or(0).max(0) as usize;
+            }
+            word if SKIPPED_DESTINATIONS.contains(&word) => self.skip_group(),
+            _ => {}
+        }
+    }
+
+    fn group(&self) -> Group {
+        self.groups.last().copied().unwrap_or(Group {
+            skip: false,
+            unicode_skip: 1,
+        })
+    }
+
+    fn skip_group(&mut self) {
+        if let Some(group) = self.groups.last_mut() {
+            group.skip = true;
+        }
+    }
+
+    /// Push a byte of text in the document's code page, assumed to be Windows-1252.
+    fn push_byte(&mut self, byte: u8) {
+        if self.fallback > 0 {
+            self.fallback -= 1;
+            return;
+        }
+
+        let ch = match byte {
+            0x80 => '€',
+            0x85 => '…',
+            0x91 => '‘',
+            0x92 => '’',
+            0x93 => '“',
+            0x94 => '”',
+            0x95 => '•',
+            0x96 => '–',
+            0x97 => '—',
+            0x99 => '™',
+            byte => char::from(byte),
+        };
+
+        self.push(ch);
+    }
+
+    fn push_unicode(&mut self, code: u16) {
+        let units = match self.surrogate.take() {
+            Some(high) => vec![high, code],
+            None if (0xD800..0xDC00).contains(&code) => {
+                self.surrogate = Some(code);
+                return;
+            }
+            None => vec![code],
+        };
+
+        for ch in char::decode_utf16(units) {
+            self.push(ch.unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+    }
+
+    fn push(&mut self, ch: char) {
+        if !self.group().skip {
+            self.current.push(ch);
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        if self.group().skip {
+            return;
+        }
+        self.paragraphs.push(std::mem::take(&mut self.current));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{core::document::parser::StringParseConfig, error::ChonkitErr};
+
+    const INPUT: &[u8] = include_bytes!("../../../../test/docs/test.rtf");
+
+    #[test]
+    fn extracts_text() {
+        let out = parse(&StringParseConfig::default(), INPUT).unwrap();
+
+        assert_eq!(
+            "The finest ragu in the galaxy\n\
+             Raguru crafts dishes from his floating kitchen.\n\
+             Guran spices – served with café crème\n\
+             and a chef’s smile.\n",
+            out
+        );
+    }
+
+    #[test]
+    fn applies_config() {
+        let config = StringParseConfig::new(1, 0).with_filter("^and");
+        let out = parse(&config, INPUT).unwrap();
+
+        assert_eq!(
+            "Raguru crafts dishes from his floating kitchen.\n\
+             Guran spices – served with café crème\n",
+            out
+        );
+    }
+
+    #[test]
+    fn rejects_non_rtf() {
+        let err = parse(&StringParseConfig::default(), b"Raguru").unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidFile(_)));
+    }
+}
@@ -0,0 +1,111 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Sanitize parsed text for embedding and display.
+///
+/// * Unicode whitespace, e.g. non-breaking spaces, is replaced with regular spaces.
+///   Form feeds and line or paragraph separators are treated as line breaks.
+/// * Control characters other than newlines and tabs are removed, as are zero width
+///   characters and soft hyphens.
+/// * Runs of spaces are collapsed into one and lines are trimmed.
+/// * Consecutive blank lines are collapsed into one, unless `keep_blank_lines` is `true`.
+///
+/// * `normalize_unicode`: If `true`, the text is NFC normalized first.
+pub fn sanitize(input: &str, keep_blank_lines: bool, normalize_unicode: bool) -> String {
+    let input = if normalize_unicode {
+        input.nfc().collect::<String>()
+    } else {
+        input.to_string()
+    };
+
+    let mut out = String::with_capacity(input.len());
+    let mut line = String::new();
+    let mut blank_lines = 0;
+
+    let mut end_line = |line: &mut String, out: &mut String| {
+        let trimmed = line.trim_matches(' ');
+
+        if trimmed.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 && !keep_blank_lines {
+                line.clear();
+                return;
+            }
+        } else {
+            blank_lines = 0;
+        }
+
+        out.push_str(trimmed);
+        out.push('\n');
+        line.clear();
+    };
+
+    for ch in input.chars() {
+        match ch {
+            '\n' | '\u{000B}' | '\u{000C}' | '\u{2028}' | '\u{2029}' => {
+                end_line(&mut line, &mut out)
+            }
+            '\t' => line.push('\t'),
+            '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}' => {}
+            ch if ch.is_whitespace() => {
+                if !line.ends_with(' ') {
+                    line.push(' ');
+                }
+            }
+            ch if ch.is_control() => {}
+            ch => line.push(ch),
+        }
+    }
+
+    // The input did not end with a line break
+    out.push_str(line.trim_matches(' '));
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize;
+
+    #[test]
+    fn normalizes_whitespace() {
+        let input = "Raguru\u{00A0}cooks   in\u{2003}his  \u{00A0} kitchen.  \n\tGura\u{200B} spices\u{0007}\r\n";
+
+        assert_eq!(
+            "Raguru cooks in his kitchen.\n\tGura spices\n",
+            sanitize(input, false, false)
+        );
+    }
+
+    #[test]
+    fn form_feeds_break_lines() {
+        let input = "End of page one.\u{000C}Start of page two.";
+
+        assert_eq!(
+            "End of page one.\nStart of page two.",
+            sanitize(input, false, false)
+        );
+    }
+
+    #[test]
+    fn collapses_blank_lines() {
+        let input = "First paragraph.\n\n  \n\u{00A0}\nSecond paragraph.\n";
+
+        assert_eq!(
+            "First paragraph.\n\nSecond paragraph.\n",
+            sanitize(input, false, false)
+        );
+        assert_eq!(
+            "First paragraph.\n\n\n\nSecond paragraph.\n",
+            sanitize(input, true, false)
+        );
+    }
+
+    #[test]
+    fn normalizes_unicode() {
+        // 'e' followed by a combining acute accent
+        let input = "Cafe\u{0301} de Gura";
+
+        assert_eq!("Cafe\u{0301} de Gura", sanitize(input, false, false));
+        assert_eq!("Caf\u{00E9} de Gura", sanitize(input, false, true));
+    }
+}
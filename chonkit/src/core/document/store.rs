@@ -1,4 +1,4 @@
-use super::{parser::ParseConfig, sha256, DocumentType};
+use super::{link_document_source, parser::ParseConfig, DocumentHashing, DocumentType};
 use crate::{
     core::{
         chunk::ChunkConfig,
@@ -10,6 +10,7 @@ use crate::{
     map_err,
 };
 use chrono::{DateTime, Utc};
+use tokio::io::{AsyncRead, AsyncSeek};
 
 pub mod external;
 
@@ -56,6 +57,11 @@ pub struct LocalPath(pub String);
 #[derive(Debug)]
 pub struct ExternalPath(pub String);
 
+/// Seekable reader over the content of a document, see [DocumentStorage::read_stream].
+pub trait DocumentReader: AsyncRead + AsyncSeek + Send + Unpin {}
+
+impl<T> DocumentReader for T where T: AsyncRead + AsyncSeek + Send + Unpin {}
+
 /// Use on adapters that use the file system to store and read documents.
 /// Serves to differentiate document sources.
 #[async_trait::async_trait]
@@ -72,6 +78,17 @@ pub trait DocumentStorage: Identity {
     /// * `path`: The unique path of the document. Implementation specific.
     async fn read(&self, path: &str) -> Result<Vec<u8>, ChonkitError>;
 
+    /// Open a reader over the content of the document located on `path`, allowing large
+    /// documents to be processed without loading them into memory whole.
+    /// Prefer [DocumentStorage::read] for small files.
+    ///
+    /// The default implementation reads the whole document into memory.
+    ///
+    /// * `path`: The unique path of the document. Implementation specific.
+    async fn read_stream(&self, path: &str) -> Result<Box<dyn DocumentReader>, ChonkitError> {
+        Ok(Box::new(std::io::Cursor::new(self.read(path).await?)))
+    }
+
     /// List all files in the storage.
     async fn list_files(&self) -> Result<Vec<DocumentFile<LocalPath>>, ChonkitError>;
 
@@ -94,7 +111,9 @@ pub trait DocumentStorage: Identity {
     /// repository.
     ///
     /// * `repository`: The repository to sync.
-    async fn sync(&self, repo: &Repository) -> Result<(), ChonkitError> {
+    /// * `hashing`: How to hash the contents of new files and whether to link them to existing
+    ///   documents with the same content.
+    async fn sync(&self, repo: &Repository, hashing: DocumentHashing) -> Result<(), ChonkitError> {
         // List all documents from repository
         let document_paths = repo.list_all_document_paths(self.id()).await?;
 
@@ -112,6 +131,19 @@ pub trait DocumentStorage: Identity {
             }
         }
 
+        // Unlink any sources that no longer exist on the file system
+        for (id, path) in repo.list_all_document_source_paths(self.id()).await? {
+            if let Err(e) = tokio::fs::metadata(&path).await {
+                match e.kind() {
+                    std::io::ErrorKind::NotFound => {
+                        tracing::info!("{path} - not found in storage, unlinking from {id}");
+                        repo.remove_document_source(self.id(), &path).await?;
+                    }
+                    _ => return map_err!(Err(e)),
+                }
+            }
+        }
+
         // List all files from storage
         let files = self.list_files().await?;
 
@@ -128,7 +160,12 @@ pub trait DocumentStorage: Identity {
                     tracing::debug!("{id} - attempting to upsert configuration");
 
                     if let Err(e) = repo
-                        .upsert_document_chunk_config(id, None, ChunkConfig::snapping_default())
+                        .upsert_document_chunk_config(
+                            id,
+                            None,
+                            ChunkConfig::snapping_default(),
+                            None,
+                        )
                         .await
                     {
                         tracing::error!("{id} - error updating chunk config: {e}");
@@ -137,7 +174,7 @@ pub trait DocumentStorage: Identity {
                     tracing::info!("{id} - chunker successfully updated");
 
                     if let Err(e) = repo
-                        .upsert_document_parse_config(id, None, ParseConfig::default())
+                        .upsert_document_parse_config(id, None, ParseConfig::default(), None)
                         .await
                     {
                         tracing::error!("{id} - error updating parsing config: {e}");
@@ -148,7 +185,18 @@ pub trait DocumentStorage: Identity {
                 continue;
             }
 
-            let hash = sha256(&map_err!(tokio::fs::read(&file.path.0).await));
+            let hash = hashing
+                .algorithm
+                .hash(&map_err!(tokio::fs::read(&file.path.0).await));
+
+            if hashing.link_duplicates {
+                if let Some(existing) =
+                    link_document_source(repo, &hash, self.id(), &file.path.0).await?
+                {
+                    tracing::info!("{} - '{}' already exists", existing.id, file.name);
+                    continue;
+                }
+            }
 
             let result = repo
                 .transaction(|tx| {
@@ -1,7 +1,21 @@
-use super::provider::Identity;
+use super::{
+    model::embedding::{EmbeddingReportKind, EmbeddingRequest},
+    provider::Identity,
+    token::Tokenizer,
+    vector::normalize_vector,
+};
 use crate::error::ChonkitError;
 use chonkit_embedders::EmbeddingModel;
+use futures_util::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, future::Future, sync::Arc, time::Instant};
+use tokio::sync::{mpsc::UnboundedSender, RwLock};
+
+/// Input embedded when probing models for their dimensions.
+const PROBE_INPUT: &str = "probe";
+
+/// Used as the maximum input tokens of probed models since it cannot be obtained by probing.
+const PROBED_MAX_INPUT_TOKENS: usize = 512;
 
 /// Operations for embeddings.
 #[async_trait::async_trait]
@@ -25,6 +39,27 @@ pub trait Embedder: Identity {
     /// List all available models in the registry.
     async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError>;
 
+    /// Get multiple vectors for every element in `content`, as produced by late-interaction
+    /// (ColBERT-style) models which output a vector per token. Used with collections created
+    /// with [VectorDb::create_multivector_collection](super::vector::VectorDb::create_multivector_collection).
+    ///
+    /// Unsupported unless implemented by the embedder.
+    ///
+    /// * `content`: The text to embed.
+    /// * `model`: The embedding model to use.
+    #[cfg(feature = "multivector")]
+    async fn embed_text_multivector(
+        &self,
+        _content: &[&str],
+        _model: &str,
+    ) -> Result<MultiVectorEmbeddings, ChonkitError> {
+        crate::err!(
+            OperationUnsupported,
+            "{} does not support multi-vector embeddings",
+            self.id()
+        )
+    }
+
     /// Return the size (dimensions) of the given model's embedding space if it is supported by the embedding registry.
     ///
     /// * `model`: The model whose size to return.
@@ -37,6 +72,388 @@ pub trait Embedder: Identity {
     }
 }
 
+/// Wraps an embedder and L2 normalizes all the vectors it outputs.
+///
+/// Some providers return vectors that are not unit normalized which skews cosine distances
+/// in the vector databases. Embedders are wrapped with this at startup if configured to do so,
+/// which ensures both stored and query vectors are normalized.
+pub struct NormalizedEmbedder {
+    inner: Arc<dyn Embedder + Send + Sync>,
+}
+
+impl NormalizedEmbedder {
+    pub fn new(inner: Arc<dyn Embedder + Send + Sync>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Identity for NormalizedEmbedder {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+}
+
+#[async_trait::async_trait]
+impl Embedder for NormalizedEmbedder {
+    async fn embed_text(&self, content: &[&str], model: &str) -> Result<Embeddings, ChonkitError> {
+        let mut embeddings = self.inner.embed_text(content, model).await?;
+        embeddings.normalize();
+        Ok(embeddings)
+    }
+
+    async fn embed_image(
+        &self,
+        system: Option<&str>,
+        text: Option<&str>,
+        image: &str,
+        model: &str,
+    ) -> Result<Embeddings, ChonkitError> {
+        let mut embeddings = self.inner.embed_image(system, text, image, model).await?;
+        embeddings.normalize();
+        Ok(embeddings)
+    }
+
+    async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+        self.inner.list_embedding_models().await
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn embed_text_multivector(
+        &self,
+        content: &[&str],
+        model: &str,
+    ) -> Result<MultiVectorEmbeddings, ChonkitError> {
+        self.inner.embed_text_multivector(content, model).await
+    }
+
+    async fn model_details(&self, model: &str) -> Result<Option<EmbeddingModel>, ChonkitError> {
+        self.inner.model_details(model).await
+    }
+}
+
+/// Wraps an embedder and splits the content it embeds into sub-batches of `batch_size`,
+/// running at most `concurrency` of them at the same time.
+///
+/// Useful for latency bound remote providers, where a single large request takes considerably longer
+/// than multiple smaller concurrent ones. The output order is always the same as the input's.
+/// Keeping `concurrency` low ensures we do not run into provider rate limits.
+pub struct ConcurrentEmbedder {
+    inner: Arc<dyn Embedder + Send + Sync>,
+    batch_size: usize,
+    concurrency: usize,
+}
+
+impl ConcurrentEmbedder {
+    /// Both `batch_size` and `concurrency` are clamped to at least 1.
+    pub fn new(
+        inner: Arc<dyn Embedder + Send + Sync>,
+        batch_size: usize,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            inner,
+            batch_size: batch_size.max(1),
+            concurrency: concurrency.max(1),
+        }
+    }
+}
+
+impl Identity for ConcurrentEmbedder {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+}
+
+#[async_trait::async_trait]
+impl Embedder for ConcurrentEmbedder {
+    async fn embed_text(&self, content: &[&str], model: &str) -> Result<Embeddings, ChonkitError> {
+        if content.len() <= self.batch_size {
+            return self.inner.embed_text(content, model).await;
+        }
+
+        tracing::debug!(
+            "{} - embedding {} chunks in batches of {} (concurrency: {})",
+            self.id(),
+            content.len(),
+            self.batch_size,
+            self.concurrency
+        );
+
+        let requests = content
+            .chunks(self.batch_size)
+            .map(|batch| self.inner.embed_text(batch, model))
+            .collect::<Vec<_>>();
+
+        // `buffered` preserves the order of the batches.
+        let batches: Vec<Embeddings> = futures_util::stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+
+        let mut embeddings = Vec::with_capacity(content.len());
+        let mut tokens_used = None;
+
+        for batch in batches {
+            embeddings.extend(batch.embeddings);
+            if let Some(tokens) = batch.tokens_used {
+                tokens_used = Some(tokens_used.unwrap_or(0) + tokens);
+            }
+        }
+
+        Ok(Embeddings::new(embeddings, tokens_used))
+    }
+
+    async fn embed_image(
+        &self,
+        system: Option<&str>,
+        text: Option<&str>,
+        image: &str,
+        model: &str,
+    ) -> Result<Embeddings, ChonkitError> {
+        self.inner.embed_image(system, text, image, model).await
+    }
+
+    async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+        self.inner.list_embedding_models().await
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn embed_text_multivector(
+        &self,
+        content: &[&str],
+        model: &str,
+    ) -> Result<MultiVectorEmbeddings, ChonkitError> {
+        self.inner.embed_text_multivector(content, model).await
+    }
+
+    async fn model_details(&self, model: &str) -> Result<Option<EmbeddingModel>, ChonkitError> {
+        self.inner.model_details(model).await
+    }
+}
+
+/// Wraps an embedder and obtains the details of models it does not list by embedding
+/// a single token with them and measuring the length of the returned vector.
+///
+/// Intended for providers serving arbitrary models, e.g. vLLM, whose dimensions cannot be
+/// known in advance. Probed models are cached and are never considered multimodal.
+pub struct ProbingEmbedder {
+    inner: Arc<dyn Embedder + Send + Sync>,
+
+    /// Maps model names to their probed details.
+    probed: RwLock<HashMap<String, EmbeddingModel>>,
+}
+
+impl ProbingEmbedder {
+    pub fn new(inner: Arc<dyn Embedder + Send + Sync>) -> Self {
+        Self {
+            inner,
+            probed: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Embed [PROBE_INPUT] with the model and return its details if it produced a vector.
+    async fn probe(&self, model: &str) -> Option<EmbeddingModel> {
+        let embeddings = match self.inner.embed_text(&[PROBE_INPUT], model).await {
+            Ok(embeddings) => embeddings,
+            Err(e) => {
+                tracing::warn!("{} - unable to probe model '{model}': {e}", self.id());
+                return None;
+            }
+        };
+
+        let size = embeddings.embeddings.first().map(Vec::len)?;
+
+        if size == 0 {
+            tracing::warn!("{} - model '{model}' returned an empty vector", self.id());
+            return None;
+        }
+
+        tracing::info!("{} - probed model '{model}' (size: {size})", self.id());
+
+        Some(EmbeddingModel {
+            name: model.to_string(),
+            size,
+            provider: self.id().to_string(),
+            multimodal: false,
+            max_input_tokens: PROBED_MAX_INPUT_TOKENS,
+        })
+    }
+}
+
+impl Identity for ProbingEmbedder {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+}
+
+#[async_trait::async_trait]
+impl Embedder for ProbingEmbedder {
+    async fn embed_text(&self, content: &[&str], model: &str) -> Result<Embeddings, ChonkitError> {
+        self.inner.embed_text(content, model).await
+    }
+
+    async fn embed_image(
+        &self,
+        system: Option<&str>,
+        text: Option<&str>,
+        image: &str,
+        model: &str,
+    ) -> Result<Embeddings, ChonkitError> {
+        self.inner.embed_image(system, text, image, model).await
+    }
+
+    async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+        self.inner.list_embedding_models().await
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn embed_text_multivector(
+        &self,
+        content: &[&str],
+        model: &str,
+    ) -> Result<MultiVectorEmbeddings, ChonkitError> {
+        self.inner.embed_text_multivector(content, model).await
+    }
+
+    async fn model_details(&self, model: &str) -> Result<Option<EmbeddingModel>, ChonkitError> {
+        if let Some(details) = self.inner.model_details(model).await? {
+            return Ok(Some(details));
+        }
+
+        if let Some(details) = self.probed.read().await.get(model) {
+            return Ok(Some(details.clone()));
+        }
+
+        let Some(details) = self.probe(model).await else {
+            return Ok(None);
+        };
+
+        self.probed
+            .write()
+            .await
+            .insert(model.to_string(), details.clone());
+
+        Ok(Some(details))
+    }
+}
+
+/// Wraps an embedder and records every request made with it as an [EmbeddingRequest].
+///
+/// Records are sent to `audit` instead of being persisted here so requests never wait on the
+/// database. Embedders are wrapped with this at startup, before any batching, if configured to do so.
+pub struct AuditedEmbedder {
+    inner: Arc<dyn Embedder + Send + Sync>,
+
+    /// Used for estimating the input tokens of text requests.
+    tokenizer: Tokenizer,
+
+    audit: UnboundedSender<EmbeddingRequest>,
+}
+
+impl AuditedEmbedder {
+    pub fn new(
+        inner: Arc<dyn Embedder + Send + Sync>,
+        tokenizer: Tokenizer,
+        audit: UnboundedSender<EmbeddingRequest>,
+    ) -> Self {
+        Self {
+            inner,
+            tokenizer,
+            audit,
+        }
+    }
+
+    /// Time the `request` and send its record to the audit.
+    async fn audited(
+        &self,
+        kind: EmbeddingReportKind,
+        model: &str,
+        inputs: usize,
+        input_tokens: Option<usize>,
+        request: impl Future<Output = Result<Embeddings, ChonkitError>>,
+    ) -> Result<Embeddings, ChonkitError> {
+        let requested_at = chrono::Utc::now();
+        let start = Instant::now();
+
+        let result = request.await;
+
+        let record = EmbeddingRequest {
+            provider: self.id().to_string(),
+            model: model.to_string(),
+            kind,
+            inputs,
+            input_tokens,
+            response_tokens: result.as_ref().ok().and_then(|e| e.tokens_used),
+            latency_ms: start.elapsed().as_millis() as u64,
+            error: result.as_ref().err().map(ToString::to_string),
+            requested_at,
+        };
+
+        if self.audit.send(record).is_err() {
+            tracing::warn!("{} - embedding request audit is closed", self.id());
+        }
+
+        result
+    }
+}
+
+impl Identity for AuditedEmbedder {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+}
+
+#[async_trait::async_trait]
+impl Embedder for AuditedEmbedder {
+    async fn embed_text(&self, content: &[&str], model: &str) -> Result<Embeddings, ChonkitError> {
+        let tokenizer = self.tokenizer.for_model(model);
+        let input_tokens = content.iter().map(|c| tokenizer.count(c).model).sum();
+
+        self.audited(
+            EmbeddingReportKind::Text,
+            model,
+            content.len(),
+            Some(input_tokens),
+            self.inner.embed_text(content, model),
+        )
+        .await
+    }
+
+    async fn embed_image(
+        &self,
+        system: Option<&str>,
+        text: Option<&str>,
+        image: &str,
+        model: &str,
+    ) -> Result<Embeddings, ChonkitError> {
+        self.audited(
+            EmbeddingReportKind::Image,
+            model,
+            1,
+            None,
+            self.inner.embed_image(system, text, image, model),
+        )
+        .await
+    }
+
+    async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+        self.inner.list_embedding_models().await
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn embed_text_multivector(
+        &self,
+        content: &[&str],
+        model: &str,
+    ) -> Result<MultiVectorEmbeddings, ChonkitError> {
+        self.inner.embed_text_multivector(content, model).await
+    }
+
+    async fn model_details(&self, model: &str) -> Result<Option<EmbeddingModel>, ChonkitError> {
+        self.inner.model_details(model).await
+    }
+}
+
 /// The result of embedding chunks.
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Embeddings {
@@ -59,6 +476,35 @@ impl Embeddings {
             source: EmbeddingSource::Model,
         }
     }
+
+    /// L2 normalize all the vectors in place.
+    pub fn normalize(&mut self) {
+        for vector in self.embeddings.iter_mut() {
+            normalize_vector(vector);
+        }
+    }
+}
+
+/// The result of embedding chunks with a multi-vector model.
+#[cfg(feature = "multivector")]
+#[derive(Debug)]
+pub struct MultiVectorEmbeddings {
+    /// The vectors of every chunk, 1:1 with the original chunks. The amount of vectors per
+    /// chunk varies, usually with the amount of tokens in it.
+    pub embeddings: Vec<Vec<Vec<f64>>>,
+
+    /// Amount of tokens spent on the embedding, if applicable.
+    pub tokens_used: Option<usize>,
+}
+
+#[cfg(feature = "multivector")]
+impl MultiVectorEmbeddings {
+    pub fn new(embeddings: Vec<Vec<Vec<f64>>>, tokens_used: Option<usize>) -> Self {
+        Self {
+            embeddings,
+            tokens_used,
+        }
+    }
 }
 
 /// Represents the origin of embeddings.
@@ -97,3 +543,349 @@ impl std::fmt::Display for EmbeddingSource {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AuditedEmbedder, ConcurrentEmbedder, Embedder, Embeddings, NormalizedEmbedder,
+        ProbingEmbedder,
+    };
+    use crate::{
+        app::test::TestEmbedder,
+        core::{model::embedding::EmbeddingReportKind, provider::Identity, token::Tokenizer},
+        err,
+        error::ChonkitError,
+    };
+    use chonkit_embedders::EmbeddingModel;
+    use std::{sync::Arc, time::Duration};
+
+    struct StubEmbedder;
+
+    impl Identity for StubEmbedder {
+        fn id(&self) -> &'static str {
+            "stub"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Embedder for StubEmbedder {
+        async fn embed_text(
+            &self,
+            content: &[&str],
+            _model: &str,
+        ) -> Result<Embeddings, ChonkitError> {
+            Ok(Embeddings::new(vec![vec![3.0, 4.0]; content.len()], None))
+        }
+
+        async fn embed_image(
+            &self,
+            _system: Option<&str>,
+            _text: Option<&str>,
+            _image: &str,
+            _model: &str,
+        ) -> Result<Embeddings, ChonkitError> {
+            Ok(Embeddings::new(vec![vec![0.0, 2.0]], None))
+        }
+
+        async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn normalized_embedder_normalizes_output() {
+        let embedder = NormalizedEmbedder::new(Arc::new(StubEmbedder));
+
+        assert_eq!("stub", embedder.id());
+
+        let embeddings = embedder.embed_text(&["foo", "bar"], "model").await.unwrap();
+        assert_eq!(vec![vec![0.6, 0.8]; 2], embeddings.embeddings);
+
+        let embeddings = embedder
+            .embed_image(None, None, "image", "model")
+            .await
+            .unwrap();
+        assert_eq!(vec![vec![0.0, 1.0]], embeddings.embeddings);
+    }
+
+    /// Sleeps for a fixed amount of time per call and embeds every input as a single element vector
+    /// containing the input parsed as a number.
+    struct SleepyEmbedder(Duration);
+
+    impl Identity for SleepyEmbedder {
+        fn id(&self) -> &'static str {
+            "sleepy"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Embedder for SleepyEmbedder {
+        async fn embed_text(
+            &self,
+            content: &[&str],
+            _model: &str,
+        ) -> Result<Embeddings, ChonkitError> {
+            tokio::time::sleep(self.0).await;
+            Ok(Embeddings::new(
+                content.iter().map(|c| vec![c.parse().unwrap()]).collect(),
+                Some(content.len()),
+            ))
+        }
+
+        async fn embed_image(
+            &self,
+            _system: Option<&str>,
+            _text: Option<&str>,
+            _image: &str,
+            _model: &str,
+        ) -> Result<Embeddings, ChonkitError> {
+            err!(OperationUnsupported, "{} does not embed images", self.id())
+        }
+
+        async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_embedder_preserves_order_and_reduces_wall_time() {
+        let delay = Duration::from_millis(100);
+        let content = (0..40).map(|i| i.to_string()).collect::<Vec<_>>();
+        let content = content.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+        // 8 batches of 5
+        let sequential = ConcurrentEmbedder::new(Arc::new(SleepyEmbedder(delay)), 5, 1);
+        let concurrent = ConcurrentEmbedder::new(Arc::new(SleepyEmbedder(delay)), 5, 8);
+
+        let start = std::time::Instant::now();
+        let expected = sequential.embed_text(&content, "model").await.unwrap();
+        let sequential_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let embeddings = concurrent.embed_text(&content, "model").await.unwrap();
+        let concurrent_time = start.elapsed();
+
+        assert!(sequential_time >= delay * 8);
+        assert!(concurrent_time < delay * 4);
+
+        assert_eq!(expected.embeddings, embeddings.embeddings);
+        assert_eq!(Some(40), embeddings.tokens_used);
+
+        for (i, embedding) in embeddings.embeddings.iter().enumerate() {
+            assert_eq!(vec![i as f64], *embedding);
+        }
+    }
+
+    #[tokio::test]
+    async fn audited_embedder_records_every_provider_request() {
+        let delay = Duration::from_millis(20);
+        let content = (0..10).map(|i| i.to_string()).collect::<Vec<_>>();
+        let content = content.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let audited = AuditedEmbedder::new(Arc::new(SleepyEmbedder(delay)), Tokenizer::new(), tx);
+
+        // Auditing happens before batching, so every batch is a separate request
+        let embedder = ConcurrentEmbedder::new(Arc::new(audited), 5, 2);
+
+        embedder.embed_text(&content, "model").await.unwrap();
+        embedder
+            .embed_image(None, None, "image", "model")
+            .await
+            .unwrap_err();
+
+        let mut requests = vec![];
+        while let Ok(request) = rx.try_recv() {
+            requests.push(request);
+        }
+
+        assert_eq!(3, requests.len());
+
+        for request in &requests[..2] {
+            assert_eq!("sleepy", request.provider);
+            assert_eq!("model", request.model);
+            assert_eq!(EmbeddingReportKind::Text, request.kind);
+            assert_eq!(5, request.inputs);
+            assert_eq!(Some(5), request.input_tokens);
+            assert_eq!(Some(5), request.response_tokens);
+            assert!(request.latency_ms >= delay.as_millis() as u64);
+            assert!(request.error.is_none());
+        }
+
+        let image = &requests[2];
+        assert_eq!(EmbeddingReportKind::Image, image.kind);
+        assert_eq!(None, image.input_tokens);
+        assert!(image.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn probing_embedder_probes_unlisted_models_once() {
+        let inner = Arc::new(
+            TestEmbedder::new("unlisted", 3)
+                .with_listed_model("listed")
+                .with_rejected_model("missing"),
+        );
+        let embedder = ProbingEmbedder::new(inner.clone());
+
+        let listed = embedder.model_details("listed").await.unwrap().unwrap();
+        assert_eq!("listed", listed.name);
+        assert_eq!(0, inner.calls());
+
+        for _ in 0..3 {
+            let probed = embedder.model_details("local").await.unwrap().unwrap();
+            assert_eq!("local", probed.name);
+            assert_eq!(3, probed.size);
+            assert!(!probed.multimodal);
+        }
+
+        assert_eq!(1, inner.calls());
+
+        assert!(embedder.model_details("missing").await.unwrap().is_none());
+    }
+
+    /// Words multi-vector embedded as one-hot vectors by [MultiVectorStubEmbedder]. Words outside
+    /// of it share the last dimension.
+    #[cfg(feature = "multivector")]
+    const VOCABULARY: &[&str] = &["ragu", "raguru", "stardust", "kitchen", "moon", "salt"];
+
+    /// Embeds every word of the content as its own vector, like late-interaction models do
+    /// with tokens.
+    #[cfg(feature = "multivector")]
+    struct MultiVectorStubEmbedder;
+
+    #[cfg(feature = "multivector")]
+    impl Identity for MultiVectorStubEmbedder {
+        fn id(&self) -> &'static str {
+            "multivector"
+        }
+    }
+
+    #[cfg(feature = "multivector")]
+    #[async_trait::async_trait]
+    impl Embedder for MultiVectorStubEmbedder {
+        async fn embed_text(
+            &self,
+            _content: &[&str],
+            _model: &str,
+        ) -> Result<Embeddings, ChonkitError> {
+            err!(
+                OperationUnsupported,
+                "{} only embeds multi-vectors",
+                self.id()
+            )
+        }
+
+        async fn embed_image(
+            &self,
+            _system: Option<&str>,
+            _text: Option<&str>,
+            _image: &str,
+            _model: &str,
+        ) -> Result<Embeddings, ChonkitError> {
+            err!(OperationUnsupported, "{} does not embed images", self.id())
+        }
+
+        async fn list_embedding_models(&self) -> Result<Vec<EmbeddingModel>, ChonkitError> {
+            Ok(vec![])
+        }
+
+        async fn embed_text_multivector(
+            &self,
+            content: &[&str],
+            _model: &str,
+        ) -> Result<super::MultiVectorEmbeddings, ChonkitError> {
+            let embed_word = |word: &str| {
+                let word = word.to_lowercase();
+                let i = VOCABULARY
+                    .iter()
+                    .position(|w| *w == word)
+                    .unwrap_or(VOCABULARY.len());
+                let mut vector = vec![0.0; VOCABULARY.len() + 1];
+                vector[i] = 1.0;
+                vector
+            };
+
+            Ok(super::MultiVectorEmbeddings::new(
+                content
+                    .iter()
+                    .map(|c| c.split_whitespace().map(embed_word).collect())
+                    .collect(),
+                None,
+            ))
+        }
+    }
+
+    /// Sum of the best cosine similarities of every query vector with any of the document's.
+    #[cfg(feature = "multivector")]
+    fn max_sim(query: &[Vec<f64>], document: &[Vec<f64>]) -> f64 {
+        let cosine = |a: &[f64], b: &[f64]| {
+            let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+            let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            dot / (norm(a) * norm(b))
+        };
+
+        query
+            .iter()
+            .map(|q| {
+                document
+                    .iter()
+                    .map(|d| cosine(q, d))
+                    .fold(f64::MIN, f64::max)
+            })
+            .sum()
+    }
+
+    #[cfg(feature = "multivector")]
+    #[tokio::test]
+    async fn multivector_embeddings_rank_with_max_sim() {
+        // Decorators pass multi-vector embeddings through
+        let embedder = ConcurrentEmbedder::new(
+            Arc::new(NormalizedEmbedder::new(Arc::new(MultiVectorStubEmbedder))),
+            2,
+            2,
+        );
+
+        let documents = [
+            "Raguru seasons his ragu with stardust",
+            "The kitchen floats above the moon",
+            "A pinch of salt from the moon",
+        ];
+
+        let embeddings = embedder
+            .embed_text_multivector(&documents, "model")
+            .await
+            .unwrap()
+            .embeddings;
+
+        assert_eq!(documents.len(), embeddings.len());
+        for (document, vectors) in documents.iter().zip(embeddings.iter()) {
+            assert_eq!(document.split_whitespace().count(), vectors.len());
+        }
+
+        let query = embedder
+            .embed_text_multivector(&["salt moon"], "model")
+            .await
+            .unwrap()
+            .embeddings
+            .remove(0);
+
+        let mut ranked = embeddings
+            .iter()
+            .enumerate()
+            .map(|(i, document)| (i, max_sim(&query, document)))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        assert_eq!(vec![(2, 2.0), (1, 1.0), (0, 0.0)], ranked);
+
+        // Single vector embedders do not produce multi-vectors
+        let result = NormalizedEmbedder::new(Arc::new(StubEmbedder))
+            .embed_text_multivector(&["salt"], "model")
+            .await;
+
+        assert!(matches!(
+            result.unwrap_err().error,
+            crate::error::ChonkitErr::OperationUnsupported(_)
+        ));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::error::ChonkitError;
+use futures_util::{stream::BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+/// Instructions for generating a hypothetical answer to a search query.
+const HYDE_INSTRUCTIONS: &str =
+    "Write a short passage that answers the question given by the user, \
+as if it were taken from a document containing the answer. Respond only with the passage.";
+
+/// Instructions for generating terms related to a search query.
+const EXPAND_INSTRUCTIONS: &str = "List synonyms and closely related terms for the key terms \
+in the search query given by the user. Respond only with the terms, separated by spaces.";
+
+/// Instructions for answering a question from retrieved document chunks.
+const ANSWER_INSTRUCTIONS: &str = "Answer the question given by the user using only the \
+numbered context passages. Refer to the passages you use by their number, e.g. [1]. \
+If the context does not contain the answer, say that you do not know.";
+
+/// Instructions for summarizing a document.
+const SUMMARY_INSTRUCTIONS: &str = "Summarize the document given by the user in a single \
+paragraph, covering all of its main topics. Respond only with the summary.";
+
+/// The maximum amount of characters of a document given to the LLM when summarizing it.
+const SUMMARY_INPUT_CHARS: usize = 32_000;
+
+/// Text completion with instruction following language models.
+#[async_trait::async_trait]
+pub trait Llm {
+    /// Complete the `prompt` according to the `instructions`.
+    ///
+    /// * `instructions`: The system prompt.
+    /// * `prompt`: The user prompt.
+    async fn complete(&self, instructions: &str, prompt: &str) -> Result<String, ChonkitError>;
+
+    /// Same as [complete](Self::complete), but the completion is streamed as it is generated.
+    ///
+    /// By default, the whole completion is emitted at once.
+    async fn complete_stream(
+        &self,
+        instructions: &str,
+        prompt: &str,
+    ) -> Result<BoxStream<'static, Result<String, ChonkitError>>, ChonkitError> {
+        let completion = self.complete(instructions, prompt).await?;
+        Ok(futures_util::stream::once(async { Ok(completion) }).boxed())
+    }
+}
+
+/// Stream an answer to the `query` from the `context` passages, given in order of relevance.
+/// The passages are numbered starting from 1 so the answer can cite them.
+pub async fn answer(
+    llm: &(dyn Llm + Send + Sync),
+    query: &str,
+    context: &[&str],
+) -> Result<BoxStream<'static, Result<String, ChonkitError>>, ChonkitError> {
+    llm.complete_stream(ANSWER_INSTRUCTIONS, &answer_prompt(query, context))
+        .await
+}
+
+/// Summarize the `content` of a document. Only the start of long documents is given to the
+/// LLM, since they would exceed its context.
+pub async fn summarize(
+    llm: &(dyn Llm + Send + Sync),
+    content: &str,
+) -> Result<String, ChonkitError> {
+    let end = content
+        .char_indices()
+        .nth(SUMMARY_INPUT_CHARS)
+        .map_or(content.len(), |(i, _)| i);
+
+    let summary = llm.complete(SUMMARY_INSTRUCTIONS, &content[..end]).await?;
+
+    Ok(summary.trim().to_string())
+}
+
+fn answer_prompt(query: &str, context: &[&str]) -> String {
+    let mut prompt = String::from("Context:\n");
+
+    for (i, passage) in context.iter().enumerate() {
+        prompt.push_str(&format!("\n[{}] {}\n", i + 1, passage.trim()));
+    }
+
+    prompt.push_str(&format!("\nQuestion: {query}"));
+
+    prompt
+}
+
+/// How a search query is rewritten before it is embedded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryRewrite {
+    /// The query is embedded as is.
+    #[default]
+    None,
+
+    /// A hypothetical answer to the query is generated and embedded instead of the query.
+    Hyde,
+
+    /// Synonyms and related terms are appended to the query.
+    Expand,
+}
+
+impl QueryRewrite {
+    /// Rewrite the `query` using the `llm`. Returns the text to embed.
+    pub async fn rewrite(
+        &self,
+        llm: &(dyn Llm + Send + Sync),
+        query: &str,
+    ) -> Result<String, ChonkitError> {
+        match self {
+            QueryRewrite::None => Ok(query.to_string()),
+            QueryRewrite::Hyde => llm.complete(HYDE_INSTRUCTIONS, query).await,
+            QueryRewrite::Expand => {
+                let terms = llm.complete(EXPAND_INSTRUCTIONS, query).await?;
+                Ok(format!("{query} {}", terms.trim()))
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
+use sqlx::{Postgres, QueryBuilder};
 use validify::{field_err, Validate, ValidationError};
 
 /// Vector collection models.
@@ -23,6 +24,24 @@ pub mod image;
 pub struct List<T> {
     pub total: Option<usize>,
     pub items: Vec<T>,
+
+    /// Set for lists obtained with [List::paginated].
+    #[serde(flatten)]
+    pub pagination: Option<ListPagination>,
+}
+
+/// Pagination metadata of a [List], so clients need not compute it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPagination {
+    /// The page the items are on, starting from 1.
+    pub page: usize,
+
+    /// The maximum amount of items on the page.
+    pub per_page: usize,
+
+    /// Whether there are items on subsequent pages.
+    pub has_next: bool,
 }
 
 impl<'s, T> utoipa::ToSchema<'s> for List<T>
@@ -47,6 +66,24 @@ where
                 "items",
                 utoipa::openapi::schema::ArrayBuilder::new().items(item_schema),
             )
+            .property(
+                "page",
+                utoipa::openapi::schema::ObjectBuilder::new()
+                    .title(Some("page"))
+                    .schema_type(utoipa::openapi::SchemaType::Integer),
+            )
+            .property(
+                "perPage",
+                utoipa::openapi::schema::ObjectBuilder::new()
+                    .title(Some("perPage"))
+                    .schema_type(utoipa::openapi::SchemaType::Integer),
+            )
+            .property(
+                "hasNext",
+                utoipa::openapi::schema::ObjectBuilder::new()
+                    .title(Some("hasNext"))
+                    .schema_type(utoipa::openapi::SchemaType::Boolean),
+            )
             .build();
 
         (
@@ -58,7 +95,30 @@ where
 
 impl<T> List<T> {
     pub fn new(total: Option<usize>, items: Vec<T>) -> Self {
-        Self { total, items }
+        Self {
+            total,
+            items,
+            pagination: None,
+        }
+    }
+
+    /// Include the pagination metadata of the page the list was obtained with.
+    /// Without a total, there is assumed to be a next page if this one is full.
+    pub fn paginated(mut self, pagination: Pagination) -> Self {
+        let Pagination { per_page, page } = pagination;
+
+        let has_next = match self.total {
+            Some(total) => page.saturating_mul(per_page) < total,
+            None => self.items.len() == per_page,
+        };
+
+        self.pagination = Some(ListPagination {
+            page,
+            per_page,
+            has_next,
+        });
+
+        self
     }
 }
 
@@ -102,6 +162,15 @@ impl Pagination {
         let Self { page, per_page } = self;
         (*per_page as i64, ((page - 1) * *per_page) as i64)
     }
+
+    /// Limit `per_page` to at most `max_per_page`. The page is kept as is, i.e. the offset
+    /// is computed from the clamped page size.
+    pub fn clamp(self, max_per_page: usize) -> Self {
+        Self {
+            per_page: self.per_page.min(max_per_page),
+            page: self.page,
+        }
+    }
 }
 
 impl Default for Pagination {
@@ -180,6 +249,18 @@ impl<T> PaginationSort<T> {
             .map(|pagination| pagination.to_limit_offset())
             .unwrap_or(Pagination::default().to_limit_offset())
     }
+
+    /// The pagination, or the default one if missing.
+    pub fn pagination(&self) -> Pagination {
+        self.pagination.unwrap_or_default()
+    }
+
+    /// See [Pagination::clamp]. Missing pagination is clamped from the default one.
+    pub fn clamp(mut self, max_per_page: usize) -> Self {
+        let pagination = self.pagination.unwrap_or_default();
+        self.pagination = Some(pagination.clamp(max_per_page));
+        self
+    }
 }
 
 impl<T> Default for PaginationSort<T> {
@@ -202,6 +283,7 @@ pub enum SortDirection {
 }
 
 /// Struct used for search functionality when querying various models.
+#[serde_as]
 #[derive(Debug, Clone, Validate, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Search<T> {
@@ -214,6 +296,41 @@ pub struct Search<T> {
     /// the possible search values for a given query.
     #[serde(alias = "search.column")]
     pub column: T,
+
+    /// If `true`, accents are ignored when matching the column, e.g. searching for `resume`
+    /// matches `résumé`.
+    ///
+    /// Default: `false`
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default, alias = "search.accentInsensitive")]
+    pub accent_insensitive: bool,
+}
+
+impl<T: ToSearchColumn> Search<T> {
+    /// Push the `<column> ILIKE <term>` condition to the query, binding the term.
+    /// If the search is accent insensitive, both sides are wrapped in `unaccent`.
+    ///
+    /// * `prefix`: The table to prefix the column with, see [ToSearchColumn::to_search_column_prefixed].
+    pub fn push_condition<'q, 'args>(
+        &self,
+        query: &'q mut QueryBuilder<'args, Postgres>,
+        prefix: Option<&str>,
+    ) -> &'q mut QueryBuilder<'args, Postgres> {
+        let column = match prefix {
+            Some(prefix) => self.column.to_search_column_prefixed(prefix),
+            None => self.column.to_search_column().to_string(),
+        };
+        let q = format!("%{}%", self.q);
+
+        if self.accent_insensitive {
+            query
+                .push(format!("unaccent({column}) ILIKE unaccent("))
+                .push_bind(q)
+                .push(")")
+        } else {
+            query.push(column).push(" ILIKE ").push_bind(q)
+        }
+    }
 }
 
 /// Intended to be implemented on enums that serve as search columns.
@@ -275,3 +392,66 @@ macro_rules! search_column {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{List, ListPagination, Pagination, PaginationSort, SortDirection};
+
+    #[test]
+    fn pagination_is_clamped() {
+        let pagination = Pagination::new(1_000_000, 3).clamp(200);
+        assert_eq!((200, 400), pagination.to_limit_offset());
+
+        let pagination = Pagination::new(10, 3).clamp(200);
+        assert_eq!((10, 20), pagination.to_limit_offset());
+
+        let pagination = Pagination::default().clamp(200);
+        assert_eq!((200, 0), pagination.to_limit_offset());
+    }
+
+    #[test]
+    fn pagination_sort_is_clamped() {
+        let params = PaginationSort::<()>::new(
+            Pagination::new(1_000_000, 1),
+            "name".to_string(),
+            SortDirection::Asc,
+        )
+        .clamp(200);
+        assert_eq!((200, 0), params.to_limit_offset());
+        assert_eq!(("name", "ASC"), params.to_sort());
+
+        let params = PaginationSort::<()> {
+            pagination: None,
+            ..Default::default()
+        }
+        .clamp(200);
+        assert_eq!((200, 0), params.to_limit_offset());
+    }
+
+    #[test]
+    fn list_pagination_reports_next_page() {
+        let list = List::new(Some(5), vec![1, 2]).paginated(Pagination::new(2, 2));
+        assert_eq!(
+            Some(ListPagination {
+                page: 2,
+                per_page: 2,
+                has_next: true,
+            }),
+            list.pagination
+        );
+
+        let list = List::new(Some(5), vec![5]).paginated(Pagination::new(2, 3));
+        assert!(!list.pagination.unwrap().has_next);
+
+        // Without a total, full pages are assumed to have a next one
+        let list = List::new(None, vec![1, 2]).paginated(Pagination::new(2, 1));
+        assert!(list.pagination.unwrap().has_next);
+
+        let list = List::new(None, vec![1]).paginated(Pagination::new(2, 1));
+        assert!(!list.pagination.unwrap().has_next);
+
+        // The default page size does not overflow
+        let list = List::new(Some(1), vec![1]).paginated(Pagination::default());
+        assert!(!list.pagination.unwrap().has_next);
+    }
+}
@@ -3,7 +3,7 @@ use crate::search_column;
 use super::document::DocumentShort;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 /// Vector collection model.
@@ -20,6 +20,30 @@ pub struct Collection {
     pub embedder: String,
     /// Vector database provider ID.
     pub provider: String,
+    /// Whether the embedding model supports images. `None` if unknown, e.g. for collections
+    /// obtained by syncing with the vector database.
+    pub multimodal: Option<bool>,
+    /// Template the chunks are wrapped with before they are embedded.
+    /// See [chunk_template][crate::core::chunk::apply_chunk_template].
+    pub chunk_template: Option<String>,
+    /// Labels for organizing collections, e.g. `project:alpha`.
+    pub tags: Option<Vec<String>>,
+    /// Chunks with a cosine similarity of at least this value with an earlier chunk of the
+    /// same document are not inserted. `None` if disabled.
+    pub insert_dedup_threshold: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A stable name resolving to a collection. Searches can target the alias instead of the
+/// collection ID, so the collection behind it can be swapped without changing clients.
+#[derive(Debug, Serialize, FromRow, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionAlias {
+    /// Primary key.
+    pub alias: String,
+    /// The collection the alias currently resolves to.
+    pub collection_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -38,6 +62,10 @@ pub struct CollectionInsert<'a> {
     pub model: &'a str,
     pub embedder: &'a str,
     pub provider: &'a str,
+    pub multimodal: Option<bool>,
+    pub chunk_template: Option<&'a str>,
+    pub tags: Option<&'a [String]>,
+    pub insert_dedup_threshold: Option<f64>,
 }
 
 impl<'a> CollectionInsert<'a> {
@@ -48,6 +76,76 @@ impl<'a> CollectionInsert<'a> {
             model,
             embedder,
             provider,
+            multimodal: None,
+            chunk_template: None,
+            tags: None,
+            insert_dedup_threshold: None,
+        }
+    }
+
+    /// Record whether the collection's model supports images.
+    pub fn with_multimodal(mut self, multimodal: bool) -> Self {
+        self.multimodal = Some(multimodal);
+        self
+    }
+
+    /// Wrap the collection's chunks with the template before embedding them.
+    pub fn with_chunk_template(mut self, template: Option<&'a str>) -> Self {
+        self.chunk_template = template;
+        self
+    }
+
+    /// Label the collection with the tags.
+    pub fn with_tags(mut self, tags: Option<&'a [String]>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Skip inserting chunks nearly identical to earlier chunks of the same document.
+    pub fn with_insert_dedup_threshold(mut self, threshold: Option<f64>) -> Self {
+        self.insert_dedup_threshold = threshold;
+        self
+    }
+}
+
+/// Exact match filters for listing collections.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CollectionFilter<'a> {
+    /// Only collections labeled with the tag.
+    pub tag: Option<&'a str>,
+    /// Only collections stored in the vector database provider.
+    pub provider: Option<&'a str>,
+    /// Only collections embedded with the embedding provider.
+    pub embedder: Option<&'a str>,
+}
+
+impl CollectionFilter<'_> {
+    /// Push the filters as conditions of the query.
+    ///
+    /// * `separator`: The keyword preceding the first condition, e.g. `WHERE` if the query
+    ///   has no other conditions.
+    pub fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>, mut separator: &str) {
+        if let Some(tag) = self.tag {
+            query
+                .push(separator)
+                .push_bind(tag.to_string())
+                .push(" = ANY(tags)");
+            separator = " AND ";
+        }
+
+        if let Some(provider) = self.provider {
+            query
+                .push(separator)
+                .push("provider = ")
+                .push_bind(provider.to_string());
+            separator = " AND ";
+        }
+
+        if let Some(embedder) = self.embedder {
+            query
+                .push(separator)
+                .push("embedder = ")
+                .push_bind(embedder.to_string());
         }
     }
 }
@@ -39,6 +39,14 @@ pub struct Document {
     /// File tags.
     pub tags: Option<Vec<String>>,
 
+    /// Custom attributes, e.g. `author` or `source_url`. Copied to the payload of the
+    /// document's vectors when embedding.
+    pub metadata: Option<serde_json::Value>,
+
+    /// Set if storing the document's images failed. The images can be reprocessed
+    /// once the image storage is available.
+    pub images_pending: bool,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -50,6 +58,7 @@ impl Document {
             "docx" => "application/docx",
             "json" => "application/json",
             "xml" => "application/xml",
+            "md" => "text/markdown",
             _ => "application/plain",
         }
     }
@@ -62,6 +71,24 @@ search_column! {
     Label => "label",
 }
 
+/// A location the content of a document was obtained from.
+///
+/// Every document has its own `src` and `path` as its first source. When the same content is
+/// obtained from other locations, those are linked to the document as additional sources.
+#[derive(Debug, Serialize, FromRow, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSource {
+    pub document_id: uuid::Uuid,
+
+    /// Content source, e.g. `fs`.
+    pub src: String,
+
+    /// Path to the file in the source.
+    pub path: String,
+
+    pub created_at: DateTime<Utc>,
+}
+
 /// Document struct for display purposes when listing collections.
 #[derive(Debug, Serialize, Default, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -103,6 +130,7 @@ pub struct DocumentConfig {
     pub ext: String,
     pub hash: String,
     pub src: String,
+    pub metadata: Option<serde_json::Value>,
     pub chunk_config: Option<ChunkConfig>,
     pub parse_config: Option<ParseConfig>,
 }
@@ -116,6 +144,7 @@ impl DocumentConfig {
             ext: document.ext,
             hash: document.hash,
             src: document.src,
+            metadata: document.metadata,
             chunk_config: Some(chunk_config),
             parse_config: Some(parse_config),
         }
@@ -133,6 +162,7 @@ pub struct DocumentInsert<'a> {
     pub src: &'a str,
     pub label: Option<&'a str>,
     pub tags: Option<Vec<String>>,
+    pub metadata: Option<serde_json::Value>,
 }
 
 impl<'a> DocumentInsert<'a> {
@@ -152,6 +182,7 @@ impl<'a> DocumentInsert<'a> {
             src,
             label: None,
             tags: None,
+            metadata: None,
         }
     }
 
@@ -164,6 +195,11 @@ impl<'a> DocumentInsert<'a> {
         self.tags = Some(tags);
         self
     }
+
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
 }
 
 /// DTO for updating intended for user APIs.
@@ -172,6 +208,9 @@ pub struct DocumentMetadataUpdate<'a> {
     pub name: Option<&'a str>,
     pub label: Option<&'a str>,
     pub tags: Option<&'a [String]>,
+
+    /// Replaces the existing metadata if set.
+    pub metadata: Option<&'a serde_json::Value>,
 }
 
 /// Main config model for the `chunkers` table.
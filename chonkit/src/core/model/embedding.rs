@@ -1,6 +1,6 @@
-use crate::search_column;
+use crate::{core::token::TokenCount, search_column};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgRow, FromRow, Row};
 use uuid::Uuid;
 
@@ -58,6 +58,8 @@ impl<'r> FromRow<'r, PgRow> for EmbeddingReport {
                             document_id,
                             document_name,
                             report: addition_report_base(row, base)?,
+                            chunk_tokens: None,
+                            chunk_fallback: row.try_get("chunk_fallback")?,
                         }),
                     })
                 }
@@ -122,6 +124,124 @@ pub enum EmbeddingReportType {
     ImageAddition(ImageEmbeddingAdditionReport),
     TextRemoval(TextEmbeddingRemovalReport),
     ImageRemoval(ImageEmbeddingRemovalReport),
+
+    /// Only emitted by batch jobs skipping existing embeddings, never persisted.
+    TextSkipped(TextEmbeddingSkipReport),
+}
+
+/// Used to filter embedding reports by the action they describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingReportAction {
+    /// Embeddings were added to a collection.
+    Addition,
+
+    /// Embeddings were removed from a collection.
+    Removal,
+}
+
+/// Used to filter embedding reports by the kind of content that was embedded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingReportKind {
+    Text,
+    Image,
+}
+
+impl EmbeddingReportKind {
+    /// The value of the `type` column in the report tables.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Image => "image",
+        }
+    }
+}
+
+/// A single request made to an embedding provider, recorded if auditing is enabled.
+///
+/// Unlike reports, which describe embedding a whole document or image, requests are recorded
+/// for every call to the provider, e.g. once per batch when embedding in batches.
+#[derive(Debug, Clone)]
+pub struct EmbeddingRequest {
+    pub provider: String,
+    pub model: String,
+    pub kind: EmbeddingReportKind,
+
+    /// The amount of inputs embedded in the request.
+    pub inputs: usize,
+
+    /// Estimated with the model's tokenizer. Not estimated for images.
+    pub input_tokens: Option<usize>,
+
+    /// Tokens the provider reports it used, if it reports them.
+    pub response_tokens: Option<usize>,
+
+    pub latency_ms: u64,
+
+    /// The error the request failed with, if any.
+    pub error: Option<String>,
+
+    pub requested_at: DateTime<Utc>,
+}
+
+/// How long embedding reports are kept, given as an amount followed by a unit, e.g. `30d`.
+///
+/// Supported units are `d` (days), `h` (hours) and `m` (minutes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportRetention(chrono::Duration);
+
+impl ReportRetention {
+    /// Reports that finished before the returned time are outside of the retention window.
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.0
+    }
+}
+
+impl std::str::FromStr for ReportRetention {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let Some(unit) = s.chars().last() else {
+            return Err("retention cannot be empty".to_string());
+        };
+
+        let amount = s[..s.len() - unit.len_utf8()]
+            .parse::<u32>()
+            .map_err(|e| format!("invalid retention amount in '{s}': {e}"))?;
+
+        let duration = match unit {
+            'd' => chrono::Duration::days(amount.into()),
+            'h' => chrono::Duration::hours(amount.into()),
+            'm' => chrono::Duration::minutes(amount.into()),
+            _ => {
+                return Err(format!(
+                    "invalid retention unit in '{s}', expected d, h or m"
+                ))
+            }
+        };
+
+        if duration.is_zero() {
+            return Err("retention must be greater than 0".to_string());
+        }
+
+        Ok(Self(duration))
+    }
+}
+
+impl std::fmt::Display for ReportRetention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let minutes = self.0.num_minutes();
+        if minutes % (60 * 24) == 0 {
+            write!(f, "{}d", minutes / (60 * 24))
+        } else if minutes % 60 == 0 {
+            write!(f, "{}h", minutes / 60)
+        } else {
+            write!(f, "{minutes}m")
+        }
+    }
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -129,6 +249,65 @@ pub struct TextEmbeddingAdditionReport {
     pub document_id: Uuid,
     pub document_name: String,
     pub report: EmbeddingAdditionReport,
+
+    /// Token counts of the embedded chunks. Only present if a detailed report was requested
+    /// and never persisted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_tokens: Option<ChunkTokenBreakdown>,
+
+    /// Whether the configured chunker failed and the document was chunked with its fallback.
+    /// Such documents can be re-embedded once the configured chunker is available again.
+    pub chunk_fallback: bool,
+}
+
+/// Per chunk token counts of an embedded document.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ChunkTokenBreakdown {
+    /// The token count of each chunk, in chunk order.
+    pub counts: Vec<TokenCount>,
+
+    /// The sum of all chunk token counts.
+    pub total: TokenCount,
+
+    /// The smallest chunk token count, per tokenizer.
+    pub min: TokenCount,
+
+    /// The largest chunk token count, per tokenizer.
+    pub max: TokenCount,
+
+    /// The average chunk token count, per tokenizer, rounded down.
+    pub avg: TokenCount,
+}
+
+impl ChunkTokenBreakdown {
+    pub fn new(counts: Vec<TokenCount>) -> Self {
+        let mut total = TokenCount::default();
+        let mut min = counts.first().copied().unwrap_or_default();
+        let mut max = TokenCount::default();
+
+        for count in counts.iter() {
+            total += *count;
+            min.cl100k = min.cl100k.min(count.cl100k);
+            min.o200k = min.o200k.min(count.o200k);
+            max.cl100k = max.cl100k.max(count.cl100k);
+            max.o200k = max.o200k.max(count.o200k);
+            min.model = min.model.min(count.model);
+            max.model = max.model.max(count.model);
+        }
+
+        let avg = match counts.len() {
+            0 => TokenCount::default(),
+            n => TokenCount::new(total.cl100k / n, total.o200k / n, total.model / n),
+        };
+
+        Self {
+            counts,
+            total,
+            min,
+            max,
+            avg,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -144,12 +323,34 @@ pub struct TextEmbeddingRemovalReport {
     pub report: EmbeddingReportBase,
 }
 
+/// A document that was not embedded because it already is embedded in the collection.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TextEmbeddingSkipReport {
+    pub document_id: Uuid,
+    pub collection_id: Uuid,
+    pub collection_name: String,
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct ImageEmbeddingRemovalReport {
     pub image_id: Uuid,
     pub report: EmbeddingReportBase,
 }
 
+/// The embeddings of a document and its images removed from a single collection.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DocumentEmbeddingRemovalReport {
+    pub document_id: Uuid,
+
+    /// Whether the collection held text embeddings of the document.
+    pub text: bool,
+
+    /// The images of the document whose embeddings were removed from the collection.
+    pub image_ids: Vec<Uuid>,
+
+    pub report: EmbeddingReportBase,
+}
+
 /// The details of adding embeddings to a collection.
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct EmbeddingAdditionReport {
@@ -281,3 +482,59 @@ search_column! {
     // Image search fields for image embeddings
     ImageId => "image_id",
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkTokenBreakdown, ReportRetention};
+    use crate::core::token::TokenCount;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn parses_report_retention() {
+        let now = Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap();
+
+        let retention = "30d".parse::<ReportRetention>().unwrap();
+        assert_eq!(now - Duration::days(30), retention.cutoff(now));
+        assert_eq!("30d", retention.to_string());
+
+        let retention = "36h".parse::<ReportRetention>().unwrap();
+        assert_eq!(now - Duration::hours(36), retention.cutoff(now));
+        assert_eq!("36h", retention.to_string());
+
+        assert_eq!("90m", "90m".parse::<ReportRetention>().unwrap().to_string());
+
+        assert!("".parse::<ReportRetention>().is_err());
+        assert!("d".parse::<ReportRetention>().is_err());
+        assert!("0d".parse::<ReportRetention>().is_err());
+        assert!("30w".parse::<ReportRetention>().is_err());
+        assert!("-1d".parse::<ReportRetention>().is_err());
+    }
+
+    #[test]
+    fn breaks_down_chunk_tokens() {
+        let breakdown = ChunkTokenBreakdown::new(vec![
+            TokenCount::new(4, 3, 5),
+            TokenCount::new(10, 12, 9),
+            TokenCount::new(7, 6, 7),
+        ]);
+
+        assert_eq!(21, breakdown.total.cl100k);
+        assert_eq!(21, breakdown.total.o200k);
+        assert_eq!((4, 3), (breakdown.min.cl100k, breakdown.min.o200k));
+        assert_eq!((10, 12), (breakdown.max.cl100k, breakdown.max.o200k));
+        assert_eq!((7, 7), (breakdown.avg.cl100k, breakdown.avg.o200k));
+        assert_eq!(
+            (5, 9, 7),
+            (
+                breakdown.min.model,
+                breakdown.max.model,
+                breakdown.avg.model
+            )
+        );
+
+        let empty = ChunkTokenBreakdown::new(vec![]);
+        assert_eq!(0, empty.total.cl100k);
+        assert_eq!(0, empty.min.cl100k);
+        assert_eq!(0, empty.avg.o200k);
+    }
+}
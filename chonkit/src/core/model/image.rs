@@ -155,6 +155,26 @@ impl ImageData {
         })
     }
 
+    /// Downscale the image so neither side exceeds `size`, preserving the aspect ratio.
+    /// The thumbnail is always encoded as PNG since not every source format can be encoded.
+    pub fn thumbnail(&self, size: u32) -> Result<Self, ChonkitError> {
+        let img = map_err!(image::load_from_memory_with_format(
+            &self.bytes,
+            self.format
+        ))
+        .thumbnail(size, size);
+
+        let mut bytes = std::io::Cursor::new(vec![]);
+        map_err!(img.write_to(&mut bytes, image::ImageFormat::Png));
+
+        Ok(Self {
+            bytes: bytes.into_inner(),
+            format: image::ImageFormat::Png,
+            width: img.width(),
+            height: img.height(),
+        })
+    }
+
     pub fn size_in_mb(&self) -> usize {
         (self.bytes.len() as f64 / 1024.0 / 1024.0) as usize
     }
@@ -210,6 +230,42 @@ pub struct ImageModel {
     pub image_number: Option<i32>,
 }
 
+/// Aggregate version of [ImageModel] with the collections the image is embedded in.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageDisplay {
+    pub image: ImageModel,
+    pub collections: Vec<ImageCollection>,
+
+    /// Base64 data URI of the downscaled image, included only when requested.
+    pub thumbnail: Option<String>,
+}
+
+impl ImageDisplay {
+    pub fn new(image: ImageModel, collections: Vec<ImageCollection>) -> Self {
+        Self {
+            image,
+            collections,
+            thumbnail: None,
+        }
+    }
+}
+
+/// Collection an image is embedded in.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageCollection {
+    pub id: Uuid,
+    pub name: String,
+    pub provider: String,
+}
+
+impl ImageCollection {
+    pub fn new(id: Uuid, name: String, provider: String) -> Self {
+        Self { id, name, provider }
+    }
+}
+
 pub struct InsertImage<'a> {
     pub path: &'a str,
     pub hash: &'a str,
@@ -1,9 +1,12 @@
 use crate::{
     core::{
         model::{
-            collection::{Collection, CollectionDisplay, CollectionInsert, CollectionSearchColumn},
+            collection::{
+                Collection, CollectionAlias, CollectionDisplay, CollectionFilter, CollectionInsert,
+                CollectionSearchColumn,
+            },
             document::DocumentShort,
-            List, PaginationSort, ToSearchColumn,
+            List, PaginationSort,
         },
         repo::{Repository, Transaction},
     },
@@ -20,6 +23,7 @@ impl Repository {
     pub async fn list_collections(
         &self,
         params: PaginationSort<CollectionSearchColumn>,
+        filter: CollectionFilter<'_>,
     ) -> Result<List<Collection>, ChonkitError> {
         let mut count =
             sqlx::query_builder::QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM collections");
@@ -28,22 +32,19 @@ impl Repository {
         let (sort_by, sort_dir) = params.to_sort();
 
         let mut query = sqlx::query_builder::QueryBuilder::<Postgres>::new(
-            "SELECT id, name, model, embedder, provider, created_at, updated_at FROM collections",
+            "SELECT id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold, created_at, updated_at FROM collections",
         );
 
-        if let Some(ref search) = params.search {
-            let q = format!("%{}%", search.q);
-            count
-                .push(" WHERE ")
-                .push(search.column.to_search_column())
-                .push(" ILIKE ")
-                .push_bind(q.clone());
-
-            query
-                .push(" WHERE ")
-                .push(search.column.to_search_column())
-                .push(" ILIKE ")
-                .push_bind(q);
+        for builder in [&mut query, &mut count] {
+            let mut separator = " WHERE ";
+
+            if let Some(ref search) = params.search {
+                builder.push(separator);
+                search.push_condition(builder, None);
+                separator = " AND ";
+            }
+
+            filter.push_conditions(builder, separator);
         }
 
         query
@@ -66,14 +67,16 @@ impl Repository {
     pub async fn list_collections_display(
         &self,
         p: PaginationSort<CollectionSearchColumn>,
+        filter: CollectionFilter<'_>,
     ) -> Result<List<CollectionDisplay>, ChonkitError> {
         let (limit, offset) = p.to_limit_offset();
         let (sort_by, sort_dir) = p.to_sort();
 
-        let total = map_err!(sqlx::query!("SELECT COUNT(id) FROM collections")
-            .fetch_one(&self.client)
-            .await
-            .map(|row| row.count));
+        let mut count =
+            sqlx::query_builder::QueryBuilder::<Postgres>::new("SELECT COUNT(id) FROM collections");
+        filter.push_conditions(&mut count, " WHERE ");
+
+        let total: i64 = map_err!(count.build_query_scalar().fetch_one(&self.client).await);
 
         let mut query = sqlx::query_builder::QueryBuilder::<Postgres>::new(
             r#"
@@ -99,11 +102,16 @@ impl Repository {
                                 collections.model,
                                 collections.embedder,
                                 collections.provider,
+                                collections.multimodal,
+                                collections.chunk_template,
+                                collections.tags,
+                                collections.insert_dedup_threshold,
                                 collections.created_at,
                                 collections.updated_at
                         FROM collections
         "#,
         );
+        filter.push_conditions(&mut query, " WHERE ");
         query
             .push(" LIMIT ")
             .push_bind(limit)
@@ -118,6 +126,10 @@ impl Repository {
                         cols.model,
                         cols.embedder,
                         cols.provider,
+                        cols.multimodal,
+                        cols.chunk_template,
+                        cols.tags,
+                        cols.insert_dedup_threshold,
                         cols.created_at,
                         cols.updated_at,
                         docs.document_id,
@@ -143,6 +155,10 @@ impl Repository {
                 model: collection_row.model,
                 embedder: collection_row.embedder,
                 provider: collection_row.provider,
+                multimodal: collection_row.multimodal,
+                chunk_template: collection_row.chunk_template,
+                tags: collection_row.tags,
+                insert_dedup_threshold: collection_row.insert_dedup_threshold,
                 created_at: collection_row.created_at,
                 updated_at: collection_row.updated_at,
             };
@@ -173,7 +189,7 @@ impl Repository {
         }
 
         Ok(List::new(
-            total.map(|total| total as usize),
+            Some(total as usize),
             result.drain().map(|(_, v)| v).collect(),
         ))
     }
@@ -189,22 +205,30 @@ impl Repository {
             model,
             embedder,
             provider,
+            multimodal,
+            chunk_template,
+            tags,
+            insert_dedup_threshold,
         } = insert;
 
         let query = sqlx::query_as!(
             Collection,
             "INSERT INTO collections
-                (id, name, model, embedder, provider)
+                (id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold)
              VALUES
-                ($1, $2, $3, $4, $5)
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9)
              RETURNING 
-                id, name, model, embedder, provider, created_at, updated_at
+                id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold, created_at, updated_at
              ",
             id,
             name,
             model,
             embedder,
-            provider
+            provider,
+            multimodal,
+            chunk_template,
+            tags,
+            insert_dedup_threshold
         );
 
         let collection = if let Some(tx) = tx {
@@ -231,10 +255,72 @@ impl Repository {
         Ok(result.rows_affected())
     }
 
+    /// Replace the tags of a collection. Returns the amount of updated rows.
+    pub async fn update_collection_tags(
+        &self,
+        id: Uuid,
+        tags: Option<&[String]>,
+    ) -> Result<u64, ChonkitError> {
+        let result = map_err!(
+            sqlx::query!("UPDATE collections SET tags = $2 WHERE id = $1", id, tags)
+                .execute(&self.client)
+                .await
+        );
+        Ok(result.rows_affected())
+    }
+
+    /// Change the model of a collection and the embedder providing it.
+    ///
+    /// Once a collection has any embeddings, its model and embedder are read-only since its vectors
+    /// would no longer match the ones created for queries. Setting the current ones again is allowed.
+    pub async fn update_collection_model(
+        &self,
+        id: Uuid,
+        model: &str,
+        embedder: &str,
+    ) -> Result<(), ChonkitError> {
+        let result = map_err!(
+            sqlx::query!(
+                r#"
+                UPDATE collections SET model = $2, embedder = $3
+                WHERE id = $1
+                AND (
+                    (model = $2 AND embedder = $3)
+                    OR (
+                        NOT EXISTS (SELECT 1 FROM embeddings WHERE collection_id = $1)
+                        AND NOT EXISTS (SELECT 1 FROM image_embeddings WHERE collection_id = $1)
+                    )
+                )
+                "#,
+                id,
+                model,
+                embedder
+            )
+            .execute(&self.client)
+            .await
+        );
+
+        if result.rows_affected() > 0 {
+            return Ok(());
+        }
+
+        let Some(collection) = self.get_collection_by_id(id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{id}'");
+        };
+
+        err!(
+            ModelImmutable,
+            "Collection '{}' has embeddings created with model '{}' ({}); create a new collection with model '{model}' and embed its documents there instead",
+            collection.name,
+            collection.model,
+            collection.embedder
+        )
+    }
+
     pub async fn get_collection_by_id(&self, id: Uuid) -> Result<Option<Collection>, ChonkitError> {
         Ok(map_err!(sqlx::query_as!(
             Collection,
-            "SELECT id, name, model, embedder, provider, created_at, updated_at FROM collections WHERE id = $1",
+            "SELECT id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold, created_at, updated_at FROM collections WHERE id = $1",
             id
         )
         .fetch_optional(&self.client)
@@ -247,7 +333,7 @@ impl Repository {
     ) -> Result<Option<CollectionDisplay>, ChonkitError> {
         let collection = map_err!(sqlx::query_as!(
             Collection,
-            "SELECT id, name, model, embedder, provider, created_at, updated_at FROM collections WHERE id = $1",
+            "SELECT id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold, created_at, updated_at FROM collections WHERE id = $1",
             collection_id
         )
         .fetch_optional(&self.client)
@@ -283,7 +369,7 @@ impl Repository {
     ) -> Result<Option<Collection>, ChonkitError> {
         Ok(map_err!(sqlx::query_as!(
             Collection,
-            "SELECT id, name, model, embedder, provider, created_at, updated_at FROM collections WHERE name = $1 AND provider = $2",
+            "SELECT id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold, created_at, updated_at FROM collections WHERE name = $1 AND provider = $2",
             name,
             provider
         )
@@ -291,6 +377,20 @@ impl Repository {
         .await))
     }
 
+    /// Get the collections with the given name in any of the vector database providers.
+    pub async fn list_collections_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Vec<Collection>, ChonkitError> {
+        Ok(map_err!(sqlx::query_as!(
+            Collection,
+            "SELECT id, name, model, embedder, provider, multimodal, chunk_template, tags, insert_dedup_threshold, created_at, updated_at FROM collections WHERE name = $1",
+            name
+        )
+        .fetch_all(&self.client)
+        .await))
+    }
+
     pub async fn get_document_assigned_collections(
         &self,
         document_id: Uuid,
@@ -322,7 +422,7 @@ impl Repository {
             r#"
             SELECT collections.id, collections.name, collections.provider FROM collections
                 WHERE collections.id IN (
-                        SELECT image_id FROM image_embeddings
+                        SELECT collection_id FROM image_embeddings
                         WHERE image_embeddings.image_id = $1 
                 )
             "#,
@@ -336,6 +436,72 @@ impl Repository {
             .map(|record| (record.id, record.name, record.provider))
             .collect())
     }
+
+    /// Point the alias to the collection, creating it if it does not exist.
+    /// Swapping the collection of an existing alias happens in a single statement, so concurrent
+    /// readers see either the old or the new collection.
+    pub async fn upsert_collection_alias(
+        &self,
+        alias: &str,
+        collection_id: Uuid,
+    ) -> Result<CollectionAlias, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query_as!(
+                CollectionAlias,
+                "INSERT INTO collection_aliases(alias, collection_id) VALUES ($1, $2)
+                 ON CONFLICT(alias) DO UPDATE SET collection_id = EXCLUDED.collection_id
+                 RETURNING alias, collection_id, created_at, updated_at",
+                alias,
+                collection_id
+            )
+            .fetch_one(&self.client)
+            .await
+        ))
+    }
+
+    pub async fn get_collection_alias(
+        &self,
+        alias: &str,
+    ) -> Result<Option<CollectionAlias>, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query_as!(
+                CollectionAlias,
+                "SELECT alias, collection_id, created_at, updated_at
+                 FROM collection_aliases WHERE alias = $1",
+                alias
+            )
+            .fetch_optional(&self.client)
+            .await
+        ))
+    }
+
+    /// List aliases, optionally only those pointing to the given collection.
+    pub async fn list_collection_aliases(
+        &self,
+        collection_id: Option<Uuid>,
+    ) -> Result<Vec<CollectionAlias>, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query_as!(
+                CollectionAlias,
+                "SELECT alias, collection_id, created_at, updated_at
+                 FROM collection_aliases
+                 WHERE $1::UUID IS NULL OR collection_id = $1
+                 ORDER BY alias",
+                collection_id
+            )
+            .fetch_all(&self.client)
+            .await
+        ))
+    }
+
+    pub async fn delete_collection_alias(&self, alias: &str) -> Result<u64, ChonkitError> {
+        let result = map_err!(
+            sqlx::query!("DELETE FROM collection_aliases WHERE alias = $1", alias)
+                .execute(&self.client)
+                .await
+        );
+        Ok(result.rows_affected())
+    }
 }
 
 /// Private DTO for joining collections and the documents they contain.
@@ -346,9 +512,166 @@ struct CollectionDocumentJoin {
     model: String,
     embedder: String,
     provider: String,
+    multimodal: Option<bool>,
+    chunk_template: Option<String>,
+    tags: Option<Vec<String>>,
+    insert_dedup_threshold: Option<f64>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     document_id: Option<Uuid>,
     document_name: Option<String>,
     document_count: i64,
 }
+
+#[cfg(test)]
+#[suitest::suite(pg_collection_repo_int)]
+#[suitest::suite_cfg(sequential = true)]
+mod tests {
+    use crate::{
+        app::test::{init_repository, PostgresContainer},
+        core::{
+            model::{
+                collection::{CollectionFilter, CollectionInsert},
+                PaginationSort,
+            },
+            repo::Repository,
+        },
+    };
+    use suitest::before_all;
+
+    #[before_all]
+    async fn setup() -> (Repository, PostgresContainer) {
+        let (repository, pg_img) = init_repository().await;
+        (repository, pg_img)
+    }
+
+    #[test]
+    async fn listing_collections_by_tag_works(repo: Repository) {
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        let alpha = tags(&["project:alpha", "env:prod"]);
+        let beta = tags(&["project:beta", "env:prod"]);
+
+        let mut collections = vec![];
+        for (name, tags) in [
+            ("Alpha", Some(&alpha[..])),
+            ("Beta", Some(&beta[..])),
+            ("Untagged", None),
+        ] {
+            let insert =
+                CollectionInsert::new(name, "model", "embedder", "provider").with_tags(tags);
+            collections.push(repo.insert_collection(insert, None).await.unwrap());
+        }
+
+        let [alpha_collection, beta_collection, untagged] = &collections[..] else {
+            unreachable!();
+        };
+
+        assert_eq!(Some(alpha.clone()), alpha_collection.tags);
+        assert_eq!(None, untagged.tags);
+
+        let list = |tag| {
+            let filter = CollectionFilter {
+                tag,
+                ..Default::default()
+            };
+            repo.list_collections(PaginationSort::default(), filter)
+        };
+
+        let alpha_list = list(Some("project:alpha")).await.unwrap();
+        assert_eq!(Some(1), alpha_list.total);
+        assert_eq!(alpha_collection.id, alpha_list.items[0].id);
+
+        let mut prod: Vec<_> = list(Some("env:prod"))
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        prod.sort();
+        let mut expected = vec![alpha_collection.id, beta_collection.id];
+        expected.sort();
+        assert_eq!(expected, prod);
+
+        assert_eq!(Some(3), list(None).await.unwrap().total);
+        assert_eq!(Some(0), list(Some("project:gamma")).await.unwrap().total);
+
+        // Tags are editable
+        let staging = tags(&["env:staging"]);
+        assert_eq!(
+            1,
+            repo.update_collection_tags(beta_collection.id, Some(&staging))
+                .await
+                .unwrap()
+        );
+
+        let prod = list(Some("env:prod")).await.unwrap();
+        assert_eq!(Some(1), prod.total);
+        assert_eq!(alpha_collection.id, prod.items[0].id);
+
+        let staged = list(Some("env:staging")).await.unwrap();
+        assert_eq!(beta_collection.id, staged.items[0].id);
+
+        for collection in collections.iter() {
+            repo.delete_collection(collection.id).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn listing_collections_by_provider_and_embedder_works(repo: Repository) {
+        let mut collections = vec![];
+        for (name, embedder, provider) in [
+            ("Ragu", "openai", "qdrant"),
+            ("Pasta", "fembed", "qdrant"),
+            ("Pizza", "openai", "weaviate"),
+        ] {
+            let insert = CollectionInsert::new(name, "model", embedder, provider);
+            collections.push(repo.insert_collection(insert, None).await.unwrap());
+        }
+
+        let [ragu, _, pizza] = &collections[..] else {
+            unreachable!();
+        };
+
+        let qdrant = CollectionFilter {
+            provider: Some("qdrant"),
+            ..Default::default()
+        };
+
+        let list = repo
+            .list_collections(PaginationSort::default(), qdrant)
+            .await
+            .unwrap();
+        assert_eq!(Some(2), list.total);
+        assert!(list.items.iter().all(|c| c.provider == "qdrant"));
+
+        let qdrant_openai = CollectionFilter {
+            embedder: Some("openai"),
+            ..qdrant
+        };
+
+        let list = repo
+            .list_collections(PaginationSort::default(), qdrant_openai)
+            .await
+            .unwrap();
+        assert_eq!(Some(1), list.total);
+        assert_eq!(ragu.id, list.items[0].id);
+
+        let weaviate = CollectionFilter {
+            provider: Some("weaviate"),
+            ..Default::default()
+        };
+
+        let display = repo
+            .list_collections_display(PaginationSort::default(), weaviate)
+            .await
+            .unwrap();
+        assert_eq!(Some(1), display.total);
+        assert_eq!(pizza.id, display.items[0].collection.id);
+
+        for collection in collections.iter() {
+            repo.delete_collection(collection.id).await.unwrap();
+        }
+    }
+}
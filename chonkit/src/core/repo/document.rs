@@ -1,6 +1,5 @@
 use crate::core::document::parser::ParseConfig;
 use crate::core::model::document::DocumentSearchColumn;
-use crate::core::model::ToSearchColumn;
 use crate::core::repo::{Repository, Transaction};
 use crate::error::ChonkitError;
 use crate::{
@@ -10,7 +9,7 @@ use crate::{
             collection::CollectionShort,
             document::{
                 Document, DocumentChunkConfig, DocumentConfig, DocumentDisplay, DocumentInsert,
-                DocumentMetadataUpdate, DocumentParseConfig,
+                DocumentMetadataUpdate, DocumentParseConfig, DocumentSource,
             },
             List, PaginationSort,
         },
@@ -31,7 +30,7 @@ impl Repository {
         Ok(map_err!(
             sqlx::query_as!(
                 Document,
-                "SELECT id, name, path, ext, hash, src, label, tags, created_at, updated_at
+                "SELECT id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at
              FROM documents 
              WHERE id = $1",
                 id
@@ -56,6 +55,7 @@ impl Repository {
                         d.ext,
                         d.hash,
                         d.src,
+                        d.metadata,
                         c.config AS "chunk_config: Option<Json<ChunkConfig>>",
                         p.config AS "parse_config: _"
                     FROM documents d 
@@ -80,7 +80,7 @@ impl Repository {
             sqlx::query_as!(
                 Document,
                 r#"
-                    SELECT id, name, path, ext, hash, src, label, tags, created_at, updated_at 
+                    SELECT id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at 
                     FROM documents 
                     WHERE path = $1 AND src = $2
                 "#,
@@ -105,7 +105,7 @@ impl Repository {
         Ok(map_err!(
             sqlx::query_as!(
                 Document,
-                "SELECT id, name, path, ext, hash, src, label, tags, created_at, updated_at 
+                "SELECT id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at 
                  FROM documents 
                  WHERE hash = $1",
                 hash
@@ -115,6 +115,37 @@ impl Repository {
         ))
     }
 
+    /// Get the summary generated for the document content with the given hash.
+    pub async fn get_document_summary(&self, hash: &str) -> Result<Option<String>, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query!(
+                "SELECT summary FROM document_summaries WHERE document_hash = $1",
+                hash
+            )
+            .fetch_optional(&self.client)
+            .await
+        )
+        .map(|el| el.summary))
+    }
+
+    pub async fn upsert_document_summary(
+        &self,
+        hash: &str,
+        summary: &str,
+    ) -> Result<(), ChonkitError> {
+        map_err!(
+            sqlx::query!(
+                "INSERT INTO document_summaries(document_hash, summary) VALUES ($1, $2)
+                 ON CONFLICT(document_hash) DO UPDATE SET summary = EXCLUDED.summary",
+                hash,
+                summary
+            )
+            .execute(&self.client)
+            .await
+        );
+        Ok(())
+    }
+
     pub async fn get_document_count(&self) -> Result<usize, ChonkitError> {
         Ok(map_err!(
             sqlx::query!("SELECT COUNT(id) FROM documents")
@@ -130,6 +161,7 @@ impl Repository {
         params: PaginationSort<DocumentSearchColumn>,
         src: Option<&str>,
         ready: Option<bool>,
+        require_embeddings: bool,
     ) -> Result<List<Document>, ChonkitError> {
         let mut count_query =
             sqlx::query_builder::QueryBuilder::<Postgres>::new("SELECT COUNT(id) FROM documents");
@@ -148,183 +180,65 @@ impl Repository {
                 documents.src,
                 documents.label,
                 documents.tags,
+                documents.metadata,
+                documents.images_pending,
                 documents.created_at,
                 documents.updated_at
             FROM documents"#,
         );
 
-        let ready_join = r#"
-            INNER JOIN chunkers ON chunkers.document_id = documents.id
-            INNER JOIN parsers ON parsers.document_id = documents.id
-        "#;
-
         // Needs to be prefixed with either WHERE or AND, depending on other conditions.
 
-        let ready_condition = r#"
+        let mut ready_condition = r#"
             EXISTS (
                 SELECT 1 FROM chunkers WHERE chunkers.document_id = documents.id
             )
             AND EXISTS (
                 SELECT 1 FROM parsers WHERE parsers.document_id = documents.id
             )
-        "#;
+        "#
+        .to_string();
 
-        let not_ready_condition = r#"
-            NOT EXISTS (
+        let mut not_ready_condition = r#"
+            (NOT EXISTS (
                 SELECT 1 FROM chunkers WHERE chunkers.document_id = documents.id
             )
             AND NOT EXISTS (
                 SELECT 1 FROM parsers WHERE parsers.document_id = documents.id
-            )
-        "#;
-
-        match (ready, src, &params.search) {
-            (Some(ready), Some(src), Some(search)) => {
-                let q = format!("%{}%", search.q);
-                if ready {
-                    query
-                        .push(ready_join)
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q.clone());
-
-                    count_query
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q)
-                        .push(" AND ")
-                        .push(ready_condition);
-                } else {
-                    query
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q.clone())
-                        .push(" AND ")
-                        .push(not_ready_condition);
-
-                    count_query
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q)
-                        .push(" AND ")
-                        .push(not_ready_condition);
-                }
-            }
-            (Some(ready), Some(src), None) => {
-                if ready {
-                    query.push(ready_join).push(" WHERE src = ").push_bind(src);
-                    count_query
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(ready_condition);
-                } else {
-                    query
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(not_ready_condition);
-
-                    count_query
-                        .push(" WHERE src = ")
-                        .push_bind(src)
-                        .push(" AND ")
-                        .push(not_ready_condition);
-                }
-            }
-            (Some(ready), None, None) => {
-                if ready {
-                    query.push(ready_join);
-                    count_query.push(" WHERE ").push(ready_condition);
-                } else {
-                    query.push(" WHERE ").push(not_ready_condition);
-                    count_query.push(" WHERE ").push(not_ready_condition);
-                }
-            }
-            (None, Some(src), Some(search)) => {
-                let q = format!("%{}%", search.q);
-                query
-                    .push(" WHERE src = ")
-                    .push_bind(src)
-                    .push(" AND ")
-                    .push(search.column.to_search_column())
-                    .push(" ILIKE ")
-                    .push_bind(q.clone());
+            ))
+        "#
+        .to_string();
+
+        if require_embeddings {
+            let embedded =
+                "EXISTS (SELECT 1 FROM embeddings WHERE embeddings.document_id = documents.id)";
+            ready_condition.push_str(&format!(" AND {embedded}"));
+            not_ready_condition = format!("({not_ready_condition} OR NOT {embedded})");
+        }
 
-                count_query
-                    .push(" WHERE src = ")
-                    .push_bind(src)
-                    .push(" AND ")
-                    .push(search.column.to_search_column())
-                    .push(" ILIKE ")
-                    .push_bind(q);
-            }
-            (None, Some(src), None) => {
-                query.push(" WHERE src = ").push_bind(src);
-                count_query.push(" WHERE src = ").push_bind(src);
+        for builder in [&mut query, &mut count_query] {
+            let mut separator = " WHERE ";
+
+            if let Some(src) = src {
+                builder.push(separator).push("src = ").push_bind(src);
+                separator = " AND ";
             }
-            (Some(ready), None, Some(search)) => {
-                let q = format!("%{}%", search.q);
-                if ready {
-                    query
-                        .push(ready_join)
-                        .push(" WHERE ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q.clone());
-
-                    count_query
-                        .push(" WHERE ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q)
-                        .push(" AND ")
-                        .push(ready_condition);
-                } else {
-                    query
-                        .push(" WHERE ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q.clone())
-                        .push(" AND ")
-                        .push(not_ready_condition);
-
-                    count_query
-                        .push(" WHERE ")
-                        .push(search.column.to_search_column())
-                        .push(" ILIKE ")
-                        .push_bind(q)
-                        .push(" AND ")
-                        .push(not_ready_condition);
-                }
+
+            if let Some(search) = &params.search {
+                builder.push(separator);
+                search.push_condition(builder, None);
+                separator = " AND ";
             }
-            (None, None, Some(search)) => {
-                let q = format!("%{}%", search.q);
-                query
-                    .push(" WHERE ")
-                    .push(search.column.to_search_column())
-                    .push(" ILIKE ")
-                    .push_bind(q.clone());
 
-                count_query
-                    .push(" WHERE ")
-                    .push(search.column.to_search_column())
-                    .push(" ILIKE ")
-                    .push_bind(q);
+            match ready {
+                Some(true) => {
+                    builder.push(separator).push(&ready_condition);
+                }
+                Some(false) => {
+                    builder.push(separator).push(&not_ready_condition);
+                }
+                None => {}
             }
-            (None, None, None) => (),
         }
 
         query
@@ -347,6 +261,27 @@ impl Repository {
         Ok(List::new(Some(total as usize), documents))
     }
 
+    /// List all documents, or only those obtained from `src`, ordered by creation time.
+    pub async fn list_all_documents(
+        &self,
+        src: Option<&str>,
+    ) -> Result<Vec<Document>, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query_as!(
+                Document,
+                r#"
+                    SELECT id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at
+                    FROM documents
+                    WHERE $1::TEXT IS NULL OR src = $1
+                    ORDER BY created_at
+                "#,
+                src
+            )
+            .fetch_all(&self.client)
+            .await
+        ))
+    }
+
     pub async fn list_all_document_paths(
         &self,
         src: &str,
@@ -361,6 +296,87 @@ impl Repository {
         .collect())
     }
 
+    /// List all sources of a document, starting with the one it was first obtained from.
+    pub async fn list_document_sources(
+        &self,
+        document_id: Uuid,
+    ) -> Result<Vec<DocumentSource>, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query_as!(
+                DocumentSource,
+                r#"
+                    SELECT id AS "document_id!", src AS "src!", path AS "path!", created_at AS "created_at!"
+                    FROM documents
+                    WHERE id = $1
+                    UNION ALL
+                    SELECT document_id, src, path, created_at
+                    FROM document_sources
+                    WHERE document_id = $1
+                    ORDER BY 4
+                "#,
+                document_id
+            )
+            .fetch_all(&self.client)
+            .await
+        ))
+    }
+
+    /// List the paths of all sources linked to documents from `src`. Does not include the
+    /// sources the documents were first obtained from.
+    pub async fn list_all_document_source_paths(
+        &self,
+        src: &str,
+    ) -> Result<Vec<(Uuid, String)>, ChonkitError> {
+        Ok(map_err!(
+            sqlx::query!(
+                "SELECT document_id, path FROM document_sources WHERE src = $1",
+                src
+            )
+            .fetch_all(&self.client)
+            .await
+        )
+        .into_iter()
+        .map(|el| (el.document_id, el.path))
+        .collect())
+    }
+
+    /// Link an additional source to the document. Linking an already linked source is a no-op.
+    pub async fn insert_document_source(
+        &self,
+        document_id: Uuid,
+        src: &str,
+        path: &str,
+    ) -> Result<(), ChonkitError> {
+        map_err!(
+            sqlx::query!(
+                r#"
+                INSERT INTO document_sources(document_id, src, path)
+                VALUES($1, $2, $3)
+                ON CONFLICT DO NOTHING
+                "#,
+                document_id,
+                src,
+                path
+            )
+            .execute(&self.client)
+            .await
+        );
+        Ok(())
+    }
+
+    pub async fn remove_document_source(&self, src: &str, path: &str) -> Result<u64, ChonkitError> {
+        let result = map_err!(
+            sqlx::query!(
+                "DELETE FROM document_sources WHERE src = $1 AND path = $2",
+                src,
+                path
+            )
+            .execute(&self.client)
+            .await
+        );
+        Ok(result.rows_affected())
+    }
+
     pub async fn list_all_document_update_times(
         &self,
         src: &str,
@@ -395,6 +411,31 @@ impl Repository {
         Ok(())
     }
 
+    pub async fn update_document_hash(
+        &self,
+        id: Uuid,
+        hash: &str,
+        tx: Option<&mut Transaction<'_>>,
+    ) -> Result<Document, ChonkitError> {
+        let query = sqlx::query_as!(
+            Document,
+            r#"
+                UPDATE documents
+                SET hash = $1
+                WHERE id = $2
+                RETURNING
+                    id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at
+            "#,
+            hash,
+            id
+        );
+
+        match tx {
+            Some(tx) => Ok(map_err!(query.fetch_one(&mut **tx).await)),
+            None => Ok(map_err!(query.fetch_one(&self.client).await)),
+        }
+    }
+
     pub async fn list_documents_with_collections(
         &self,
         params: PaginationSort<DocumentSearchColumn>,
@@ -417,6 +458,8 @@ impl Repository {
                         documents.src,
                         documents.label,
                         documents.tags,
+                        documents.metadata,
+                        documents.images_pending,
                         documents.created_at,
                         documents.updated_at,
                         collections.id AS collection_id,
@@ -436,36 +479,24 @@ impl Repository {
                 count_query.push(" WHERE src = ").push_bind(src);
             }
             (None, Some(search)) => {
-                let q = format!("%{}%", search.q);
-                query
-                    .push(" WHERE ")
-                    .push(search.column.to_search_column_prefixed("documents"))
-                    .push(" ILIKE ")
-                    .push_bind(q.clone());
+                query.push(" WHERE ");
+                search.push_condition(&mut query, Some("documents"));
 
-                count_query
-                    .push(" WHERE ")
-                    .push(search.column.to_search_column_prefixed("documents"))
-                    .push(" ILIKE ")
-                    .push_bind(q);
+                count_query.push(" WHERE ");
+                search.push_condition(&mut count_query, Some("documents"));
             }
             (Some(src), Some(search)) => {
-                let q = format!("%{}%", search.q);
                 query
                     .push(" WHERE documents.src = ")
                     .push_bind(src)
-                    .push(" AND ")
-                    .push(search.column.to_search_column_prefixed("documents"))
-                    .push(" ILIKE ")
-                    .push_bind(q.clone());
+                    .push(" AND ");
+                search.push_condition(&mut query, Some("documents"));
 
                 count_query
                     .push(" WHERE documents.src = ")
                     .push_bind(src)
-                    .push(" AND ")
-                    .push(search.column.to_search_column_prefixed("documents"))
-                    .push(" ILIKE ")
-                    .push_bind(q);
+                    .push(" AND ");
+                search.push_condition(&mut count_query, Some("documents"));
             }
             (None, None) => (),
         }
@@ -533,14 +564,15 @@ impl Repository {
             hash,
             label,
             tags,
+            metadata,
         } = params;
 
         Ok(map_err!(
             sqlx::query_as!(
                 Document,
-                "INSERT INTO documents(id, name, path, ext, hash, src, label, tags)
-             VALUES($1, $2, $3, $4, $5, $6, $7, $8)
-             RETURNING id, name, path, ext, hash, src, label, tags, created_at, updated_at",
+                "INSERT INTO documents(id, name, path, ext, hash, src, label, tags, metadata)
+             VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at",
                 id,
                 name,
                 path,
@@ -549,6 +581,7 @@ impl Repository {
                 src,
                 label,
                 tags.as_deref(),
+                metadata,
             )
             .fetch_one(&self.client)
             .await
@@ -561,7 +594,12 @@ impl Repository {
         update: DocumentMetadataUpdate<'_>,
         tx: Option<&mut Transaction<'_>>,
     ) -> Result<Document, ChonkitError> {
-        let DocumentMetadataUpdate { name, label, tags } = update;
+        let DocumentMetadataUpdate {
+            name,
+            label,
+            tags,
+            metadata,
+        } = update;
 
         let query = sqlx::query_as!(
             Document,
@@ -570,15 +608,17 @@ impl Repository {
                 SET
                     name = $1,
                     label = $2,
-                    tags = $3
+                    tags = $3,
+                    metadata = COALESCE($4, metadata)
                 WHERE
-                    id = $4
+                    id = $5
                 RETURNING
-                    id, name, path, ext, hash, src, label, tags, created_at, updated_at
+                    id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at
             "#,
             name.as_ref(),
             label.as_ref(),
             tags.as_deref(),
+            metadata,
             id
         );
 
@@ -588,6 +628,23 @@ impl Repository {
         }
     }
 
+    pub async fn update_document_images_pending(
+        &self,
+        id: uuid::Uuid,
+        pending: bool,
+    ) -> Result<(), ChonkitError> {
+        map_err!(
+            sqlx::query!(
+                "UPDATE documents SET images_pending = $1 WHERE id = $2",
+                pending,
+                id
+            )
+            .execute(&self.client)
+            .await
+        );
+        Ok(())
+    }
+
     pub async fn remove_document_by_id(
         &self,
         id: uuid::Uuid,
@@ -661,6 +718,7 @@ impl Repository {
         document_id: uuid::Uuid,
         collection_id: Option<uuid::Uuid>,
         chunker: ChunkConfig,
+        tx: Option<&mut Transaction<'_>>,
     ) -> Result<DocumentChunkConfig, ChonkitError> {
         let config = InsertConfig::new(document_id, chunker);
 
@@ -670,23 +728,24 @@ impl Repository {
             config,
         } = config;
 
-        let config = map_err!(
-            sqlx::query_as!(
-                SelectConfig::<ChunkConfig>,
-                r#"INSERT INTO chunkers (id, document_id, collection_id, config)
-                   VALUES ($1, $2, $3, $4)
-                   ON CONFLICT (document_id, collection_id) DO UPDATE SET config = $4
-                   RETURNING id, document_id, config AS "config: _", created_at, updated_at
-                "#,
-                id,
-                document_id,
-                collection_id,
-                config as Json<ChunkConfig>,
-            )
-            .fetch_one(&self.client)
-            .await
+        let query = sqlx::query_as!(
+            SelectConfig::<ChunkConfig>,
+            r#"INSERT INTO chunkers (id, document_id, collection_id, config)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (document_id, collection_id) DO UPDATE SET config = $4
+               RETURNING id, document_id, config AS "config: _", created_at, updated_at
+            "#,
+            id,
+            document_id,
+            collection_id,
+            config as Json<ChunkConfig>,
         );
 
+        let config = match tx {
+            Some(tx) => map_err!(query.fetch_one(&mut **tx).await),
+            None => map_err!(query.fetch_one(&self.client).await),
+        };
+
         Ok(DocumentChunkConfig::from(config))
     }
 
@@ -695,6 +754,7 @@ impl Repository {
         document_id: uuid::Uuid,
         collection_id: Option<uuid::Uuid>,
         config: ParseConfig,
+        tx: Option<&mut Transaction<'_>>,
     ) -> Result<DocumentParseConfig, ChonkitError> {
         let config = InsertConfig::new(document_id, config);
 
@@ -704,23 +764,24 @@ impl Repository {
             config,
         } = config;
 
-        let config = map_err!(
-            sqlx::query_as!(
-                SelectConfig::<ParseConfig>,
-                r#"INSERT INTO parsers (id, document_id, collection_id, config)
-                   VALUES ($1, $2, $3, $4)
-                   ON CONFLICT(document_id, collection_id) DO UPDATE SET config = $4
-                   RETURNING id, document_id, config AS "config: _", created_at, updated_at
-                "#,
-                id,
-                document_id,
-                collection_id,
-                config as Json<ParseConfig>,
-            )
-            .fetch_one(&self.client)
-            .await
+        let query = sqlx::query_as!(
+            SelectConfig::<ParseConfig>,
+            r#"INSERT INTO parsers (id, document_id, collection_id, config)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT(document_id, collection_id) DO UPDATE SET config = $4
+               RETURNING id, document_id, config AS "config: _", created_at, updated_at
+            "#,
+            id,
+            document_id,
+            collection_id,
+            config as Json<ParseConfig>,
         );
 
+        let config = match tx {
+            Some(tx) => map_err!(query.fetch_one(&mut **tx).await),
+            None => map_err!(query.fetch_one(&self.client).await),
+        };
+
         Ok(DocumentParseConfig::from(config))
     }
 
@@ -740,15 +801,16 @@ impl Repository {
             hash,
             label,
             tags,
+            metadata,
         } = document;
 
         let document = map_err!(
             sqlx::query_as!(
                 Document,
                 r#"
-                INSERT INTO documents(id, name, path, ext, hash, src, label, tags)
-                VALUES($1, $2, $3, $4, $5, $6, $7, $8)
-                RETURNING id, name, path, ext, hash, src, label, tags, created_at, updated_at
+                INSERT INTO documents(id, name, path, ext, hash, src, label, tags, metadata)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                RETURNING id, name, path, ext, hash, src, label, tags, metadata, images_pending, created_at, updated_at
                 "#,
                 id,
                 name,
@@ -758,6 +820,7 @@ impl Repository {
                 src,
                 label,
                 tags.as_deref(),
+                metadata,
             )
             .fetch_one(&mut **tx)
             .await
@@ -835,6 +898,7 @@ struct SelectDocumentConfig {
     ext: String,
     hash: String,
     src: String,
+    metadata: Option<serde_json::Value>,
     chunk_config: Option<Json<ChunkConfig>>,
     parse_config: Option<Json<ParseConfig>>,
 }
@@ -848,6 +912,7 @@ impl From<SelectDocumentConfig> for DocumentConfig {
             ext,
             hash,
             src,
+            metadata,
             chunk_config,
             parse_config,
         }: SelectDocumentConfig,
@@ -859,6 +924,7 @@ impl From<SelectDocumentConfig> for DocumentConfig {
             ext,
             hash,
             src,
+            metadata,
             chunk_config: chunk_config.map(|c| c.0),
             parse_config: parse_config.map(|c| c.0),
         }
@@ -921,7 +987,9 @@ mod tests {
             chunk::ChunkConfig,
             document::{parser::ParseConfig, DocumentType, TextDocumentType},
             model::{
+                collection::CollectionInsert,
                 document::{DocumentInsert, DocumentSearchColumn},
+                embedding::TextEmbeddingInsert,
                 Pagination, PaginationSort, Search,
             },
             repo::Repository,
@@ -957,6 +1025,66 @@ mod tests {
         assert!(doc.is_none());
     }
 
+    #[test]
+    async fn linking_document_sources_works(repo: Repository) {
+        let doc = DocumentInsert::new(
+            "My linked file",
+            "/path/to/linked/file",
+            DocumentType::Text(TextDocumentType::Txt),
+            "Linked hash",
+            "fs",
+        );
+        let doc = repo.insert_document(doc).await.unwrap();
+
+        repo.insert_document_source(doc.id, "google_drive", "/drive/linked/file")
+            .await
+            .unwrap();
+
+        // Linking twice is a no-op
+        repo.insert_document_source(doc.id, "google_drive", "/drive/linked/file")
+            .await
+            .unwrap();
+
+        let sources = repo.list_document_sources(doc.id).await.unwrap();
+
+        assert_eq!(2, sources.len());
+        assert_eq!(
+            ("fs", "/path/to/linked/file"),
+            (&*sources[0].src, &*sources[0].path)
+        );
+        assert_eq!(
+            ("google_drive", "/drive/linked/file"),
+            (&*sources[1].src, &*sources[1].path)
+        );
+
+        repo.remove_document_by_id(doc.id, None).await.unwrap();
+
+        let paths = repo
+            .list_all_document_source_paths("google_drive")
+            .await
+            .unwrap();
+        assert!(paths.is_empty());
+    }
+
+    #[test]
+    async fn document_summaries_work(repo: Repository) {
+        assert!(repo
+            .get_document_summary("Summarized hash")
+            .await
+            .unwrap()
+            .is_none());
+
+        repo.upsert_document_summary("Summarized hash", "Raguru cooks.")
+            .await
+            .unwrap();
+        repo.upsert_document_summary("Summarized hash", "Raguru cooks ragu.")
+            .await
+            .unwrap();
+
+        let summary = repo.get_document_summary("Summarized hash").await.unwrap();
+        assert_eq!(Some("Raguru cooks ragu."), summary.as_deref());
+    }
+
     #[test]
     async fn inserting_chunk_config_works(repo: Repository) {
         let doc = DocumentInsert::new(
@@ -968,7 +1096,7 @@ mod tests {
         );
         let doc = repo.insert_document(doc).await.unwrap();
         let chunker = ChunkConfig::sliding(420, 69).unwrap();
-        repo.upsert_document_chunk_config(doc.id, None, chunker.clone())
+        repo.upsert_document_chunk_config(doc.id, None, chunker.clone(), None)
             .await
             .unwrap();
         let config = repo
@@ -1058,7 +1186,7 @@ mod tests {
         .unwrap();
 
         let docs = repo
-            .list_documents(PaginationSort::default(), None, None)
+            .list_documents(PaginationSort::default(), None, None, false)
             .await
             .unwrap();
 
@@ -1066,7 +1194,7 @@ mod tests {
         assert_eq!(4, docs.total.unwrap());
 
         let docs = repo
-            .list_documents(PaginationSort::default(), None, Some(true))
+            .list_documents(PaginationSort::default(), None, Some(true), false)
             .await
             .unwrap();
 
@@ -1076,7 +1204,7 @@ mod tests {
         assert_eq!("/path/to/file/1/ready", docs.items[1].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), None, Some(false))
+            .list_documents(PaginationSort::default(), None, Some(false), false)
             .await
             .unwrap();
 
@@ -1086,7 +1214,7 @@ mod tests {
         assert_eq!("/path/to/file/1", docs.items[1].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), Some("fs"), Some(true))
+            .list_documents(PaginationSort::default(), Some("fs"), Some(true), false)
             .await
             .unwrap();
 
@@ -1095,7 +1223,7 @@ mod tests {
         assert_eq!("/path/to/file/1/ready", docs.items[0].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), Some("fs"), Some(false))
+            .list_documents(PaginationSort::default(), Some("fs"), Some(false), false)
             .await
             .unwrap();
 
@@ -1104,7 +1232,7 @@ mod tests {
         assert_eq!("/path/to/file/1", docs.items[0].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), Some("other"), Some(true))
+            .list_documents(PaginationSort::default(), Some("other"), Some(true), false)
             .await
             .unwrap();
 
@@ -1113,7 +1241,7 @@ mod tests {
         assert_eq!("/path/to/file/2/ready", docs.items[0].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), Some("other"), Some(false))
+            .list_documents(PaginationSort::default(), Some("other"), Some(false), false)
             .await
             .unwrap();
 
@@ -1122,7 +1250,7 @@ mod tests {
         assert_eq!("/path/to/file/2", docs.items[0].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), Some("fs"), None)
+            .list_documents(PaginationSort::default(), Some("fs"), None, false)
             .await
             .unwrap();
 
@@ -1132,7 +1260,7 @@ mod tests {
         assert_eq!("/path/to/file/1", docs.items[1].path);
 
         let docs = repo
-            .list_documents(PaginationSort::default(), Some("other"), None)
+            .list_documents(PaginationSort::default(), Some("other"), None, false)
             .await
             .unwrap();
 
@@ -1147,18 +1275,22 @@ mod tests {
             search: Some(Search {
                 q: "/1".to_string(),
                 column: DocumentSearchColumn::Path,
+                accent_insensitive: false,
             }),
             ..Default::default()
         };
 
-        let docs = repo.list_documents(pag.clone(), None, None).await.unwrap();
+        let docs = repo
+            .list_documents(pag.clone(), None, None, false)
+            .await
+            .unwrap();
 
         assert_eq!(2, docs.items.len());
         assert_eq!(2, docs.total.unwrap());
         assert_eq!("/path/to/file/1/ready", docs.items[0].path);
 
         let docs = repo
-            .list_documents(pag.clone(), None, Some(true))
+            .list_documents(pag.clone(), None, Some(true), false)
             .await
             .unwrap();
 
@@ -1167,7 +1299,7 @@ mod tests {
         assert_eq!("/path/to/file/1/ready", docs.items[0].path);
 
         let docs = repo
-            .list_documents(pag.clone(), None, Some(false))
+            .list_documents(pag.clone(), None, Some(false), false)
             .await
             .unwrap();
 
@@ -1178,7 +1310,7 @@ mod tests {
         // src + search check
 
         let docs = repo
-            .list_documents(pag.clone(), Some("fs"), Some(true))
+            .list_documents(pag.clone(), Some("fs"), Some(true), false)
             .await
             .unwrap();
 
@@ -1187,7 +1319,7 @@ mod tests {
         assert_eq!("/path/to/file/1/ready", docs.items[0].path);
 
         let docs = repo
-            .list_documents(pag.clone(), Some("fs"), Some(false))
+            .list_documents(pag.clone(), Some("fs"), Some(false), false)
             .await
             .unwrap();
 
@@ -1196,7 +1328,7 @@ mod tests {
         assert_eq!("/path/to/file/1", docs.items[0].path);
 
         let docs = repo
-            .list_documents(pag.clone(), Some("other"), Some(true))
+            .list_documents(pag.clone(), Some("other"), Some(true), false)
             .await
             .unwrap();
 
@@ -1204,7 +1336,7 @@ mod tests {
         assert_eq!(0, docs.total.unwrap());
 
         let docs = repo
-            .list_documents(pag.clone(), Some("other"), Some(false))
+            .list_documents(pag.clone(), Some("other"), Some(false), false)
             .await
             .unwrap();
 
@@ -1219,6 +1351,7 @@ mod tests {
                 },
                 None,
                 Some(true),
+                false,
             )
             .await
             .unwrap();
@@ -1235,6 +1368,7 @@ mod tests {
                 },
                 None,
                 Some(true),
+                false,
             )
             .await
             .unwrap();
@@ -1243,4 +1377,174 @@ mod tests {
         assert_eq!(2, docs.total.unwrap());
         assert_eq!("/path/to/file/1/ready", docs.items[0].path);
     }
+
+    #[test]
+    async fn accent_insensitive_search_works(repo: Repository) {
+        let names = ["Résumé de Raguru", "Crème brûlée"];
+
+        let mut ids = vec![];
+        for (i, name) in names.iter().enumerate() {
+            let path = format!("/path/to/unaccent/{i}");
+            let hash = format!("SHA256_UNACCENT_{i}");
+            let doc = DocumentInsert::new(
+                name,
+                &path,
+                DocumentType::Text(TextDocumentType::Txt),
+                &hash,
+                "unaccent",
+            );
+            ids.push(repo.insert_document(doc).await.unwrap().id);
+        }
+
+        let mut pag = PaginationSort {
+            search: Some(Search {
+                q: "resume".to_string(),
+                column: DocumentSearchColumn::Name,
+                accent_insensitive: false,
+            }),
+            ..Default::default()
+        };
+
+        let docs = repo
+            .list_documents(pag.clone(), Some("unaccent"), None, false)
+            .await
+            .unwrap();
+
+        assert!(docs.items.is_empty());
+        assert_eq!(0, docs.total.unwrap());
+
+        pag.search.as_mut().unwrap().accent_insensitive = true;
+
+        let docs = repo
+            .list_documents(pag.clone(), Some("unaccent"), None, false)
+            .await
+            .unwrap();
+
+        assert_eq!(1, docs.items.len());
+        assert_eq!(1, docs.total.unwrap());
+        assert_eq!("Résumé de Raguru", docs.items[0].name);
+
+        // Case is ignored as well
+        pag.search.as_mut().unwrap().q = "CREME BRULEE".to_string();
+
+        let docs = repo
+            .list_documents_with_collections(pag, Some("unaccent"))
+            .await
+            .unwrap();
+
+        assert_eq!(1, docs.items.len());
+        assert_eq!(1, docs.total.unwrap());
+        assert_eq!("Crème brûlée", docs.items[0].document.name);
+
+        for id in ids {
+            repo.remove_document_by_id(id, None).await.unwrap();
+        }
+    }
+
+    #[test]
+    async fn bulk_configuring_documents_makes_them_ready(repo: Repository) {
+        let mut ids = vec![];
+        for i in 0..3 {
+            let doc = repo
+                .insert_document(DocumentInsert::new(
+                    &format!("Bulk {i}"),
+                    &format!("/path/to/bulk/{i}"),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    &format!("BulkHash{i}"),
+                    "bulk",
+                ))
+                .await
+                .unwrap();
+            ids.push(doc.id);
+        }
+
+        let ready = |require_embeddings| {
+            let repo = repo.clone();
+            async move {
+                repo.list_documents(
+                    PaginationSort::default(),
+                    Some("bulk"),
+                    Some(true),
+                    require_embeddings,
+                )
+                .await
+                .unwrap()
+                .items
+            }
+        };
+
+        assert!(ready(false).await.is_empty());
+
+        for id in ids.iter().copied() {
+            repo.transaction(|tx| {
+                Box::pin(async move {
+                    repo.upsert_document_parse_config(
+                        id,
+                        None,
+                        ParseConfig::default(),
+                        Some(&mut *tx),
+                    )
+                    .await?;
+                    repo.upsert_document_chunk_config(
+                        id,
+                        None,
+                        ChunkConfig::snapping_default(),
+                        Some(tx),
+                    )
+                    .await?;
+                    Result::<(), ChonkitError>::Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        }
+
+        let mut ready_ids = ready(false)
+            .await
+            .into_iter()
+            .map(|doc| doc.id)
+            .collect::<Vec<_>>();
+        ready_ids.sort();
+        ids.sort();
+
+        assert_eq!(ids, ready_ids);
+
+        // Configuring again only replaces the configurations
+        repo.upsert_document_chunk_config(ids[0], None, ChunkConfig::snapping_default(), None)
+            .await
+            .unwrap();
+        assert_eq!(3, ready(false).await.len());
+
+        // Configured documents are not ready until embedded when embeddings are required
+        assert!(ready(true).await.is_empty());
+
+        let collection = repo
+            .insert_collection(
+                CollectionInsert::new("Bulk", "model", "embedder", "qdrant"),
+                None,
+            )
+            .await
+            .unwrap();
+        repo.insert_text_embeddings(TextEmbeddingInsert::new(ids[0], collection.id), None)
+            .await
+            .unwrap();
+
+        let ready_ids = ready(true)
+            .await
+            .into_iter()
+            .map(|doc| doc.id)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![ids[0]], ready_ids);
+
+        let not_ready = repo
+            .list_documents(PaginationSort::default(), Some("bulk"), Some(false), true)
+            .await
+            .unwrap();
+        assert_eq!(2, not_ready.items.len());
+        assert_eq!(2, not_ready.total.unwrap());
+
+        for id in ids {
+            repo.remove_document_by_id(id, None).await.unwrap();
+        }
+    }
 }
@@ -2,9 +2,10 @@ use crate::{
     core::{
         model::{
             embedding::{
-                EmbeddingReport, ImageEmbedding, ImageEmbeddingAdditionReport,
-                ImageEmbeddingInsert, ImageEmbeddingRemovalReport, TextEmbedding,
-                TextEmbeddingAdditionReport, TextEmbeddingInsert, TextEmbeddingRemovalReport,
+                EmbeddingReport, EmbeddingReportAction, EmbeddingRequest, ImageEmbedding,
+                ImageEmbeddingAdditionReport, ImageEmbeddingInsert, ImageEmbeddingRemovalReport,
+                TextEmbedding, TextEmbeddingAdditionReport, TextEmbeddingInsert,
+                TextEmbeddingRemovalReport,
             },
             List, Pagination,
         },
@@ -14,9 +15,69 @@ use crate::{
     error::ChonkitError,
     map_err,
 };
-use sqlx::Postgres;
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
 use uuid::Uuid;
 
+/// Selects addition reports in the shape expected by [EmbeddingReport]'s `FromRow`.
+const SELECT_ADDITION_REPORTS: &str = r#"
+    SELECT 
+        'addition' as "report_type",
+        id, 
+        type,
+        collection_id,
+        collection_name, 
+        vector_db,
+
+        model_used,
+        embedding_provider,
+        total_vectors,
+        tokens_used,
+        cache,
+        chunk_fallback,
+
+        started_at,
+        finished_at,
+
+        -- Document fields
+        document_id,
+        document_name,
+
+        -- Image fields
+        image_id
+    FROM embedding_reports"#;
+
+/// Selects removal reports in the shape expected by [EmbeddingReport]'s `FromRow`.
+/// Columns only applicable to additions are `NULL`.
+const SELECT_REMOVAL_REPORTS: &str = r#"
+    SELECT 
+        'removal' as "report_type",
+        id, 
+        type,
+        collection_id,
+        collection_name, 
+        vector_db,
+
+        -- Fields only applicable to addition
+        NULL as model_used,
+        NULL as embedding_provider,
+        NULL as total_vectors,
+        NULL as tokens_used,
+        NULL as cache,
+        NULL as chunk_fallback,
+
+        started_at,
+        finished_at,
+
+        -- Document fields
+        document_id,
+        document_name,
+
+        -- Image fields
+        image_id
+
+    FROM embedding_removal_reports"#;
+
 impl Repository {
     pub async fn insert_text_embeddings(
         &self,
@@ -342,11 +403,12 @@ impl Repository {
                     total_vectors,
                     tokens_used,
                     cache,
+                    chunk_fallback,
                     started_at,
                     finished_at,
                     type
                 ) 
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, 'text')
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, 'text')
             "#,
                 report.report.base.collection_id,
                 report.report.base.collection_name,
@@ -358,6 +420,7 @@ impl Repository {
                 report.report.total_vectors,
                 report.report.tokens_used.map(|t| t as i32),
                 report.report.cache,
+                report.chunk_fallback,
                 report.report.base.started_at,
                 report.report.base.finished_at,
             )
@@ -410,98 +473,26 @@ impl Repository {
             .map(|p| p.to_limit_offset())
             .unwrap_or_else(|| Pagination::default().to_limit_offset());
 
-        let mut query = sqlx::query_builder::QueryBuilder::<Postgres>::new(
-            r#"
-                SELECT 
-                    'addition' as "report_type",
-                    id, 
-                    type,
-                    collection_id,
-                    collection_name, 
-                    vector_db,
-
-                    model_used,
-                    embedding_provider,
-                    total_vectors,
-                    tokens_used,
-                    cache,
+        let additions = params.report_type != Some(EmbeddingReportAction::Removal);
+        let removals = params.report_type != Some(EmbeddingReportAction::Addition);
 
-                    started_at,
-                    finished_at,
+        let mut query = QueryBuilder::<Postgres>::new("");
 
-                    -- Document fields
-                    document_id,
-                    document_name,
-
-                    -- Image fields
-                    image_id
-                FROM embedding_reports"#,
-        );
-
-        let mut removal_query = sqlx::query_builder::QueryBuilder::<Postgres>::new(
-            r#"
-                SELECT 
-                    'removal' as "report_type",
-                    id, 
-                    type,
-                    collection_id,
-                    collection_name, 
-                    vector_db,
-
-                    -- Fields only applicable to addition
-                    NULL as model_used,
-                    NULL as embedding_provider,
-                    NULL as total_vectors,
-                    NULL as tokens_used,
-                    NULL as cache,
-
-                    started_at,
-                    finished_at,
-
-                    -- Document fields
-                    document_id,
-                    document_name,
-
-                    -- Image fields
-                    image_id
+        if additions {
+            query.push(SELECT_ADDITION_REPORTS);
+            push_report_filters(&mut query, &params);
+        }
 
-                FROM embedding_removal_reports
-            "#,
-        );
+        if additions && removals {
+            query.push(" UNION ");
+        }
 
-        match (params.collection, params.document) {
-            (Some(collection_id), Some(document_id)) => {
-                query
-                    .push(" WHERE collection_id = ")
-                    .push_bind(collection_id)
-                    .push(" AND document_id = ")
-                    .push_bind(document_id);
-                removal_query
-                    .push(" WHERE collection_id = ")
-                    .push_bind(collection_id)
-                    .push(" AND document_id = ")
-                    .push_bind(document_id);
-            }
-            (Some(collection_id), None) => {
-                query
-                    .push(" WHERE collection_id = ")
-                    .push_bind(collection_id);
-                removal_query
-                    .push(" WHERE collection_id = ")
-                    .push_bind(collection_id);
-            }
-            (None, Some(document_id)) => {
-                query.push(" WHERE document_id = ").push_bind(document_id);
-                removal_query
-                    .push(" WHERE document_id = ")
-                    .push_bind(document_id);
-            }
-            (None, None) => {}
+        if removals {
+            query.push(SELECT_REMOVAL_REPORTS);
+            push_report_filters(&mut query, &params);
         }
 
         query
-            .push(" UNION ")
-            .push(removal_query.sql())
             .push(" ORDER BY finished_at DESC")
             .push(" LIMIT ")
             .push_bind(limit)
@@ -512,4 +503,536 @@ impl Repository {
             query.build_query_as().fetch_all(&self.client).await
         ))
     }
+
+    /// Get an addition or removal report by its ID. Since additions and removals are stored
+    /// separately their IDs overlap, so the `action` determines which one is returned.
+    pub async fn get_embedding_report(
+        &self,
+        id: i32,
+        action: EmbeddingReportAction,
+    ) -> Result<Option<EmbeddingReport>, ChonkitError> {
+        let mut query = QueryBuilder::<Postgres>::new(match action {
+            EmbeddingReportAction::Addition => SELECT_ADDITION_REPORTS,
+            EmbeddingReportAction::Removal => SELECT_REMOVAL_REPORTS,
+        });
+
+        query.push(" WHERE id = ").push_bind(id);
+
+        Ok(map_err!(
+            query.build_query_as().fetch_optional(&self.client).await
+        ))
+    }
+
+    pub async fn insert_embedding_request(
+        &self,
+        request: &EmbeddingRequest,
+    ) -> Result<(), ChonkitError> {
+        map_err!(
+            sqlx::query!(
+                r#"
+                INSERT INTO embedding_requests(
+                    type,
+                    embedding_provider,
+                    model,
+                    inputs,
+                    input_tokens,
+                    response_tokens,
+                    latency_ms,
+                    error,
+                    requested_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+                request.kind.as_str(),
+                request.provider,
+                request.model,
+                request.inputs as i32,
+                request.input_tokens.map(|t| t as i32),
+                request.response_tokens.map(|t| t as i32),
+                request.latency_ms as i32,
+                request.error,
+                request.requested_at,
+            )
+            .execute(&self.client)
+            .await
+        );
+
+        Ok(())
+    }
+
+    /// Delete all addition and removal reports that finished before `cutoff`.
+    /// Returns the amount of deleted reports.
+    pub async fn delete_reports_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, ChonkitError> {
+        self.transaction(|tx| {
+            Box::pin(async move {
+                let additions = map_err!(
+                    sqlx::query!(
+                        "DELETE FROM embedding_reports WHERE finished_at < $1",
+                        cutoff
+                    )
+                    .execute(&mut **tx)
+                    .await
+                );
+
+                let removals = map_err!(
+                    sqlx::query!(
+                        "DELETE FROM embedding_removal_reports WHERE finished_at < $1",
+                        cutoff
+                    )
+                    .execute(&mut **tx)
+                    .await
+                );
+
+                Ok(additions.rows_affected() + removals.rows_affected())
+            })
+        })
+        .await
+    }
+}
+
+/// Push the `WHERE` clause for listing embedding reports. Both report tables share
+/// the filtered columns so this is applied to each side of the `UNION`.
+fn push_report_filters(
+    query: &mut QueryBuilder<'_, Postgres>,
+    params: &ListEmbeddingReportsParams,
+) {
+    let ListEmbeddingReportsParams {
+        collection,
+        document,
+        from,
+        to,
+        kind,
+        ..
+    } = params;
+
+    if collection.is_none()
+        && document.is_none()
+        && from.is_none()
+        && to.is_none()
+        && kind.is_none()
+    {
+        return;
+    }
+
+    query.push(" WHERE ");
+
+    let mut filters = query.separated(" AND ");
+
+    if let Some(collection_id) = collection {
+        filters
+            .push("collection_id = ")
+            .push_bind_unseparated(*collection_id);
+    }
+
+    if let Some(document_id) = document {
+        filters
+            .push("document_id = ")
+            .push_bind_unseparated(*document_id);
+    }
+
+    if let Some(from) = from {
+        filters.push("finished_at >= ").push_bind_unseparated(*from);
+    }
+
+    if let Some(to) = to {
+        filters.push("finished_at <= ").push_bind_unseparated(*to);
+    }
+
+    if let Some(kind) = kind {
+        filters.push("type = ").push_bind_unseparated(kind.as_str());
+    }
+}
+
+#[cfg(test)]
+#[suitest::suite(pg_embedding_repo_int)]
+#[suitest::suite_cfg(sequential = true)]
+mod tests {
+    use crate::{
+        app::test::{init_repository, PostgresContainer},
+        core::{
+            document::{DocumentType, TextDocumentType},
+            model::{
+                document::DocumentInsert,
+                embedding::{
+                    EmbeddingAdditionReport, EmbeddingReportAction, EmbeddingReportBase,
+                    EmbeddingReportKind, EmbeddingReportType, EmbeddingRequest,
+                    ImageEmbeddingAdditionReport, TextEmbeddingAdditionReport,
+                    TextEmbeddingRemovalReport,
+                },
+                image::InsertImage,
+            },
+            repo::Repository,
+            service::embedding::ListEmbeddingReportsParams,
+        },
+    };
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use suitest::before_all;
+    use uuid::Uuid;
+
+    #[before_all]
+    async fn setup() -> (Repository, PostgresContainer, Vec<DateTime<Utc>>) {
+        let (repo, pg_img) = init_repository().await;
+
+        let document = repo
+            .insert_document(DocumentInsert::new(
+                "Reported",
+                "/path/to/reported",
+                DocumentType::Text(TextDocumentType::Txt),
+                "ReportedHash",
+                "fs",
+            ))
+            .await
+            .unwrap();
+
+        let image = repo
+            .insert_image(
+                InsertImage {
+                    path: "reported.png",
+                    hash: "ReportedImageHash",
+                    src: "minio",
+                    format: "png",
+                    width: 1,
+                    height: 1,
+                    description: None,
+                    document_id: None,
+                    page_number: None,
+                    image_number: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        // One report per day, starting at 2025-01-01
+        let times = (0..4)
+            .map(|day| Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::days(day))
+            .collect::<Vec<_>>();
+
+        let base = |at: DateTime<Utc>| EmbeddingReportBase {
+            collection_id: None,
+            collection_name: "Reports".to_string(),
+            vector_db: "qdrant".to_string(),
+            started_at: at,
+            finished_at: at,
+        };
+
+        let addition = |at: DateTime<Utc>| EmbeddingAdditionReport {
+            model_used: "model".to_string(),
+            tokens_used: None,
+            embedding_provider: "provider".to_string(),
+            total_vectors: 1,
+            cache: false,
+            base: base(at),
+        };
+
+        repo.insert_text_embedding_report(&TextEmbeddingAdditionReport {
+            document_id: document.id,
+            document_name: document.name.clone(),
+            report: addition(times[0]),
+            chunk_tokens: None,
+            chunk_fallback: false,
+        })
+        .await
+        .unwrap();
+
+        repo.insert_image_embedding_report(&ImageEmbeddingAdditionReport {
+            image_id: image.id,
+            report: addition(times[1]),
+        })
+        .await
+        .unwrap();
+
+        repo.insert_text_embedding_removal_report(&TextEmbeddingRemovalReport {
+            document_id: document.id,
+            document_name: document.name.clone(),
+            report: base(times[2]),
+        })
+        .await
+        .unwrap();
+
+        repo.insert_text_embedding_report(&TextEmbeddingAdditionReport {
+            document_id: document.id,
+            document_name: document.name,
+            report: addition(times[3]),
+            chunk_tokens: None,
+            chunk_fallback: false,
+        })
+        .await
+        .unwrap();
+
+        (repo, pg_img, times)
+    }
+
+    #[test]
+    async fn listing_reports_works(repo: Repository) {
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams::default())
+            .await
+            .unwrap();
+
+        assert_eq!(4, reports.len());
+        assert!(matches!(
+            reports[0].report,
+            EmbeddingReportType::TextAddition(_)
+        ));
+        assert!(matches!(
+            reports[3].report,
+            EmbeddingReportType::TextAddition(_)
+        ));
+    }
+
+    #[test]
+    async fn listing_reports_by_time_range_works(repo: Repository, times: Vec<DateTime<Utc>>) {
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                from: Some(times[1]),
+                to: Some(times[2]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(2, reports.len());
+        assert!(matches!(
+            reports[0].report,
+            EmbeddingReportType::TextRemoval(_)
+        ));
+        assert!(matches!(
+            reports[1].report,
+            EmbeddingReportType::ImageAddition(_)
+        ));
+
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                from: Some(times[3] + Duration::seconds(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    async fn listing_reports_by_type_works(repo: Repository, times: Vec<DateTime<Utc>>) {
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                report_type: Some(EmbeddingReportAction::Removal),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(1, reports.len());
+        assert!(matches!(
+            reports[0].report,
+            EmbeddingReportType::TextRemoval(_)
+        ));
+
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                report_type: Some(EmbeddingReportAction::Addition),
+                kind: Some(EmbeddingReportKind::Text),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(2, reports.len());
+        assert!(reports
+            .iter()
+            .all(|r| matches!(r.report, EmbeddingReportType::TextAddition(_))));
+
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                kind: Some(EmbeddingReportKind::Image),
+                to: Some(times[3]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(1, reports.len());
+        assert!(matches!(
+            reports[0].report,
+            EmbeddingReportType::ImageAddition(_)
+        ));
+
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                document: Some(Uuid::new_v4()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    async fn getting_report_by_id_works(repo: Repository) {
+        let removals = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                report_type: Some(EmbeddingReportAction::Removal),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let id = removals[0].id;
+
+        let report = repo
+            .get_embedding_report(id, EmbeddingReportAction::Removal)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(id, report.id);
+        let EmbeddingReportType::TextRemoval(report) = report.report else {
+            panic!("expected text removal report, got {:?}", report.report);
+        };
+        assert_eq!("Reported", report.document_name);
+
+        let additions = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                report_type: Some(EmbeddingReportAction::Addition),
+                kind: Some(EmbeddingReportKind::Image),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let report = repo
+            .get_embedding_report(additions[0].id, EmbeddingReportAction::Addition)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(
+            report.report,
+            EmbeddingReportType::ImageAddition(_)
+        ));
+
+        let missing = repo
+            .get_embedding_report(i32::MAX, EmbeddingReportAction::Addition)
+            .await
+            .unwrap();
+
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    async fn deleting_old_reports_keeps_recent_ones(repo: Repository, times: Vec<DateTime<Utc>>) {
+        let document = repo
+            .insert_document(DocumentInsert::new(
+                "Cleanup",
+                "/path/to/cleanup",
+                DocumentType::Text(TextDocumentType::Txt),
+                "CleanupHash",
+                "fs",
+            ))
+            .await
+            .unwrap();
+
+        // Everything reported in the setup is newer than the cutoff
+        let cutoff = times[0] - Duration::days(30);
+        let old = times[0] - Duration::days(60);
+
+        let base = || EmbeddingReportBase {
+            collection_id: None,
+            collection_name: "Cleanup".to_string(),
+            vector_db: "qdrant".to_string(),
+            started_at: old,
+            finished_at: old,
+        };
+
+        repo.insert_text_embedding_report(&TextEmbeddingAdditionReport {
+            document_id: document.id,
+            document_name: document.name.clone(),
+            report: EmbeddingAdditionReport {
+                model_used: "model".to_string(),
+                tokens_used: None,
+                embedding_provider: "provider".to_string(),
+                total_vectors: 1,
+                cache: false,
+                base: base(),
+            },
+            chunk_tokens: None,
+            chunk_fallback: false,
+        })
+        .await
+        .unwrap();
+
+        repo.insert_text_embedding_removal_report(&TextEmbeddingRemovalReport {
+            document_id: document.id,
+            document_name: document.name,
+            report: base(),
+        })
+        .await
+        .unwrap();
+
+        let deleted = repo.delete_reports_older_than(cutoff).await.unwrap();
+
+        assert_eq!(2, deleted);
+
+        let reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams::default())
+            .await
+            .unwrap();
+
+        assert_eq!(4, reports.len());
+
+        let old_reports = repo
+            .list_collection_embedding_reports(ListEmbeddingReportsParams {
+                to: Some(cutoff),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(old_reports.is_empty());
+    }
+
+    #[test]
+    async fn inserting_embedding_requests_works(repo: Repository) {
+        let request = |error: Option<&str>| EmbeddingRequest {
+            provider: "audited".to_string(),
+            model: "model".to_string(),
+            kind: EmbeddingReportKind::Text,
+            inputs: 5,
+            input_tokens: Some(20),
+            response_tokens: error.is_none().then_some(21),
+            latency_ms: 150,
+            error: error.map(String::from),
+            requested_at: Utc::now(),
+        };
+
+        repo.insert_embedding_request(&request(None)).await.unwrap();
+        repo.insert_embedding_request(&request(Some("rate limited")))
+            .await
+            .unwrap();
+
+        let rows = sqlx::query!(
+            "SELECT type, inputs, input_tokens, response_tokens, latency_ms, error
+             FROM embedding_requests WHERE embedding_provider = 'audited' ORDER BY id"
+        )
+        .fetch_all(&repo.client)
+        .await
+        .unwrap();
+
+        assert_eq!(2, rows.len());
+
+        assert_eq!("text", rows[0].r#type);
+        assert_eq!(5, rows[0].inputs);
+        assert_eq!(Some(20), rows[0].input_tokens);
+        assert_eq!(Some(21), rows[0].response_tokens);
+        assert_eq!(150, rows[0].latency_ms);
+        assert!(rows[0].error.is_none());
+
+        assert_eq!(None, rows[1].response_tokens);
+        assert_eq!(Some("rate limited"), rows[1].error.as_deref());
+    }
 }
@@ -244,3 +244,146 @@ impl Repository {
         Ok(())
     }
 }
+
+#[cfg(test)]
+#[suitest::suite(pg_image_repo_int)]
+#[suitest::suite_cfg(sequential = true)]
+mod tests {
+    use crate::{
+        app::test::{init_repository, PostgresContainer},
+        core::{
+            document::{DocumentType, TextDocumentType},
+            model::{
+                collection::CollectionInsert, document::DocumentInsert,
+                embedding::ImageEmbeddingInsert, image::InsertImage,
+            },
+            repo::Repository,
+        },
+    };
+    use suitest::before_all;
+    use uuid::Uuid;
+
+    #[before_all]
+    async fn setup() -> (Repository, PostgresContainer) {
+        let (repository, pg_img) = init_repository().await;
+        (repository, pg_img)
+    }
+
+    #[test]
+    async fn listing_document_images_with_collections_works(repo: Repository) {
+        let document = repo
+            .insert_document(DocumentInsert::new(
+                "Illustrated",
+                "/path/to/illustrated",
+                DocumentType::Text(TextDocumentType::Txt),
+                "IllustratedHash",
+                "fs",
+            ))
+            .await
+            .unwrap();
+
+        let insert = |path, hash, page_number| InsertImage {
+            path,
+            hash,
+            src: "fs",
+            format: "png",
+            width: 2,
+            height: 1,
+            description: None,
+            document_id: Some(document.id),
+            page_number: Some(page_number),
+            image_number: Some(0),
+        };
+
+        // Inserted out of order to check the images are listed by page
+        let second = repo
+            .insert_image(insert("second.png", "SecondHash", 2), None)
+            .await
+            .unwrap();
+        let first = repo
+            .insert_image(insert("first.png", "FirstHash", 1), None)
+            .await
+            .unwrap();
+
+        let mut collections = vec![];
+        for name in ["Images", "MoreImages"] {
+            collections.push(
+                repo.insert_collection(
+                    CollectionInsert::new(name, "model", "embedder", "qdrant"),
+                    None,
+                )
+                .await
+                .unwrap(),
+            );
+        }
+
+        for collection in &collections {
+            repo.insert_image_embeddings(ImageEmbeddingInsert::new(first.id, collection.id), None)
+                .await
+                .unwrap();
+        }
+
+        repo.insert_image_embeddings(
+            ImageEmbeddingInsert::new(second.id, collections[1].id),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let images = repo
+            .list_all_document_images(document.id, "fs")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![first.id, second.id],
+            images.iter().map(|i| i.id).collect::<Vec<_>>()
+        );
+        assert_eq!((2, 1), (images[0].width, images[0].height));
+
+        // Images from other storage providers are not listed
+        assert!(repo
+            .list_all_document_images(document.id, "minio")
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut first_collections = repo.get_image_assigned_collections(first.id).await.unwrap();
+        first_collections.sort_by(|a, b| a.1.cmp(&b.1));
+
+        assert_eq!(
+            vec![
+                (
+                    collections[0].id,
+                    "Images".to_string(),
+                    "qdrant".to_string()
+                ),
+                (
+                    collections[1].id,
+                    "MoreImages".to_string(),
+                    "qdrant".to_string()
+                ),
+            ],
+            first_collections
+        );
+
+        let second_collections = repo
+            .get_image_assigned_collections(second.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![(
+                collections[1].id,
+                "MoreImages".to_string(),
+                "qdrant".to_string()
+            )],
+            second_collections
+        );
+
+        assert!(repo
+            .get_image_assigned_collections(Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    core::vector::{CollectionItemPayload, CollectionSearchItem},
+    err,
+    error::ChonkitError,
+};
+use std::cmp::Ordering;
+
+/// Scores the relevance of documents to a query with a model looking at both at once, e.g. a
+/// cross-encoder. More accurate than comparing vectors, but too slow to run on a whole
+/// collection, so it is only used for reordering the results of the vector search.
+#[async_trait::async_trait]
+pub trait Reranker {
+    /// Score the relevance of every one of the `documents` to the `query`. The scores are
+    /// returned in the order of the documents, higher scores being more relevant.
+    ///
+    /// * `model`: The reranking model.
+    /// * `query`: The search query.
+    /// * `documents`: The contents to score.
+    async fn rerank(
+        &self,
+        model: &str,
+        query: &str,
+        documents: &[&str],
+    ) -> Result<Vec<f64>, ChonkitError>;
+}
+
+/// Reorder the search items by the scores the reranker gives their contents, most relevant
+/// first. Items without text, e.g. images, cannot be scored and are kept after the rest in
+/// their original order.
+pub async fn rerank_search_items(
+    reranker: &(dyn Reranker + Send + Sync),
+    model: &str,
+    query: &str,
+    items: Vec<CollectionSearchItem>,
+) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+    let documents = items
+        .iter()
+        .filter_map(|item| match item.item.payload {
+            CollectionItemPayload::Text { ref content, .. } => Some(content.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if documents.is_empty() {
+        return Ok(items);
+    }
+
+    let scores = reranker.rerank(model, query, &documents).await?;
+
+    if scores.len() != documents.len() {
+        return err!(
+            Rerank,
+            "expected {} scores, got {}",
+            documents.len(),
+            scores.len()
+        );
+    }
+
+    let (mut text, rest): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|item| matches!(item.item.payload, CollectionItemPayload::Text { .. }));
+
+    for (item, score) in text.iter_mut().zip(scores) {
+        item.rerank_score = Some(score);
+    }
+
+    // Stable, so equally scored items keep the order of the vector search
+    text.sort_by(|a, b| match (a.rerank_score, b.rerank_score) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        _ => Ordering::Equal,
+    });
+
+    text.extend(rest);
+
+    Ok(text)
+}
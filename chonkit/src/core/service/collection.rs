@@ -1,28 +1,151 @@
+use crate::config::{
+    DEFAULT_ASK_CONTEXT_TOKENS, DEFAULT_MAX_PAGE_SIZE, DEFAULT_MAX_VECTOR_SIZE,
+    DEFAULT_SEARCH_LIMIT, DEFAULT_SEARCH_TIMEOUT,
+};
+use crate::core::chunk::validate_chunk_template;
+use crate::core::llm::{self, Llm, QueryRewrite};
 use crate::core::model::collection::{
-    Collection, CollectionDisplay, CollectionDisplayAggregate, CollectionInsert,
-    CollectionSearchColumn,
+    Collection, CollectionAlias, CollectionDisplay, CollectionDisplayAggregate, CollectionFilter,
+    CollectionInsert, CollectionSearchColumn,
 };
+use crate::core::model::embedding::TextEmbeddingInsert;
 use crate::core::model::{List, PaginationSort};
 use crate::core::provider::ProviderState;
 use crate::core::repo::Repository;
-use crate::core::vector::CreateVectorCollection;
-use crate::error::ChonkitError;
+use crate::core::rerank::{rerank_search_items, Reranker};
+use crate::core::token::Tokenizer;
+use crate::core::vector::{
+    adapt_vector_dimension, deduplicate_search_items, highlight_search_items,
+    query_all_with_timeout, query_with_timeout, replace_with_parents, score_search_items,
+    sort_search_items, validate_vector, CollectionItemInsert, CollectionItemInsertPayload,
+    CollectionItemPayload, CollectionItemText, CreateVectorCollection, DistanceMetric,
+    ExportedVector, VectorDb, VectorQuery,
+};
+use crate::error::{ChonkitErr, ChonkitError};
 use crate::{err, map_err};
-use dto::{CollectionData, CreateCollectionPayload, SearchPayload, SyncIncompatibilityResolution};
+use dto::{
+    AskEvent, AskPayload, Citation, CollectionData, CollectionImportResult, CollectionManifest,
+    CollectionMetric, CollectionModality, CreateCollectionPayload, MultiSearchItem,
+    MultiSearchPayload, SearchEvent, SearchPayload, SetAliasPayload, SyncIncompatibilityResolution,
+};
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tracing::info;
 use uuid::Uuid;
 use validify::{Validate, Validify};
 
+/// Amount of vectors fetched from the vector database at a time when exporting collections.
+const EXPORT_PAGE_SIZE: u32 = 256;
+
+/// Maximum amount of vectors inserted to the vector database at a time when importing
+/// collections.
+const IMPORT_BATCH_SIZE: usize = 256;
+
+/// How many times the limit of vectors is queried when search results are deduplicated, so
+/// the remaining results can still fill the limit.
+const DEDUP_CANDIDATE_FACTOR: u32 = 4;
+
+/// How many times the limit of vectors is queried when search results are reranked, if the
+/// amount of candidates is not specified.
+const RERANK_CANDIDATE_FACTOR: u32 = 4;
+
+/// The amount of documents found by their summaries that two-stage searches narrow the chunk
+/// search to, if not specified.
+const DEFAULT_SUMMARY_DOCUMENTS: u32 = 5;
+
 /// High level operations related to collections.
 #[derive(Clone)]
 pub struct CollectionService {
     repo: Repository,
     providers: ProviderState,
+
+    /// Amount of results returned by searches that do not specify a limit.
+    search_limit: u32,
+
+    /// Distance threshold applied to searches that do not specify one.
+    search_max_distance: Option<f64>,
+
+    /// How long searches that do not specify a timeout wait for the vector database.
+    search_timeout: Duration,
+
+    /// Used for rewriting search queries and answering questions. Searches requesting a
+    /// rewrite and questions fail without one.
+    llm: Option<Arc<dyn Llm + Send + Sync>>,
+
+    /// Used for reordering search results. Searches requesting a rerank fail without one.
+    reranker: Option<Arc<dyn Reranker + Send + Sync>>,
+
+    /// Used for limiting the context given to the LLM when answering questions.
+    tokenizer: Tokenizer,
+
+    /// Token budget of the context of questions that do not specify one.
+    ask_context_tokens: usize,
+
+    /// Listings requesting larger pages, or no pages at all, are clamped to this.
+    max_page_size: usize,
+
+    /// Collections of models reporting a larger vector size are not created.
+    max_vector_size: usize,
 }
 
 impl CollectionService {
-    pub fn new(repo: Repository, providers: ProviderState) -> Self {
-        Self { repo, providers }
+    pub fn new(repo: Repository, providers: ProviderState, tokenizer: Tokenizer) -> Self {
+        Self {
+            repo,
+            providers,
+            search_limit: DEFAULT_SEARCH_LIMIT,
+            search_max_distance: None,
+            search_timeout: Duration::from_millis(DEFAULT_SEARCH_TIMEOUT),
+            llm: None,
+            reranker: None,
+            tokenizer,
+            ask_context_tokens: DEFAULT_ASK_CONTEXT_TOKENS,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            max_vector_size: DEFAULT_MAX_VECTOR_SIZE,
+        }
+    }
+
+    /// Set the maximum amount of items returned by a single page of listings.
+    pub fn with_max_page_size(mut self, max_page_size: usize) -> Self {
+        self.max_page_size = max_page_size;
+        self
+    }
+
+    /// Set the maximum vector size of the models of new collections.
+    pub fn with_max_vector_size(mut self, max_vector_size: usize) -> Self {
+        self.max_vector_size = max_vector_size;
+        self
+    }
+
+    /// Set the limit and distance threshold used for searches that omit them.
+    pub fn with_search_defaults(mut self, limit: u32, max_distance: Option<f64>) -> Self {
+        self.search_limit = limit;
+        self.search_max_distance = max_distance;
+        self
+    }
+
+    /// Set how long searches that omit a timeout wait for the vector database.
+    pub fn with_search_timeout(mut self, timeout: Duration) -> Self {
+        self.search_timeout = timeout;
+        self
+    }
+
+    /// Set the LLM used for rewriting search queries and answering questions.
+    pub fn with_llm(mut self, llm: Arc<dyn Llm + Send + Sync>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    /// Set the reranker used for reordering search results.
+    pub fn with_reranker(mut self, reranker: Arc<dyn Reranker + Send + Sync>) -> Self {
+        self.reranker = Some(reranker);
+        self
+    }
+
+    /// Set the token budget of the context used for questions that omit it.
+    pub fn with_ask_context_tokens(mut self, tokens: usize) -> Self {
+        self.ask_context_tokens = tokens;
+        self
     }
 }
 
@@ -30,20 +153,33 @@ impl CollectionService {
     /// List vector collections.
     ///
     /// * `p`: Pagination params.
+    /// * `filter`: Only list the collections matching the filter.
     pub async fn list_collections(
         &self,
         p: PaginationSort<CollectionSearchColumn>,
+        filter: CollectionFilter<'_>,
     ) -> Result<List<Collection>, ChonkitError> {
         map_err!(p.validate());
-        self.repo.list_collections(p).await
+        let p = p.clamp(self.max_page_size);
+        let pagination = p.pagination();
+        self.repo
+            .list_collections(p, filter)
+            .await
+            .map(|list| list.paginated(pagination))
     }
 
     pub async fn list_collections_display(
         &self,
         p: PaginationSort<CollectionSearchColumn>,
+        filter: CollectionFilter<'_>,
     ) -> Result<List<CollectionDisplay>, ChonkitError> {
         map_err!(p.validate());
-        self.repo.list_collections_display(p).await
+        let p = p.clamp(self.max_page_size);
+        let pagination = p.pagination();
+        self.repo
+            .list_collections_display(p, filter)
+            .await
+            .map(|list| list.paginated(pagination))
     }
 
     /// Get the collection for the given ID.
@@ -123,8 +259,16 @@ impl CollectionService {
             vector_provider,
             embedding_provider,
             groups,
+            modality,
+            chunk_template,
+            tags,
+            insert_dedup_threshold,
         } = data;
 
+        if let Some(ref template) = chunk_template {
+            validate_chunk_template(template)?;
+        }
+
         let vector_db = self.providers.vector.get_provider(&vector_provider)?;
         let embedder = self.providers.embedding.get_provider(&embedding_provider)?;
 
@@ -136,6 +280,23 @@ impl CollectionService {
             );
         };
 
+        // Misconfigured models would otherwise create collections that fail on every insert
+        if model_details.size == 0 || model_details.size > self.max_vector_size {
+            return err!(
+                InvalidEmbeddingModel,
+                "Model {model} reports a vector size of {}, expected between 1 and {}",
+                model_details.size,
+                self.max_vector_size
+            );
+        }
+
+        if modality == CollectionModality::Multimodal && !model_details.multimodal {
+            return err!(
+                InvalidEmbeddingModel,
+                "Model {model} is not multimodal and cannot be used for image collections"
+            );
+        }
+
         info!(
             "Creating collection '{name}' of size '{}'",
             model_details.size
@@ -145,7 +306,11 @@ impl CollectionService {
             .transaction(|tx| {
                 Box::pin(async move {
                     let insert =
-                        CollectionInsert::new(&name, &model, embedder.id(), vector_db.id());
+                        CollectionInsert::new(&name, &model, embedder.id(), vector_db.id())
+                            .with_multimodal(model_details.multimodal)
+                            .with_chunk_template(chunk_template.as_deref())
+                            .with_tags(tags.as_deref())
+                            .with_insert_dedup_threshold(insert_dedup_threshold);
                     let collection = self.repo.insert_collection(insert, Some(tx)).await?;
 
                     let data = CreateVectorCollection::new(
@@ -165,6 +330,51 @@ impl CollectionService {
             .await
     }
 
+    /// Get the configuration of a collection as a manifest, from which an equivalent
+    /// collection can be created with [create_collection_from_manifest][Self::create_collection_from_manifest].
+    ///
+    /// * `id`: Collection ID.
+    pub async fn get_collection_manifest(
+        &self,
+        id: Uuid,
+    ) -> Result<CollectionManifest, ChonkitError> {
+        let CollectionData {
+            collection,
+            vector_collection,
+        } = self.get_collection(id).await?;
+
+        let modality = if collection.multimodal.unwrap_or(false) {
+            CollectionModality::Multimodal
+        } else {
+            CollectionModality::Text
+        };
+
+        Ok(CollectionManifest {
+            name: collection.name,
+            model: collection.model,
+            vector_provider: collection.provider,
+            embedding_provider: collection.embedder,
+            metric: CollectionMetric::Cosine,
+            groups: vector_collection.groups,
+            modality,
+            chunk_template: collection.chunk_template,
+            tags: collection.tags,
+            insert_dedup_threshold: collection.insert_dedup_threshold,
+        })
+    }
+
+    /// Create an empty collection from a manifest obtained with
+    /// [get_collection_manifest][Self::get_collection_manifest]. The manifest is validated
+    /// the same as the payload of [create_collection][Self::create_collection].
+    ///
+    /// * `manifest`: The configuration of the collection.
+    pub async fn create_collection_from_manifest(
+        &self,
+        manifest: CollectionManifest,
+    ) -> Result<Collection, ChonkitError> {
+        self.create_collection(manifest.into()).await
+    }
+
     /// Delete a vector collection and all its corresponding embedding entries.
     /// It is assumed the vector provider has a collection with the name
     /// equal to the one found in the collection with the given ID.
@@ -195,6 +405,70 @@ impl CollectionService {
         Ok(())
     }
 
+    /// Replace the tags of a collection.
+    ///
+    /// * `id`: Collection ID.
+    /// * `tags`: The new tags, `None` removes them.
+    pub async fn update_collection_tags(
+        &self,
+        id: Uuid,
+        tags: Option<Vec<String>>,
+    ) -> Result<(), ChonkitError> {
+        if self
+            .repo
+            .update_collection_tags(id, tags.as_deref())
+            .await?
+            == 0
+        {
+            return err!(DoesNotExist, "Collection with ID '{id}'");
+        }
+        Ok(())
+    }
+
+    /// Change the embedding model of a collection. The new model must produce vectors of the
+    /// collection's size.
+    ///
+    /// Fails with [ModelImmutable][crate::error::ChonkitErr::ModelImmutable] if the collection
+    /// already has embeddings, which have to be migrated to a new collection instead.
+    ///
+    /// * `id`: Collection ID.
+    /// * `model`: The new model.
+    /// * `embedding_provider`: The embedder providing the model.
+    pub async fn update_collection_model(
+        &self,
+        id: Uuid,
+        model: &str,
+        embedding_provider: &str,
+    ) -> Result<(), ChonkitError> {
+        let Some(collection) = self.repo.get_collection_by_id(id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{id}'");
+        };
+
+        let embedder = self.providers.embedding.get_provider(embedding_provider)?;
+
+        let Some(model_details) = embedder.model_details(model).await? else {
+            let embedder_id = embedder.id();
+            return err!(
+                InvalidEmbeddingModel,
+                "Model {model} not supported by embedder '{embedder_id}'"
+            );
+        };
+
+        let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+        let vector_collection = vector_db.get_collection(&collection.name).await?;
+
+        if model_details.size != vector_collection.size {
+            return err!(ChonkitErr::DimensionMismatch {
+                model_size: model_details.size,
+                collection_size: vector_collection.size,
+            });
+        }
+
+        self.repo
+            .update_collection_model(id, model, embedder.id())
+            .await
+    }
+
     /// Sync the collections in the repository with the ones in the vector DB.
     pub async fn sync(&self, mode: SyncIncompatibilityResolution) -> Result<(), ChonkitError> {
         tracing::info!("Starting collection sync");
@@ -204,7 +478,7 @@ impl CollectionService {
 
             let collections = self
                 .repo
-                .list_collections(PaginationSort::default())
+                .list_collections(PaginationSort::default(), CollectionFilter::default())
                 .await?;
 
             for collection in collections {
@@ -256,6 +530,74 @@ impl CollectionService {
         Ok(())
     }
 
+    /// Point an alias to a collection, swapping it if it already points to another one.
+    pub async fn set_alias(
+        &self,
+        payload: SetAliasPayload,
+    ) -> Result<CollectionAlias, ChonkitError> {
+        map_err!(payload.validate());
+
+        let SetAliasPayload {
+            alias,
+            collection_id,
+        } = payload;
+
+        if self
+            .repo
+            .get_collection_by_id(collection_id)
+            .await?
+            .is_none()
+        {
+            return err!(DoesNotExist, "Collection with ID '{collection_id}'");
+        }
+
+        let alias = self
+            .repo
+            .upsert_collection_alias(&alias, collection_id)
+            .await?;
+
+        info!(
+            "Alias '{}' now points to collection '{}'",
+            alias.alias, alias.collection_id
+        );
+
+        Ok(alias)
+    }
+
+    /// List aliases, optionally only those pointing to the given collection.
+    pub async fn list_aliases(
+        &self,
+        collection_id: Option<Uuid>,
+    ) -> Result<Vec<CollectionAlias>, ChonkitError> {
+        self.repo.list_collection_aliases(collection_id).await
+    }
+
+    pub async fn delete_alias(&self, alias: &str) -> Result<(), ChonkitError> {
+        if self.repo.delete_collection_alias(alias).await? == 0 {
+            return err!(DoesNotExist, "Alias '{alias}'");
+        }
+        Ok(())
+    }
+
+    /// Get the ID of the collection the search targets, either directly or by its alias.
+    async fn resolve_collection_id(&self, search: &SearchPayload) -> Result<Uuid, ChonkitError> {
+        if let Some(collection_id) = search.collection_id {
+            return Ok(collection_id);
+        }
+
+        let Some(ref alias) = search.collection_alias else {
+            return err!(
+                InvalidParameter,
+                "Searches require a collection ID or alias"
+            );
+        };
+
+        match self.repo.get_collection_alias(alias).await? {
+            Some(alias) => Ok(alias.collection_id),
+            None => err!(DoesNotExist, "Alias '{alias}'"),
+        }
+    }
+
     /// Query the vector database (semantic search).
     /// Limit defaults to 5.
     ///
@@ -266,12 +608,14 @@ impl CollectionService {
     ) -> Result<dto::CollectionSearchResult, ChonkitError> {
         map_err!(search.validate());
 
-        let Some(collection) = self.repo.get_collection_by_id(search.collection_id).await? else {
-            return err!(
-                DoesNotExist,
-                "Collection with ID '{}'",
-                search.collection_id
-            );
+        if let Some(ref filter) = search.filter {
+            filter.validate()?;
+        }
+
+        let collection_id = self.resolve_collection_id(&search).await?;
+
+        let Some(collection) = self.repo.get_collection_by_id(collection_id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{collection_id}'");
         };
 
         let vector_db = self.providers.vector.get_provider(&collection.provider)?;
@@ -280,39 +624,685 @@ impl CollectionService {
             .embedding
             .get_provider(&collection.embedder)?;
 
-        let mut embeddings = embedder
-            .embed_text(&[&search.query], &collection.model)
-            .await?;
+        let rewritten_query = match (search.rewrite, &self.llm) {
+            (QueryRewrite::None, _) => None,
+            (rewrite, Some(llm)) => Some(rewrite.rewrite(llm.as_ref(), &search.query).await?),
+            (rewrite, None) => {
+                return err!(
+                    OperationUnsupported,
+                    "Query rewrite '{rewrite:?}' requested, but no LLM is configured"
+                )
+            }
+        };
+
+        let reranker = match (&search.rerank_model, &self.reranker) {
+            (None, _) => None,
+            (Some(model), Some(reranker)) => Some((model, reranker)),
+            (Some(model), None) => {
+                return err!(
+                    OperationUnsupported,
+                    "Rerank with '{model}' requested, but no reranker is configured"
+                )
+            }
+        };
+
+        let query = rewritten_query.as_deref().unwrap_or(&search.query);
+
+        let timeout = search
+            .timeout
+            .map(Duration::from_millis)
+            .unwrap_or(self.search_timeout);
+
+        // Two-stage retrieval, the chunks are only searched in the documents whose
+        // summaries are nearest to the query
+        let documents = match search.summary_collection_id {
+            Some(summary_collection_id) => {
+                let documents = self
+                    .search_summaries(
+                        summary_collection_id,
+                        query,
+                        search
+                            .summary_documents
+                            .unwrap_or(DEFAULT_SUMMARY_DOCUMENTS),
+                        timeout,
+                    )
+                    .await?;
+
+                if documents.is_empty() {
+                    return Ok(dto::CollectionSearchResult {
+                        query: search.query,
+                        rewritten_query,
+                        items: vec![],
+                        diagnostics: search.debug.unwrap_or(false).then(Default::default),
+                        adapted_dimension: None,
+                    });
+                }
+
+                Some(documents)
+            }
+            None => None,
+        };
+
+        tracing::debug!("search - embedding query '{query}'");
+
+        let mut embeddings = embedder.embed_text(&[query], &collection.model).await?;
 
         debug_assert_eq!(1, embeddings.embeddings.len());
 
-        let chunks = vector_db
-            .query(
-                std::mem::take(&mut embeddings.embeddings[0]),
-                &collection.name,
-                search.limit.unwrap_or(5),
-                search.max_distance,
-            )
-            .await?;
+        validate_vector(&embeddings.embeddings[0])?;
+
+        let adapted_dimension = if search.dimension_adapt.unwrap_or(false) {
+            let size = vector_db.get_collection(&collection.name).await?.size;
+            let query_size = embeddings.embeddings[0].len();
+
+            (query_size != size).then(|| {
+                tracing::warn!(
+                    "search - query vector of size {query_size} {} to the size {size} of collection '{}', results are degraded",
+                    if query_size < size { "padded" } else { "truncated" },
+                    collection.name
+                );
+                adapt_vector_dimension(&mut embeddings.embeddings[0], size);
+                query_size
+            })
+        } else {
+            None
+        };
+
+        let limit = search.limit.unwrap_or(self.search_limit);
+        let return_parent = search.return_parent.unwrap_or(false);
+        let dedup = search.dedup_by_document.is_some() || search.dedup_similarity.is_some();
+        let max_distance = search.max_distance.or(self.search_max_distance);
+        let debug = search.debug.unwrap_or(false);
+
+        let mut candidates = if dedup || return_parent {
+            limit.saturating_mul(DEDUP_CANDIDATE_FACTOR)
+        } else {
+            limit
+        };
+
+        if reranker.is_some() {
+            let rerank_candidates = search
+                .rerank_candidates
+                .unwrap_or(limit.saturating_mul(RERANK_CANDIDATE_FACTOR));
+            candidates = candidates.max(rerank_candidates);
+        }
+
+        let query = VectorQuery::new(
+            std::mem::take(&mut embeddings.embeddings[0]),
+            &collection.name,
+            candidates,
+        )
+        // When debugging, the threshold is applied below to capture what it excludes
+        .with_max_distance(if debug { None } else { max_distance })
+        .with_documents(documents.as_deref())
+        .with_filter(search.filter.as_ref());
+
+        let mut chunks = query_with_timeout(vector_db.as_ref(), query, timeout).await?;
+
+        let diagnostics = debug.then(|| {
+            let candidates = chunks.len();
+            let mut best_filtered_distance: Option<f64> = None;
+
+            if let Some(max_distance) = max_distance {
+                chunks.retain(|chunk| match chunk.distance {
+                    Some(distance) if distance > max_distance => {
+                        best_filtered_distance =
+                            Some(best_filtered_distance.map_or(distance, |d| d.min(distance)));
+                        false
+                    }
+                    _ => true,
+                });
+            }
+
+            dto::SearchDiagnostics {
+                candidates,
+                best_filtered_distance,
+            }
+        });
+
+        // Ties are broken here since their order varies between vector databases
+        sort_search_items(&mut chunks);
+
+        // Parents are swapped in first so that similarity is judged on the returned content
+        if return_parent {
+            chunks = replace_with_parents(chunks);
+        }
+
+        if dedup {
+            chunks =
+                deduplicate_search_items(chunks, search.dedup_by_document, search.dedup_similarity);
+        }
+
+        // The original query is used since rewrites are meant for the vector search
+        if let Some((model, reranker)) = reranker {
+            chunks = rerank_search_items(reranker.as_ref(), model, &search.query, chunks).await?;
+        }
+
+        chunks.truncate(limit as usize);
+
+        // Both providers create collections with the cosine metric
+        score_search_items(&mut chunks, DistanceMetric::Cosine);
+
+        if search.highlight.unwrap_or(false) {
+            highlight_search_items(&mut chunks, &search.query);
+        }
 
         tracing::debug!("search - successful query ({} results)", chunks.len());
 
         Ok(dto::CollectionSearchResult {
             query: search.query,
+            rewritten_query,
             items: chunks,
+            diagnostics,
+            adapted_dimension,
+        })
+    }
+
+    /// Get the documents whose summaries are nearest to the `query`, in order of relevance.
+    ///
+    /// * `collection_id`: The collection holding the document summaries.
+    /// * `query`: The search query.
+    /// * `limit`: The maximum amount of documents to return.
+    /// * `timeout`: How long to wait for the vector database.
+    async fn search_summaries(
+        &self,
+        collection_id: Uuid,
+        query: &str,
+        limit: u32,
+        timeout: Duration,
+    ) -> Result<Vec<Uuid>, ChonkitError> {
+        let Some(collection) = self.repo.get_collection_by_id(collection_id).await? else {
+            return err!(DoesNotExist, "Summary collection with ID '{collection_id}'");
+        };
+
+        let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+        let embedder = self
+            .providers
+            .embedding
+            .get_provider(&collection.embedder)?;
+
+        let mut embeddings = embedder.embed_text(&[query], &collection.model).await?;
+
+        validate_vector(&embeddings.embeddings[0])?;
+
+        let query = VectorQuery::new(
+            std::mem::take(&mut embeddings.embeddings[0]),
+            &collection.name,
+            limit,
+        );
+
+        let summaries = query_with_timeout(vector_db.as_ref(), query, timeout).await?;
+
+        let mut documents = vec![];
+        for document_id in summaries.into_iter().filter_map(|s| s.item.document_id) {
+            if !documents.contains(&document_id) {
+                documents.push(document_id);
+            }
+        }
+
+        tracing::debug!(
+            "search - narrowed to {} documents by summary",
+            documents.len()
+        );
+
+        Ok(documents)
+    }
+
+    /// Query multiple collections at once and merge their results by distance, nearest first.
+    /// The collections are queried concurrently and each result is tagged with the
+    /// collection it came from. The query is embedded once for every distinct model.
+    ///
+    /// All collections must have vectors of the same size.
+    ///
+    /// * `search`: Search params.
+    pub async fn search_multi(
+        &self,
+        search: MultiSearchPayload,
+    ) -> Result<dto::MultiSearchResult, ChonkitError> {
+        map_err!(search.validate());
+
+        if let Some(ref filter) = search.filter {
+            filter.validate()?;
+        }
+
+        let mut collections = vec![];
+        let mut size = None;
+
+        for collection_id in search.collection_ids.iter() {
+            let Some(collection) = self.repo.get_collection_by_id(*collection_id).await? else {
+                return err!(DoesNotExist, "Collection with ID '{collection_id}'");
+            };
+
+            let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+            let collection_size = vector_db.get_collection(&collection.name).await?.size;
+
+            match size {
+                None => size = Some(collection_size),
+                Some(size) if size != collection_size => {
+                    return err!(
+                        InvalidParameter,
+                        "Collection '{}' has vectors of size {collection_size}, expected {size}",
+                        collection.name
+                    );
+                }
+                Some(_) => {}
+            }
+
+            collections.push((collection, vector_db));
+        }
+
+        tracing::debug!("search - embedding query '{}'", search.query);
+
+        let mut queries: HashMap<(&str, &str), Vec<f64>> = HashMap::new();
+
+        for (collection, _) in collections.iter() {
+            let key = (collection.embedder.as_str(), collection.model.as_str());
+
+            if queries.contains_key(&key) {
+                continue;
+            }
+
+            let embedder = self
+                .providers
+                .embedding
+                .get_provider(&collection.embedder)?;
+
+            let mut embeddings = embedder
+                .embed_text(&[&search.query], &collection.model)
+                .await?;
+
+            validate_vector(&embeddings.embeddings[0])?;
+
+            queries.insert(key, std::mem::take(&mut embeddings.embeddings[0]));
+        }
+
+        let limit = search.limit.unwrap_or(self.search_limit);
+        let max_distance = search.max_distance.or(self.search_max_distance);
+        let timeout = search
+            .timeout
+            .map(Duration::from_millis)
+            .unwrap_or(self.search_timeout);
+        let filter = search.filter.as_ref();
+
+        let results = query_all_with_timeout(
+            collections.iter().map(|(collection, vector_db)| {
+                let query =
+                    queries[&(collection.embedder.as_str(), collection.model.as_str())].clone();
+                let query = VectorQuery::new(query, &collection.name, limit)
+                    .with_max_distance(max_distance)
+                    .with_filter(filter);
+                (vector_db.as_ref(), query)
+            }),
+            timeout,
+        )
+        .await?;
+
+        let mut items = vec![];
+        let mut timed_out = vec![];
+
+        for ((collection, _), chunks) in collections.iter().zip(results) {
+            let Some(mut chunks) = chunks else {
+                tracing::warn!(
+                    "search - '{}' did not respond within {}ms, leaving it out",
+                    collection.name,
+                    timeout.as_millis()
+                );
+                timed_out.push(collection.id);
+                continue;
+            };
+
+            // Ties within a collection are broken like in single collection searches
+            sort_search_items(&mut chunks);
+
+            items.extend(chunks.into_iter().map(|item| MultiSearchItem {
+                collection_id: collection.id,
+                item,
+            }));
+        }
+
+        if timed_out.len() == collections.len() {
+            return err!(
+                Timeout,
+                "none of the collections responded to the query within {}ms",
+                timeout.as_millis()
+            );
+        }
+
+        // The sort is stable, so ties between collections keep the order they were given in
+        items.sort_by(|a, b| match (a.item.distance, b.item.distance) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        items.truncate(limit as usize);
+
+        // Both providers create collections with the cosine metric
+        for MultiSearchItem { item, .. } in items.iter_mut() {
+            item.score = item
+                .distance
+                .map(|distance| DistanceMetric::Cosine.score(distance));
+        }
+
+        tracing::debug!(
+            "search - successful query of {} collections ({} results)",
+            collections.len(),
+            items.len()
+        );
+
+        Ok(dto::MultiSearchResult {
+            query: search.query,
+            items,
+            timed_out,
+        })
+    }
+
+    /// Same as [search](Self::search), but the results are streamed as they become
+    /// available. The vector database hits are emitted first, followed by their final
+    /// ordering, which is always the last event.
+    ///
+    /// Reranking happens before the hits are emitted, so the final ordering is currently the
+    /// one of the hits.
+    pub fn search_stream(
+        &self,
+        search: SearchPayload,
+    ) -> impl Stream<Item = Result<SearchEvent, ChonkitError>> + Send + 'static {
+        let service = self.clone();
+
+        futures_util::stream::once(async move { service.search(search).await }).flat_map(|result| {
+            let events = match result {
+                Ok(hits) => {
+                    let order = (0..hits.items.len()).collect();
+                    vec![
+                        Ok(SearchEvent::Hits(hits)),
+                        Ok(SearchEvent::Ranked { order }),
+                    ]
+                }
+                Err(e) => vec![Err(e)],
+            };
+            futures_util::stream::iter(events)
         })
     }
+
+    /// Answer a question using the chunks retrieved by searching the collection.
+    ///
+    /// The text chunks are given to the LLM in order of relevance, until the token budget is
+    /// exhausted. The chunks that made it into the context are emitted first as citations,
+    /// followed by the answer as it is generated.
+    ///
+    /// Errors occurring before the answer is generated, e.g. when no LLM is configured, are
+    /// returned immediately instead of being streamed.
+    pub async fn ask(
+        &self,
+        ask: AskPayload,
+    ) -> Result<impl Stream<Item = Result<AskEvent, ChonkitError>> + Send + 'static, ChonkitError>
+    {
+        map_err!(ask.validate());
+
+        let Some(llm) = self.llm.clone() else {
+            return err!(
+                OperationUnsupported,
+                "Answering questions requires an LLM to be configured"
+            );
+        };
+
+        let budget = ask.max_context_tokens.unwrap_or(self.ask_context_tokens);
+        let result = self.search(ask.search).await?;
+
+        let mut context = vec![];
+        let mut citations = vec![];
+        let mut tokens = 0;
+
+        for hit in result.items.iter() {
+            // Images cannot be given to the LLM
+            let CollectionItemPayload::Text {
+                ref content,
+                chunk_index,
+                ..
+            } = hit.item.payload
+            else {
+                continue;
+            };
+
+            // Count conservatively since the LLM's tokenizer is unknown
+            let count = self.tokenizer.count(content);
+            let count = count.cl100k.max(count.o200k);
+
+            if tokens + count > budget {
+                break;
+            }
+
+            tokens += count;
+            context.push(content.as_str());
+            citations.push(Citation {
+                number: citations.len() + 1,
+                document_id: hit.item.document_id,
+                chunk_index,
+                distance: hit.distance,
+            });
+        }
+
+        tracing::debug!(
+            "ask - answering from {} chunks ({tokens} tokens)",
+            context.len()
+        );
+
+        let answer = llm::answer(llm.as_ref(), &result.query, &context).await?;
+
+        Ok(
+            futures_util::stream::once(async move { Ok(AskEvent::Citations { citations }) })
+                .chain(answer.map_ok(|text| AskEvent::Answer { text })),
+        )
+    }
+
+    /// Stream the text vectors of a collection along with their payloads. The vectors are
+    /// fetched from the vector database in pages as the stream is consumed.
+    ///
+    /// * `id`: Collection ID.
+    pub async fn export_collection(
+        &self,
+        id: Uuid,
+    ) -> Result<
+        impl Stream<Item = Result<ExportedVector, ChonkitError>> + Send + 'static,
+        ChonkitError,
+    > {
+        let Some(collection) = self.repo.get_collection_by_id(id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{id}'");
+        };
+
+        let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+        let name = collection.name;
+
+        // The outer option is `None` once the last page is fetched
+        let pages =
+            futures_util::stream::try_unfold(Some(None), move |cursor: Option<Option<String>>| {
+                let vector_db = vector_db.clone();
+                let name = name.clone();
+                async move {
+                    let Some(cursor) = cursor else {
+                        return Ok::<_, ChonkitError>(None);
+                    };
+
+                    let page = vector_db
+                        .list_vectors(&name, cursor, EXPORT_PAGE_SIZE)
+                        .await?;
+
+                    Ok(Some((page.vectors, page.next.map(Some))))
+                }
+            });
+
+        Ok(pages
+            .map_ok(|vectors| futures_util::stream::iter(vectors.into_iter().map(Ok)))
+            .try_flatten())
+    }
+
+    /// Insert previously exported vectors to a collection without embedding their content.
+    /// The vectors are inserted as they are received, in batches of consecutive vectors
+    /// belonging to the same document.
+    ///
+    /// Documents that exist in the repository get their embeddings registered for the collection.
+    /// Vectors inserted before an error is encountered are not removed.
+    ///
+    /// * `id`: The ID of the collection to import to.
+    /// * `vectors`: The exported vectors.
+    pub async fn import_collection(
+        &self,
+        id: Uuid,
+        vectors: impl Stream<Item = Result<ExportedVector, ChonkitError>>,
+    ) -> Result<CollectionImportResult, ChonkitError> {
+        let Some(collection) = self.repo.get_collection_by_id(id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{id}'");
+        };
+
+        let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+
+        let mut vectors = std::pin::pin!(vectors);
+        let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+
+        // Amount of imported vectors per document
+        let mut imported = HashMap::new();
+
+        while let Some(vector) = vectors.next().await {
+            let vector = vector?;
+
+            let is_next_document = batch
+                .first()
+                .is_some_and(|v: &ExportedVector| v.document_id != vector.document_id);
+
+            if is_next_document || batch.len() == IMPORT_BATCH_SIZE {
+                self.import_batch(
+                    &*vector_db,
+                    collection.id,
+                    &collection.name,
+                    std::mem::take(&mut batch),
+                    &mut imported,
+                )
+                .await?;
+            }
+
+            batch.push(vector);
+        }
+
+        self.import_batch(
+            &*vector_db,
+            collection.id,
+            &collection.name,
+            batch,
+            &mut imported,
+        )
+        .await?;
+
+        let result = CollectionImportResult {
+            total_documents: imported.len(),
+            total_vectors: imported.values().sum(),
+        };
+
+        info!(
+            "Imported {} vectors of {} documents to '{}'",
+            result.total_vectors, result.total_documents, collection.name
+        );
+
+        Ok(result)
+    }
+
+    /// Insert vectors belonging to a single document to the collection.
+    ///
+    /// * `imported`: Amount of vectors imported so far per document, updated with the batch.
+    async fn import_batch(
+        &self,
+        vector_db: &(dyn VectorDb + Send + Sync),
+        collection_id: Uuid,
+        collection: &str,
+        batch: Vec<ExportedVector>,
+        imported: &mut HashMap<Uuid, usize>,
+    ) -> Result<(), ChonkitError> {
+        let Some(document_id) = batch.first().map(|v| v.document_id) else {
+            return Ok(());
+        };
+
+        if !imported.contains_key(&document_id) {
+            self.register_imported_embeddings(document_id, collection_id)
+                .await?;
+        }
+
+        let offset = imported.entry(document_id).or_default();
+
+        let (items, vectors): (Vec<_>, Vec<_>) = batch
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let item = CollectionItemText {
+                    document_id,
+                    // Vectors exported without chunk indices are indexed by their order,
+                    // synthetic ones never have one
+                    chunk_index: (!v.synthetic).then(|| v.chunk_index.unwrap_or(*offset + i)),
+                    content: &v.content,
+                    metadata: v.metadata.as_ref(),
+                    synthetic: v.synthetic,
+                    parent_index: v.parent_index,
+                    parent_content: v.parent_content.as_deref(),
+                };
+                (item, v.vector.clone())
+            })
+            .unzip();
+
+        vector_db
+            .insert_embeddings(CollectionItemInsert {
+                collection,
+                payload: CollectionItemInsertPayload::Text { items, vectors },
+            })
+            .await?;
+
+        *offset += batch.len();
+
+        Ok(())
+    }
+
+    /// Register the embeddings of an imported document for the collection, provided the
+    /// document exists.
+    async fn register_imported_embeddings(
+        &self,
+        document_id: Uuid,
+        collection_id: Uuid,
+    ) -> Result<(), ChonkitError> {
+        if self.repo.get_document_by_id(document_id).await?.is_none() {
+            tracing::warn!("Importing vectors of unknown document {document_id}");
+            return Ok(());
+        }
+
+        let existing = self
+            .repo
+            .get_text_embeddings(document_id, collection_id)
+            .await?;
+
+        if existing.is_some() {
+            return err!(
+                AlreadyExists,
+                "Embeddings for document '{document_id}' in collection '{collection_id}'"
+            );
+        }
+
+        self.repo
+            .insert_text_embeddings(TextEmbeddingInsert::new(document_id, collection_id), None)
+            .await?;
+
+        Ok(())
+    }
 }
 
 pub mod dto {
     use crate::core::{
+        llm::QueryRewrite,
         model::collection::Collection,
-        vector::{CollectionSearchItem, VectorCollection},
+        vector::{CollectionSearchItem, FilterExpr, VectorCollection},
     };
     use serde::{Deserialize, Serialize};
     use utoipa::ToSchema;
     use uuid::Uuid;
-    use validify::{field_err, Validate, ValidationError, Validify};
+    use validify::{
+        field_err, schema_err, schema_validation, Validate, ValidationError, ValidationErrors,
+        Validify,
+    };
 
     /// Determines what to do when collections being synced do not conform to current data
     /// structures.
@@ -383,11 +1373,128 @@ pub mod dto {
         /// Optional collection groups that indicate which user groups can use it.
         #[validate(length(min = 1))]
         pub groups: Option<Vec<String>>,
+
+        /// What the collection is intended to store. Multimodal collections require a
+        /// multimodal model.
+        #[serde(default)]
+        pub modality: CollectionModality,
+
+        /// Template the chunks are wrapped with before they are embedded, e.g.
+        /// `Document: {name}\n\n{chunk}`. Supports the `{name}`, `{page}` and `{chunk}`
+        /// placeholders, the latter being required. Stored chunks are not affected.
+        #[serde(default)]
+        pub chunk_template: Option<String>,
+
+        /// Labels for organizing collections, e.g. `project:alpha`. Collections can be
+        /// listed by tag.
+        #[serde(default)]
+        pub tags: Option<Vec<String>>,
+
+        /// Chunks with a cosine similarity of at least this value, between 0 and 1, with an
+        /// earlier chunk of the same document are not inserted. Keeps near duplicates, e.g.
+        /// from overlapping chunks, out of the collection at the cost of insert latency.
+        #[serde(default)]
+        #[validate(range(min = 0., max = 1.))]
+        pub insert_dedup_threshold: Option<f64>,
+    }
+
+    /// The configuration of a collection, without its vectors. Used for recreating the
+    /// collection in another environment.
+    #[derive(Debug, Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CollectionManifest {
+        /// Collection name. Validated like the name of a new collection on import.
+        pub name: String,
+
+        /// Collection embedding model, has to be available on the embedding provider.
+        pub model: String,
+
+        /// Vector database provider.
+        pub vector_provider: String,
+
+        /// Embeddings provider.
+        pub embedding_provider: String,
+
+        /// The metric vectors are compared with.
+        #[serde(default)]
+        pub metric: CollectionMetric,
+
+        /// Collection groups that indicate which user groups can use it.
+        #[serde(default)]
+        pub groups: Option<Vec<String>>,
+
+        /// What the collection is intended to store.
+        #[serde(default)]
+        pub modality: CollectionModality,
+
+        /// Template the chunks are wrapped with before they are embedded.
+        #[serde(default)]
+        pub chunk_template: Option<String>,
+
+        /// Labels for organizing collections.
+        #[serde(default)]
+        pub tags: Option<Vec<String>>,
+
+        /// The similarity threshold of near duplicate chunks left out on insertion.
+        #[serde(default)]
+        pub insert_dedup_threshold: Option<f64>,
+    }
+
+    impl From<CollectionManifest> for CreateCollectionPayload {
+        fn from(manifest: CollectionManifest) -> Self {
+            let CollectionManifest {
+                name,
+                model,
+                vector_provider,
+                embedding_provider,
+                // The only metric collections are created with
+                metric: CollectionMetric::Cosine,
+                groups,
+                modality,
+                chunk_template,
+                tags,
+                insert_dedup_threshold,
+            } = manifest;
+
+            Self {
+                name,
+                model,
+                vector_provider,
+                embedding_provider,
+                groups,
+                modality,
+                chunk_template,
+                tags,
+                insert_dedup_threshold,
+            }
+        }
+    }
+
+    /// The metric vectors of a collection are compared with. All vector providers create
+    /// collections with the cosine metric.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub enum CollectionMetric {
+        #[default]
+        Cosine,
+    }
+
+    /// The kind of content a collection is intended to store.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub enum CollectionModality {
+        /// Text embeddings only.
+        #[default]
+        Text,
+
+        /// Text and image embeddings.
+        Multimodal,
     }
 
     /// Params for semantic search.
     #[derive(Debug, Deserialize, Validate, ToSchema)]
     #[serde(rename_all = "camelCase")]
+    #[validate(Self::validate_schema)]
     pub struct SearchPayload {
         /// The text to search by.
         #[validate(length(min = 1))]
@@ -395,9 +1502,14 @@ pub mod dto {
 
         /// The collection to search in. Has priority over
         /// everything else.
-        pub collection_id: Uuid,
+        pub collection_id: Option<Uuid>,
 
-        /// Amount of results to return.
+        /// The alias of the collection to search in, resolved to the collection it currently
+        /// points to. Required if `collectionId` is omitted.
+        #[validate(length(min = 1, max = 64))]
+        pub collection_alias: Option<String>,
+
+        /// Amount of results to return. If omitted, the server default is used.
         #[validate(range(min = 1.))]
         pub limit: Option<u32>,
 
@@ -405,14 +1517,263 @@ pub mod dto {
         /// below this value will be excluded.
         /// A similarity of 0 means the vectors are identical, a similarity of 2 means they are
         /// opposite.
+        /// If omitted, the server default is used, if any.
+        #[validate(range(min = 0., max = 2.))]
+        pub max_distance: Option<f64>,
+
+        /// How the query is rewritten before it is embedded. Requires an LLM to be configured
+        /// for anything other than `none`.
+        #[serde(default)]
+        pub rewrite: QueryRewrite,
+
+        /// The maximum amount of results from a single document. Useful when chunks overlap,
+        /// as neighbouring chunks tend to be retrieved together.
+        #[validate(range(min = 1.))]
+        pub dedup_by_document: Option<usize>,
+
+        /// Results sharing at least this share of words, between 0 and 1, with a more relevant
+        /// result are left out. The share is relative to the shorter of the two chunks.
+        #[validate(range(min = 0., max = 1.))]
+        pub dedup_similarity: Option<f64>,
+
+        /// How long to wait for the vector database, in milliseconds. If omitted, the server
+        /// default is used.
+        #[validate(range(min = 1.))]
+        pub timeout: Option<u64>,
+
+        /// If given, the search is done in two stages. The summaries in this collection are
+        /// searched first and only the chunks of the documents whose summaries match best are
+        /// searched in the collection.
+        pub summary_collection_id: Option<Uuid>,
+
+        /// The amount of documents found by their summaries that the chunk search is narrowed to.
+        /// Only used with `summaryCollectionId`. Defaults to 5.
+        #[validate(range(min = 1.))]
+        pub summary_documents: Option<u32>,
+
+        /// If `true`, chunks obtained with parent-child chunking are returned with the content
+        /// of their parent window and only the most relevant chunk of every window is kept.
+        /// Defaults to `false`.
+        pub return_parent: Option<bool>,
+
+        /// If `true`, every returned chunk includes the byte ranges of the query terms in its
+        /// content, matched as whole words regardless of case. Defaults to `false`.
+        pub highlight: Option<bool>,
+
+        /// If `true`, the result includes diagnostics of the retrieval, useful for tuning
+        /// `maxDistance`. Defaults to `false`.
+        pub debug: Option<bool>,
+
+        /// If given, the results of the vector search are reordered by the scores this model
+        /// of the configured reranker gives them, e.g. a cross-encoder.
+        #[validate(length(min = 1))]
+        pub rerank_model: Option<String>,
+
+        /// The amount of results of the vector search that are reranked before the `limit` is
+        /// applied. Only used with `rerankModel`. Defaults to 4 times the limit.
+        #[validate(range(min = 1.))]
+        pub rerank_candidates: Option<u32>,
+
+        /// If given, only chunks whose metadata matches it are searched, e.g.
+        /// `{ "and": [{ "field": "year", "op": "gte", "value": 2020 }, ...] }`. Conditions can
+        /// be combined with `and` and `or`. Qdrant only supports ranges on numbers. Weaviate
+        /// applies the filter to the nearest chunks, so fewer results than the limit may be
+        /// returned.
+        pub filter: Option<FilterExpr>,
+
+        /// If `true` and the query vector is not of the collection's size, it is padded with
+        /// zeros or truncated to it instead of failing the search. This is lossy and only meant
+        /// for searching collections of a previous model while migrating. Defaults to `false`.
+        pub dimension_adapt: Option<bool>,
+    }
+
+    impl SearchPayload {
+        #[schema_validation]
+        fn validate_schema(&self) -> Result<(), ValidationErrors> {
+            if self.collection_id.is_some() == self.collection_alias.is_some() {
+                schema_err!(
+                    "collection",
+                    "exactly one of collectionId and collectionAlias must be given"
+                );
+            }
+        }
+    }
+
+    /// Params for searching multiple collections at once.
+    #[derive(Debug, Deserialize, Validate, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MultiSearchPayload {
+        /// The text to search by.
+        #[validate(length(min = 1))]
+        pub query: String,
+
+        /// The collections to search in. Their vectors must be of the same size.
+        #[validate(length(min = 1, max = 32))]
+        pub collection_ids: Vec<Uuid>,
+
+        /// Amount of results to return in total. If omitted, the server default is used.
+        #[validate(range(min = 1.))]
+        pub limit: Option<u32>,
+
+        /// The similarity threshold for vector retrieval, between 0 and 2, see
+        /// [SearchPayload::max_distance]. If omitted, the server default is used, if any.
         #[validate(range(min = 0., max = 2.))]
         pub max_distance: Option<f64>,
+
+        /// How long to wait for each vector database query, in milliseconds. If omitted, the
+        /// server default is used.
+        #[validate(range(min = 1.))]
+        pub timeout: Option<u64>,
+
+        /// If given, only chunks whose metadata matches it are searched, see
+        /// [SearchPayload::filter].
+        pub filter: Option<FilterExpr>,
+    }
+
+    /// Params for pointing an alias to a collection.
+    #[derive(Debug, Deserialize, Validate, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetAliasPayload {
+        /// The alias, created if it does not exist.
+        #[validate(length(min = 1, max = 64))]
+        #[validate(custom(alias_characters))]
+        pub alias: String,
+
+        /// The collection the alias points to.
+        pub collection_id: Uuid,
+    }
+
+    fn alias_characters(s: &str) -> Result<(), ValidationError> {
+        if !s
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(field_err!(
+                "alias",
+                "alias must be alphanumeric with underscores, dashes or dots [a-z A-Z 0-9 _ - .]"
+            ));
+        }
+        Ok(())
     }
 
     #[derive(Debug, Serialize, ToSchema)]
     #[serde(rename_all = "camelCase")]
     pub struct CollectionSearchResult {
         pub query: String,
+
+        /// The text embedded instead of the query, if it was rewritten.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub rewritten_query: Option<String>,
+
         pub items: Vec<CollectionSearchItem>,
+
+        /// Only present if requested with `debug`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub diagnostics: Option<SearchDiagnostics>,
+
+        /// The size of the query vector before it was padded or truncated to the size of the
+        /// collection. Only present if `dimensionAdapt` changed it.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub adapted_dimension: Option<usize>,
+    }
+
+    #[derive(Debug, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MultiSearchResult {
+        pub query: String,
+
+        /// The results of all collections, nearest first.
+        pub items: Vec<MultiSearchItem>,
+
+        /// The collections that did not respond within the timeout, their results are missing
+        /// from `items`.
+        pub timed_out: Vec<Uuid>,
+    }
+
+    /// A search result tagged with the collection it came from.
+    #[derive(Debug, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MultiSearchItem {
+        pub collection_id: Uuid,
+
+        #[serde(flatten)]
+        pub item: CollectionSearchItem,
+    }
+
+    /// How the results of a search were obtained.
+    #[derive(Debug, Default, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SearchDiagnostics {
+        /// The amount of results returned by the vector database before the `maxDistance`
+        /// threshold was applied.
+        pub candidates: usize,
+
+        /// The distance of the nearest result excluded by the `maxDistance` threshold.
+        /// Absent if the threshold excluded nothing.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub best_filtered_distance: Option<f64>,
+    }
+
+    /// Events emitted when streaming search results.
+    #[derive(Debug, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase", tag = "type")]
+    pub enum SearchEvent {
+        /// The nearest neighbours of the query, as returned by the vector database.
+        Hits(CollectionSearchResult),
+
+        /// The final ordering of the hits, given as their indices in the `hits` event.
+        /// Always the last event of the stream.
+        Ranked { order: Vec<usize> },
+    }
+
+    /// Params for answering a question from the contents of a collection.
+    #[derive(Debug, Deserialize, Validate, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AskPayload {
+        /// The search retrieving the context of the answer. Its query is the question.
+        #[validate]
+        #[serde(flatten)]
+        pub search: SearchPayload,
+
+        /// The maximum amount of tokens of the retrieved chunks given to the LLM.
+        /// If omitted, the server default is used.
+        #[validate(range(min = 1.))]
+        pub max_context_tokens: Option<usize>,
+    }
+
+    /// A chunk given to the LLM as context when answering a question.
+    #[derive(Debug, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Citation {
+        /// The number the answer refers to the chunk by, starting from 1.
+        pub number: usize,
+
+        pub document_id: Option<Uuid>,
+
+        /// Not present on vectors inserted before chunk indices were stored.
+        pub chunk_index: Option<usize>,
+
+        pub distance: Option<f64>,
+    }
+
+    /// Events emitted when answering a question.
+    #[derive(Debug, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase", tag = "type")]
+    pub enum AskEvent {
+        /// The chunks the answer is based on, in order of relevance. Always the first event.
+        Citations { citations: Vec<Citation> },
+
+        /// A part of the answer, emitted as it is generated.
+        Answer { text: String },
+    }
+
+    #[derive(Debug, Serialize, ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CollectionImportResult {
+        /// Amount of imported vectors.
+        pub total_vectors: usize,
+
+        /// Amount of documents the imported vectors belong to.
+        pub total_documents: usize,
     }
 }
@@ -1,13 +1,26 @@
-use crate::core::document::parser::{parse_text, ParseConfig, ParseOutput};
-use crate::core::document::{get_image, process_document_images, store_document, store_images};
-use crate::core::model::document::{DocumentMetadataUpdate, DocumentSearchColumn};
-use crate::core::model::image::{Image, ImageData, ImageModel};
+use crate::core::cache::IdempotencyCache;
+use crate::core::document::archive::{self, ArchiveEntry, ArchiveLimits};
+use crate::core::document::parser::{
+    markdown, parse_text, parse_with_timeout, ParseConfig, ParseOutput, ParserDefaults,
+};
+use crate::core::document::store::DocumentStorage;
+use crate::core::document::{
+    get_image, link_document_source, process_document_images, store_document,
+    store_document_images, store_images, DocumentHashing, ImageExtraction,
+};
+use crate::core::model::document::{DocumentMetadataUpdate, DocumentSearchColumn, DocumentSource};
+use crate::core::model::image::{Image, ImageCollection, ImageData, ImageDisplay, ImageModel};
 use crate::core::service::document::dto::{
-    ListImagesParameters, ParsedDocumentPage, ParsedDocumentSection,
+    DocumentImagesParameters, ListImagesParameters, ParsedDocument, ParsedDocumentPage,
+    ParsedDocumentParameters, ParsedDocumentSection,
 };
 use crate::core::token::{TokenCount, Tokenizer};
 use crate::{
-    config::{DEFAULT_DOCUMENT_CONTENT, DEFAULT_DOCUMENT_NAME, FS_STORE_ID},
+    config::{
+        DEFAULT_ARCHIVE_MAX_ENTRY_SIZE, DEFAULT_ARCHIVE_MAX_SIZE, DEFAULT_DOCUMENT_CONTENT,
+        DEFAULT_DOCUMENT_NAME, DEFAULT_MAX_CHUNKS, DEFAULT_MAX_PAGE_SIZE, DEFAULT_PARSE_TIMEOUT,
+        FS_STORE_ID, MAX_INLINE_PREVIEW_SIZE,
+    },
     core::{
         chunk::{ChunkConfig, ChunkedDocument},
         document::{DocumentType, TextDocumentType},
@@ -16,17 +29,33 @@ use crate::{
             List, PaginationSort,
         },
         provider::ProviderState,
-        repo::Repository,
+        repo::{Repository, STARTUP_LOCK_KEY},
     },
     err,
-    error::ChonkitError,
+    error::{ChonkitErr, ChonkitError},
     map_err,
 };
-use dto::{ChunkForPreview, ChunkPreview, DocumentUpload, ParseOutputPreview, ParsePreview};
-use std::time::Instant;
+use base64::Engine;
+use dto::{
+    BulkConfigurePayload, BulkDeletePayload, ChunkDiff, ChunkForPreview, ChunkPreview,
+    DocumentConfigureResult, DocumentDeleteResult, DocumentHashMismatch, DocumentUpload,
+    HashVerificationEvent, HashVerificationSummary, ParseOutputPreview, ParsePreview,
+    VerifyHashesPayload,
+};
+use futures_util::Stream;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::Sender;
 use uuid::Uuid;
 use validify::{Validate, Validify};
 
+/// The amount of verified documents between progress events when verifying hashes.
+const HASH_VERIFICATION_PROGRESS_INTERVAL: usize = 100;
+
+/// The maximum width and height of image thumbnails, in pixels.
+const IMAGE_THUMBNAIL_SIZE: u32 = 256;
+
 /// High level operations for document management.
 ///
 /// Documents include textual documents, as well as images
@@ -35,6 +64,41 @@ pub struct DocumentService {
     repo: Repository,
     providers: ProviderState,
     tokenizer: Tokenizer,
+
+    /// The maximum amount of chunks a document can be chunked into when previewing.
+    max_chunks: usize,
+
+    /// Listings requesting larger pages, or no pages at all, are clamped to this.
+    max_page_size: usize,
+
+    /// Parsing configurations for documents without a stored one.
+    parser_defaults: ParserDefaults,
+
+    /// How long to wait for parsing when the parsing configuration does not specify it.
+    parse_timeout: Duration,
+
+    /// Stores the documents created with idempotency keys.
+    /// If not set, uploads with idempotency keys behave like regular uploads.
+    idempotency: Option<IdempotencyCache>,
+
+    /// How document contents are hashed and whether duplicates are linked.
+    hashing: DocumentHashing,
+
+    /// Uploads of any other document types are rejected before anything is stored.
+    allowed_types: Vec<DocumentType>,
+
+    /// Limits applied when extracting documents from uploaded archives.
+    archive_limits: ArchiveLimits,
+
+    /// Determines which images extracted from documents are stored.
+    image_extraction: ImageExtraction,
+
+    /// If `true`, documents are only ready once they are embedded in at least one collection,
+    /// in addition to having a parsing and chunking configuration.
+    ready_requires_embeddings: bool,
+
+    /// IDs of the storages currently being synced, shared between all clones of the service.
+    syncing: Arc<Mutex<HashSet<String>>>,
 }
 
 impl DocumentService {
@@ -43,14 +107,95 @@ impl DocumentService {
             repo,
             providers,
             tokenizer,
+            max_chunks: DEFAULT_MAX_CHUNKS,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            parser_defaults: ParserDefaults::default(),
+            parse_timeout: Duration::from_millis(DEFAULT_PARSE_TIMEOUT),
+            idempotency: None,
+            hashing: DocumentHashing::default(),
+            allowed_types: DocumentType::SUPPORTED.to_vec(),
+            archive_limits: ArchiveLimits {
+                max_size: DEFAULT_ARCHIVE_MAX_SIZE,
+                max_entry_size: DEFAULT_ARCHIVE_MAX_ENTRY_SIZE,
+            },
+            image_extraction: ImageExtraction::default(),
+            ready_requires_embeddings: false,
+            syncing: Arc::default(),
         }
     }
 
+    /// Set the maximum amount of chunks a document can be chunked into.
+    pub fn with_max_chunks(mut self, max_chunks: usize) -> Self {
+        self.max_chunks = max_chunks;
+        self
+    }
+
+    /// Set the maximum amount of items returned by a single page of listings.
+    pub fn with_max_page_size(mut self, max_page_size: usize) -> Self {
+        self.max_page_size = max_page_size;
+        self
+    }
+
+    /// Set the parsing configurations used for documents without a stored one.
+    pub fn with_parser_defaults(mut self, parser_defaults: ParserDefaults) -> Self {
+        self.parser_defaults = parser_defaults;
+        self
+    }
+
+    /// Set how long to wait for parsing when the parsing configuration does not specify it.
+    pub fn with_parse_timeout(mut self, timeout: Duration) -> Self {
+        self.parse_timeout = timeout;
+        self
+    }
+
+    /// Set the cache used for idempotent uploads.
+    pub fn with_idempotency(mut self, cache: IdempotencyCache) -> Self {
+        self.idempotency = Some(cache);
+        self
+    }
+
+    /// Set how document contents are hashed and whether duplicates are linked.
+    pub fn with_hashing(mut self, hashing: DocumentHashing) -> Self {
+        self.hashing = hashing;
+        self
+    }
+
+    /// Set the document types accepted for upload.
+    pub fn with_allowed_types(mut self, allowed_types: Vec<DocumentType>) -> Self {
+        self.allowed_types = allowed_types;
+        self
+    }
+
+    /// Set the limits applied when extracting documents from uploaded archives.
+    pub fn with_archive_limits(mut self, limits: ArchiveLimits) -> Self {
+        self.archive_limits = limits;
+        self
+    }
+
+    /// Set which images extracted from documents are stored.
+    pub fn with_image_extraction(mut self, extraction: ImageExtraction) -> Self {
+        self.image_extraction = extraction;
+        self
+    }
+
+    /// Set whether documents must be embedded to be considered ready.
+    pub fn with_ready_requires_embeddings(mut self, require: bool) -> Self {
+        self.ready_requires_embeddings = require;
+        self
+    }
+
+    /// The document types accepted for upload.
+    pub fn allowed_types(&self) -> &[DocumentType] {
+        &self.allowed_types
+    }
+
     /// Get a paginated list of documents from the repository.
     ///
     /// * `p`: Pagination and sorting options.
     /// * `src`: Optional document source to filter by.
     /// * `ready`: If given and `true`, return only documents that are ready for processing.
+    ///   Documents are ready once they have a parsing and chunking configuration and, if
+    ///   configured so, are embedded in at least one collection.
     pub async fn list_documents(
         &self,
         p: PaginationSort<DocumentSearchColumn>,
@@ -58,7 +203,12 @@ impl DocumentService {
         ready: Option<bool>,
     ) -> Result<List<Document>, ChonkitError> {
         map_err!(p.validate());
-        self.repo.list_documents(p, src, ready).await
+        let p = p.clamp(self.max_page_size);
+        let pagination = p.pagination();
+        self.repo
+            .list_documents(p, src, ready, self.ready_requires_embeddings)
+            .await
+            .map(|list| list.paginated(pagination))
     }
 
     /// Get a paginated list of documents from the repository with additional info for each.
@@ -70,7 +220,12 @@ impl DocumentService {
         src: Option<&str>,
     ) -> Result<List<DocumentDisplay>, ChonkitError> {
         map_err!(p.validate());
-        self.repo.list_documents_with_collections(p, src).await
+        let p = p.clamp(self.max_page_size);
+        let pagination = p.pagination();
+        self.repo
+            .list_documents_with_collections(p, src)
+            .await
+            .map(|list| list.paginated(pagination))
     }
 
     /// Get a document from the repository.
@@ -105,7 +260,11 @@ impl DocumentService {
         self.repo.update_document_metadata(id, update, None).await
     }
 
-    /// Used for already uploaded documents to re-process their images.
+    /// Used for already uploaded documents to re-process their images, e.g. when their
+    /// images are pending because the image storage was unavailable during upload.
+    ///
+    /// Unlike during upload, this waits for the images to be processed and returns an
+    /// error if any of them could not be stored.
     pub async fn process_document_images(&self, id: Uuid) -> Result<(), ChonkitError> {
         let Some(document) = self.repo.get_document_by_id(id).await? else {
             return err!(DoesNotExist, "Document with ID {id}");
@@ -118,9 +277,10 @@ impl DocumentService {
             .read(&document.path)
             .await?;
 
-        process_document_images(
+        store_document_images(
             self.repo.clone(),
             self.providers.image.clone(),
+            self.image_extraction,
             id,
             DocumentType::try_from(document.ext.as_str())?,
             file,
@@ -133,6 +293,9 @@ impl DocumentService {
     /// Insert the document metadata to the repository and persist it
     /// in the underlying storage implementation.
     ///
+    /// If linking duplicates is enabled and a document with the same content exists, the upload
+    /// is linked to it as an additional source and the existing document is returned.
+    ///
     /// * `store`: The storage implementation.
     /// * `params`: Upload params.
     pub async fn upload(&self, mut params: DocumentUpload<'_>) -> Result<Document, ChonkitError> {
@@ -140,20 +303,185 @@ impl DocumentService {
 
         let DocumentUpload { ref name, ty, file } = params;
 
+        ty.ensure_allowed(&self.allowed_types)?;
+
+        if is_blank(ty, file) {
+            return err!(InvalidFile, "Document '{name}' has no embeddable content");
+        }
+
         let img_store = self.providers.image.clone();
         let doc_store = self.providers.document.get_provider(FS_STORE_ID)?;
 
-        let document = store_document(&self.repo, &*doc_store, name, ty, file).await?;
+        let hash = self.hashing.algorithm.hash(file);
+
+        if self.hashing.link_duplicates {
+            let path = doc_store.absolute_path(name, ty);
+            if let Some(existing) =
+                link_document_source(&self.repo, &hash, doc_store.id(), &path).await?
+            {
+                return Ok(existing);
+            }
+        }
+
+        let document = store_document(&self.repo, &*doc_store, name, ty, file, &hash).await?;
 
-        // Process images in the background as it can take a while
+        // Process images in the background as it can take a while. Failures do not affect
+        // the upload, the document's images are marked as pending instead.
 
         let file = file.to_vec();
 
-        process_document_images(self.repo.clone(), img_store, document.id, ty, file).await?;
+        process_document_images(
+            self.repo.clone(),
+            img_store,
+            self.image_extraction,
+            document.id,
+            ty,
+            file,
+        );
 
         Ok(document)
     }
 
+    /// Extract the files of a zip or gzipped tar archive so they can be uploaded as documents.
+    /// See [archive::extract].
+    ///
+    /// * `archive`: The archive bytes.
+    pub async fn extract_archive(
+        &self,
+        archive: Vec<u8>,
+    ) -> Result<Vec<ArchiveEntry>, ChonkitError> {
+        let limits = self.archive_limits;
+        let extract = move || archive::extract(&archive, limits);
+        map_err!(tokio::task::spawn_blocking(extract).await)
+    }
+
+    /// Replace the content of a document in its storage, keeping its ID, configurations and
+    /// the collections it is embedded in.
+    ///
+    /// The hash of the document is recomputed and its update time bumped, which marks its
+    /// embeddings as outdated. If the content is unchanged, the document is returned as is.
+    ///
+    /// * `id`: Document ID.
+    /// * `file`: The new content of the document.
+    pub async fn update_content(&self, id: Uuid, file: &[u8]) -> Result<Document, ChonkitError> {
+        if file.is_empty() {
+            return err!(InvalidFile, "Document content cannot be empty");
+        }
+
+        let Some(document) = self.repo.get_document_by_id(id).await? else {
+            return err!(DoesNotExist, "Document with ID {id}");
+        };
+
+        let hash = self.hashing.algorithm.hash(file);
+
+        if hash == document.hash {
+            return Ok(document);
+        }
+
+        if let Some(existing) = self.repo.get_document_by_hash(&hash).await? {
+            return err!(
+                AlreadyExists,
+                "New content of '{}' has same hash as existing '{}' ({})",
+                document.name,
+                existing.name,
+                existing.id
+            );
+        }
+
+        let ty = DocumentType::try_from(document.ext.as_str())?;
+        let store = self.providers.document.get_provider(&document.src)?;
+
+        // Kept in case writing the new content fails after the old one is deleted
+        let previous = store.read(&document.path).await?;
+
+        let updated = self
+            .repo
+            .transaction(|tx| {
+                Box::pin(async {
+                    let updated = self.repo.update_document_hash(id, &hash, Some(tx)).await?;
+
+                    store.delete(&document.path).await?;
+
+                    if let Err(e) = store.write(&document.path, file).await {
+                        if let Err(e) = store.write(&document.path, &previous).await {
+                            tracing::error!("{id} - failed to restore previous content: {e}");
+                        }
+                        return Err(e);
+                    }
+
+                    Ok(updated)
+                })
+            })
+            .await?;
+
+        process_document_images(
+            self.repo.clone(),
+            self.providers.image.clone(),
+            self.image_extraction,
+            id,
+            ty,
+            file.to_vec(),
+        );
+
+        Ok(updated)
+    }
+
+    /// Same as [upload](Self::upload), but repeated uploads of the same file with the same `key`
+    /// return the document created by the first one as long as the key has not expired.
+    ///
+    /// Returns an error if an upload with the same key is still in progress.
+    ///
+    /// * `key`: Idempotency key provided by the client.
+    /// * `params`: Upload params.
+    pub async fn upload_idempotent(
+        &self,
+        key: &str,
+        params: DocumentUpload<'_>,
+    ) -> Result<Document, ChonkitError> {
+        let Some(ref cache) = self.idempotency else {
+            return self.upload(params).await;
+        };
+
+        // Keys are scoped to file names since a single request can upload multiple files
+        let key = format!("idempotency:{key}:{}", params.name);
+
+        match cache.claim(&key).await? {
+            None => {}
+            Some(Some(id)) => return self.get_document(id).await,
+            Some(None) => {
+                return err!(
+                    AlreadyExists,
+                    "Upload of '{}' with the same idempotency key is in progress",
+                    params.name
+                )
+            }
+        }
+
+        match self.upload(params).await {
+            Ok(document) => {
+                cache.complete(&key, document.id).await?;
+                Ok(document)
+            }
+            Err(e) => {
+                // Failed uploads can be retried with the same key
+                if let Err(e) = cache.release(&key).await {
+                    tracing::error!("Error releasing idempotency key '{key}': {e}");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// List the sources of a document, starting with the one it was first obtained from.
+    ///
+    /// * `id`: Document ID.
+    pub async fn list_sources(&self, id: Uuid) -> Result<Vec<DocumentSource>, ChonkitError> {
+        if self.repo.get_document_by_id(id).await?.is_none() {
+            return err!(DoesNotExist, "Document with ID {id}");
+        }
+        self.repo.list_document_sources(id).await
+    }
+
     /// Remove the document from the repo, delete it from storage, delete all of its images,
     /// and remove all of its text embeddings and image embeddings from all vector databases.
     ///
@@ -200,6 +528,173 @@ impl DocumentService {
 
         // Remove the document only when we are certain it is cleaned up
 
+        self.remove_document(&document, &*store).await
+    }
+
+    /// Delete multiple documents along with their storage files, images and embeddings.
+    ///
+    /// Vectors are deleted with a single operation per collection for all the documents
+    /// embedded in it. Failing to delete a document does not prevent deleting the rest.
+    /// Returns the outcome for each of the document IDs, in the order they were given.
+    ///
+    /// * `payload`: The IDs of the documents to delete.
+    pub async fn delete_many(
+        &self,
+        payload: BulkDeletePayload,
+    ) -> Result<Vec<DocumentDeleteResult>, ChonkitError> {
+        map_err!(payload.validate());
+
+        let mut ids = payload.ids;
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+
+        let mut failures: HashMap<Uuid, ChonkitError> = HashMap::new();
+        let mut documents = vec![];
+        let mut images = vec![];
+
+        // (vector provider, collection name) -> (document IDs, image IDs)
+        let mut collections: HashMap<(String, String), (Vec<Uuid>, Vec<Uuid>)> = HashMap::new();
+
+        for id in ids.iter().copied() {
+            let result: Result<(), ChonkitError> = async {
+                let Some(document) = self.repo.get_document_by_id(id).await? else {
+                    return err!(DoesNotExist, "Document with ID {id}");
+                };
+
+                let store = self.providers.document.get_provider(&document.src)?;
+
+                let document_collections = self
+                    .repo
+                    .get_document_assigned_collections(document.id)
+                    .await?;
+
+                let document_images = self
+                    .repo
+                    .list_all_document_images(document.id, self.providers.image.id())
+                    .await?;
+
+                for (_, name, provider) in document_collections {
+                    let (document_ids, image_ids) =
+                        collections.entry((provider, name)).or_default();
+                    document_ids.push(document.id);
+                    image_ids.extend(document_images.iter().map(|image| image.id));
+                }
+
+                images.extend(document_images);
+                documents.push((document, store));
+
+                Ok(())
+            }
+            .await;
+
+            if let Err(e) = result {
+                failures.insert(id, e);
+            }
+        }
+
+        for ((provider, name), (document_ids, image_ids)) in collections {
+            let result: Result<(), ChonkitError> = async {
+                let vector_db = self.providers.vector.get_provider(&provider)?;
+                vector_db
+                    .delete_text_embeddings_many(&name, &document_ids)
+                    .await?;
+                vector_db
+                    .delete_image_embeddings_many(&name, &image_ids)
+                    .await
+            }
+            .await;
+
+            if let Err(e) = result {
+                tracing::error!("Failed to delete embeddings from collection '{name}': {e}");
+            }
+        }
+
+        for image in images {
+            if let Err(e) = self.providers.image.delete_image(&image.path).await {
+                tracing::error!("Failed to delete image '{}': {e}", image.path);
+            }
+        }
+
+        // Remove the documents only when we are certain they are cleaned up
+
+        for (document, store) in documents {
+            if let Err(e) = self.remove_document(&document, &*store).await {
+                failures.insert(document.id, e);
+            }
+        }
+
+        Ok(ids
+            .into_iter()
+            .map(|id| DocumentDeleteResult::new(id, failures.remove(&id)))
+            .collect())
+    }
+
+    /// Apply the same parsing and chunking configuration to many documents, making them ready
+    /// for processing. Each document is configured in its own transaction, so a failing
+    /// document does not affect the others.
+    ///
+    /// Returns the outcome for each unique ID in the order given.
+    pub async fn configure_many(
+        &self,
+        payload: BulkConfigurePayload,
+    ) -> Result<Vec<DocumentConfigureResult>, ChonkitError> {
+        map_err!(payload.validate());
+
+        let BulkConfigurePayload {
+            mut ids,
+            parser,
+            chunker,
+        } = payload;
+
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+
+        let (parser, chunker) = (&parser, &chunker);
+        let mut results = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let result = async {
+                if self.repo.get_document_by_id(id).await?.is_none() {
+                    return err!(DoesNotExist, "Document with ID {id}");
+                }
+
+                self.repo
+                    .transaction(|tx| {
+                        Box::pin(async move {
+                            self.repo
+                                .upsert_document_parse_config(
+                                    id,
+                                    None,
+                                    parser.clone(),
+                                    Some(&mut *tx),
+                                )
+                                .await?;
+                            self.repo
+                                .upsert_document_chunk_config(id, None, chunker.clone(), Some(tx))
+                                .await?;
+                            Ok(())
+                        })
+                    })
+                    .await
+            }
+            .await;
+
+            if let Err(e) = &result {
+                tracing::error!("{id} - error configuring document: {e}");
+            }
+
+            results.push(DocumentConfigureResult::new(id, result.err()));
+        }
+
+        Ok(results)
+    }
+
+    /// Remove the document from the repository and delete its file from the `store`.
+    async fn remove_document(
+        &self,
+        document: &Document,
+        store: &(dyn DocumentStorage + Send + Sync),
+    ) -> Result<(), ChonkitError> {
         self.repo
             .transaction(|tx| {
                 Box::pin(async {
@@ -209,20 +704,25 @@ impl DocumentService {
                     store.delete(&document.path).await
                 })
             })
-            .await?;
-
-        Ok(())
+            .await
     }
 
     /// Sync storage contents with the repo.
+    ///
+    /// Only one sync can run per storage at a time. Attempting to sync a storage that is
+    /// already being synced, manually or on its schedule, results in an error.
     pub async fn sync(&self, provider: &str) -> Result<(), ChonkitError> {
         let __start = Instant::now();
 
         let store = self.providers.document.get_provider(provider)?;
 
+        let Some(_guard) = SyncGuard::acquire(&self.syncing, store.id()) else {
+            return err!(AlreadyExists, "Storage '{}' is already syncing", store.id());
+        };
+
         tracing::info!("Syncing documents with {}", store.id());
 
-        store.sync(&self.repo).await?;
+        store.sync(&self.repo, self.hashing).await?;
 
         tracing::info!(
             "Syncing finished for storage '{}', took {}ms",
@@ -233,6 +733,128 @@ impl DocumentService {
         Ok(())
     }
 
+    /// Recompute the content hashes of all documents, or only those from the given source,
+    /// from the content in their stores and report the ones whose stored hash no longer
+    /// matches, e.g. because of bit rot or manual edits.
+    ///
+    /// Mismatches and documents that could not be verified are reported as they are found,
+    /// interleaved with progress events, followed by a summary. If fixing is requested, the
+    /// stored hashes are replaced with the recomputed ones, which also bumps the documents'
+    /// `updated_at` and outdates their embeddings.
+    ///
+    /// Verification stops early if the returned stream is dropped.
+    pub fn verify_hashes(
+        &self,
+        payload: VerifyHashesPayload,
+    ) -> impl Stream<Item = Result<HashVerificationEvent, ChonkitError>> + Send + 'static {
+        let (tx, rx) = tokio::sync::mpsc::channel(HASH_VERIFICATION_PROGRESS_INTERVAL);
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service.verify_hashes_into(payload, &tx).await {
+                let _ = tx.send(Err(e)).await;
+            }
+        });
+
+        tokio_stream::wrappers::ReceiverStream::new(rx)
+    }
+
+    async fn verify_hashes_into(
+        &self,
+        payload: VerifyHashesPayload,
+        tx: &Sender<Result<HashVerificationEvent, ChonkitError>>,
+    ) -> Result<(), ChonkitError> {
+        let documents = self.repo.list_all_documents(payload.src.as_deref()).await?;
+        let fix = payload.fix.unwrap_or(false);
+
+        let mut summary = HashVerificationSummary {
+            total: documents.len(),
+            ..Default::default()
+        };
+
+        for (i, document) in documents.into_iter().enumerate() {
+            let event = match self.verify_hash(&document, fix).await {
+                Ok(None) => None,
+                Ok(Some(mismatch)) => {
+                    summary.mismatched += 1;
+                    summary.fixed += mismatch.fixed as usize;
+                    Some(HashVerificationEvent::Mismatch(mismatch))
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    Some(HashVerificationEvent::Failed {
+                        document_id: document.id,
+                        error: e.error.to_string(),
+                    })
+                }
+            };
+
+            let verified = i + 1;
+            let progress = (verified % HASH_VERIFICATION_PROGRESS_INTERVAL == 0).then_some(
+                HashVerificationEvent::Progress {
+                    verified,
+                    total: summary.total,
+                },
+            );
+
+            for event in event.into_iter().chain(progress) {
+                if tx.send(Ok(event)).await.is_err() {
+                    tracing::debug!("Hash verification cancelled after {verified} documents");
+                    return Ok(());
+                }
+            }
+        }
+
+        tracing::info!(
+            "Verified hashes of {} documents ({} mismatched, {} fixed, {} failed)",
+            summary.total,
+            summary.mismatched,
+            summary.fixed,
+            summary.failed
+        );
+
+        let _ = tx.send(Ok(HashVerificationEvent::Done(summary))).await;
+
+        Ok(())
+    }
+
+    /// Recompute the hash of the document, returning the mismatch if it differs from the
+    /// stored one.
+    async fn verify_hash(
+        &self,
+        document: &Document,
+        fix: bool,
+    ) -> Result<Option<DocumentHashMismatch>, ChonkitError> {
+        let content = self
+            .providers
+            .document
+            .get_provider(&document.src)?
+            .read(&document.path)
+            .await?;
+
+        let actual = self.hashing.algorithm.hash(&content);
+
+        if actual == document.hash {
+            return Ok(None);
+        }
+
+        if fix {
+            self.repo
+                .update_document_hash(document.id, &actual, None)
+                .await?;
+        }
+
+        Ok(Some(DocumentHashMismatch {
+            document_id: document.id,
+            name: document.name.clone(),
+            src: document.src.clone(),
+            path: document.path.clone(),
+            stored: document.hash.clone(),
+            actual,
+            fixed: fix,
+        }))
+    }
+
     /// Chunk the document without saving any embeddings. Useful for previewing.
     ///
     /// * `document_id`: ID of the document to chunk.
@@ -246,56 +868,194 @@ impl DocumentService {
 
         let parse_config = match config.parse_config {
             Some(cfg) => cfg,
-            None => self
-                .get_config(document_id)
-                .await?
-                .parse_config
-                .unwrap_or_default(),
+            None => {
+                let document = self.get_config(document_id).await?;
+                self.parser_defaults
+                    .resolve(document.ext.as_str().try_into()?, document.parse_config)
+            }
+        };
+
+        let tokenizer = self.tokenizer_for(config.model.as_deref());
+
+        let (document, content) = self.get_document_with_content(document_id).await?;
+
+        let preview = self
+            .parse_content_preview(
+                document.ext.as_str().try_into()?,
+                content,
+                parse_config,
+                &tokenizer,
+            )
+            .await?;
+
+        self.chunk_parsed_preview(preview, config.chunker, &tokenizer)
+            .await
+    }
+
+    /// Chunk the document with two configurations and compare the resulting chunk boundaries.
+    /// Useful for seeing how a configuration change, e.g. to the skips, affects the chunks
+    /// before re-embedding.
+    ///
+    /// * `document_id`: ID of the document to chunk.
+    /// * `payload`: The configurations to compare.
+    pub async fn chunk_diff(
+        &self,
+        document_id: Uuid,
+        payload: dto::ChunkDiffPayload,
+    ) -> Result<ChunkDiff, ChonkitError> {
+        map_err!(payload.validate());
+
+        let parse_config = match payload.parse_config {
+            Some(cfg) => cfg,
+            None => {
+                let document = self.get_config(document_id).await?;
+                self.parser_defaults
+                    .resolve(document.ext.as_str().try_into()?, document.parse_config)
+            }
+        };
+
+        let (document, content) = self.get_document_with_content(document_id).await?;
+
+        let preview = self
+            .parse_content_preview(
+                document.ext.as_str().try_into()?,
+                content,
+                parse_config,
+                &self.tokenizer,
+            )
+            .await?;
+
+        let ParseOutputPreview::String(text) = preview.content else {
+            return err!(
+                InvalidParameter,
+                "Chunk diffs cannot be obtained with the section parser"
+            );
+        };
+
+        let mut boundaries = vec![];
+        let mut counts = vec![];
+
+        for config in [payload.before, payload.after] {
+            let chunked =
+                crate::core::chunk::chunk(&self.providers, config, &text, self.max_chunks).await?;
+
+            counts.push(chunked.chunks.len());
+            boundaries.push(match chunked.chunks {
+                ChunkedDocument::Ref(chunks) => chunk_boundaries(&text, &chunks),
+                ChunkedDocument::Owned(chunks) => chunk_boundaries(&text, &chunks),
+            });
+        }
+
+        let (before, after) = (&boundaries[0], &boundaries[1]);
+
+        Ok(ChunkDiff {
+            added: after.difference(before).copied().collect(),
+            removed: before.difference(after).copied().collect(),
+            chunks_before: counts[0],
+            chunks_after: counts[1],
+            chunk_count_delta: counts[1] as i64 - counts[0] as i64,
+        })
+    }
+
+    /// Parse and chunk inline content without storing anything. Useful for tuning parsing and
+    /// chunking configurations before uploading documents.
+    ///
+    /// * `payload`: The content, its type and the configurations to preview with.
+    pub async fn inline_preview(
+        &self,
+        payload: dto::InlinePreviewPayload,
+    ) -> Result<ChunkPreview, ChonkitError> {
+        map_err!(payload.validate());
+
+        let ty = DocumentType::try_from(payload.ty.as_str())?;
+
+        let content = match ty {
+            DocumentType::Text(_) | DocumentType::Markdown => payload.content.into_bytes(),
+            _ => map_err!(base64::engine::general_purpose::STANDARD.decode(payload.content)),
         };
 
+        if content.len() > MAX_INLINE_PREVIEW_SIZE {
+            return err!(
+                InvalidParameter,
+                "Inline content has {} bytes, the maximum is {MAX_INLINE_PREVIEW_SIZE}",
+                content.len()
+            );
+        }
+
+        let tokenizer = self.tokenizer_for(payload.model.as_deref());
+
+        let preview = self
+            .parse_content_preview(
+                ty,
+                content,
+                self.parser_defaults.resolve(ty, payload.parse_config),
+                &tokenizer,
+            )
+            .await?;
+
+        self.chunk_parsed_preview(preview, Some(payload.chunker), &tokenizer)
+            .await
+    }
+
+    /// Chunk the output of a parse preview.
+    ///
+    /// * `preview`: The parsed content.
+    /// * `chunker`: Chunking configuration, required for string outputs.
+    /// * `tokenizer`: Counts the tokens of the chunks.
+    async fn chunk_parsed_preview(
+        &self,
+        preview: ParsePreview,
+        chunker: Option<ChunkConfig>,
+        tokenizer: &Tokenizer,
+    ) -> Result<ChunkPreview, ChonkitError> {
         let ParsePreview {
             content: text,
             total_tokens,
-        } = self.parse_preview(document_id, parse_config).await?;
+            ..
+        } = preview;
 
         match text {
             ParseOutputPreview::String(text) => {
-                let Some(chunker) = config.chunker else {
+                let Some(chunker) = chunker else {
                     return err!(InvalidParameter, "Chunking configuration must be specified when previewing with generic parser");
                 };
 
                 let mut total_tokens_post = TokenCount::default();
 
-                let chunks =
-                    match crate::core::chunk::chunk(&self.providers, chunker, &text).await? {
-                        ChunkedDocument::Ref(chunked) => chunked
-                            .into_iter()
-                            .map(|s| {
-                                let token_count = self.tokenizer.count(s);
-                                total_tokens_post += token_count;
-                                ChunkForPreview {
-                                    token_count,
-                                    chunk: s.to_string(),
-                                }
-                            })
-                            .collect(),
-                        ChunkedDocument::Owned(chunked) => chunked
-                            .into_iter()
-                            .map(|s| {
-                                let token_count = self.tokenizer.count(&s);
-                                total_tokens_post += token_count;
-                                ChunkForPreview {
-                                    token_count,
-                                    chunk: s,
-                                }
-                            })
-                            .collect(),
-                    };
+                let chunked =
+                    crate::core::chunk::chunk(&self.providers, chunker, &text, self.max_chunks)
+                        .await?;
+
+                let chunks = match chunked.chunks {
+                    ChunkedDocument::Ref(chunked) => chunked
+                        .into_iter()
+                        .map(|s| {
+                            let token_count = tokenizer.count(s);
+                            total_tokens_post += token_count;
+                            ChunkForPreview {
+                                token_count,
+                                chunk: s.to_string(),
+                            }
+                        })
+                        .collect(),
+                    ChunkedDocument::Owned(chunked) => chunked
+                        .into_iter()
+                        .map(|s| {
+                            let token_count = tokenizer.count(&s);
+                            total_tokens_post += token_count;
+                            ChunkForPreview {
+                                token_count,
+                                chunk: s,
+                            }
+                        })
+                        .collect(),
+                };
 
                 Ok(ChunkPreview {
                     chunks,
                     total_tokens_pre: total_tokens,
                     total_tokens_post,
+                    tuned_config: chunked.tuned,
                 })
             }
             ParseOutputPreview::Sections(sections) => {
@@ -310,7 +1070,7 @@ impl DocumentService {
                         acc.push('\n');
                         acc
                     });
-                    let count = self.tokenizer.count(&content);
+                    let count = tokenizer.count(&content);
                     chunks.push(ChunkForPreview {
                         token_count: count,
                         chunk: content,
@@ -323,6 +1083,7 @@ impl DocumentService {
                     chunks,
                     total_tokens_pre: total_tokens,
                     total_tokens_post: total_tokens,
+                    tuned_config: None,
                 })
             }
         }
@@ -345,9 +1106,102 @@ impl DocumentService {
 
         let ext = document.ext.as_str().try_into()?;
 
-        let output = map_err!(
-            tokio::task::spawn_blocking(move || { parse_text(config, ext, &content) }).await
-        )?;
+        self.parse_content_preview(ext, content, config, &self.tokenizer)
+            .await
+    }
+
+    /// Get the complete parse of a stored document for external processing, paginated by
+    /// section for sectioned documents. Uses the document's stored parsing configuration
+    /// unless one is given.
+    ///
+    /// * `id`: Document ID.
+    /// * `params`: The parsing configuration as JSON and the pagination of sections.
+    pub async fn get_parsed(
+        &self,
+        id: Uuid,
+        params: ParsedDocumentParameters,
+    ) -> Result<ParsedDocument, ChonkitError> {
+        let config = match params.config {
+            Some(config) => match serde_json::from_str(&config) {
+                Ok(config) => config,
+                Err(e) => return err!(InvalidParameter, "Invalid parse configuration; {e}"),
+            },
+            None => {
+                let document = self.get_config(id).await?;
+                self.parser_defaults
+                    .resolve(document.ext.as_str().try_into()?, document.parse_config)
+            }
+        };
+
+        let (document, content) = self.get_document_with_content(id).await?;
+
+        let ParsePreview {
+            content,
+            total_tokens,
+            metadata,
+        } = self
+            .parse_content_preview(
+                document.ext.as_str().try_into()?,
+                content,
+                config,
+                &self.tokenizer,
+            )
+            .await?;
+
+        let (content, total_sections) = match content {
+            ParseOutputPreview::String(text) => (ParseOutputPreview::String(text), None),
+            ParseOutputPreview::Sections(sections) => {
+                let pagination = params.pagination.unwrap_or_default();
+                map_err!(pagination.validate());
+
+                let (limit, offset) = pagination.clamp(self.max_page_size).to_limit_offset();
+                let total = sections.len();
+                let sections = sections
+                    .into_iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .collect();
+
+                (ParseOutputPreview::Sections(sections), Some(total))
+            }
+        };
+
+        Ok(ParsedDocument {
+            content,
+            total_sections,
+            total_tokens,
+            metadata,
+        })
+    }
+
+    /// Get the tokenizer counting the tokens of the `model`, or the default one if not given.
+    fn tokenizer_for(&self, model: Option<&str>) -> Tokenizer {
+        match model {
+            Some(model) => self.tokenizer.for_model(model),
+            None => self.tokenizer.clone(),
+        }
+    }
+
+    /// Parse the `content` as a document of type `ext` and remap the output for display.
+    ///
+    /// * `ext`: Document type of the content.
+    /// * `content`: Document bytes.
+    /// * `config`: Parsing configuration.
+    /// * `tokenizer`: Counts the tokens of the output.
+    async fn parse_content_preview(
+        &self,
+        ext: DocumentType,
+        content: Vec<u8>,
+        config: ParseConfig,
+        tokenizer: &Tokenizer,
+    ) -> Result<ParsePreview, ChonkitError> {
+        let metadata = match ext {
+            DocumentType::Markdown => markdown::front_matter(&content),
+            _ => None,
+        };
+
+        let timeout = config.timeout().unwrap_or(self.parse_timeout);
+        let output = parse_with_timeout(timeout, move || parse_text(config, ext, &content)).await?;
 
         if output.is_empty() {
             return err!(InvalidFile, "Parsing resulted in empty output");
@@ -355,8 +1209,9 @@ impl DocumentService {
 
         match output {
             ParseOutput::String(text) => Ok(ParsePreview {
-                total_tokens: self.tokenizer.count(&text),
+                total_tokens: tokenizer.count(&text),
                 content: dto::ParseOutputPreview::String(text),
+                metadata,
             }),
             ParseOutput::Sections(document_sections) => {
                 let mut total_tokens = TokenCount::default();
@@ -366,7 +1221,7 @@ impl DocumentService {
                     let mut pages = vec![];
 
                     for page in section.pages {
-                        total_tokens += self.tokenizer.count(&page.content);
+                        total_tokens += tokenizer.count(&page.content);
                         pages.push(ParsedDocumentPage {
                             content: page.content,
                             number: page.number,
@@ -379,6 +1234,7 @@ impl DocumentService {
                 Ok(ParsePreview {
                     total_tokens,
                     content: dto::ParseOutputPreview::Sections(sections),
+                    metadata,
                 })
             }
         }
@@ -401,7 +1257,7 @@ impl DocumentService {
         }
 
         self.repo
-            .upsert_document_parse_config(id, collection_id, config)
+            .upsert_document_parse_config(id, collection_id, config, None)
             .await?;
 
         Ok(())
@@ -422,36 +1278,45 @@ impl DocumentService {
         }
 
         self.repo
-            .upsert_document_chunk_config(id, collection_id, config)
+            .upsert_document_chunk_config(id, collection_id, config, None)
             .await?;
 
         Ok(())
     }
 
     /// Creates the default document if no other document exists.
-    pub async fn create_default_document(&self) {
-        let count = self.repo.get_document_count().await.unwrap_or(0);
+    ///
+    /// Runs under the startup advisory lock, so instances starting against the same database
+    /// wait for each other and only the first one creates the document.
+    pub async fn create_default_document(&self) -> Result<(), ChonkitError> {
+        self.repo
+            .with_advisory_lock(STARTUP_LOCK_KEY, || async {
+                let count = self.repo.get_document_count().await?;
 
-        if count > 0 {
-            tracing::info!("Found existing documents, skipping default document creation");
-            return;
-        }
+                if count > 0 {
+                    tracing::info!("Found existing documents, skipping default document creation");
+                    return Ok(());
+                }
 
-        match self
-            .upload(DocumentUpload::new(
-                String::from(DEFAULT_DOCUMENT_NAME),
-                DocumentType::Text(TextDocumentType::Txt),
-                DEFAULT_DOCUMENT_CONTENT.as_bytes(),
-            ))
-            .await
-        {
-            Ok(_) => tracing::info!("Created default document '{DEFAULT_DOCUMENT_NAME}'"),
-            Err(e) => {
-                if let crate::error::ChonkitErr::AlreadyExists(_) = e.error {
-                    tracing::info!("Default document '{DEFAULT_DOCUMENT_NAME}' already exists");
+                let upload = DocumentUpload::new(
+                    String::from(DEFAULT_DOCUMENT_NAME),
+                    DocumentType::Text(TextDocumentType::Txt),
+                    DEFAULT_DOCUMENT_CONTENT.as_bytes(),
+                );
+
+                match self.upload(upload).await {
+                    Ok(_) => {
+                        tracing::info!("Created default document '{DEFAULT_DOCUMENT_NAME}'");
+                        Ok(())
+                    }
+                    Err(e) if matches!(e.error, ChonkitErr::AlreadyExists(_)) => {
+                        tracing::info!("Default document '{DEFAULT_DOCUMENT_NAME}' already exists");
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
                 }
-            }
-        }
+            })
+            .await?
     }
 
     /// Get a document with its content bytes.
@@ -506,9 +1371,55 @@ impl DocumentService {
         &self,
         parameters: ListImagesParameters,
     ) -> Result<List<ImageModel>, ChonkitError> {
+        let pagination = parameters.pagination.unwrap_or_default();
         self.repo
             .list_document_images(self.providers.image.id(), parameters)
             .await
+            .map(|list| list.paginated(pagination))
+    }
+
+    /// List all images extracted from the document along with the collections each image is
+    /// embedded in. Thumbnails are loaded from the image storage only when requested.
+    ///
+    /// * `document_id`: Document ID.
+    /// * `parameters`: Whether to include thumbnails.
+    pub async fn list_document_images(
+        &self,
+        document_id: Uuid,
+        parameters: DocumentImagesParameters,
+    ) -> Result<Vec<ImageDisplay>, ChonkitError> {
+        if self.repo.get_document_by_id(document_id).await?.is_none() {
+            return err!(DoesNotExist, "Document with ID {document_id}");
+        }
+
+        let images = self
+            .repo
+            .list_all_document_images(document_id, self.providers.image.id())
+            .await?;
+
+        let mut displays = Vec::with_capacity(images.len());
+
+        for image in images {
+            let collections = self
+                .repo
+                .get_image_assigned_collections(image.id)
+                .await?
+                .into_iter()
+                .map(|(id, name, provider)| ImageCollection::new(id, name, provider))
+                .collect();
+
+            let mut display = ImageDisplay::new(image, collections);
+
+            if parameters.thumbnail.unwrap_or_default() {
+                let (image, _) = self.get_image(display.image.id).await?;
+                let thumbnail = image.image.thumbnail(IMAGE_THUMBNAIL_SIZE)?;
+                display.thumbnail = Some(thumbnail.to_b64_data_uri());
+            }
+
+            displays.push(display);
+        }
+
+        Ok(displays)
     }
 
     /// Update an image's description which is used to enrich its embeddings.
@@ -539,6 +1450,67 @@ impl DocumentService {
     }
 }
 
+/// Marks a storage as syncing for as long as it is held, see [DocumentService::sync].
+struct SyncGuard<'a> {
+    syncing: &'a Mutex<HashSet<String>>,
+    id: &'a str,
+}
+
+impl<'a> SyncGuard<'a> {
+    /// Returns `None` if the storage is already syncing.
+    fn acquire(syncing: &'a Mutex<HashSet<String>>, id: &'a str) -> Option<Self> {
+        let mut current = syncing.lock().expect("syncing storages poisoned");
+        current
+            .insert(id.to_string())
+            .then_some(Self { syncing, id })
+    }
+}
+
+impl Drop for SyncGuard<'_> {
+    fn drop(&mut self) {
+        self.syncing
+            .lock()
+            .expect("syncing storages poisoned")
+            .remove(self.id);
+    }
+}
+
+/// Whether the file is empty or, for text documents, consists of whitespace only.
+/// Other document types are only checked for being empty since they can contain images
+/// without any text, which can still be embedded.
+fn is_blank(ty: DocumentType, file: &[u8]) -> bool {
+    match ty {
+        DocumentType::Text(_) => std::str::from_utf8(file).is_ok_and(|text| text.trim().is_empty()),
+        _ => file.is_empty(),
+    }
+}
+
+/// The byte offsets in the text at which the chunks end, excluding the end of the text.
+///
+/// Chunks are located in order since their starts never decrease, even with overlap.
+/// Chunks not found verbatim in the text, e.g. ones prefixed with headings, are skipped.
+fn chunk_boundaries(text: &str, chunks: &[impl AsRef<str>]) -> BTreeSet<usize> {
+    let mut boundaries = BTreeSet::new();
+    let mut cursor = 0;
+
+    for chunk in chunks {
+        let chunk = chunk.as_ref();
+
+        let Some(start) = text[cursor..].find(chunk).map(|start| cursor + start) else {
+            continue;
+        };
+
+        let end = start + chunk.len();
+        if end < text.trim_end().len() {
+            boundaries.insert(end);
+        }
+
+        cursor = start;
+    }
+
+    boundaries
+}
+
 /// Document service DTOs.
 pub mod dto {
     use crate::core::{
@@ -547,7 +1519,9 @@ pub mod dto {
         model::Pagination,
         token::TokenCount,
     };
+    use crate::error::ChonkitError;
     use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
     use uuid::Uuid;
     use validify::{Validate, Validify};
 
@@ -571,6 +1545,148 @@ pub mod dto {
         }
     }
 
+    /// Used for deleting multiple documents at once.
+    #[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BulkDeletePayload {
+        /// The IDs of the documents to delete.
+        #[validate(length(min = 1))]
+        pub ids: Vec<Uuid>,
+    }
+
+    /// Params for applying the same configuration to many documents.
+    #[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BulkConfigurePayload {
+        /// The IDs of the documents to configure.
+        #[validate(length(min = 1))]
+        pub ids: Vec<Uuid>,
+
+        /// Parsing configuration applied to every document.
+        #[validate]
+        pub parser: ParseConfig,
+
+        /// Chunking configuration applied to every document.
+        pub chunker: ChunkConfig,
+    }
+
+    /// The outcome of configuring a single document in a bulk configuration.
+    #[derive(Debug, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentConfigureResult {
+        /// Document ID.
+        pub id: Uuid,
+
+        /// Whether the configuration was applied to the document.
+        pub configured: bool,
+
+        /// The reason the document was not configured.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub error: Option<String>,
+    }
+
+    impl DocumentConfigureResult {
+        pub fn new(id: Uuid, error: Option<ChonkitError>) -> Self {
+            Self {
+                id,
+                configured: error.is_none(),
+                error: error.map(|e| e.error.to_string()),
+            }
+        }
+    }
+
+    /// The outcome of deleting a single document in a bulk deletion.
+    #[derive(Debug, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentDeleteResult {
+        /// Document ID.
+        pub id: Uuid,
+
+        /// Whether the document was deleted.
+        pub deleted: bool,
+
+        /// The reason the document was not deleted.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub error: Option<String>,
+    }
+
+    impl DocumentDeleteResult {
+        pub fn new(id: Uuid, error: Option<ChonkitError>) -> Self {
+            Self {
+                id,
+                deleted: error.is_none(),
+                error: error.map(|e| e.error.to_string()),
+            }
+        }
+    }
+
+    /// Params for verifying document hashes.
+    #[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct VerifyHashesPayload {
+        /// Only verify documents obtained from this source, e.g. `fs`.
+        /// If omitted, all documents are verified.
+        pub src: Option<String>,
+
+        /// If `true`, mismatched hashes are replaced with the recomputed ones.
+        /// Defaults to `false`.
+        pub fix: Option<bool>,
+    }
+
+    /// A document whose stored hash does not match its content.
+    #[derive(Debug, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentHashMismatch {
+        pub document_id: Uuid,
+        pub name: String,
+        pub src: String,
+        pub path: String,
+
+        /// The hash stored with the document.
+        pub stored: String,
+
+        /// The hash of the document's content in its store.
+        pub actual: String,
+
+        /// Whether the stored hash was replaced with the actual one.
+        pub fixed: bool,
+    }
+
+    #[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct HashVerificationSummary {
+        /// The amount of verified documents.
+        pub total: usize,
+        pub mismatched: usize,
+        pub fixed: usize,
+
+        /// The amount of documents that could not be verified.
+        pub failed: usize,
+    }
+
+    /// Events emitted when verifying document hashes.
+    #[derive(Debug, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase", tag = "type")]
+    pub enum HashVerificationEvent {
+        Mismatch(DocumentHashMismatch),
+
+        /// The document could not be read from its store or its hash could not be fixed.
+        Failed {
+            #[serde(rename = "documentId")]
+            document_id: Uuid,
+            error: String,
+        },
+
+        /// Emitted periodically during verification.
+        Progress {
+            verified: usize,
+            total: usize,
+        },
+
+        /// Always the last event of the stream.
+        Done(HashVerificationSummary),
+    }
+
     /// DTO used for previewing chunks.
     #[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
     #[serde(rename_all = "camelCase")]
@@ -581,6 +1697,50 @@ pub mod dto {
 
         /// Chunking configuration.
         pub chunker: Option<ChunkConfig>,
+
+        /// Embedding model to count the tokens of the chunks for, see
+        /// [Tokenizer::for_model][crate::core::token::Tokenizer::for_model].
+        pub model: Option<String>,
+    }
+
+    /// DTO used for comparing the chunks of a document obtained with two configurations.
+    #[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ChunkDiffPayload {
+        /// Parsing configuration. If not given, the document's configuration is used.
+        #[serde(alias = "parser")]
+        pub parse_config: Option<ParseConfig>,
+
+        /// The configuration to compare against, e.g. the one currently in use.
+        pub before: ChunkConfig,
+
+        /// The configuration to compare.
+        pub after: ChunkConfig,
+    }
+
+    /// DTO used for previewing content that is not stored as a document.
+    #[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InlinePreviewPayload {
+        /// The content to preview. Plain text and Markdown are given as is,
+        /// all other document types must be base64 encoded.
+        #[validate(length(min = 1, message = "Content cannot be empty."))]
+        pub content: String,
+
+        /// Document type, either as an extension or a mime type.
+        #[serde(alias = "type")]
+        pub ty: String,
+
+        /// Parsing configuration. Uses the default if not provided.
+        #[serde(alias = "parser")]
+        pub parse_config: Option<ParseConfig>,
+
+        /// Chunking configuration.
+        pub chunker: ChunkConfig,
+
+        /// Embedding model to count the tokens of the chunks for, see
+        /// [Tokenizer::for_model][crate::core::token::Tokenizer::for_model].
+        pub model: Option<String>,
     }
 
     #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -596,6 +1756,28 @@ pub mod dto {
         pub chunks: Vec<ChunkForPreview>,
         pub total_tokens_pre: TokenCount,
         pub total_tokens_post: TokenCount,
+
+        /// The configuration the chunks were obtained with, if it was auto-tuned.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub tuned_config: Option<ChunkConfig>,
+    }
+
+    /// The difference between the chunk boundaries of two chunking configurations.
+    /// Boundaries are the byte offsets in the parsed text at which chunks end.
+    #[derive(Debug, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ChunkDiff {
+        /// Boundaries only present with the `after` configuration.
+        pub added: Vec<usize>,
+
+        /// Boundaries only present with the `before` configuration.
+        pub removed: Vec<usize>,
+
+        pub chunks_before: usize,
+        pub chunks_after: usize,
+
+        /// `chunksAfter - chunksBefore`.
+        pub chunk_count_delta: i64,
     }
 
     #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -603,6 +1785,10 @@ pub mod dto {
     pub struct ParsePreview {
         pub content: ParseOutputPreview,
         pub total_tokens: TokenCount,
+
+        /// Document metadata not included in the parsed content, e.g. Markdown front matter.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub metadata: Option<BTreeMap<String, String>>,
     }
 
     #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -630,6 +1816,44 @@ pub mod dto {
         pub number: usize,
     }
 
+    /// The complete parse of a stored document.
+    #[derive(Debug, Serialize, utoipa::ToSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ParsedDocument {
+        /// The parsed text, or the requested page of sections for sectioned documents.
+        pub content: ParseOutputPreview,
+
+        /// The amount of sections in the whole document. Only present for sectioned documents.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub total_sections: Option<usize>,
+
+        /// Token count of the whole document.
+        pub total_tokens: TokenCount,
+
+        /// Document metadata not included in the parsed content, e.g. Markdown front matter.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub metadata: Option<BTreeMap<String, String>>,
+    }
+
+    #[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ParsedDocumentParameters {
+        /// The parsing configuration as JSON. Defaults to the document's stored configuration.
+        pub config: Option<String>,
+
+        /// Pagination of sections. Ignored for documents not parsed into sections.
+        #[serde(flatten)]
+        #[param(inline)]
+        pub pagination: Option<Pagination>,
+    }
+
+    #[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentImagesParameters {
+        /// If `true`, include a downscaled PNG of each image as a base64 data URI.
+        pub thumbnail: Option<bool>,
+    }
+
     #[derive(Debug, Deserialize, utoipa::ToSchema)]
     #[serde(rename_all = "camelCase")]
     pub struct ListImagesParameters {
@@ -1,27 +1,50 @@
-use crate::config::DEFAULT_IMAGE_PATCH_SIZE;
+use crate::config::{
+    DEFAULT_IMAGE_PATCH_SIZE, DEFAULT_MAX_CHUNKS, DEFAULT_MAX_PAGE_SIZE, DEFAULT_MAX_VECTOR_SIZE,
+    DEFAULT_PARSE_TIMEOUT,
+};
 use crate::core::cache::embedding::{
     CachedImageEmbeddings, CachedTextEmbeddings, ImageEmbeddingCacheKey, TextEmbeddingCacheKey,
 };
 use crate::core::cache::{ImageEmbeddingCache, TextEmbeddingCache};
-use crate::core::chunk::{ChunkConfig, ChunkedDocument};
-use crate::core::document::get_image;
-use crate::core::document::parser::{parse_text, ParseConfig, ParseOutput};
-use crate::core::embeddings::Embeddings;
+use crate::core::chunk::{apply_chunk_template, join_chunks, ChunkConfig, ChunkedDocument};
+use crate::core::document::parser::{
+    parse_text_from_reader, parse_with_timeout, ParseConfig, ParseOutput, ParserDefaults,
+};
+use crate::core::document::{get_image, DocumentType};
+use crate::core::llm::{self, Llm};
+use crate::core::model::document::DocumentConfig;
 use crate::core::model::embedding::{
-    EmbeddingAdditionReport, EmbeddingReport, EmbeddingReportBase, ImageEmbeddingAdditionReport,
-    ImageEmbeddingInsert, ImageEmbeddingRemovalReport, TextEmbedding, TextEmbeddingAdditionReport,
-    TextEmbeddingInsert, TextEmbeddingRemovalReport,
+    ChunkTokenBreakdown, DocumentEmbeddingRemovalReport, EmbeddingAdditionReport, EmbeddingReport,
+    EmbeddingReportAction, EmbeddingReportBase, EmbeddingReportKind, ImageEmbeddingAdditionReport,
+    ImageEmbeddingInsert, ImageEmbeddingRemovalReport, ReportRetention, TextEmbedding,
+    TextEmbeddingAdditionReport, TextEmbeddingInsert, TextEmbeddingRemovalReport,
 };
 use crate::core::model::{List, Pagination};
 use crate::core::provider::ProviderState;
 use crate::core::repo::Repository;
-use crate::core::vector::CollectionItemInsert;
-use crate::error::ChonkitError;
+use crate::core::service::collection::{
+    dto::{CollectionModality, CreateCollectionPayload},
+    CollectionService,
+};
+use crate::core::token::Tokenizer;
+use crate::core::vector::{
+    chunk_id, near_duplicates, validate_vectors, CollectionItemInsert, CollectionItemPayload,
+};
+use crate::error::{ChonkitErr, ChonkitError};
 use crate::{err, map_err};
 use chonkit_embedders::EmbeddingModel;
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio_util::io::SyncIoBridge;
 use uuid::Uuid;
-use validify::Validate;
+use validify::{schema_err, schema_validation, Validate, ValidationErrors};
 
 #[derive(Clone)]
 pub struct EmbeddingService {
@@ -29,6 +52,30 @@ pub struct EmbeddingService {
     providers: ProviderState,
     text_cache: TextEmbeddingCache,
     image_cache: ImageEmbeddingCache,
+
+    /// The maximum amount of chunks a document can be chunked into before embedding.
+    max_chunks: usize,
+
+    /// Listings requesting larger pages, or no pages at all, are clamped to this.
+    max_page_size: usize,
+
+    /// Parsing configurations for documents without a stored one.
+    parser_defaults: ParserDefaults,
+
+    /// How long to wait for parsing when the parsing configuration does not specify it.
+    parse_timeout: Duration,
+
+    /// Used to count chunk tokens for detailed reports.
+    tokenizer: Tokenizer,
+
+    /// Used for summarizing documents. Summaries cannot be embedded without one.
+    llm: Option<Arc<dyn Llm + Send + Sync>>,
+
+    /// If set, embedding into collection names that do not exist creates the collections.
+    auto_create: Option<AutoCreateCollections>,
+
+    /// The maximum vector size of the models of automatically created collections.
+    max_vector_size: usize,
 }
 
 impl EmbeddingService {
@@ -37,15 +84,66 @@ impl EmbeddingService {
         providers: ProviderState,
         text_cache: TextEmbeddingCache,
         image_cache: ImageEmbeddingCache,
+        tokenizer: Tokenizer,
     ) -> Self {
         Self {
             repo,
             providers,
             text_cache,
             image_cache,
+            max_chunks: DEFAULT_MAX_CHUNKS,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            parser_defaults: ParserDefaults::default(),
+            parse_timeout: Duration::from_millis(DEFAULT_PARSE_TIMEOUT),
+            tokenizer,
+            llm: None,
+            auto_create: None,
+            max_vector_size: DEFAULT_MAX_VECTOR_SIZE,
         }
     }
 
+    /// Set the maximum amount of chunks a document can be chunked into.
+    pub fn with_max_chunks(mut self, max_chunks: usize) -> Self {
+        self.max_chunks = max_chunks;
+        self
+    }
+
+    /// Set the maximum amount of items returned by a single page of listings.
+    pub fn with_max_page_size(mut self, max_page_size: usize) -> Self {
+        self.max_page_size = max_page_size;
+        self
+    }
+
+    /// Set the parsing configurations used for documents without a stored one.
+    pub fn with_parser_defaults(mut self, parser_defaults: ParserDefaults) -> Self {
+        self.parser_defaults = parser_defaults;
+        self
+    }
+
+    /// Set how long to wait for parsing when the parsing configuration does not specify it.
+    pub fn with_parse_timeout(mut self, timeout: Duration) -> Self {
+        self.parse_timeout = timeout;
+        self
+    }
+
+    /// Set the LLM used for summarizing documents.
+    pub fn with_llm(mut self, llm: Arc<dyn Llm + Send + Sync>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    /// Create collections that do not exist when embedding into them by name.
+    pub fn with_auto_create_collections(mut self, auto_create: AutoCreateCollections) -> Self {
+        self.auto_create = Some(auto_create);
+        self
+    }
+
+    /// Set the maximum vector size of the models of automatically created collections.
+    pub fn with_max_vector_size(mut self, max_vector_size: usize) -> Self {
+        self.max_vector_size = max_vector_size;
+        self
+    }
+
     pub async fn get_embeddings(
         &self,
         document_id: Uuid,
@@ -62,7 +160,11 @@ impl EmbeddingService {
         collection_id: Option<Uuid>,
     ) -> Result<List<TextEmbedding>, ChonkitError> {
         map_err!(pagination.validate());
-        self.repo.list_embeddings(pagination, collection_id).await
+        let pagination = pagination.clamp(self.max_page_size);
+        self.repo
+            .list_embeddings(pagination, collection_id)
+            .await
+            .map(|list| list.paginated(pagination))
     }
 
     pub async fn list_outdated_embeddings(
@@ -97,13 +199,23 @@ impl EmbeddingService {
             collection: collection_id,
         } = input;
 
-        let (image, image_meta) =
-            get_image(self.repo.clone(), &*self.providers.image, image_id).await?;
-
         let Some(collection) = self.repo.get_collection_by_id(collection_id).await? else {
             return err!(DoesNotExist, "Collection with ID '{}'", collection_id);
         };
 
+        // Collections created with a text-only model are rejected before doing any work
+        if collection.multimodal == Some(false) {
+            return err!(
+                InvalidEmbeddingModel,
+                "Collection '{}' uses the text-only model '{}' and cannot store image embeddings",
+                collection.name,
+                collection.model
+            );
+        }
+
+        let (image, image_meta) =
+            get_image(self.repo.clone(), &*self.providers.image, image_id).await?;
+
         if self
             .repo
             .get_image_embeddings(image_id, collection_id)
@@ -160,6 +272,11 @@ impl EmbeddingService {
 
         match cached {
             Ok(Some(embeddings)) => {
+                validate_vectors(
+                    std::slice::from_ref(&embeddings.embeddings),
+                    model_details.size,
+                )?;
+
                 vector_db
                     .insert_embeddings(CollectionItemInsert::new_image(
                         image_meta.document_id,
@@ -203,6 +320,8 @@ impl EmbeddingService {
                     .embed_image(None, None, &b64, &model_details.name)
                     .await?;
 
+                validate_vectors(&embeddings.embeddings, model_details.size)?;
+
                 let vector = std::mem::take(&mut embeddings.embeddings[0]);
                 tokens_used = embeddings.tokens_used.map(|t| t as i32);
 
@@ -275,18 +394,334 @@ impl EmbeddingService {
     ///
     /// Errors if embeddings already exist in the collection
     /// for the document to prevent duplication in semantic search.
+    ///
+    /// The repository entry is committed only after the vectors are stored. If the commit
+    /// fails, the vectors are removed on a best effort basis, so the vector DB may briefly
+    /// hold vectors without a repository entry, but never the other way around.
     pub async fn create_text_embeddings(
         &self,
         input: EmbedTextInput,
+    ) -> Result<TextEmbeddingAdditionReport, ChonkitError> {
+        map_err!(input.validate());
+
+        let collection = self.resolve_collection_id(&input).await?;
+
+        self.embed_text_into(
+            input.document,
+            collection,
+            input.detailed,
+            input.embed_metadata,
+            input.no_cache,
+            &mut TextEmbeddingSession::default(),
+        )
+        .await
+    }
+
+    /// Replace the embeddings of the document in all the collections its text is embedded in.
+    /// Used to bring the embeddings up to date once the content of the document changes.
+    ///
+    /// Returns the report for each of the collections.
+    pub async fn refresh_text_embeddings(
+        &self,
+        document_id: Uuid,
+    ) -> Result<Vec<TextEmbeddingAdditionReport>, ChonkitError> {
+        let collections = self
+            .repo
+            .get_document_assigned_collections(document_id)
+            .await?;
+
+        let mut reports = Vec::with_capacity(collections.len());
+
+        for (collection_id, _, _) in collections {
+            self.delete_text_embeddings(collection_id, document_id)
+                .await?;
+
+            let report = self
+                .create_text_embeddings(EmbedTextInput::new(document_id, collection_id))
+                .await?;
+
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+
+    /// Get the ID of the collection the input embeds into, either directly or by its name.
+    ///
+    /// Collections referenced by a name that does not exist are created with the
+    /// [AutoCreateCollections] defaults, if configured.
+    async fn resolve_collection_id(&self, input: &EmbedTextInput) -> Result<Uuid, ChonkitError> {
+        if let Some(collection_id) = input.collection {
+            return Ok(collection_id);
+        }
+
+        let Some(ref name) = input.collection_name else {
+            return err!(
+                InvalidParameter,
+                "Embedding requires a collection ID or name"
+            );
+        };
+
+        let mut collections = self.repo.list_collections_by_name(name).await?;
+
+        match collections.len() {
+            0 => {}
+            1 => return Ok(collections.remove(0).id),
+            _ => {
+                return err!(
+                    InvalidParameter,
+                    "Collection name '{name}' is used by multiple vector providers, use its ID"
+                )
+            }
+        }
+
+        let Some(ref auto_create) = self.auto_create else {
+            return err!(DoesNotExist, "Collection '{name}'");
+        };
+
+        tracing::info!(
+            "Creating collection '{name}' with model '{}' ({}) in '{}'",
+            auto_create.model,
+            auto_create.embedding_provider,
+            auto_create.vector_provider
+        );
+
+        let collection = CollectionService::new(
+            self.repo.clone(),
+            self.providers.clone(),
+            self.tokenizer.clone(),
+        )
+        .with_max_vector_size(self.max_vector_size)
+        .create_collection(CreateCollectionPayload {
+            name: name.clone(),
+            model: auto_create.model.clone(),
+            vector_provider: auto_create.vector_provider.clone(),
+            embedding_provider: auto_create.embedding_provider.clone(),
+            groups: None,
+            modality: CollectionModality::Text,
+            chunk_template: None,
+            tags: None,
+            insert_dedup_threshold: None,
+        })
+        .await?;
+
+        Ok(collection.id)
+    }
+
+    /// Embed a document into multiple collections, yielding a report for each collection
+    /// in the order they were given.
+    ///
+    /// The document is parsed and chunked at most once and the chunks are embedded at most once
+    /// per embedding model, i.e. collections using the same model reuse the embeddings.
+    /// Errors are yielded per collection and do not prevent embedding into the rest.
+    pub fn create_text_embeddings_fanout(
+        &self,
+        input: EmbedFanoutInput,
+    ) -> impl Stream<Item = Result<TextEmbeddingAdditionReport, ChonkitError>> + Send + 'static
+    {
+        let EmbedFanoutInput {
+            document,
+            collections,
+            detailed,
+            embed_metadata,
+        } = input;
+
+        let state = (
+            self.clone(),
+            TextEmbeddingSession::default(),
+            collections.into_iter(),
+        );
+
+        futures_util::stream::unfold(
+            state,
+            move |(service, mut session, mut collections)| async move {
+                let collection = collections.next()?;
+                let result = service
+                    .embed_text_into(
+                        document,
+                        collection,
+                        detailed,
+                        embed_metadata,
+                        false,
+                        &mut session,
+                    )
+                    .await;
+                Some((result, (service, session, collections)))
+            },
+        )
+    }
+
+    /// Summarize the document with the LLM and embed the summary as the document's only vector
+    /// in the summary collection. Searches can then find relevant documents by their summary
+    /// before searching their chunks.
+    ///
+    /// Summaries are stored by the hash of the document content, so a document is only
+    /// summarized again if its content changes.
+    ///
+    /// * `document_id`: The document to summarize.
+    /// * `collection_id`: The collection holding the summaries.
+    pub async fn embed_summary(
+        &self,
+        document_id: Uuid,
+        collection_id: Uuid,
+    ) -> Result<TextEmbeddingAdditionReport, ChonkitError> {
+        let Some(llm) = &self.llm else {
+            return err!(
+                OperationUnsupported,
+                "Summaries require an LLM, but none is configured"
+            );
+        };
+
+        let Some(document) = self.repo.get_document_config_by_id(document_id).await? else {
+            return err!(DoesNotExist, "Document with ID {}", document_id);
+        };
+
+        let Some(collection) = self.repo.get_collection_by_id(collection_id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{}'", collection_id);
+        };
+
+        let start = chrono::Utc::now();
+
+        if self
+            .repo
+            .get_text_embeddings(document.id, collection.id)
+            .await?
+            .is_some()
+        {
+            return err!(
+                AlreadyExists,
+                "Embeddings for document '{}' in collection '{}'",
+                document.id,
+                collection.name
+            );
+        }
+
+        let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+        let embedder = self
+            .providers
+            .embedding
+            .get_provider(&collection.embedder)?;
+
+        let v_collection = vector_db.get_collection(&collection.name).await?;
+
+        let summary = match self.repo.get_document_summary(&document.hash).await? {
+            Some(summary) => {
+                tracing::debug!("{} - using stored summary", document.id);
+                summary
+            }
+            None => {
+                let parse_cfg = self.parser_defaults.resolve(
+                    document.ext.as_str().try_into()?,
+                    document.parse_config.clone(),
+                );
+                let (chunks, _) = self.chunk_text(&document, parse_cfg, None).await?;
+                let content = chunks.join("\n");
+
+                tracing::debug!("{} - summarizing document", document.id);
+
+                let summary = llm::summarize(llm.as_ref(), &content).await?;
+                self.repo
+                    .upsert_document_summary(&document.hash, &summary)
+                    .await?;
+                summary
+            }
+        };
+
+        let mut embedded = embedder
+            .embed_text(&[summary.as_str()], &collection.model)
+            .await?;
+
+        validate_vectors(&embedded.embeddings, v_collection.size)?;
+
+        let vector = embedded.embeddings.remove(0);
+
+        let compensation = (vector_db.clone(), collection.name.clone());
+
+        self.repo
+            .transaction_or_compensate(
+                |tx| {
+                Box::pin(async move {
+                    self.repo
+                        .insert_text_embeddings(
+                            TextEmbeddingInsert::new(document.id, collection.id),
+                            Some(tx),
+                        )
+                        .await?;
+
+                    let report = TextEmbeddingAdditionReport {
+                        document_id: document.id,
+                        document_name: document.name,
+                        report: EmbeddingAdditionReport {
+                            model_used: collection.model,
+                            tokens_used: embedded.tokens_used.map(|t| t as i32),
+                            embedding_provider: collection.embedder,
+                            total_vectors: 1,
+                            cache: false,
+                            base: EmbeddingReportBase {
+                                collection_id: Some(collection.id),
+                                collection_name: collection.name.clone(),
+                                vector_db: collection.provider,
+                                started_at: start,
+                                finished_at: chrono::Utc::now(),
+                            },
+                        },
+                        chunk_tokens: None,
+                        chunk_fallback: false,
+                    };
+
+                    self.repo.insert_text_embedding_report(&report).await?;
+
+                    vector_db
+                        .insert_embeddings(CollectionItemInsert::new_text(
+                            document.id,
+                            &collection.name,
+                            &[summary.as_str()],
+                            vec![vector],
+                            document.metadata.as_ref(),
+                        ))
+                        .await?;
+
+                    Ok(report)
+                })
+            },
+                |report| async move {
+                    let (vector_db, collection_name) = compensation;
+                    if let Err(e) = vector_db
+                        .delete_text_embeddings(&collection_name, report.document_id)
+                        .await
+                    {
+                        tracing::error!(
+                            "{} - orphaned summary vector in '{collection_name}' after failed commit: {e}",
+                            report.document_id
+                        );
+                    }
+                },
+            )
+            .await
+    }
+
+    /// Embed the document into the collection, reusing the chunks and embeddings from the
+    /// `session` if possible. If `detailed` is `true`, the report includes the token count
+    /// of every chunk. If `embed_metadata` is `true`, a synthetic chunk holding the document's
+    /// name and metadata is embedded along with the chunks. If `no_cache` is `true`, the
+    /// embedding cache is neither read nor written.
+    async fn embed_text_into(
+        &self,
+        document_id: Uuid,
+        collection_id: Uuid,
+        detailed: bool,
+        embed_metadata: bool,
+        no_cache: bool,
+        session: &mut TextEmbeddingSession,
     ) -> Result<TextEmbeddingAdditionReport, ChonkitError> {
         // Make sure the collection and document exist.
 
-        let Some(document) = self.repo.get_document_config_by_id(input.document).await? else {
-            return err!(DoesNotExist, "Document with ID {}", input.document);
+        let Some(document) = self.repo.get_document_config_by_id(document_id).await? else {
+            return err!(DoesNotExist, "Document with ID {}", document_id);
         };
 
-        let Some(collection) = self.repo.get_collection_by_id(input.collection).await? else {
-            return err!(DoesNotExist, "Collection with ID '{}'", input.collection);
+        let Some(collection) = self.repo.get_collection_by_id(collection_id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{}'", collection_id);
         };
 
         let start = chrono::Utc::now();
@@ -308,7 +743,6 @@ impl EmbeddingService {
 
         // Load providers and check for state treachery
 
-        let storage = self.providers.document.get_provider(&document.src)?;
         let vector_db = self.providers.vector.get_provider(&collection.provider)?;
         let embedder = self
             .providers
@@ -327,21 +761,23 @@ impl EmbeddingService {
         };
 
         if model_details.size != v_collection.size {
-            return err!(
-                InvalidEmbeddingModel,
-                "Model size ({}) not compatible with collection size ({})"
-                model_details.size,
-                v_collection.size
-            );
+            return err!(ChonkitErr::DimensionMismatch {
+                model_size: model_details.size,
+                collection_size: v_collection.size,
+            });
         }
 
         // Load parser and chunker
 
-        let parse_cfg = document.parse_config.unwrap_or_default();
+        let parse_cfg = self.parser_defaults.resolve(
+            document.ext.as_str().try_into()?,
+            document.parse_config.clone(),
+        );
 
         let chunk_cfg = match parse_cfg {
             ParseConfig::String(_) => document
                 .chunk_config
+                .clone()
                 .or(Some(ChunkConfig::snapping_default())),
             // Sectioned parsers do not support chunking
             ParseConfig::Section(_) => None,
@@ -357,32 +793,173 @@ impl EmbeddingService {
                 .unwrap_or("none".to_string())
         );
 
-        // Check embedding cache
+        // Without a key the cache is neither read nor written
+        let text_cache_key = match no_cache {
+            true => None,
+            false => Some(TextEmbeddingCacheKey::new(
+                &collection.model,
+                &document.hash,
+                chunk_cfg.as_ref(),
+                &parse_cfg,
+                collection.chunk_template.as_deref(),
+            )?),
+        };
 
-        let text_cache_key = TextEmbeddingCacheKey::new(
-            &collection.model,
-            &document.hash,
-            chunk_cfg.as_ref(),
-            &parse_cfg,
-        )?;
+        let session_key = (
+            collection.embedder.clone(),
+            collection.model.clone(),
+            collection.chunk_template.clone(),
+        );
 
-        let cached = match self.text_cache.get(&text_cache_key).await {
-            Ok(embeddings) => embeddings,
-            Err(e) => {
-                tracing::debug!(
-                    "{} -  failed to get embeddings from cache: {e}",
-                    document.name
-                );
-                None
-            }
+        // Sectioned documents yield a chunk per section, so the sections are their pages
+        let pages = match parse_cfg {
+            ParseConfig::Section(ref config) => config
+                .sections
+                .iter()
+                .map(|range| {
+                    if range.end > range.start {
+                        format!("{}-{}", range.start, range.end)
+                    } else {
+                        range.start.to_string()
+                    }
+                })
+                .collect(),
+            ParseConfig::String(_) => vec![],
         };
 
-        if let Some(embeddings) = cached {
-            tracing::debug!("{} - using cached embeddings", document.id);
-            return self
-                .repo
-                .transaction(|tx| {
+        // Parent windows are assembled from the chunks on insert, so they need not be cached
+        let parent_child = match chunk_cfg {
+            Some(ChunkConfig::ParentChild(ref config)) => Some(config.clone()),
+            _ => None,
+        };
+
+        // Check the session and the embedding cache before doing any work
+
+        let (chunks, embeddings, tokens_used, cache, chunk_fallback) =
+            match (&session.chunks, session.embeddings.get(&session_key)) {
+                (Some(chunks), Some(embeddings)) => {
+                    tracing::debug!("{} - reusing embeddings from session", document.id);
+                    let fallback = session.chunk_fallback;
+                    (chunks.clone(), embeddings.clone(), Some(0), true, fallback)
+                }
+                _ => match self
+                    .get_cached_text_embeddings(text_cache_key.as_ref())
+                    .await
+                {
+                    Some(cached) => {
+                        tracing::debug!("{} - using cached embeddings", document.id);
+                        (cached.chunks, cached.embeddings, Some(0), true, false)
+                    }
+                    None => {
+                        let chunks = match session.chunks {
+                            Some(ref chunks) => chunks.clone(),
+                            None => {
+                                let (chunks, fallback) =
+                                    self.chunk_text(&document, parse_cfg, chunk_cfg).await?;
+                                session.chunk_fallback = fallback;
+                                chunks
+                            }
+                        };
+
+                        tracing::debug!(
+                            "{} - generating embeddings ({} total chunks)",
+                            document.name,
+                            chunks.len()
+                        );
+
+                        // Only the embedded text is templated, the chunks are stored as they are
+                        let templated = collection.chunk_template.as_deref().map(|template| {
+                            chunks
+                                .iter()
+                                .enumerate()
+                                .map(|(i, chunk)| {
+                                    let page = pages.get(i).map(|page| page.as_str());
+                                    apply_chunk_template(template, &document.name, page, chunk)
+                                })
+                                .collect::<Vec<_>>()
+                        });
+
+                        let input = templated.as_ref().unwrap_or(&chunks);
+
+                        let embeddings = embedder
+                            .embed_text(
+                                &input.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+                                &collection.model,
+                            )
+                            .await?;
+
+                        debug_assert_eq!(chunks.len(), embeddings.embeddings.len());
+
+                        let fallback = session.chunk_fallback;
+                        (
+                            chunks,
+                            embeddings.embeddings,
+                            embeddings.tokens_used,
+                            false,
+                            fallback,
+                        )
+                    }
+                },
+            };
+
+        validate_vectors(&embeddings, v_collection.size)?;
+
+        // Only affects the insert, the cached and shared embeddings hold all the chunks
+        let duplicates = match collection.insert_dedup_threshold {
+            Some(threshold) => near_duplicates(&embeddings, threshold),
+            None => HashSet::new(),
+        };
+
+        if !duplicates.is_empty() {
+            tracing::debug!(
+                "{} - skipping {} near duplicate chunks",
+                document.id,
+                duplicates.len()
+            );
+        }
+
+        // Embedded on its own so the cached and shared embeddings only ever hold the chunks
+        let synthetic = if embed_metadata {
+            let content = metadata_chunk(&document.name, document.metadata.as_ref());
+            let mut embedded = embedder
+                .embed_text(&[content.as_str()], &collection.model)
+                .await?;
+            validate_vectors(&embedded.embeddings, v_collection.size)?;
+            Some((content, embedded.embeddings.remove(0), embedded.tokens_used))
+        } else {
+            None
+        };
+
+        let report_tokens = match synthetic {
+            Some((_, _, synthetic_tokens)) => tokens_used.zip(synthetic_tokens).map(|(a, b)| a + b),
+            None => tokens_used,
+        };
+
+        let chunk_tokens = detailed.then(|| {
+            let tokenizer = self.tokenizer.for_model(&collection.model);
+            ChunkTokenBreakdown::new(chunks.iter().map(|chunk| tokenizer.count(chunk)).collect())
+        });
+
+        if session.chunks.is_none() {
+            session.chunks = Some(chunks.clone());
+        }
+
+        session
+            .embeddings
+            .entry(session_key)
+            .or_insert_with(|| embeddings.clone());
+
+        // The vectors are inserted last in the transaction, so any failure before them rolls
+        // back cleanly. If the commit itself fails, the vectors are already stored and have to
+        // be removed, otherwise they would be searchable without a repository entry.
+        let compensation = (vector_db.clone(), collection.name.clone());
+
+        self.repo
+            .transaction_or_compensate(
+                |tx| {
                     Box::pin(async move {
+                        // Repository operations go first since we can revert those with the tx
+
                         self.repo
                             .insert_text_embeddings(
                                 TextEmbeddingInsert::new(document.id, collection.id),
@@ -395,10 +972,12 @@ impl EmbeddingService {
                             document_name: document.name,
                             report: EmbeddingAdditionReport {
                                 model_used: collection.model,
-                                tokens_used: Some(0),
+                                tokens_used: report_tokens.map(|t| t as i32),
                                 embedding_provider: collection.embedder.clone(),
-                                total_vectors: embeddings.embeddings.len() as i32,
-                                cache: true,
+                                total_vectors: (embeddings.len() - duplicates.len()
+                                    + synthetic.is_some() as usize)
+                                    as i32,
+                                cache,
                                 base: EmbeddingReportBase {
                                     collection_id: Some(collection.id),
                                     collection_name: collection.name.clone(),
@@ -407,62 +986,117 @@ impl EmbeddingService {
                                     finished_at: chrono::Utc::now(),
                                 },
                             },
+                            chunk_tokens,
+                            chunk_fallback,
                         };
 
                         self.repo.insert_text_embedding_report(&report).await?;
 
-                        vector_db
-                            .insert_embeddings(CollectionItemInsert::new_text(
-                                document.id,
-                                &collection.name,
-                                &embeddings
-                                    .chunks
-                                    .iter()
-                                    .map(|s| s.as_str())
-                                    .collect::<Vec<&str>>(),
-                                embeddings.embeddings,
-                            ))
-                            .await?;
+                        let chunks_ref = chunks.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+                        let mut insert = CollectionItemInsert::new_text(
+                            document.id,
+                            &collection.name,
+                            &chunks_ref,
+                            embeddings.clone(),
+                            document.metadata.as_ref(),
+                        );
 
-                        Ok(report)
-                    })
-                })
-                .await;
-        }
+                        let parents = parent_child.as_ref().map(|config| config.parents(&chunks));
+                        if let (Some(config), Some(parents)) = (&parent_child, &parents) {
+                            insert = insert.with_parents(config, parents);
+                        }
+
+                        insert = insert.without_chunks(&duplicates);
 
-        // From this point on we are certain nothing is cached
+                        if let Some((ref content, ref vector, _)) = synthetic {
+                            insert = insert.with_synthetic_text(
+                                document.id,
+                                content,
+                                vector.clone(),
+                                document.metadata.as_ref(),
+                            );
+                        }
 
-        // Read and parse
-        let content_bytes = storage.read(&document.path).await?;
+                        vector_db.insert_embeddings(insert).await?;
 
-        let parse_output = parse_text(parse_cfg, document.ext.try_into()?, &content_bytes)?;
+                        // Fallback chunks are not cached so that the configured chunker is
+                        // used again the next time the document is embedded
+                        if cache || chunk_fallback {
+                            return Ok(report);
+                        }
 
-        // Chunk and embed
-        let chunks: Vec<String>;
-        let embeddings: Embeddings;
+                        let Some(text_cache_key) = text_cache_key else {
+                            return Ok(report);
+                        };
 
-        match parse_output {
-            ParseOutput::String(text) => match chunk_cfg {
-                Some(cfg) => {
-                    chunks = match crate::core::chunk::chunk(&self.providers, cfg, &text).await? {
-                        ChunkedDocument::Ref(r) => {
-                            r.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+                        if let Err(e) = self
+                            .text_cache
+                            .set(
+                                &text_cache_key,
+                                CachedTextEmbeddings::new(embeddings, tokens_used, chunks),
+                            )
+                            .await
+                        {
+                            tracing::warn!("failed to cache embeddings: {e}");
                         }
-                        ChunkedDocument::Owned(o) => o,
-                    };
 
-                    embeddings = embedder
-                        .embed_text(
-                            &chunks.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
-                            &collection.model,
-                        )
-                        .await?;
-                }
-                None => {
-                    embeddings = embedder.embed_text(&[&text], &collection.model).await?;
-                    chunks = vec![text];
-                }
-            },
+                        Ok(report)
+                    })
+                },
+                |report| async move {
+                    let (vector_db, collection_name) = compensation;
+                    if let Err(e) = vector_db
+                        .delete_text_embeddings(&collection_name, report.document_id)
+                        .await
+                    {
+                        tracing::error!(
+                            "{} - orphaned vectors in '{collection_name}' after failed commit: {e}",
+                            report.document_id
+                        );
+                    }
+                },
+            )
+            .await
+    }
+
+    /// Get the embeddings from the cache, if there is a key to get them with. Errors are only
+    /// logged since the cache is not essential for embedding.
+    async fn get_cached_text_embeddings(
+        &self,
+        key: Option<&TextEmbeddingCacheKey>,
+    ) -> Option<CachedTextEmbeddings> {
+        match self.text_cache.get(key?).await {
+            Ok(embeddings) => embeddings,
+            Err(e) => {
+                tracing::debug!("failed to get embeddings from cache: {e}");
+                None
+            }
+        }
+    }
+
+    /// Read the document from its storage, then parse and chunk it. Also returns whether the
+    /// chunks were obtained with the fallback of the chunking configuration.
+    ///
+    /// In case of sectioned parsers, the sections are the chunks.
+    async fn chunk_text(
+        &self,
+        document: &DocumentConfig,
+        parse_cfg: ParseConfig,
+        chunk_cfg: Option<ChunkConfig>,
+    ) -> Result<(Vec<String>, bool), ChonkitError> {
+        let storage = self.providers.document.get_provider(&document.src)?;
+        let reader = storage.read_stream(&document.path).await?;
+        let ext: DocumentType = document.ext.as_str().try_into()?;
+
+        // Large documents are read as they are parsed instead of being loaded whole
+        let timeout = parse_cfg.timeout().unwrap_or(self.parse_timeout);
+        let parse_output = parse_with_timeout(timeout, move || {
+            parse_text_from_reader(parse_cfg, ext, SyncIoBridge::new(reader))
+        })
+        .await?;
+
+        let text = match parse_output {
+            ParseOutput::String(text) => text,
             ParseOutput::Sections(sections) => {
                 let mut section_chunks = Vec::with_capacity(sections.len());
 
@@ -477,91 +1111,41 @@ impl EmbeddingService {
                     section_chunks.push(content);
                 }
 
-                embeddings = embedder
-                    .embed_text(
-                        &section_chunks
-                            .iter()
-                            .map(|s| s.as_str())
-                            .collect::<Vec<_>>(),
-                        &collection.model,
-                    )
-                    .await?;
-                // In case of sectioned parsers, we define the sections as chunks
-                chunks = section_chunks
+                if section_chunks.iter().all(|chunk| chunk.trim().is_empty()) {
+                    return err!(
+                        InvalidFile,
+                        "Document '{}' has no embeddable content",
+                        document.name
+                    );
+                }
+
+                return Ok((section_chunks, false));
             }
         };
 
-        // Chunking and embedding is done, store everything
-
-        tracing::debug!(
-            "{} - generating embeddings ({} total chunks)",
-            document.name,
-            chunks.len()
-        );
-
-        debug_assert_eq!(chunks.len(), embeddings.embeddings.len());
-
-        self.repo
-            .transaction(|tx| {
-                Box::pin(async move {
-                    // Repository operations go first since we can revert those with the tx
-
-                    self.repo
-                        .insert_text_embeddings(
-                            TextEmbeddingInsert::new(document.id, collection.id),
-                            Some(tx),
-                        )
-                        .await?;
-
-                    let report = TextEmbeddingAdditionReport {
-                        document_id: document.id,
-                        document_name: document.name,
-                        report: EmbeddingAdditionReport {
-                            model_used: collection.model,
-                            tokens_used: embeddings.tokens_used.map(|t| t as i32),
-                            embedding_provider: collection.embedder.clone(),
-                            total_vectors: embeddings.embeddings.len() as i32,
-                            cache: false,
-                            base: EmbeddingReportBase {
-                                collection_id: Some(collection.id),
-                                collection_name: collection.name.clone(),
-                                vector_db: collection.provider.clone(),
-                                started_at: start,
-                                finished_at: chrono::Utc::now(),
-                            },
-                        },
-                    };
+        // Documents consisting only of images parse to nothing, but can still be embedded
+        // as images
+        if text.trim().is_empty() {
+            return err!(
+                InvalidFile,
+                "Document '{}' has no embeddable content",
+                document.name
+            );
+        }
 
-                    self.repo.insert_text_embedding_report(&report).await?;
+        let Some(chunk_cfg) = chunk_cfg else {
+            return Ok((vec![text], false));
+        };
 
-                    vector_db
-                        .insert_embeddings(CollectionItemInsert::new_text(
-                            document.id,
-                            &collection.name,
-                            &chunks.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
-                            embeddings.embeddings.clone(),
-                        ))
-                        .await?;
+        let chunked =
+            crate::core::chunk::chunk(&self.providers, chunk_cfg, &text, self.max_chunks).await?;
 
-                    if let Err(e) = self
-                        .text_cache
-                        .set(
-                            &text_cache_key,
-                            CachedTextEmbeddings::new(
-                                embeddings.embeddings,
-                                embeddings.tokens_used,
-                                chunks,
-                            ),
-                        )
-                        .await
-                    {
-                        tracing::warn!("failed to cache embeddings: {e}");
-                    }
+        let chunks = match chunked.chunks {
+            ChunkedDocument::Ref(r) => r.iter().map(|s| s.to_string()).collect(),
+            ChunkedDocument::Owned(o) => o,
+        };
 
-                    Ok(report)
-                })
-            })
-            .await
+        Ok((chunks, chunked.fallback))
     }
 
     /// Returns the number of rows deleted from the db and the number of vectors deleted from the collection.
@@ -704,60 +1288,105 @@ impl EmbeddingService {
             .await
     }
 
-    /// Delete all text and image embeddings from the database and vector database.
-    pub async fn delete_all_embeddings(&self, document_id: Uuid) -> Result<(), ChonkitError> {
+    /// Delete all text and image embeddings of a document from every collection, leaving the
+    /// document itself intact.
+    ///
+    /// Returns a report for every collection embeddings were removed from.
+    pub async fn delete_all_embeddings(
+        &self,
+        document_id: Uuid,
+    ) -> Result<Vec<DocumentEmbeddingRemovalReport>, ChonkitError> {
         let Some(document) = self.repo.get_document_by_id(document_id).await? else {
             return err!(DoesNotExist, "Document with ID {document_id}");
         };
 
-        let collections = self
+        let images = self
             .repo
-            .get_document_assigned_collections(document_id)
+            .list_all_document_images(document_id, self.providers.image.id())
             .await?;
 
-        let images = self
+        // Images can be embedded in collections the document's text is not embedded in
+        let mut collections = self
             .repo
-            .list_all_document_images(document_id, self.providers.image.id())
+            .get_document_assigned_collections(document_id)
             .await?;
 
+        for image in images.iter() {
+            for collection in self.repo.get_image_assigned_collections(image.id).await? {
+                if !collections.iter().any(|(id, _, _)| *id == collection.0) {
+                    collections.push(collection);
+                }
+            }
+        }
+
+        let mut reports = vec![];
+
         for (collection_id, collection_name, provider) in collections {
             let images = &images[..];
             let document_name = &document.name;
+            let start = chrono::Utc::now();
 
-            self.repo
+            let report = self
+                .repo
                 .transaction(|tx| {
                     Box::pin(async move {
                         let vector_db = self.providers.vector.get_provider(&provider)?;
 
-                        self.repo
-                            .delete_text_embeddings(document_id, collection_id, Some(tx))
-                            .await?;
+                        let text = self
+                            .repo
+                            .delete_text_embeddings(document_id, collection_id, Some(&mut *tx))
+                            .await?
+                            > 0;
 
+                        let mut image_ids = vec![];
                         for image in images {
-                            self.repo
-                                .delete_image_embeddings(image.id, collection_id, Some(tx))
-                                .await?;
+                            if self
+                                .repo
+                                .delete_image_embeddings(image.id, collection_id, Some(&mut *tx))
+                                .await?
+                                > 0
+                            {
+                                image_ids.push(image.id);
+                            }
                         }
 
-                        vector_db
-                            .count_vectors(&collection_name, document_id)
-                            .await?;
+                        if text {
+                            vector_db
+                                .delete_text_embeddings(&collection_name, document_id)
+                                .await?;
+                        }
 
-                        vector_db
-                            .delete_text_embeddings(&collection_name, document_id)
-                            .await?;
+                        if !image_ids.is_empty() {
+                            vector_db
+                                .delete_image_embeddings_many(&collection_name, &image_ids)
+                                .await?;
+                        }
 
                         tracing::debug!(
                             "{} - deleted embeddings from collection '{collection_name}'",
                             document_name
                         );
-                        Ok(())
+
+                        Ok(DocumentEmbeddingRemovalReport {
+                            document_id,
+                            text,
+                            image_ids,
+                            report: EmbeddingReportBase {
+                                collection_id: Some(collection_id),
+                                collection_name,
+                                vector_db: provider,
+                                started_at: start,
+                                finished_at: chrono::Utc::now(),
+                            },
+                        })
                     })
                 })
                 .await?;
+
+            reports.push(report);
         }
 
-        Ok(())
+        Ok(reports)
     }
 
     pub async fn count_embeddings(
@@ -772,34 +1401,260 @@ impl EmbeddingService {
         vector_db.count_vectors(&collection.name, document_id).await
     }
 
+    /// Reconstruct the text of a document from its chunks stored in the collection, for when
+    /// the chunks are not otherwise available. The chunks are ordered by their index and
+    /// joined with [join_chunks], which removes their overlap where possible.
+    ///
+    /// Synthetic chunks and chunks stored without an index are left out.
+    ///
+    /// * `collection_id`: Collection ID.
+    /// * `document_id`: Document ID.
+    pub async fn reconstruct_document(
+        &self,
+        collection_id: Uuid,
+        document_id: Uuid,
+    ) -> Result<ReconstructedDocument, ChonkitError> {
+        let Some(collection) = self.repo.get_collection_by_id(collection_id).await? else {
+            return err!(DoesNotExist, "Collection with ID '{collection_id}'");
+        };
+
+        let vector_db = self.providers.vector.get_provider(&collection.provider)?;
+
+        let mut chunks: Vec<(usize, String)> = vector_db
+            .list_document_items(&collection.name, document_id)
+            .await?
+            .into_iter()
+            .filter_map(|item| match item.payload {
+                CollectionItemPayload::Text {
+                    content,
+                    chunk_index: Some(index),
+                    synthetic: false,
+                    ..
+                } => Some((index, content)),
+                _ => None,
+            })
+            .collect();
+
+        if chunks.is_empty() {
+            return err!(
+                DoesNotExist,
+                "Chunks of document '{document_id}' in collection '{collection_id}'"
+            );
+        }
+
+        chunks.sort_by_key(|(index, _)| *index);
+
+        let stored: HashSet<usize> = chunks.iter().map(|(index, _)| *index).collect();
+        let last = chunks[chunks.len() - 1].0;
+        let missing = (0..last).filter(|index| !stored.contains(index)).collect();
+
+        Ok(ReconstructedDocument {
+            content: join_chunks(chunks.iter().map(|(_, content)| content.as_str())),
+            chunks: chunks.len(),
+            chunk_ids: chunks
+                .iter()
+                .map(|(index, _)| chunk_id(document_id, *index))
+                .collect(),
+            missing,
+        })
+    }
+
     pub async fn list_collection_embedding_reports(
         &self,
-        params: ListEmbeddingReportsParams,
+        mut params: ListEmbeddingReportsParams,
     ) -> Result<Vec<EmbeddingReport>, ChonkitError> {
         map_err!(params.validate());
+        let options = params.options.unwrap_or_default();
+        params.options = Some(options.clamp(self.max_page_size));
         self.repo.list_collection_embedding_reports(params).await
     }
+
+    pub async fn get_embedding_report(
+        &self,
+        id: i32,
+        action: EmbeddingReportAction,
+    ) -> Result<EmbeddingReport, ChonkitError> {
+        match self.repo.get_embedding_report(id, action).await? {
+            Some(report) => Ok(report),
+            None => err!(DoesNotExist, "Embedding report with ID '{id}'"),
+        }
+    }
+
+    /// Delete all embedding reports outside of the `retention` window.
+    /// Returns the amount of deleted reports.
+    pub async fn cleanup_reports(&self, retention: ReportRetention) -> Result<u64, ChonkitError> {
+        let cutoff = retention.cutoff(Utc::now());
+        let deleted = self.repo.delete_reports_older_than(cutoff).await?;
+        tracing::info!("Deleted {deleted} embedding report(s) finished before {cutoff}");
+        Ok(deleted)
+    }
 }
 
 /// Used for embedding text from documents, one document at a time.
-#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
 #[cfg_attr(test, derive(Clone))]
 #[serde(rename_all = "camelCase")]
+#[validate(Self::validate_schema)]
 pub struct EmbedTextInput {
     /// The ID of the document to embed.
     pub document: Uuid,
 
     /// The ID of the collection in which to store the embeddings to.
-    pub collection: Uuid,
+    pub collection: Option<Uuid>,
+
+    /// The name of the collection in which to store the embeddings to. Required if
+    /// `collection` is omitted. If automatic collection creation is enabled, a collection
+    /// with the name is created if it does not exist.
+    #[validate(length(min = 1))]
+    pub collection_name: Option<String>,
+
+    /// If `true`, the report includes the token count of every chunk.
+    #[serde(default)]
+    pub detailed: bool,
+
+    /// If `true`, a synthetic chunk holding the document's name and metadata is embedded
+    /// along with the chunks, so queries matching the title find the document.
+    #[serde(default)]
+    pub embed_metadata: bool,
+
+    /// If `true`, the embedding cache is neither read nor written and the chunks are always
+    /// embedded by the embedder. Useful for content that must not be persisted in the cache.
+    #[serde(default)]
+    pub no_cache: bool,
 }
 
 impl EmbedTextInput {
     pub fn new(document: Uuid, collection: Uuid) -> Self {
         Self {
             document,
-            collection,
+            collection: Some(collection),
+            collection_name: None,
+            detailed: false,
+            embed_metadata: false,
+            no_cache: false,
+        }
+    }
+
+    /// Embed into the collection with the given name instead of by its ID.
+    pub fn by_name(document: Uuid, collection_name: impl Into<String>) -> Self {
+        Self {
+            document,
+            collection: None,
+            collection_name: Some(collection_name.into()),
+            detailed: false,
+            embed_metadata: false,
+            no_cache: false,
+        }
+    }
+
+    #[schema_validation]
+    fn validate_schema(&self) -> Result<(), ValidationErrors> {
+        if self.collection.is_some() == self.collection_name.is_some() {
+            schema_err!(
+                "collection",
+                "exactly one of collection and collectionName must be given"
+            );
         }
     }
+
+    /// Include the token count of every chunk in the report.
+    pub fn detailed(mut self) -> Self {
+        self.detailed = true;
+        self
+    }
+
+    /// Embed a synthetic chunk holding the document's name and metadata.
+    pub fn with_metadata(mut self) -> Self {
+        self.embed_metadata = true;
+        self
+    }
+
+    /// Bypass the embedding cache.
+    pub fn without_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+}
+
+/// Used for embedding the summary of a document.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedSummaryInput {
+    /// The ID of the document to summarize.
+    pub document: Uuid,
+
+    /// The ID of the collection in which to store the summary.
+    pub collection: Uuid,
+}
+
+/// The configuration of collections created when embedding into a collection name
+/// that does not exist.
+#[derive(Debug, Clone)]
+pub struct AutoCreateCollections {
+    /// Vector database provider of the created collections.
+    pub vector_provider: String,
+
+    /// Embedding provider of the created collections.
+    pub embedding_provider: String,
+
+    /// Embedding model of the created collections.
+    pub model: String,
+}
+
+/// Used for embedding a single document into multiple collections.
+#[derive(Debug, Deserialize, Validate, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedFanoutInput {
+    /// The ID of the document to embed.
+    pub document: Uuid,
+
+    /// The IDs of the collections in which to store the embeddings.
+    #[validate(length(min = 1))]
+    pub collections: Vec<Uuid>,
+
+    /// If `true`, the reports include the token count of every chunk.
+    #[serde(default)]
+    pub detailed: bool,
+
+    /// If `true`, a synthetic chunk holding the document's name and metadata is embedded
+    /// into every collection along with the chunks.
+    #[serde(default)]
+    pub embed_metadata: bool,
+}
+
+/// Holds the results of processing a single document, so that embedding it into multiple
+/// collections does not repeat any work.
+#[derive(Default)]
+struct TextEmbeddingSession {
+    /// The chunks of the document, set once it is processed.
+    chunks: Option<Vec<String>>,
+
+    /// Whether the chunks were obtained with the fallback of the chunking configuration.
+    chunk_fallback: bool,
+
+    /// The embeddings of the chunks, keyed by embedding provider, model and chunk template.
+    embeddings: HashMap<(String, String, Option<String>), Vec<Vec<f64>>>,
+}
+
+/// Metadata attributes included in the synthetic chunk of a document, if present.
+const METADATA_CHUNK_ATTRIBUTES: &[&str] = &["title", "author"];
+
+/// Compose the synthetic chunk of a document from its name and the
+/// [METADATA_CHUNK_ATTRIBUTES] present in its metadata.
+fn metadata_chunk(name: &str, metadata: Option<&serde_json::Value>) -> String {
+    let mut content = name.to_string();
+
+    for attribute in METADATA_CHUNK_ATTRIBUTES {
+        let value = metadata
+            .and_then(|metadata| metadata.get(attribute))
+            .and_then(|value| value.as_str());
+
+        if let Some(value) = value {
+            content.push_str(&format!("\n{attribute}: {value}"));
+        }
+    }
+
+    content
 }
 
 /// Used for embedding single images.
@@ -821,11 +1676,79 @@ impl EmbedImageInput {
 }
 
 #[derive(Debug, Default, Deserialize, Validate, utoipa::IntoParams, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[validate(Self::validate_schema)]
 pub struct ListEmbeddingReportsParams {
     pub collection: Option<Uuid>,
     pub document: Option<Uuid>,
+
+    /// Only include reports that finished at or after this UTC datetime.
+    pub from: Option<DateTime<Utc>>,
+
+    /// Only include reports that finished at or before this UTC datetime.
+    pub to: Option<DateTime<Utc>>,
+
+    /// Only include additions or removals.
+    pub report_type: Option<EmbeddingReportAction>,
+
+    /// Only include reports of text or image embeddings.
+    pub kind: Option<EmbeddingReportKind>,
+
     #[validate]
     #[serde(flatten)]
     #[param(inline)]
     pub options: Option<Pagination>,
 }
+
+impl ListEmbeddingReportsParams {
+    #[schema_validation]
+    fn validate_schema(&self) -> Result<(), ValidationErrors> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                schema_err!("from>to", "from must not be later than to");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct GetEmbeddingReportParams {
+    /// Whether the report is of an addition or a removal.
+    pub kind: EmbeddingReportAction,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct CleanupReportsParams {
+    /// Reports that finished earlier than this are deleted, e.g. `30d`.
+    /// Supported units are `d` (days), `h` (hours) and `m` (minutes).
+    #[serde_as(as = "DisplayFromStr")]
+    #[param(value_type = String)]
+    pub older_than: ReportRetention,
+}
+
+/// The text of a document reconstructed from its chunks stored in a collection.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconstructedDocument {
+    /// The joined chunks, without their overlap where it could be removed.
+    pub content: String,
+
+    /// The amount of chunks the content was reconstructed from.
+    pub chunks: usize,
+
+    /// The stable IDs of the chunks the content was reconstructed from, in order. They do not
+    /// change when the document is embedded again with the same chunking.
+    pub chunk_ids: Vec<Uuid>,
+
+    /// Indices of chunks not stored in the collection, e.g. left out as near duplicates.
+    /// Their content is missing from the reconstruction.
+    pub missing: Vec<usize>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReportsResult {
+    /// The amount of deleted reports.
+    pub deleted: u64,
+}
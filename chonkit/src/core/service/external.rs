@@ -1,5 +1,9 @@
 use crate::core::{
-    document::store::external::ExternalDocumentStorage, provider::ProviderState, repo::Repository,
+    document::{
+        store::external::ExternalDocumentStorage, DocumentHashing, DocumentType, ImageExtraction,
+    },
+    provider::ProviderState,
+    repo::Repository,
 };
 use file::ExternalFileService;
 
@@ -9,15 +13,45 @@ pub mod file;
 pub struct ServiceFactory {
     repo: Repository,
     providers: ProviderState,
+    hashing: DocumentHashing,
+    allowed_types: Vec<DocumentType>,
+    image_extraction: ImageExtraction,
 }
 
 impl ServiceFactory {
     pub fn new(repo: Repository, providers: ProviderState) -> Self {
-        Self { repo, providers }
+        Self {
+            repo,
+            providers,
+            hashing: DocumentHashing::default(),
+            allowed_types: DocumentType::SUPPORTED.to_vec(),
+            image_extraction: ImageExtraction::default(),
+        }
+    }
+
+    /// Set how the contents of imported documents are hashed and whether duplicates are linked.
+    pub fn with_hashing(mut self, hashing: DocumentHashing) -> Self {
+        self.hashing = hashing;
+        self
+    }
+
+    /// Set the document types accepted for import. Files of other types are skipped.
+    pub fn with_allowed_types(mut self, allowed_types: Vec<DocumentType>) -> Self {
+        self.allowed_types = allowed_types;
+        self
+    }
+
+    /// Set which images extracted from imported documents are stored.
+    pub fn with_image_extraction(mut self, extraction: ImageExtraction) -> Self {
+        self.image_extraction = extraction;
+        self
     }
 
     /// Create an instance of [ExternalFileService] using the provided storage API.
     pub fn storage<T: ExternalDocumentStorage>(&self, api: T) -> ExternalFileService<T> {
         ExternalFileService::new(self.repo.clone(), self.providers.clone(), api)
+            .with_hashing(self.hashing)
+            .with_allowed_types(self.allowed_types.clone())
+            .with_image_extraction(self.image_extraction)
     }
 }
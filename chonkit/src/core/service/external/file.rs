@@ -2,8 +2,9 @@ use crate::{
     core::{
         chunk::ChunkConfig,
         document::{
-            parser::ParseConfig, process_document_images, sha256,
-            store::external::ExternalDocumentStorage,
+            link_document_source, parser::ParseConfig, process_document_images,
+            store::external::ExternalDocumentStorage, DocumentHashing, DocumentType,
+            ImageExtraction,
         },
         model::document::{Document, DocumentInsert},
         provider::ProviderState,
@@ -21,6 +22,13 @@ pub struct ExternalFileService<T> {
     repo: Repository,
     providers: ProviderState,
     api: T,
+    hashing: DocumentHashing,
+
+    /// Files of any other document types are skipped before being downloaded.
+    allowed_types: Vec<DocumentType>,
+
+    /// Determines which images extracted from imported documents are stored.
+    image_extraction: ImageExtraction,
 }
 
 impl<T> ExternalFileService<T> {
@@ -29,8 +37,29 @@ impl<T> ExternalFileService<T> {
             repo,
             providers,
             api,
+            hashing: DocumentHashing::default(),
+            allowed_types: DocumentType::SUPPORTED.to_vec(),
+            image_extraction: ImageExtraction::default(),
         }
     }
+
+    /// Set how the contents of imported documents are hashed and whether duplicates are linked.
+    pub fn with_hashing(mut self, hashing: DocumentHashing) -> Self {
+        self.hashing = hashing;
+        self
+    }
+
+    /// Set the document types accepted for import.
+    pub fn with_allowed_types(mut self, allowed_types: Vec<DocumentType>) -> Self {
+        self.allowed_types = allowed_types;
+        self
+    }
+
+    /// Set which images extracted from imported documents are stored.
+    pub fn with_image_extraction(mut self, extraction: ImageExtraction) -> Self {
+        self.image_extraction = extraction;
+        self
+    }
 }
 
 impl<T> ExternalFileService<T>
@@ -51,7 +80,12 @@ where
 
         // Files from GDrive have their external file ID as the path
         for file_id in file_ids {
-            let file = match self.api.get_file(&file_id).await {
+            let file = match self
+                .api
+                .get_file(&file_id)
+                .await
+                .and_then(|file| file.ext.ensure_allowed(&self.allowed_types).map(|_| file))
+            {
                 Ok(file) => file,
                 Err(e) => {
                     results.failed.push(ImportFailure::new(
@@ -73,14 +107,14 @@ where
                 }
             };
 
-            let hash = sha256(&content);
+            let hash = self.hashing.algorithm.hash(&content);
+            let path = storage.absolute_path(&file.name, file.ext);
 
-            if let Some(existing) = self.repo.get_document_by_hash(&hash).await? {
+            if let Some(existing) = self.find_existing(&hash, &path).await? {
                 results.skipped.push(existing);
                 continue;
             }
 
-            let path = storage.absolute_path(&file.name, file.ext);
             let name = file.name.clone();
 
             let document = self
@@ -111,11 +145,11 @@ where
             process_document_images(
                 self.repo.clone(),
                 self.providers.image.clone(),
+                self.image_extraction,
                 document.id,
                 file.ext,
                 content,
-            )
-            .await?;
+            );
 
             results.success.push(document);
         }
@@ -129,6 +163,8 @@ where
     /// * `file_id`: The external file ID.
     pub async fn import_document(&self, file_id: &str) -> Result<Document, ChonkitError> {
         let file = self.api.get_file(file_id).await?;
+        file.ext.ensure_allowed(&self.allowed_types)?;
+
         let storage = match self.providers.document.get_provider(self.api.id()) {
             Ok(store) => store,
             Err(e) => {
@@ -144,9 +180,9 @@ where
 
         let content = self.api.download(file_id).await?;
 
-        let hash = sha256(&content);
+        let hash = self.hashing.algorithm.hash(&content);
 
-        if let Some(existing) = self.repo.get_document_by_hash(&hash).await? {
+        if let Some(existing) = self.find_existing(&hash, &local_path).await? {
             return Ok(existing);
         }
 
@@ -166,15 +202,31 @@ where
         process_document_images(
             self.repo.clone(),
             self.providers.image.clone(),
+            self.image_extraction,
             document.id,
             file.ext,
             content,
-        )
-        .await?;
+        );
 
         Ok(document)
     }
 
+    /// Get the document with the same content as the file being imported, linking the file to
+    /// it if linking duplicates is enabled.
+    ///
+    /// * `hash`: Content hash of the imported file.
+    /// * `path`: Local path of the imported file.
+    async fn find_existing(
+        &self,
+        hash: &str,
+        path: &str,
+    ) -> Result<Option<Document>, ChonkitError> {
+        if self.hashing.link_duplicates {
+            return link_document_source(&self.repo, hash, self.api.id(), path).await;
+        }
+        self.repo.get_document_by_hash(hash).await
+    }
+
     /// Check the modification time of a document on the external API and compare it with the
     /// local modification time. Returns a list of all files whose external modification time
     /// is newer.
@@ -184,7 +236,13 @@ where
             .list_all_document_update_times(self.api.id())
             .await?;
 
-        let ext_documents = self.api.list_files(None).await?;
+        let ext_documents = self
+            .api
+            .list_files(None)
+            .await?
+            .into_iter()
+            .filter(|document| self.allowed_types.contains(&document.ext))
+            .collect::<Vec<_>>();
 
         let storage = self.providers.document.get_provider(self.api.id())?;
 
@@ -223,7 +281,8 @@ pub struct ImportResult {
     /// Failed to import files.
     failed: Vec<ImportFailure>,
 
-    /// Skipped files in case of path collisions.
+    /// Skipped files in case of content collisions. If linking duplicates is enabled, the
+    /// imported files are linked to these documents as additional sources.
     skipped: Vec<Document>,
 }
 
@@ -1,35 +1,97 @@
 use serde::Serialize;
-use std::{ops::AddAssign, sync::Arc};
+use std::{
+    collections::HashMap,
+    ops::AddAssign,
+    sync::{Arc, Mutex},
+};
+use tiktoken_rs::{tokenizer::Tokenizer as Encoding, CoreBPE};
 
 /// Holds tokenizers so we don't have to load them every time.
 #[derive(Clone)]
 pub struct Tokenizer {
     /// Tokenizer for ChatGPT and Ada models.
-    cl100k: Arc<tiktoken_rs::CoreBPE>,
+    cl100k: Arc<CoreBPE>,
 
     /// Tokenizer for GPT-4 models.
-    o200k: Arc<tiktoken_rs::CoreBPE>,
+    o200k: Arc<CoreBPE>,
+
+    /// Tokenizers of models using neither of the above, loaded the first time they are needed.
+    /// Shared between all tokenizers obtained from the same one.
+    loaded: Arc<Mutex<HashMap<Encoding, Arc<CoreBPE>>>>,
+
+    /// Tokenizer of the model the tokens are counted for, see [Tokenizer::for_model].
+    model: Arc<CoreBPE>,
 }
 
 impl Tokenizer {
     pub fn new() -> Self {
         let chat_gpt_ada_tokenizer =
-            tiktoken_rs::cl100k_base().expect("unable to load cl100k_base tokenizer");
+            Arc::new(tiktoken_rs::cl100k_base().expect("unable to load cl100k_base tokenizer"));
 
         let gpt_4o_tokenizer =
             tiktoken_rs::o200k_base().expect("unable to load o200k_base tokenizer");
 
         Self {
-            cl100k: Arc::new(chat_gpt_ada_tokenizer),
+            model: chat_gpt_ada_tokenizer.clone(),
+            cl100k: chat_gpt_ada_tokenizer,
             o200k: Arc::new(gpt_4o_tokenizer),
+            loaded: Arc::default(),
+        }
+    }
+
+    /// Obtain a tokenizer that additionally counts the tokens of `model` with the tokenizer
+    /// the model uses, making the counts match what the provider bills.
+    ///
+    /// Models whose tokenizer is unknown, e.g. ones not provided by OpenAI, are counted with
+    /// the default cl100k tokenizer.
+    pub fn for_model(&self, model: &str) -> Self {
+        let tokenizer = match tiktoken_rs::tokenizer::get_tokenizer(model) {
+            Some(Encoding::Cl100kBase) | None => self.cl100k.clone(),
+            Some(Encoding::O200kBase) => self.o200k.clone(),
+            Some(encoding) => self.load(encoding),
+        };
+
+        Self {
+            model: tokenizer,
+            ..self.clone()
         }
     }
 
     pub fn count(&self, text: &str) -> TokenCount {
-        TokenCount::new(
-            self.cl100k.encode_with_special_tokens(text).len(),
-            self.o200k.encode_with_special_tokens(text).len(),
-        )
+        let cl100k = self.cl100k.encode_with_special_tokens(text).len();
+        let o200k = self.o200k.encode_with_special_tokens(text).len();
+
+        let model = if Arc::ptr_eq(&self.model, &self.cl100k) {
+            cl100k
+        } else if Arc::ptr_eq(&self.model, &self.o200k) {
+            o200k
+        } else {
+            self.model.encode_with_special_tokens(text).len()
+        };
+
+        TokenCount::new(cl100k, o200k, model)
+    }
+
+    /// Get the tokenizer of the encoding, loading it if this is the first time it is used.
+    /// Falls back to cl100k if it cannot be loaded.
+    fn load(&self, encoding: Encoding) -> Arc<CoreBPE> {
+        let mut loaded = self.loaded.lock().expect("tokenizers poisoned");
+
+        if let Some(tokenizer) = loaded.get(&encoding) {
+            return tokenizer.clone();
+        }
+
+        match tiktoken_rs::get_bpe_from_tokenizer(encoding) {
+            Ok(tokenizer) => {
+                let tokenizer = Arc::new(tokenizer);
+                loaded.insert(encoding, tokenizer.clone());
+                tokenizer
+            }
+            Err(e) => {
+                tracing::warn!("Unable to load {encoding:?} tokenizer, using cl100k: {e}");
+                self.cl100k.clone()
+            }
+        }
     }
 }
 
@@ -46,11 +108,19 @@ pub struct TokenCount {
 
     /// Number of tokens for GPT-4o models.
     pub o200k: usize,
+
+    /// Number of tokens for the embedding model the text is counted for.
+    /// Equal to `cl100k` if no model is given or its tokenizer is unknown.
+    pub model: usize,
 }
 
 impl TokenCount {
-    pub fn new(cl100k: usize, o200k: usize) -> Self {
-        Self { cl100k, o200k }
+    pub fn new(cl100k: usize, o200k: usize, model: usize) -> Self {
+        Self {
+            cl100k,
+            o200k,
+            model,
+        }
     }
 }
 
@@ -58,5 +128,40 @@ impl AddAssign for TokenCount {
     fn add_assign(&mut self, rhs: Self) {
         self.cl100k += rhs.cl100k;
         self.o200k += rhs.o200k;
+        self.model += rhs.model;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tokenizer;
+
+    const TEXT: &str = "Raguru kuha ragu u svojoj plutajućoj kuhinji.";
+
+    #[test]
+    fn counts_with_the_model_tokenizer() {
+        let tokenizer = Tokenizer::new();
+
+        let ada = tokenizer.for_model("text-embedding-3-small").count(TEXT);
+        let davinci = tokenizer.for_model("text-davinci-003").count(TEXT);
+
+        assert_eq!(20, ada.model);
+        assert_eq!(24, davinci.model);
+
+        // The default counts do not depend on the model
+        assert_eq!(ada.cl100k, davinci.cl100k);
+        assert_eq!(ada.o200k, davinci.o200k);
+
+        assert_eq!(17, tokenizer.for_model("gpt-4o").count(TEXT).model);
+    }
+
+    #[test]
+    fn unknown_models_use_the_default_tokenizer() {
+        let tokenizer = Tokenizer::new();
+
+        let count = tokenizer.for_model("BAAI/bge-small-en-v1.5").count(TEXT);
+
+        assert_eq!(count.cl100k, count.model);
+        assert_eq!(tokenizer.count(TEXT).model, count.model);
     }
 }
@@ -1,6 +1,16 @@
-use super::provider::Identity;
-use crate::error::ChonkitError;
+use super::{chunk::ParentChildConfig, provider::Identity};
+use crate::{
+    err,
+    error::{ChonkitErr, ChonkitError},
+};
 use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::Semaphore;
 use uuid::Uuid;
 
 // Collection (identity vector) properties.
@@ -16,6 +26,8 @@ pub const COLLECTION_GROUPS_PROPERTY: &str = "groups";
 
 // General properties
 pub const DOCUMENT_ID_PROPERTY: &str = "document_id";
+/// Custom document attributes copied from the document's metadata.
+pub const METADATA_PROPERTY: &str = "metadata";
 
 /// Keep in sync with [CollectionItem].
 pub const IMAGE_ID_PROPERTY: &str = "image_id";
@@ -28,6 +40,18 @@ pub const IMAGE_DESCRIPTION_PROPERTY: &str = "image_description";
 
 // Text properties
 pub const CONTENT_PROPERTY: &str = "content";
+/// Position of the chunk in the document it was obtained from.
+pub const CHUNK_INDEX_PROPERTY: &str = "chunk_index";
+/// Set on chunks that are not part of the document's content, e.g. its title and metadata.
+pub const SYNTHETIC_PROPERTY: &str = "synthetic";
+/// Position of the parent window the chunk belongs to, only set with parent-child chunking.
+pub const PARENT_INDEX_PROPERTY: &str = "parent_index";
+/// Content of the parent window the chunk belongs to.
+pub const PARENT_CONTENT_PROPERTY: &str = "parent_content";
+
+/// Amount of items fetched from the vector database at a time when listing the items of
+/// a document, see [VectorDb::list_document_items].
+pub const LIST_DOCUMENT_PAGE_SIZE: u32 = 256;
 
 /// Vector database operations.
 #[async_trait::async_trait]
@@ -46,6 +70,23 @@ pub trait VectorDb: Identity {
         data: CreateVectorCollection<'_>,
     ) -> Result<(), ChonkitError>;
 
+    /// Create a vector collection storing multiple vectors per item, searched with
+    /// [VectorDb::query_multivector]. Its items are inserted with
+    /// [CollectionItemInsert::new_multi_text].
+    ///
+    /// Unsupported unless implemented by the vector database.
+    #[cfg(feature = "multivector")]
+    async fn create_multivector_collection(
+        &self,
+        _data: CreateVectorCollection<'_>,
+    ) -> Result<(), ChonkitError> {
+        err!(
+            OperationUnsupported,
+            "{} does not support multi-vector collections",
+            self.id()
+        )
+    }
+
     /// Update a collection's groups property.
     /// Implementations should take care to completely replace the groups with the new ones.
     ///
@@ -73,14 +114,46 @@ pub trait VectorDb: Identity {
     /// * `search`: The query to use as the search vector.
     /// * `collection`: The collection to search in.
     /// * `limit`: Amount of results to return.
+    /// * `max_distance`: Results further from the search vector are excluded.
+    /// * `documents`: If given, only vectors of these documents are searched.
+    /// * `filter`: If given, only vectors whose metadata matches it are searched.
     async fn query(
         &self,
         search: Vec<f64>,
         collection: &str,
         limit: u32,
         max_distance: Option<f64>,
+        documents: Option<&[Uuid]>,
+        filter: Option<&FilterExpr>,
     ) -> Result<Vec<CollectionSearchItem>, ChonkitError>;
 
+    /// Perform late-interaction search in a collection created with
+    /// [VectorDb::create_multivector_collection]. Items are scored with MaxSim, i.e. the sum
+    /// of the best similarities of every query vector with any of the item's vectors. Like in
+    /// [VectorDb::query], lower distances are nearer; the returned distance is the sum of the
+    /// distances of every query vector to the nearest of the item's vectors.
+    ///
+    /// Unsupported unless implemented by the vector database.
+    ///
+    /// * `search`: The query vectors, usually one per query token.
+    /// * `collection`: The collection to search in.
+    /// * `limit`: Amount of results to return.
+    /// * `documents`: If given, only vectors of these documents are searched.
+    #[cfg(feature = "multivector")]
+    async fn query_multivector(
+        &self,
+        _search: Vec<Vec<f64>>,
+        _collection: &str,
+        _limit: u32,
+        _documents: Option<&[Uuid]>,
+    ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        err!(
+            OperationUnsupported,
+            "{} does not support multi-vector search",
+            self.id()
+        )
+    }
+
     /// Store the contents and their vectors to the vector storage.
     /// For text embeddings the `contents` and `vectors` inputs are expected to
     /// be 1:1, i.e. the same index into both lists should
@@ -110,6 +183,28 @@ pub trait VectorDb: Identity {
         image_id: Uuid,
     ) -> Result<(), ChonkitError>;
 
+    /// Delete the text embedding vectors tagged with any of the given `document_ids` in a
+    /// single operation.
+    ///
+    /// * `collection`: The collection to delete from.
+    /// * `document_ids`: The ids of the documents whose vectors to delete.
+    async fn delete_text_embeddings_many(
+        &self,
+        collection: &str,
+        document_ids: &[Uuid],
+    ) -> Result<(), ChonkitError>;
+
+    /// Delete the image embedding vectors tagged with any of the given `image_ids` in a
+    /// single operation.
+    ///
+    /// * `collection`: The collection to delete from.
+    /// * `image_ids`: The ids of the images whose vectors to delete.
+    async fn delete_image_embeddings_many(
+        &self,
+        collection: &str,
+        image_ids: &[Uuid],
+    ) -> Result<(), ChonkitError>;
+
     /// Returns the amount of vectors tagged with the given `document_id`.
     ///
     /// * `collection`: The collection to count in.
@@ -119,204 +214,1039 @@ pub trait VectorDb: Identity {
         collection: &str,
         document_id: Uuid,
     ) -> Result<usize, ChonkitError>;
-}
 
-/// Used by vector databases to represent a collection with an identity vector.
-///
-/// Always make sure this struct follows the structure of [CreateVectorCollection]
-/// as that one is used for inserting collections and will determine the ID vector's
-/// properties.
-#[derive(Debug, Serialize, Deserialize, Default, utoipa::ToSchema)]
-#[serde(rename_all(serialize = "camelCase"))]
-pub struct VectorCollection {
-    /// Unique collection identifier generated by the (non-vector) database.
-    pub collection_id: Uuid,
+    /// List the text vectors of a collection one page at a time, along with their payloads.
+    /// Image vectors and the collection info vector are skipped, so pages can hold fewer
+    /// than `limit` vectors even when there are more to list.
+    ///
+    /// * `collection`: The collection to list from.
+    /// * `cursor`: The [VectorPage::next] cursor of the previous page, `None` for the first page.
+    /// * `limit`: The maximum amount of vectors to fetch from the vector database.
+    async fn list_vectors(
+        &self,
+        collection: &str,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<VectorPage, ChonkitError>;
 
-    /// Unique collection name.
-    pub name: String,
+    /// List the text items of a document in a collection, in no particular order.
+    ///
+    /// By default the whole collection is paged through with [VectorDb::list_vectors].
+    /// Vector databases able to filter by document should override this.
+    ///
+    /// * `collection`: The collection to list from.
+    /// * `document_id`: The id of the document whose items to list.
+    async fn list_document_items(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<Vec<CollectionItem>, ChonkitError> {
+        let mut items = vec![];
+        let mut cursor = None;
 
-    /// Collection vector size
-    pub size: usize,
+        loop {
+            let page = self
+                .list_vectors(collection, cursor, LIST_DOCUMENT_PAGE_SIZE)
+                .await?;
 
-    /// Embedding provider used for the embedding_model.
-    pub embedding_provider: String,
+            items.extend(
+                page.vectors
+                    .into_iter()
+                    .filter(|vector| vector.document_id == document_id)
+                    .map(|vector| CollectionItem {
+                        document_id: Some(vector.document_id),
+                        metadata: vector.metadata,
+                        payload: CollectionItemPayload::Text {
+                            content: vector.content,
+                            chunk_index: vector.chunk_index,
+                            synthetic: vector.synthetic,
+                            parent_index: vector.parent_index,
+                            parent_content: vector.parent_content,
+                        },
+                    }),
+            );
 
-    /// Embedding model used for the collection, has to be available on the embedding provider.
-    pub embedding_model: String,
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => return Ok(items),
+            }
+        }
+    }
+}
 
-    /// Collection user groups.
-    pub groups: Option<Vec<String>>,
+/// Wraps a vector database and limits the amount of concurrent requests made to it.
+///
+/// Requests over the limit wait until one of the running requests completes. This prevents
+/// concurrent batch embeddings from overwhelming backends that share a single client.
+pub struct ConcurrentVectorDb {
+    inner: Arc<dyn VectorDb + Send + Sync>,
+    permits: Semaphore,
 }
 
-impl VectorCollection {
-    pub fn new(
-        id: Uuid,
-        name: String,
-        size: usize,
-        embedding_provider: String,
-        embedding_model: String,
-        groups: Option<Vec<String>>,
-    ) -> Self {
+impl ConcurrentVectorDb {
+    /// `max_requests` is clamped to at least 1.
+    pub fn new(inner: Arc<dyn VectorDb + Send + Sync>, max_requests: usize) -> Self {
         Self {
-            collection_id: id,
-            name,
-            size,
-            embedding_provider,
-            embedding_model,
-            groups,
+            inner,
+            permits: Semaphore::new(max_requests.max(1)),
         }
     }
 
-    pub fn with_id(mut self, id: Uuid) -> Self {
-        self.collection_id = id;
-        self
+    async fn acquire(&self) -> tokio::sync::SemaphorePermit<'_> {
+        // The semaphore is never closed
+        self.permits
+            .acquire()
+            .await
+            .expect("vector db semaphore closed")
     }
+}
 
-    pub fn with_name(mut self, name: String) -> Self {
-        self.name = name;
-        self
+impl Identity for ConcurrentVectorDb {
+    fn id(&self) -> &'static str {
+        self.inner.id()
     }
+}
 
-    pub fn with_size(mut self, size: usize) -> Self {
-        self.size = size;
-        self
+#[async_trait::async_trait]
+impl VectorDb for ConcurrentVectorDb {
+    async fn list_vector_collections(&self) -> Vec<Result<VectorCollection, ChonkitError>> {
+        let _permit = self.acquire().await;
+        self.inner.list_vector_collections().await
     }
 
-    pub fn with_embedding_provider(mut self, provider: String) -> Self {
-        self.embedding_provider = provider;
-        self
+    async fn create_vector_collection(
+        &self,
+        data: CreateVectorCollection<'_>,
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.create_vector_collection(data).await
     }
 
-    pub fn with_embedding_model(mut self, model: String) -> Self {
-        self.embedding_model = model;
-        self
+    #[cfg(feature = "multivector")]
+    async fn create_multivector_collection(
+        &self,
+        data: CreateVectorCollection<'_>,
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.create_multivector_collection(data).await
     }
 
-    pub fn with_groups(mut self, groups: Option<Vec<String>>) -> Self {
-        self.groups = groups;
-        self
+    async fn update_collection_groups(
+        &self,
+        collection: &str,
+        groups: Option<Vec<String>>,
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .update_collection_groups(collection, groups)
+            .await
     }
 
-    /// This implementation must always take care to follow the structure of
-    /// [CreateVectorCollection] since they share the same properties.
-    pub fn query_properties() -> &'static [&'static str] {
-        &[
-            COLLECTION_ID_PROPERTY,
-            COLLECTION_NAME_PROPERTY,
-            COLLECTION_SIZE_PROPERTY,
-            COLLECTION_EMBEDDING_PROVIDER_PROPERTY,
-            COLLECTION_EMBEDDING_MODEL_PROPERTY,
-            COLLECTION_GROUPS_PROPERTY,
-        ]
+    async fn get_collection(&self, name: &str) -> Result<VectorCollection, ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.get_collection(name).await
+    }
+
+    async fn delete_vector_collection(&self, name: &str) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.delete_vector_collection(name).await
+    }
+
+    async fn query(
+        &self,
+        search: Vec<f64>,
+        collection: &str,
+        limit: u32,
+        max_distance: Option<f64>,
+        documents: Option<&[Uuid]>,
+        filter: Option<&FilterExpr>,
+    ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .query(search, collection, limit, max_distance, documents, filter)
+            .await
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn query_multivector(
+        &self,
+        search: Vec<Vec<f64>>,
+        collection: &str,
+        limit: u32,
+        documents: Option<&[Uuid]>,
+    ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .query_multivector(search, collection, limit, documents)
+            .await
+    }
+
+    async fn insert_embeddings(
+        &self,
+        insert: CollectionItemInsert<'_>,
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.insert_embeddings(insert).await
+    }
+
+    async fn delete_text_embeddings(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .delete_text_embeddings(collection, document_id)
+            .await
+    }
+
+    async fn delete_image_embeddings(
+        &self,
+        collection: &str,
+        image_id: Uuid,
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .delete_image_embeddings(collection, image_id)
+            .await
+    }
+
+    async fn delete_text_embeddings_many(
+        &self,
+        collection: &str,
+        document_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .delete_text_embeddings_many(collection, document_ids)
+            .await
+    }
+
+    async fn delete_image_embeddings_many(
+        &self,
+        collection: &str,
+        image_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .delete_image_embeddings_many(collection, image_ids)
+            .await
+    }
+
+    async fn count_vectors(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<usize, ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.count_vectors(collection, document_id).await
+    }
+
+    async fn list_vectors(
+        &self,
+        collection: &str,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<VectorPage, ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner.list_vectors(collection, cursor, limit).await
+    }
+
+    async fn list_document_items(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<Vec<CollectionItem>, ChonkitError> {
+        let _permit = self.acquire().await;
+        self.inner
+            .list_document_items(collection, document_id)
+            .await
     }
 }
 
-/// Used by vector databases to create a collection with an identity vector.
+/// Wraps a vector database and splits text inserts into sub-batches of at most `batch_size`
+/// vectors, inserted one after another.
 ///
-/// Must always be kept in sync with [VectorCollection].
-#[derive(Debug, Serialize)]
-pub struct CreateVectorCollection<'a> {
-    pub collection_id: Uuid,
-    pub name: &'a str,
-    pub size: usize,
-    pub embedding_provider: &'a str,
-    pub embedding_model: &'a str,
-    pub groups: Option<Vec<String>>,
+/// Backends limit the size of a single insert request, which documents with thousands of
+/// chunks can exceed. If a sub-batch fails, the vectors of the document inserted by the
+/// previous ones are removed on a best effort basis so the document is not left partially
+/// embedded.
+pub struct BatchedVectorDb {
+    inner: Arc<dyn VectorDb + Send + Sync>,
+    batch_size: usize,
 }
 
-impl<'a> CreateVectorCollection<'a> {
-    pub fn new(
-        id: Uuid,
-        name: &'a str,
-        size: usize,
-        embedding_provider: &'a str,
-        embedding_model: &'a str,
-        groups: Option<Vec<String>>,
-    ) -> Self {
+impl BatchedVectorDb {
+    /// `batch_size` is clamped to at least 1.
+    pub fn new(inner: Arc<dyn VectorDb + Send + Sync>, batch_size: usize) -> Self {
         Self {
-            collection_id: id,
-            name,
-            size,
-            embedding_provider,
-            embedding_model,
-            groups,
+            inner,
+            batch_size: batch_size.max(1),
         }
     }
 }
 
-impl<'a> From<&'a VectorCollection> for CreateVectorCollection<'a> {
-    fn from(value: &'a VectorCollection) -> Self {
-        Self {
-            collection_id: value.collection_id,
-            name: &value.name,
-            size: value.size,
-            embedding_provider: &value.embedding_provider,
-            embedding_model: &value.embedding_model,
-            groups: value.groups.clone(),
+/// Split the items and their vectors into consecutive sub-batches of at most `size`.
+fn split_batch<'a, V>(
+    items: Vec<CollectionItemText<'a>>,
+    vectors: Vec<V>,
+    size: usize,
+) -> Vec<(Vec<CollectionItemText<'a>>, Vec<V>)> {
+    let mut items = items.into_iter();
+    let mut vectors = vectors.into_iter();
+    let mut batches = vec![];
+
+    loop {
+        let batch_items = items.by_ref().take(size).collect::<Vec<_>>();
+        if batch_items.is_empty() {
+            return batches;
         }
+        let batch_vectors = vectors.by_ref().take(size).collect();
+        batches.push((batch_items, batch_vectors));
     }
 }
 
-#[derive(Debug)]
-pub struct CollectionItemInsert<'a> {
-    /// Specifies the collection to insert to.
-    pub collection: &'a str,
-
-    /// The payload to insert.
-    pub payload: CollectionItemInsertPayload<'a>,
+impl Identity for BatchedVectorDb {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
 }
 
-impl<'a> CollectionItemInsert<'a> {
-    pub fn new_text(
-        document_id: Uuid,
-        collection: &'a str,
-        content: &'a [&'a str],
-        vectors: Vec<Vec<f64>>,
-    ) -> Self {
-        Self {
-            collection,
-            payload: CollectionItemInsertPayload::Text {
-                items: content
-                    .iter()
-                    .map(|content| CollectionItemText {
-                        document_id,
-                        content,
-                    })
-                    .collect(),
-                vectors,
-            },
-        }
+#[async_trait::async_trait]
+impl VectorDb for BatchedVectorDb {
+    async fn list_vector_collections(&self) -> Vec<Result<VectorCollection, ChonkitError>> {
+        self.inner.list_vector_collections().await
     }
 
-    pub fn new_image(
-        document_id: Option<Uuid>,
-        collection: &'a str,
-        image_id: Uuid,
-        image_b64: &'a str,
-        image_path: &'a str,
-        image_description: Option<&'a str>,
-        vector: Vec<f64>,
-    ) -> Self {
-        Self {
-            collection,
-            payload: CollectionItemInsertPayload::Image {
-                item: CollectionItemImage {
-                    document_id,
-                    image_id,
-                    image_b64,
-                    image_path,
-                    image_description,
-                },
-                vector,
-            },
-        }
+    async fn create_vector_collection(
+        &self,
+        data: CreateVectorCollection<'_>,
+    ) -> Result<(), ChonkitError> {
+        self.inner.create_vector_collection(data).await
     }
-}
 
-/// Transport DTO for inserting collection items.
-///
-/// The `item` fields are payloads, i.e. the original chunks/blobs.
-///
+    #[cfg(feature = "multivector")]
+    async fn create_multivector_collection(
+        &self,
+        data: CreateVectorCollection<'_>,
+    ) -> Result<(), ChonkitError> {
+        self.inner.create_multivector_collection(data).await
+    }
+
+    async fn update_collection_groups(
+        &self,
+        collection: &str,
+        groups: Option<Vec<String>>,
+    ) -> Result<(), ChonkitError> {
+        self.inner
+            .update_collection_groups(collection, groups)
+            .await
+    }
+
+    async fn get_collection(&self, name: &str) -> Result<VectorCollection, ChonkitError> {
+        self.inner.get_collection(name).await
+    }
+
+    async fn delete_vector_collection(&self, name: &str) -> Result<(), ChonkitError> {
+        self.inner.delete_vector_collection(name).await
+    }
+
+    async fn query(
+        &self,
+        search: Vec<f64>,
+        collection: &str,
+        limit: u32,
+        max_distance: Option<f64>,
+        documents: Option<&[Uuid]>,
+        filter: Option<&FilterExpr>,
+    ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        self.inner
+            .query(search, collection, limit, max_distance, documents, filter)
+            .await
+    }
+
+    #[cfg(feature = "multivector")]
+    async fn query_multivector(
+        &self,
+        search: Vec<Vec<f64>>,
+        collection: &str,
+        limit: u32,
+        documents: Option<&[Uuid]>,
+    ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+        self.inner
+            .query_multivector(search, collection, limit, documents)
+            .await
+    }
+
+    async fn insert_embeddings(
+        &self,
+        insert: CollectionItemInsert<'_>,
+    ) -> Result<(), ChonkitError> {
+        let CollectionItemInsert {
+            collection,
+            payload,
+        } = insert;
+
+        let (document_id, batches) = match payload {
+            CollectionItemInsertPayload::Text { items, vectors }
+                if items.len() > self.batch_size =>
+            {
+                (
+                    items[0].document_id,
+                    split_batch(items, vectors, self.batch_size)
+                        .into_iter()
+                        .map(|(items, vectors)| CollectionItemInsertPayload::Text {
+                            items,
+                            vectors,
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            }
+            #[cfg(feature = "multivector")]
+            CollectionItemInsertPayload::MultiText { items, vectors }
+                if items.len() > self.batch_size =>
+            {
+                (
+                    items[0].document_id,
+                    split_batch(items, vectors, self.batch_size)
+                        .into_iter()
+                        .map(|(items, vectors)| CollectionItemInsertPayload::MultiText {
+                            items,
+                            vectors,
+                        })
+                        .collect(),
+                )
+            }
+            payload => {
+                return self
+                    .inner
+                    .insert_embeddings(CollectionItemInsert {
+                        collection,
+                        payload,
+                    })
+                    .await
+            }
+        };
+
+        let total = batches.len();
+
+        for (i, payload) in batches.into_iter().enumerate() {
+            let insert = CollectionItemInsert {
+                collection,
+                payload,
+            };
+
+            let Err(e) = self.inner.insert_embeddings(insert).await else {
+                continue;
+            };
+
+            tracing::error!(
+                "{} - inserting sub-batch {}/{total} of document '{document_id}' into '{collection}' failed: {e}",
+                self.id(),
+                i + 1
+            );
+
+            if i > 0 {
+                if let Err(e) = self
+                    .inner
+                    .delete_text_embeddings(collection, document_id)
+                    .await
+                {
+                    tracing::error!(
+                        "{} - unable to remove inserted sub-batches of document '{document_id}' from '{collection}': {e}",
+                        self.id()
+                    );
+                }
+            }
+
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_text_embeddings(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<(), ChonkitError> {
+        self.inner
+            .delete_text_embeddings(collection, document_id)
+            .await
+    }
+
+    async fn delete_image_embeddings(
+        &self,
+        collection: &str,
+        image_id: Uuid,
+    ) -> Result<(), ChonkitError> {
+        self.inner
+            .delete_image_embeddings(collection, image_id)
+            .await
+    }
+
+    async fn delete_text_embeddings_many(
+        &self,
+        collection: &str,
+        document_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        self.inner
+            .delete_text_embeddings_many(collection, document_ids)
+            .await
+    }
+
+    async fn delete_image_embeddings_many(
+        &self,
+        collection: &str,
+        image_ids: &[Uuid],
+    ) -> Result<(), ChonkitError> {
+        self.inner
+            .delete_image_embeddings_many(collection, image_ids)
+            .await
+    }
+
+    async fn count_vectors(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<usize, ChonkitError> {
+        self.inner.count_vectors(collection, document_id).await
+    }
+
+    async fn list_vectors(
+        &self,
+        collection: &str,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<VectorPage, ChonkitError> {
+        self.inner.list_vectors(collection, cursor, limit).await
+    }
+
+    async fn list_document_items(
+        &self,
+        collection: &str,
+        document_id: Uuid,
+    ) -> Result<Vec<CollectionItem>, ChonkitError> {
+        self.inner
+            .list_document_items(collection, document_id)
+            .await
+    }
+}
+
+/// Parameters of a [VectorDb::query].
+#[derive(Debug)]
+pub struct VectorQuery<'a> {
+    /// The vector to search with.
+    pub search: Vec<f64>,
+
+    /// The collection to search in.
+    pub collection: &'a str,
+
+    /// The maximum amount of results.
+    pub limit: u32,
+
+    /// Results further than this distance are excluded.
+    pub max_distance: Option<f64>,
+
+    /// Restricts the results to chunks of these documents.
+    pub documents: Option<&'a [Uuid]>,
+
+    /// Restricts the results to chunks whose metadata satisfies the filter.
+    pub filter: Option<&'a FilterExpr>,
+}
+
+impl<'a> VectorQuery<'a> {
+    pub fn new(search: Vec<f64>, collection: &'a str, limit: u32) -> Self {
+        Self {
+            search,
+            collection,
+            limit,
+            max_distance: None,
+            documents: None,
+            filter: None,
+        }
+    }
+
+    pub fn with_max_distance(mut self, max_distance: Option<f64>) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn with_documents(mut self, documents: Option<&'a [Uuid]>) -> Self {
+        self.documents = documents;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Option<&'a FilterExpr>) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// Run a [VectorDb::query], failing with a timeout error if the vector database does not respond
+/// within `timeout`.
+///
+/// None of the backends stream their results, so no partial results are available on timeout.
+pub async fn query_with_timeout(
+    vector_db: &(dyn VectorDb + Send + Sync),
+    query: VectorQuery<'_>,
+    timeout: Duration,
+) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+    let VectorQuery {
+        search,
+        collection,
+        limit,
+        max_distance,
+        documents,
+        filter,
+    } = query;
+
+    match tokio::time::timeout(
+        timeout,
+        vector_db.query(search, collection, limit, max_distance, documents, filter),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => err!(
+            Timeout,
+            "'{}' did not respond to the query on '{collection}' within {}ms",
+            vector_db.id(),
+            timeout.as_millis()
+        ),
+    }
+}
+
+/// Run the `queries` concurrently with [query_with_timeout]. Queries that time out do not fail
+/// the others, their results are `None`.
+pub async fn query_all_with_timeout<'a>(
+    queries: impl IntoIterator<Item = (&'a (dyn VectorDb + Send + Sync), VectorQuery<'a>)>,
+    timeout: Duration,
+) -> Result<Vec<Option<Vec<CollectionSearchItem>>>, ChonkitError> {
+    let results = futures_util::future::join_all(
+        queries
+            .into_iter()
+            .map(|(vector_db, query)| query_with_timeout(vector_db, query, timeout)),
+    )
+    .await;
+
+    results
+        .into_iter()
+        .map(|result| match result {
+            Ok(items) => Ok(Some(items)),
+            Err(e) if matches!(e.error, ChonkitErr::Timeout(_)) => Ok(None),
+            Err(e) => Err(e),
+        })
+        .collect()
+}
+
+/// Check that `vector` contains only finite values.
+///
+/// Backends tend to fail opaquely (or worse, silently store garbage) when given NaNs or infinities,
+/// so we check the vectors we receive from embedders before passing them on.
+pub fn validate_vector(vector: &[f64]) -> Result<(), ChonkitError> {
+    if let Some(i) = vector.iter().position(|x| !x.is_finite()) {
+        return err!(
+            InvalidVector,
+            "vector contains a non-finite value ({}) at position {i}",
+            vector[i]
+        );
+    }
+    Ok(())
+}
+
+/// Pad the vector with zeros or truncate it to `size`.
+///
+/// Lossy, the vector is no longer in the space of its model. Only meant for searching
+/// collections of a previous model with vectors of a new one while migrating.
+pub fn adapt_vector_dimension(vector: &mut Vec<f64>, size: usize) {
+    vector.resize(size, 0.);
+}
+
+/// Check that every vector in `vectors` is of the given `size` and contains only finite values.
+///
+/// * `vectors`: The vectors to check.
+/// * `size`: The expected vector size, i.e. the size of the collection they are inserted to.
+pub fn validate_vectors(vectors: &[Vec<f64>], size: usize) -> Result<(), ChonkitError> {
+    for (i, vector) in vectors.iter().enumerate() {
+        if vector.len() != size {
+            return err!(
+                InvalidVector,
+                "vector at index {i} has size {} (expected {size})",
+                vector.len()
+            );
+        }
+
+        if let Some(j) = vector.iter().position(|x| !x.is_finite()) {
+            return err!(
+                InvalidVector,
+                "vector at index {i} contains a non-finite value ({}) at position {j}",
+                vector[j]
+            );
+        }
+    }
+    Ok(())
+}
+
+/// L2 normalize `vector` in place. Zero vectors are left untouched.
+pub fn normalize_vector(vector: &mut [f64]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+
+    if norm == 0.0 || !norm.is_finite() {
+        return;
+    }
+
+    for x in vector.iter_mut() {
+        *x /= norm;
+    }
+}
+
+/// Cosine similarity of the vectors, between -1 and 1. Zero if either is a zero vector.
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+
+    if norms == 0.0 {
+        return 0.0;
+    }
+
+    a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>() / norms
+}
+
+/// Find the vectors that are near duplicates of a preceding one, i.e. have a cosine similarity
+/// of at least `threshold` with a vector that is not a near duplicate itself.
+///
+/// Returns the indices of the near duplicates.
+pub fn near_duplicates(vectors: &[Vec<f64>], threshold: f64) -> HashSet<usize> {
+    let mut kept: Vec<&[f64]> = vec![];
+    let mut duplicates = HashSet::new();
+
+    for (i, vector) in vectors.iter().enumerate() {
+        if kept
+            .iter()
+            .any(|other| cosine_similarity(vector, other) >= threshold)
+        {
+            duplicates.insert(i);
+        } else {
+            kept.push(vector);
+        }
+    }
+
+    duplicates
+}
+
+/// Used by vector databases to represent a collection with an identity vector.
+///
+/// Always make sure this struct follows the structure of [CreateVectorCollection]
+/// as that one is used for inserting collections and will determine the ID vector's
+/// properties.
+#[derive(Debug, Serialize, Deserialize, Default, utoipa::ToSchema)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct VectorCollection {
+    /// Unique collection identifier generated by the (non-vector) database.
+    pub collection_id: Uuid,
+
+    /// Unique collection name.
+    pub name: String,
+
+    /// Collection vector size
+    pub size: usize,
+
+    /// Embedding provider used for the embedding_model.
+    pub embedding_provider: String,
+
+    /// Embedding model used for the collection, has to be available on the embedding provider.
+    pub embedding_model: String,
+
+    /// Collection user groups.
+    pub groups: Option<Vec<String>>,
+}
+
+impl VectorCollection {
+    pub fn new(
+        id: Uuid,
+        name: String,
+        size: usize,
+        embedding_provider: String,
+        embedding_model: String,
+        groups: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            collection_id: id,
+            name,
+            size,
+            embedding_provider,
+            embedding_model,
+            groups,
+        }
+    }
+
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.collection_id = id;
+        self
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_embedding_provider(mut self, provider: String) -> Self {
+        self.embedding_provider = provider;
+        self
+    }
+
+    pub fn with_embedding_model(mut self, model: String) -> Self {
+        self.embedding_model = model;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: Option<Vec<String>>) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// This implementation must always take care to follow the structure of
+    /// [CreateVectorCollection] since they share the same properties.
+    pub fn query_properties() -> &'static [&'static str] {
+        &[
+            COLLECTION_ID_PROPERTY,
+            COLLECTION_NAME_PROPERTY,
+            COLLECTION_SIZE_PROPERTY,
+            COLLECTION_EMBEDDING_PROVIDER_PROPERTY,
+            COLLECTION_EMBEDDING_MODEL_PROPERTY,
+            COLLECTION_GROUPS_PROPERTY,
+        ]
+    }
+}
+
+/// Used by vector databases to create a collection with an identity vector.
+///
+/// Must always be kept in sync with [VectorCollection].
+#[derive(Debug, Serialize)]
+pub struct CreateVectorCollection<'a> {
+    pub collection_id: Uuid,
+    pub name: &'a str,
+    pub size: usize,
+    pub embedding_provider: &'a str,
+    pub embedding_model: &'a str,
+    pub groups: Option<Vec<String>>,
+}
+
+impl<'a> CreateVectorCollection<'a> {
+    pub fn new(
+        id: Uuid,
+        name: &'a str,
+        size: usize,
+        embedding_provider: &'a str,
+        embedding_model: &'a str,
+        groups: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            collection_id: id,
+            name,
+            size,
+            embedding_provider,
+            embedding_model,
+            groups,
+        }
+    }
+}
+
+impl<'a> From<&'a VectorCollection> for CreateVectorCollection<'a> {
+    fn from(value: &'a VectorCollection) -> Self {
+        Self {
+            collection_id: value.collection_id,
+            name: &value.name,
+            size: value.size,
+            embedding_provider: &value.embedding_provider,
+            embedding_model: &value.embedding_model,
+            groups: value.groups.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CollectionItemInsert<'a> {
+    /// Specifies the collection to insert to.
+    pub collection: &'a str,
+
+    /// The payload to insert.
+    pub payload: CollectionItemInsertPayload<'a>,
+}
+
+impl<'a> CollectionItemInsert<'a> {
+    /// * `metadata`: Custom document attributes stored in the payload of every vector.
+    pub fn new_text(
+        document_id: Uuid,
+        collection: &'a str,
+        content: &'a [&'a str],
+        vectors: Vec<Vec<f64>>,
+        metadata: Option<&'a serde_json::Value>,
+    ) -> Self {
+        Self {
+            collection,
+            payload: CollectionItemInsertPayload::Text {
+                items: text_items(document_id, content, metadata),
+                vectors,
+            },
+        }
+    }
+
+    /// Like [CollectionItemInsert::new_text], but with multiple vectors per chunk, obtained
+    /// with [Embedder::embed_text_multivector](super::embeddings::Embedder::embed_text_multivector).
+    #[cfg(feature = "multivector")]
+    pub fn new_multi_text(
+        document_id: Uuid,
+        collection: &'a str,
+        content: &'a [&'a str],
+        vectors: Vec<Vec<Vec<f64>>>,
+        metadata: Option<&'a serde_json::Value>,
+    ) -> Self {
+        Self {
+            collection,
+            payload: CollectionItemInsertPayload::MultiText {
+                items: text_items(document_id, content, metadata),
+                vectors,
+            },
+        }
+    }
+
+    /// Prepend a synthetic text item, i.e. one whose content is not a chunk of the document.
+    /// Synthetic items have no chunk index. Has no effect on image inserts.
+    pub fn with_synthetic_text(
+        mut self,
+        document_id: Uuid,
+        content: &'a str,
+        vector: Vec<f64>,
+        metadata: Option<&'a serde_json::Value>,
+    ) -> Self {
+        if let CollectionItemInsertPayload::Text {
+            ref mut items,
+            ref mut vectors,
+        } = self.payload
+        {
+            items.insert(
+                0,
+                CollectionItemText {
+                    document_id,
+                    chunk_index: None,
+                    content,
+                    metadata,
+                    synthetic: true,
+                    parent_index: None,
+                    parent_content: None,
+                },
+            );
+            vectors.insert(0, vector);
+        }
+        self
+    }
+
+    /// Store the parent window of every chunk alongside it. Must be called before adding
+    /// synthetic items. Has no effect on image inserts.
+    ///
+    /// * `parents`: The parent windows obtained from [ParentChildConfig::parents].
+    pub fn with_parents(mut self, config: &ParentChildConfig, parents: &'a [String]) -> Self {
+        if let CollectionItemInsertPayload::Text { ref mut items, .. } = self.payload {
+            for item in items.iter_mut() {
+                let Some(chunk_index) = item.chunk_index else {
+                    continue;
+                };
+                let parent_index = config.parent_index(chunk_index);
+                item.parent_index = Some(parent_index);
+                item.parent_content = parents.get(parent_index).map(String::as_str);
+            }
+        }
+        self
+    }
+
+    /// Leave out the chunks at the given indices. The remaining chunks keep their chunk
+    /// indices. Synthetic items are always kept. Has no effect on other inserts.
+    pub fn without_chunks(mut self, skip: &HashSet<usize>) -> Self {
+        if skip.is_empty() {
+            return self;
+        }
+
+        if let CollectionItemInsertPayload::Text {
+            ref mut items,
+            ref mut vectors,
+        } = self.payload
+        {
+            (*items, *vectors) = std::mem::take(items)
+                .into_iter()
+                .zip(std::mem::take(vectors))
+                .filter(|(item, _)| !item.chunk_index.is_some_and(|i| skip.contains(&i)))
+                .unzip();
+        }
+
+        self
+    }
+
+    pub fn new_image(
+        document_id: Option<Uuid>,
+        collection: &'a str,
+        image_id: Uuid,
+        image_b64: &'a str,
+        image_path: &'a str,
+        image_description: Option<&'a str>,
+        vector: Vec<f64>,
+    ) -> Self {
+        Self {
+            collection,
+            payload: CollectionItemInsertPayload::Image {
+                item: CollectionItemImage {
+                    document_id,
+                    image_id,
+                    image_b64,
+                    image_path,
+                    image_description,
+                },
+                vector,
+            },
+        }
+    }
+}
+
+/// The ID of the chunk at `chunk_index` of a document, a UUIDv5 of the index in the namespace of
+/// the document ID.
+///
+/// Chunk IDs are stable, re-embedding a document with the same chunking yields the same IDs, so
+/// clients can store them to reference chunks. Vectors of chunks are stored under their ID.
+pub fn chunk_id(document_id: Uuid, chunk_index: usize) -> Uuid {
+    Uuid::new_v5(&document_id, chunk_index.to_string().as_bytes())
+}
+
+/// Text items of the chunks of a document, indexed by their position in `content`.
+fn text_items<'a>(
+    document_id: Uuid,
+    content: &'a [&'a str],
+    metadata: Option<&'a serde_json::Value>,
+) -> Vec<CollectionItemText<'a>> {
+    content
+        .iter()
+        .enumerate()
+        .map(|(chunk_index, content)| CollectionItemText {
+            document_id,
+            chunk_index: Some(chunk_index),
+            content,
+            metadata,
+            synthetic: false,
+            parent_index: None,
+            parent_content: None,
+        })
+        .collect()
+}
+
+/// Transport DTO for inserting collection items.
+///
+/// The `item` fields are payloads, i.e. the original chunks/blobs.
+///
 /// The `vector(s)` are the embeddings.
 #[derive(Debug)]
 pub enum CollectionItemInsertPayload<'a> {
@@ -324,41 +1254,431 @@ pub enum CollectionItemInsertPayload<'a> {
         items: Vec<CollectionItemText<'a>>,
         vectors: Vec<Vec<f64>>,
     },
-    Image {
-        item: CollectionItemImage<'a>,
-        vector: Vec<f64>,
+    Image {
+        item: CollectionItemImage<'a>,
+        vector: Vec<f64>,
+    },
+    /// Text items with multiple vectors each, only insertable to multi-vector collections.
+    #[cfg(feature = "multivector")]
+    MultiText {
+        items: Vec<CollectionItemText<'a>>,
+        vectors: Vec<Vec<Vec<f64>>>,
+    },
+}
+
+/// This structure must always be kept in sync with [CollectionItem] and its corresponding flattened
+/// [CollectionItemPayload::Text].
+#[derive(Debug, Serialize)]
+pub struct CollectionItemText<'a> {
+    pub document_id: Uuid,
+    /// Only `None` for synthetic items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    pub content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<&'a serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub synthetic: bool,
+    /// Only set with parent-child chunking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_content: Option<&'a str>,
+}
+
+impl CollectionItemText<'_> {
+    /// The ID the vector is stored under, the [chunk_id] of chunks. Synthetic items are not
+    /// chunks and get a random one.
+    pub fn id(&self) -> Uuid {
+        match self.chunk_index {
+            Some(chunk_index) if !self.synthetic => chunk_id(self.document_id, chunk_index),
+            _ => Uuid::new_v4(),
+        }
+    }
+}
+
+/// This structure must always be kept in sync with [CollectionItem] and its corresponding flattened
+/// [CollectionItemPayload::Image].
+#[derive(Debug, Serialize)]
+pub struct CollectionItemImage<'a> {
+    pub document_id: Option<Uuid>,
+    pub image_id: Uuid,
+    pub image_b64: &'a str,
+    pub image_path: &'a str,
+    pub image_description: Option<&'a str>,
+}
+
+/// A text vector and its payload as stored in a collection. Used for exporting collections
+/// and importing them without embedding the content again.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ExportedVector {
+    pub document_id: Uuid,
+
+    /// Not present on synthetic chunks and vectors inserted before chunk indices were stored.
+    pub chunk_index: Option<usize>,
+
+    pub content: String,
+
+    /// Whether the chunk is not part of the document's content, see [SYNTHETIC_PROPERTY].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub synthetic: bool,
+
+    /// Custom document attributes stored with the vector, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+
+    /// Only present on chunks obtained with parent-child chunking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_index: Option<usize>,
+
+    /// The content of the parent window, present alongside `parent_index`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_content: Option<String>,
+
+    pub vector: Vec<f64>,
+}
+
+/// A page of vectors obtained with [VectorDb::list_vectors].
+#[derive(Debug)]
+pub struct VectorPage {
+    pub vectors: Vec<ExportedVector>,
+
+    /// Cursor for obtaining the next page, `None` if this is the last one.
+    pub next: Option<String>,
+}
+
+/// Contains the collection item payload and the distance to the query vector.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CollectionSearchItem {
+    pub distance: Option<f64>,
+
+    /// Relevance of the item between 0 and 1, where 1 is best, obtained from the distance
+    /// with [DistanceMetric::score].
+    pub score: Option<f64>,
+
+    /// Relevance of the item given by the reranker, higher is better. Only present when
+    /// reranking is requested, see [rerank_search_items][crate::core::rerank::rerank_search_items].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f64>,
+
+    pub item: CollectionItem,
+
+    /// Byte ranges (start inclusive, end exclusive) of the query terms in the item's content.
+    /// Only present when highlighting is requested, see [highlight_search_items].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<(usize, usize)>,
+
+    /// The stable ID of the chunk, see [chunk_id]. Not present on synthetic chunks and images.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_id: Option<Uuid>,
+}
+
+impl CollectionSearchItem {
+    pub fn new(item: CollectionItem, distance: Option<f64>) -> Self {
+        let id = match (item.document_id, &item.payload) {
+            (
+                Some(document_id),
+                CollectionItemPayload::Text {
+                    chunk_index: Some(chunk_index),
+                    synthetic: false,
+                    ..
+                },
+            ) => Some(chunk_id(document_id, *chunk_index)),
+            _ => None,
+        };
+
+        Self {
+            distance,
+            score: None,
+            rerank_score: None,
+            item,
+            highlights: vec![],
+            chunk_id: id,
+        }
+    }
+}
+
+/// The metric used to measure the distance between vectors of a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// One minus the cosine similarity, between 0 and 2.
+    Cosine,
+
+    /// The negative dot product, unbounded.
+    Dot,
+
+    /// The euclidean distance, at least 0.
+    Euclid,
+}
+
+impl DistanceMetric {
+    /// Convert a distance of this metric to a relevance score between 0 and 1, where 1 is best.
+    pub fn score(&self, distance: f64) -> f64 {
+        let score = match self {
+            Self::Cosine => 1. - distance / 2.,
+            Self::Dot => 1. / (1. + distance.exp()),
+            Self::Euclid => 1. / (1. + distance),
+        };
+
+        score.clamp(0., 1.)
+    }
+}
+
+/// Populate the scores of the items from their distances.
+pub fn score_search_items(items: &mut [CollectionSearchItem], metric: DistanceMetric) {
+    for item in items {
+        item.score = item.distance.map(|distance| metric.score(distance));
+    }
+}
+
+/// Order the items by distance, nearest first. Items at equal distance are ordered by their
+/// document ID and then their chunk index, so ties do not depend on the vector database.
+/// Items without a distance come last.
+pub fn sort_search_items(items: &mut [CollectionSearchItem]) {
+    let chunk_index = |item: &CollectionSearchItem| match item.item.payload {
+        CollectionItemPayload::Text { chunk_index, .. } => chunk_index,
+        CollectionItemPayload::Image { .. } => None,
+    };
+
+    items.sort_by(|a, b| {
+        match (a.distance, b.distance) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+        .then_with(|| a.item.document_id.cmp(&b.item.document_id))
+        .then_with(|| chunk_index(a).cmp(&chunk_index(b)))
+    });
+}
+
+/// Leave out search results crowding out more diverse ones. The items are expected in order of
+/// relevance and the most relevant one of any duplicates is kept.
+///
+/// * `per_document`: The maximum amount of items from a single document.
+/// * `max_similarity`: Text items sharing at least this share of words with a kept text item,
+///   relative to the shorter of the two, are left out.
+pub fn deduplicate_search_items(
+    items: Vec<CollectionSearchItem>,
+    per_document: Option<usize>,
+    max_similarity: Option<f64>,
+) -> Vec<CollectionSearchItem> {
+    let mut document_counts = HashMap::<Uuid, usize>::new();
+    let mut kept_words: Vec<HashSet<String>> = vec![];
+    let mut kept = vec![];
+
+    for item in items {
+        if let (Some(max), Some(document_id)) = (per_document, item.item.document_id) {
+            if document_counts
+                .get(&document_id)
+                .is_some_and(|count| *count >= max)
+            {
+                continue;
+            }
+        }
+
+        if let (Some(threshold), CollectionItemPayload::Text { content, .. }) =
+            (max_similarity, &item.item.payload)
+        {
+            let words = words(content);
+            if kept_words
+                .iter()
+                .any(|kept| word_overlap(kept, &words) >= threshold)
+            {
+                continue;
+            }
+            kept_words.push(words);
+        }
+
+        if let Some(document_id) = item.item.document_id {
+            *document_counts.entry(document_id).or_default() += 1;
+        }
+
+        kept.push(item);
+    }
+
+    kept
+}
+
+/// Replace the content of text items with the parent window they belong to. Items are expected
+/// in order of relevance and only the most relevant child of every parent window is kept.
+/// Items without a parent window are left as they are.
+pub fn replace_with_parents(items: Vec<CollectionSearchItem>) -> Vec<CollectionSearchItem> {
+    let mut seen = HashSet::<(Option<Uuid>, usize)>::new();
+    let mut kept = vec![];
+
+    for mut item in items {
+        if let CollectionItemPayload::Text {
+            ref mut content,
+            parent_index: Some(parent_index),
+            ref mut parent_content,
+            ..
+        } = item.item.payload
+        {
+            if !seen.insert((item.item.document_id, parent_index)) {
+                continue;
+            }
+            if let Some(parent) = parent_content.take() {
+                *content = parent;
+            }
+        }
+
+        kept.push(item);
+    }
+
+    kept
+}
+
+/// Mark where the terms of the `query` occur in the content of text items.
+///
+/// Terms are matched as whole words, case-insensitively. The ranges are byte offsets into the
+/// content and always lie on character boundaries. Image items are left without highlights.
+pub fn highlight_search_items(items: &mut [CollectionSearchItem], query: &str) {
+    let terms = words(query);
+
+    for item in items {
+        if let CollectionItemPayload::Text { ref content, .. } = item.item.payload {
+            item.highlights = word_ranges(content)
+                .filter(|(start, end)| terms.contains(&content[*start..*end].to_lowercase()))
+                .collect();
+        }
+    }
+}
+
+/// The byte ranges of the words in the text, using the same boundaries as [words].
+fn word_ranges(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut start = None;
+
+    text.char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+        .filter_map(move |(i, c)| match (start, c.is_alphanumeric()) {
+            (None, true) => {
+                start = Some(i);
+                None
+            }
+            (Some(s), false) => {
+                start = None;
+                Some((s, i))
+            }
+            _ => None,
+        })
+}
+
+/// The share of words of the smaller set that are also in the other one, between 0 and 1.
+///
+/// Unlike the Jaccard index, this detects chunks sharing only an overlapping region with a
+/// larger neighbour.
+fn word_overlap(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let smaller = a.len().min(b.len());
+    if smaller == 0 {
+        return 0.;
+    }
+    a.intersection(b).count() as f64 / smaller as f64
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// A boolean filter on the custom metadata stored with the vectors, e.g.
+/// `category = "legal" AND year >= 2020` is
+///
+/// ```json
+/// { "and": [
+///     { "field": "category", "op": "eq", "value": "legal" },
+///     { "field": "year", "op": "gte", "value": 2020 }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum FilterExpr {
+    /// Matches if all of the expressions match.
+    And {
+        and: Vec<FilterExpr>,
+    },
+
+    /// Matches if any of the expressions match.
+    Or {
+        or: Vec<FilterExpr>,
     },
+
+    Condition(FilterCondition),
 }
 
-/// This structure must always be kept in sync with [CollectionItem] and its corresponding flattened
-/// [CollectionItemPayload::Text].
-#[derive(Debug, Serialize)]
-pub struct CollectionItemText<'a> {
-    pub document_id: Uuid,
-    pub content: &'a str,
+impl FilterExpr {
+    /// Check the expression can be evaluated, i.e. every `and` and `or` has operands and every
+    /// condition names a field.
+    pub fn validate(&self) -> Result<(), ChonkitError> {
+        match self {
+            FilterExpr::And { and: operands } | FilterExpr::Or { or: operands } => {
+                if operands.is_empty() {
+                    return err!(InvalidParameter, "filter 'and' and 'or' must not be empty");
+                }
+                operands.iter().try_for_each(FilterExpr::validate)
+            }
+            FilterExpr::Condition(condition) => {
+                if condition.field.split('.').any(str::is_empty) {
+                    return err!(
+                        InvalidParameter,
+                        "invalid filter field '{}'",
+                        condition.field
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
-/// This structure must always be kept in sync with [CollectionItem] and its corresponding flattened
-/// [CollectionItemPayload::Image].
-#[derive(Debug, Serialize)]
-pub struct CollectionItemImage<'a> {
-    pub document_id: Option<Uuid>,
-    pub image_id: Uuid,
-    pub image_b64: &'a str,
-    pub image_path: &'a str,
-    pub image_description: Option<&'a str>,
+/// Compares a metadata field with a value.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FilterCondition {
+    /// The metadata field, nested fields are separated by dots, e.g. `author.name`.
+    pub field: String,
+
+    pub op: FilterOp,
+
+    pub value: FilterValue,
 }
 
-/// Contains the collection item payload and the distance to the query vector.
-#[derive(Debug, Serialize, utoipa::ToSchema)]
-pub struct CollectionSearchItem {
-    pub distance: Option<f64>,
-    pub item: CollectionItem,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
 }
 
-impl CollectionSearchItem {
-    pub fn new(item: CollectionItem, distance: Option<f64>) -> Self {
-        Self { distance, item }
+impl FilterOp {
+    /// Whether the operation orders values rather than comparing them for equality.
+    pub fn is_range(&self) -> bool {
+        matches!(self, Self::Gt | Self::Gte | Self::Lt | Self::Lte)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl FilterValue {
+    /// The value as a number, if it is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            FilterValue::Integer(value) => Some(*value as f64),
+            FilterValue::Float(value) => Some(*value),
+            FilterValue::Bool(_) | FilterValue::String(_) => None,
+        }
     }
 }
 
@@ -366,6 +1686,15 @@ impl CollectionSearchItem {
 #[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
 pub struct CollectionItem {
     pub document_id: Option<Uuid>,
+
+    /// Custom document attributes stored with the vector, if any.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_metadata",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub metadata: Option<serde_json::Value>,
+
     #[serde(flatten)]
     pub payload: CollectionItemPayload,
 }
@@ -375,6 +1704,11 @@ impl CollectionItem {
         &[
             DOCUMENT_ID_PROPERTY,
             CONTENT_PROPERTY,
+            CHUNK_INDEX_PROPERTY,
+            SYNTHETIC_PROPERTY,
+            PARENT_INDEX_PROPERTY,
+            PARENT_CONTENT_PROPERTY,
+            METADATA_PROPERTY,
             // IMAGE_B64_PROPERTY,
             IMAGE_ID_PROPERTY,
             IMAGE_PATH_PROPERTY,
@@ -383,11 +1717,58 @@ impl CollectionItem {
     }
 }
 
+/// Vector databases without schemaless nested objects store the metadata as a JSON string.
+pub(crate) fn deserialize_metadata<'de, D>(
+    deserializer: D,
+) -> Result<Option<serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(serde_json::Value::String(metadata)) => Ok(Some(
+            serde_json::from_str(&metadata).unwrap_or(serde_json::Value::String(metadata)),
+        )),
+        metadata => Ok(metadata),
+    }
+}
+
+/// Vector databases return `null` for flags missing from the payload.
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<bool>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 #[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
 #[serde(untagged)]
 pub enum CollectionItemPayload {
     Text {
         content: String,
+
+        /// Not present on synthetic chunks and vectors inserted before chunk indices
+        /// were stored.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chunk_index: Option<usize>,
+
+        /// Whether the chunk is not part of the document's content, e.g. it holds the
+        /// document's title and metadata.
+        #[serde(
+            default,
+            deserialize_with = "deserialize_flag",
+            skip_serializing_if = "std::ops::Not::not"
+        )]
+        synthetic: bool,
+
+        /// The position of the parent window the chunk belongs to. Only present on chunks
+        /// obtained with parent-child chunking.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parent_index: Option<usize>,
+
+        /// The content of the parent window. Never serialized, searches return it in place
+        /// of the content when requested.
+        #[serde(default, skip_serializing)]
+        parent_content: Option<String>,
     },
     Image {
         image_id: Uuid,
@@ -413,8 +1794,751 @@ impl CollectionItemPayload {
 
     pub fn as_content(&self) -> String {
         match self {
-            CollectionItemPayload::Text { content } => content.clone(),
+            CollectionItemPayload::Text { content, .. } => content.clone(),
+            _ => panic!("not text"),
+        }
+    }
+
+    pub fn chunk_index(&self) -> Option<usize> {
+        match self {
+            CollectionItemPayload::Text { chunk_index, .. } => *chunk_index,
+            _ => panic!("not text"),
+        }
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        match self {
+            CollectionItemPayload::Text { synthetic, .. } => *synthetic,
             _ => panic!("not text"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        adapt_vector_dimension, deduplicate_search_items, highlight_search_items, near_duplicates,
+        normalize_vector, query_all_with_timeout, query_with_timeout, replace_with_parents,
+        sort_search_items, validate_vector, validate_vectors, BatchedVectorDb, CollectionItem,
+        CollectionItemInsert, CollectionItemInsertPayload, CollectionItemPayload,
+        CollectionSearchItem, ConcurrentVectorDb, CreateVectorCollection, DistanceMetric,
+        FilterExpr, VectorCollection, VectorDb, VectorPage, VectorQuery,
+    };
+    use crate::{
+        core::provider::Identity,
+        err,
+        error::{ChonkitErr, ChonkitError},
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn near_duplicates_skip_vectors_similar_to_kept_ones() {
+        let vectors = vec![
+            vec![1., 0., 0.],
+            vec![0.99, 0.05, 0.],
+            vec![0., 1., 0.],
+            vec![2., 0., 0.],
+            vec![0.7, 0.7, 0.],
+        ];
+
+        assert_eq!(HashSet::from([1, 3]), near_duplicates(&vectors, 0.95));
+        assert_eq!(HashSet::from([3]), near_duplicates(&vectors, 1.));
+
+        // Chunks keep their indices when near duplicates are left out
+        let content = ["a", "b", "c", "d", "e"];
+        let insert = CollectionItemInsert::new_text(
+            Uuid::new_v4(),
+            "collection",
+            &content,
+            vectors.clone(),
+            None,
+        )
+        .without_chunks(&near_duplicates(&vectors, 0.95));
+
+        let CollectionItemInsertPayload::Text { items, vectors } = insert.payload else {
+            panic!("not text");
+        };
+
+        let indices: Vec<_> = items.iter().filter_map(|item| item.chunk_index).collect();
+        assert_eq!(vec![0, 2, 4], indices);
+        assert_eq!(3, vectors.len());
+    }
+
+    #[test]
+    fn cosine_distance_scores_identical_vectors_best() {
+        let cosine_distance = |a: &[f64], b: &[f64]| {
+            let dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+            let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+            1. - dot / (norm(a) * norm(b))
+        };
+
+        let vector = [0.3, -0.5, 0.8];
+        let opposite = vector.map(|x| -x);
+        let orthogonal = [0.5, 0.3, 0.];
+
+        let score = |other: &[f64]| DistanceMetric::Cosine.score(cosine_distance(&vector, other));
+
+        assert!((score(&vector) - 1.).abs() < 1e-9);
+        assert!(score(&opposite).abs() < 1e-9);
+        assert!((score(&orthogonal) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scores_stay_within_bounds() {
+        for metric in [
+            DistanceMetric::Cosine,
+            DistanceMetric::Dot,
+            DistanceMetric::Euclid,
+        ] {
+            for distance in [-1e6, -2., 0., 0.5, 2., 1e6] {
+                let score = metric.score(distance);
+                assert!((0. ..=1.).contains(&score), "{metric:?}: {score}");
+            }
+
+            // Closer is always better
+            assert!(metric.score(0.1) > metric.score(0.2));
+        }
+    }
+
+    #[test]
+    fn normalizes_vector() {
+        let mut vector = vec![3.0, 4.0];
+
+        normalize_vector(&mut vector);
+
+        assert_eq!(vec![0.6, 0.8], vector);
+
+        let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+        assert!((norm - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn normalize_leaves_zero_vector_untouched() {
+        let mut vector = vec![0.0; 4];
+        normalize_vector(&mut vector);
+        assert_eq!(vec![0.0; 4], vector);
+    }
+
+    #[test]
+    fn rejects_nan_vector() {
+        let err = validate_vector(&[0.1, f64::NAN, 0.3]).unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidVector(_)));
+
+        let err = validate_vectors(&[vec![0.1, 0.2], vec![f64::INFINITY, 0.2]], 2).unwrap_err();
+        let ChonkitErr::InvalidVector(msg) = err.error else {
+            panic!("expected invalid vector error, got {err:?}");
+        };
+        assert!(msg.contains("index 1"));
+    }
+
+    #[test]
+    fn rejects_vector_of_wrong_size() {
+        let err = validate_vectors(&[vec![0.1, 0.2, 0.3]], 2).unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::InvalidVector(_)));
+        assert!(validate_vectors(&[vec![0.1, 0.2]], 2).is_ok());
+    }
+
+    #[tokio::test]
+    async fn concurrent_vector_db_respects_request_limit() {
+        let inner = Arc::new(SlowVectorDb::default());
+        let vector_db = Arc::new(ConcurrentVectorDb::new(inner.clone(), 3));
+
+        let inserts = (0..24).map(|_| {
+            let vector_db = vector_db.clone();
+            tokio::spawn(async move {
+                vector_db
+                    .insert_embeddings(CollectionItemInsert::new_text(
+                        Uuid::new_v4(),
+                        "collection",
+                        &["ragu"],
+                        vec![vec![0.1, 0.2]],
+                        None,
+                    ))
+                    .await
+            })
+        });
+
+        for insert in futures_util::future::join_all(inserts).await {
+            insert.unwrap().unwrap();
+        }
+
+        assert_eq!(24, inner.inserted.load(Ordering::SeqCst));
+        assert_eq!(3, inner.peak.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn query_times_out() {
+        let vector_db = SlowVectorDb::default();
+
+        let err = query_with_timeout(
+            &vector_db,
+            VectorQuery::new(vec![0.1, 0.2], "collection", 5),
+            Duration::from_millis(5),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.error, ChonkitErr::Timeout(_)));
+
+        let items = query_with_timeout(
+            &vector_db,
+            VectorQuery::new(vec![0.1, 0.2], "collection", 5),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn query_all_keeps_results_of_queries_in_time() {
+        let vector_db = SlowVectorDb::default();
+
+        let results = query_all_with_timeout(
+            [
+                (
+                    &vector_db as &(dyn VectorDb + Send + Sync),
+                    VectorQuery::new(vec![0.1, 0.2], "collection", 5),
+                ),
+                (&vector_db, VectorQuery::new(vec![0.1, 0.2], "fast", 5)),
+            ],
+            Duration::from_millis(5),
+        )
+        .await
+        .unwrap();
+
+        assert!(results[0].is_none());
+        assert!(results[1].as_ref().is_some_and(Vec::is_empty));
+    }
+
+    /// Sleeps on every insert and query, except for queries on the `fast` collection, and records
+    /// the highest amount of concurrent inserts.
+    #[derive(Default)]
+    struct SlowVectorDb {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+        inserted: AtomicUsize,
+    }
+
+    impl Identity for SlowVectorDb {
+        fn id(&self) -> &'static str {
+            "slow"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl VectorDb for SlowVectorDb {
+        async fn list_vector_collections(&self) -> Vec<Result<VectorCollection, ChonkitError>> {
+            vec![]
+        }
+
+        async fn create_vector_collection(
+            &self,
+            _data: CreateVectorCollection<'_>,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn update_collection_groups(
+            &self,
+            _collection: &str,
+            _groups: Option<Vec<String>>,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn get_collection(&self, _name: &str) -> Result<VectorCollection, ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_vector_collection(&self, _name: &str) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn query(
+            &self,
+            _search: Vec<f64>,
+            collection: &str,
+            _limit: u32,
+            _max_distance: Option<f64>,
+            _documents: Option<&[Uuid]>,
+            _filter: Option<&FilterExpr>,
+        ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+            if collection != "fast" {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Ok(vec![])
+        }
+
+        async fn insert_embeddings(
+            &self,
+            _insert: CollectionItemInsert<'_>,
+        ) -> Result<(), ChonkitError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+
+            tokio::time::sleep(Duration::from_millis(20)).await;
+
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            self.inserted.fetch_add(1, Ordering::SeqCst);
+
+            Ok(())
+        }
+
+        async fn delete_text_embeddings(
+            &self,
+            _collection: &str,
+            _document_id: Uuid,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_image_embeddings(
+            &self,
+            _collection: &str,
+            _image_id: Uuid,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_text_embeddings_many(
+            &self,
+            _collection: &str,
+            _document_ids: &[Uuid],
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_image_embeddings_many(
+            &self,
+            _collection: &str,
+            _image_ids: &[Uuid],
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn count_vectors(
+            &self,
+            _collection: &str,
+            _document_id: Uuid,
+        ) -> Result<usize, ChonkitError> {
+            unsupported()
+        }
+
+        async fn list_vectors(
+            &self,
+            _collection: &str,
+            _cursor: Option<String>,
+            _limit: u32,
+        ) -> Result<VectorPage, ChonkitError> {
+            unsupported()
+        }
+    }
+
+    #[tokio::test]
+    async fn batched_vector_db_splits_inserts() {
+        let inner = Arc::new(CountingVectorDb::default());
+        let vector_db = BatchedVectorDb::new(inner.clone(), 100);
+
+        let document_id = Uuid::new_v4();
+        let content = vec!["ragu"; 500];
+
+        vector_db
+            .insert_embeddings(CollectionItemInsert::new_text(
+                document_id,
+                "collection",
+                &content,
+                vec![vec![0.1, 0.2]; 500],
+                None,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(vec![100; 5], *inner.batches.lock().unwrap());
+        assert_eq!(
+            500,
+            vector_db
+                .count_vectors("collection", document_id)
+                .await
+                .unwrap()
+        );
+
+        // Inserts within the limit are passed through as they are
+        vector_db
+            .insert_embeddings(CollectionItemInsert::new_text(
+                document_id,
+                "collection",
+                &content[..42],
+                vec![vec![0.1, 0.2]; 42],
+                None,
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(Some(&42), inner.batches.lock().unwrap().last());
+    }
+
+    #[tokio::test]
+    async fn batched_vector_db_removes_inserted_batches_on_failure() {
+        let inner = Arc::new(CountingVectorDb {
+            fail_after: Some(2),
+            ..Default::default()
+        });
+        let vector_db = BatchedVectorDb::new(inner.clone(), 100);
+
+        let document_id = Uuid::new_v4();
+        let content = vec!["ragu"; 500];
+
+        let result = vector_db
+            .insert_embeddings(CollectionItemInsert::new_text(
+                document_id,
+                "collection",
+                &content,
+                vec![vec![0.1, 0.2]; 500],
+                None,
+            ))
+            .await;
+
+        assert!(matches!(result.unwrap_err().error, ChonkitErr::Timeout(_)));
+        assert_eq!(vec![100; 2], *inner.batches.lock().unwrap());
+        assert_eq!(
+            0,
+            vector_db
+                .count_vectors("collection", document_id)
+                .await
+                .unwrap()
+        );
+    }
+
+    /// Keeps count of the text vectors of every document and records the size of every insert.
+    /// Fails all inserts after `fail_after` successful ones, if set.
+    #[derive(Default)]
+    struct CountingVectorDb {
+        counts: Mutex<HashMap<Uuid, usize>>,
+        batches: Mutex<Vec<usize>>,
+        fail_after: Option<usize>,
+    }
+
+    impl Identity for CountingVectorDb {
+        fn id(&self) -> &'static str {
+            "counting"
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl VectorDb for CountingVectorDb {
+        async fn list_vector_collections(&self) -> Vec<Result<VectorCollection, ChonkitError>> {
+            vec![]
+        }
+
+        async fn create_vector_collection(
+            &self,
+            _data: CreateVectorCollection<'_>,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn update_collection_groups(
+            &self,
+            _collection: &str,
+            _groups: Option<Vec<String>>,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn get_collection(&self, _name: &str) -> Result<VectorCollection, ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_vector_collection(&self, _name: &str) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn query(
+            &self,
+            _search: Vec<f64>,
+            _collection: &str,
+            _limit: u32,
+            _max_distance: Option<f64>,
+            _documents: Option<&[Uuid]>,
+            _filter: Option<&FilterExpr>,
+        ) -> Result<Vec<CollectionSearchItem>, ChonkitError> {
+            unsupported()
+        }
+
+        async fn insert_embeddings(
+            &self,
+            insert: CollectionItemInsert<'_>,
+        ) -> Result<(), ChonkitError> {
+            let CollectionItemInsertPayload::Text { items, .. } = insert.payload else {
+                return unsupported();
+            };
+
+            let mut batches = self.batches.lock().unwrap();
+            if self.fail_after.is_some_and(|max| batches.len() >= max) {
+                return err!(Timeout, "insert timed out");
+            }
+            batches.push(items.len());
+
+            for item in items {
+                *self
+                    .counts
+                    .lock()
+                    .unwrap()
+                    .entry(item.document_id)
+                    .or_default() += 1;
+            }
+
+            Ok(())
+        }
+
+        async fn delete_text_embeddings(
+            &self,
+            _collection: &str,
+            document_id: Uuid,
+        ) -> Result<(), ChonkitError> {
+            self.counts.lock().unwrap().remove(&document_id);
+            Ok(())
+        }
+
+        async fn delete_image_embeddings(
+            &self,
+            _collection: &str,
+            _image_id: Uuid,
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_text_embeddings_many(
+            &self,
+            _collection: &str,
+            _document_ids: &[Uuid],
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn delete_image_embeddings_many(
+            &self,
+            _collection: &str,
+            _image_ids: &[Uuid],
+        ) -> Result<(), ChonkitError> {
+            unsupported()
+        }
+
+        async fn count_vectors(
+            &self,
+            _collection: &str,
+            document_id: Uuid,
+        ) -> Result<usize, ChonkitError> {
+            Ok(self
+                .counts
+                .lock()
+                .unwrap()
+                .get(&document_id)
+                .copied()
+                .unwrap_or_default())
+        }
+
+        async fn list_vectors(
+            &self,
+            _collection: &str,
+            _cursor: Option<String>,
+            _limit: u32,
+        ) -> Result<VectorPage, ChonkitError> {
+            unsupported()
+        }
+    }
+
+    /// The test vector databases only support the operations under test.
+    fn unsupported<T>() -> Result<T, ChonkitError> {
+        err!(
+            OperationUnsupported,
+            "Test vector databases only support the operations under test"
+        )
+    }
+
+    fn text_item(document_id: Uuid, chunk_index: usize, content: &str) -> CollectionSearchItem {
+        CollectionSearchItem::new(
+            CollectionItem {
+                document_id: Some(document_id),
+                metadata: None,
+                payload: CollectionItemPayload::Text {
+                    content: content.to_string(),
+                    chunk_index: Some(chunk_index),
+                    synthetic: false,
+                    parent_index: None,
+                    parent_content: None,
+                },
+            },
+            Some(0.1),
+        )
+    }
+
+    fn chunk_indices(items: &[CollectionSearchItem]) -> Vec<usize> {
+        items
+            .iter()
+            .map(|item| item.item.payload.chunk_index().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_ties_by_document_and_chunk() {
+        let (a, b) = (Uuid::from_u128(1), Uuid::from_u128(2));
+
+        let mut items = vec![
+            text_item(b, 0, "Gura cooks."),
+            text_item(a, 1, "Raguru bakes."),
+            text_item(b, 1, "Gura bakes."),
+            text_item(a, 0, "Raguru cooks."),
+        ];
+        items[2].distance = Some(0.05);
+
+        sort_search_items(&mut items);
+
+        let order: Vec<_> = items
+            .iter()
+            .map(|item| {
+                (
+                    item.item.document_id.unwrap(),
+                    item.item.payload.chunk_index(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            vec![(b, Some(1)), (a, Some(0)), (a, Some(1)), (b, Some(0))],
+            order
+        );
+    }
+
+    #[test]
+    fn deduplicates_by_document() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let items = vec![
+            text_item(a, 0, "Raguru cooks."),
+            text_item(a, 1, "Raguru bakes."),
+            text_item(b, 2, "Gura cooks."),
+            text_item(a, 3, "Raguru fries."),
+            text_item(b, 4, "Gura bakes."),
+        ];
+
+        let items = deduplicate_search_items(items, Some(1), None);
+
+        assert_eq!(vec![0, 2], chunk_indices(&items));
+    }
+
+    #[test]
+    fn deduplicates_overlapping_content() {
+        let document = Uuid::new_v4();
+
+        let items = vec![
+            text_item(document, 0, "Raguru seasons his ragu with stardust"),
+            // Overlaps the end of the first chunk
+            text_item(document, 1, "ragu with stardust and a pinch of salt"),
+            text_item(document, 2, "Gura serves the ragu in a floating bowl"),
+        ];
+
+        let deduplicated = deduplicate_search_items(items, None, Some(0.4));
+        assert_eq!(vec![0, 2], chunk_indices(&deduplicated));
+
+        let items = vec![
+            text_item(document, 0, "Raguru seasons his ragu with stardust"),
+            text_item(document, 1, "ragu with stardust and a pinch of salt"),
+        ];
+
+        let deduplicated = deduplicate_search_items(items, None, Some(0.9));
+        assert_eq!(vec![0, 1], chunk_indices(&deduplicated));
+    }
+
+    #[test]
+    fn replaces_children_with_parents() {
+        let document = Uuid::new_v4();
+        let child = |chunk_index: usize, parent_index: Option<usize>| {
+            let mut item = text_item(document, chunk_index, "Raguru");
+            if let CollectionItemPayload::Text {
+                parent_index: ref mut index,
+                ref mut parent_content,
+                ..
+            } = item.item.payload
+            {
+                *index = parent_index;
+                *parent_content = parent_index.map(|i| format!("Parent {i}"));
+            }
+            item
+        };
+
+        let items = vec![
+            child(3, Some(1)),
+            child(0, Some(0)),
+            // Same parent as the first item
+            child(2, Some(1)),
+            child(5, None),
+        ];
+
+        let items = replace_with_parents(items);
+
+        assert_eq!(vec![3, 0, 5], chunk_indices(&items));
+        assert_eq!("Parent 1", items[0].item.payload.as_content());
+        assert_eq!("Parent 0", items[1].item.payload.as_content());
+        assert_eq!("Raguru", items[2].item.payload.as_content());
+    }
+
+    #[test]
+    fn highlights_query_terms() {
+        let content = "Ragu à la Raguru: RAGU, raguś & more ragu";
+        let mut items = vec![text_item(Uuid::new_v4(), 0, content)];
+
+        highlight_search_items(&mut items, "ragu Raguś");
+
+        let highlights = &items[0].highlights;
+        assert_eq!(vec![(0, 4), (19, 23), (25, 31), (39, 43)], *highlights);
+
+        let matched: Vec<_> = highlights
+            .iter()
+            .map(|(start, end)| &content[*start..*end])
+            .collect();
+        assert_eq!(vec!["Ragu", "RAGU", "raguś", "ragu"], matched);
+    }
+
+    #[test]
+    fn adapts_vector_dimension() {
+        let mut vector = vec![0.1, 0.2, 0.3];
+
+        adapt_vector_dimension(&mut vector, 5);
+        assert_eq!(vec![0.1, 0.2, 0.3, 0., 0.], vector);
+
+        adapt_vector_dimension(&mut vector, 2);
+        assert_eq!(vec![0.1, 0.2], vector);
+    }
+
+    #[test]
+    fn filter_validation() {
+        // category = legal AND (year >= 2020 OR author.name != Raguru)
+        let filter: FilterExpr = serde_json::from_value(serde_json::json!({ "and": [
+            { "field": "category", "op": "eq", "value": "legal" },
+            { "or": [
+                { "field": "year", "op": "gte", "value": 2020 },
+                { "field": "author.name", "op": "ne", "value": "Raguru" },
+            ] },
+        ] }))
+        .unwrap();
+        filter.validate().unwrap();
+
+        let empty: FilterExpr = serde_json::from_value(serde_json::json!({ "or": [] })).unwrap();
+        assert!(empty.validate().is_err());
+
+        let field: FilterExpr = serde_json::from_value(
+            serde_json::json!({ "field": "author.", "op": "eq", "value": "Raguru" }),
+        )
+        .unwrap();
+        assert!(field.validate().is_err());
+    }
+}
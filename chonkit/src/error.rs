@@ -31,6 +31,24 @@ pub enum ChonkitErr {
     #[error("Invalid embedding model; {0}")]
     InvalidEmbeddingModel(String),
 
+    /// The vectors of the collection's model do not fit the vector collection,
+    /// i.e. the collection has to be recreated.
+    #[error("Dimension mismatch; model size ({model_size}) not compatible with collection size ({collection_size})")]
+    DimensionMismatch {
+        model_size: usize,
+        collection_size: usize,
+    },
+
+    /// The model of a collection cannot change once it has embeddings.
+    #[error("Collection model is immutable; {0}")]
+    ModelImmutable(String),
+
+    #[error("Invalid vector; {0}")]
+    InvalidVector(String),
+
+    #[error("Images pending; {0}")]
+    ImagesPending(String),
+
     #[error("Invalid parameter; {0}")]
     InvalidParameter(String),
 
@@ -76,6 +94,12 @@ pub enum ChonkitErr {
     #[error("Docx read; {0}")]
     DocxRead(#[from] docx_rs::ReaderError),
 
+    #[error("Zip; {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[error("Xml; {0}")]
+    Xml(#[from] quick_xml::Error),
+
     #[error("Validation; {0}")]
     Validation(#[from] ValidationErrors),
 
@@ -106,6 +130,15 @@ pub enum ChonkitErr {
     #[error("reqwest: {0}")]
     Reqwest(#[from] reqwest::Error),
 
+    #[error("llm: {0}")]
+    Llm(String),
+
+    #[error("rerank: {0}")]
+    Rerank(String),
+
+    #[error("timeout: {0}")]
+    Timeout(String),
+
     #[error("invalid header: {0}")]
     InvalidHeader(#[from] reqwest::header::InvalidHeaderValue),
 
@@ -177,6 +210,26 @@ impl ChonkitError {
             src = source.source();
         }
     }
+
+    /// Whether the operation that caused the error is likely to succeed when retried, e.g.
+    /// on timeouts and rate limits. Errors caused by the input, such as invalid models or
+    /// missing documents, are never transient.
+    pub fn is_transient(&self) -> bool {
+        match self.error {
+            ChonkitErr::Timeout(_) => true,
+            ChonkitErr::Embedding(ref e) => e.is_transient(),
+            ChonkitErr::Reqwest(ref e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().is_some_and(|status| {
+                        status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                    })
+            }
+            ChonkitErr::Sqlx(sqlx::Error::PoolTimedOut | sqlx::Error::Io(_)) => true,
+            ChonkitErr::CachePool(_) => true,
+            _ => false,
+        }
+    }
 }
 
 #[macro_export]
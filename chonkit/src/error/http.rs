@@ -9,6 +9,8 @@ impl ChonkitError {
         match self.error {
             E::ParseInt(_) => SC::BAD_REQUEST,
             E::AlreadyExists(_) => SC::CONFLICT,
+            E::DimensionMismatch { .. } => SC::CONFLICT,
+            E::ModelImmutable(_) => SC::CONFLICT,
             E::DoesNotExist(_) => SC::NOT_FOUND,
             E::Validation(_)
             | E::Regex(_)
@@ -16,9 +18,12 @@ impl ChonkitError {
             | E::InvalidFile(_)
             | E::UnsupportedFileType(_)
             | E::InvalidProvider(_)
-            | E::InvalidEmbeddingModel(_) => SC::UNPROCESSABLE_ENTITY,
+            | E::InvalidEmbeddingModel(_)
+            | E::InvalidVector(_) => SC::UNPROCESSABLE_ENTITY,
             E::ParsePdf(_)
             | E::DocxRead(_)
+            | E::Zip(_)
+            | E::Xml(_)
             | E::Sqlx(_)
             | E::Http(_)
             | E::IO(_)
@@ -32,6 +37,8 @@ impl ChonkitError {
             | E::S3(_)
             | E::CachePool(_)
             | E::Tokio(_)
+            | E::Llm(_)
+            | E::Rerank(_)
             | E::Axum(_) => SC::INTERNAL_SERVER_ERROR,
             #[cfg(feature = "qdrant")]
             E::QdrantDb(_) => SC::INTERNAL_SERVER_ERROR,
@@ -45,6 +52,8 @@ impl ChonkitError {
             E::Unauthorized => SC::UNAUTHORIZED,
             E::Reqwest(ref e) => e.status().unwrap_or(SC::INTERNAL_SERVER_ERROR),
             E::InvalidParameter(_) => SC::BAD_REQUEST,
+            E::ImagesPending(_) => SC::SERVICE_UNAVAILABLE,
+            E::Timeout(_) => SC::GATEWAY_TIMEOUT,
             #[cfg(feature = "gdrive")]
             E::GoogleApi(_) => SC::INTERNAL_SERVER_ERROR,
             E::OperationUnsupported(_) => SC::BAD_REQUEST,
@@ -72,6 +81,15 @@ where
     }
 }
 
+/// Body of [ChonkitErr::DimensionMismatch] responses.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DimensionMismatch {
+    message: String,
+    model_size: usize,
+    collection_size: usize,
+}
+
 #[derive(Debug, Serialize)]
 enum ErrorType {
     Internal,
@@ -106,6 +124,26 @@ impl IntoResponse for ChonkitError {
             CE::InvalidEmbeddingModel(e) => {
                 (status, ResponseError::new(ET::Api, e)).into_response()
             }
+            CE::DimensionMismatch {
+                model_size,
+                collection_size,
+            } => (
+                status,
+                ResponseError::new(
+                    ET::Api,
+                    DimensionMismatch {
+                        message: format!(
+                            "Model size ({model_size}) not compatible with collection size ({collection_size})"
+                        ),
+                        model_size,
+                        collection_size,
+                    },
+                ),
+            )
+                .into_response(),
+            CE::ModelImmutable(e) => (status, ResponseError::new(ET::Api, e)).into_response(),
+            CE::InvalidVector(e) => (status, ResponseError::new(ET::Api, e)).into_response(),
+            CE::ImagesPending(e) => (status, ResponseError::new(ET::Api, e)).into_response(),
             CE::Batch => (
                 status,
                 ResponseError::new(ET::Internal, "Batch embedding error".to_string()),
@@ -113,6 +151,8 @@ impl IntoResponse for ChonkitError {
                 .into_response(),
             CE::ParsePdf(e) => (status, e.to_string()).into_response(),
             CE::DocxRead(e) => (status, e.to_string()).into_response(),
+            CE::Zip(e) => (status, e.to_string()).into_response(),
+            CE::Xml(e) => (status, e.to_string()).into_response(),
             CE::AlreadyExists(e) => (status, ResponseError::new(ET::Api, e)).into_response(),
             #[cfg(feature = "weaviate")]
             CE::Weaviate(e) => (status, ResponseError::new(ET::Api, e)).into_response(),
@@ -133,6 +173,9 @@ impl IntoResponse for ChonkitError {
             CE::ParseConfig(e) => (status, e.to_string()).into_response(),
             CE::Unauthorized => (status, "Unauthorized".to_string()).into_response(),
             CE::Reqwest(e) => (status, e.to_string()).into_response(),
+            CE::Llm(e) => (status, ResponseError::new(ET::Internal, e)).into_response(),
+            CE::Rerank(e) => (status, ResponseError::new(ET::Internal, e)).into_response(),
+            CE::Timeout(e) => (status, ResponseError::new(ET::Internal, e)).into_response(),
             CE::InvalidParameter(e) => (status, e).into_response(),
             #[cfg(feature = "gdrive")]
             CE::GoogleApi(e) => (status, e.to_string()).into_response(),
@@ -11,7 +11,7 @@ pub mod core;
 pub mod error;
 
 use clap::Parser;
-use tracing::info;
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() {
@@ -24,11 +24,54 @@ async fn main() {
         .await
         .expect("error while starting TCP listener");
 
+    let batch_embedder = app.batch_embedder.clone();
+
     let router = crate::app::server::router::router(app);
 
     info!("Listening on {addr}");
 
     axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal(batch_embedder.clone()))
         .await
         .expect("error while starting server");
+
+    let timeout = args.shutdown_timeout();
+
+    if !batch_embedder.drained(timeout).await {
+        warn!(
+            "Batch embedding jobs did not finish within {}s, exiting anyway",
+            timeout.as_secs()
+        );
+    }
+
+    info!("Shut down");
+}
+
+/// Resolves on `Ctrl+C` or `SIGTERM`, stopping the batch embedder from taking new jobs.
+async fn shutdown_signal(batch_embedder: crate::app::batch::BatchEmbedderHandle) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("error while installing Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("error while installing SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutting down");
+
+    batch_embedder.shutdown();
 }
@@ -0,0 +1,256 @@
+use super::ChunkerError;
+
+/// Chunker for Markdown documents that never lets a chunk cross a heading of level
+/// `max_level` or lower, so every chunk belongs to exactly one section.
+///
+/// The input is divided into sections, one per ATX heading (`#` to `######`). A new chunk
+/// starts at every heading of level `max_level` or lower. Deeper subsections are merged into
+/// the chunk of their section for as long as the chunk does not exceed `size`. Subsections
+/// larger than `size` are never split and form a chunk of their own.
+///
+/// Every chunk is prefixed with the headings of the sections it is nested in, so that its
+/// heading path is preserved even when it starts at a subsection.
+/// Headings in fenced code blocks are ignored.
+#[derive(Debug, Clone)]
+pub struct ByHeading {
+    /// Headings of this level or lower always start a new chunk.
+    pub max_level: usize,
+
+    /// The size budget of chunks when merging subsections.
+    pub size: usize,
+}
+
+impl ByHeading {
+    /// Errors if `max_level` is not a valid heading level or `size` is 0.
+    pub fn new(max_level: usize, size: usize) -> Result<Self, ChunkerError> {
+        if !(1..=6).contains(&max_level) {
+            return Err(ChunkerError::Config(
+                "max_level must be between 1 and 6".to_string(),
+            ));
+        }
+
+        if size == 0 {
+            return Err(ChunkerError::Config(
+                "size must be greater than 0".to_string(),
+            ));
+        }
+
+        Ok(Self { max_level, size })
+    }
+
+    pub fn chunk(&self, input: &str) -> Vec<String> {
+        let mut chunks = vec![];
+        let mut current: Option<(&Section, usize)> = None;
+
+        let sections = sections(input);
+
+        for section in sections.iter() {
+            let boundary = section.level.is_some_and(|level| level <= self.max_level);
+
+            match current {
+                Some((first, ref mut end))
+                    if !boundary && section.end - first.start <= self.size =>
+                {
+                    *end = section.end;
+                }
+                _ => {
+                    if let Some((first, end)) = current {
+                        chunks.push(first.with_path(&input[first.start..end]));
+                    }
+                    current = Some((section, section.end));
+                }
+            }
+        }
+
+        if let Some((first, end)) = current {
+            chunks.push(first.with_path(&input[first.start..end]));
+        }
+
+        chunks
+    }
+}
+
+/// A heading and the content up to the next heading, or the content before the first heading.
+#[derive(Debug)]
+struct Section<'a> {
+    /// `None` for the content before the first heading.
+    level: Option<usize>,
+
+    /// The heading lines of the sections this one is nested in, outermost first.
+    path: Vec<&'a str>,
+
+    start: usize,
+    end: usize,
+}
+
+impl Section<'_> {
+    /// Prefix the chunk starting at this section with the headings it is nested in.
+    fn with_path(&self, chunk: &str) -> String {
+        let mut output = String::with_capacity(chunk.len());
+        for heading in self.path.iter() {
+            output.push_str(heading);
+            output.push('\n');
+        }
+        output.push_str(chunk);
+        output
+    }
+}
+
+/// Divide the input into sections. Whitespace before the first heading is omitted.
+fn sections(input: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![];
+
+    // Headings of the current section and the ones it is nested in, with their levels
+    let mut path: Vec<(usize, &str)> = vec![];
+
+    let mut current = Section {
+        level: None,
+        path: vec![],
+        start: 0,
+        end: 0,
+    };
+
+    let mut fenced = false;
+    let mut offset = 0;
+
+    for line in input.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        } else if let Some(level) = heading_level(line).filter(|_| !fenced) {
+            current.end = offset;
+            if current.level.is_some() || !input[..offset].trim().is_empty() {
+                sections.push(current);
+            }
+
+            path.retain(|(parent, _)| *parent < level);
+
+            current = Section {
+                level: Some(level),
+                path: path.iter().map(|(_, heading)| *heading).collect(),
+                start: offset,
+                end: offset,
+            };
+
+            path.push((level, line.trim_end()));
+        }
+
+        offset += line.len();
+    }
+
+    current.end = offset;
+    if current.level.is_some() || !input.trim().is_empty() {
+        sections.push(current);
+    }
+
+    sections
+}
+
+/// The level of the ATX heading on the line, if it is one.
+fn heading_level(line: &str) -> Option<usize> {
+    // Up to 3 spaces of indentation are allowed
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+
+    let line = &line[indent..];
+    let level = line.len() - line.trim_start_matches('#').len();
+
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+
+    match line[level..].chars().next() {
+        None | Some(' ' | '\t' | '\r' | '\n') => Some(level),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Preamble.
+
+# Guide
+
+Introduction.
+
+## Install
+
+Download it.
+
+### Linux
+
+Use the package manager.
+
+### Windows
+
+Use the installer.
+
+## Usage
+
+```sh
+# not a heading
+chonkit --help
+```
+";
+
+    #[test]
+    fn by_heading_splits_at_max_level_and_carries_path() {
+        let chunker = ByHeading::new(2, 1000).unwrap();
+
+        let chunks = chunker.chunk(INPUT);
+
+        let expected = [
+            "Preamble.\n\n",
+            "# Guide\n\nIntroduction.\n\n",
+            "# Guide\n## Install\n\nDownload it.\n\n### Linux\n\nUse the package manager.\n\n### Windows\n\nUse the installer.\n\n",
+            "# Guide\n## Usage\n\n```sh\n# not a heading\nchonkit --help\n```\n",
+        ];
+
+        assert_eq!(expected.len(), chunks.len());
+
+        for (chunk, test) in chunks.into_iter().zip(expected) {
+            assert_eq!(test, chunk);
+        }
+    }
+
+    #[test]
+    fn by_heading_does_not_merge_subsections_over_size() {
+        let chunker = ByHeading::new(2, 40).unwrap();
+
+        let chunks = chunker.chunk(INPUT);
+
+        let expected = [
+            "Preamble.\n\n",
+            "# Guide\n\nIntroduction.\n\n",
+            "# Guide\n## Install\n\nDownload it.\n\n",
+            "# Guide\n## Install\n### Linux\n\nUse the package manager.\n\n",
+            "# Guide\n## Install\n### Windows\n\nUse the installer.\n\n",
+            "# Guide\n## Usage\n\n```sh\n# not a heading\nchonkit --help\n```\n",
+        ];
+
+        assert_eq!(expected.len(), chunks.len());
+
+        for (chunk, test) in chunks.into_iter().zip(expected) {
+            assert_eq!(test, chunk);
+        }
+    }
+
+    #[test]
+    fn by_heading_invalid_config() {
+        assert!(ByHeading::new(0, 1000).is_err());
+        assert!(ByHeading::new(7, 1000).is_err());
+        assert!(ByHeading::new(2, 0).is_err());
+    }
+
+    #[test]
+    fn by_heading_empty() {
+        let chunker = ByHeading::new(2, 1000).unwrap();
+        assert!(chunker.chunk("").is_empty());
+        assert!(chunker.chunk("\n\n").is_empty());
+    }
+}
@@ -30,6 +30,9 @@ pub(super) struct Cursor<'a> {
     /// Delimiter to split by.
     pub delim: char,
     pub chars: Peekable<Chars<'a>>,
+
+    /// Whether delimiters must be followed by whitespace to count as delimiters.
+    pub requires_whitespace: bool,
 }
 
 impl<'a> Cursor<'a> {
@@ -40,9 +43,15 @@ impl<'a> Cursor<'a> {
             byte_count: byte_count(input),
             byte_offset: 0,
             delim,
+            requires_whitespace: true,
         }
     }
 
+    pub fn with_boundary_requires_whitespace(mut self, requires_whitespace: bool) -> Self {
+        self.requires_whitespace = requires_whitespace;
+        self
+    }
+
     pub fn finished(&self) -> bool {
         self.byte_offset == self.byte_count - self.delim.len_utf8()
     }
@@ -87,7 +96,7 @@ impl<'a> Cursor<'a> {
                     self.byte_offset += ch.len_utf8();
                     stop = false;
                     // We don't count delimiters behind non-whitespace as actual delimiters
-                } else if !ch.is_whitespace() {
+                } else if self.requires_whitespace && !ch.is_whitespace() {
                     self.chars.next();
                     self.byte_offset += ch.len_utf8();
                     stop = false;
@@ -175,6 +184,9 @@ pub(super) struct CursorRev<'a> {
 
     /// The delimiter to snap to
     delim: char,
+
+    /// Whether delimiters must be followed by whitespace to count as delimiters.
+    requires_whitespace: bool,
 }
 
 impl<'a> CursorRev<'a> {
@@ -192,9 +204,15 @@ impl<'a> CursorRev<'a> {
             byte_offset,
             chars,
             delim,
+            requires_whitespace: true,
         }
     }
 
+    pub fn with_boundary_requires_whitespace(mut self, requires_whitespace: bool) -> Self {
+        self.requires_whitespace = requires_whitespace;
+        self
+    }
+
     pub fn finished(&self) -> bool {
         self.byte_offset == 0
     }
@@ -227,6 +245,16 @@ impl<'a> CursorRev<'a> {
                 continue;
             }
 
+            // We don't count delimiters behind non-whitespace as actual delimiters
+            if self.requires_whitespace
+                && self.buf[self.byte_offset + ch.len_utf8()..]
+                    .chars()
+                    .next()
+                    .is_some_and(|next| !next.is_whitespace())
+            {
+                continue;
+            }
+
             let mut stop = true;
 
             while let Some(peek) = self.chars.peek().cloned() {
@@ -0,0 +1,176 @@
+use super::{
+    cursor::{Cursor, DEFAULT_SKIP_B, DEFAULT_SKIP_F},
+    ChunkerError,
+};
+use tracing::debug;
+
+/// Chunker dividing the input into a fixed amount of roughly equally sized chunks.
+///
+/// Chunks are cut at the sentence stops closest to the even division of the input,
+/// found the same way as in the [Snapping](super::Snapping) chunker. Chunks do not overlap.
+///
+/// Inputs with fewer sentences than `count` will produce fewer chunks.
+///
+/// The default delimiter is `'.'`.
+/// The default forward skips are [DEFAULT_SKIP_F].
+/// The default backward skips are [DEFAULT_SKIP_B].
+#[derive(Debug, Clone)]
+pub struct FixedCount {
+    /// The amount of chunks to produce.
+    pub count: usize,
+
+    /// The delimiter to use to split sentences.
+    pub delimiter: char,
+
+    /// See [Snapping::skip_forward](super::Snapping::skip_forward).
+    pub skip_forward: Vec<String>,
+
+    /// See [Snapping::skip_back](super::Snapping::skip_back).
+    pub skip_back: Vec<String>,
+
+    /// See [Snapping::boundary_requires_whitespace](super::Snapping::boundary_requires_whitespace).
+    pub boundary_requires_whitespace: bool,
+}
+
+impl FixedCount {
+    /// Create a new `FixedCount` chunker using the default delimiter and skips.
+    /// Errors if `count` is 0.
+    pub fn new(count: usize) -> Result<Self, ChunkerError> {
+        if count == 0 {
+            return Err(ChunkerError::Config(
+                "count must be greater than 0".to_string(),
+            ));
+        }
+        Ok(Self {
+            count,
+            delimiter: '.',
+            skip_forward: DEFAULT_SKIP_F.iter().map(|e| e.to_string()).collect(),
+            skip_back: DEFAULT_SKIP_B.iter().map(|e| e.to_string()).collect(),
+            boundary_requires_whitespace: true,
+        })
+    }
+
+    pub fn chunk<'a>(&self, input: &'a str) -> Result<Vec<&'a str>, ChunkerError> {
+        let input = input.trim();
+
+        if input.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let boundaries = self.sentence_boundaries(input);
+
+        let mut chunks = Vec::with_capacity(self.count);
+        let mut start = 0;
+
+        for i in 1..self.count {
+            let target = input.len() * i / self.count;
+
+            // The boundaries closest to the target on either side
+            let next = boundaries.partition_point(|offset| *offset < target);
+            let closest = [next.checked_sub(1), Some(next)]
+                .into_iter()
+                .flatten()
+                .filter_map(|i| boundaries.get(i).copied())
+                .filter(|offset| *offset > start)
+                .min_by_key(|offset| offset.abs_diff(target));
+
+            let Some(end) = closest else {
+                continue;
+            };
+
+            chunks.push(&input[start..end]);
+            start = end;
+        }
+
+        chunks.push(&input[start..]);
+
+        debug!("Chunked {} chunks, requested {}", chunks.len(), self.count);
+
+        Ok(chunks)
+    }
+
+    /// Byte offsets following every sentence stop in the input, excluding the end of it.
+    fn sentence_boundaries(&self, input: &str) -> Vec<usize> {
+        let mut cursor = Cursor::new(input, self.delimiter)
+            .with_boundary_requires_whitespace(self.boundary_requires_whitespace);
+
+        let mut boundaries = vec![];
+
+        while !cursor.finished() {
+            let offset = cursor.byte_offset;
+
+            cursor.advance();
+            while cursor.advance_if_peek(&self.skip_forward, &self.skip_back) {
+                cursor.advance();
+            }
+
+            if cursor.byte_offset == offset || cursor.finished() {
+                break;
+            }
+
+            // Only cut where text remains, otherwise the last chunk would be empty
+            if !input[cursor.byte_offset..].trim().is_empty() {
+                boundaries.push(cursor.byte_offset);
+            }
+        }
+
+        boundaries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_count_works() {
+        let input =
+            "I have a sentence. It is not very long. Here is another. Long schlong ding dong.";
+        let chunker = FixedCount::new(2).unwrap();
+
+        let chunks = chunker.chunk(input).unwrap();
+
+        assert_eq!(
+            vec![
+                "I have a sentence. It is not very long.",
+                " Here is another. Long schlong ding dong."
+            ],
+            chunks
+        );
+    }
+
+    #[test]
+    fn fixed_count_produces_exact_count() {
+        let input = "Raguru cooks ragu in his floating kitchen. ".repeat(1000);
+        let chunker = FixedCount::new(7).unwrap();
+
+        let chunks = chunker.chunk(&input).unwrap();
+
+        assert_eq!(7, chunks.len());
+        assert_eq!(input.trim(), chunks.concat());
+        for chunk in chunks {
+            assert!(chunk.trim_end().ends_with('.'));
+        }
+    }
+
+    #[test]
+    fn fixed_count_short_input() {
+        let input = "Only one sentence. And another.";
+        let chunker = FixedCount::new(5).unwrap();
+
+        let chunks = chunker.chunk(input).unwrap();
+
+        assert_eq!(vec!["Only one sentence.", " And another."], chunks);
+    }
+
+    #[test]
+    fn fixed_count_empty() {
+        let chunker = FixedCount::new(3).unwrap();
+        assert!(chunker.chunk("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn fixed_count_rejects_zero() {
+        assert!(FixedCount::new(0).is_err());
+    }
+}
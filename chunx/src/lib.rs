@@ -1,11 +1,15 @@
 use std::str::Utf8Error;
 
+pub mod by_heading;
 mod cursor;
+pub mod fixed_count;
 pub mod semantic;
 pub mod sliding;
 pub mod snapping;
 pub mod splitline;
 
+pub use by_heading::ByHeading;
+pub use fixed_count::FixedCount;
 pub use semantic::Semantic;
 pub use sliding::Sliding;
 pub use snapping::Snapping;
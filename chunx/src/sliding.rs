@@ -1,18 +1,43 @@
-use super::ChunkerError;
+use super::{
+    cursor::{Cursor, DEFAULT_SKIP_B, DEFAULT_SKIP_F},
+    ChunkerError,
+};
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 const SLIDING_WINDOW_DEFAULT_SIZE: usize = 1000;
 const SLIDING_WINDOW_DEFAULT_OVERLAP: usize = 200;
 
+/// Determines which boundaries the windows of the [Sliding] chunker are snapped to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapTarget {
+    /// Windows are cut at exact offsets, snapped only to char boundaries.
+    #[default]
+    Char,
+
+    /// Windows are extended to the boundaries of the words they cut.
+    Word,
+
+    /// Windows are extended to the boundaries of the sentences they cut. Sentences are
+    /// found like in the [Snapping][super::Snapping] chunker, with its default delimiter
+    /// and skips.
+    Sentence,
+}
+
 /// The most basic of chunkers.
 ///
 /// `size` determines the base amount for every chunk and
 /// `overlap` determines how much back and front characters
 /// to extend the base with.
+///
+/// `snap_to` determines the boundaries the windows are extended to. The default
+/// [SnapTarget::Char] does not extend them.
 #[derive(Debug, Clone)]
 pub struct Sliding {
     pub size: usize,
     pub overlap: usize,
+    pub snap_to: SnapTarget,
 }
 
 impl Sliding {
@@ -24,11 +49,25 @@ impl Sliding {
                 "overlap must be less than size".to_string(),
             ));
         }
-        Ok(Self { size, overlap })
+        Ok(Self {
+            size,
+            overlap,
+            snap_to: SnapTarget::default(),
+        })
+    }
+
+    /// Set the boundaries the windows are extended to. See [SnapTarget].
+    pub fn with_snap_to(mut self, snap_to: SnapTarget) -> Self {
+        self.snap_to = snap_to;
+        self
     }
 
     pub fn chunk<'a>(&self, input: &'a str) -> Result<Vec<&'a str>, ChunkerError> {
-        let Sliding { size, overlap } = self;
+        let Sliding {
+            size,
+            overlap,
+            snap_to,
+        } = self;
 
         let input = input.trim();
 
@@ -43,6 +82,11 @@ impl Sliding {
             return Ok(vec![input]);
         }
 
+        let sentence_ends = match snap_to {
+            SnapTarget::Sentence => sentence_ends(input),
+            _ => vec![],
+        };
+
         let mut chunks = vec![];
 
         let mut start = 0;
@@ -62,14 +106,33 @@ impl Sliding {
                 chunk_end += 1;
             }
 
-            if chunk_end > input_size {
-                let chunk = &input[chunk_start..input_size];
-                chunks.push(chunk);
-                break;
+            chunk_start = snap_start(input, chunk_start, *snap_to, &sentence_ends);
+
+            let last = chunk_end > input_size;
+
+            let snapped_end = if last {
+                input_size
+            } else {
+                snap_end(input, chunk_end, *snap_to, &sentence_ends)
+            };
+
+            let chunk = &input[chunk_start..snapped_end];
+            match snap_to {
+                SnapTarget::Char => chunks.push(chunk),
+                _ => {
+                    // Windows snapped to whitespace should not carry it, and short windows
+                    // can snap to the same boundaries as the previous one
+                    let chunk = chunk.trim();
+                    if !chunk.is_empty() && chunks.last() != Some(&chunk) {
+                        chunks.push(chunk);
+                    }
+                }
             }
 
-            let chunk = &input[chunk_start..chunk_end];
-            chunks.push(chunk);
+            // Any further windows would be contained in this one
+            if last || (snapped_end == input_size && snapped_end != chunk_end) {
+                break;
+            }
 
             start = end;
             end += size;
@@ -85,6 +148,87 @@ impl Sliding {
     }
 }
 
+/// Move the start of a window back to the start of the word or sentence it cuts.
+/// `start` must be on a char boundary.
+fn snap_start(
+    input: &str,
+    mut start: usize,
+    snap_to: SnapTarget,
+    sentence_ends: &[usize],
+) -> usize {
+    match snap_to {
+        SnapTarget::Char => start,
+        SnapTarget::Word => {
+            while cuts_word(input, start) {
+                start -= input[..start].chars().next_back().map_or(0, char::len_utf8);
+            }
+            start
+        }
+        SnapTarget::Sentence => {
+            let ends_before = sentence_ends.partition_point(|end| *end <= start);
+            let start = ends_before.checked_sub(1).map_or(0, |i| sentence_ends[i]);
+            let sentence = &input[start..];
+            start + sentence.len() - sentence.trim_start().len()
+        }
+    }
+}
+
+/// Move the end of a window forward to the end of the word or sentence it cuts.
+/// `end` must be on a char boundary.
+fn snap_end(input: &str, mut end: usize, snap_to: SnapTarget, sentence_ends: &[usize]) -> usize {
+    match snap_to {
+        SnapTarget::Char => end,
+        SnapTarget::Word => {
+            while cuts_word(input, end) {
+                end += input[end..].chars().next().map_or(0, char::len_utf8);
+            }
+            end
+        }
+        SnapTarget::Sentence => {
+            let ends_before = sentence_ends.partition_point(|e| *e < end);
+            sentence_ends
+                .get(ends_before)
+                .copied()
+                .unwrap_or(input.len())
+        }
+    }
+}
+
+/// Returns `true` if `offset` is between two non-whitespace chars.
+fn cuts_word(input: &str, offset: usize) -> bool {
+    let before = input[..offset].chars().next_back();
+    let after = input[offset..].chars().next();
+    before.is_some_and(|ch| !ch.is_whitespace()) && after.is_some_and(|ch| !ch.is_whitespace())
+}
+
+/// Byte offsets directly following the sentence stops of the input, found with the
+/// default delimiter and skips of the [Snapping][super::Snapping] chunker.
+fn sentence_ends(input: &str) -> Vec<usize> {
+    let skip_forward: Vec<String> = DEFAULT_SKIP_F.iter().map(|s| s.to_string()).collect();
+    let skip_back: Vec<String> = DEFAULT_SKIP_B.iter().map(|s| s.to_string()).collect();
+
+    let mut cursor = Cursor::new(input, '.');
+    let mut ends = vec![];
+
+    while !cursor.finished() {
+        let offset = cursor.byte_offset;
+
+        cursor.advance();
+        while cursor.advance_if_peek(&skip_forward, &skip_back) {
+            cursor.advance();
+        }
+
+        // The cursor does not advance past multi-byte chars at the end of the input
+        if cursor.byte_offset == offset {
+            break;
+        }
+
+        ends.push(cursor.get_slice().len());
+    }
+
+    ends
+}
+
 impl Default for Sliding {
     fn default() -> Self {
         Self::new(SLIDING_WINDOW_DEFAULT_SIZE, SLIDING_WINDOW_DEFAULT_OVERLAP)
@@ -126,6 +270,54 @@ mod tests {
         assert_eq!(input, chunks[0]);
     }
 
+    #[test]
+    fn sliding_window_snaps_to_words() {
+        let input = "Sticks and stones may break my bones, but words will never leverage agile frameworks to provide a robust synopsis for high level overviews.";
+        let window = Sliding::new(30, 20).unwrap().with_snap_to(SnapTarget::Word);
+        let chunks = window.chunk(input).unwrap();
+
+        let words: Vec<&str> = input.split_whitespace().collect();
+
+        assert!(chunks.len() > 1);
+        for chunk in chunks.iter() {
+            assert_eq!(chunk.trim(), *chunk);
+            for word in chunk.split_whitespace() {
+                assert!(words.contains(&word), "split word '{word}' in '{chunk}'");
+            }
+        }
+        assert!(input.starts_with(chunks[0]));
+        assert!(input.ends_with(chunks[chunks.len() - 1]));
+    }
+
+    #[test]
+    fn sliding_window_snaps_to_sentences() {
+        let input = "Raguru stirs the ragu. The twin moons rise over Gura. Labamba sings of the old days, e.g. the harvest. The ragu is ready.";
+        let window = Sliding::new(30, 10)
+            .unwrap()
+            .with_snap_to(SnapTarget::Sentence);
+        let chunks = window.chunk(input).unwrap();
+
+        let sentences = [
+            "Raguru stirs the ragu.",
+            "The twin moons rise over Gura.",
+            "Labamba sings of the old days, e.g. the harvest.",
+            "The ragu is ready.",
+        ];
+
+        assert!(chunks.len() > 1);
+        for chunk in chunks.iter() {
+            assert!(chunk.ends_with('.'), "'{chunk}' does not end a sentence");
+            assert!(
+                sentences.iter().any(|s| chunk.starts_with(s)),
+                "'{chunk}' does not start a sentence"
+            );
+        }
+        assert_eq!(
+            "Raguru stirs the ragu. The twin moons rise over Gura.",
+            chunks[0]
+        );
+    }
+
     #[test]
     fn sliding_window_unicode() {
         let input = "Dobrodošli u budućnost, počeo je kraj\nVrata pakla se otvaraju zapalila su raj\nNe, ovo nije bajka, ovo nije san,\nOvo je rase čovječanske sudnji dan";
@@ -2,10 +2,78 @@ use super::{
     cursor::{byte_count, Cursor, CursorRev, DEFAULT_SKIP_B, DEFAULT_SKIP_F},
     ChunkerError,
 };
+use serde::{Deserialize, Serialize};
 
 const DEFAULT_SIZE: usize = 2000;
 const DEFAULT_OVERLAP: usize = 5;
 
+/// Abbreviations the [SentenceSegmenter::Unicode] segmenter never ends sentences on,
+/// in addition to the backward skips.
+#[cfg(feature = "segmenter")]
+const SEGMENTER_ABBREVIATIONS: &[&str] = &["Dr", "Mr", "Mrs", "Ms", "Prof", "Sr", "Jr", "St", "vs"];
+
+/// Determines how the [Snapping] chunker finds sentence boundaries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SentenceSegmenter {
+    /// Sentences end on the delimiter, unless matched by the skips.
+    #[default]
+    Heuristic,
+
+    /// Sentences end on the boundaries defined by the Unicode text segmentation standard
+    /// (UAX #29), unless preceded by a backward skip or a common abbreviation.
+    /// Handles decimals, quotes and sentence terminators other than the delimiter.
+    ///
+    /// Requires the `segmenter` feature.
+    Unicode,
+}
+
+/// Languages with built-in skips for their common abbreviations, added to the skips of the
+/// [Snapping] chunker with [Snapping::with_language].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnappingLanguage {
+    /// The default skips, [DEFAULT_SKIP_F] and [DEFAULT_SKIP_B].
+    #[default]
+    English,
+    German,
+    French,
+    Spanish,
+    Italian,
+}
+
+impl SnappingLanguage {
+    /// Patterns in front of delimiters not treated as sentence stops, mostly the trailing
+    /// parts of abbreviations with multiple delimiters.
+    pub fn skip_forward(&self) -> &'static [&'static str] {
+        match self {
+            Self::English => DEFAULT_SKIP_F,
+            Self::German => &["B.", "h.", "a.", "T."],
+            Self::French => &["ex.", "-à-d."],
+            Self::Spanish => &["ej."],
+            Self::Italian => &[],
+        }
+    }
+
+    /// Patterns behind delimiters not treated as sentence stops.
+    pub fn skip_back(&self) -> &'static [&'static str] {
+        match self {
+            Self::English => DEFAULT_SKIP_B,
+            Self::German => &[
+                "z.B", "d.h", "u.a", "z.T", "usw", "bzw", "vgl", "ggf", "evtl", "inkl", "sog",
+                "Nr", "Dr", "Prof",
+            ],
+            Self::French => &["c.-à-d", "p.ex", "etc", "cf", "env", "Mme", "Mlle", "Dr"],
+            Self::Spanish => &[
+                "p.ej", "etc", "Sr", "Sra", "Srta", "Dr", "Dra", "Ud", "Uds", "aprox",
+            ],
+            Self::Italian => &[
+                "ad es", "ecc", "Sig", "Sig.ra", "Sigg", "Dott", "Prof", "pag",
+            ],
+        }
+    }
+}
+
 /// Heuristic chunker for texts intended for humans, e.g. documentation, books, blogs, etc.
 ///
 /// Essentially a sliding window that is aware of sentence stops,
@@ -50,6 +118,19 @@ pub struct Snapping {
     ///
     /// Useful for common abbreviations and urls.
     pub skip_back: Vec<String>,
+
+    /// If `true`, delimiters are only treated as sentence stops when followed by whitespace,
+    /// e.g. a space or a newline. If `false`, any delimiter not matched by the skips is a
+    /// sentence stop, regardless of what follows it.
+    ///
+    /// Defaults to `true`.
+    pub boundary_requires_whitespace: bool,
+
+    /// How sentence boundaries are found. When not [SentenceSegmenter::Heuristic], the
+    /// delimiter, the forward skips and `boundary_requires_whitespace` have no effect.
+    ///
+    /// Defaults to [SentenceSegmenter::Heuristic].
+    pub segmenter: SentenceSegmenter,
 }
 
 impl Default for Snapping {
@@ -84,6 +165,8 @@ impl Snapping {
             delimiter,
             skip_forward,
             skip_back,
+            boundary_requires_whitespace: true,
+            segmenter: SentenceSegmenter::default(),
         })
     }
 
@@ -100,6 +183,34 @@ impl Snapping {
         })
     }
 
+    /// Set whether delimiters need to be followed by whitespace to be treated as sentence stops.
+    /// See [Snapping::boundary_requires_whitespace].
+    pub fn with_boundary_requires_whitespace(mut self, requires_whitespace: bool) -> Self {
+        self.boundary_requires_whitespace = requires_whitespace;
+        self
+    }
+
+    /// Set how sentence boundaries are found. See [Snapping::segmenter].
+    pub fn with_segmenter(mut self, segmenter: SentenceSegmenter) -> Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Add the built-in skips of the language to the skips, ignoring ones already present.
+    pub fn with_language(mut self, language: SnappingLanguage) -> Self {
+        for skip in language.skip_forward() {
+            if !self.skip_forward.iter().any(|s| s == skip) {
+                self.skip_forward.push(skip.to_string());
+            }
+        }
+        for skip in language.skip_back() {
+            if !self.skip_back.iter().any(|s| s == skip) {
+                self.skip_back.push(skip.to_string());
+            }
+        }
+        self
+    }
+
     /// Extend the forward and backward skips.
     pub fn extend_skips(&mut self, skip_forward: Vec<String>, skip_back: Vec<String>) {
         self.skip_forward.extend(skip_forward);
@@ -111,12 +222,18 @@ impl Snapping {
             return Ok(vec![]);
         }
 
+        if self.segmenter == SentenceSegmenter::Unicode {
+            return self.chunk_segmented(input);
+        }
+
         let Self {
             size,
             overlap,
             delimiter,
             skip_forward,
             skip_back,
+            boundary_requires_whitespace,
+            segmenter: _,
         } = self;
 
         let total_bytes = byte_count(input);
@@ -139,7 +256,14 @@ impl Snapping {
                 chunk.push(char);
                 chunk_byte_size += char.len_utf8();
                 let prev = &input[..current_offset - chunk_byte_size];
-                let prev = previous_chunk(prev, *overlap, *delimiter, skip_forward, skip_back);
+                let prev = previous_chunk(
+                    prev,
+                    *overlap,
+                    *delimiter,
+                    skip_forward,
+                    skip_back,
+                    *boundary_requires_whitespace,
+                );
                 chunks.push(format!("{prev}{chunk}"));
                 break;
             }
@@ -170,8 +294,14 @@ impl Snapping {
                     // Special case if skip is at the end of the input
                     if current_offset == total_bytes {
                         let prev = &input[..current_offset - chunk_byte_size];
-                        let prev =
-                            previous_chunk(prev, *overlap, *delimiter, skip_forward, skip_back);
+                        let prev = previous_chunk(
+                            prev,
+                            *overlap,
+                            *delimiter,
+                            skip_forward,
+                            skip_back,
+                            *boundary_requires_whitespace,
+                        );
                         chunks.push(format!("{prev}{chunk}"));
                         break 'outer;
                     }
@@ -180,10 +310,10 @@ impl Snapping {
                 }
             }
 
-            // Skip any delimiters not followed by a space
+            // Skip any delimiters not followed by whitespace
             // so as to skip the next check
             if let Some(ch) = chars.peek() {
-                if !ch.is_whitespace() {
+                if *boundary_requires_whitespace && !ch.is_whitespace() {
                     chunk.push(char);
                     chunk_byte_size += char.len_utf8();
                     continue;
@@ -206,9 +336,22 @@ impl Snapping {
             let prev = &input[..current_offset - chunk_byte_size];
             let next = &input[current_offset..];
 
-            let prev = previous_chunk(prev, *overlap, *delimiter, skip_forward, skip_back);
-            let (next, next_offset) =
-                next_chunk(next, *overlap, *delimiter, skip_forward, skip_back);
+            let prev = previous_chunk(
+                prev,
+                *overlap,
+                *delimiter,
+                skip_forward,
+                skip_back,
+                *boundary_requires_whitespace,
+            );
+            let (next, next_offset) = next_chunk(
+                next,
+                *overlap,
+                *delimiter,
+                skip_forward,
+                skip_back,
+                *boundary_requires_whitespace,
+            );
 
             let offset = current_offset;
 
@@ -235,6 +378,100 @@ impl Snapping {
 
         Ok(chunks)
     }
+
+    #[cfg(feature = "segmenter")]
+    fn chunk_segmented(&self, input: &str) -> Result<Vec<String>, ChunkerError> {
+        let sentences = segment_sentences(input, &self.skip_back);
+        Ok(window_sentences(&sentences, self.size, self.overlap))
+    }
+
+    #[cfg(not(feature = "segmenter"))]
+    fn chunk_segmented(&self, _input: &str) -> Result<Vec<String>, ChunkerError> {
+        Err(ChunkerError::Config(
+            "the unicode sentence segmenter requires the `segmenter` feature".to_string(),
+        ))
+    }
+}
+
+/// Split the input into sentences using Unicode sentence boundaries.
+///
+/// Trailing whitespace is moved to the start of the next sentence so that the output is
+/// consistent with the heuristic. Boundaries after abbreviations are ignored.
+#[cfg(feature = "segmenter")]
+fn segment_sentences<'a>(input: &'a str, skip_back: &[String]) -> Vec<&'a str> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    // Only periods can end abbreviations
+    let is_abbreviation = |sentence: &str| {
+        let Some(sentence) = sentence.strip_suffix('.') else {
+            return false;
+        };
+
+        SEGMENTER_ABBREVIATIONS
+            .iter()
+            .copied()
+            .chain(skip_back.iter().map(String::as_str))
+            .any(|abbreviation| {
+                sentence
+                    .strip_suffix(abbreviation)
+                    .is_some_and(|rest| !rest.ends_with(char::is_alphanumeric))
+            })
+    };
+
+    let mut sentences = vec![];
+    let mut start = 0;
+    let mut offset = 0;
+
+    for segment in input.split_sentence_bounds() {
+        offset += segment.len();
+
+        let end = offset - (segment.len() - segment.trim_end().len());
+
+        if end == start || is_abbreviation(&input[start..end]) {
+            continue;
+        }
+
+        sentences.push(&input[start..end]);
+        start = end;
+    }
+
+    let rest = &input[start..];
+
+    match sentences.last_mut() {
+        Some(last) if rest.trim().is_empty() => *last = &input[start - last.len()..],
+        _ if !rest.is_empty() => sentences.push(rest),
+        _ => {}
+    }
+
+    sentences
+}
+
+/// Join the sentences into chunks of at least `size` bytes, each with `overlap` leading and
+/// trailing sentences. Sentences used as trailing overlap are not used as the base of the
+/// next chunk, same as in the heuristic.
+#[cfg(feature = "segmenter")]
+fn window_sentences(sentences: &[&str], size: usize, overlap: usize) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut start = 0;
+
+    while start < sentences.len() {
+        let mut end = start;
+        let mut chunk_byte_size = 0;
+
+        while end < sentences.len() && chunk_byte_size < size {
+            chunk_byte_size += sentences[end].len();
+            end += 1;
+        }
+
+        let prev = start.saturating_sub(overlap);
+        let next = (end + overlap).min(sentences.len());
+
+        chunks.push(sentences[prev..next].concat());
+
+        start = next;
+    }
+
+    chunks
 }
 
 #[inline(always)]
@@ -244,8 +481,10 @@ fn previous_chunk<'a>(
     delim: char,
     skip_forward: &[String],
     skip_back: &[String],
+    requires_whitespace: bool,
 ) -> &'a str {
-    let mut p_cursor = CursorRev::new(input, delim);
+    let mut p_cursor =
+        CursorRev::new(input, delim).with_boundary_requires_whitespace(requires_whitespace);
     for _ in 0..overlap {
         p_cursor.advance();
         while p_cursor.advance_if_peek(skip_forward, skip_back) {
@@ -262,8 +501,10 @@ fn next_chunk<'a>(
     delim: char,
     skip_forward: &[String],
     skip_back: &[String],
+    requires_whitespace: bool,
 ) -> (&'a str, usize) {
-    let mut n_cursor = Cursor::new(input, delim);
+    let mut n_cursor =
+        Cursor::new(input, delim).with_boundary_requires_whitespace(requires_whitespace);
     for _ in 0..overlap {
         n_cursor.advance();
         while n_cursor.advance_if_peek(skip_forward, skip_back) {
@@ -365,6 +606,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn snapping_skips_german_abbreviations() {
+        let input = "Es gibt viele Soßen, z.B. Ragu und Pesto. Ragu kocht lange. Man isst es heiß.";
+
+        let chunker = Snapping::default_with_size(1, 1)
+            .unwrap()
+            .with_language(SnappingLanguage::German);
+
+        let expected = [
+            "Es gibt viele Soßen, z.B. Ragu und Pesto. Ragu kocht lange.",
+            " Ragu kocht lange. Man isst es heiß.",
+        ];
+
+        let chunks = chunker.chunk(input).unwrap();
+        assert_eq!(2, chunks.len());
+
+        for (chunk, test) in chunks.into_iter().zip(expected.into_iter()) {
+            assert_eq!(test, chunk);
+        }
+
+        // Without the language the abbreviation ends a sentence
+        let chunks = Snapping::default_with_size(1, 1)
+            .unwrap()
+            .chunk(input)
+            .unwrap();
+        assert!(chunks[1].starts_with(" Ragu und Pesto."));
+    }
+
+    #[test]
+    fn snapping_skips_french_abbreviations() {
+        let input = "Le ragù est une sauce, c.-à-d. un accompagnement. Il cuit longtemps. On le sert chaud.";
+
+        let chunker = Snapping::default_with_size(1, 1)
+            .unwrap()
+            .with_language(SnappingLanguage::French);
+
+        let expected = [
+            "Le ragù est une sauce, c.-à-d. un accompagnement. Il cuit longtemps.",
+            " Il cuit longtemps. On le sert chaud.",
+        ];
+
+        let chunks = chunker.chunk(input).unwrap();
+        assert_eq!(2, chunks.len());
+
+        for (chunk, test) in chunks.into_iter().zip(expected.into_iter()) {
+            assert_eq!(test, chunk);
+        }
+
+        // Neither are abbreviations with multiple delimiters split when whitespace is not required
+        let chunks = Snapping::default_with_size(1, 1)
+            .unwrap()
+            .with_language(SnappingLanguage::French)
+            .with_boundary_requires_whitespace(false)
+            .chunk(input)
+            .unwrap();
+        assert_eq!(2, chunks.len());
+        assert_eq!(expected[0], chunks[0]);
+    }
+
     #[test]
     fn snapping_table_of_contents() {
         let input =
@@ -401,4 +701,144 @@ mod tests {
         let chunks = chunker.chunk(input).unwrap();
         assert_eq!(vec![input.to_string()], chunks);
     }
+
+    #[test]
+    fn snapping_splits_on_newlines() {
+        let input = "A sentence.\nNext sentence.";
+        let chunker = Snapping::default_with_size(1, 0).unwrap();
+
+        let chunks = chunker.chunk(input).unwrap();
+
+        assert_eq!(vec!["A sentence.", "\nNext sentence."], chunks);
+    }
+
+    #[test]
+    fn snapping_overlap_skips_boundaries_without_whitespace() {
+        let input = "A.\nVersion 1.2 ships.\nIt is fast.\nIt is small.\nThe end.";
+        let chunker = Snapping::default_with_size(1, 1).unwrap();
+
+        let chunks = chunker.chunk(input).unwrap();
+
+        assert_eq!(
+            vec![
+                "A.\nVersion 1.2 ships.",
+                "\nVersion 1.2 ships.\nIt is fast.\nIt is small.",
+                "\nIt is small.\nThe end."
+            ],
+            chunks
+        );
+    }
+
+    #[test]
+    fn snapping_boundary_without_whitespace() {
+        let input = "A sentence.Next sentence.";
+
+        let chunker = Snapping::default_with_size(1, 0).unwrap();
+        let chunks = chunker.chunk(input).unwrap();
+        assert_eq!(vec![input], chunks);
+
+        let chunker = chunker.with_boundary_requires_whitespace(false);
+        let chunks = chunker.chunk(input).unwrap();
+        assert_eq!(vec!["A sentence.", "Next sentence."], chunks);
+    }
+
+    #[cfg(feature = "segmenter")]
+    fn chunk_both(input: &str) -> (Vec<String>, Vec<String>) {
+        let heuristic = Snapping::default_with_size(1, 0).unwrap();
+        let segmenter = heuristic.clone().with_segmenter(SentenceSegmenter::Unicode);
+        (
+            heuristic.chunk(input).unwrap(),
+            segmenter.chunk(input).unwrap(),
+        )
+    }
+
+    #[cfg(feature = "segmenter")]
+    #[test]
+    fn segmenter_keeps_decimals() {
+        let input = "Pi is roughly 3.14 and e is 2.71. Both are irrational.";
+        let expected = vec!["Pi is roughly 3.14 and e is 2.71.", " Both are irrational."];
+
+        let (heuristic, segmenter) = chunk_both(input);
+
+        assert_eq!(expected, heuristic);
+        assert_eq!(expected, segmenter);
+    }
+
+    #[cfg(feature = "segmenter")]
+    #[test]
+    fn segmenter_keeps_abbreviated_titles() {
+        let input = "Dr. Smith cooks ragu. Mr. Raguru eats it.";
+
+        let (heuristic, segmenter) = chunk_both(input);
+
+        assert_eq!(
+            vec!["Dr.", " Smith cooks ragu.", " Mr.", " Raguru eats it."],
+            heuristic
+        );
+        assert_eq!(
+            vec!["Dr. Smith cooks ragu.", " Mr. Raguru eats it."],
+            segmenter
+        );
+    }
+
+    #[cfg(feature = "segmenter")]
+    #[test]
+    fn segmenter_splits_after_quotes() {
+        let input = "He said \"Stop.\" Then he left.";
+
+        let (heuristic, segmenter) = chunk_both(input);
+
+        assert_eq!(vec![input], heuristic);
+        assert_eq!(vec!["He said \"Stop.\"", " Then he left."], segmenter);
+    }
+
+    #[cfg(feature = "segmenter")]
+    #[test]
+    fn segmenter_splits_on_all_terminators() {
+        let input = "Is it ragu? It is! Raguru made it.";
+
+        let (heuristic, segmenter) = chunk_both(input);
+
+        assert_eq!(vec![input], heuristic);
+        assert_eq!(
+            vec!["Is it ragu?", " It is!", " Raguru made it."],
+            segmenter
+        );
+    }
+
+    #[cfg(feature = "segmenter")]
+    #[test]
+    fn segmenter_overlaps_like_heuristic() {
+        let input =
+            "I have a sentence. It is not very long. Here is another. Long schlong ding dong.";
+
+        let heuristic = Snapping::default_with_size(1, 1).unwrap();
+        let segmenter = heuristic.clone().with_segmenter(SentenceSegmenter::Unicode);
+
+        assert_eq!(
+            heuristic.chunk(input).unwrap(),
+            segmenter.chunk(input).unwrap()
+        );
+    }
+
+    #[cfg(feature = "segmenter")]
+    #[test]
+    fn segmenter_moves_whitespace_to_next_sentence() {
+        let input = "A sentence.\nNext sentence.\n";
+
+        let (heuristic, segmenter) = chunk_both(input);
+
+        assert_eq!(vec!["A sentence.", "\nNext sentence.\n"], heuristic);
+        assert_eq!(heuristic, segmenter);
+    }
+
+    #[cfg(not(feature = "segmenter"))]
+    #[test]
+    fn segmenter_requires_feature() {
+        let chunker = Snapping::default().with_segmenter(SentenceSegmenter::Unicode);
+        assert!(matches!(
+            chunker.chunk("A sentence."),
+            Err(ChunkerError::Config(_))
+        ));
+    }
 }
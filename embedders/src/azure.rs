@@ -2,49 +2,71 @@ use super::EmbeddingModel;
 use crate::{
     openai_common::{
         handle_request_error, EmbeddingRequest, EmbeddingResponse, OpenAIEmbeddingResponse,
-        TEXT_EMBEDDING_ADA_002, TEXT_EMBEDDING_ADA_002_SIZE,
+        EMBEDDING_MODELS,
     },
     EmbeddingError,
 };
-use std::error::Error;
+use std::{collections::HashMap, error::Error};
 
 pub struct AzureEmbeddings {
     endpoint: String,
     key: String,
     api_version: String,
+
+    /// Maps OpenAI model names to the names of the Azure deployments serving them.
+    deployments: HashMap<String, String>,
+
     client: reqwest::Client,
 }
 
 impl AzureEmbeddings {
-    pub fn new(endpoint: String, api_key: String, api_version: String) -> Self {
+    pub fn new(
+        endpoint: String,
+        api_key: String,
+        api_version: String,
+        deployments: HashMap<String, String>,
+    ) -> Self {
         Self {
             endpoint,
             key: api_key,
             api_version,
+            deployments,
             client: reqwest::Client::new(),
         }
     }
 
+    /// Lists the models that have a deployment configured. Models whose size is unknown are
+    /// omitted.
     pub fn list_models(&self) -> Vec<EmbeddingModel> {
-        vec![EmbeddingModel {
-            name: TEXT_EMBEDDING_ADA_002.to_string(),
-            size: TEXT_EMBEDDING_ADA_002_SIZE,
-            provider: "azure".to_string(),
-            multimodal: false,
-            max_input_tokens: 8191,
-        }]
+        let mut models: Vec<EmbeddingModel> = self
+            .deployments
+            .keys()
+            .filter_map(|model| {
+                let (name, size) = EMBEDDING_MODELS
+                    .iter()
+                    .find(|(name, _)| *name == model.as_str())?;
+                Some(EmbeddingModel {
+                    name: name.to_string(),
+                    size: *size,
+                    provider: "azure".to_string(),
+                    multimodal: false,
+                    max_input_tokens: 8191,
+                })
+            })
+            .collect();
+
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+
+        models
     }
 
     pub async fn embed(
         &self,
         input: &[&str],
-        deployment: &str,
+        model: &str,
     ) -> Result<EmbeddingResponse, EmbeddingError> {
         let request = EmbeddingRequest { input };
-        let url = format!(
-            "{}/openai/deployments/{deployment}/embeddings",
-            self.endpoint
-        );
+        let url = self.deployment_url(model)?;
 
         let response = match self
             .client
@@ -89,4 +111,62 @@ impl AzureEmbeddings {
             total_tokens: response.usage.total_tokens,
         })
     }
+
+    /// Get the embeddings URL of the deployment serving the `model`.
+    fn deployment_url(&self, model: &str) -> Result<String, EmbeddingError> {
+        let Some(deployment) = self.deployments.get(model) else {
+            return Err(EmbeddingError::InvalidModel(format!(
+                "no Azure deployment configured for '{model}'"
+            )));
+        };
+
+        Ok(format!(
+            "{}/openai/deployments/{deployment}/embeddings",
+            self.endpoint
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AzureEmbeddings;
+    use crate::{openai_common::TEXT_EMBEDDING_3_SMALL, EmbeddingError};
+    use std::collections::HashMap;
+
+    fn embedder() -> AzureEmbeddings {
+        AzureEmbeddings::new(
+            "https://ragu.openai.azure.com".to_string(),
+            "key".to_string(),
+            "2024-02-01".to_string(),
+            HashMap::from([(
+                TEXT_EMBEDDING_3_SMALL.to_string(),
+                "ragu-small-prod".to_string(),
+            )]),
+        )
+    }
+
+    #[test]
+    fn url_targets_mapped_deployment() {
+        let url = embedder().deployment_url(TEXT_EMBEDDING_3_SMALL).unwrap();
+
+        assert_eq!(
+            "https://ragu.openai.azure.com/openai/deployments/ragu-small-prod/embeddings",
+            url
+        );
+        assert!(!url.contains(TEXT_EMBEDDING_3_SMALL));
+    }
+
+    #[test]
+    fn unknown_model_errors() {
+        let result = embedder().deployment_url("text-embedding-ada-002");
+        assert!(matches!(result, Err(EmbeddingError::InvalidModel(_))));
+    }
+
+    #[test]
+    fn lists_only_mapped_models() {
+        let models = embedder().list_models();
+        assert_eq!(1, models.len());
+        assert_eq!(TEXT_EMBEDDING_3_SMALL, models[0].name);
+        assert_eq!(1536, models[0].size);
+    }
 }
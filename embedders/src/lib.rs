@@ -14,7 +14,7 @@ pub mod azure;
 #[cfg(feature = "vllm")]
 pub mod vllm;
 
-#[cfg(any(feature = "azure", feature = "openai"))]
+#[cfg(any(feature = "azure", feature = "openai", feature = "vllm"))]
 mod openai_common {
     use crate::EmbeddingError;
     use reqwest::Response;
@@ -178,3 +178,35 @@ pub enum EmbeddingError {
     #[error("{0}")]
     Response(String),
 }
+
+impl EmbeddingError {
+    /// Whether the error is likely to go away when the request is retried, e.g. timeouts,
+    /// rate limits and server errors of the downstream service.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            #[cfg(any(
+                feature = "openai",
+                feature = "fe-remote",
+                feature = "azure",
+                feature = "vllm"
+            ))]
+            EmbeddingError::Reqwest(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().is_some_and(|status| {
+                        status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                    })
+            }
+
+            #[cfg(any(feature = "openai", feature = "azure", feature = "vllm"))]
+            EmbeddingError::OpenAI(e) => {
+                matches!(
+                    e.error.r#type.as_str(),
+                    "server_error" | "rate_limit_error" | "requests" | "tokens"
+                ) || e.error.code.is_some_and(|code| code == 429 || code >= 500)
+            }
+
+            _ => false,
+        }
+    }
+}
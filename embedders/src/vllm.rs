@@ -1,16 +1,156 @@
 use crate::{
     openai_common::{
-        handle_request_error, EmbeddingRequest, EmbeddingResponse, OpenAIEmbeddingResponse,
+        handle_request_error, EmbeddingRequest, EmbeddingResponse, OpenAIEmbeddingResponse, Usage,
     },
     EmbeddingError, EmbeddingModel,
 };
 use reqwest::header::HeaderMap;
-use serde::Serialize;
-use std::error::Error;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error};
+
+/// Maximum input tokens of models pooled by the client, whose limits are not known.
+const POOLED_MODEL_MAX_INPUT_TOKENS: usize = 8192;
 
 pub struct VllmEmbeddings {
     endpoint: String,
     client: reqwest::Client,
+
+    /// Maps names of models served without a pooler to how their outputs are pooled.
+    pooling: HashMap<String, ModelPooling>,
+}
+
+/// How the token-level outputs of a model are reduced to a single vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pooling {
+    /// Average of all token vectors.
+    Mean,
+
+    /// The vector of the first token.
+    Cls,
+
+    /// The vector of the last token.
+    LastToken,
+}
+
+impl Pooling {
+    /// Reduce the token vectors to a single vector. Errors if there are no tokens or their
+    /// vectors are not of the same size.
+    pub fn pool(&self, tokens: &[Vec<f64>]) -> Result<Vec<f64>, EmbeddingError> {
+        let Some(size) = tokens.first().map(Vec::len) else {
+            return Err(EmbeddingError::Response(
+                "cannot pool empty token outputs".to_string(),
+            ));
+        };
+
+        if let Some(token) = tokens.iter().find(|token| token.len() != size) {
+            return Err(EmbeddingError::Response(format!(
+                "token outputs of different sizes ({size} and {})",
+                token.len()
+            )));
+        }
+
+        match self {
+            Pooling::Cls => Ok(tokens[0].clone()),
+            Pooling::LastToken => Ok(tokens[tokens.len() - 1].clone()),
+            Pooling::Mean => {
+                let mut pooled = vec![0.; size];
+                for token in tokens {
+                    for (sum, value) in pooled.iter_mut().zip(token) {
+                        *sum += value;
+                    }
+                }
+                let count = tokens.len() as f64;
+                pooled.iter_mut().for_each(|value| *value /= count);
+                Ok(pooled)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Pooling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pooling::Mean => write!(f, "mean"),
+            Pooling::Cls => write!(f, "cls"),
+            Pooling::LastToken => write!(f, "last-token"),
+        }
+    }
+}
+
+impl std::str::FromStr for Pooling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mean" => Ok(Self::Mean),
+            "cls" => Ok(Self::Cls),
+            "last-token" => Ok(Self::LastToken),
+            _ => Err(format!(
+                "unsupported pooling '{s}', expected mean, cls or last-token"
+            )),
+        }
+    }
+}
+
+/// Client-side pooling of a model whose deployment returns token-level outputs, e.g. the last
+/// hidden states of a base model.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelPooling {
+    pub pooling: Pooling,
+
+    /// If `true`, the pooled vectors are L2 normalized.
+    pub normalize: bool,
+
+    /// The size of the model's vectors. Pooled vectors of any other size are rejected.
+    pub size: usize,
+}
+
+impl ModelPooling {
+    /// Pool the output of the model, unless the server already pooled it, normalize it if
+    /// configured and check its size.
+    fn apply(&self, model: &str, output: RawEmbedding) -> Result<Vec<f64>, EmbeddingError> {
+        let mut vector = match output {
+            RawEmbedding::Pooled(vector) => vector,
+            RawEmbedding::Tokens(tokens) => self.pooling.pool(&tokens)?,
+        };
+
+        if vector.len() != self.size {
+            return Err(EmbeddingError::Response(format!(
+                "model '{model}' produced a vector of size {}, expected {}",
+                vector.len(),
+                self.size
+            )));
+        }
+
+        if self.normalize {
+            let norm = vector.iter().map(|value| value * value).sum::<f64>().sqrt();
+            if norm > 0. {
+                vector.iter_mut().for_each(|value| *value /= norm);
+            }
+        }
+
+        Ok(vector)
+    }
+}
+
+/// Embedding response which, unlike the OpenAI one, may contain token-level outputs.
+#[derive(Debug, Deserialize)]
+struct VllmEmbeddingResponse {
+    data: Vec<VllmEmbeddingObject>,
+    model: String,
+    usage: Usage,
+}
+
+#[derive(Debug, Deserialize)]
+struct VllmEmbeddingObject {
+    embedding: RawEmbedding,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawEmbedding {
+    Pooled(Vec<f64>),
+    Tokens(Vec<Vec<f64>>),
 }
 
 impl VllmEmbeddings {
@@ -26,9 +166,34 @@ impl VllmEmbeddings {
         Self {
             endpoint,
             client: client.build().expect("unable to build http client"),
+            pooling: HashMap::new(),
         }
     }
 
+    /// Set the models whose outputs are pooled by the client. The models are listed as text
+    /// models of the configured size.
+    pub fn with_pooling(mut self, pooling: HashMap<String, ModelPooling>) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
+    /// Get the vectors from the response, pooling them if configured for the model.
+    fn vectors(
+        &self,
+        model: &str,
+        data: Vec<VllmEmbeddingObject>,
+    ) -> Result<Vec<Vec<f64>>, EmbeddingError> {
+        data.into_iter()
+            .map(|object| match (self.pooling.get(model), object.embedding) {
+                (Some(pooling), output) => pooling.apply(model, output),
+                (None, RawEmbedding::Pooled(vector)) => Ok(vector),
+                (None, RawEmbedding::Tokens(_)) => Err(EmbeddingError::Response(format!(
+                    "model '{model}' returned token-level outputs and has no pooling configured"
+                ))),
+            })
+            .collect()
+    }
+
     pub async fn embed(
         &self,
         input: &[&str],
@@ -49,7 +214,7 @@ impl VllmEmbeddings {
             return Err(handle_request_error(response).await);
         }
 
-        let response = match response.json::<OpenAIEmbeddingResponse>().await {
+        let response = match response.json::<VllmEmbeddingResponse>().await {
             Ok(res) => res,
             Err(e) => {
                 tracing::error!("Error decoding OpenAI response: {}", e);
@@ -67,7 +232,7 @@ impl VllmEmbeddings {
         );
 
         Ok(EmbeddingResponse {
-            embeddings: response.data.into_iter().map(|o| o.embedding).collect(),
+            embeddings: self.vectors(model, response.data)?,
             prompt_tokens: response.usage.prompt_tokens,
             total_tokens: response.usage.total_tokens,
         })
@@ -143,7 +308,21 @@ impl VllmEmbeddings {
     }
 
     pub fn list_models(&self) -> Vec<EmbeddingModel> {
-        vec![
+        let mut pooled: Vec<EmbeddingModel> = self
+            .pooling
+            .iter()
+            .map(|(name, pooling)| EmbeddingModel {
+                name: name.clone(),
+                size: pooling.size,
+                provider: "vllm".to_string(),
+                multimodal: false,
+                max_input_tokens: POOLED_MODEL_MAX_INPUT_TOKENS,
+            })
+            .collect();
+
+        pooled.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let models = vec![
             EmbeddingModel {
                 name: "qwen2-dse".to_string(),
                 size: 1536,
@@ -158,7 +337,13 @@ impl VllmEmbeddings {
                 multimodal: true,
                 max_input_tokens: 4096,
             },
-        ]
+        ];
+
+        models
+            .into_iter()
+            .filter(|model| !self.pooling.contains_key(&model.name))
+            .chain(pooled)
+            .collect()
     }
 }
 
@@ -203,3 +388,52 @@ struct ImageInput<'a> {
 struct ImageInputInner<'a> {
     url: &'a str,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ModelPooling, Pooling, VllmEmbeddingResponse, VllmEmbeddings};
+    use std::collections::HashMap;
+
+    const TOKEN_RESPONSE: &str = r#"{
+        "model": "raguru",
+        "data": [{ "embedding": [[1.0, 2.0, 0.0], [3.0, 4.0, 0.0], [2.0, 0.0, 6.0]] }],
+        "usage": { "prompt_tokens": 3, "total_tokens": 3 }
+    }"#;
+
+    fn embedder(pooling: Pooling, normalize: bool, size: usize) -> VllmEmbeddings {
+        VllmEmbeddings::new("http://localhost".to_string(), None).with_pooling(HashMap::from([(
+            "raguru".to_string(),
+            ModelPooling {
+                pooling,
+                normalize,
+                size,
+            },
+        )]))
+    }
+
+    fn vectors(embedder: &VllmEmbeddings) -> Result<Vec<Vec<f64>>, crate::EmbeddingError> {
+        let response: VllmEmbeddingResponse = serde_json::from_str(TOKEN_RESPONSE).unwrap();
+        embedder.vectors("raguru", response.data)
+    }
+
+    #[test]
+    fn mean_pools_token_outputs() {
+        let vectors = vectors(&embedder(Pooling::Mean, false, 3)).unwrap();
+        assert_eq!(vec![vec![2.0, 2.0, 2.0]], vectors);
+    }
+
+    #[test]
+    fn pools_normalizes_and_validates_size() {
+        let cls = vectors(&embedder(Pooling::Cls, false, 3)).unwrap();
+        assert_eq!(vec![vec![1.0, 2.0, 0.0]], cls);
+
+        let last = vectors(&embedder(Pooling::LastToken, true, 3)).unwrap();
+        let norm = 40f64.sqrt();
+        assert_eq!(vec![vec![2.0 / norm, 0.0, 6.0 / norm]], last);
+
+        assert!(vectors(&embedder(Pooling::Mean, false, 4)).is_err());
+
+        let unpooled = VllmEmbeddings::new("http://localhost".to_string(), None);
+        assert!(vectors(&unpooled).is_err());
+    }
+}